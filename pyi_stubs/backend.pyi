@@ -0,0 +1,18 @@
+def set_backend(name: str, chip: str = "/dev/gpiochip0") -> None:
+    """
+    Selects how GPIOManager and PWMManager drive the pins. The backend can only be changed while no pin or PWM channel
+    is set up.
+
+    :param name: "rppal" for the registers of the Raspberry Pi (the default), "gpiochip" for the GPIO character device
+        of any board or "mock" for simulated pins driven with gpio_manager.mock.
+    :param chip: The character device used by the gpiochip backend.
+    :raises RuntimeError: If pins or PWM channels are set up.
+    """
+    ...
+
+
+def current_backend() -> str:
+    """
+    Returns the backend the pins are driven through, "rppal", "gpiochip" or "mock".
+    """
+    ...
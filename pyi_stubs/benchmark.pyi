@@ -0,0 +1,17 @@
+
+
+def benchmark_latency(out_pin: int, in_pin: int, samples: int = 1000) -> Dict[str, Dict[str, Union[float, List[float]]]]:
+    """
+    Measures the interrupt latency of the library with an output pin wired to an input pin. The output is toggled and
+    the time until each edge is returned by a blocking wait, as wait_for_edge does, and until it reaches an interrupt
+    callback is measured in Rust. Both pins must be free.
+
+    :param out_pin: The output pin, wired to the input pin.
+    :param in_pin: The input pin.
+    :param samples: The number of edges measured for each method.
+    :return: The statistics of each method as wait_for_edge and callback, each with min_us, mean_us, median_us, p99_us,
+        max_us, stdev_us and the sorted latencies as samples_us.
+    :raises ValueError: If samples is 0, the pins are the same or one of them is set up.
+    :raises RuntimeError: If an edge doesn't reach the input within a second, the pins aren't wired together.
+    """
+    ...
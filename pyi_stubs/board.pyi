@@ -0,0 +1,50 @@
+def board_info() -> Dict[str, Optional[Union[str, int]]]:
+    """
+    Returns information about the board the library runs on.
+
+    :return: The model as model, the SoC as soc, the revision code as revision, the RAM size in megabytes as ram_mb and
+        the number of pins of the GPIO header as header_pins. The revision and RAM size are None when they can't be
+        read, the header pins are None for compute modules.
+    """
+    ...
+
+
+def pinout() -> List[Dict[str, Union[Optional[int], Optional[str], Dict[str, str]]]]:
+    """
+    Returns the pins of the GPIO header of the detected board, in physical order. Compute modules are described with
+    the 40 pin header of their IO boards.
+
+    :return: For every pin its physical number as physical, its BCM GPIO number as bcm (None for power and ground
+        pins), its name as name, the pull applied at power on as default_pull ("up", "down" or None) and its alternate
+        functions by pinctrl name as alt_functions.
+    """
+    ...
+
+
+def cpu_temperature() -> float:
+    """
+    Returns the temperature of the SoC in degrees Celsius, read from its thermal zone, the value vcgencmd measure_temp
+    reports.
+
+    :return: The temperature in degrees Celsius.
+    """
+    ...
+
+
+def active_overlays() -> List[str]:
+    """
+    Returns the device tree overlays in use, the ones applied at boot, loaded at runtime or named in config.txt.
+
+    :return: The names of the overlays, without their parameters.
+    """
+    ...
+
+
+def interface_status() -> Dict[str, Dict[str, Union[bool, List[str], Optional[str]]]]:
+    """
+    Returns whether the I2C, SPI, UART and PWM interfaces are enabled, found by the devices the kernel created for them.
+
+    :return: For every interface by name (i2c, spi, uart and pwm) a dict with enabled, its devices as a list of paths
+        and a hint on how to enable it, None when it is enabled.
+    """
+    ...
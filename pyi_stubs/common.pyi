@@ -9,11 +9,90 @@ Classes:
 
 - GPIOManager: Manages GPIO pins, including input and output configurations, and supports callback assignments.
 
+- GPIOChipManager: Drives the lines of any GPIO chip through the GPIO character device, for boards other than the Pi.
+
+- ScheduledAction: Handle of a pin change scheduled with the GPIOManager.
+
+- GPIOPin: Handle on a configured pin, returned by indexing the GPIOManager with a pin number or alias.
+
 - PWMManager: Controls Pulse Width Modulation (PWM) functionality for GPIO pins.
 
+- PWM: Drives PWM on a pin, using hardware PWM when the pin has a channel and software PWM otherwise.
+
+- ComplementaryPWM: Drives two pins as a complementary PWM pair with dead time for half-bridge drivers.
+
+- FanController: Drives a fan from a PWM pin following a temperature curve with hysteresis and a minimum duty cycle.
+
+- ServoGroup: Moves several servos to target angles over the same duration with synchronized updates.
+
+- PIDController: Holds a tachometer speed or measured value at a setpoint by driving a PWM output from a Rust loop.
+
+- TouchButton: Turns a pin wired to a bare electrode into a touch input with callbacks, see GPIOManager.touch_read.
+
+- WaveformManager: Composes multi-pin waveforms in the style of pigpio and plays them by DMA.
+
 - I2CManager: Provides I2C communication functions for interacting with I2C devices.
 
-- Enums: Defines enums such as PinState, LogicLevel, InternPullResistorState, and TriggerEdge for easy configuration
+- I2CWrite, I2CRead: Segments of a combined I2C transaction.
+
+- I2CDevice: Communicates with a single I2C device without passing its address to every call.
+
+- SoftI2C: Bit-banged I2C master on any two GPIO pins, with clock stretching.
+
+- SoftSPI: Bit-banged SPI master on any GPIO pins, in SPI modes 0 to 3.
+
+- SPIDevice: Communicates with a single device of a SoftSPI bus through its own chip select.
+
+- SoftUART: Bit-banged 8N1 serial port on any GPIO pins, receiving in the background.
+
+- ModbusRTU: Modbus RTU master reading and writing the registers of slaves over a SoftUART.
+
+- Register, RegisterMap: Reads and writes the registers of an I2CDevice by name.
+
+- SMBus: Drop-in replacement for the SMBus class of smbus2, built on the I2CManager.
+
+- i2c_msg: Message of a combined transaction performed by SMBus.i2c_rdwr.
+
+- EEPROM: Reads and writes AT24Cxx I2C EEPROMs with page boundary aware writes.
+
+- RTC: Reads and sets the time and alarms of DS1307 and DS3231 real time clocks.
+
+- compat.RPiGPIO: Drop-in replacement for the RPi.GPIO module, built on the GPIOManager.
+
+- gpiozero.GPIOManagerFactory: gpiozero pin factory built on the GPIOManager.
+
+- cli.main: The command line interface installed as the gpio_manager command.
+
+- board_info, pinout, cpu_temperature: Describe the board the library runs on, the pins of its GPIO header and the
+  temperature of its SoC.
+
+- active_overlays, interface_status: Report the device tree overlays in use and whether I2C, SPI, UART and PWM are enabled.
+
+- diagnose: Reports the setup issues of the environment, such as missing permissions, disabled interfaces and claimed pins.
+
+- benchmark_latency: Measures the edge to callback and edge to wait_for_edge latency with an output wired to an input.
+
+- set_log_level: Sets the level of the gpio_manager logger of the logging module, which receives the warnings of the library.
+
+- get_metrics: Returns the counters of pin events, I2C operations and lock contention for monitoring.
+
+- set_backend, current_backend: Select whether the pins are driven through rppal, the GPIO character device or simulated pins.
+
+- read, write, on_edge: Read, drive and watch pins through the shared GPIOManager without setting them up first.
+
+- set_defaults: Sets the debounce time, pull resistor, logic level and reset at exit used when the arguments are omitted.
+
+- mqtt_bridge, MQTTBridge: Bridges the pins to an MQTT broker from a Rust client thread.
+
+- serve_events, EventServer: Streams the pin events to TCP and WebSocket clients and runs their commands.
+
+- RemoteGPIOManager: Drives the pins of a remote Pi running the event server, returned by GPIOManager.connect.
+
+- serve_daemon, GPIODaemon: Owns the GPIO on behalf of other processes connecting to its Unix socket.
+
+- serve_http, HTTPServer: Exposes a JSON API over HTTP for the pins, PWM and I2C.
+
+- Enums: Defines enums such as PinState, LogicLevel, PinDirection, OutputMode, InternPullResistorState, TriggerEdge, TimestampClock, and Easing for easy configuration
 of pin states and edge triggers.
 
 Example usage:
@@ -23,6 +102,8 @@ Example usage:
     gpio.assign_callback(pin_num=4, callback=my_callback)
 """
 
+from typing import Union
+
 
 class InternPullResistorState:
     """Enum representing the GPIO pin state types for input pins."""
@@ -68,6 +149,34 @@ class LogicLevel:
     """
 
 
+class PinDirection:
+    """Enum representing the direction of a pin."""
+    INPUT: 'PinDirection'
+    """
+    The pin reads its line.
+    """
+    OUTPUT: 'PinDirection'
+    """
+    The pin drives its line.
+    """
+
+
+class OutputMode:
+    """Enum representing how an output pin drives its line."""
+    PUSH_PULL: 'OutputMode'
+    """
+    Drives the line both high and low (Default).
+    """
+    OPEN_DRAIN: 'OutputMode'
+    """
+    Pulls the line low and releases it for high, the line needs a pull-up.
+    """
+    OPEN_SOURCE: 'OutputMode'
+    """
+    Pulls the line high and releases it for low, the line needs a pull-down.
+    """
+
+
 class TriggerEdge:
     """Enum representing the trigger edge types. Triggers are based off logic level changes"""
     RISING: 'TriggerEdge'
@@ -82,3 +191,50 @@ class TriggerEdge:
     """
     Trigger on both edges (Default).
     """
+
+
+class TimestampClock:
+    """Enum representing the clock of the trigger times passed to callbacks."""
+    MONOTONIC: 'TimestampClock'
+    """
+    Time since an arbitrary point, never jumps when the system time changes.
+    """
+    REALTIME: 'TimestampClock'
+    """
+    Time since the unix time epoch, follows changes of the system time (Default).
+    """
+    BOOTTIME: 'TimestampClock'
+    """
+    Like MONOTONIC, but keeps counting while the system is suspended.
+    """
+
+
+# The methods taking these enums also accept their names, compared ignoring case, underscores and dashes, and for the
+# enums of a level, 1, 0, True or False.
+InternPullResistorStateLike = Union[InternPullResistorState, str]
+PinStateLike = Union[PinState, str, int, bool]
+LogicLevelLike = Union[LogicLevel, str, int, bool]
+PinDirectionLike = Union[PinDirection, str]
+OutputModeLike = Union[OutputMode, str]
+TriggerEdgeLike = Union[TriggerEdge, str]
+TimestampClockLike = Union[TimestampClock, str]
+
+
+class Easing:
+    """Enum representing the easing curves used to ramp a PWM duty cycle."""
+    LINEAR: 'Easing'
+    """
+    Changes the duty cycle at a constant rate (Default).
+    """
+    EASE_IN: 'Easing'
+    """
+    Starts slowly and speeds up towards the end.
+    """
+    EASE_OUT: 'Easing'
+    """
+    Starts quickly and slows down towards the end.
+    """
+    EASE_IN_OUT: 'Easing'
+    """
+    Starts and ends slowly, fastest in the middle.
+    """
@@ -0,0 +1,80 @@
+class ComplementaryPWM:
+    """
+    ComplementaryPWM drives two pins as a complementary pair for half-bridge drivers, the second pin is the inverse of
+    the first with a dead time inserted at every transition, so both pins are never high at the same time. The signal
+    is generated by a dedicated thread, the timing stays aligned as the duty cycle and frequency change.
+    """
+
+    def __init__(self, pin: int, complementary_pin: int, frequency_hz: Optional[float] = 1000,
+                 duty_cycle: Optional[float] = 0, dead_time_us: Optional[float] = 1) -> None:
+        """
+        Sets up two free pins as a complementary pair, both pins stay low until the pair is started.
+
+        :param pin: The GPIO pin driven with the duty cycle.
+        :param complementary_pin: The GPIO pin driven with the inverse of the duty cycle.
+        :param frequency_hz: The frequency in Hertz.
+        :param duty_cycle: The duty cycle of the first pin (0 to 100).
+        :param dead_time_us: The time both pins are low at every transition in microseconds.
+        """
+        ...
+
+    def start(self) -> None:
+        """Starts driving the pair."""
+        ...
+
+    def stop(self) -> None:
+        """Stops driving the pair, both pins are set low."""
+        ...
+
+    def close(self) -> None:
+        """Stops the pair and releases both pins."""
+        ...
+
+    def set_duty_cycle(self, duty_cycle: float) -> None:
+        """
+        Sets the duty cycle of the first pin, the complementary pin is high for the rest of the period minus the dead
+        times.
+
+        :param duty_cycle: The duty cycle (0 to 100).
+        """
+        ...
+
+    def set_frequency(self, frequency_hz: float) -> None:
+        """
+        Sets the frequency, the period must stay longer than twice the dead time.
+
+        :param frequency_hz: The frequency in Hertz.
+        """
+        ...
+
+    def set_dead_time(self, dead_time_us: float) -> None:
+        """
+        Sets the dead time, which must be less than half the period.
+
+        :param dead_time_us: The time both pins are low at every transition in microseconds.
+        """
+        ...
+
+    def get_duty_cycle(self) -> float:
+        """
+        :return: The duty cycle of the first pin (0 to 100).
+        """
+        ...
+
+    def get_frequency(self) -> float:
+        """
+        :return: The frequency in Hertz.
+        """
+        ...
+
+    def get_dead_time(self) -> float:
+        """
+        :return: The dead time in microseconds.
+        """
+        ...
+
+    def is_active(self) -> bool:
+        """
+        :return: Whether the pair is being driven.
+        """
+        ...
@@ -0,0 +1,39 @@
+class GPIODaemon:
+    """
+    Handle of the daemon started with serve_daemon.
+    """
+
+    path: str
+    """
+    The path of the socket the daemon listens on.
+    """
+
+    def client_count(self) -> int:
+        """
+        Returns the number of connected clients.
+        """
+        ...
+
+    def stop(self) -> None:
+        """
+        Stops the daemon, disconnecting its clients and resetting their pins, and removes its socket. Stopping a daemon
+        that was replaced or already stopped does nothing.
+        """
+        ...
+
+
+def serve_daemon(path: Optional[str] = "/run/gpio_manager.sock", mode: Optional[int] = 0o660) -> GPIODaemon:
+    """
+    Starts a daemon owning the GPIO hardware on behalf of other processes, which connect to its Unix socket with
+    GPIOManager.connect("unix:<path>"). It runs in Rust threads so it keeps working while Python is busy. A daemon
+    already running is replaced.
+
+    Every pin belongs to the client that set it up: the commands of the other clients on the pin are rejected, the
+    edges of input pins are only sent to their owner, and the pins of a client are reset when it disconnects. I2C
+    transfers of the clients are serialized by the daemon. Clients speak the protocol of the event server.
+
+    :param path: The path of the socket. A socket left by a daemon that didn't stop cleanly is replaced.
+    :param mode: The permissions of the socket, the processes allowed to connect.
+    :return: The handle of the daemon.
+    """
+    ...
@@ -0,0 +1,13 @@
+def set_defaults(debounce_ms: Optional[float] = None, pull: Optional[InternPullResistorStateLike] = None,
+                 logic_level: Optional[LogicLevelLike] = None, reset_on_exit: Optional[bool] = None) -> None:
+    """
+    Sets the arguments the GPIOManager falls back to when they are omitted, the arguments left as None keep their
+    current default. The defaults apply to the pins set up after the call.
+
+    :param debounce_ms: The debounce time of assign_callback in milliseconds (initially 2).
+    :param pull: The pull resistor of add_input_pin and set_direction (initially AUTO).
+    :param logic_level: The logic level of add_input_pin, add_output_pin and setup_pwm (initially HIGH).
+    :param reset_on_exit: Whether the pins are reset with cleanup when the interpreter exits (initially False).
+    :raises ValueError: If the debounce time is negative.
+    """
+    ...
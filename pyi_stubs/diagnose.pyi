@@ -0,0 +1,13 @@
+
+
+def diagnose() -> Dict[str, Any]:
+    """
+    Checks the environment the library runs in, to find the setup issues that keep pins from working.
+
+    :return: A report with the board as board (the result of board_info, None when the board can't be detected), the
+        backend in use as backend, the uid and groups of the process as user, whether every GPIO, I2C and SPI device
+        node is readable and writable as devices, whether the embedded pinctrl is available as pinctrl, the PWM chips
+        and exported channels as pwm, the result of interface_status as interfaces, the claims of the header pins held
+        by the kernel or another process as conflicts and the issues found with how to fix them as problems.
+    """
+    ...
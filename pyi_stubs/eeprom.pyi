@@ -0,0 +1,108 @@
+class EEPROMModel:
+    """Enum representing the supported AT24Cxx EEPROM models."""
+    AT24C02: 'EEPROMModel'
+    """
+    256 bytes, 8 byte pages.
+    """
+    AT24C04: 'EEPROMModel'
+    """
+    512 bytes, 16 byte pages.
+    """
+    AT24C08: 'EEPROMModel'
+    """
+    1 KiB, 16 byte pages.
+    """
+    AT24C16: 'EEPROMModel'
+    """
+    2 KiB, 16 byte pages.
+    """
+    AT24C32: 'EEPROMModel'
+    """
+    4 KiB, 32 byte pages.
+    """
+    AT24C64: 'EEPROMModel'
+    """
+    8 KiB, 32 byte pages.
+    """
+    AT24C128: 'EEPROMModel'
+    """
+    16 KiB, 64 byte pages.
+    """
+    AT24C256: 'EEPROMModel'
+    """
+    32 KiB, 64 byte pages.
+    """
+    AT24C512: 'EEPROMModel'
+    """
+    64 KiB, 128 byte pages.
+    """
+
+
+class EEPROM:
+    """EEPROM provides page boundary aware access to AT24Cxx I2C EEPROMs on the bus opened by the I2CManager."""
+
+    def __init__(self, model: EEPROMModel, addr: Optional[int] = 0x50, write_timeout_ms: Optional[float] = 20,
+                 bus: Optional[int] = None) -> None:
+        """
+        Initializes a new EEPROM instance. The I2C bus must be opened with the I2CManager before reading or writing.
+
+        :param model: The EEPROM model (set it by using gpio_manager.EEPROMModel.[AT24C02 ... AT24C512]).
+        :param addr: The I2C address of the EEPROM.
+        :param write_timeout_ms: The maximum time to wait for a write cycle to finish in milliseconds.
+        :param bus: The I2C bus the EEPROM is connected to (default is the first bus opened).
+        """
+        ...
+
+    def read(self, mem_addr: int, length: int) -> bytes:
+        """
+        Reads data from the EEPROM, reads may be of any length and cross page boundaries.
+
+        :param mem_addr: The memory address to start reading from.
+        :param length: The number of bytes to read.
+        :return: The bytes read.
+        """
+        ...
+
+    def write(self, mem_addr: int, data: Union[bytes, bytearray, memoryview, List[int]]) -> None:
+        """
+        Writes data to the EEPROM. The data is split at page boundaries and each page write waits for the write cycle
+        to finish before continuing.
+
+        :param mem_addr: The memory address to start writing to.
+        :param data: The bytes to write.
+        """
+        ...
+
+    def read_byte(self, mem_addr: int) -> int:
+        """
+        Reads a single byte from the EEPROM.
+
+        :param mem_addr: The memory address to read.
+        :return: The byte read.
+        """
+        ...
+
+    def write_byte(self, mem_addr: int, data: int) -> None:
+        """
+        Writes a single byte to the EEPROM.
+
+        :param mem_addr: The memory address to write.
+        :param data: The byte to write.
+        """
+        ...
+
+    def get_size(self) -> int:
+        """
+        Gets the size of the EEPROM.
+
+        :return: The size in bytes.
+        """
+        ...
+
+    def get_page_size(self) -> int:
+        """
+        Gets the page size of the EEPROM.
+
+        :return: The page size in bytes.
+        """
+        ...
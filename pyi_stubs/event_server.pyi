@@ -0,0 +1,43 @@
+class EventServer:
+    """
+    Handle of the event server started with serve_events.
+    """
+
+    port: int
+    """
+    The port the server listens on, useful when it was started on port 0.
+    """
+
+    def client_count(self) -> int:
+        """
+        Returns the number of connected clients.
+        """
+        ...
+
+    def stop(self) -> None:
+        """
+        Stops the server and disconnects its clients. Stopping a server that was replaced or already stopped does
+        nothing.
+        """
+        ...
+
+
+def serve_events(port: int, host: Optional[str] = "127.0.0.1", pins: Optional[List[int]] = None) -> EventServer:
+    """
+    Starts a server streaming the edges of the input pins to its clients as JSON frames and running their commands,
+    from Rust threads so it keeps working while Python is busy. A server already running is replaced.
+
+    Clients connect over TCP, exchanging one JSON object per line, or over WebSocket, exchanging one JSON object per
+    text message. Every edge is sent as {"event": "edge", "pin": 17, "edge": "rising", "state": true, "timestamp": ...}.
+    The commands are {"cmd": "set", "pin": 27, "state": true}, {"cmd": "duty", "pin": 12, "duty_cycle": 50},
+    {"cmd": "get", "pin": 17}, {"cmd": "i2c", "addr": 72, "write": [0], "read": 2}, add_input, add_output, setup_pwm,
+    start_pwm, stop_pwm and reset, each is answered with {"ok": true, "result": ...} or {"ok": false, "error": "..."}
+    carrying the id of the command when it has one. The server has no authentication, only expose it on trusted
+    networks.
+
+    :param port: The port to listen on, 0 picks a free port.
+    :param host: The address to listen on, "0.0.0.0" accepts clients on every interface. Only local clients by default.
+    :param pins: The input pins streamed, every input pin if None. Pins set up later are streamed as well.
+    :return: The handle of the server.
+    """
+    ...
@@ -0,0 +1,75 @@
+class FanController:
+    """
+    FanController drives a fan from a PWM pin following a temperature curve. A Rust thread reads the CPU temperature,
+    or a sensor through a function, every interval and sets the duty cycle interpolated from the curve. The fan slows
+    down only once the temperature dropped by the hysteresis, and a fan that is on never runs below the minimum duty
+    cycle it needs to keep spinning.
+    """
+
+    def __init__(self, pin: int, curve: List[Tuple[float, float]], sensor: Optional[Callable[[], float]] = None,
+                 interval_ms: Optional[float] = 2000, hysteresis_c: Optional[float] = 2,
+                 min_duty_cycle: Optional[float] = 0, spin_up_ms: Optional[float] = 0,
+                 frequency_hz: Optional[float] = 1000,
+                 logic_level: Optional[LogicLevelLike] = LogicLevel.HIGH) -> None:
+        """
+        Sets up the PWM of the fan, through the hardware PWM channel of the pin when it has one. The fan stays off
+        until the controller is started.
+
+        :param pin: The GPIO pin driving the fan.
+        :param curve: The points of the curve as (temperature_c, duty_cycle) with increasing temperatures, the duty
+                      cycle (0 to 100) is interpolated between them and held below the first and above the last.
+        :param sensor: A function returning the temperature in degrees Celsius, None reads the CPU temperature.
+        :param interval_ms: The time between two readings in milliseconds.
+        :param hysteresis_c: How far in degrees Celsius the temperature must drop before the fan slows down.
+        :param min_duty_cycle: The lowest duty cycle the fan keeps spinning at, lower duty cycles other than 0 are
+                               raised to it.
+        :param spin_up_ms: The time the fan runs at full speed when it starts from a stop in milliseconds.
+        :param frequency_hz: The PWM frequency in Hertz, 4-pin PC fans expect 25000.
+        :param logic_level: The logic level of the pin, LOW inverts the signal.
+        """
+        ...
+
+    def start(self) -> None:
+        """Starts the PWM signal and the control thread, the first reading is taken right away."""
+        ...
+
+    def stop(self) -> None:
+        """Stops the control thread and the PWM signal, the fan pin is driven to its inactive level."""
+        ...
+
+    def close(self) -> None:
+        """Stops the controller and releases the pin."""
+        ...
+
+    def set_curve(self, curve: List[Tuple[float, float]]) -> None:
+        """
+        Replaces the curve, used from the next reading.
+
+        :param curve: The points of the curve as (temperature_c, duty_cycle) with increasing temperatures.
+        """
+        ...
+
+    def curve_duty_cycle(self, temperature_c: float) -> float:
+        """
+        :param temperature_c: The temperature in degrees Celsius.
+        :return: The duty cycle of the curve at the temperature, without the hysteresis and minimum duty cycle.
+        """
+        ...
+
+    def get_temperature(self) -> Optional[float]:
+        """
+        :return: The last temperature read in degrees Celsius, or None before the first reading or when it failed.
+        """
+        ...
+
+    def get_duty_cycle(self) -> float:
+        """
+        :return: The duty cycle the fan runs at (0 to 100).
+        """
+        ...
+
+    def is_active(self) -> bool:
+        """
+        :return: Whether the control thread is running.
+        """
+        ...
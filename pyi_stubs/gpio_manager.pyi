@@ -1,4 +1,66 @@
-from typing import Optional, Tuple, Callable
+import datetime
+import os
+from typing import Any, Optional, Tuple, Callable, List, Union, Dict, Iterator
+
+
+class ScheduledAction:
+    """Handle of a pin change scheduled with GPIOManager.schedule or GPIOManager.schedule_in."""
+
+    def cancel(self) -> bool:
+        """
+        Cancels the pin change.
+        :return: Whether the change was still pending.
+        """
+        ...
+
+    def is_pending(self) -> bool:
+        """
+        :return: Whether the pin change is still waiting for its time.
+        """
+        ...
+
+    def get_pin(self) -> int:
+        """
+        :return: The GPIO pin of the change.
+        """
+        ...
+
+    def get_state(self) -> PinState:
+        """
+        :return: The state the pin is set to.
+        """
+        ...
+
+
+class GPIOPin:
+    """
+    Handle on a configured pin, returned by indexing the GPIOManager with a pin number or alias. It reads and writes
+    the pin through the manager, so it follows the pin when it is set up again.
+    """
+
+    @property
+    def pin(self) -> int:
+        """The GPIO pin."""
+        ...
+
+    @property
+    def mode(self) -> Optional[str]:
+        """What the pin is set up as, "input", "output" or "pwm", or None once it was reset."""
+        ...
+
+    def read(self) -> PinState:
+        """
+        Reads the state of the pin, the level of an input or the state an output is driven to.
+        :return: HIGH when the pin is active for its logic level.
+        """
+        ...
+
+    def write(self, state: PinStateLike) -> None:
+        """
+        Drives an output pin.
+        :param state: The state to drive the pin to.
+        """
+        ...
 
 
 class GPIOManager:
@@ -8,48 +70,227 @@ class GPIOManager:
         """Initializes a new GPIOManager instance."""
         ...
 
+    @staticmethod
+    def connect(address: str, timeout_ms: Optional[float] = 5000) -> 'RemoteGPIOManager':
+        """
+        Connects to a remote Pi running the event server (see serve_events), or to the daemon owning the GPIO of this
+        Pi (see serve_daemon), returning a manager driving its pins with the API of the GPIOManager.
+
+        :param address: The address of the server as "host:port", or the socket of the daemon as "unix:<path>".
+        :param timeout_ms: The longest time to wait for the connection and for the reply to every command.
+        :return: The manager of the remote Pi.
+        """
+        ...
+
     def add_input_pin(self, pin_num: int,
-                      pull_resistor_state: Optional[InternPullResistorState] = InternPullResistorState.AUTO,
-                      logic_level: Optional[LogicLevel] = LogicLevel.HIGH) -> None:
+                      pull_resistor_state: Optional[InternPullResistorStateLike] = None,
+                      logic_level: Optional[LogicLevelLike] = None, force: bool = False) -> None:
         """
         Sets up an input pin but does not assign a callback yet.
 
         :param pin_num: The GPIO pin to configure as input.
-        :param pull_resistor_state: The pin state (set it by using gpio_manager.InternPullResistorState.[PULLUP, PULLDOWN, EXTERNAL, or AUTO]).
-        :param logic_level: The logic level of the pin (set it by using gpio_manager.LogicLevel.[HIGH or LOW]).
+        :param pull_resistor_state: The pin state (set it by using gpio_manager.InternPullResistorState.[PULLUP, PULLDOWN, EXTERNAL, or AUTO]), None uses the default of set_defaults.
+        :param logic_level: The logic level of the pin (set it by using gpio_manager.LogicLevel.[HIGH or LOW]), None uses the default of set_defaults.
+        :param force: Takes the pin even when the kernel or another process claims it, such as the pins of an enabled I2C, SPI or UART bus.
+        """
+        ...
+
+    def set_pull(self, pin_num: int, pull_resistor_state: InternPullResistorStateLike) -> None:
+        """
+        Changes the pull resistor of a configured input pin, its callbacks and interrupt stay set up.
+
+        :param pin_num: The GPIO pin.
+        :param pull_resistor_state: The new pull resistor, AUTO pulls the pin to its inactive level and EXTERNAL disables the internal pull resistor.
         """
         ...
 
-    def assign_callback(self, pin_num: int, callback: Callable[..., None], trigger_edge: Optional[TriggerEdge] =
-    TriggerEdge.BOTH, debounce_time_ms: Optional[float] = 2, args: Optional[Tuple] = None, include_trigger_time:
-    Optional[bool] = False, include_trigger_edge: Optional[bool] = False) -> None:
+    def assign_callback(self, pin_num: int, callback: Callable[..., None], trigger_edge: Optional[TriggerEdgeLike] =
+    TriggerEdge.BOTH, debounce_time_ms: Optional[float] = None, args: Optional[Tuple] = None, include_trigger_time:
+    Optional[bool] = False, include_trigger_edge: Optional[bool] = False, filter: str = "hardware",
+    kwargs: Optional[Dict[str, Any]] = None, include_pin_state: Optional[bool] = False, priority: int = 0,
+    weak: bool = False) -> None:
         """
-        Assigns a callback to an input pin. If enabled, TriggerTime is a float representing the time the trigger occurred since unix time epoch. TriggerEdge is an enum representing the edge that triggered the
+        Assigns a callback to an input pin. If enabled, TriggerTime is a float representing the time the trigger occurred since unix time epoch, the clock and resolution can be changed with set_timestamp_clock. TriggerEdge is an enum representing the edge that triggered the
         callback (gpio_manager.TriggerEdge.[RISING, FALLING]). You can assign more than one callback to each pin by calling this function multiple times with different callbacks.
 
         :param pin_num: The GPIO pin.
         :param callback: The callback function to be invoked on pin change.
         :param trigger_edge: The edge trigger type (set using gpio_manager.TriggerEdge.[RISING, FALLING, BOTH]).
         :param args: The arguments to pass to the callback function.
-        :param debounce_time_ms: The debounce time in milliseconds.
+        :param kwargs: The keyword arguments to pass to the callback function.
+        :param include_pin_state: Whether to include the state of the pin read when the callback is called, following the logic level of the pin. (Will be passed after the trigger time and edge when they are included)
+        :param priority: The callbacks of a pin are called from the highest priority to the lowest, callbacks of the same priority in the order they were assigned.
+        :param weak: Whether the manager only holds a weak reference to the callback, so it doesn't keep the object of a bound method alive. Once the object is garbage collected the callback is unregistered at the next edge of the pin.
+        :param debounce_time_ms: The debounce time in milliseconds, None uses the default of set_defaults.
         :param include_trigger_time: Whether to include the trigger time in the callback. (Will be the first argument)
         :param include_trigger_edge: Whether to include the trigger edge in the callback. (Will be the second argument if include_trigger_time is True, otherwise the first
         argument)
+        :param filter: "hardware" debounces in the gpiochip character device, so glitches never reach the process and an edge is reported once the line is stable for the debounce time. "software" delivers the first edge right away and drops the edges following it within the debounce time. "stable" reports an edge once the line held its new level for the debounce time, timed in Rust. "integrator" counts the time the line spends high up and the time it spends low down, between 0 and the debounce time, and reports an edge when the count reaches either end, so short glitches only delay an edge instead of restarting it.
+        """
+        ...
+
+    def set_timestamp_clock(self, clock: Optional[TimestampClockLike] = TimestampClock.REALTIME,
+                            nanoseconds: Optional[bool] = False) -> None:
+        """
+        Sets the clock and resolution of the trigger times passed to callbacks assigned with include_trigger_time. The edge timestamps of the kernel are taken from CLOCK_MONOTONIC and converted to the chosen clock without losing their nanosecond resolution.
+
+        :param clock: The clock of the trigger times (set it by using gpio_manager.TimestampClock.[MONOTONIC, REALTIME, BOOTTIME]).
+        :param nanoseconds: Whether the trigger time is an int in nanoseconds instead of a float in seconds.
+        """
+        ...
+
+    def start_logger(self, path: str, pins: Optional[list[int]] = None, format: Optional[str] = "csv",
+                     flush_interval_ms: Optional[int] = 1000) -> None:
+        """
+        Starts logging the edges of input pins to a file from Rust, without calling into Python for every edge. The file is appended to and a logger already running is replaced. The timestamps are in seconds and follow the clock chosen with set_timestamp_clock. Pins without callbacks are debounced in hardware for 2 ms.
+
+        :param path: The file the edges are written to.
+        :param pins: The input pins logged, every input pin if None. Pins set up later are logged as well.
+        :param format: "csv" writes lines of timestamp,pin,edge under a header, "jsonl" writes a JSON object per line.
+        :param flush_interval_ms: The time in ms between writes of the buffered lines to the file.
+        """
+        ...
+
+    def stop_logger(self) -> bool:
+        """
+        Stops the event logger and writes the lines it buffered to the file.
+
+        :return: Whether the logger was running.
+        """
+        ...
+
+    def load_config(self, config: Union[str, 'os.PathLike[str]', Dict[str, Any]]) -> Dict[str, int]:
+        """
+        Sets up the pins described by a configuration file or dict, so the wiring lives in a config file instead of
+        setup code. The whole configuration is validated before any pin is set up, errors name the offending entry,
+        and the pins already set up are reset when one fails.
+
+        The configuration has a pins table of pins by name, the names become aliases of the pins. Every pin has a pin
+        number and a mode of "input" (default), "output" or "pwm". Inputs take a pull of "auto", "up", "down" or
+        "external", outputs a state of "high" or "low" and an output_mode of "push_pull", "open_drain" or
+        "open_source", and PWM pins the frequency_hz, duty_cycle, period_ms and pulse_width_ms of setup_pwm and start
+        to start the signal. Every pin takes a logic_level of "high" or "low". The callbacks list written by
+        export_config is accepted on inputs and ignored.
+
+        :param config: The path of a .toml, .json, .yaml or .yml file, or the configuration as a dict. TOML needs
+            Python 3.11 or the tomli package, YAML the PyYAML package.
+        :return: The pins by name.
         """
         ...
 
-    def add_output_pin(self, pin_num: int, pin_state: Optional[PinState] = PinState.LOW,
-                       logic_level: Optional[LogicLevel] = LogicLevel.HIGH) -> None:
+    def get_aliases(self) -> Dict[str, int]:
+        """
+        Returns the names given to the pins by load_config, a name is forgotten when its pin is reset.
+
+        :return: The pins by name.
+        """
+        ...
+
+    def __contains__(self, key: Union[int, str]) -> bool:
+        """
+        :param key: A pin number or alias.
+        :return: Whether the pin is set up.
+        """
+        ...
+
+    def __getitem__(self, key: Union[int, str]) -> GPIOPin:
+        """
+        Returns a handle on a pin that is set up, raising a KeyError for pins that aren't.
+        :param key: A pin number or alias.
+        :return: The GPIOPin handle.
+        """
+        ...
+
+    def __iter__(self) -> Iterator[int]:
+        """Iterates over the numbers of the pins set up, in ascending order."""
+        ...
+
+    def __len__(self) -> int:
+        """:return: The number of pins set up."""
+        ...
+
+    def snapshot(self) -> Dict[int, Tuple[str, Optional[PinState]]]:
+        """
+        Captures the mode and logical state of every pin set up, to compare later with diff. PWM pins are captured
+        with a state of None, as their level keeps changing.
+
+        :return: The mode ("input", "output" or "pwm") and state of every pin.
+        """
+        ...
+
+    def diff(self, snapshot: Dict[int, Tuple[str, Optional[PinState]]]) \
+            -> Dict[int, Tuple[Optional[Tuple[str, Optional[PinState]]], Optional[Tuple[str, Optional[PinState]]]]]:
+        """
+        Compares the pins with a snapshot, returning the pins whose mode or state changed since it was taken. Pins set
+        up since then have None as their old entry, and pins reset since then None as their new entry.
+
+        :param snapshot: A snapshot returned by snapshot.
+        :return: The old and new entry of every pin that changed.
+        """
+        ...
+
+    def export_config(self, path: Optional[Union[str, 'os.PathLike[str]']] = None) -> Dict[str, Any]:
+        """
+        Returns the configuration of the pins set up, in the format of load_config, for saving a working setup and
+        restoring it with apply_config. Pins are named by their alias, or as "gpio<N>" without one. Outputs are saved
+        with the state they are driven to and PWM pins with their frequency, duty cycle and whether they run.
+
+        Inputs with callbacks get a callbacks list describing them, with the name of the function, the trigger edge,
+        the debounce and the extra arguments asked for. Functions can't be saved, so the list is only informational
+        and the callbacks must be assigned again after restoring.
+
+        :param path: The .toml, .json, .yaml or .yml file to write the configuration to, or None to only return it.
+            YAML needs the PyYAML package.
+        :return: The configuration, with a pins table of pins by name.
+        """
+        ...
+
+    def apply_config(self, config: Union[str, 'os.PathLike[str]', Dict[str, Any]]) -> Dict[str, int]:
+        """
+        Replaces the pins set up with the pins of a configuration, as returned or written by export_config. Unlike
+        load_config, the pins currently set up are reset first, after the configuration is validated.
+
+        :param config: The path of a .toml, .json, .yaml or .yml file, or the configuration as a dict.
+        :return: The pins by name.
+        """
+        ...
+
+    def add_output_pin(self, pin_num: int, pin_state: Optional[PinStateLike] = PinState.LOW,
+                       logic_level: Optional[LogicLevelLike] = None,
+                       output_mode: Optional[OutputModeLike] = OutputMode.PUSH_PULL, force: bool = False) -> None:
         """
         Sets up an output pin.
 
         :param pin_num: The GPIO pin to configure as output.
         :param pin_state: The initial state of the pin (set it by using gpio_manager.PINState.[HIGH or LOW]).
-        :param logic_level: The logic level of the pin (set it by using gpio_manager.LogicLevel.[HIGH or LOW]).
+        :param logic_level: The logic level of the pin (set it by using gpio_manager.LogicLevel.[HIGH or LOW]), None uses the default of set_defaults.
+        :param output_mode: How the pin drives its line, OPEN_DRAIN only pulls the line low and releases it for high, OPEN_SOURCE only pulls it high and releases it for low.
+        :param force: Takes the pin even when the kernel or another process claims it, such as the pins of an enabled I2C, SPI or UART bus.
+        """
+        ...
+
+    def set_logic_level(self, pin_num: int, logic_level: LogicLevelLike) -> None:
+        """
+        Changes the logic level of a configured pin. Reads, writes, trigger edges and PWM duty cycles are interpreted with the new level from then on, outputs keep their state and PWM pins their duty cycle, so their line flips.
+
+        :param pin_num: The GPIO pin.
+        :param logic_level: The new logic level of the pin.
+        """
+        ...
+
+    def set_direction(self, pin_num: int, direction: PinDirectionLike, pin_state: Optional[PinStateLike] = None,
+                      pull_resistor_state: Optional[InternPullResistorStateLike] = None) -> None:
+        """
+        Switches a configured pin between input and output without resetting it, for bidirectional protocols. The logic level of the pin is kept. The callbacks of an input pin stay registered while it is an output and fire again once it switches back to an input.
+
+        :param pin_num: The GPIO pin.
+        :param direction: The new direction of the pin.
+        :param pin_state: The state a pin switching to an output is set to, None keeps the state of an output and sets a switching input low.
+        :param pull_resistor_state: The pull resistor of a pin switching to an input, None uses the default of set_defaults.
         """
         ...
 
-    def set_output_pin(self, pin_num: int, pin_state: PinState) -> None:
+    def set_output_pin(self, pin_num: int, pin_state: PinStateLike) -> None:
         """
         Sets the state of an output pin.
 
@@ -67,6 +308,106 @@ class GPIOManager:
         """
         ...
 
+    def pin_stats(self, pin_num: int, reset: bool = False) -> Dict[str, Union[int, float, Optional[PinState]]]:
+        """
+        Returns the activity of a pin since it was set up, to find inputs that chatter and outputs that are stuck.
+
+        :param pin_num: The GPIO pin.
+        :param reset: Zeroes the counters after they are returned, so the next call covers the time since this one.
+        :return: The number of writes, reads, interrupts and callbacks dispatched, the time in seconds the counters cover
+            as seconds, the last known state (None until the pin is written, read or has an edge), the wall clock time
+            of its last change as last_change and the seconds since as seconds_since_change (None without a change).
+        :raises ValueError: If the pin was never set up.
+        """
+        ...
+
+    def read_all(self, as_dict: Optional[bool] = False) -> Union[int, Dict[int, PinState]]:
+        """
+        Reads the level of every GPIO of the header (0 to 27) at the same instant with a single register read, whether the pins are set up or not. The physical levels are returned, the logic level of configured pins isn't applied.
+
+        :param as_dict: Return a dict of the PinState of every pin instead of a bit mask.
+        :return: A bit mask where bit N is set when GPIO N is high, or a dict from the pin to its PinState.
+        """
+        ...
+
+    def capture(self, pins: List[int], sample_rate_hz: float, duration_ms: float, trigger_pin: Optional[int] = None,
+                trigger_edge: Optional[TriggerEdgeLike] = TriggerEdge.BOTH, timeout_ms: Optional[float] = None) -> bytes:
+        """
+        Samples the levels of pins at a fixed rate like a logic analyzer, whether the pins are set up or not. The samples are taken by a Rust loop with the GIL released and returned when the capture is done.
+
+        :param pins: The GPIO pins to capture (0 to 27), bit N of every sample is the level of the Nth pin.
+        :param sample_rate_hz: The number of samples per second.
+        :param duration_ms: The length of the capture in milliseconds.
+        :param trigger_pin: A pin whose edge starts the capture, the capture starts right away if it is None.
+        :param trigger_edge: The physical edge on the trigger pin that starts the capture.
+        :param timeout_ms: The longest time to wait for the trigger, forever if it is None. A TimeoutError is raised when it expires.
+        :return: The samples, 1 byte each for up to 8 pins, 2 bytes for up to 16 pins and 4 bytes otherwise, little endian, so they can be loaded with numpy.frombuffer(data, dtype=numpy.uint8) (or uint16, uint32).
+        """
+        ...
+
+    def sample_pin(self, pin_num: int, rate_hz: float, n_samples: int, buffer: Optional[Any] = None) -> Any:
+        """
+        Samples the level of a single pin at a fixed rate into a buffer, every byte is set to 1 for high and 0 for low. A buffer passed in is filled in place without allocating, so it can be reused to sample continuously. The GIL is released while sampling.
+
+        :param pin_num: The GPIO pin to sample (0 to 27).
+        :param rate_hz: The number of samples per second.
+        :param n_samples: The number of samples to take.
+        :param buffer: A writable contiguous buffer of bytes with room for the samples (bytearray, memoryview, numpy uint8 array), a new bytearray is returned if it is None.
+        :return: The buffer holding the samples.
+        """
+        ...
+
+    def touch_read(self, pin_num: int, samples: int = 10,
+                   pull_resistor_state: Optional[InternPullResistorStateLike] = InternPullResistorState.PULLUP,
+                   timeout_us: float = 10000) -> float:
+        """
+        Measures the capacitance of an electrode wired to a pin, such as a piece of foil, as the time it takes to charge
+        through the pull-up resistor. For every sample the pin is driven low to discharge the electrode, then released
+        and timed until it reads high. A finger on the electrode makes the charge time longer. A free pin is set up as
+        an output. The GIL is released while measuring.
+
+        :param pin_num: The GPIO pin wired to the electrode (0 to 27).
+        :param samples: The number of charge times averaged.
+        :param pull_resistor_state: PULLUP charges the electrode through the internal pull-up, EXTERNAL through a
+                                    resistor to 3.3V.
+        :param timeout_us: The longest charge time measured in microseconds, samples that take longer count as the
+                           timeout.
+        :return: The average charge time in microseconds.
+        """
+        ...
+
+    def rc_read(self, pin_num: int, timeout_ms: float = 100, samples: int = 1, discharge_ms: float = 10) -> float:
+        """
+        Reads an analog sensor without an ADC by RC timing: a resistive sensor, such as a photoresistor or a thermistor,
+        charges a capacitor wired from the pin to ground. For every sample the pin is driven low to empty the capacitor,
+        then released and timed until it reads high, a higher resistance gives a longer time. A free pin is set up as
+        an output. The GIL is released while measuring.
+
+        :param pin_num: The GPIO pin wired to the capacitor and the sensor (0 to 27).
+        :param timeout_ms: The longest time measured in milliseconds, samples that take longer count as the timeout.
+        :param samples: The number of times averaged.
+        :param discharge_ms: The time the capacitor is emptied for before every sample in milliseconds.
+        :return: The average charge time in microseconds.
+        """
+        ...
+
+    def rc_read_normalized(self, pin_num: int, low_us: float, high_us: float, timeout_ms: float = 100,
+                           samples: int = 1, discharge_ms: float = 10) -> float:
+        """
+        Reads an analog sensor by RC timing like rc_read and scales the time between two calibrated times to a value
+        between 0 and 1. The calibrated times are read with rc_read at both ends of the range, such as in the dark and
+        in bright light.
+
+        :param pin_num: The GPIO pin wired to the capacitor and the sensor (0 to 27).
+        :param low_us: The charge time in microseconds that reads as 0.
+        :param high_us: The charge time in microseconds that reads as 1.
+        :param timeout_ms: The longest time measured in milliseconds.
+        :param samples: The number of times averaged.
+        :param discharge_ms: The time the capacitor is emptied for before every sample in milliseconds.
+        :return: The reading between 0 and 1, times outside the calibrated range are clamped.
+        """
+        ...
+
     def unassign_callbacks(self, pin_num: int) -> None:
         """
         Unassigns all callbacks from an input pin.
@@ -75,6 +416,15 @@ class GPIOManager:
         """
         ...
 
+    def get_callbacks(self, pin_num: int) -> List[Tuple[Callable[..., None], TriggerEdge, int]]:
+        """
+        Returns the callbacks of an input pin in the order they are called, from the highest priority to the lowest.
+
+        :param pin_num: The GPIO pin.
+        :return: The callback, the edge it is assigned to and its priority.
+        """
+        ...
+
     def unassign_callback(self, pin_num: int, callback: Callable[..., None]) -> None:
         """
         Unassigns a specific callback from an input pin.
@@ -84,11 +434,35 @@ class GPIOManager:
         """
     ...
 
-    def wait_for_edge(self, pin_num: int, trigger_edge: Optional[TriggerEdge] = TriggerEdge.BOTH, timeout_ms:
+    def watch_activity(self, pin_num: int, max_silence_ms: float, callback: Callable[..., None],
+                       args: Optional[Tuple] = None) -> None:
+        """
+        Calls a function from Rust when an input pin has no edge for longer than a window, such as a fan tachometer
+        going silent or a heartbeat line dying. The function is called once per silence, the next edge starts a new
+        window. Every pin is watched from the same thread.
+
+        :param pin_num: The input pin.
+        :param max_silence_ms: The longest time without an edge in milliseconds, the window starts now.
+        :param callback: The function to call, with the args.
+        :param args: The arguments to pass to the function.
+        :raises ValueError: If the pin isn't an input pin or the window isn't positive.
+        """
+        ...
+
+    def unwatch_activity(self, pin_num: int) -> bool:
+        """
+        Stops watching the activity of a pin.
+
+        :param pin_num: The GPIO pin.
+        :return: Whether the pin was watched.
+        """
+        ...
+
+    def wait_for_edge(self, pin_num: int, trigger_edge: Optional[TriggerEdgeLike] = TriggerEdge.BOTH, timeout_ms:
     Optional[float] = None, debounce_ms: Optional[float] = 2) -> None:
         """
         Waits for an edge on the assigned pin. This function block for the given timeout, or waits forever if it is 
-        set to a negative number or None.
+        set to a negative number or None. The GIL is released while waiting, so other Python threads keep running.
 
         :param pin_num: The GPIO pin.
         :param trigger_edge: The trigger type (set using gpio_manager.TriggerEdge.[RISING, FALLING, BOTH]).
@@ -97,9 +471,29 @@ class GPIOManager:
         """
         ...
 
+    def wait_for_edges(self, pin_num: int, count: int, trigger_edge: Optional[TriggerEdgeLike] = TriggerEdge.BOTH,
+                       timeout_ms: Optional[float] = None, debounce_ms: Optional[float] = None,
+                       intervals: bool = False) -> List[float]:
+        """
+        Waits for a number of edges on an input pin and returns when each of them occurred. The edges are queued by the
+        kernel and collected in Rust, so none is missed between two of them. The GIL is released while waiting.
+
+        :param pin_num: The GPIO pin.
+        :param count: The number of edges to wait for.
+        :param trigger_edge: The edges collected (set using gpio_manager.TriggerEdge.[RISING, FALLING, BOTH]).
+        :param timeout_ms: The longest time to wait for all the edges in milliseconds, the edges collected so far are
+                           returned when it expires.
+        :param debounce_ms: Debounce time in milliseconds, None keeps every edge.
+        :param intervals: Whether the times between consecutive edges are returned instead of the timestamps.
+        :return: The timestamps of the edges, following the clock set with set_timestamp_clock, or the intervals between
+                 them, in seconds or in nanoseconds when set_timestamp_clock chose nanoseconds.
+        """
+        ...
+
     def setup_pwm(self, pin_num, frequency_hz: Optional[float] = None, duty_cycle: Optional[float] = None,
                   period_ms: Optional[float] = None,
-                  pulse_width_ms: Optional[float] = None, logic_level: Optional[LogicLevel] = LogicLevel.HIGH) -> None:
+                  pulse_width_ms: Optional[float] = None, logic_level: Optional[LogicLevelLike] = None,
+                  force: bool = False) -> None:
         """
         Sets up a PWM signal on the given pin. If The pin must be set up as an output pin before calling this
         function, the values for the logic level and current state will be preserved otherwise the default values
@@ -114,15 +508,226 @@ class GPIOManager:
         :param duty_cycle: The pulse width of the pwm signal as a percentage of the frequency (Duty cycle must be between 0 and 100).
         :param period_ms: The period in milliseconds.
         :param pulse_width_ms: The pulse width in milliseconds.
-        :param logic_level: The logic level of the pin (set it by using gpio_manager.LogicLevel.[HIGH or LOW]).
+        :param logic_level: The logic level of the pin (set it by using gpio_manager.LogicLevel.[HIGH or LOW]), None uses the default of set_defaults.
+        :param force: Takes the pin even when the kernel or another process claims it.
         """
         ...
 
-    def set_pwm_duty_cycle(self, pin_num: int, duty_cycle: float) -> None:
+    def set_pwm_duty_cycle(self, pin_num: int, duty_cycle: float, ramp_ms: Optional[float] = None,
+                           easing: Optional['Easing'] = Easing.LINEAR) -> None:
         """
-        Sets the PWM signal's duty cycle.
+        Sets the PWM signal's duty cycle, either immediately or ramped over a duration.
         :param pin_num: The GPIO pin.
         :param duty_cycle: The pulse width of the pwm signal as a percentage of the frequency (Duty cycle must be between 0 and 100).
+        :param ramp_ms: The time to ramp from the current duty cycle to the new one in milliseconds, the ramp runs in the background (None sets the duty cycle immediately).
+        :param easing: The curve the ramp follows (set it by using gpio_manager.Easing.[LINEAR, EASE_IN, EASE_OUT or EASE_IN_OUT]).
+        """
+        ...
+
+    def play_pwm_pattern(self, pin_num: int, pattern: List[Tuple[float, float]], loop: bool = False) -> None:
+        """
+        Plays a duty cycle pattern on a PWM pin from a background thread, for example to make an LED breathe. Setting
+        the duty cycle, playing another pattern or resetting the pin stops the pattern.
+        :param pin_num: The GPIO pin, which must be set up for PWM.
+        :param pattern: The steps as (duty_cycle, hold_ms), the duty cycle (0 to 100) is held for the time in milliseconds before the next step.
+        :param loop: Whether the pattern starts over after the last step.
+        """
+        ...
+
+    def play_melody(self, pin_num: int, melody: str, bpm: float = 120, non_blocking: bool = True) -> None:
+        """
+        Plays a melody on a buzzer wired to a PWM pin, parsed and timed in Rust. The notes are separated by spaces and
+        written <note><octave>:<length>, such as C4:8 or F#5:4., where the length is the denominator of the note value
+        (default is 4), a trailing . makes a dotted note and R is a rest. Setting the duty cycle, playing a pattern or
+        another melody or resetting the pin stops the melody.
+        :param pin_num: The GPIO pin, which must be set up for PWM and started.
+        :param melody: The notes of the melody.
+        :param bpm: The tempo in quarter notes per minute.
+        :param non_blocking: Whether the melody plays from a background thread, when False the call returns once the melody ended.
+        """
+        ...
+
+    def set_pwm_gamma(self, pin_num: int, gamma: Optional[float] = None, lut: Optional[List[float]] = None) -> None:
+        """
+        Sets a gamma curve for a PWM pin, so duty cycles are interpreted as perceived brightness. The curve is applied
+        before programming the pin, the getters keep returning the brightness. Passing neither gamma nor lut disables the curve.
+        :param pin_num: The GPIO pin.
+        :param gamma: The exponent of the curve, 2.2 suits most LEDs.
+        :param lut: Duty cycles (0 to 100) for brightness values evenly spaced from 0 to 100, used in place of the exponent.
+        """
+        ...
+
+    def set_pwm_phase(self, pin_num: int, phase_deg: Optional[float] = None) -> None:
+        """
+        Sets the phase offset of a PWM pin. Pins with a phase offset are driven by a single engine thread from a common
+        time base, so pins sharing a frequency keep their offset.
+        :param pin_num: The GPIO pin.
+        :param phase_deg: The offset of the period in degrees (0 to 360), None returns the pin to the regular software PWM.
+        """
+        ...
+
+    def set_pwm_high_resolution(self, pin_num: int, enabled: Optional[bool] = True) -> None:
+        """
+        Selects whether a PWM pin is driven by the high resolution engine of the GPIOManager instead of a thread per pin.
+        The engine drives all its pins from a dedicated thread that sleeps until shortly before every edge and
+        busy-waits for the rest, for frequencies up to tens of kHz and steady servo pulses.
+        :param pin_num: The GPIO pin.
+        :param enabled: Whether the engine drives the pin.
+        """
+        ...
+
+    def configure_pwm_engine(self, busy_wait_us: Optional[float] = 100, priority: Optional[int] = None) -> None:
+        """
+        Configures the thread of the high resolution PWM engine.
+        :param busy_wait_us: The time the engine busy-waits before every edge in microseconds, 0 only sleeps.
+        :param priority: The SCHED_FIFO real time priority of the thread (1 to 99), which needs root or the CAP_SYS_NICE capability. None uses the normal scheduler.
+        """
+        ...
+
+    def get_pwm_engine_stats(self) -> Dict[str, float]:
+        """
+        Gets the timing statistics of the high resolution PWM engine, the jitter is how late the edges were written.
+        :return: A dict with the number of edges as edges, and the mean and max jitter in microseconds as mean_jitter_us and max_jitter_us.
+        """
+        ...
+
+    def reset_pwm_engine_stats(self) -> None:
+        """
+        Resets the timing statistics of the high resolution PWM engine.
+        """
+        ...
+
+    def pin_capabilities(self, pin_num: int) -> Dict[str, Union[Optional[int], List[str], Dict[str, str]]]:
+        """
+        Gets the alternate functions a pin of the header supports on the detected board.
+        :param pin_num: The GPIO pin (0 to 27).
+        :return: The hardware PWM channel driving the pin with the PWMManager as pwm_channel (None if it has none), the PWM, I2C, SPI and UART functions of the pin as lists under pwm, i2c, spi and uart, and every alternate function by its pinctrl name (a0 to a8) as alt_functions.
+        """
+        ...
+
+    def set_drive_strength(self, pin_num: int, ma: int) -> None:
+        """
+        Sets the drive strength of the pad of a pin. On the Raspberry Pi 4 and older GPIO 0 to 27 share their pad settings, so the change applies to all of them. Needs root access to the pad control registers.
+        :param pin_num: The GPIO pin (0 to 27).
+        :param ma: The drive strength in mA, 2 to 16 in steps of 2 on the Raspberry Pi 4 and older, 2, 4, 8 or 12 on the Raspberry Pi 5.
+        """
+        ...
+
+    def set_slew_rate(self, pin_num: int, fast: bool) -> None:
+        """
+        Sets whether the slew rate of the pad of a pin is limited. Shares the pad settings like set_drive_strength.
+        :param pin_num: The GPIO pin (0 to 27).
+        :param fast: True for fast edges, False to limit the slew rate.
+        """
+        ...
+
+    def set_hysteresis(self, pin_num: int, enabled: bool) -> None:
+        """
+        Enables or disables the input hysteresis (Schmitt trigger) of the pad of a pin. Shares the pad settings like set_drive_strength.
+        :param pin_num: The GPIO pin (0 to 27).
+        :param enabled: Whether the hysteresis is enabled.
+        """
+        ...
+
+    def get_pad_config(self, pin_num: int) -> Dict[str, Union[int, bool, List[int]]]:
+        """
+        Gets the pad settings of a pin.
+        :param pin_num: The GPIO pin (0 to 27).
+        :return: The drive strength in mA as drive_ma, whether the slew rate is fast as slew_fast, whether the input hysteresis is enabled as hysteresis, the drive strengths the board supports as drive_strengths and whether the settings are shared by GPIO 0 to 27 as shared.
+        """
+        ...
+
+    def set_alt_function(self, pin_num: int, alt: Union[int, str]) -> None:
+        """
+        Routes a pin to one of its alternate functions, for example to hand it to a UART, SPI, I2C or PWM peripheral. The pin must not be in use by the manager.
+        :param pin_num: The GPIO pin (0 to 27).
+        :param alt: The alternate function by its number (0 to 5, or 0 to 8 on the Raspberry Pi 5), its pinctrl name such as "a0", or the name of the function such as "TXD0".
+        """
+        ...
+
+    def get_function(self, pin_num: int) -> str:
+        """
+        Gets the function a pin is routed to.
+        :param pin_num: The GPIO pin.
+        :return: "input" or "output" for a plain GPIO pin, the name of the alternate function such as "TXD0" otherwise, or "none" for a pin that isn't routed anywhere.
+        """
+        ...
+
+    def send_pulses(self, pin_num: int, pulses: List[Tuple[PinState, float]], repeat: Optional[int] = 1,
+                    callback: Optional[Callable[[], None]] = None) -> None:
+        """
+        Sends a pulse train on a pin from a dedicated real time thread, the call returns immediately. A pulse train already running on the pin is stopped.
+        :param pin_num: The GPIO pin, which must be below 32. The pin is set up as an output if it isn't in use.
+        :param pulses: The states of the pin with their durations in microseconds.
+        :param repeat: The number of times the pulses are sent, None repeats them until stop_pulses is called.
+        :param callback: Called without arguments once the pulse train ends.
+        """
+        ...
+
+    def stop_pulses(self, pin_num: int) -> bool:
+        """
+        Stops the pulse train running on a pin, the pin keeps its last state.
+        :param pin_num: The GPIO pin.
+        :return: Whether a pulse train was running on the pin.
+        """
+        ...
+
+    def start_heartbeat(self, pin_num: int, interval_ms: float, pattern: str = "toggle",
+                        pulse_ms: Optional[float] = None) -> None:
+        """
+        Drives a heartbeat on an output pin from a Rust thread, so an external watchdog keeps seeing it while the Python
+        interpreter is busy or stuck. A heartbeat or pulse train already running on the pin is replaced.
+
+        :param pin_num: The GPIO pin, which must be below 32.
+        :param interval_ms: The time between two beats in milliseconds.
+        :param pattern: "toggle" inverts the pin every interval, "pulse" sends a short high pulse every interval.
+        :param pulse_ms: The width of the pulses of the "pulse" pattern in milliseconds, a tenth of the interval by default.
+        :raises ValueError: If the interval isn't positive, the pulse isn't shorter than the interval or the pattern is unknown.
+        """
+        ...
+
+    def stop_heartbeat(self, pin_num: int) -> bool:
+        """
+        Stops the heartbeat of a pin, the pin keeps its last state.
+
+        :param pin_num: The GPIO pin.
+        :return: Whether a heartbeat or pulse train was running on the pin.
+        """
+        ...
+
+    def schedule(self, pin_num: int, state: PinStateLike, at_monotonic_time: float) -> ScheduledAction:
+        """
+        Sets an output pin to a state at a time of the monotonic clock, the change is made by a timer thread so the call returns immediately.
+        :param pin_num: The GPIO pin, which must be set up as an output.
+        :param state: The state to set the pin to.
+        :param at_monotonic_time: The time in seconds of the clock used by time.monotonic(), times in the past are run immediately.
+        :return: A handle to cancel the change.
+        """
+        ...
+
+    def schedule_in(self, pin_num: int, state: PinStateLike, delay_ms: float) -> ScheduledAction:
+        """
+        Sets an output pin to a state after a delay, the change is made by a timer thread so the call returns immediately.
+        :param pin_num: The GPIO pin, which must be set up as an output.
+        :param state: The state to set the pin to.
+        :param delay_ms: The delay in milliseconds.
+        :return: A handle to cancel the change.
+        """
+        ...
+
+    def cancel_scheduled(self, pin_num: Optional[int] = None) -> int:
+        """
+        Cancels the scheduled changes of a pin, or of all pins.
+        :param pin_num: The GPIO pin, None cancels the changes of all pins.
+        :return: The number of changes cancelled.
+        """
+        ...
+
+    def get_pwm_phase(self, pin_num: int) -> Optional[float]:
+        """
+        Gets the phase offset of a PWM pin.
+        :param pin_num: The GPIO pin.
+        :return: The phase offset in degrees, or None if the pin has no phase offset.
         """
         ...
 
@@ -150,10 +755,20 @@ class GPIOManager:
         """
         ...
 
-    def start_pwm(self, pin_num: int) -> None:
+    def start_pwm(self, pin_num: int, ramp_ms: Optional[float] = None) -> None:
         """
-        Starts the PWM signal.
+        Starts the PWM signal, either at its duty cycle or ramped up to it from 0 for a soft start.
         :param pin_num: The GPIO pin.
+        :param ramp_ms: The time to ramp from 0 to the configured duty cycle in milliseconds, the ramp runs in the
+                        background (default is None, which starts at the configured duty cycle).
+        """
+        ...
+
+    def start_pwm_group(self, pin_nums: List[int]) -> None:
+        """
+        Starts the PWM signals of several pins back to back, so they start as close to simultaneously as possible.
+        None of the signals is started if a pin isn't setup for PWM.
+        :param pin_nums: The GPIO pins.
         """
         ...
 
@@ -164,6 +779,46 @@ class GPIOManager:
         """
         ...
 
+    def get_pwm_frequency(self, pin_num: int) -> float:
+        """
+        Gets the frequency of a software PWM pin.
+        :param pin_num: The GPIO pin.
+        :return: The frequency in Hertz.
+        """
+        ...
+
+    def get_pwm_duty_cycle(self, pin_num: int) -> float:
+        """
+        Gets the duty cycle of a software PWM pin, relative to the logic level of the pin.
+        :param pin_num: The GPIO pin.
+        :return: The duty cycle (0 to 100).
+        """
+        ...
+
+    def get_pwm_period(self, pin_num: int) -> float:
+        """
+        Gets the period of a software PWM pin.
+        :param pin_num: The GPIO pin.
+        :return: The period in milliseconds.
+        """
+        ...
+
+    def get_pwm_pulse_width(self, pin_num: int) -> float:
+        """
+        Gets the pulse width of a software PWM pin.
+        :param pin_num: The GPIO pin.
+        :return: The pulse width in milliseconds.
+        """
+        ...
+
+    def is_pwm_active(self, pin_num: int) -> bool:
+        """
+        Checks whether the PWM signal of a software PWM pin is started.
+        :param pin_num: The GPIO pin.
+        :return: True if the signal is started, False if it is stopped.
+        """
+        ...
+
     def reset_pin(self, pin_num: int) -> None:
         """
         Resets the given pin so it can set to either input or output.
@@ -0,0 +1,117 @@
+class GPIOChipManager:
+    """
+    Drives the lines of a GPIO chip through the GPIO character device with the API of the GPIOManager, for the boards
+    rppal doesn't support, like the Orange Pi, Rock Pi and BeagleBone. Pins are the line offsets of the chip. It can be
+    used as a context manager that releases its lines on exit.
+    """
+
+    def __init__(self, chip: str = "/dev/gpiochip0") -> None:
+        """
+        Opens a GPIO chip.
+
+        :param chip: The character device of the chip.
+        """
+        ...
+
+    def chip_info(self) -> Dict[str, Any]:
+        """
+        Returns the name, label and number of lines of the chip.
+
+        :return: The name, label and lines of the chip.
+        """
+        ...
+
+    def get_lines(self) -> List[Dict[str, Any]]:
+        """
+        Returns the lines of the chip, like gpioinfo.
+
+        :return: The offset, name, consumer, used, output and active_low of every line.
+        """
+        ...
+
+    def find_line(self, name: str) -> int:
+        """
+        Returns the offset of the line with a name, as named by the device tree of the board.
+
+        :param name: The name of the line.
+        :return: The offset of the line.
+        """
+        ...
+
+    def add_input_pin(self, pin_num: int,
+                      pull_resistor_state: Optional[InternPullResistorStateLike] = InternPullResistorState.AUTO,
+                      logic_level: Optional[LogicLevelLike] = LogicLevel.HIGH) -> None:
+        """
+        Sets up an input line.
+
+        :param pin_num: The line offset.
+        :param pull_resistor_state: The bias of the line, AUTO leaves it as it is.
+        :param logic_level: The logic level of the line.
+        """
+        ...
+
+    def add_output_pin(self, pin_num: int, pin_state: Optional[PinStateLike] = PinState.LOW,
+                       logic_level: Optional[LogicLevelLike] = LogicLevel.HIGH,
+                       output_mode: Optional[OutputModeLike] = OutputMode.PUSH_PULL) -> None:
+        """
+        Sets up an output line.
+
+        :param pin_num: The line offset.
+        :param pin_state: The initial state of the line.
+        :param logic_level: The logic level of the line.
+        :param output_mode: How the line is driven.
+        """
+        ...
+
+    def set_output_pin(self, pin_num: int, pin_state: PinStateLike) -> None:
+        """
+        Sets the state of an output line.
+        """
+        ...
+
+    def get_pin(self, pin_num: int) -> PinState:
+        """
+        Reads the logical state of a line, inputs read the line and outputs the state they are driven to.
+        """
+        ...
+
+    def assign_callback(self, pin_num: int, callback: Callable[..., Any],
+                        trigger_edge: Optional[TriggerEdgeLike] = TriggerEdge.BOTH, debounce_time_ms: Optional[float] = 2,
+                        args: Optional[Tuple[Any, ...]] = None, include_trigger_time: Optional[bool] = False,
+                        include_trigger_edge: Optional[bool] = False) -> None:
+        """
+        Assigns a callback to an input line, called from a thread of the line. The edges are debounced by the kernel.
+
+        :param pin_num: The line offset.
+        :param callback: The function to invoke on pin change.
+        :param trigger_edge: The edges calling the callback.
+        :param debounce_time_ms: The debounce time in milliseconds, set by the first callback of the line.
+        :param args: Arguments to pass to the callback function.
+        :param include_trigger_time: Whether the time of the edge is passed first, in seconds since the Unix epoch.
+        :param include_trigger_edge: Whether the edge is passed after the time.
+        """
+        ...
+
+    def unassign_callbacks(self, pin_num: int) -> None:
+        """
+        Removes the callbacks of an input line.
+        """
+        ...
+
+    def reset_pin(self, pin_num: int) -> None:
+        """
+        Releases a line, which keeps its last state.
+        """
+        ...
+
+    def cleanup(self) -> None:
+        """
+        Releases the lines set up through this manager.
+        """
+        ...
+
+    def __enter__(self) -> 'GPIOChipManager':
+        ...
+
+    def __exit__(self, *args: Any) -> bool:
+        ...
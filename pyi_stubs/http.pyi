@@ -0,0 +1,32 @@
+class HTTPServer:
+    """
+    Handle of the HTTP server started with serve_http.
+    """
+
+    port: int
+    """
+    The port the server listens on, useful when it was started on port 0.
+    """
+
+    def stop(self) -> None:
+        """
+        Stops the server.
+        """
+        ...
+
+
+def serve_http(port: int, host: Optional[str] = "127.0.0.1") -> HTTPServer:
+    """
+    Starts an HTTP server exposing a JSON API for reading the inputs, setting the outputs, adjusting PWM and performing
+    I2C transactions. It runs in a Rust thread, so it answers while Python code is blocked.
+
+    The endpoints are GET /pins, GET /pins/<pin>, PUT /pins/<pin> with {"state": true}, PUT /pins/<pin>/pwm with
+    {"duty_cycle": 50} and POST /i2c/<bus>/<addr> with {"write": [0], "read": 2}. Every endpoint answers with
+    {"ok": true, "result": ...}, or with {"ok": false, "error": "..."} and the status 400 when the command fails. The
+    server has no authentication, only expose it on trusted networks.
+
+    :param port: The port to listen on, 0 picks a free port.
+    :param host: The address to listen on, local clients only by default.
+    :return: The handle of the server.
+    """
+    ...
@@ -0,0 +1,131 @@
+class I2CDevice:
+    """
+    I2CDevice provides methods to communicate with a single device, created with I2CManager.device. The slave address is
+    only selected again when another device was used on the bus in the meantime.
+    """
+
+    def read(self, length: int) -> bytes:
+        """
+        Reads data from the device.
+
+        :param length: The number of bytes to read.
+        :return: The bytes read.
+        """
+        ...
+
+    def write(self, data: Union[bytes, bytearray, memoryview, List[int]]) -> None:
+        """
+        Writes data to the device.
+
+        :param data: The bytes to write.
+        """
+        ...
+
+    def read_byte(self) -> int:
+        """
+        Reads a single byte from the device.
+
+        :return: The byte read.
+        """
+        ...
+
+    def write_byte(self, value: int) -> None:
+        """
+        Writes a single byte to the device.
+
+        :param value: The byte to write.
+        """
+        ...
+
+    def read_register(self, register: int) -> int:
+        """
+        Reads an 8 bit register of the device.
+
+        :param register: The register to read.
+        :return: The value of the register.
+        """
+        ...
+
+    def write_register(self, register: int, value: int) -> None:
+        """
+        Writes an 8 bit register of the device.
+
+        :param register: The register to write.
+        :param value: The value to write.
+        """
+        ...
+
+    def read_registers(self, register: int, length: int) -> bytes:
+        """
+        Reads consecutive registers of the device, starting at the given register.
+
+        :param register: The first register to read.
+        :param length: The number of bytes to read.
+        :return: The bytes read.
+        """
+        ...
+
+    def write_registers(self, register: int, data: Union[bytes, bytearray, memoryview, List[int]]) -> None:
+        """
+        Writes consecutive registers of the device, starting at the given register.
+
+        :param register: The first register to write.
+        :param data: The bytes to write.
+        """
+        ...
+
+    def read_word(self, register: int, big_endian: Optional[bool] = False) -> int:
+        """
+        Reads a 16 bit word from a register of the device using a single SMBus transaction.
+
+        :param register: The register to read.
+        :param big_endian: Treat the high byte as the first byte on the bus, SMBus devices send the low byte first.
+        :return: The word read.
+        """
+        ...
+
+    def write_word(self, register: int, value: int, big_endian: Optional[bool] = False) -> None:
+        """
+        Writes a 16 bit word to a register of the device using a single SMBus transaction.
+
+        :param register: The register to write.
+        :param value: The word to write.
+        :param big_endian: Send the high byte first, SMBus devices expect the low byte first.
+        """
+        ...
+
+    def write_read(self, write_data: Union[bytes, bytearray, memoryview, List[int]], read_length: int) -> bytes:
+        """
+        Performs a write followed by a read operation.
+
+        :param write_data: The bytes to write.
+        :param read_length: The number of bytes to read.
+        :return: The bytes read.
+        """
+        ...
+
+    def transaction(self, segments: List[Union[I2CWrite, I2CRead]]) -> List[bytes]:
+        """
+        Performs a combined transaction, the segments are sent with a repeated start between them and a single stop at
+        the end.
+
+        :param segments: The segments of the transaction, up to 42 segments can be used.
+        :return: The data read by every read segment, in order.
+        """
+        ...
+
+    def get_address(self) -> int:
+        """
+        Gets the I2C address of the device.
+
+        :return: The I2C address.
+        """
+        ...
+
+    def get_bus(self) -> int:
+        """
+        Gets the I2C bus the device is connected to.
+
+        :return: The bus number.
+        """
+        ...
@@ -1,3 +1,59 @@
+class I2CProbeMode:
+    """Enum representing how an address is probed when scanning the I2C bus."""
+    AUTO: 'I2CProbeMode'
+    """
+    Picks the probe for each address the same way i2cdetect does (Default).
+    """
+    QUICK_WRITE: 'I2CProbeMode'
+    """
+    Probes using an SMBus quick write, this can confuse some write only devices.
+    """
+    READ: 'I2CProbeMode'
+    """
+    Probes by reading a single byte, this can lock up some devices that don't expect reads.
+    """
+
+
+class I2CErrorKind:
+    """Enum representing the kinds of bus errors that can be retried."""
+    NACK: 'I2CErrorKind'
+    """
+    The device did not acknowledge its address or data (ENXIO, EREMOTEIO).
+    """
+    ARBITRATION_LOST: 'I2CErrorKind'
+    """
+    Another master took over the bus (EAGAIN).
+    """
+    TIMEOUT: 'I2CErrorKind'
+    """
+    The transfer or a clock stretch timed out (ETIMEDOUT, EIO).
+    """
+
+
+class I2CWrite:
+    """A write segment of a combined I2C transaction."""
+
+    def __init__(self, data: Union[bytes, bytearray, memoryview, List[int]]) -> None:
+        """
+        Initializes a new write segment.
+
+        :param data: The bytes to write.
+        """
+        ...
+
+
+class I2CRead:
+    """A read segment of a combined I2C transaction."""
+
+    def __init__(self, length: int) -> None:
+        """
+        Initializes a new read segment.
+
+        :param length: The number of bytes to read.
+        """
+        ...
+
+
 class I2CManager:
     """I2CManager provides methods to manage I2C communication."""
 
@@ -7,108 +63,161 @@ class I2CManager:
 
     def open(self, bus: Optional[int] = 1) -> None:
         """
-        Opens the I2C bus.
+        Opens an I2C bus. More than one bus can be open at the same time, the first bus opened is the default bus used by
+        operations that don't specify a bus.
 
         :param bus: The I2C bus number to open (default is 1).
         """
         ...
 
-    def close(self) -> None:
+    def close(self, bus: Optional[int] = None) -> None:
+        """
+        Closes an I2C bus, or every open bus if no bus is given. Pollers reading from a closed bus are stopped.
+
+        :param bus: The I2C bus number to close.
+        """
+        ...
+
+    def get_open_buses(self) -> List[int]:
+        """
+        Gets the I2C buses that are currently open.
+
+        :return: The open bus numbers.
+        """
+        ...
+
+    def device(self, addr: int, bus: Optional[int] = None) -> I2CDevice:
         """
-        Closes the I2C bus.
+        Creates a handle bound to a single device, so the address doesn't have to be passed to every call.
+
+        :param addr: The I2C slave address.
+        :param bus: The I2C bus the device is connected to (default is the first bus opened). The bus must be open.
+        :return: The device handle.
+        """
+        ...
+
+    def set_retry_policy(self, retries: Optional[int] = 0, backoff_ms: Optional[float] = 0,
+                         retry_on: Optional[List[I2CErrorKind]] = None) -> None:
+        """
+        Sets how failed transfers are retried on every bus, including transfers made by the EEPROM and RTC helpers. Bus
+        scans are never retried.
+
+        :param retries: The number of times a failed transfer is retried, 0 disables retrying.
+        :param backoff_ms: The delay before the first retry in milliseconds, doubled after every retry.
+        :param retry_on: The kinds of errors that are retried, all kinds are retried if it is None.
         """
         ...
 
-    def write_byte(self, addr: int, data: int) -> None:
+    def get_retry_policy(self) -> Tuple[int, float, List[I2CErrorKind]]:
+        """
+        Gets the retry policy.
+
+        :return: The number of retries, the initial backoff in milliseconds and the kinds of errors that are retried.
+        """
+        ...
+
+    def write_byte(self, addr: int, data: int, bus: Optional[int] = None) -> None:
         """
         Writes a single byte to the I2C slave device.
 
         :param addr: The I2C slave address.
         :param data: The byte to write.
+        :param bus: The I2C bus to use (default is the first bus opened).
         """
         ...
 
-    def block_write_byte(self, addr: int, command: int, data: int) -> None:
+    def block_write_byte(self, addr: int, command: int, data: int, bus: Optional[int] = None) -> None:
         """
         Writes a single byte with a command to the I2C slave device.
 
         :param addr: The I2C slave address.
         :param command: The command to send.
         :param data: The byte to write.
+        :param bus: The I2C bus to use (default is the first bus opened).
         """
         ...
 
-    def read_byte(self, addr: int) -> int:
+    def read_byte(self, addr: int, bus: Optional[int] = None) -> int:
         """
         Reads a single byte from the I2C slave device.
 
         :param addr: The I2C slave address.
+        :param bus: The I2C bus to use (default is the first bus opened).
         :return: The byte read.
         """
         ...
 
-    def block_read_byte(self, addr: int, command: int) -> int:
+    def block_read_byte(self, addr: int, command: int, bus: Optional[int] = None) -> int:
         """
         Reads a single byte with a command from the I2C slave device.
 
         :param addr: The I2C slave address.
         :param command: The command to send before reading.
+        :param bus: The I2C bus to use (default is the first bus opened).
         :return: The byte read.
         """
         ...
 
-    def write(self, addr: int, data: bytes) -> None:
+    def write(self, addr: int, data: Union[bytes, bytearray, memoryview, List[int]], bus: Optional[int] = None) -> None:
         """
         Writes data to the I2C slave device.
 
         :param addr: The I2C slave address.
         :param data: The bytes to write.
+        :param bus: The I2C bus to use (default is the first bus opened).
         """
         ...
 
-    def block_write(self, addr: int, command: int, data: bytes) -> None:
+    def block_write(self, addr: int, command: int, data: Union[bytes, bytearray, memoryview, List[int]],
+                    bus: Optional[int] = None) -> None:
         """
         Writes data with a command to the I2C slave device.
 
         :param addr: The I2C slave address.
         :param command: The command to send.
         :param data: The bytes to write.
+        :param bus: The I2C bus to use (default is the first bus opened).
         """
         ...
 
-    def read(self, addr: int, length: int) -> bytes:
+    def read(self, addr: int, length: int, bus: Optional[int] = None) -> bytes:
         """
         Reads data from the I2C slave device.
 
         :param addr: The I2C slave address.
         :param length: The number of bytes to read.
+        :param bus: The I2C bus to use (default is the first bus opened).
         :return: The bytes read.
         """
         ...
 
-    def block_read(self, addr: int, command: int, length: int) -> bytes:
+    def block_read(self, addr: int, command: int, length: int, bus: Optional[int] = None) -> bytes:
         """
         Reads data with a command from the I2C slave device.
 
         :param addr: The I2C slave address.
         :param command: The command to send before reading.
         :param length: The number of bytes to read.
+        :param bus: The I2C bus to use (default is the first bus opened).
         :return: The bytes read.
         """
         ...
 
-    def write_read(self, addr: int, write_data: bytes, read_length: int) -> bytes:
+    def write_read(self, addr: int, write_data: Union[bytes, bytearray, memoryview, List[int]], read_length: int,
+                   bus: Optional[int] = None) -> bytes:
         """
         Performs a write followed by a read operation.
 
         :param addr: The I2C slave address.
         :param write_data: The bytes to write.
         :param read_length: The number of bytes to read.
+        :param bus: The I2C bus to use (default is the first bus opened).
         :return: The bytes read.
         """
         ...
 
-    def block_write_read(self, addr: int, command: int, write_data: bytes, read_length: int) -> bytes:
+    def block_write_read(self, addr: int, command: int, write_data: Union[bytes, bytearray, memoryview, List[int]],
+                         read_length: int, bus: Optional[int] = None) -> bytes:
         """
         Performs a block write followed by a block read operation.
 
@@ -116,6 +225,174 @@ class I2CManager:
         :param command: The command to send.
         :param write_data: The bytes to write.
         :param read_length: The number of bytes to read.
+        :param bus: The I2C bus to use (default is the first bus opened).
         :return: The bytes read.
         """
         ...
+
+    def read_word(self, addr: int, command: int, big_endian: Optional[bool] = False, bus: Optional[int] = None) -> int:
+        """
+        Reads a 16 bit word from a register of the I2C slave device using a single SMBus transaction.
+
+        :param addr: The I2C slave address.
+        :param command: The register to read.
+        :param big_endian: Treat the high byte as the first byte on the bus, SMBus devices send the low byte first.
+        :param bus: The I2C bus to use (default is the first bus opened).
+        :return: The word read.
+        """
+        ...
+
+    def write_word(self, addr: int, command: int, value: int, big_endian: Optional[bool] = False,
+                   bus: Optional[int] = None) -> None:
+        """
+        Writes a 16 bit word to a register of the I2C slave device using a single SMBus transaction.
+
+        :param addr: The I2C slave address.
+        :param command: The register to write.
+        :param value: The word to write.
+        :param big_endian: Send the high byte first, SMBus devices expect the low byte first.
+        :param bus: The I2C bus to use (default is the first bus opened).
+        """
+        ...
+
+    def quick_command(self, addr: int, read: Optional[bool] = False, bus: Optional[int] = None) -> None:
+        """
+        Sends an SMBus quick command, which transfers a single bit in place of the read/write bit of the address.
+
+        :param addr: The I2C slave address.
+        :param read: The value of the read/write bit.
+        :param bus: The I2C bus to use (default is the first bus opened).
+        """
+        ...
+
+    def process_call(self, addr: int, command: int, value: int, big_endian: Optional[bool] = False,
+                     bus: Optional[int] = None) -> int:
+        """
+        Sends a 16 bit word to a register and reads the 16 bit word the device responds with in a single SMBus
+        transaction.
+
+        :param addr: The I2C slave address.
+        :param command: The register to send the word to.
+        :param value: The word to send.
+        :param big_endian: Transfer the high byte of both words first, SMBus devices use the low byte first.
+        :param bus: The I2C bus to use (default is the first bus opened).
+        :return: The word received.
+        """
+        ...
+
+    def block_process_call(self, addr: int, command: int, data: Union[bytes, bytearray, memoryview, List[int]],
+                           bus: Optional[int] = None) -> bytes:
+        """
+        Sends a block of up to 32 bytes to a register and reads the block the device responds with in a single SMBus
+        transaction.
+
+        :param addr: The I2C slave address.
+        :param command: The register to send the block to.
+        :param data: The block to send.
+        :param bus: The I2C bus to use (default is the first bus opened).
+        :return: The block received.
+        """
+        ...
+
+    def set_pec(self, enabled: bool, bus: Optional[int] = None) -> None:
+        """
+        Enables or disables SMBus packet error checking, which appends a CRC-8 byte to every SMBus transaction except
+        the quick command.
+
+        :param enabled: Whether packet error checking is used.
+        :param bus: The I2C bus to configure (default is the first bus opened).
+        """
+        ...
+
+    def get_pec(self, bus: Optional[int] = None) -> bool:
+        """
+        Gets whether SMBus packet error checking is enabled.
+
+        :param bus: The I2C bus to check (default is the first bus opened).
+        :return: True if packet error checking is enabled.
+        """
+        ...
+
+    def set_addr_10bit(self, enabled: bool, bus: Optional[int] = None) -> None:
+        """
+        Enables or disables 10-bit addressing. While it is enabled every address on the bus is sent as a 10-bit address,
+        so devices with 7-bit addresses can't be reached.
+
+        :param enabled: Whether 10-bit addresses are used.
+        :param bus: The I2C bus to configure (default is the first bus opened).
+        """
+        ...
+
+    def get_addr_10bit(self, bus: Optional[int] = None) -> bool:
+        """
+        Gets whether 10-bit addressing is enabled.
+
+        :param bus: The I2C bus to check (default is the first bus opened).
+        :return: True if 10-bit addressing is enabled.
+        """
+        ...
+
+    def transaction(self, addr: int, segments: List[Union[I2CWrite, I2CRead]], bus: Optional[int] = None) -> List[bytes]:
+        """
+        Performs a combined transaction, the segments are sent with a repeated start between them and a single stop at
+        the end, so no other master can access the device in the middle of the transaction.
+
+        :param addr: The I2C slave address.
+        :param segments: The segments of the transaction, up to 42 segments can be used.
+        :param bus: The I2C bus to use (default is the first bus opened).
+        :return: The data read by every read segment, in order.
+        """
+        ...
+
+    def poll(self, addr: int, command: int, length: int, interval_ms: float, callback: Callable[..., None],
+             args: Optional[Tuple] = None, on_change_only: Optional[bool] = True, bus: Optional[int] = None) -> int:
+        """
+        Starts a background thread that periodically reads a register block of a device and passes the data to the
+        callback. The reads run in Rust, so the GIL is only taken to call the callback.
+
+        :param addr: The I2C slave address.
+        :param command: The register to read.
+        :param length: The number of bytes to read.
+        :param interval_ms: The time between reads in milliseconds.
+        :param callback: The function called with the data read as its first argument.
+        :param args: Additional arguments passed to the callback after the data.
+        :param on_change_only: Only call the callback when the data differs from the previous read.
+        :param bus: The I2C bus to use (default is the first bus opened). The bus must be open.
+        :return: The id of the poller, used to stop it.
+        """
+        ...
+
+    def stop_poll(self, poll_id: int) -> None:
+        """
+        Stops a poller started with poll.
+
+        :param poll_id: The id returned by poll.
+        """
+        ...
+
+    def recover_bus(self, bus: Optional[int] = None, scl_pin: Optional[int] = None, sda_pin: Optional[int] = None) -> bool:
+        """
+        Recovers a bus where a slave is holding SDA low, for example after the master was interrupted in the middle of a
+        read. The pins are temporarily taken as GPIO, up to 9 clock pulses are sent until the slave releases SDA, then a
+        STOP condition is issued and the I2C function of the pins is restored.
+
+        :param bus: The I2C bus to recover (default is the first bus opened).
+        :param scl_pin: The GPIO pin used as SCL (default is 3 for bus 1 and 1 for bus 0).
+        :param sda_pin: The GPIO pin used as SDA (default is 2 for bus 1 and 0 for bus 0).
+        :return: True if SDA was released.
+        """
+        ...
+
+    def scan(self, start: Optional[int] = 0x03, end: Optional[int] = 0x77,
+             probe_mode: Optional[I2CProbeMode] = I2CProbeMode.AUTO, bus: Optional[int] = None) -> List[int]:
+        """
+        Scans the I2C bus for devices by probing every address in the given range.
+
+        :param start: The first address to probe.
+        :param end: The last address to probe, up to 0x3FF when 10-bit addressing is enabled.
+        :param probe_mode: How each address is probed (set it by using gpio_manager.I2CProbeMode.[AUTO, QUICK_WRITE or READ]).
+        AUTO uses a read for the 0x30 - 0x37 and 0x50 - 0x5F ranges and a quick write everywhere else, like i2cdetect.
+        :param bus: The I2C bus to use (default is the first bus opened).
+        :return: The addresses of the devices that responded.
+        """
+        ...
@@ -0,0 +1,9 @@
+def set_log_level(level: Union[int, str]) -> None:
+    """
+    Sets the level of the gpio_manager logger of the logging module, which receives the warnings of the library
+    (pinctrl failures, dropped events, callback errors and cleanup actions). Messages below the level are dropped
+    without entering Python, the level of the library is WARNING until it is set.
+
+    :param level: A level of the logging module, like logging.DEBUG or "DEBUG".
+    """
+    ...
@@ -0,0 +1,10 @@
+def get_metrics() -> Dict[str, Union[int, Dict[int, int]]]:
+    """
+    Returns the counters the library keeps since it was loaded, for exporting to monitoring systems like Prometheus.
+
+    :return: interrupts, callback_invocations, callback_errors and dropped_events map the pins to their counts, pins
+        without events are left out. i2c_transactions and i2c_failures count the I2C operations and the ones that
+        failed, lock_contentions and lock_wait_ns count the times a thread waited for the lock of the GPIO manager or of
+        the I2C buses and the total time it waited in nanoseconds.
+    """
+    ...
@@ -0,0 +1,64 @@
+class ModbusRTU:
+    """
+    ModbusRTU is a Modbus RTU master on a SoftUART, for the industrial sensors and power meters speaking Modbus over
+    RS-485. Requests are framed with their CRC, the bus is left silent for 3.5 characters between frames and the
+    responses are checked. Put the SoftUART in RS-485 mode with enable_rs485 for a half duplex transceiver.
+
+    A slave that doesn't respond raises a TimeoutError, and an exception response or a corrupted one raises a
+    RuntimeError.
+    """
+
+    def __init__(self, uart: SoftUART, timeout_ms: float = 1000, retries: int = 0) -> None:
+        """
+        Takes the serial port of the bus, with an RX and a TX pin. The port shouldn't be read by anything else while
+        requests are made.
+
+        :param uart: The serial port of the bus.
+        :param timeout_ms: How long a slave has to respond in milliseconds.
+        :param retries: How many times a request without a response is sent again.
+        """
+        ...
+
+    def read_holding_registers(self, slave: int, address: int, count: int = 1) -> list[int]:
+        """
+        Reads consecutive holding registers of a slave (function code 0x03).
+
+        :param slave: The address of the slave, from 1 to 247.
+        :param address: The address of the first register.
+        :param count: The number of registers to read, from 1 to 125.
+        :return: The values of the registers.
+        """
+        ...
+
+    def read_input_registers(self, slave: int, address: int, count: int = 1) -> list[int]:
+        """
+        Reads consecutive input registers of a slave (function code 0x04).
+
+        :param slave: The address of the slave, from 1 to 247.
+        :param address: The address of the first register.
+        :param count: The number of registers to read, from 1 to 125.
+        :return: The values of the registers.
+        """
+        ...
+
+    def write_single_register(self, slave: int, address: int, value: int) -> None:
+        """
+        Writes a single holding register of a slave (function code 0x06). Slave 0 broadcasts the write to every slave,
+        which don't respond.
+
+        :param slave: The address of the slave, from 1 to 247, or 0 to broadcast.
+        :param address: The address of the register.
+        :param value: The value to write.
+        """
+        ...
+
+    def write_multiple_registers(self, slave: int, address: int, values: list[int]) -> None:
+        """
+        Writes consecutive holding registers of a slave (function code 0x10). Slave 0 broadcasts the write to every
+        slave, which don't respond.
+
+        :param slave: The address of the slave, from 1 to 247, or 0 to broadcast.
+        :param address: The address of the first register.
+        :param values: The values to write, 1 to 123 registers.
+        """
+        ...
@@ -0,0 +1,42 @@
+class MQTTBridge:
+    """
+    Handle of the MQTT bridge started with mqtt_bridge.
+    """
+
+    def is_connected(self) -> bool:
+        """
+        Returns whether the bridge is connected to the broker.
+        """
+        ...
+
+    def stop(self) -> None:
+        """
+        Stops the bridge, publishing "offline" as its status and disconnecting from the broker. Stopping a bridge that
+        was replaced or already stopped does nothing.
+        """
+        ...
+
+
+def mqtt_bridge(broker: str, topic_prefix: str, port: Optional[int] = 1883, client_id: Optional[str] = None,
+                username: Optional[str] = None, password: Optional[str] = None,
+                pins: Optional[List[int]] = None) -> MQTTBridge:
+    """
+    Starts a bridge between the pins and an MQTT broker, run by a Rust client thread so it keeps working while Python
+    is busy. A bridge already running is replaced.
+
+    The state of the input pins is published to <topic_prefix>/<pin>/state as "1" or "0" after every edge, retained so
+    new subscribers get the current state. Output pins are set by publishing 1, 0, ON, OFF, HIGH, LOW, TRUE or FALSE to
+    <topic_prefix>/<pin>/set, and the duty cycle of PWM pins by publishing 0 to 100 to <topic_prefix>/<pin>/pwm/set.
+    The bridge publishes "online" to <topic_prefix>/status and the broker publishes "offline" when the connection is
+    lost. Input pins without callbacks are debounced in hardware for 2 ms.
+
+    :param broker: The host name or address of the broker.
+    :param topic_prefix: The prefix of the topics of the bridge.
+    :param port: The port of the broker.
+    :param client_id: The client id, derived from the prefix if None.
+    :param username: The user name for brokers requiring a login.
+    :param password: The password of the user.
+    :param pins: The input pins published, every input pin if None. Pins set up later are published as well.
+    :return: The handle of the bridge.
+    """
+    ...
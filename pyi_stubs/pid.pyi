@@ -0,0 +1,104 @@
+class PIDController:
+    """
+    PIDController holds a measurement at a setpoint by driving a PWM output, for example the speed of a fan or motor.
+    The loop runs at a fixed rate on a Rust thread, reading the speed of a tachometer from the pulses on an input pin or
+    any value, such as an ADC reading, through a function. The integral stops growing while the output is saturated,
+    so the controller recovers quickly when the setpoint can be reached again.
+    """
+
+    def __init__(self, output_pin: int, kp: float, ki: float = 0, kd: float = 0, setpoint: float = 0,
+                 measure: Optional[Callable[[], float]] = None, tach_pin: Optional[int] = None,
+                 pulses_per_revolution: float = 2, rate_hz: float = 100, output_min: float = 0,
+                 output_max: float = 100, frequency_hz: float = 1000) -> None:
+        """
+        Sets up the PWM output, through the hardware PWM channel of the pin when it has one, and the measurement
+        source. The output stays off until the controller is started.
+
+        :param output_pin: The GPIO pin of the PWM output.
+        :param kp: The proportional gain, in duty cycle percent per unit of error.
+        :param ki: The integral gain, per unit of error and second.
+        :param kd: The derivative gain, per unit of error per second.
+        :param setpoint: The value the measurement is held at.
+        :param measure: A function returning the measurement, for example an ADC reading.
+        :param tach_pin: A free pin receiving the pulses of a tachometer, the measurement is its speed in RPM, used in
+                         place of a function.
+        :param pulses_per_revolution: The pulses of the tachometer per revolution, PC fans give 2.
+        :param rate_hz: The rate of the loop in Hertz.
+        :param output_min: The lowest duty cycle of the output.
+        :param output_max: The highest duty cycle of the output.
+        :param frequency_hz: The PWM frequency in Hertz.
+        """
+        ...
+
+    def start(self) -> None:
+        """Starts the PWM output and the loop from a cleared integral."""
+        ...
+
+    def stop(self) -> None:
+        """Stops the loop and the PWM signal, the output pin is driven to its inactive level."""
+        ...
+
+    def close(self) -> None:
+        """Stops the controller and releases its pins."""
+        ...
+
+    def set_setpoint(self, setpoint: float) -> None:
+        """
+        Sets the value the measurement is held at, used from the next step.
+
+        :param setpoint: The setpoint.
+        """
+        ...
+
+    def get_setpoint(self) -> float:
+        """
+        :return: The setpoint.
+        """
+        ...
+
+    def set_gains(self, kp: Optional[float] = None, ki: Optional[float] = None, kd: Optional[float] = None) -> None:
+        """
+        Sets the gains while the loop runs, the gains not given are kept.
+
+        :param kp: The proportional gain.
+        :param ki: The integral gain.
+        :param kd: The derivative gain.
+        """
+        ...
+
+    def get_gains(self) -> Tuple[float, float, float]:
+        """
+        :return: The gains as (kp, ki, kd).
+        """
+        ...
+
+    def set_output_limits(self, output_min: float, output_max: float) -> None:
+        """
+        Sets the range of duty cycles of the output.
+
+        :param output_min: The lowest duty cycle (0 to 100).
+        :param output_max: The highest duty cycle (0 to 100).
+        """
+        ...
+
+    def reset(self) -> None:
+        """Clears the integral and the previous measurement, for example after the load changed."""
+        ...
+
+    def get_measurement(self) -> Optional[float]:
+        """
+        :return: The last measurement, or None before the first step.
+        """
+        ...
+
+    def get_output(self) -> float:
+        """
+        :return: The duty cycle of the output (0 to 100).
+        """
+        ...
+
+    def is_active(self) -> bool:
+        """
+        :return: Whether the loop is running.
+        """
+        ...
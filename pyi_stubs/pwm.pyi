@@ -0,0 +1,114 @@
+class PWM:
+    """
+    PWM drives a PWM signal on a pin with one set of methods, using the hardware PWM channel of the pin when it has one
+    and software PWM through the GPIOManager otherwise.
+    """
+
+    def __init__(self, pin: int, frequency_hz: Optional[float] = None, duty_cycle: Optional[float] = None,
+                 period_ms: Optional[float] = None, pulse_width_ms: Optional[float] = None,
+                 logic_level: Optional[LogicLevelLike] = LogicLevel.HIGH) -> None:
+        """
+        Sets up PWM on a pin. The hardware PWM channel is used when the pin has one and it can be set up, for example
+        when the PWM overlay is enabled, otherwise the pin falls back to software PWM.
+        The value of frequency_hz and duty_cycle overwrites period_ms and pulse_width_ms if they are set.
+
+        :param pin: The GPIO pin.
+        :param frequency_hz: The frequency in Hertz (default is 1000).
+        :param duty_cycle: The duty cycle (0 to 100, default is 0).
+        :param period_ms: The period in milliseconds.
+        :param pulse_width_ms: The pulse width in milliseconds.
+        :param logic_level: The logic level of the pin, LOW inverts the signal.
+        """
+        ...
+
+    def start(self) -> None:
+        """Starts the PWM signal."""
+        ...
+
+    def stop(self) -> None:
+        """Stops the PWM signal, the pin is driven to its inactive level."""
+        ...
+
+    def set_duty_cycle(self, duty_cycle: float, ramp_ms: Optional[float] = None,
+                       easing: Optional['Easing'] = Easing.LINEAR) -> None:
+        """
+        Sets the duty cycle, either immediately or ramped over a duration.
+
+        :param duty_cycle: The duty cycle (0 to 100).
+        :param ramp_ms: The time to ramp from the current duty cycle to the new one in milliseconds, the ramp runs in
+                        the background (None sets the duty cycle immediately).
+        :param easing: The curve the ramp follows.
+        """
+        ...
+
+    def play_pattern(self, pattern: List[Tuple[float, float]], loop: bool = False) -> None:
+        """
+        Plays a duty cycle pattern from a background thread, for example to make an LED breathe. Setting the duty
+        cycle, playing another pattern or resetting the output stops the pattern.
+
+        :param pattern: The steps as (duty_cycle, hold_ms), the duty cycle (0 to 100) is held for the time in milliseconds before the next step.
+        :param loop: Whether the pattern starts over after the last step.
+        """
+        ...
+
+    def set_gamma(self, gamma: Optional[float] = None, lut: Optional[List[float]] = None) -> None:
+        """
+        Sets a gamma curve, so duty cycles are interpreted as perceived brightness. Passing neither gamma nor lut
+        disables the curve.
+
+        :param gamma: The exponent of the curve, 2.2 suits most LEDs.
+        :param lut: Duty cycles (0 to 100) for brightness values evenly spaced from 0 to 100, used in place of the exponent.
+        """
+        ...
+
+    def set_frequency(self, frequency_hz: float) -> None:
+        """
+        Sets the frequency, keeping the duty cycle.
+
+        :param frequency_hz: The frequency in Hertz.
+        """
+        ...
+
+    def set_period(self, period_ms: float) -> None:
+        """
+        Sets the period.
+
+        :param period_ms: The period in milliseconds.
+        """
+        ...
+
+    def set_pulse_width(self, pulse_width_ms: float) -> None:
+        """
+        Sets the pulse width, which must be less than the period.
+
+        :param pulse_width_ms: The pulse width in milliseconds.
+        """
+        ...
+
+    def reset(self) -> None:
+        """Stops the PWM signal and releases the pin, so it can be set up again."""
+        ...
+
+    def is_hardware(self) -> bool:
+        """
+        Returns whether the signal is generated by a hardware PWM channel.
+
+        :return: True for hardware PWM, False for software PWM.
+        """
+        ...
+
+    def get_channel(self) -> Optional[int]:
+        """
+        Returns the hardware PWM channel driving the pin.
+
+        :return: The channel, or None for software PWM.
+        """
+        ...
+
+    def get_pin(self) -> int:
+        """
+        Returns the GPIO pin.
+
+        :return: The GPIO pin.
+        """
+        ...
@@ -8,7 +8,7 @@ class PWMManager:
     def setup_pwm_channel(self, channel_num: int, frequency_hz: Optional[float] = None,
                           duty_cycle: Optional[float] = None, period_ms: Optional[float] = None,
                           pulse_width_ms: Optional[float] = None,
-                          logic_level: Optional['LogicLevel'] = LogicLevel.HIGH) -> None:
+                          logic_level: Optional[LogicLevelLike] = LogicLevel.HIGH, strict: bool = True) -> None:
         """
         Sets up a PWM channel with the specified parameters.
         The value of frequency_hz and duty_cycle overwrites period_ms and pulse_width_ms if they are set.
@@ -21,14 +21,27 @@ class PWMManager:
         :param period_ms: The period in milliseconds.
         :param pulse_width_ms: The pulse width in milliseconds.
         :param logic_level: The Logic level of the PWM signal (set using LogicLevel.[NORMAL or INVERSE]).
+        :param strict: Whether a failure to route the pin to the PWM peripheral raises an OSError, when False a warning is logged to the gpio_manager logger and the channel is set up anyway.
         """
         ...
 
-    def start_pwm_channel(self, channel_num: int) -> None:
+    def start_pwm_channel(self, channel_num: int, ramp_ms: Optional[float] = None) -> None:
         """
-        Starts the PWM signal on the specified channel.
+        Starts the PWM signal on the specified channel, either at its duty cycle or ramped up to it from 0 for a soft
+        start.
 
         :param channel_num: The PWM channel number (0 or 1).
+        :param ramp_ms: The time to ramp from 0 to the configured duty cycle in milliseconds, the ramp runs in the
+                        background (default is None, which starts at the configured duty cycle).
+        """
+        ...
+
+    def start_channels(self, channel_nums: List[int]) -> None:
+        """
+        Starts the PWM signals of several channels back to back under a single lock, so they start as close to
+        simultaneously as possible. None of the channels is started if one isn't initialized.
+
+        :param channel_nums: The PWM channel numbers.
         """
         ...
 
@@ -48,12 +61,39 @@ class PWMManager:
         """
         ...
 
-    def set_duty_cycle(self, channel_num: int, duty_cycle: float) -> None:
+    def set_duty_cycle(self, channel_num: int, duty_cycle: float, ramp_ms: Optional[float] = None,
+                       easing: Optional['Easing'] = Easing.LINEAR) -> None:
         """
-        Sets the duty cycle for the specified PWM channel.
+        Sets the duty cycle for the specified PWM channel, either immediately or ramped over a duration.
 
         :param channel_num: The PWM channel number (0 or 1).
         :param duty_cycle: The new duty cycle (0 to 100).
+        :param ramp_ms: The time to ramp from the current duty cycle to the new one in milliseconds, the ramp runs in
+                        the background (None sets the duty cycle immediately).
+        :param easing: The curve the ramp follows (set it by using gpio_manager.Easing.[LINEAR, EASE_IN, EASE_OUT or EASE_IN_OUT]).
+        """
+        ...
+
+    def play_pattern(self, channel_num: int, pattern: List[Tuple[float, float]], loop: bool = False) -> None:
+        """
+        Plays a duty cycle pattern on the specified PWM channel from a background thread, for example to make an LED
+        breathe. Setting the duty cycle, playing another pattern or resetting the channel stops the pattern.
+
+        :param channel_num: The PWM channel number (0 or 1).
+        :param pattern: The steps as (duty_cycle, hold_ms), the duty cycle (0 to 100) is held for the time in milliseconds before the next step.
+        :param loop: Whether the pattern starts over after the last step.
+        """
+        ...
+
+    def set_gamma(self, channel_num: int, gamma: Optional[float] = None, lut: Optional[List[float]] = None) -> None:
+        """
+        Sets a gamma curve for the specified PWM channel, so duty cycles are interpreted as perceived brightness. The
+        curve is applied before programming the channel, get_duty_cycle keeps returning the brightness. Passing neither
+        gamma nor lut disables the curve.
+
+        :param channel_num: The PWM channel number (0 or 1).
+        :param gamma: The exponent of the curve, 2.2 suits most LEDs.
+        :param lut: Duty cycles (0 to 100) for brightness values evenly spaced from 0 to 100, used in place of the exponent.
         """
         ...
 
@@ -103,6 +143,19 @@ class PWMManager:
         """
         ...
 
+    def capture(self, channel_num: int, duration_ms: float = 100) -> Tuple[float, float]:
+        """
+        Measures the frequency and the duty cycle of an external PWM signal fed into the pin of a channel, by sampling
+        the pin as fast as possible from Rust for the duration. The channel must not be set up, a free pin is set up as
+        an input without pull resistor for the capture and released after. The GIL is released while measuring.
+
+        :param channel_num: The PWM channel whose pin the signal is fed into.
+        :param duration_ms: The time to measure for in milliseconds, it should span several periods.
+        :return: The frequency in Hertz and the duty cycle (0 to 100), averaged over the periods seen. A signal without
+                 a full period reads as 0 Hz with a duty cycle of 0 or 100 for its level.
+        """
+        ...
+
     def get_period(self, channel_num: int) -> float:
         """
         Gets the current period of the specified PWM channel.
@@ -121,6 +174,26 @@ class PWMManager:
         """
         ...
 
+    @staticmethod
+    def channel_for_pin(pin_num: int) -> Optional[int]:
+        """
+        Returns the hardware PWM channel a GPIO pin is driven by.
+
+        :param pin_num: The GPIO pin.
+        :return: The PWM channel number, or None if the pin has no hardware PWM channel.
+        """
+        ...
+
+    @staticmethod
+    def available_channels() -> Dict[int, int]:
+        """
+        Returns the hardware PWM channels of the detected board with the GPIO pin each one is routed to. The channels
+        use GPIO 18 and 19 on every board, with alternate function a3 on the Raspberry Pi 5 and a5 on earlier models.
+
+        :return: The GPIO pin of every PWM channel.
+        """
+        ...
+
     def cleanup(self) -> None:
         """
         Sets all PWM channels to the disabled state and clears them from the set list
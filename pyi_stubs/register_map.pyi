@@ -0,0 +1,79 @@
+class Register:
+    """Register describes a single register of a device for use in a RegisterMap."""
+
+    def __init__(self, name: str, address: int, width: Optional[int] = 1, big_endian: Optional[bool] = False,
+                 signed: Optional[bool] = False, scale: Optional[float] = None) -> None:
+        """
+        Initializes a new register description.
+
+        :param name: The name used to access the register.
+        :param address: The address of the first byte of the register.
+        :param width: The width of the register in bytes, from 1 to 8.
+        :param big_endian: The first byte holds the most significant byte.
+        :param signed: The register holds a two's complement value.
+        :param scale: The value of one raw count, registers with a scale are read and written as floats.
+        """
+        ...
+
+    def get_name(self) -> str:
+        """
+        Gets the name of the register.
+
+        :return: The name of the register.
+        """
+        ...
+
+    def get_address(self) -> int:
+        """
+        Gets the address of the register.
+
+        :return: The address of the register.
+        """
+        ...
+
+
+class RegisterMap:
+    """RegisterMap reads and writes the registers of an I2CDevice by name, packing and unpacking the values in Rust."""
+
+    def __init__(self, device: I2CDevice, registers: List[Register]) -> None:
+        """
+        Initializes a new register map for a device.
+
+        :param device: The device the registers belong to.
+        :param registers: The registers of the device.
+        """
+        ...
+
+    def read(self, name: str) -> Union[int, float]:
+        """
+        Reads a register by name.
+
+        :param name: The name of the register.
+        :return: The value of the register, scaled registers return a float.
+        """
+        ...
+
+    def write(self, name: str, value: Union[int, float]) -> None:
+        """
+        Writes a register by name.
+
+        :param name: The name of the register.
+        :param value: The value to write, the value of scaled registers is rounded to the nearest raw count.
+        """
+        ...
+
+    def read_all(self) -> Dict[str, Union[int, float]]:
+        """
+        Reads every register in the order they were declared.
+
+        :return: The value of every register by name.
+        """
+        ...
+
+    def get_names(self) -> List[str]:
+        """
+        Gets the names of the registers in the order they were declared.
+
+        :return: The register names.
+        """
+        ...
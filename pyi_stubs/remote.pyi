@@ -0,0 +1,135 @@
+class RemoteGPIOManager:
+    """
+    Drives the pins of a remote Pi running the event server, or of the daemon owning the GPIO of this Pi, with the API
+    of the GPIOManager. Returned by
+    GPIOManager.connect, it can be used as a context manager that resets its pins and closes the connection.
+    """
+
+    def add_input_pin(self, pin_num: int,
+                      pull_resistor_state: Optional[InternPullResistorStateLike] = InternPullResistorState.AUTO,
+                      logic_level: Optional[LogicLevelLike] = LogicLevel.HIGH) -> None:
+        """
+        Sets up an input pin of the remote Pi, its edges are streamed to this manager.
+
+        :param pin_num: The GPIO pin to configure as input.
+        :param pull_resistor_state: The pull resistor of the pin.
+        :param logic_level: The logic level of the pin.
+        """
+        ...
+
+    def add_output_pin(self, pin_num: int, pin_state: Optional[PinStateLike] = PinState.LOW,
+                       logic_level: Optional[LogicLevelLike] = LogicLevel.HIGH) -> None:
+        """
+        Sets up an output pin of the remote Pi.
+
+        :param pin_num: The GPIO pin to configure as output.
+        :param pin_state: The initial state of the pin.
+        :param logic_level: The logic level of the pin.
+        """
+        ...
+
+    def set_output_pin(self, pin_num: int, pin_state: PinStateLike) -> None:
+        """
+        Sets the state of an output pin of the remote Pi.
+        """
+        ...
+
+    def get_pin(self, pin_num: int) -> PinState:
+        """
+        Reads the state of an input pin of the remote Pi.
+        """
+        ...
+
+    def setup_pwm(self, pin_num: int, frequency_hz: Optional[float] = None, duty_cycle: Optional[float] = None,
+                  period_ms: Optional[float] = None, pulse_width_ms: Optional[float] = None,
+                  logic_level: Optional[LogicLevelLike] = LogicLevel.HIGH) -> None:
+        """
+        Sets up a software PWM pin of the remote Pi, see GPIOManager.setup_pwm.
+        """
+        ...
+
+    def start_pwm(self, pin_num: int) -> None:
+        """
+        Starts the PWM signal of a pin of the remote Pi.
+        """
+        ...
+
+    def stop_pwm(self, pin_num: int) -> None:
+        """
+        Stops the PWM signal of a pin of the remote Pi.
+        """
+        ...
+
+    def set_pwm_duty_cycle(self, pin_num: int, duty_cycle: float) -> None:
+        """
+        Sets the duty cycle (0 to 100) of a PWM pin of the remote Pi.
+        """
+        ...
+
+    def i2c_write_read(self, addr: int, write_data: Union[bytes, bytearray, memoryview, List[int]], read_length: int,
+                       bus: Optional[int] = 1) -> bytes:
+        """
+        Writes data to an I2C device and reads its response in one transaction on the remote Pi, opening the bus if
+        needed.
+
+        :param addr: The I2C slave address.
+        :param write_data: The data to write, empty for a plain read.
+        :param read_length: The number of bytes to read, 0 for a plain write.
+        :param bus: The I2C bus to use.
+        :return: The data read.
+        """
+        ...
+
+    def assign_callback(self, pin_num: int, callback: Callable[..., None],
+                        trigger_edge: Optional[TriggerEdgeLike] = TriggerEdge.BOTH, args: Optional[Tuple] = None,
+                        include_trigger_time: Optional[bool] = False,
+                        include_trigger_edge: Optional[bool] = False) -> None:
+        """
+        Assigns a callback to an input pin of the remote Pi, called from the callback thread of the connection.
+        The edges are debounced by the server.
+
+        :param pin_num: The GPIO pin.
+        :param callback: The function invoked on pin change.
+        :param trigger_edge: The edges calling the callback.
+        :param args: The arguments passed to the callback.
+        :param include_trigger_time: Whether the time of the edge on the remote Pi is passed first.
+        :param include_trigger_edge: Whether the edge is passed after the time.
+        """
+        ...
+
+    def unassign_callbacks(self, pin_num: int) -> None:
+        """
+        Removes the callbacks of a pin of the remote Pi.
+        """
+        ...
+
+    def reset_pin(self, pin_num: int) -> None:
+        """
+        Resets a pin of the remote Pi.
+        """
+        ...
+
+    def cleanup(self) -> None:
+        """
+        Resets the pins of the remote Pi set up through this manager.
+        """
+        ...
+
+    def close(self) -> None:
+        """
+        Closes the connection to the remote Pi. The pins keep their state on an event server, while a daemon resets the
+        pins of the clients that disconnect.
+        """
+        ...
+
+    def is_connected(self) -> bool:
+        """
+        Returns whether the connection to the remote Pi is open.
+        """
+        ...
+
+    def __enter__(self) -> 'RemoteGPIOManager':
+        ...
+
+    def __exit__(self, *args: Any) -> bool:
+        ...
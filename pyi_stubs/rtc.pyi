@@ -0,0 +1,113 @@
+class RTCModel:
+    """Enum representing the supported real time clock models."""
+    DS1307: 'RTCModel'
+    """
+    DS1307 real time clock (no alarms).
+    """
+    DS3231: 'RTCModel'
+    """
+    DS3231 temperature compensated real time clock with two alarms.
+    """
+
+
+class RTC:
+    """RTC provides methods to read and set the time, alarms and square wave output of DS1307 and DS3231 real time clocks."""
+
+    def __init__(self, model: RTCModel, addr: Optional[int] = 0x68, bus: Optional[int] = None) -> None:
+        """
+        Initializes a new RTC instance. The I2C bus must be opened with the I2CManager before using the clock.
+
+        :param model: The real time clock model (set it by using gpio_manager.RTCModel.[DS1307 or DS3231]).
+        :param addr: The I2C address of the real time clock.
+        :param bus: The I2C bus the real time clock is connected to (default is the first bus opened).
+        """
+        ...
+
+    def get_time(self) -> datetime.datetime:
+        """
+        Reads the current time from the real time clock.
+
+        :return: The time stored in the clock (without timezone information).
+        """
+        ...
+
+    def set_time(self, time: Optional[datetime.datetime] = None) -> None:
+        """
+        Sets the time of the real time clock and starts the oscillator.
+
+        :param time: The time to set, the current system time is used if it is None.
+        """
+        ...
+
+    def lost_power(self) -> bool:
+        """
+        Checks if the clock stopped since the time was last set (for example because the backup battery is empty).
+
+        :return: True if the time stored in the clock is not valid.
+        """
+        ...
+
+    def set_alarm(self, alarm_num: int, second: Optional[int] = None, minute: Optional[int] = None,
+                  hour: Optional[int] = None, day: Optional[int] = None, weekday: Optional[int] = None) -> None:
+        """
+        Sets an alarm on the DS3231 and enables its interrupt on the INT/SQW pin. Fields that are None match any
+        value, once a field is set all smaller fields must be set as well (for example setting the hour requires the
+        minute).
+
+        :param alarm_num: The alarm to set (1 or 2, alarm 2 has no seconds).
+        :param second: The second the alarm triggers at (0 to 59).
+        :param minute: The minute the alarm triggers at (0 to 59).
+        :param hour: The hour the alarm triggers at (0 to 23).
+        :param day: The day of the month the alarm triggers at (1 to 31).
+        :param weekday: The day of the week the alarm triggers at (1 = Monday, 7 = Sunday), can't be used with day.
+        """
+        ...
+
+    def disable_alarm(self, alarm_num: int) -> None:
+        """
+        Disables the interrupt of an alarm on the DS3231.
+
+        :param alarm_num: The alarm to disable (1 or 2).
+        """
+        ...
+
+    def alarm_triggered(self, alarm_num: int) -> bool:
+        """
+        Checks if an alarm on the DS3231 has triggered.
+
+        :param alarm_num: The alarm to check (1 or 2).
+        :return: True if the alarm has triggered since its flag was last cleared.
+        """
+        ...
+
+    def clear_alarm(self, alarm_num: int) -> None:
+        """
+        Clears the triggered flag of an alarm on the DS3231, releasing the INT/SQW pin.
+
+        :param alarm_num: The alarm to clear (1 or 2).
+        """
+        ...
+
+    def set_square_wave(self, frequency_hz: Optional[int] = None) -> None:
+        """
+        Enables or disables the square wave output. On the DS3231 enabling the square wave disables the alarm
+        interrupts since both share the INT/SQW pin.
+
+        :param frequency_hz: The frequency of the square wave, None disables the output. The DS1307 supports 1, 4096,
+        8192 and 32768 Hz, the DS3231 supports 1, 1024, 4096 and 8192 Hz.
+        """
+        ...
+
+    def assign_alarm_callback(self, pin_num: int, callback: Callable[..., None], args: Optional[Tuple] = None,
+                              clear_alarms: Optional[bool] = True) -> None:
+        """
+        Wires the INT/SQW pin of the clock to a GPIO pin and assigns a callback that is invoked when an alarm
+        triggers. The pin is set up as an input with a pull-up since the INT/SQW output is open drain and active low.
+
+        :param pin_num: The GPIO pin connected to the INT/SQW pin.
+        :param callback: The callback function to be invoked when an alarm triggers.
+        :param args: The arguments to pass to the callback function.
+        :param clear_alarms: Whether to clear the alarm flags before invoking the callback so the pin is released for
+        the next alarm.
+        """
+        ...
@@ -0,0 +1,80 @@
+class ServoGroup:
+    """
+    ServoGroup moves several servos together, for robot arms and other mechanisms that need coordinated motion. A move
+    brings every servo to its target angle over the same duration, one Rust thread updates all the servos on the same
+    ticks so they start and arrive together, then calls the completion callback.
+    """
+
+    def __init__(self, pins: List[int], min_pulse_us: Union[float, List[float]] = 1000,
+                 max_pulse_us: Union[float, List[float]] = 2000, min_angle: float = 0, max_angle: float = 180,
+                 initial_angle: Optional[float] = None, frequency_hz: float = 50) -> None:
+        """
+        Sets up the servos of the group, each on a free pin through its hardware PWM channel when it has one. The
+        signals start right away at the initial angle.
+
+        :param pins: The GPIO pins of the servos, the angles of the moves are given in this order.
+        :param min_pulse_us: The pulse width at the minimum angle in microseconds, for all servos or for each.
+        :param max_pulse_us: The pulse width at the maximum angle in microseconds, for all servos or for each.
+        :param min_angle: The minimum angle in degrees.
+        :param max_angle: The maximum angle in degrees.
+        :param initial_angle: The angle the servos start at, None is the middle of the range.
+        :param frequency_hz: The frequency of the servo signals in Hertz.
+        """
+        ...
+
+    def move_to(self, angles: List[Optional[float]], duration_ms: float = 1000,
+                easing: Optional['Easing'] = Easing.LINEAR, callback: Optional[Callable[[], None]] = None) -> None:
+        """
+        Moves the servos to their target angles over the duration in the background, replacing the running move. All
+        the servos start and arrive together.
+
+        :param angles: The target angle of every servo in the order of the pins, None keeps a servo where it is.
+        :param duration_ms: The duration of the move in milliseconds.
+        :param easing: The curve the move follows.
+        :param callback: A function called once the servos reached their targets, it isn't called when the move is
+                         replaced or stopped.
+        """
+        ...
+
+    def set_angles(self, angles: List[Optional[float]]) -> None:
+        """
+        Sets the angles of the servos immediately, stopping the running move.
+
+        :param angles: The angle of every servo in the order of the pins, None keeps a servo where it is.
+        """
+        ...
+
+    def stop(self) -> None:
+        """Stops the running move, the servos hold the angles they reached."""
+        ...
+
+    def wait(self, timeout_ms: Optional[float] = None) -> bool:
+        """
+        Blocks until the running move completes. The GIL is released while waiting.
+
+        :param timeout_ms: The maximum time to wait in milliseconds, None waits without limit.
+        :return: Whether no move is running anymore, False when the timeout expired first.
+        """
+        ...
+
+    def close(self) -> None:
+        """Stops the group and releases the pins of its servos."""
+        ...
+
+    def get_angles(self) -> List[float]:
+        """
+        :return: The current angle of every servo in the order of the pins.
+        """
+        ...
+
+    def get_pins(self) -> List[int]:
+        """
+        :return: The GPIO pins of the servos.
+        """
+        ...
+
+    def is_moving(self) -> bool:
+        """
+        :return: Whether a move is running.
+        """
+        ...
@@ -0,0 +1,36 @@
+def read(pin_num: int) -> bool:
+    """
+    Reads a pin through the shared GPIOManager. A pin that isn't set up is added as an input with the default pull
+    resistor, and an output pin returns the state it is driven to.
+
+    :param pin_num: The GPIO pin.
+    :return: True when the pin is logic high.
+    """
+    ...
+
+
+def write(pin_num: int, pin_state: PinStateLike) -> None:
+    """
+    Drives a pin through the shared GPIOManager. A pin that isn't set up is added as a push-pull output starting in
+    the given state.
+
+    :param pin_num: The GPIO pin.
+    :param pin_state: The state to drive the pin to.
+    :raises ValueError: If the pin is set up as an input.
+    """
+    ...
+
+
+def on_edge(pin_num: int, callback: Callable[..., None], trigger_edge: TriggerEdgeLike = TriggerEdge.BOTH,
+            debounce_time_ms: Optional[float] = None, args: Optional[Tuple] = None) -> None:
+    """
+    Calls a function on the edges of a pin through the shared GPIOManager. A pin that isn't set up is added as an
+    input with the default pull resistor.
+
+    :param pin_num: The GPIO pin.
+    :param callback: The function to call, with the args.
+    :param trigger_edge: The edges to call the function on.
+    :param debounce_time_ms: The debounce time in milliseconds, None uses the default of set_defaults.
+    :param args: The arguments to pass to the function.
+    """
+    ...
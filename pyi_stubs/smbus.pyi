@@ -0,0 +1,250 @@
+class SMBus:
+    """
+    SMBus is a drop-in replacement for the SMBus class of smbus2, so existing sensor libraries can use the I2CManager
+    without code changes. The bus is shared with the I2CManager, so the retry policy and PEC setting apply to both. The
+    force arguments are accepted for compatibility but ignored.
+    """
+
+    pec: bool
+    """Whether SMBus packet error checking is enabled on the bus."""
+
+    def __init__(self, bus: Optional[Union[int, str]] = None, force: Optional[bool] = False) -> None:
+        """
+        Initializes a new SMBus instance, opening the bus if one is given.
+
+        :param bus: The I2C bus number or device path such as /dev/i2c-1.
+        :param force: Ignored, accepted for compatibility with smbus2.
+        """
+        ...
+
+    def __enter__(self) -> 'SMBus':
+        ...
+
+    def __exit__(self, *args) -> bool:
+        ...
+
+    def open(self, bus: Union[int, str]) -> None:
+        """
+        Opens an I2C bus, the bus is opened in the I2CManager if it isn't open already.
+
+        :param bus: The I2C bus number or device path such as /dev/i2c-1.
+        """
+        ...
+
+    def close(self) -> None:
+        """
+        Detaches from the bus. The bus stays open in the I2CManager, since it may be used by other objects.
+        """
+        ...
+
+    def enable_pec(self, enable: Optional[bool] = True) -> None:
+        """
+        Enables or disables SMBus packet error checking.
+
+        :param enable: Whether packet error checking is used.
+        """
+        ...
+
+    def write_quick(self, i2c_addr: int, force: Optional[bool] = None) -> None:
+        """
+        Sends a quick command with the write bit set.
+
+        :param i2c_addr: The I2C slave address.
+        :param force: Ignored.
+        """
+        ...
+
+    def read_byte(self, i2c_addr: int, force: Optional[bool] = None) -> int:
+        """
+        Reads a single byte from a device.
+
+        :param i2c_addr: The I2C slave address.
+        :param force: Ignored.
+        :return: The byte read.
+        """
+        ...
+
+    def write_byte(self, i2c_addr: int, value: int, force: Optional[bool] = None) -> None:
+        """
+        Writes a single byte to a device.
+
+        :param i2c_addr: The I2C slave address.
+        :param value: The byte to write.
+        :param force: Ignored.
+        """
+        ...
+
+    def read_byte_data(self, i2c_addr: int, register: int, force: Optional[bool] = None) -> int:
+        """
+        Reads a single byte from a register.
+
+        :param i2c_addr: The I2C slave address.
+        :param register: The register to read.
+        :param force: Ignored.
+        :return: The byte read.
+        """
+        ...
+
+    def write_byte_data(self, i2c_addr: int, register: int, value: int, force: Optional[bool] = None) -> None:
+        """
+        Writes a single byte to a register.
+
+        :param i2c_addr: The I2C slave address.
+        :param register: The register to write.
+        :param value: The byte to write.
+        :param force: Ignored.
+        """
+        ...
+
+    def read_word_data(self, i2c_addr: int, register: int, force: Optional[bool] = None) -> int:
+        """
+        Reads a 16 bit word from a register, the low byte is received first.
+
+        :param i2c_addr: The I2C slave address.
+        :param register: The register to read.
+        :param force: Ignored.
+        :return: The word read.
+        """
+        ...
+
+    def write_word_data(self, i2c_addr: int, register: int, value: int, force: Optional[bool] = None) -> None:
+        """
+        Writes a 16 bit word to a register, the low byte is sent first.
+
+        :param i2c_addr: The I2C slave address.
+        :param register: The register to write.
+        :param value: The word to write.
+        :param force: Ignored.
+        """
+        ...
+
+    def process_call(self, i2c_addr: int, register: int, value: int, force: Optional[bool] = None) -> int:
+        """
+        Sends a 16 bit word to a register and reads the word the device responds with.
+
+        :param i2c_addr: The I2C slave address.
+        :param register: The register to send the word to.
+        :param value: The word to send.
+        :param force: Ignored.
+        :return: The word received.
+        """
+        ...
+
+    def read_block_data(self, i2c_addr: int, register: int, force: Optional[bool] = None) -> List[int]:
+        """
+        Reads an SMBus block of up to 32 bytes from a register, the device sends the length of the block first.
+
+        :param i2c_addr: The I2C slave address.
+        :param register: The register to read.
+        :param force: Ignored.
+        :return: The block read.
+        """
+        ...
+
+    def write_block_data(self, i2c_addr: int, register: int, data: Union[List[int], bytes],
+                         force: Optional[bool] = None) -> None:
+        """
+        Writes an SMBus block of up to 32 bytes to a register, the length of the block is sent first.
+
+        :param i2c_addr: The I2C slave address.
+        :param register: The register to write.
+        :param data: The block to write.
+        :param force: Ignored.
+        """
+        ...
+
+    def block_process_call(self, i2c_addr: int, register: int, data: Union[List[int], bytes],
+                           force: Optional[bool] = None) -> List[int]:
+        """
+        Sends an SMBus block to a register and reads the block the device responds with.
+
+        :param i2c_addr: The I2C slave address.
+        :param register: The register to send the block to.
+        :param data: The block to send.
+        :param force: Ignored.
+        :return: The block received.
+        """
+        ...
+
+    def read_i2c_block_data(self, i2c_addr: int, register: int, length: int, force: Optional[bool] = None) -> List[int]:
+        """
+        Reads a block of up to 32 bytes starting at a register, without a length byte.
+
+        :param i2c_addr: The I2C slave address.
+        :param register: The first register to read.
+        :param length: The number of bytes to read.
+        :param force: Ignored.
+        :return: The block read.
+        """
+        ...
+
+    def write_i2c_block_data(self, i2c_addr: int, register: int, data: Union[List[int], bytes],
+                             force: Optional[bool] = None) -> None:
+        """
+        Writes a block of up to 32 bytes starting at a register, without a length byte.
+
+        :param i2c_addr: The I2C slave address.
+        :param register: The first register to write.
+        :param data: The block to write.
+        :param force: Ignored.
+        """
+        ...
+
+    def i2c_rdwr(self, *i2c_msgs: 'i2c_msg') -> None:
+        """
+        Performs the messages as a single combined transaction, with a repeated start between messages and a single stop
+        at the end. The read messages are filled with the data received.
+
+        :param i2c_msgs: The messages of the transaction, up to 42 messages can be used.
+        """
+        ...
+
+
+class i2c_msg:
+    """
+    i2c_msg is a message of a combined transaction performed by SMBus.i2c_rdwr, matching the i2c_msg class of smbus2.
+    Messages are created with i2c_msg.read and i2c_msg.write, iterating a message yields its bytes.
+    """
+
+    addr: int
+    """The I2C slave address of the message."""
+
+    flags: int
+    """The flags of the message, 1 (I2C_M_RD) for a read."""
+
+    len: int
+    """The number of bytes of the message."""
+
+    buf: bytes
+    """The bytes of the message, the data received for a read performed already."""
+
+    @staticmethod
+    def read(address: int, length: int) -> 'i2c_msg':
+        """
+        Creates a message reading bytes from a device.
+
+        :param address: The I2C slave address.
+        :param length: The number of bytes to read.
+        :return: The message.
+        """
+        ...
+
+    @staticmethod
+    def write(address: int, buf: Union[List[int], bytes, str]) -> 'i2c_msg':
+        """
+        Creates a message writing bytes to a device.
+
+        :param address: The I2C slave address.
+        :param buf: The bytes to write.
+        :return: The message.
+        """
+        ...
+
+    def __len__(self) -> int:
+        ...
+
+    def __iter__(self) -> Iterator[int]:
+        ...
+
+    def __bytes__(self) -> bytes:
+        ...
@@ -0,0 +1,189 @@
+class SoftI2C:
+    """
+    SoftI2C is an I2C master bit-banged on any two GPIO pins, with the API of the I2CManager for a single bus. The lines
+    are driven open drain and the devices may stretch the clock. Only 7-bit addresses are supported.
+    """
+
+    def __init__(self, scl_pin: int, sda_pin: int, frequency_hz: Optional[float] = 100000,
+                 stretch_timeout_ms: Optional[float] = 25, pull_up: Optional[bool] = True) -> None:
+        """
+        Takes two free pins as the SCL and SDA lines of a software I2C bus and releases them to the idle level. The bus
+        needs pull-up resistors, the internal pull-ups are only enough for short wires at low speeds.
+
+        :param scl_pin: The GPIO pin of the clock line.
+        :param sda_pin: The GPIO pin of the data line.
+        :param frequency_hz: The clock frequency, the time the pins take to switch makes the actual clock slower.
+        :param stretch_timeout_ms: How long a device may hold SCL low to stretch the clock before the transfer fails.
+        :param pull_up: Enables the internal pull-ups of the pins, with the rppal backend.
+        """
+        ...
+
+    def close(self) -> None:
+        """
+        Releases the pins of the bus.
+        """
+        ...
+
+    def get_pins(self) -> Tuple[int, int]:
+        """
+        Gets the SCL and SDA pins.
+
+        :return: The SCL and SDA pins.
+        """
+        ...
+
+    def write_byte(self, addr: int, data: int) -> None:
+        """
+        Writes a single byte to the I2C slave device.
+
+        :param addr: The I2C slave address.
+        :param data: The byte to write.
+        """
+        ...
+
+    def block_write_byte(self, addr: int, command: int, data: int) -> None:
+        """
+        Writes a single byte to a register of the I2C slave device.
+
+        :param addr: The I2C slave address.
+        :param command: The register to write.
+        :param data: The byte to write.
+        """
+        ...
+
+    def read_byte(self, addr: int) -> int:
+        """
+        Reads a single byte from the I2C slave device.
+
+        :param addr: The I2C slave address.
+        :return: The byte read.
+        """
+        ...
+
+    def block_read_byte(self, addr: int, command: int) -> int:
+        """
+        Reads a single byte from a register of the I2C slave device.
+
+        :param addr: The I2C slave address.
+        :param command: The register to read.
+        :return: The byte read.
+        """
+        ...
+
+    def write(self, addr: int, data: Union[bytes, bytearray, memoryview, List[int]]) -> None:
+        """
+        Writes data to the I2C slave device.
+
+        :param addr: The I2C slave address.
+        :param data: The data to write.
+        """
+        ...
+
+    def block_write(self, addr: int, command: int, data: Union[bytes, bytearray, memoryview, List[int]]) -> None:
+        """
+        Writes data to a register of the I2C slave device.
+
+        :param addr: The I2C slave address.
+        :param command: The register to write.
+        :param data: The data to write.
+        """
+        ...
+
+    def read(self, addr: int, length: int) -> bytes:
+        """
+        Reads data from the I2C slave device.
+
+        :param addr: The I2C slave address.
+        :param length: The number of bytes to read.
+        :return: The data read.
+        """
+        ...
+
+    def block_read(self, addr: int, command: int, length: int) -> bytes:
+        """
+        Reads data from a register of the I2C slave device.
+
+        :param addr: The I2C slave address.
+        :param command: The register to read.
+        :param length: The number of bytes to read.
+        :return: The data read.
+        """
+        ...
+
+    def write_read(self, addr: int, write_data: Union[bytes, bytearray, memoryview, List[int]], read_length: int) -> bytes:
+        """
+        Performs a write followed by a read with a repeated start between them.
+
+        :param addr: The I2C slave address.
+        :param write_data: The data to write.
+        :param read_length: The number of bytes to read.
+        :return: The data read.
+        """
+        ...
+
+    def read_word(self, addr: int, command: int, big_endian: Optional[bool] = False) -> int:
+        """
+        Reads a 16 bit word from a register of the I2C slave device.
+
+        :param addr: The I2C slave address.
+        :param command: The register to read.
+        :param big_endian: Treat the high byte as the first byte on the bus, SMBus devices send the low byte first.
+        :return: The word read.
+        """
+        ...
+
+    def write_word(self, addr: int, command: int, value: int, big_endian: Optional[bool] = False) -> None:
+        """
+        Writes a 16 bit word to a register of the I2C slave device.
+
+        :param addr: The I2C slave address.
+        :param command: The register to write.
+        :param value: The word to write.
+        :param big_endian: Send the high byte first, SMBus devices expect the low byte first.
+        """
+        ...
+
+    def quick_command(self, addr: int, read: Optional[bool] = False) -> None:
+        """
+        Sends a quick command, which only sends the address with a single bit in place of the read/write bit.
+
+        :param addr: The I2C slave address.
+        :param read: The value of the read/write bit.
+        """
+        ...
+
+    def process_call(self, addr: int, command: int, value: int, big_endian: Optional[bool] = False) -> int:
+        """
+        Sends a 16 bit word to a register and reads the 16 bit word the device responds with.
+
+        :param addr: The I2C slave address.
+        :param command: The register to send the word to.
+        :param value: The word to send.
+        :param big_endian: Transfer the high byte of both words first, SMBus devices use the low byte first.
+        :return: The word received.
+        """
+        ...
+
+    def transaction(self, addr: int, segments: List[Union[I2CWrite, I2CRead]]) -> List[bytes]:
+        """
+        Performs a combined transaction, the segments are sent with a repeated start between them and a single stop at
+        the end.
+
+        :param addr: The I2C slave address.
+        :param segments: The segments of the transaction.
+        :return: The data read by every read segment, in order.
+        """
+        ...
+
+    def scan(self, start: Optional[int] = 0x03, end: Optional[int] = 0x77,
+             probe_mode: Optional[I2CProbeMode] = I2CProbeMode.AUTO) -> List[int]:
+        """
+        Scans the bus for devices by probing every address in the given range.
+
+        :param start: The first address to probe.
+        :param end: The last address to probe, up to 0x7F.
+        :param probe_mode: How each address is probed (set it by using gpio_manager.I2CProbeMode.[AUTO, QUICK_WRITE or READ]).
+        AUTO uses a read for the 0x30 - 0x37 and 0x50 - 0x5F ranges and a quick write everywhere else, like i2cdetect.
+        :return: The addresses of the devices that responded.
+        """
+        ...
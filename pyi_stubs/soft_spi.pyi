@@ -0,0 +1,139 @@
+class SPIMode:
+    """Enum representing the clock polarity (CPOL) and phase (CPHA) of an SPI bus."""
+    MODE0: 'SPIMode'
+    """
+    The clock idles low and data is sampled on the rising edge (Default).
+    """
+    MODE1: 'SPIMode'
+    """
+    The clock idles low and data is sampled on the falling edge.
+    """
+    MODE2: 'SPIMode'
+    """
+    The clock idles high and data is sampled on the falling edge.
+    """
+    MODE3: 'SPIMode'
+    """
+    The clock idles high and data is sampled on the rising edge.
+    """
+
+
+class SoftSPI:
+    """
+    SoftSPI is an SPI master bit-banged on any GPIO pins, for when the pins of the hardware SPI buses are taken or a bus
+    is needed on other pins. The clock is generated in Rust with the GIL released and reaches a few hundred kHz.
+    """
+
+    def __init__(self, sclk_pin: int, mosi_pin: Optional[int] = None, miso_pin: Optional[int] = None,
+                 cs_pin: Optional[int] = None, mode: Optional[SPIMode] = SPIMode.MODE0,
+                 frequency_hz: Optional[float] = 500000, lsb_first: Optional[bool] = False,
+                 cs_active_high: Optional[bool] = False) -> None:
+        """
+        Takes the pins of a software SPI bus. MOSI and MISO can be left out for devices that are only written to or only
+        read from, and the chip select for a single device that is always selected.
+
+        :param sclk_pin: The GPIO pin of the clock line.
+        :param mosi_pin: The GPIO pin data is sent on.
+        :param miso_pin: The GPIO pin data is received on.
+        :param cs_pin: The GPIO pin of the chip select.
+        :param mode: The clock polarity and phase (set it by using gpio_manager.SPIMode.[MODE0, MODE1, MODE2 or MODE3]).
+        :param frequency_hz: The clock frequency, a transfer runs slower when the pins can't be switched that fast.
+        :param lsb_first: Send and receive the least significant bit of each byte first.
+        :param cs_active_high: Select the device by driving the chip select high instead of low.
+        """
+        ...
+
+    def close(self) -> None:
+        """
+        Releases the pins of the bus, including the chip selects of its devices.
+        """
+        ...
+
+    def device(self, cs_pin: int, cs_active_high: Optional[bool] = False, setup_us: Optional[float] = None,
+               hold_us: Optional[float] = None) -> SPIDevice:
+        """
+        Creates a handle for a device on the bus with its own chip select, which is selected around every transfer made
+        through the handle. Any number of devices can share the bus, each on a free pin.
+
+        :param cs_pin: The GPIO pin of the chip select of the device.
+        :param cs_active_high: Select the device by driving the chip select high instead of low.
+        :param setup_us: The delay from selecting the device to the first clock edge in microseconds, half a clock
+        period when None.
+        :param hold_us: The delay from the last clock edge to deselecting the device in microseconds, half a clock
+        period when None.
+        :return: The device handle.
+        """
+        ...
+
+    def get_pins(self) -> Tuple[int, Optional[int], Optional[int], Optional[int]]:
+        """
+        Gets the SCLK, MOSI, MISO and CS pins, None for the lines that aren't used.
+        """
+        ...
+
+    def set_mode(self, mode: SPIMode) -> None:
+        """
+        Sets the clock polarity and phase, the clock is moved to its new idle level.
+
+        :param mode: The clock polarity and phase.
+        """
+        ...
+
+    def get_mode(self) -> SPIMode:
+        """
+        Gets the clock polarity and phase.
+        """
+        ...
+
+    def set_frequency(self, frequency_hz: float) -> None:
+        """
+        Sets the clock frequency.
+
+        :param frequency_hz: The clock frequency.
+        """
+        ...
+
+    def get_frequency(self) -> float:
+        """
+        Gets the clock frequency.
+        """
+        ...
+
+    def transfer(self, data: Union[bytes, bytearray, memoryview, List[int]]) -> bytes:
+        """
+        Sends the data while receiving as many bytes, with the device selected for the whole transfer.
+
+        :param data: The data to send.
+        :return: The data received, as long as the data sent. Without a MISO pin every byte is 0.
+        """
+        ...
+
+    def write(self, data: Union[bytes, bytearray, memoryview, List[int]]) -> None:
+        """
+        Sends data to the device, discarding the bytes received.
+
+        :param data: The data to send.
+        """
+        ...
+
+    def read(self, length: int, fill: Optional[int] = 0) -> bytes:
+        """
+        Reads data from the device, sending the fill byte for every byte read.
+
+        :param length: The number of bytes to read.
+        :param fill: The byte sent while reading.
+        :return: The data read.
+        """
+        ...
+
+    def write_read(self, write_data: Union[bytes, bytearray, memoryview, List[int]], read_length: int,
+                   fill: Optional[int] = 0) -> bytes:
+        """
+        Sends data and then reads the response, with the device selected for both.
+
+        :param write_data: The data to send.
+        :param read_length: The number of bytes to read after it.
+        :param fill: The byte sent while reading.
+        :return: The data read after the data was sent.
+        """
+        ...
@@ -0,0 +1,166 @@
+class SoftUART:
+    """
+    SoftUART is a serial port bit-banged on any GPIO pins, for a second serial device when the hardware UART is used by
+    the console. Frames are 8N1. The TX pin is driven with the timing done in Rust, and the RX pin is decoded from the
+    timestamps of its edges, so bytes are received in the background, up to 4096 bytes are kept until they are read.
+    """
+
+    def __init__(self, tx_pin: Optional[int] = None, rx_pin: Optional[int] = None, baud_rate: Optional[int] = 9600,
+                 timeout_ms: Optional[float] = None) -> None:
+        """
+        Takes the pins of a software serial port, either one can be left out for a port that only sends or only
+        receives. The TX pin idles high and the RX pin is pulled up.
+
+        :param tx_pin: The GPIO pin data is sent on.
+        :param rx_pin: The GPIO pin data is received on.
+        :param baud_rate: The baud rate, from 300 to 38400.
+        :param timeout_ms: How long reads wait for data, and writes wait for CTS with flow control, in milliseconds.
+        None waits forever.
+        """
+        ...
+
+    def close(self) -> None:
+        """
+        Stops receiving and releases the pins. Reads waiting for data return what was received, and the data not read
+        yet can still be read.
+        """
+        ...
+
+    def set_timeout(self, timeout_ms: Optional[float]) -> None:
+        """
+        Sets how long reads wait for data, and writes wait for CTS with flow control.
+
+        :param timeout_ms: The timeout in milliseconds, 0 returns right away with the data already received and None
+        waits forever.
+        """
+        ...
+
+    def get_timeout(self) -> Optional[float]:
+        """
+        Gets the timeout in milliseconds, None when reads wait forever.
+        """
+        ...
+
+    def enable_flow_control(self, rts_pin: Optional[int] = None, cts_pin: Optional[int] = None) -> None:
+        """
+        Enables RTS/CTS hardware flow control on the given pins, either one can be left out. RTS is asserted (low) while
+        the receive buffer has room, and released once it is three quarters full until it is read down to a quarter.
+        Writes wait for CTS to be asserted (low) by the other device before every byte. CTS is pulled up, so nothing is
+        sent while it isn't connected.
+
+        :param rts_pin: The GPIO pin of the RTS output, wired to the CTS input of the other device.
+        :param cts_pin: The GPIO pin of the CTS input, wired to the RTS output of the other device.
+        """
+        ...
+
+    def disable_flow_control(self) -> None:
+        """
+        Disables flow control and releases its pins.
+        """
+        ...
+
+    def get_flow_control_pins(self) -> Tuple[Optional[int], Optional[int]]:
+        """
+        Gets the RTS and CTS pins of flow control, None for the pin that isn't used.
+        """
+        ...
+
+    def bytes_available(self) -> int:
+        """
+        Gets the number of bytes received and not read yet.
+        """
+        ...
+
+    def enable_rs485(self, de_pin: int, de_active_high: Optional[bool] = True, pre_delay_us: Optional[float] = 0,
+                     post_delay_us: Optional[float] = 0) -> None:
+        """
+        Switches the port to RS-485 mode, where the driver enable pin of the transceiver is asserted before the first
+        start bit of every write and released right after its last stop bit. The timing is done in Rust, so the bus is
+        free for the reply of the other device within microseconds. The DE and RE pins of most transceivers are tied
+        together, which also disables the receiver while sending so the port doesn't receive its own data.
+
+        :param de_pin: The GPIO pin of the driver enable of the transceiver.
+        :param de_active_high: Whether the pin is driven high to enable the driver.
+        :param pre_delay_us: The delay from asserting the pin to the first start bit in microseconds.
+        :param post_delay_us: The delay from the end of the last stop bit to releasing the pin in microseconds.
+        """
+        ...
+
+    def disable_rs485(self) -> None:
+        """
+        Leaves RS-485 mode and releases the driver enable pin.
+        """
+        ...
+
+    def get_rs485_pin(self) -> Optional[int]:
+        """
+        Gets the driver enable pin of RS-485 mode, None when the port isn't in RS-485 mode.
+        """
+        ...
+
+    def get_pins(self) -> Tuple[Optional[int], Optional[int]]:
+        """
+        Gets the TX and RX pins, None for the pin that isn't used.
+        """
+        ...
+
+    def get_baud_rate(self) -> int:
+        """
+        Gets the baud rate.
+        """
+        ...
+
+    def get_framing_errors(self) -> int:
+        """
+        Gets the number of frames received with a low stop bit, which were dropped. They are caused by a wrong baud
+        rate, noise on the line or a break.
+        """
+        ...
+
+    def write(self, data: Union[bytes, bytearray, memoryview, List[int]]) -> None:
+        """
+        Sends the data on the TX pin, returning once the stop bit of the last byte is sent. In RS-485 mode the driver
+        enable pin is asserted for the time of the write. With flow control every byte waits for CTS, and a TimeoutError
+        is raised when it isn't asserted within the timeout. The GIL is released while sending.
+
+        :param data: The data to send.
+        """
+        ...
+
+    def read(self, length: Optional[int] = 1) -> bytes:
+        """
+        Reads bytes received on the RX pin, waiting until the given number of bytes has arrived or the timeout passes.
+        The GIL is released while waiting.
+
+        :param length: The number of bytes to read.
+        :return: The bytes read, fewer than requested when the timeout passed or the port was closed while waiting.
+        """
+        ...
+
+    def read_line(self, terminator: Optional[bytes] = b'\n', max_length: Optional[int] = None) -> bytes:
+        """
+        Reads a line, up to and including the terminator, for line oriented devices such as GPS receivers and modems.
+
+        :param terminator: The end of a line.
+        :param max_length: The longest line read, the bytes are returned without a terminator when it is reached.
+        :return: The line with its terminator, or the bytes received without one when the timeout passed or the port
+        was closed first.
+        """
+        ...
+
+    def read_until(self, byte: int, max_length: Optional[int] = None) -> bytes:
+        """
+        Reads up to and including the first occurrence of a byte.
+
+        :param byte: The byte to read up to.
+        :param max_length: The most bytes read, the bytes are returned without the byte when it is reached.
+        :return: The bytes up to and including the byte, or the bytes received without it when the timeout passed or
+        the port was closed first.
+        """
+        ...
+
+    def flush_input(self) -> None:
+        """
+        Discards the bytes received but not read yet.
+        """
+        ...
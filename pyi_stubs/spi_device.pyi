@@ -0,0 +1,56 @@
+class SPIDevice:
+    """
+    SPIDevice provides methods to communicate with a single device of a SoftSPI bus, created with SoftSPI.device. The
+    chip select of the device is selected around every transfer, so devices on different chip selects can share the bus.
+    """
+
+    def close(self) -> None:
+        """
+        Releases the chip select of the device, the bus stays open for its other devices.
+        """
+        ...
+
+    def get_cs_pin(self) -> int:
+        """
+        Gets the chip select pin of the device.
+        """
+        ...
+
+    def transfer(self, data: Union[bytes, bytearray, memoryview, List[int]]) -> bytes:
+        """
+        Sends the data while receiving as many bytes, with the device selected for the whole transfer.
+
+        :param data: The data to send.
+        :return: The data received, as long as the data sent.
+        """
+        ...
+
+    def write(self, data: Union[bytes, bytearray, memoryview, List[int]]) -> None:
+        """
+        Sends data to the device, discarding the bytes received.
+
+        :param data: The data to send.
+        """
+        ...
+
+    def read(self, length: int, fill: Optional[int] = 0) -> bytes:
+        """
+        Reads data from the device, sending the fill byte for every byte read.
+
+        :param length: The number of bytes to read.
+        :param fill: The byte sent while reading.
+        :return: The data read.
+        """
+        ...
+
+    def write_read(self, write_data: Union[bytes, bytearray, memoryview, List[int]], read_length: int,
+                   fill: Optional[int] = 0) -> bytes:
+        """
+        Sends data and then reads the response, with the device selected for both.
+
+        :param write_data: The data to send.
+        :param read_length: The number of bytes to read after it.
+        :param fill: The byte sent while reading.
+        :return: The data read after the data was sent.
+        """
+        ...
@@ -0,0 +1,71 @@
+class TouchButton:
+    """
+    TouchButton turns a pin wired to a bare electrode, such as a piece of foil, into a touch input. A Rust thread
+    measures the time the electrode takes to charge through the pull-up resistor every poll interval, a finger adds
+    capacitance and makes the charge time longer than the threshold.
+    """
+
+    def __init__(self, pin: int, threshold_us: Optional[float] = None, samples: int = 10, poll_ms: float = 20,
+                 on_touch: Optional[Callable[[], None]] = None, on_release: Optional[Callable[[], None]] = None,
+                 pull_resistor_state: Optional[InternPullResistorStateLike] = InternPullResistorState.PULLUP,
+                 timeout_us: float = 10000) -> None:
+        """
+        Sets up a free pin as a touch button and starts polling it. Without a threshold the button is calibrated at
+        rest, so it must not be touched while it is created.
+
+        :param pin: The GPIO pin wired to the electrode (0 to 27).
+        :param threshold_us: The charge time in microseconds above which the button reads as touched, None calibrates
+                             it at 1.3 times the charge time at rest.
+        :param samples: The number of charge times averaged for every reading.
+        :param poll_ms: The time between two readings in milliseconds.
+        :param on_touch: A function called when the button is touched.
+        :param on_release: A function called when the button is released.
+        :param pull_resistor_state: PULLUP charges the electrode through the internal pull-up, EXTERNAL through a
+                                    resistor to 3.3V.
+        :param timeout_us: The longest charge time measured in microseconds.
+        """
+        ...
+
+    def calibrate(self) -> float:
+        """
+        Measures the charge time at rest again and sets the threshold from it, the button must not be touched.
+
+        :return: The new threshold in microseconds.
+        """
+        ...
+
+    def set_threshold(self, threshold_us: float) -> None:
+        """
+        Sets the charge time above which the button reads as touched.
+
+        :param threshold_us: The threshold in microseconds.
+        """
+        ...
+
+    def get_threshold(self) -> float:
+        """
+        :return: The threshold in microseconds.
+        """
+        ...
+
+    def read(self) -> Optional[float]:
+        """
+        :return: The last charge time measured in microseconds, or None before the first reading.
+        """
+        ...
+
+    def is_touched(self) -> bool:
+        """
+        :return: Whether the button is touched.
+        """
+        ...
+
+    def close(self) -> None:
+        """Stops polling the button and releases the pin."""
+        ...
+
+    def get_pin(self) -> int:
+        """
+        :return: The GPIO pin.
+        """
+        ...
@@ -0,0 +1,103 @@
+class WaveformManager:
+    """
+    WaveformManager composes precise multi-pin waveforms in the style of pigpio and plays them by DMA, so the timing
+    doesn't depend on the CPU load. A waveform is a list of pulses (on_mask, off_mask, delay_us): the pins set in
+    on_mask are driven high, the pins set in off_mask low, then the next pulse follows after the delay. Masks address
+    GPIO 0 to 31 and drive physical levels, delays have a resolution of 0.2 µs.
+
+    DMA playback needs the BCM2835 to BCM2711 and root access, the RP1 of the Raspberry Pi 5 can't drive its pins by
+    DMA. The delays are paced by the PWM peripheral, which can't be used for hardware PWM or analog audio at the same
+    time. On the mock backend the waveforms are simulated by a thread.
+
+    Waveforms can be given names and chained with loops, for example to play stepper motor acceleration profiles.
+    """
+
+    def __init__(self) -> None:
+        """Initializes a new WaveformManager instance, all instances share the same waveforms."""
+        ...
+
+    def wave_add_generic(self, pulses: List[Tuple[int, int, float]]) -> int:
+        """
+        Appends pulses to the waveform being composed.
+
+        :param pulses: The pulses as (on_mask, off_mask, delay_us), delays are rounded to 0.2 µs.
+        :return: The number of pulses in the waveform being composed.
+        """
+        ...
+
+    def wave_create(self, name: Optional[str] = None) -> int:
+        """
+        Creates a waveform from the pulses added since the last call and clears them.
+
+        :param name: A name the waveform can be referenced by in place of its id.
+        :return: The id of the waveform.
+        """
+        ...
+
+    def wave_delete(self, wave: Union[int, str]) -> None:
+        """
+        Deletes a waveform, stopping the transmission if it uses the waveform.
+
+        :param wave: The id or the name of the waveform.
+        """
+        ...
+
+    def wave_clear(self) -> None:
+        """Stops any transmission and deletes all waveforms and pending pulses."""
+        ...
+
+    def wave_send_once(self, wave: Union[int, str]) -> None:
+        """
+        Transmits a waveform once, replacing the current transmission. Pins in the masks that aren't in use are set up
+        as outputs.
+
+        :param wave: The id or the name of the waveform.
+        """
+        ...
+
+    def wave_send_repeat(self, wave: Union[int, str]) -> None:
+        """
+        Transmits a waveform repeatedly until it is stopped, replacing the current transmission.
+
+        :param wave: The id or the name of the waveform.
+        """
+        ...
+
+    def wave_chain(self, chain: List[Union[int, str, Tuple[list, Optional[int]]]], loops: Optional[int] = 1) -> None:
+        """
+        Transmits waveforms back to back without gaps, replacing the current transmission. Elements of the chain are
+        waveforms or (chain, loops) tuples, which play the nested chain loops times or forever for None.
+
+        :param chain: The waveforms, by id or name, and loops to play.
+        :param loops: The number of times the whole chain is played, None plays it until it is stopped.
+        """
+        ...
+
+    def wave_tx_busy(self) -> bool:
+        """
+        :return: Whether a waveform or chain is being transmitted.
+        """
+        ...
+
+    def wave_tx_at(self) -> Optional[int]:
+        """
+        :return: The id of the waveform being transmitted, within a chain the waveform currently playing, or None.
+        """
+        ...
+
+    def wave_tx_name(self) -> Optional[str]:
+        """
+        :return: The name of the waveform being transmitted, or None if it has no name or nothing is transmitted.
+        """
+        ...
+
+    def wave_tx_stop(self) -> None:
+        """Stops the transmission, the pins keep their last level."""
+        ...
+
+    def wave_get_micros(self, wave: Union[int, str]) -> float:
+        """
+        :param wave: The id or the name of the waveform.
+        :return: The length of the waveform in microseconds.
+        """
+        ...
@@ -0,0 +1,7 @@
+"""
+Rust bindings for the rppal crate to manage GPIO pins on a Raspberry Pi.
+
+The classes and functions live in the native module gpio_manager._gpio_manager, this package re-exports them along with
+its submodules cli, compat, gpiozero and mock.
+"""
+from gpio_manager._gpio_manager import *  # noqa: F401,F403
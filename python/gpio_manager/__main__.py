@@ -0,0 +1,6 @@
+"""Runs the command line interface with ``python -m gpio_manager``, like the ``gpio_manager`` command."""
+import sys
+
+from gpio_manager.cli import main
+
+sys.exit(main())
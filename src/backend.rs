@@ -0,0 +1,323 @@
+use crate::error::GpioError;
+use crate::gpiochip_backend::GpiochipBackend;
+use crate::log_module;
+use crate::mock_backend::MockBackend;
+use crate::rppal_backend::RppalBackend;
+use once_cell::sync::Lazy;
+use rppal::gpio::{Bias, Event, Level, Trigger};
+use rppal::pwm::Polarity;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+
+/// An error of a backend, holding the description of the error it came from.
+pub struct Error(String);
+
+
+impl Error {
+    pub fn new(message: impl Into<String>) -> Self {
+        Error(message.into())
+    }
+}
+
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+
+impl From<rppal::gpio::Error> for Error {
+    fn from(e: rppal::gpio::Error) -> Self {
+        Error(format!("{:?}", e))
+    }
+}
+
+
+impl From<rppal::pwm::Error> for Error {
+    fn from(e: rppal::pwm::Error) -> Self {
+        Error(format!("{:?}", e))
+    }
+}
+
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error(e.to_string())
+    }
+}
+
+
+#[cfg(feature = "python")]
+impl From<Error> for pyo3::PyErr {
+    fn from(e: Error) -> Self {
+        pyo3::PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.0)
+    }
+}
+
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The callback of an asynchronous interrupt, called from a thread of the backend.
+pub type InterruptCallback = Box<dyn FnMut(Event) + Send>;
+
+
+/// A way of driving the pins, selected with set_backend.
+pub(crate) trait Backend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Takes a pin as an input with the given pull resistor.
+    fn input(&self, pin_num: u8, bias: Bias) -> Result<Box<dyn InputLine>>;
+
+    /// Takes a pin as an output driven to the given level.
+    fn output(&self, pin_num: u8, level: Level) -> Result<Box<dyn OutputLine>>;
+
+    /// Takes a hardware PWM channel, disabled unless enabled is set.
+    fn pwm(&self, channel_num: u8, frequency: f64, duty_cycle: f64, polarity: Polarity, enabled: bool) -> Result<Box<dyn PwmLine>>;
+
+    /// Reads the levels of every pin at once, bit N is the level of GPIO N.
+    fn read_levels(&self) -> Result<u32>;
+
+    /// Describes what the kernel or another process uses a pin for, None when the pin is free or it can't be told.
+    fn claimed_by(&self, _pin_num: u8) -> Option<String> {
+        None
+    }
+}
+
+
+/// An input pin of a backend, the methods follow the input pins of rppal.
+pub(crate) trait InputLine: Send {
+    fn read(&self) -> Level;
+    fn set_bias(&mut self, bias: Bias) -> Result<()>;
+    fn set_interrupt(&mut self, trigger: Trigger, debounce: Option<Duration>) -> Result<()>;
+    fn clear_interrupt(&mut self) -> Result<()>;
+    fn poll_interrupt(&mut self, reset: bool, timeout: Option<Duration>) -> Result<Option<Event>>;
+    fn set_async_interrupt(&mut self, trigger: Trigger, debounce: Option<Duration>, callback: InterruptCallback) -> Result<()>;
+    fn clear_async_interrupt(&mut self) -> Result<()>;
+    fn set_reset_on_drop(&mut self, reset_on_drop: bool);
+}
+
+
+/// An output pin of a backend, the methods follow the output pins of rppal.
+pub(crate) trait OutputLine: Send {
+    fn write(&mut self, level: Level);
+    fn is_set_high(&self) -> bool;
+    /// Starts software PWM on the pin, the duty cycle is a fraction.
+    fn set_pwm_frequency(&mut self, frequency: f64, duty_cycle: f64) -> Result<()>;
+    fn clear_pwm(&mut self) -> Result<()>;
+    /// Switches the output driver of the pin on or off, the pin floats while it is off.
+    fn set_output_enabled(&mut self, enabled: bool) -> Result<()>;
+    fn set_reset_on_drop(&mut self, reset_on_drop: bool);
+}
+
+
+/// A hardware PWM channel of a backend, the methods follow the PWM channels of rppal.
+pub(crate) trait PwmLine: Send {
+    fn period(&self) -> Result<Duration>;
+    fn set_period(&self, period: Duration) -> Result<()>;
+    fn pulse_width(&self) -> Result<Duration>;
+    fn set_pulse_width(&self, pulse_width: Duration) -> Result<()>;
+    fn frequency(&self) -> Result<f64>;
+    fn set_frequency(&self, frequency: f64, duty_cycle: f64) -> Result<()>;
+    fn duty_cycle(&self) -> Result<f64>;
+    fn set_duty_cycle(&self, duty_cycle: f64) -> Result<()>;
+    fn set_polarity(&self, polarity: Polarity) -> Result<()>;
+    fn is_enabled(&self) -> Result<bool>;
+    fn enable(&self) -> Result<()>;
+    fn disable(&self) -> Result<()>;
+}
+
+
+/// An input pin taken from the current backend.
+pub struct InputPin(Box<dyn InputLine>);
+
+
+impl InputPin {
+    pub fn new(pin_num: u8, bias: Bias) -> Result<Self> {
+        Ok(InputPin(current().input(pin_num, bias)?))
+    }
+
+    pub fn is_high(&self) -> bool {
+        self.0.read() == Level::High
+    }
+
+    pub fn set_bias(&mut self, bias: Bias) -> Result<()> {
+        self.0.set_bias(bias)
+    }
+
+    pub fn set_interrupt(&mut self, trigger: Trigger, debounce: Option<Duration>) -> Result<()> {
+        self.0.set_interrupt(trigger, debounce)
+    }
+
+    pub fn clear_interrupt(&mut self) -> Result<()> {
+        self.0.clear_interrupt()
+    }
+
+    pub fn poll_interrupt(&mut self, reset: bool, timeout: Option<Duration>) -> Result<Option<Event>> {
+        self.0.poll_interrupt(reset, timeout)
+    }
+
+    pub fn set_async_interrupt(&mut self, trigger: Trigger, debounce: Option<Duration>, callback: impl FnMut(Event) + Send + 'static) -> Result<()> {
+        self.0.set_async_interrupt(trigger, debounce, Box::new(callback))
+    }
+
+    pub fn clear_async_interrupt(&mut self) -> Result<()> {
+        self.0.clear_async_interrupt()
+    }
+
+    pub fn set_reset_on_drop(&mut self, reset_on_drop: bool) {
+        self.0.set_reset_on_drop(reset_on_drop)
+    }
+}
+
+
+/// An output pin taken from the current backend.
+pub struct OutputPin(Box<dyn OutputLine>);
+
+
+impl OutputPin {
+    pub fn new(pin_num: u8, level: Level) -> Result<Self> {
+        Ok(OutputPin(current().output(pin_num, level)?))
+    }
+
+    pub fn write(&mut self, level: Level) {
+        self.0.write(level)
+    }
+
+    pub fn set_high(&mut self) {
+        self.0.write(Level::High)
+    }
+
+    pub fn set_low(&mut self) {
+        self.0.write(Level::Low)
+    }
+
+    pub fn is_set_high(&self) -> bool {
+        self.0.is_set_high()
+    }
+
+    pub fn set_pwm_frequency(&mut self, frequency: f64, duty_cycle: f64) -> Result<()> {
+        self.0.set_pwm_frequency(frequency, duty_cycle)
+    }
+
+    pub fn clear_pwm(&mut self) -> Result<()> {
+        self.0.clear_pwm()
+    }
+
+    pub fn set_output_enabled(&mut self, enabled: bool) -> Result<()> {
+        self.0.set_output_enabled(enabled)
+    }
+
+    pub fn set_reset_on_drop(&mut self, reset_on_drop: bool) {
+        self.0.set_reset_on_drop(reset_on_drop)
+    }
+}
+
+
+/// A hardware PWM channel taken from the current backend.
+pub struct Pwm(Box<dyn PwmLine>);
+
+
+impl Pwm {
+    pub fn with_frequency(channel_num: u8, frequency: f64, duty_cycle: f64, polarity: Polarity, enabled: bool) -> Result<Self> {
+        Ok(Pwm(current().pwm(channel_num, frequency, duty_cycle, polarity, enabled)?))
+    }
+
+    pub fn period(&self) -> Result<Duration> {
+        self.0.period()
+    }
+
+    pub fn set_period(&self, period: Duration) -> Result<()> {
+        self.0.set_period(period)
+    }
+
+    pub fn pulse_width(&self) -> Result<Duration> {
+        self.0.pulse_width()
+    }
+
+    pub fn set_pulse_width(&self, pulse_width: Duration) -> Result<()> {
+        self.0.set_pulse_width(pulse_width)
+    }
+
+    pub fn frequency(&self) -> Result<f64> {
+        self.0.frequency()
+    }
+
+    pub fn set_frequency(&self, frequency: f64, duty_cycle: f64) -> Result<()> {
+        self.0.set_frequency(frequency, duty_cycle)
+    }
+
+    pub fn duty_cycle(&self) -> Result<f64> {
+        self.0.duty_cycle()
+    }
+
+    pub fn set_duty_cycle(&self, duty_cycle: f64) -> Result<()> {
+        self.0.set_duty_cycle(duty_cycle)
+    }
+
+    pub fn set_polarity(&self, polarity: Polarity) -> Result<()> {
+        self.0.set_polarity(polarity)
+    }
+
+    pub fn is_enabled(&self) -> Result<bool> {
+        self.0.is_enabled()
+    }
+
+    pub fn enable(&self) -> Result<()> {
+        self.0.enable()
+    }
+
+    pub fn disable(&self) -> Result<()> {
+        self.0.disable()
+    }
+}
+
+
+// The backend new pins are taken from, rppal until set_backend is called
+static BACKEND: Lazy<RwLock<Arc<dyn Backend>>> = Lazy::new(|| RwLock::new(Arc::new(RppalBackend)));
+
+
+pub(crate) fn current() -> Arc<dyn Backend> {
+    Arc::clone(&BACKEND.read().unwrap())
+}
+
+
+/// Returns whether the pins are driven through rppal, which the features reaching the registers of the Raspberry Pi
+/// depend on.
+pub(crate) fn is_rppal() -> bool {
+    current().name() == "rppal"
+}
+
+
+/// Returns whether the pins are simulated by the mock backend.
+pub(crate) fn is_mock() -> bool {
+    current().name() == "mock"
+}
+
+
+/// Returns the backend with the given name, "rppal", "gpiochip" or "mock". The gpiochip backend opens the chip.
+pub(crate) fn open(name: &str, chip: &str) -> crate::error::Result<Arc<dyn Backend>> {
+    Ok(match name {
+        "rppal" => Arc::new(RppalBackend),
+        "gpiochip" => Arc::new(GpiochipBackend::open(chip)
+            .map_err(|e| GpioError::Os { errno: e.raw_os_error(), message: format!("Unable to open {}: {}", chip, e) })?),
+        "mock" => Arc::new(MockBackend),
+        _ => return Err(GpioError::InvalidArgument(format!("Backend must be rppal, gpiochip or mock, The value {} does not meet this condition", name))),
+    })
+}
+
+
+/// Makes a backend the one new pins are taken from, the pins already taken keep their backend.
+pub(crate) fn set_current(backend: Arc<dyn Backend>) {
+    log_module::info(&format!("Using the {} backend", backend.name()));
+    *BACKEND.write().unwrap() = backend;
+}
@@ -0,0 +1,52 @@
+use crate::backend;
+use crate::gpio_module::GPIOManager;
+use crate::pwm_module::PWMManager;
+use pyo3::{pyfunction, PyErr, PyResult};
+
+
+#[pyfunction]
+#[pyo3(signature = (name, chip = "/dev/gpiochip0"))]
+/// Selects how GPIOManager and PWMManager drive the pins. The backend can only be changed while no pin or PWM channel
+/// is set up.
+///
+/// The backends are:
+/// - ```"rppal"```: The registers of the Raspberry Pi, through rppal (the default).
+/// - ```"gpiochip"```: The GPIO character device of any board, the lines of the chip are the pin numbers. PWM channels
+///   use the PWM sysfs interface and read_all isn't supported.
+/// - ```"mock"```: Simulated pins without hardware, for tests. The inputs are driven with ```gpio_manager.mock```.
+///
+/// Capturing, pad control and alternate functions reach the registers of the Raspberry Pi whatever the backend.
+///
+/// Parameters:
+/// - ```name``` (str): The backend, "rppal", "gpiochip" or "mock".
+/// - ```chip``` (str): The character device used by the gpiochip backend (default is "/dev/gpiochip0").
+///
+/// Example usage:
+/// ```python
+/// gpio_manager.set_backend("mock")
+/// manager.add_input_pin(17)
+/// gpio_manager.mock.set_input(17, gpio_manager.PinState.HIGH)
+/// ```
+pub fn set_backend(name: &str, chip: &str) -> PyResult<()> {
+    let backend = backend::open(name, chip)?;
+    // The managers stay locked while the backend is swapped, so no pin is taken from the old one meanwhile
+    let gpio_manager = GPIOManager::new_rust_reference().get_manager();
+    let gpio_manager = gpio_manager.lock().unwrap();
+    let pwm_manager = PWMManager::new_rust_reference();
+    let pwm_manager = pwm_manager.lock().unwrap();
+    if !gpio_manager.input_pins.is_empty() || !gpio_manager.output_pins.is_empty() || pwm_manager.has_channels() {
+        return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("The backend can't be changed while pins or PWM channels are set up, call cleanup first"));
+    }
+    backend::set_current(backend);
+    Ok(())
+}
+
+
+#[pyfunction]
+/// Returns the backend the pins are driven through, "rppal", "gpiochip" or "mock".
+///
+/// Example usage:
+/// ```gpio_manager.current_backend()```
+pub fn current_backend() -> &'static str {
+    backend::current().name()
+}
@@ -0,0 +1,133 @@
+use crate::backend::{InputPin, OutputPin};
+use crate::gpio_module::GPIOManager;
+use crate::{metrics, release_gil};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rppal::gpio::{Bias, Level, Trigger};
+use std::sync::mpsc;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+
+// How long an edge may take to reach the input before the pins are taken as not wired together
+const EDGE_TIMEOUT: Duration = Duration::from_secs(1);
+// Pause between two samples, so an edge is handled before the next one is made
+const SAMPLE_GAP: Duration = Duration::from_millis(1);
+
+
+/// The error returned when an edge of the output doesn't reach the input.
+fn not_wired(out_pin: u8, in_pin: u8) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("No edge reached GPIO {} within {} ms, check that GPIO {} is wired to it",
+                                                              in_pin, EDGE_TIMEOUT.as_millis(), out_pin))
+}
+
+
+/// Toggles the output and measures how long each edge takes to be returned by a blocking wait on the input.
+fn measure_wait(output: &mut OutputPin, input: &mut InputPin, samples: usize) -> Option<Vec<Duration>> {
+    input.set_interrupt(Trigger::Both, None).ok()?;
+    let mut latencies = Vec::with_capacity(samples);
+    for _ in 0..samples {
+        let level = if output.is_set_high() { Level::Low } else { Level::High };
+        let start = Instant::now();
+        output.write(level);
+        input.poll_interrupt(false, Some(EDGE_TIMEOUT)).ok()??;
+        latencies.push(start.elapsed());
+        sleep(SAMPLE_GAP);
+    }
+    input.clear_interrupt().ok()?;
+    Some(latencies)
+}
+
+
+/// Toggles the output and measures how long each edge takes to reach an asynchronous interrupt callback of the input.
+fn measure_callback(output: &mut OutputPin, input: &mut InputPin, samples: usize) -> Option<Vec<Duration>> {
+    let (sender, receiver) = mpsc::channel();
+    input.set_async_interrupt(Trigger::Both, None, move |_| {
+        let _ = sender.send(Instant::now());
+    }).ok()?;
+    let mut latencies = Vec::with_capacity(samples);
+    for _ in 0..samples {
+        let level = if output.is_set_high() { Level::Low } else { Level::High };
+        let start = Instant::now();
+        output.write(level);
+        let called = receiver.recv_timeout(EDGE_TIMEOUT).ok()?;
+        latencies.push(called.duration_since(start));
+        sleep(SAMPLE_GAP);
+    }
+    input.clear_async_interrupt().ok()?;
+    Some(latencies)
+}
+
+
+/// Summarizes latencies into a dict of statistics in microseconds.
+fn summary<'py>(py: Python<'py>, mut latencies: Vec<Duration>) -> PyResult<Bound<'py, PyDict>> {
+    latencies.sort();
+    let us: Vec<f64> = latencies.iter().map(|latency| latency.as_secs_f64() * 1_000_000f64).collect();
+    let mean = us.iter().sum::<f64>() / us.len() as f64;
+    let variance = us.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / us.len() as f64;
+    let percentile = |p: f64| us[((us.len() - 1) as f64 * p).round() as usize];
+    let result = PyDict::new(py);
+    result.set_item("min_us", us[0])?;
+    result.set_item("mean_us", mean)?;
+    result.set_item("median_us", percentile(0.5))?;
+    result.set_item("p99_us", percentile(0.99))?;
+    result.set_item("max_us", us[us.len() - 1])?;
+    result.set_item("stdev_us", variance.sqrt())?;
+    result.set_item("samples_us", us)?;
+    Ok(result)
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (out_pin, in_pin, samples = 1000))]
+/// Measures the interrupt latency of the library with an output pin wired to an input pin. The output is toggled and
+/// the time until each edge is returned by a blocking wait, as wait_for_edge does, and until it reaches an interrupt
+/// callback is measured in Rust. Python callbacks add the time to take the GIL on top of the callback latency.
+///
+/// Both pins must be free, they are taken from the current backend for the run and released after it.
+///
+/// Parameters:
+/// - ```out_pin``` (int): The output pin, wired to the input pin.
+/// - ```in_pin``` (int): The input pin.
+/// - ```samples``` (int): The number of edges measured for each method (default is 1000).
+///
+/// Returns:
+/// - ```dict```: The statistics of each method as `wait_for_edge` and `callback`, each with `min_us`, `mean_us`,
+///   `median_us`, `p99_us`, `max_us`, `stdev_us` and the sorted latencies as `samples_us`.
+///
+/// Example usage:
+/// ```python
+/// result = gpio_manager.benchmark_latency(20, 21, 500)
+/// print(result["callback"]["median_us"], result["wait_for_edge"]["p99_us"])
+/// ```
+pub fn benchmark_latency(py: Python, out_pin: u8, in_pin: u8, samples: usize) -> PyResult<Bound<PyDict>> {
+    if samples == 0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Samples must be greater than 0, The value {} does not meet this condition", samples)));
+    }
+    if out_pin == in_pin {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "The output and input pins must be different pins wired together, both are GPIO {}", out_pin)));
+    }
+    let gpio_manager = GPIOManager::new_rust_reference();
+    {
+        let manager = gpio_manager.get_manager();
+        let manager = metrics::lock(&manager);
+        for pin_num in [out_pin, in_pin] {
+            if gpio_manager.is_input_pin(pin_num, &manager) || gpio_manager.is_output_pin(pin_num, &manager) {
+                return Err(GPIOManager::pin_error(&manager, pin_num, "benchmark it"));
+            }
+        }
+    }
+    let mut output = OutputPin::new(out_pin, Level::Low)?;
+    let mut input = InputPin::new(in_pin, Bias::Off)?;
+    let (wait, callback) = release_gil(|| {
+        let wait = measure_wait(&mut output, &mut input, samples);
+        let callback = measure_callback(&mut output, &mut input, samples);
+        (wait, callback)
+    });
+    let result = PyDict::new(py);
+    result.set_item("wait_for_edge", summary(py, wait.ok_or_else(|| not_wired(out_pin, in_pin))?)?)?;
+    result.set_item("callback", summary(py, callback.ok_or_else(|| not_wired(out_pin, in_pin))?)?)?;
+    Ok(result)
+}
@@ -0,0 +1,442 @@
+use crate::error::{GpioError, Result};
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::{PyDict, PyList};
+#[cfg(feature = "python")]
+use pyo3::{pyfunction, PyErr, PyResult, Python};
+use rppal::system::{DeviceInfo, Model, SoC};
+#[cfg(feature = "python")]
+use std::collections::HashMap;
+use std::fs;
+
+
+// Highest GPIO pin routed to the 40 pin header
+pub const MAX_HEADER_GPIO: u8 = 27;
+// The thermal zones of the kernel, one of them measures the SoC
+const THERMAL_ZONES: &str = "/sys/class/thermal";
+
+// Alternate functions a0 to a5 of the GPIO pins of the header on the BCM2835, BCM2836 and BCM2837
+const BCM2835_FUNCTIONS: [[&str; 6]; 28] = [
+    ["SDA0", "SA5", "PCLK", "AVEOUT_VCLK", "AVEIN_VCLK", ""],
+    ["SCL0", "SA4", "DE", "AVEOUT_DSYNC", "AVEIN_DSYNC", ""],
+    ["SDA1", "SA3", "LCD_VSYNC", "AVEOUT_VSYNC", "AVEIN_VSYNC", ""],
+    ["SCL1", "SA2", "LCD_HSYNC", "AVEOUT_HSYNC", "AVEIN_HSYNC", ""],
+    ["GPCLK0", "SA1", "DPI_D0", "AVEOUT_VID0", "AVEIN_VID0", "ARM_TDI"],
+    ["GPCLK1", "SA0", "DPI_D1", "AVEOUT_VID1", "AVEIN_VID1", "ARM_TDO"],
+    ["GPCLK2", "SOE_N", "DPI_D2", "AVEOUT_VID2", "AVEIN_VID2", "ARM_RTCK"],
+    ["SPI0_CE1_N", "SWE_N", "DPI_D3", "AVEOUT_VID3", "AVEIN_VID3", ""],
+    ["SPI0_CE0_N", "SD0", "DPI_D4", "AVEOUT_VID4", "AVEIN_VID4", ""],
+    ["SPI0_MISO", "SD1", "DPI_D5", "AVEOUT_VID5", "AVEIN_VID5", ""],
+    ["SPI0_MOSI", "SD2", "DPI_D6", "AVEOUT_VID6", "AVEIN_VID6", ""],
+    ["SPI0_SCLK", "SD3", "DPI_D7", "AVEOUT_VID7", "AVEIN_VID7", ""],
+    ["PWM0", "SD4", "DPI_D8", "AVEOUT_VID8", "AVEIN_VID8", "ARM_TMS"],
+    ["PWM1", "SD5", "DPI_D9", "AVEOUT_VID9", "AVEIN_VID9", "ARM_TCK"],
+    ["TXD0", "SD6", "DPI_D10", "AVEOUT_VID10", "AVEIN_VID10", "TXD1"],
+    ["RXD0", "SD7", "DPI_D11", "AVEOUT_VID11", "AVEIN_VID11", "RXD1"],
+    ["FL0", "SD8", "DPI_D12", "CTS0", "SPI1_CE2_N", "CTS1"],
+    ["FL1", "SD9", "DPI_D13", "RTS0", "SPI1_CE1_N", "RTS1"],
+    ["PCM_CLK", "SD10", "DPI_D14", "I2CSL_SDA_MOSI", "SPI1_CE0_N", "PWM0"],
+    ["PCM_FS", "SD11", "DPI_D15", "I2CSL_SCL_SCLK", "SPI1_MISO", "PWM1"],
+    ["PCM_DIN", "SD12", "DPI_D16", "I2CSL_MISO", "SPI1_MOSI", "GPCLK0"],
+    ["PCM_DOUT", "SD13", "DPI_D17", "I2CSL_CE_N", "SPI1_SCLK", "GPCLK1"],
+    ["SD0_CLK", "SD14", "DPI_D18", "SD1_CLK", "ARM_TRST", ""],
+    ["SD0_CMD", "SD15", "DPI_D19", "SD1_CMD", "ARM_RTCK", ""],
+    ["SD0_DAT0", "SD16", "DPI_D20", "SD1_DAT0", "ARM_TDO", ""],
+    ["SD0_DAT1", "SD17", "DPI_D21", "SD1_DAT1", "ARM_TCK", ""],
+    ["SD0_DAT2", "TE0", "DPI_D22", "SD1_DAT2", "ARM_TDI", ""],
+    ["SD0_DAT3", "TE1", "DPI_D23", "SD1_DAT3", "ARM_TMS", ""],
+];
+
+// Alternate functions a0 to a5 of the GPIO pins of the header on the BCM2711
+const BCM2711_FUNCTIONS: [[&str; 6]; 28] = [
+    ["SDA0", "SA5", "PCLK", "SPI3_CE0_N", "TXD2", "SDA6"],
+    ["SCL0", "SA4", "DE", "SPI3_MISO", "RXD2", "SCL6"],
+    ["SDA1", "SA3", "LCD_VSYNC", "SPI3_MOSI", "CTS2", "SDA3"],
+    ["SCL1", "SA2", "LCD_HSYNC", "SPI3_SCLK", "RTS2", "SCL3"],
+    ["GPCLK0", "SA1", "DPI_D0", "SPI4_CE0_N", "TXD3", "SDA3"],
+    ["GPCLK1", "SA0", "DPI_D1", "SPI4_MISO", "RXD3", "SCL3"],
+    ["GPCLK2", "SOE_N", "DPI_D2", "SPI4_MOSI", "CTS3", "SDA4"],
+    ["SPI0_CE1_N", "SWE_N", "DPI_D3", "SPI4_SCLK", "RTS3", "SCL4"],
+    ["SPI0_CE0_N", "SD0", "DPI_D4", "BSCSL_CE_N", "TXD4", "SDA4"],
+    ["SPI0_MISO", "SD1", "DPI_D5", "BSCSL_MISO", "RXD4", "SCL4"],
+    ["SPI0_MOSI", "SD2", "DPI_D6", "BSCSL_SDA_MOSI", "CTS4", "SDA5"],
+    ["SPI0_SCLK", "SD3", "DPI_D7", "BSCSL_SCL_SCLK", "RTS4", "SCL5"],
+    ["PWM0_0", "SD4", "DPI_D8", "SPI5_CE0_N", "TXD5", "SDA5"],
+    ["PWM0_1", "SD5", "DPI_D9", "SPI5_MISO", "RXD5", "SCL5"],
+    ["TXD0", "SD6", "DPI_D10", "SPI5_MOSI", "CTS5", "TXD1"],
+    ["RXD0", "SD7", "DPI_D11", "SPI5_SCLK", "RTS5", "RXD1"],
+    ["", "SD8", "DPI_D12", "CTS0", "SPI1_CE2_N", "CTS1"],
+    ["", "SD9", "DPI_D13", "RTS0", "SPI1_CE1_N", "RTS1"],
+    ["PCM_CLK", "SD10", "DPI_D14", "SPI6_CE0_N", "SPI1_CE0_N", "PWM0_0"],
+    ["PCM_FS", "SD11", "DPI_D15", "SPI6_MISO", "SPI1_MISO", "PWM0_1"],
+    ["PCM_DIN", "SD12", "DPI_D16", "SPI6_MOSI", "SPI1_MOSI", "GPCLK0"],
+    ["PCM_DOUT", "SD13", "DPI_D17", "SPI6_SCLK", "SPI1_SCLK", "GPCLK1"],
+    ["SD0_CLK", "SD14", "DPI_D18", "SD1_CLK", "ARM_TRST", "SDA6"],
+    ["SD0_CMD", "SD15", "DPI_D19", "SD1_CMD", "ARM_RTCK", "SCL6"],
+    ["SD0_DAT0", "SD16", "DPI_D20", "SD1_DAT0", "ARM_TDO", "SPI3_CE1_N"],
+    ["SD0_DAT1", "SD17", "DPI_D21", "SD1_DAT1", "ARM_TCK", "SPI4_CE1_N"],
+    ["SD0_DAT2", "TE0", "DPI_D22", "SD1_DAT2", "ARM_TDI", "SPI5_CE1_N"],
+    ["SD0_DAT3", "TE1", "DPI_D23", "SD1_DAT3", "ARM_TMS", "SPI6_CE1_N"],
+];
+
+// Alternate functions a0 to a8 of the GPIO pins of the header on the RP1 of the Raspberry Pi 5, the generic RIO and PIO
+// functions of a5 to a7 are left out
+const RP1_FUNCTIONS: [[&str; 9]; 28] = [
+    ["SPI0_SIO3", "DPI_PCLK", "TXD1", "SDA0", "", "", "", "", "SPI2_CE0"],
+    ["SPI0_SIO2", "DPI_DE", "RXD1", "SCL0", "", "", "", "", "SPI2_MISO"],
+    ["SPI0_CE3", "DPI_VSYNC", "CTS1", "SDA1", "IR_RX0", "", "", "", "SPI2_MOSI"],
+    ["SPI0_CE2", "DPI_HSYNC", "RTS1", "SCL1", "IR_TX0", "", "", "", "SPI2_SCLK"],
+    ["GPCLK0", "DPI_D0", "TXD2", "SDA2", "RI0", "", "", "", "SPI3_CE0"],
+    ["GPCLK1", "DPI_D1", "RXD2", "SCL2", "DTR0", "", "", "", "SPI3_MISO"],
+    ["GPCLK2", "DPI_D2", "CTS2", "SDA3", "DCD0", "", "", "", "SPI3_MOSI"],
+    ["SPI0_CE1", "DPI_D3", "RTS2", "SCL3", "DSR0", "", "", "", "SPI3_SCLK"],
+    ["SPI0_CE0", "DPI_D4", "TXD3", "SDA0", "", "", "", "", "SPI4_CE0"],
+    ["SPI0_MISO", "DPI_D5", "RXD3", "SCL0", "", "", "", "", "SPI4_MISO"],
+    ["SPI0_MOSI", "DPI_D6", "CTS3", "SDA1", "", "", "", "", "SPI4_MOSI"],
+    ["SPI0_SCLK", "DPI_D7", "RTS3", "SCL1", "", "", "", "", "SPI4_SCLK"],
+    ["PWM0_CHAN0", "DPI_D8", "TXD4", "SDA2", "AAUD_LEFT", "", "", "", "SPI5_CE0"],
+    ["PWM0_CHAN1", "DPI_D9", "RXD4", "SCL2", "AAUD_RIGHT", "", "", "", "SPI5_MISO"],
+    ["PWM0_CHAN2", "DPI_D10", "TXD0", "SDA3", "", "", "", "", "SPI5_MOSI"],
+    ["PWM0_CHAN3", "DPI_D11", "RXD0", "SCL3", "", "", "", "", "SPI5_SCLK"],
+    ["SPI1_CE2", "DPI_D12", "DSI0_TE_EXT", "", "CTS0", "", "", "", ""],
+    ["SPI1_CE1", "DPI_D13", "DSI1_TE_EXT", "", "RTS0", "", "", "", ""],
+    ["SPI1_CE0", "DPI_D14", "I2S0_SCLK", "PWM0_CHAN2", "I2S1_SCLK", "", "", "", "GPCLK1"],
+    ["SPI1_MISO", "DPI_D15", "I2S0_WS", "PWM0_CHAN3", "I2S1_WS", "", "", "", ""],
+    ["SPI1_MOSI", "DPI_D16", "I2S0_SDI0", "GPCLK0", "I2S1_SDI0", "", "", "", ""],
+    ["SPI1_SCLK", "DPI_D17", "I2S0_SDO0", "GPCLK1", "I2S1_SDO0", "", "", "", ""],
+    ["SD0_CLK", "DPI_D18", "I2S0_SDI1", "SDA3", "I2S1_SDI1", "", "", "", ""],
+    ["SD0_CMD", "DPI_D19", "I2S0_SDO1", "SCL3", "I2S1_SDO1", "", "", "", ""],
+    ["SD0_DAT0", "DPI_D20", "I2S0_SDI2", "", "I2S1_SDI2", "", "", "", "SPI2_CE1"],
+    ["SD0_DAT1", "DPI_D21", "I2S0_SDO2", "MIC_CLK", "I2S1_SDO2", "", "", "", "SPI3_CE1"],
+    ["SD0_DAT2", "DPI_D22", "I2S0_SDI3", "MIC_DAT0", "I2S1_SDI3", "", "", "", "SPI5_CE1"],
+    ["SD0_DAT3", "DPI_D23", "I2S0_SDO3", "MIC_DAT1", "I2S1_SDO3", "", "", "", "SPI1_CE1"],
+];
+
+
+/// A pin of the GPIO header.
+#[derive(Clone, Copy, Debug)]
+enum HeaderPin {
+    Power3V3,
+    Power5V,
+    Ground,
+    Gpio(u8),
+}
+
+
+// Pins of the 40 pin header by physical pin number, the 26 pin header of the first models is the first 26 pins
+const HEADER: [HeaderPin; 40] = [
+    HeaderPin::Power3V3, HeaderPin::Power5V,
+    HeaderPin::Gpio(2), HeaderPin::Power5V,
+    HeaderPin::Gpio(3), HeaderPin::Ground,
+    HeaderPin::Gpio(4), HeaderPin::Gpio(14),
+    HeaderPin::Ground, HeaderPin::Gpio(15),
+    HeaderPin::Gpio(17), HeaderPin::Gpio(18),
+    HeaderPin::Gpio(27), HeaderPin::Ground,
+    HeaderPin::Gpio(22), HeaderPin::Gpio(23),
+    HeaderPin::Power3V3, HeaderPin::Gpio(24),
+    HeaderPin::Gpio(10), HeaderPin::Ground,
+    HeaderPin::Gpio(9), HeaderPin::Gpio(25),
+    HeaderPin::Gpio(11), HeaderPin::Gpio(8),
+    HeaderPin::Ground, HeaderPin::Gpio(7),
+    HeaderPin::Gpio(0), HeaderPin::Gpio(1),
+    HeaderPin::Gpio(5), HeaderPin::Ground,
+    HeaderPin::Gpio(6), HeaderPin::Gpio(12),
+    HeaderPin::Gpio(13), HeaderPin::Ground,
+    HeaderPin::Gpio(19), HeaderPin::Gpio(16),
+    HeaderPin::Gpio(26), HeaderPin::Gpio(20),
+    HeaderPin::Ground, HeaderPin::Gpio(21),
+];
+
+
+/// Converts a physical pin of the 40 pin header to its BCM GPIO number.
+pub fn board_to_bcm(pin: u8) -> Option<u8> {
+    match HEADER.get((pin as usize).checked_sub(1)?) {
+        Some(HeaderPin::Gpio(gpio)) => Some(*gpio),
+        _ => None,
+    }
+}
+
+
+/// Returns the pin at a physical position of the header of a model. The first revision of the model B routes GPIO 0,
+/// 1 and 21 where later boards have GPIO 2, 3 and 27.
+fn header_pin(model: Model, physical: u8) -> HeaderPin {
+    match (model, HEADER[physical as usize - 1]) {
+        (Model::RaspberryPiBRev1, HeaderPin::Gpio(2)) => HeaderPin::Gpio(0),
+        (Model::RaspberryPiBRev1, HeaderPin::Gpio(3)) => HeaderPin::Gpio(1),
+        (Model::RaspberryPiBRev1, HeaderPin::Gpio(27)) => HeaderPin::Gpio(21),
+        (_, pin) => pin,
+    }
+}
+
+
+/// Detects the board the library runs on.
+pub fn device_info() -> Result<DeviceInfo> {
+    DeviceInfo::new().map_err(|e| GpioError::Hardware(format!("Unable to detect the board: {}", e)))
+}
+
+
+/// Reads the revision code of the board from /proc/cpuinfo, or from the device tree when the kernel doesn't list it.
+fn revision_code() -> Option<String> {
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok().and_then(|cpuinfo| {
+        cpuinfo.lines()
+               .find_map(|line| line.strip_prefix("Revision"))
+               .and_then(|value| value.split(':').nth(1))
+               .map(|value| value.trim().to_lowercase())
+    });
+    cpuinfo.or_else(|| {
+        let bytes = fs::read("/proc/device-tree/system/linux,revision").ok()?;
+        let bytes: [u8; 4] = bytes.get(..4)?.try_into().ok()?;
+        Some(format!("{:x}", u32::from_be_bytes(bytes)))
+    })
+}
+
+
+/// Decodes the RAM size in megabytes from a revision code.
+fn ram_mb(revision: &str) -> Option<u32> {
+    let code = u32::from_str_radix(revision, 16).ok()?;
+    // New style revision codes have bit 23 set and the memory size in bits 20 to 22
+    if code & (1 << 23) != 0 {
+        return Some(256 << ((code >> 20) & 0x7));
+    }
+    // Old style codes, the warranty bit of over-volted boards is ignored
+    match code & 0xffff {
+        0x0002..=0x0009 | 0x0012 => Some(256),
+        0x000d..=0x0015 => Some(512),
+        _ => None,
+    }
+}
+
+
+/// Returns the number of pins of the GPIO header of a model, compute modules have none.
+fn header_pins(model: Model) -> Option<u8> {
+    match model {
+        Model::RaspberryPiA | Model::RaspberryPiBRev1 | Model::RaspberryPiBRev2 => Some(26),
+        Model::RaspberryPiComputeModule
+        | Model::RaspberryPiComputeModule3
+        | Model::RaspberryPiComputeModule3Plus
+        | Model::RaspberryPiComputeModule4
+        | Model::RaspberryPiComputeModule4S => None,
+        _ => Some(40),
+    }
+}
+
+
+/// Returns the directory of the thermal zone of the SoC, named cpu-thermal on the Raspberry Pi, or of the first zone
+/// when none has that name.
+fn cpu_thermal_zone() -> String {
+    let mut zones: Vec<String> = fs::read_dir(THERMAL_ZONES).map(|entries| entries.filter_map(|entry| entry.ok())
+                                                                                   .map(|entry| entry.file_name().to_string_lossy().to_string())
+                                                                                   .filter(|name| name.starts_with("thermal_zone"))
+                                                                                   .collect())
+                                                            .unwrap_or_default();
+    zones.sort();
+    let zone = zones.iter()
+                    .find(|zone| fs::read_to_string(format!("{}/{}/type", THERMAL_ZONES, zone)).is_ok_and(|kind| kind.trim() == "cpu-thermal"))
+                    .or(zones.first())
+                    .map_or("thermal_zone0", |zone| zone.as_str());
+    format!("{}/{}", THERMAL_ZONES, zone)
+}
+
+
+/// Reads the temperature of the SoC in degrees Celsius from its thermal zone.
+pub fn read_cpu_temperature() -> Result<f64> {
+    let path = format!("{}/temp", cpu_thermal_zone());
+    let millidegrees = fs::read_to_string(&path).map_err(|e| GpioError::Os {
+        errno: e.raw_os_error(),
+        message: format!("Failed to read the CPU temperature from {}: {}", path, e),
+    })?;
+    // The kernel reports millidegrees
+    millidegrees.trim().parse::<f64>()
+                .map(|millidegrees| millidegrees / 1000f64)
+                .map_err(|e| GpioError::Hardware(format!("Invalid temperature {:?} in {}: {}", millidegrees.trim(), path, e)))
+}
+
+
+#[cfg(feature = "python")]
+#[pyfunction]
+/// Returns the temperature of the SoC in degrees Celsius, read from its thermal zone, the value vcgencmd measure_temp
+/// reports.
+///
+/// Returns:
+/// - `float`: The temperature in degrees Celsius.
+///
+/// Example usage:
+/// ```python
+/// if gpio_manager.cpu_temperature() > 80:
+///     print("The SoC is about to throttle")
+/// ```
+pub fn cpu_temperature() -> PyResult<f64> {
+    Ok(read_cpu_temperature()?)
+}
+
+
+#[cfg(feature = "python")]
+#[pyfunction]
+/// Returns information about the board the library runs on.
+///
+/// Returns:
+/// - `dict`: The model as `model`, the SoC as `soc`, the revision code as `revision`, the RAM size in megabytes as
+///   `ram_mb` and the number of pins of the GPIO header as `header_pins`. The revision and RAM size are None when they
+///   can't be read, the header pins are None for compute modules.
+///
+/// Example usage:
+/// ```python
+/// info = gpio_manager.board_info()
+/// if info["soc"] == "BCM2712":
+///     print("Running on a Raspberry Pi 5")
+/// ```
+pub fn board_info(py: Python) -> PyResult<Bound<PyDict>> {
+    let device = device_info()?;
+    let revision = revision_code();
+    let result = PyDict::new(py);
+    result.set_item("model", device.model().to_string())?;
+    result.set_item("soc", device.soc().to_string())?;
+    result.set_item("revision", revision.clone())?;
+    result.set_item("ram_mb", revision.as_deref().and_then(ram_mb))?;
+    result.set_item("header_pins", header_pins(device.model()))?;
+    Ok(result)
+}
+
+
+#[cfg(feature = "python")]
+#[pyfunction]
+/// Returns the pins of the GPIO header of the detected board, in physical order. Compute modules are described with
+/// the 40 pin header of their IO boards.
+///
+/// Returns:
+/// - `list[dict]`: For every pin its physical number as `physical`, its BCM GPIO number as `bcm` (None for power and
+///   ground pins), its name as `name`, the pull applied at power on as `default_pull` ("up", "down" or None) and its
+///   alternate functions by pinctrl name as `alt_functions`.
+///
+/// Example usage:
+/// ```python
+/// for pin in gpio_manager.pinout():
+///     print(pin["physical"], pin["name"], pin["alt_functions"].get("a0"))
+/// ```
+pub fn pinout(py: Python) -> PyResult<Bound<PyList>> {
+    let device = device_info()?;
+    let result = PyList::empty(py);
+    for physical in 1..=header_pins(device.model()).unwrap_or(40) {
+        let pin = PyDict::new(py);
+        pin.set_item("physical", physical)?;
+        match header_pin(device.model(), physical) {
+            HeaderPin::Gpio(gpio) => {
+                pin.set_item("bcm", gpio)?;
+                // The EEPROM of HATs is read from GPIO 0 and 1
+                let name = match gpio {
+                    0 => "ID_SD".to_string(),
+                    1 => "ID_SC".to_string(),
+                    _ => format!("GPIO{}", gpio),
+                };
+                pin.set_item("name", name)?;
+                pin.set_item("default_pull", if gpio <= 8 { "up" } else { "down" })?;
+                let functions: HashMap<String, &str> = alt_functions(device.soc(), gpio)?.into_iter().collect();
+                pin.set_item("alt_functions", functions)?;
+            }
+            power => {
+                pin.set_item("bcm", py.None())?;
+                pin.set_item("name", match power {
+                    HeaderPin::Power3V3 => "3V3",
+                    HeaderPin::Power5V => "5V",
+                    _ => "GND",
+                })?;
+                pin.set_item("default_pull", py.None())?;
+                pin.set_item("alt_functions", PyDict::new(py))?;
+            }
+        }
+        result.append(pin)?;
+    }
+    Ok(result)
+}
+
+
+/// Returns the alternate functions of a GPIO pin of the header on the SoC as `(alt, function)`, where alt is the
+/// pinctrl name of the function (a0 to a8).
+pub fn alt_functions(soc: SoC, pin_num: u8) -> Result<Vec<(String, &'static str)>> {
+    if pin_num > MAX_HEADER_GPIO {
+        return Err(GpioError::InvalidArgument(format!("GPIO {} is not routed to the header, pins 0 to {} are", pin_num, MAX_HEADER_GPIO)));
+    }
+    let functions: &[&'static str] = match soc {
+        SoC::Bcm2711 => &BCM2711_FUNCTIONS[pin_num as usize],
+        SoC::Bcm2712 => &RP1_FUNCTIONS[pin_num as usize],
+        _ => &BCM2835_FUNCTIONS[pin_num as usize],
+    };
+    Ok(functions.iter()
+                .enumerate()
+                .filter(|(_, function)| !function.is_empty())
+                .map(|(alt, function)| (format!("a{}", alt), *function))
+                .collect())
+}
+
+
+/// Returns the highest alternate function of the GPIO pins on the SoC.
+pub fn max_alt(soc: SoC) -> u8 {
+    match soc {
+        SoC::Bcm2712 => 8,
+        _ => 5,
+    }
+}
+
+
+/// An alternate function given by its number, its pinctrl name (a0 to a8) or the name of the function itself.
+#[cfg(feature = "python")]
+#[derive(FromPyObject)]
+pub enum AltFunction {
+    Number(u8),
+    Name(String),
+}
+
+
+#[cfg(feature = "python")]
+impl AltFunction {
+    /// Resolves the alternate function to its number for a GPIO pin of the header on the SoC.
+    pub fn resolve(&self, soc: SoC, pin_num: u8) -> PyResult<u8> {
+        let functions = alt_functions(soc, pin_num)?;
+        let alt = match self {
+            AltFunction::Number(alt) => *alt,
+            AltFunction::Name(name) => match name.strip_prefix('a').and_then(|alt| alt.parse::<u8>().ok()) {
+                Some(alt) => alt,
+                None => functions.iter()
+                                 .find(|(_, function)| function.eq_ignore_ascii_case(name))
+                                 .and_then(|(alt, _)| alt[1..].parse().ok())
+                                 .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("GPIO {} has no function named {} on the {}", pin_num, name, soc)))?,
+            },
+        };
+        if alt > max_alt(soc) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Alternate function must be between 0 and {} on the {}, The value {} does not meet this condition",
+                                                                               max_alt(soc), soc, alt)));
+        }
+        Ok(alt)
+    }
+}
+
+
+/// The peripheral an alternate function belongs to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Peripheral {
+    Pwm,
+    I2c,
+    Spi,
+    Uart,
+    Other,
+}
+
+
+impl Peripheral {
+    /// Classifies a function by its name. I2C and SPI slave functions count as other functions, the library only
+    /// drives the buses as master.
+    pub fn of(function: &str) -> Peripheral {
+        if function.starts_with("PWM") {
+            Peripheral::Pwm
+        } else if function.starts_with("SDA") || function.starts_with("SCL") {
+            Peripheral::I2c
+        } else if function.starts_with("SPI") {
+            Peripheral::Spi
+        } else if ["TXD", "RXD", "CTS", "RTS"].iter().any(|prefix| function.starts_with(prefix)) {
+            Peripheral::Uart
+        } else {
+            Peripheral::Other
+        }
+    }
+}
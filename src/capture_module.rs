@@ -0,0 +1,167 @@
+use crate::board_module::MAX_HEADER_GPIO;
+use crate::pinctrl::{level_reader, LevelReader};
+use crate::timing::wait_until;
+use crate::{frequency_to_period_checked, release_gil, TriggerEdge};
+use pyo3::{PyErr, PyResult};
+use std::time::{Duration, Instant};
+
+
+fn levels_error(e: std::io::Error) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Unable to read the GPIO levels: {}", e))
+}
+
+
+fn check_rate(sample_rate_hz: f64) -> PyResult<Duration> {
+    Ok(frequency_to_period_checked("Sample rate", sample_rate_hz)?)
+}
+
+
+fn check_pin(pin_num: u8) -> PyResult<()> {
+    if pin_num > MAX_HEADER_GPIO {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Sampling is limited to GPIO 0 to {}, The value {} does not meet this condition", MAX_HEADER_GPIO, pin_num)));
+    }
+    Ok(())
+}
+
+
+/// Returns the number of bytes of every sample of a capture of the given number of pins, 1, 2 or 4 so the data maps to
+/// an array of unsigned integers.
+pub fn sample_width(pin_count: usize) -> usize {
+    match pin_count {
+        0..=8 => 1,
+        9..=16 => 2,
+        _ => 4,
+    }
+}
+
+
+/// Waits for an edge on the trigger pin, polling it at the sample period. Returns the time of the edge, or None if the
+/// timeout expired first.
+fn wait_for_trigger(reader: &LevelReader, pin_num: u8, edge: TriggerEdge, period: Duration, timeout: Option<Duration>) -> Option<Instant> {
+    let start = Instant::now();
+    let mut last = reader.read() & (1 << pin_num) != 0;
+    let mut poll = 1u32;
+    loop {
+        wait_until(start + period * poll);
+        let now = Instant::now();
+        let high = reader.read() & (1 << pin_num) != 0;
+        let triggered = match edge {
+            TriggerEdge::RISING => !last && high,
+            TriggerEdge::FALLING => last && !high,
+            TriggerEdge::BOTH => last != high,
+        };
+        if triggered {
+            return Some(now);
+        }
+        if timeout.is_some_and(|timeout| now - start >= timeout) {
+            return None;
+        }
+        last = high;
+        poll = poll.wrapping_add(1);
+    }
+}
+
+
+/// Reads the levels at a fixed rate from the start time, passing every sample to the store along with its index.
+fn sample(reader: &LevelReader, start: Instant, period: Duration, count: usize, mut store: impl FnMut(usize, u32)) {
+    for index in 0..count {
+        wait_until(start + period.mul_f64(index as f64));
+        store(index, reader.read());
+    }
+}
+
+
+/// Samples the levels of the pins at a fixed rate, like a logic analyzer. Bit N of every sample is the level of the Nth
+/// pin, the samples are packed little endian with the width given by `sample_width`.
+///
+/// Parameters:
+/// - `trigger` is a pin and the edge on it that starts the capture, the capture starts right away for None.
+/// - `timeout` is the longest time to wait for the trigger, forever for None.
+pub fn capture(pins: &[u8], sample_rate_hz: f64, count: usize, trigger: Option<(u8, TriggerEdge)>, timeout: Option<Duration>) -> PyResult<Vec<u8>> {
+    let period = check_rate(sample_rate_hz)?;
+    if pins.is_empty() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("At least one pin must be captured"));
+    }
+    for pin_num in pins.iter().chain(trigger.iter().map(|(pin_num, _)| pin_num)) {
+        check_pin(*pin_num)?;
+    }
+    let width = sample_width(pins.len());
+
+    release_gil(|| {
+        let reader = level_reader().map_err(levels_error)?;
+        let start = match trigger {
+            Some((pin_num, edge)) => match wait_for_trigger(&reader, pin_num, edge, period, timeout) {
+                Some(start) => start,
+                None => return Err(PyErr::new::<pyo3::exceptions::PyTimeoutError, _>("The trigger edge did not occur before the timeout")),
+            },
+            None => Instant::now(),
+        };
+        let mut data = vec![0u8; count * width];
+        sample(&reader, start, period, count, |index, levels| {
+            let value = pins.iter()
+                            .enumerate()
+                            .fold(0u32, |value, (bit, pin_num)| value | ((levels >> pin_num) & 1) << bit);
+            data[index * width..(index + 1) * width].copy_from_slice(&value.to_le_bytes()[..width]);
+        });
+        Ok(data)
+    })
+}
+
+
+/// Samples the level of a single pin at a fixed rate into the buffer, every byte is set to 1 for high and 0 for low.
+/// Nothing is allocated while sampling, so the buffer can be reused for continuous monitoring.
+pub fn sample_pin(pin_num: u8, sample_rate_hz: f64, out: &mut [u8]) -> PyResult<()> {
+    let period = check_rate(sample_rate_hz)?;
+    check_pin(pin_num)?;
+    release_gil(|| {
+        let reader = level_reader().map_err(levels_error)?;
+        sample(&reader, Instant::now(), period, out.len(), |index, levels| out[index] = ((levels >> pin_num) & 1) as u8);
+        Ok(())
+    })
+}
+
+
+/// Measures the frequency in Hertz and the duty cycle (0 to 100) of a PWM signal fed into a pin, by polling its level
+/// as fast as possible for the duration. Both are averaged over the whole periods seen, from the first rising edge to
+/// the last. A signal without two rising edges reads as 0 Hz with a duty cycle of 0 or 100 for its level.
+pub fn measure_pwm(pin_num: u8, duration: Duration) -> PyResult<(f64, f64)> {
+    check_pin(pin_num)?;
+    release_gil(|| {
+        let reader = level_reader().map_err(levels_error)?;
+        let start = Instant::now();
+        let mut last = reader.read() & (1 << pin_num) != 0;
+        let (mut first_rise, mut last_rise, mut rises) = (None, start, 0u64);
+        let (mut high_time, mut high_time_at_last_rise, mut last_change) = (Duration::ZERO, Duration::ZERO, start);
+        let mut high_count = 0u64;
+        let mut reads = 0u64;
+        loop {
+            let now = Instant::now();
+            if now - start >= duration {
+                break;
+            }
+            let high = reader.read() & (1 << pin_num) != 0;
+            reads += 1;
+            high_count += high as u64;
+            if high != last {
+                if first_rise.is_some() && last {
+                    high_time += now - last_change;
+                }
+                if high {
+                    first_rise.get_or_insert(now);
+                    last_rise = now;
+                    rises += 1;
+                    high_time_at_last_rise = high_time;
+                }
+                last_change = now;
+                last = high;
+            }
+        }
+        match first_rise {
+            Some(first_rise) if rises >= 2 => {
+                let span = (last_rise - first_rise).as_secs_f64();
+                Ok(((rises - 1) as f64 / span, high_time_at_last_rise.as_secs_f64() / span * 100f64))
+            }
+            _ => Ok((0f64, if high_count * 2 > reads { 100f64 } else { 0f64 })),
+        }
+    })
+}
@@ -0,0 +1,220 @@
+use crate::gpio_module::GPIOManager;
+use crate::i2c_module::{I2CManager, I2CProbeMode};
+use crate::pwm_module::PWMManager;
+use crate::{InternPullResistorState, LogicLevel, OutputMode, PinState};
+use pyo3::prelude::*;
+use pyo3::{pyfunction, PyResult, Python};
+
+
+const USAGE: &str = "usage: gpio_manager <command> [arguments]
+
+commands:
+  read <pin> [--pull up|down|off]             Reads an input pin and prints HIGH or LOW
+  write <pin> high|low                        Drives an output pin, the pin keeps its state after exiting
+  pwm <channel> [--freq HZ] [--duty PERCENT]  Starts hardware PWM on a channel (0 or 1), which keeps running
+  pwm <channel> --stop                        Stops hardware PWM on a channel
+  i2c scan [--bus BUS]                        Lists the addresses of the devices on an I2C bus (default is 1)";
+
+
+/// An error of the command line, printed with the usage.
+struct UsageError(String);
+
+
+impl From<&str> for UsageError {
+    fn from(message: &str) -> Self {
+        UsageError(message.to_string())
+    }
+}
+
+
+/// The arguments of a command, options are taken out by name and the positional arguments are left in order.
+struct Args {
+    args: Vec<String>,
+}
+
+
+impl Args {
+    /// Takes the value following an option.
+    fn option(&mut self, name: &str) -> Result<Option<String>, UsageError> {
+        let Some(index) = self.args.iter().position(|arg| arg == name) else {
+            return Ok(None);
+        };
+        if index + 1 >= self.args.len() {
+            return Err(UsageError(format!("{} needs a value", name)));
+        }
+        let value = self.args.remove(index + 1);
+        self.args.remove(index);
+        Ok(Some(value))
+    }
+
+    /// Takes an option without a value, returning whether it was given.
+    fn flag(&mut self, name: &str) -> bool {
+        let given = self.args.iter().any(|arg| arg == name);
+        self.args.retain(|arg| arg != name);
+        given
+    }
+
+    /// Takes the next positional argument.
+    fn positional(&mut self, name: &str) -> Result<String, UsageError> {
+        if self.args.is_empty() {
+            return Err(UsageError(format!("missing {}", name)));
+        }
+        Ok(self.args.remove(0))
+    }
+
+    /// Fails when arguments are left that no command took.
+    fn finish(self) -> Result<(), UsageError> {
+        match self.args.first() {
+            Some(arg) => Err(UsageError(format!("unexpected argument {}", arg))),
+            None => Ok(()),
+        }
+    }
+}
+
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, UsageError> {
+    value.parse().map_err(|_| UsageError(format!("{} must be a number, got {}", name, value)))
+}
+
+
+fn read(mut args: Args) -> Result<PyResult<()>, UsageError> {
+    let pull = match args.option("--pull")?.as_deref() {
+        None => InternPullResistorState::AUTO,
+        Some("up") => InternPullResistorState::PULLUP,
+        Some("down") => InternPullResistorState::PULLDOWN,
+        Some("off") => InternPullResistorState::EXTERNAL,
+        Some(pull) => return Err(UsageError(format!("--pull must be up, down or off, got {}", pull))),
+    };
+    let pin_num: u8 = parse_number("pin", &args.positional("pin")?)?;
+    args.finish()?;
+    let gpio_manager = GPIOManager::new_rust_reference();
+    Ok(gpio_manager.add_input_pin(pin_num, Some(pull.into()), Some(LogicLevel::HIGH.into()), false).and_then(|_| {
+        let state = gpio_manager.get_pin(pin_num)?;
+        println!("{}", if state == PinState::HIGH { "HIGH" } else { "LOW" });
+        Ok(())
+    }))
+}
+
+
+fn write(mut args: Args) -> Result<PyResult<()>, UsageError> {
+    let pin_num: u8 = parse_number("pin", &args.positional("pin")?)?;
+    let state = match args.positional("state")?.to_ascii_lowercase().as_str() {
+        "high" | "1" | "on" => PinState::HIGH,
+        "low" | "0" | "off" => PinState::LOW,
+        state => return Err(UsageError(format!("the state must be high or low, got {}", state))),
+    };
+    args.finish()?;
+    Ok(GPIOManager::new_rust_reference().add_output_pin(pin_num, state.into(), Some(LogicLevel::HIGH.into()), OutputMode::PUSH_PULL.into(), false))
+}
+
+
+fn pwm(mut args: Args) -> Result<PyResult<()>, UsageError> {
+    let stop = args.flag("--stop");
+    let frequency_hz = args.option("--freq")?.map(|freq| parse_number::<f64>("--freq", &freq)).transpose()?;
+    let duty_cycle = args.option("--duty")?.map(|duty| parse_number::<f64>("--duty", &duty)).transpose()?;
+    let channel_num: u8 = parse_number("channel", &args.positional("channel")?)?;
+    args.finish()?;
+    let pwm_manager = PWMManager::new_rust_reference();
+    let pwm_manager = pwm_manager.lock().unwrap();
+    if stop {
+        // A channel started by another process is taken over by setting it up, resetting it disables and unexports it
+        return Ok(PWMManager::channel_pin(channel_num).and_then(|pin_num| {
+            if !pwm_manager.is_pin_pwm(pin_num) {
+                pwm_manager.setup_pwm_channel(channel_num, None, None, None, None, LogicLevel::HIGH.into(), false)?;
+            }
+            pwm_manager.reset_pwm_channel(channel_num)
+        }));
+    }
+    Ok(pwm_manager.setup_pwm_channel(channel_num, frequency_hz, Some(duty_cycle.unwrap_or(50f64)), None, None, LogicLevel::HIGH.into(), true)
+                  .and_then(|_| pwm_manager.start_pwm_channel(channel_num, None)))
+}
+
+
+fn i2c(mut args: Args) -> Result<PyResult<()>, UsageError> {
+    let bus: u8 = match args.option("--bus")? {
+        Some(bus) => parse_number("--bus", &bus)?,
+        None => 1,
+    };
+    if args.positional("i2c command")? != "scan" {
+        return Err("the i2c command must be scan".into());
+    }
+    args.finish()?;
+    let i2c_manager = I2CManager::new_rust_reference();
+    let i2c_manager = i2c_manager.lock().unwrap();
+    Ok(i2c_manager.open(bus).and_then(|_| i2c_manager.scan(0x03, 0x77, I2CProbeMode::AUTO, Some(bus))).map(|found| {
+        // Printed as the grid of i2cdetect
+        println!("     0  1  2  3  4  5  6  7  8  9  a  b  c  d  e  f");
+        for row in (0..0x80u16).step_by(16) {
+            let cells: Vec<String> = (row..row + 16).map(|addr| match addr {
+                _ if found.contains(&addr) => format!("{:02x}", addr),
+                0x03..=0x77 => "--".to_string(),
+                _ => "  ".to_string(),
+            }).collect();
+            println!("{:02x}: {}", row, cells.join(" "));
+        }
+    }))
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (argv = None))]
+/// Runs the command line interface, for bringing up hardware without writing a script. It is installed as the
+/// `gpio_manager` command.
+///
+/// Parameters:
+/// - ```argv``` (list[str]): The arguments without the program name, taken from `sys.argv` if None (default is None).
+///
+/// Returns:
+/// - ```int```: The exit status, 0 on success, 1 when the command failed and 2 for invalid arguments.
+///
+/// Example usage:
+/// ```python
+/// gpio_manager.cli.main(["write", "25", "high"])
+/// ```
+pub fn main(py: Python, argv: Option<Vec<String>>) -> PyResult<i32> {
+    let argv = match argv {
+        Some(argv) => argv,
+        None => py.import("sys")?.getattr("argv")?.extract::<Vec<String>>()?.into_iter().skip(1).collect(),
+    };
+    let mut args = Args { args: argv };
+    if args.flag("--help") || args.flag("-h") {
+        println!("{}", USAGE);
+        return Ok(0);
+    }
+    if args.args.is_empty() {
+        eprintln!("{}", USAGE);
+        return Ok(2);
+    }
+    let command = args.args.remove(0);
+    let result = match command.as_str() {
+        "read" => read(args),
+        "write" => write(args),
+        "pwm" => pwm(args),
+        "i2c" => i2c(args),
+        command => Err(UsageError(format!("unknown command {}", command))),
+    };
+    match result {
+        Ok(Ok(())) => Ok(0),
+        Ok(Err(e)) => {
+            eprintln!("gpio_manager {}: {}", command, e);
+            Ok(1)
+        }
+        Err(UsageError(message)) => {
+            eprintln!("gpio_manager: {}\n\n{}", message, USAGE);
+            Ok(2)
+        }
+    }
+}
+
+
+/// Adds the `cli` module providing `main`, and registers it in `sys.modules` so the `gpio_manager` console script and
+/// the `__main__` of the package can import it.
+pub fn add_cli_module(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = parent.py();
+    let cli = PyModule::new(py, "cli")?;
+    cli.gil_used(false)?;
+    cli.add_function(wrap_pyfunction!(main, &cli)?)?;
+    parent.add_submodule(&cli)?;
+    py.import("sys")?.getattr("modules")?.set_item("gpio_manager.cli", &cli)?;
+    Ok(())
+}
@@ -0,0 +1,281 @@
+use crate::backend::OutputPin;
+use crate::gpio_module::GPIOManager;
+use crate::timing::wait_until;
+use crate::{frequency_to_period_checked, us_to_duration_checked};
+use pyo3::{pyclass, pymethods, PyErr, PyResult};
+use rppal::gpio::Level;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+
+/// Timing of a complementary pair, read by the driving thread at the start of every period.
+#[derive(Clone, Copy, Debug)]
+struct PairTiming {
+    period: Duration,
+    duty_cycle: f64,
+    dead_time: Duration,
+}
+
+
+impl PairTiming {
+    fn check(&self) -> PyResult<()> {
+        if self.dead_time * 2 >= self.period {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The dead time must be less than half the period, {:?} does not meet this condition for a period of {:?}",
+                                                                               self.dead_time, self.period)));
+        }
+        Ok(())
+    }
+}
+
+
+fn check_duty_cycle(duty_cycle: f64) -> PyResult<()> {
+    if !(0f64..=100f64).contains(&duty_cycle) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Duty cycle must be between 0 and 100, The value {} does not meet this condition", duty_cycle)));
+    }
+    Ok(())
+}
+
+
+/// Drives the pair until `running` is cleared. Every period the main pin is high for the duty cycle, then both pins
+/// are low for the dead time, the complementary pin is high for the rest of the period minus a second dead time.
+fn drive_pair(main: Arc<Mutex<OutputPin>>, complementary: Arc<Mutex<OutputPin>>, timing: Arc<Mutex<PairTiming>>, running: Arc<AtomicBool>) {
+    // The pins are locked for every edge rather than for the whole run, so the GPIOManager can still clean them up
+    let set = |pin: &Mutex<OutputPin>, high: bool| pin.lock().unwrap().write(if high { Level::High } else { Level::Low });
+    let mut period_start = Instant::now();
+    while running.load(Ordering::Relaxed) {
+        let timing = *timing.lock().unwrap();
+        let high_time = timing.period.mul_f64(timing.duty_cycle / 100f64);
+
+        if timing.duty_cycle >= 100f64 {
+            set(&complementary, false);
+            set(&main, true);
+        } else if timing.duty_cycle <= 0f64 {
+            set(&main, false);
+            set(&complementary, true);
+        } else {
+            set(&main, true);
+            wait_until(period_start + high_time);
+            set(&main, false);
+            let complementary_start = high_time + timing.dead_time;
+            let complementary_end = timing.period - timing.dead_time;
+            if complementary_start < complementary_end {
+                wait_until(period_start + complementary_start);
+                set(&complementary, true);
+                wait_until(period_start + complementary_end);
+                set(&complementary, false);
+            }
+        }
+        period_start += timing.period;
+        // Start over from the current time if the thread fell behind by more than a period
+        if Instant::now() > period_start + timing.period {
+            period_start = Instant::now();
+        }
+        wait_until(period_start);
+    }
+    set(&main, false);
+    set(&complementary, false);
+}
+
+
+#[pyclass]
+/// ComplementaryPWM drives two pins as a complementary pair for half-bridge drivers, the second pin is the inverse of
+/// the first with a dead time inserted at every transition, so both pins are never high at the same time. The signal
+/// is generated by a dedicated thread, the timing stays aligned as the duty cycle and frequency change.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// bridge = gpio_manager.ComplementaryPWM(20, 21, frequency_hz=2000, duty_cycle=30, dead_time_us=5)
+/// bridge.start()
+/// bridge.set_duty_cycle(60)
+/// bridge.close()
+/// ```
+pub struct ComplementaryPWM {
+    pin: u8,
+    complementary_pin: u8,
+    main: Arc<Mutex<OutputPin>>,
+    complementary: Arc<Mutex<OutputPin>>,
+    timing: Arc<Mutex<PairTiming>>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+
+impl ComplementaryPWM {
+    fn update_timing(&self, update: impl FnOnce(&mut PairTiming)) -> PyResult<()> {
+        let mut timing = self.timing.lock().unwrap();
+        let mut new_timing = *timing;
+        update(&mut new_timing);
+        new_timing.check()?;
+        *timing = new_timing;
+        Ok(())
+    }
+}
+
+
+impl Drop for ComplementaryPWM {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+
+#[pymethods]
+impl ComplementaryPWM {
+    #[new]
+    /// Sets up two free pins as a complementary pair, both pins stay low until the pair is started.
+    ///
+    /// Parameters:
+    /// - `pin` (int): The GPIO pin driven with the duty cycle.
+    /// - `complementary_pin` (int): The GPIO pin driven with the inverse of the duty cycle.
+    /// - `frequency_hz` (float): The frequency in Hertz (default is 1000).
+    /// - `duty_cycle` (float): The duty cycle of the first pin from 0 to 100 (default is 0).
+    /// - `dead_time_us` (float): The time both pins are low at every transition in microseconds (default is 1).
+    ///
+    /// Example usage:
+    /// ```python
+    /// bridge = gpio_manager.ComplementaryPWM(20, 21, frequency_hz=2000, dead_time_us=5)
+    /// ```
+    #[pyo3(signature = (pin, complementary_pin, frequency_hz = 1000f64, duty_cycle = 0f64, dead_time_us = 1f64))]
+    fn new(pin: u8, complementary_pin: u8, frequency_hz: f64, duty_cycle: f64, dead_time_us: f64) -> PyResult<Self> {
+        if pin == complementary_pin {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("The pins of a complementary pair must be different"));
+        }
+        check_duty_cycle(duty_cycle)?;
+        let timing = PairTiming {
+            period: frequency_to_period_checked("Frequency", frequency_hz)?,
+            duty_cycle,
+            dead_time: us_to_duration_checked("Dead time", dead_time_us)?,
+        };
+        timing.check()?;
+
+        let gpio_manager = GPIOManager::new_rust_reference();
+        let main = gpio_manager.claim_output_pin(pin)?;
+        let complementary = match gpio_manager.claim_output_pin(complementary_pin) {
+            Ok(complementary) => complementary,
+            Err(e) => {
+                gpio_manager.reset_pin(pin)?;
+                return Err(e);
+            }
+        };
+        Ok(Self {
+            pin,
+            complementary_pin,
+            main,
+            complementary,
+            timing: Arc::new(Mutex::new(timing)),
+            running: Arc::new(AtomicBool::new(false)),
+            thread: None,
+        })
+    }
+
+    /// Starts driving the pair.
+    ///
+    /// Example usage:
+    /// ```python
+    /// bridge.start()
+    /// ```
+    fn start(&mut self) {
+        if self.thread.is_some() {
+            return;
+        }
+        self.running.store(true, Ordering::Relaxed);
+        let (main, complementary) = (Arc::clone(&self.main), Arc::clone(&self.complementary));
+        let (timing, running) = (Arc::clone(&self.timing), Arc::clone(&self.running));
+        self.thread = Some(thread::spawn(move || drive_pair(main, complementary, timing, running)));
+    }
+
+    /// Stops driving the pair, both pins are set low.
+    ///
+    /// Example usage:
+    /// ```python
+    /// bridge.stop()
+    /// ```
+    fn stop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    /// Stops the pair and releases both pins.
+    ///
+    /// Example usage:
+    /// ```python
+    /// bridge.close()
+    /// ```
+    fn close(&mut self) -> PyResult<()> {
+        self.stop();
+        let gpio_manager = GPIOManager::new_rust_reference();
+        gpio_manager.reset_pin(self.pin)?;
+        gpio_manager.reset_pin(self.complementary_pin)
+    }
+
+    /// Sets the duty cycle of the first pin, the complementary pin is high for the rest of the period minus the dead
+    /// times.
+    ///
+    /// Parameters:
+    /// - `duty_cycle` (float): The duty cycle from 0 to 100.
+    ///
+    /// Example usage:
+    /// ```python
+    /// bridge.set_duty_cycle(60)
+    /// ```
+    #[pyo3(signature = (duty_cycle))]
+    fn set_duty_cycle(&self, duty_cycle: f64) -> PyResult<()> {
+        check_duty_cycle(duty_cycle)?;
+        self.update_timing(|timing| timing.duty_cycle = duty_cycle)
+    }
+
+    /// Sets the frequency, the period must stay longer than twice the dead time.
+    ///
+    /// Parameters:
+    /// - `frequency_hz` (float): The frequency in Hertz.
+    ///
+    /// Example usage:
+    /// ```python
+    /// bridge.set_frequency(5000)
+    /// ```
+    #[pyo3(signature = (frequency_hz))]
+    fn set_frequency(&self, frequency_hz: f64) -> PyResult<()> {
+        let period = frequency_to_period_checked("Frequency", frequency_hz)?;
+        self.update_timing(|timing| timing.period = period)
+    }
+
+    /// Sets the dead time, which must be less than half the period.
+    ///
+    /// Parameters:
+    /// - `dead_time_us` (float): The time both pins are low at every transition in microseconds.
+    ///
+    /// Example usage:
+    /// ```python
+    /// bridge.set_dead_time(2.5)
+    /// ```
+    #[pyo3(signature = (dead_time_us))]
+    fn set_dead_time(&self, dead_time_us: f64) -> PyResult<()> {
+        let dead_time = us_to_duration_checked("Dead time", dead_time_us)?;
+        self.update_timing(|timing| timing.dead_time = dead_time)
+    }
+
+    /// Returns the duty cycle of the first pin from 0 to 100.
+    fn get_duty_cycle(&self) -> f64 {
+        self.timing.lock().unwrap().duty_cycle
+    }
+
+    /// Returns the frequency in Hertz.
+    fn get_frequency(&self) -> f64 {
+        1f64 / self.timing.lock().unwrap().period.as_secs_f64()
+    }
+
+    /// Returns the dead time in microseconds.
+    fn get_dead_time(&self) -> f64 {
+        self.timing.lock().unwrap().dead_time.as_secs_f64() * 1_000_000f64
+    }
+
+    /// Returns whether the pair is being driven.
+    fn is_active(&self) -> bool {
+        self.thread.is_some()
+    }
+}
@@ -0,0 +1,468 @@
+use crate::gpio_module::GPIOManager;
+use crate::{InternPullResistorState, LogicLevel, OutputMode, PinState, PinType, TriggerEdge};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use pyo3::IntoPyObjectExt;
+use std::collections::HashMap;
+use std::path::Path;
+
+
+// The keys every kind of pin accepts, unknown keys are rejected so typos don't go unnoticed
+// callbacks is the metadata written by export_config, callables can't be restored so it is only informational
+const INPUT_KEYS: [&str; 5] = ["pin", "mode", "pull", "logic_level", "callbacks"];
+const OUTPUT_KEYS: [&str; 5] = ["pin", "mode", "state", "logic_level", "output_mode"];
+const PWM_KEYS: [&str; 8] = ["pin", "mode", "frequency_hz", "duty_cycle", "period_ms", "pulse_width_ms", "logic_level", "start"];
+
+
+/// How a pin of the configuration is set up.
+enum PinSetup {
+    Input { pull: InternPullResistorState, logic_level: LogicLevel },
+    Output { state: PinState, logic_level: LogicLevel, output_mode: OutputMode },
+    Pwm { frequency_hz: Option<f64>, duty_cycle: Option<f64>, period_ms: Option<f64>, pulse_width_ms: Option<f64>, logic_level: LogicLevel, start: bool },
+}
+
+
+/// A pin of the configuration, validated before any pin is set up.
+struct PinEntry {
+    name: String,
+    pin_num: u8,
+    setup: PinSetup,
+}
+
+
+fn entry_error(name: &str, message: String) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("pins.{}: {}", name, message))
+}
+
+
+/// Reads a configuration file into a dict, with the parser picked by the extension of the file.
+fn read_file<'py>(py: Python<'py>, path: &str) -> PyResult<Bound<'py, PyAny>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyOSError, _>(format!("Unable to read {}: {}", path, e)))?;
+    let extension = Path::new(path).extension().and_then(|extension| extension.to_str()).unwrap_or_default().to_ascii_lowercase();
+    match extension.as_str() {
+        "json" => py.import("json")?.call_method1("loads", (text,)),
+        // tomllib is part of Python since 3.11, tomli is the same parser for older versions
+        "toml" => py.import("tomllib")
+                    .or_else(|_| py.import("tomli"))
+                    .map_err(|_| PyErr::new::<pyo3::exceptions::PyImportError, _>("Reading TOML needs Python 3.11 or the tomli package"))?
+                    .call_method1("loads", (text,)),
+        "yaml" | "yml" => py.import("yaml")
+                            .map_err(|_| PyErr::new::<pyo3::exceptions::PyImportError, _>("Reading YAML needs the PyYAML package"))?
+                            .call_method1("safe_load", (text,)),
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The configuration must be a .toml, .json, .yaml or .yml file, The value {} does not meet this condition", path))),
+    }
+}
+
+
+/// Returns a key of an entry as a string, or None if it is missing.
+fn get_str(name: &str, entry: &Bound<'_, PyDict>, key: &str) -> PyResult<Option<String>> {
+    match entry.get_item(key)? {
+        Some(value) => value.extract::<String>()
+                            .map(|value| Some(value.to_ascii_lowercase()))
+                            .map_err(|_| entry_error(name, format!("{} must be a string, The value {} does not meet this condition", key, value))),
+        None => Ok(None),
+    }
+}
+
+
+fn get_f64(name: &str, entry: &Bound<'_, PyDict>, key: &str) -> PyResult<Option<f64>> {
+    match entry.get_item(key)? {
+        Some(value) => value.extract::<f64>()
+                            .map(Some)
+                            .map_err(|_| entry_error(name, format!("{} must be a number, The value {} does not meet this condition", key, value))),
+        None => Ok(None),
+    }
+}
+
+
+fn logic_level(name: &str, entry: &Bound<'_, PyDict>) -> PyResult<LogicLevel> {
+    match get_str(name, entry, "logic_level")?.as_deref() {
+        None | Some("high") => Ok(LogicLevel::HIGH),
+        Some("low") => Ok(LogicLevel::LOW),
+        Some(level) => Err(entry_error(name, format!("logic_level must be \"high\" or \"low\", The value {} does not meet this condition", level))),
+    }
+}
+
+
+/// Validates an entry of the `pins` table.
+fn parse_entry(name: &str, entry: &Bound<'_, PyAny>) -> PyResult<PinEntry> {
+    let entry = entry.downcast::<PyDict>().map_err(|_| entry_error(name, "The entry must be a table".to_string()))?;
+    let pin_num: u8 = match entry.get_item("pin")? {
+        Some(pin) => pin.extract().map_err(|_| entry_error(name, format!("pin must be a GPIO number, The value {} does not meet this condition", pin)))?,
+        None => return Err(entry_error(name, "The entry must have a pin".to_string())),
+    };
+    let mode = get_str(name, entry, "mode")?.unwrap_or_else(|| "input".to_string());
+    let (setup, keys): (PinSetup, &[&str]) = match mode.as_str() {
+        "input" => {
+            let pull = match get_str(name, entry, "pull")?.as_deref() {
+                None | Some("auto") => InternPullResistorState::AUTO,
+                Some("up") => InternPullResistorState::PULLUP,
+                Some("down") => InternPullResistorState::PULLDOWN,
+                Some("external") | Some("off") => InternPullResistorState::EXTERNAL,
+                Some(pull) => return Err(entry_error(name, format!("pull must be \"auto\", \"up\", \"down\" or \"external\", The value {} does not meet this condition", pull))),
+            };
+            (PinSetup::Input { pull, logic_level: logic_level(name, entry)? }, &INPUT_KEYS)
+        }
+        "output" => {
+            let state = match entry.get_item("state")? {
+                None => PinState::LOW,
+                Some(state) => match state.extract::<bool>().ok().or_else(|| match state.extract::<String>().ok()?.to_ascii_lowercase().as_str() {
+                    "high" => Some(true),
+                    "low" => Some(false),
+                    _ => None,
+                }) {
+                    Some(true) => PinState::HIGH,
+                    Some(false) => PinState::LOW,
+                    None => return Err(entry_error(name, format!("state must be \"high\", \"low\", true or false, The value {} does not meet this condition", state))),
+                },
+            };
+            let output_mode = match get_str(name, entry, "output_mode")?.as_deref() {
+                None | Some("push_pull") => OutputMode::PUSH_PULL,
+                Some("open_drain") => OutputMode::OPEN_DRAIN,
+                Some("open_source") => OutputMode::OPEN_SOURCE,
+                Some(output_mode) => return Err(entry_error(name, format!("output_mode must be \"push_pull\", \"open_drain\" or \"open_source\", The value {} does not meet this condition", output_mode))),
+            };
+            (PinSetup::Output { state, logic_level: logic_level(name, entry)?, output_mode }, &OUTPUT_KEYS)
+        }
+        "pwm" => {
+            let start = match entry.get_item("start")? {
+                Some(start) => start.extract::<bool>().map_err(|_| entry_error(name, format!("start must be true or false, The value {} does not meet this condition", start)))?,
+                None => false,
+            };
+            (PinSetup::Pwm {
+                frequency_hz: get_f64(name, entry, "frequency_hz")?,
+                duty_cycle: get_f64(name, entry, "duty_cycle")?,
+                period_ms: get_f64(name, entry, "period_ms")?,
+                pulse_width_ms: get_f64(name, entry, "pulse_width_ms")?,
+                logic_level: logic_level(name, entry)?,
+                start,
+            }, &PWM_KEYS)
+        }
+        mode => return Err(entry_error(name, format!("mode must be \"input\", \"output\" or \"pwm\", The value {} does not meet this condition", mode))),
+    };
+    for key in entry.keys() {
+        let key = key.to_string();
+        if !keys.contains(&key.as_str()) {
+            return Err(entry_error(name, format!("Unknown key {} for a pin in {} mode, the keys are {}", key, mode, keys.join(", "))));
+        }
+    }
+    Ok(PinEntry { name: name.to_string(), pin_num, setup })
+}
+
+
+/// Validates the `pins` table of a configuration, rejecting pins used by two entries.
+fn parse_config(config: &Bound<'_, PyAny>) -> PyResult<Vec<PinEntry>> {
+    let config = config.downcast::<PyDict>()
+                       .map_err(|_| PyErr::new::<pyo3::exceptions::PyValueError, _>("The configuration must be a table"))?;
+    let Some(pins) = config.get_item("pins")? else {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("The configuration must have a pins table"));
+    };
+    let pins = pins.downcast::<PyDict>().map_err(|_| PyErr::new::<pyo3::exceptions::PyValueError, _>("pins must be a table of pins by name"))?;
+    let mut entries: Vec<PinEntry> = Vec::new();
+    let mut names: HashMap<u8, String> = HashMap::new();
+    for (name, entry) in pins.iter() {
+        let name = name.to_string();
+        let entry = parse_entry(&name, &entry)?;
+        if let Some(other) = names.insert(entry.pin_num, name.clone()) {
+            return Err(entry_error(&name, format!("GPIO {} is already used by pins.{}", entry.pin_num, other)));
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+
+fn apply_entry(gpio_manager: &GPIOManager, entry: &PinEntry) -> PyResult<()> {
+    match entry.setup {
+        PinSetup::Input { pull, logic_level } => gpio_manager.add_input_pin(entry.pin_num, Some(pull.into()), Some(logic_level.into()), false),
+        PinSetup::Output { state, logic_level, output_mode } => gpio_manager.add_output_pin(entry.pin_num, state.into(), Some(logic_level.into()), output_mode.into(), false),
+        PinSetup::Pwm { frequency_hz, duty_cycle, period_ms, pulse_width_ms, logic_level, start } => {
+            gpio_manager.setup_pwm(entry.pin_num, frequency_hz, duty_cycle, period_ms, pulse_width_ms, Some(logic_level.into()), false)?;
+            if start {
+                gpio_manager.start_pwm(entry.pin_num, None)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+
+/// Sets up the pins of a configuration file or dict, returning the pins by name. The whole configuration is validated
+/// first, and the pins already set up are reset when one fails, so a bad configuration leaves no pin behind.
+pub fn load_config<'py>(py: Python<'py>, gpio_manager: &GPIOManager, source: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyDict>> {
+    let entries = parse_config(&read_source(py, source)?)?;
+    apply_entries(py, gpio_manager, entries)
+}
+
+
+/// Replaces the pins set up on the manager with the pins of a configuration, as written by export_config. The
+/// configuration is validated before the current pins are reset.
+pub fn apply_config<'py>(py: Python<'py>, gpio_manager: &GPIOManager, source: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyDict>> {
+    let entries = parse_config(&read_source(py, source)?)?;
+    gpio_manager.cleanup()?;
+    apply_entries(py, gpio_manager, entries)
+}
+
+
+/// Returns the configuration of a dict, or of the file at a path.
+fn read_source<'py>(py: Python<'py>, source: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    if source.is_instance_of::<PyDict>() {
+        return Ok(source.clone());
+    }
+    let path: String = py.import("os")?.call_method1("fspath", (source,))?.extract()?;
+    read_file(py, &path)
+}
+
+
+/// Sets up the pins of a validated configuration, resetting the pins already set up when one fails.
+fn apply_entries<'py>(py: Python<'py>, gpio_manager: &GPIOManager, entries: Vec<PinEntry>) -> PyResult<Bound<'py, PyDict>> {
+    for (index, entry) in entries.iter().enumerate() {
+        if let Err(e) = apply_entry(gpio_manager, entry) {
+            for applied in &entries[..index] {
+                let _ = gpio_manager.reset_pin(applied.pin_num);
+            }
+            return Err(PyErr::from_type(e.get_type(py), format!("pins.{}: {}", entry.name, e.value(py))));
+        }
+    }
+    let aliases = PyDict::new(py);
+    for entry in entries {
+        aliases.set_item(&entry.name, entry.pin_num)?;
+        gpio_manager.set_alias(entry.name, entry.pin_num);
+    }
+    Ok(aliases)
+}
+
+
+/// A table of an exported configuration, with its keys in order.
+type ConfigTable = Vec<(&'static str, ConfigValue)>;
+
+
+/// A value of an exported configuration, kept apart from Python so it can be written as TOML.
+enum ConfigValue {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Tables(Vec<ConfigTable>),
+}
+
+
+impl ConfigValue {
+    fn to_object(&self, py: Python) -> PyResult<PyObject> {
+        Ok(match self {
+            ConfigValue::Str(value) => value.into_py_any(py)?,
+            ConfigValue::Int(value) => value.into_py_any(py)?,
+            ConfigValue::Float(value) => value.into_py_any(py)?,
+            ConfigValue::Bool(value) => value.into_py_any(py)?,
+            ConfigValue::Tables(tables) => {
+                let list = PyList::empty(py);
+                for table in tables {
+                    list.append(table_to_dict(py, table)?)?;
+                }
+                list.into_any().unbind()
+            }
+        })
+    }
+
+    fn to_toml(&self) -> String {
+        match self {
+            ConfigValue::Str(value) => toml_string(value),
+            ConfigValue::Int(value) => value.to_string(),
+            // Debug keeps the decimal point, so a whole number is read back as a float
+            ConfigValue::Float(value) => format!("{:?}", value),
+            ConfigValue::Bool(value) => value.to_string(),
+            ConfigValue::Tables(tables) => {
+                let tables: Vec<String> = tables.iter().map(|table| {
+                    let keys: Vec<String> = table.iter().map(|(key, value)| format!("{} = {}", key, value.to_toml())).collect();
+                    format!("{{ {} }}", keys.join(", "))
+                }).collect();
+                format!("[{}]", tables.join(", "))
+            }
+        }
+    }
+}
+
+
+fn table_to_dict<'py>(py: Python<'py>, table: &ConfigTable) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    for (key, value) in table {
+        dict.set_item(*key, value.to_object(py)?)?;
+    }
+    Ok(dict)
+}
+
+
+/// Quotes a string for TOML, the escapes of a TOML basic string are a subset of Rust's.
+fn toml_string(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+
+/// Quotes a key for TOML unless it is a bare key.
+fn toml_key(key: &str) -> String {
+    if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        key.to_string()
+    } else {
+        toml_string(key)
+    }
+}
+
+
+fn level_name(logic_level: LogicLevel) -> ConfigValue {
+    ConfigValue::Str(if logic_level == LogicLevel::HIGH { "high" } else { "low" }.to_string())
+}
+
+
+/// Returns the name of a callable for the callbacks metadata, as module.qualname when both are known.
+fn callable_name(callable: &Bound<'_, PyAny>) -> String {
+    let qualname = callable.getattr("__qualname__").and_then(|name| name.extract::<String>());
+    let module = callable.getattr("__module__").and_then(|name| name.extract::<String>());
+    match (module, qualname) {
+        (Ok(module), Ok(qualname)) => format!("{}.{}", module, qualname),
+        (_, Ok(qualname)) => qualname,
+        _ => callable.to_string(),
+    }
+}
+
+
+/// Collects the pins set up on the manager as entries of the pins table, named by their alias or as gpio<N>.
+fn collect_entries(py: Python, gpio_manager: &GPIOManager) -> PyResult<Vec<(String, ConfigTable)>> {
+    let manager = gpio_manager.get_manager();
+    let manager = manager.lock().unwrap();
+    let mut names: HashMap<u8, String> = HashMap::new();
+    for (name, pin_num) in &manager.aliases {
+        names.insert(*pin_num, name.clone());
+    }
+    let mut pin_nums: Vec<u8> = manager.input_pins.keys().chain(manager.output_pins.keys()).copied().collect();
+    pin_nums.sort_unstable();
+
+    let mut entries = Vec::new();
+    for pin_num in pin_nums {
+        let name = names.remove(&pin_num).unwrap_or_else(|| format!("gpio{}", pin_num));
+        let mut entry: ConfigTable = vec![("pin", ConfigValue::Int(pin_num as i64))];
+        if let Some(pin_arc) = manager.input_pins.get(&pin_num) {
+            let pin = pin_arc.lock().unwrap();
+            let pull = match pin.pull {
+                InternPullResistorState::AUTO => "auto",
+                InternPullResistorState::PULLUP => "up",
+                InternPullResistorState::PULLDOWN => "down",
+                InternPullResistorState::EXTERNAL => "external",
+            };
+            entry.push(("mode", ConfigValue::Str("input".to_string())));
+            entry.push(("pull", ConfigValue::Str(pull.to_string())));
+            entry.push(("logic_level", level_name(pin.logic_level)));
+            let callbacks = manager.callbacks.get(&pin_num).map(|callbacks| callbacks.as_slice()).unwrap_or_default();
+            if !callbacks.is_empty() {
+                let debounce = manager.debounce.get(&pin_num);
+                // Weak callbacks whose object was garbage collected are left out
+                let tables = callbacks.iter().filter_map(|callback| Some((callback, callback.target(py).ok()??))).map(|(callback, target)| {
+                    // The stored edge is the edge of the line, the edge asked for is inverted for active low pins
+                    let trigger_edge = match (callback.trigger_edge, pin.logic_level) {
+                        (TriggerEdge::BOTH, _) => "both",
+                        (TriggerEdge::RISING, LogicLevel::HIGH) | (TriggerEdge::FALLING, LogicLevel::LOW) => "rising",
+                        _ => "falling",
+                    };
+                    let mut table = vec![
+                        ("function", ConfigValue::Str(callable_name(target.bind(py)))),
+                        ("trigger_edge", ConfigValue::Str(trigger_edge.to_string())),
+                        ("include_trigger_time", ConfigValue::Bool(callback.send_time)),
+                        ("include_trigger_edge", ConfigValue::Bool(callback.send_edge)),
+                        ("include_pin_state", ConfigValue::Bool(callback.send_state)),
+                        ("priority", ConfigValue::Int(callback.priority as i64)),
+                        ("weak", ConfigValue::Bool(callback.weak)),
+                    ];
+                    if let Some(debounce) = debounce {
+                        table.push(("debounce_ms", ConfigValue::Float(debounce.period.as_secs_f64() * 1000f64)));
+                        table.push(("filter", ConfigValue::Str(debounce.filter.name().to_string())));
+                    }
+                    table
+                }).collect();
+                entry.push(("callbacks", ConfigValue::Tables(tables)));
+            }
+        } else if let Some(pwm) = manager.pwm_setup.get(&pin_num) {
+            entry.push(("mode", ConfigValue::Str("pwm".to_string())));
+            entry.push(("frequency_hz", ConfigValue::Float(pwm.frequency)));
+            entry.push(("duty_cycle", ConfigValue::Float(pwm.duty_cycle)));
+            entry.push(("logic_level", level_name(pwm.logic_level)));
+            entry.push(("start", ConfigValue::Bool(pwm.is_active)));
+        } else if let Some(pin_arc) = manager.output_pins.get(&pin_num) {
+            let (logic_level, output_mode, is_high) = {
+                let pin = pin_arc.lock().unwrap();
+                let is_high = match &pin.pin {
+                    PinType::Output(out_pin) => out_pin.lock().unwrap().is_set_high() == (pin.logic_level == LogicLevel::HIGH),
+                    _ => false,
+                };
+                (pin.logic_level, pin.output_mode, is_high)
+            };
+            let output_mode = match output_mode {
+                OutputMode::PUSH_PULL => "push_pull",
+                OutputMode::OPEN_DRAIN => "open_drain",
+                OutputMode::OPEN_SOURCE => "open_source",
+            };
+            entry.push(("mode", ConfigValue::Str("output".to_string())));
+            entry.push(("state", ConfigValue::Str(if is_high { "high" } else { "low" }.to_string())));
+            entry.push(("logic_level", level_name(logic_level)));
+            entry.push(("output_mode", ConfigValue::Str(output_mode.to_string())));
+        }
+        entries.push((name, entry));
+    }
+    Ok(entries)
+}
+
+
+/// Returns the configuration of the pins set up on the manager in the schema of load_config, writing it to a file when
+/// a path is given, with the format picked by the extension of the file.
+pub fn export_config<'py>(py: Python<'py>, gpio_manager: &GPIOManager, path: Option<&Bound<'py, PyAny>>) -> PyResult<Bound<'py, PyDict>> {
+    let entries = collect_entries(py, gpio_manager)?;
+    let pins = PyDict::new(py);
+    for (name, entry) in &entries {
+        pins.set_item(name, table_to_dict(py, entry)?)?;
+    }
+    let config = PyDict::new(py);
+    config.set_item("pins", pins)?;
+
+    let Some(path) = path else {
+        return Ok(config);
+    };
+    let path: String = py.import("os")?.call_method1("fspath", (path,))?.extract()?;
+    let extension = Path::new(&path).extension().and_then(|extension| extension.to_str()).unwrap_or_default().to_ascii_lowercase();
+    let text: String = match extension.as_str() {
+        "json" => {
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("indent", 2)?;
+            py.import("json")?.call_method("dumps", (&config,), Some(&kwargs))?.extract()?
+        }
+        "toml" => {
+            let tables: Vec<String> = entries.iter().map(|(name, entry)| {
+                let keys: Vec<String> = entry.iter().map(|(key, value)| format!("{} = {}\n", key, value.to_toml())).collect();
+                format!("[pins.{}]\n{}", toml_key(name), keys.concat())
+            }).collect();
+            tables.join("\n")
+        }
+        "yaml" | "yml" => {
+            let kwargs = PyDict::new(py);
+            kwargs.set_item("sort_keys", false)?;
+            py.import("yaml")
+              .map_err(|_| PyErr::new::<pyo3::exceptions::PyImportError, _>("Writing YAML needs the PyYAML package"))?
+              .call_method("safe_dump", (&config,), Some(&kwargs))?
+              .extract()?
+        }
+        _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The configuration must be a .toml, .json, .yaml or .yml file, The value {} does not meet this condition", path))),
+    };
+    std::fs::write(&path, text)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyOSError, _>(format!("Unable to write {}: {}", path, e)))?;
+    Ok(config)
+}
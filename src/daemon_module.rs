@@ -0,0 +1,317 @@
+use crate::event_server::{is_timeout, parse_frame, reply_to, run_command};
+use crate::gpio_module::GPIOManager;
+use crate::{log_module, PinState, TriggerEdge};
+use once_cell::sync::Lazy;
+use pyo3::{pyclass, pyfunction, pymethods, PyErr, PyResult};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+
+pub const DEFAULT_SOCKET: &str = "/run/gpio_manager.sock";
+// How often the threads check for new frames and for the daemon being stopped
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+// Frames buffered for every client, frames sent while the buffer is full are dropped
+const CLIENT_CAPACITY: usize = 1024;
+// The commands setting up a pin, which claim it for the client
+const SETUP_COMMANDS: [&str; 3] = ["add_input", "add_output", "setup_pwm"];
+
+
+/// The running daemon, each pin belongs to the client that set it up.
+struct Daemon {
+    id: u64,
+    /// The frames queued for every client by id.
+    clients: HashMap<u64, SyncSender<String>>,
+    /// The client owning every pin set up through the daemon.
+    owners: HashMap<u8, u64>,
+    stopped: Arc<AtomicBool>,
+}
+
+
+static DAEMON: Lazy<Mutex<Option<Daemon>>> = Lazy::new(|| Mutex::new(None));
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+
+/// Sends an edge of an input pin to the client owning the pin, if the daemon is running.
+pub fn publish_edge(pin_num: u8, edge: TriggerEdge, state: PinState, timestamp_ns: i128) {
+    let daemon = DAEMON.lock().unwrap();
+    let Some(daemon) = daemon.as_ref() else {
+        return;
+    };
+    if let Some(client) = daemon.owners.get(&pin_num).and_then(|owner| daemon.clients.get(owner)) {
+        let frame = json!({
+            "event": "edge",
+            "pin": pin_num,
+            "edge": if edge == TriggerEdge::RISING { "rising" } else { "falling" },
+            "state": state == PinState::HIGH,
+            "timestamp": timestamp_ns as f64 / 1_000_000_000f64,
+        });
+        if let Err(TrySendError::Full(_)) = client.try_send(frame.to_string()) {
+            log_module::debug(&format!("Dropped an edge of GPIO {}, a daemon client is not keeping up", pin_num));
+        }
+    }
+}
+
+
+/// Returns whether the pin was set up by a client of the daemon, whose edges are sent to the client.
+pub fn is_claimed(pin_num: u8) -> bool {
+    DAEMON.lock().unwrap().as_ref().is_some_and(|daemon| daemon.owners.contains_key(&pin_num))
+}
+
+
+/// Checks that the pin of a command is free or owned by the client, claiming it for setup commands. Returns whether
+/// the pin was claimed by this command.
+fn claim(daemon_id: u64, client_id: u64, command: &Value) -> PyResult<bool> {
+    let Some(pin_num) = command["pin"].as_u64().and_then(|pin_num| u8::try_from(pin_num).ok()) else {
+        return Ok(false);
+    };
+    let mut daemon = DAEMON.lock().unwrap();
+    let Some(daemon) = daemon.as_mut().filter(|daemon| daemon.id == daemon_id) else {
+        return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("The daemon was stopped"));
+    };
+    match daemon.owners.get(&pin_num) {
+        Some(owner) if *owner == client_id => Ok(false),
+        Some(_) => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("GPIO {} is used by another client of the daemon", pin_num))),
+        None if SETUP_COMMANDS.contains(&command["cmd"].as_str().unwrap_or_default()) => {
+            daemon.owners.insert(pin_num, client_id);
+            Ok(true)
+        }
+        None => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("GPIO {} was not set up by this client", pin_num))),
+    }
+}
+
+
+/// Gives up the pins of a client, or the given pin only.
+fn release(daemon_id: u64, client_id: u64, pin_num: Option<u8>) -> Vec<u8> {
+    let mut daemon = DAEMON.lock().unwrap();
+    let Some(daemon) = daemon.as_mut().filter(|daemon| daemon.id == daemon_id) else {
+        return Vec::new();
+    };
+    let released: Vec<u8> = daemon.owners
+                                  .iter()
+                                  .filter(|(pin, owner)| **owner == client_id && pin_num.is_none_or(|pin_num| pin_num == **pin))
+                                  .map(|(pin, _)| *pin)
+                                  .collect();
+    for pin in &released {
+        daemon.owners.remove(pin);
+    }
+    released
+}
+
+
+/// Runs a command of a client, keeping the pins of the other clients out of its reach.
+fn run_client_command(gpio_manager: &GPIOManager, daemon_id: u64, client_id: u64, command: &Value) -> PyResult<Value> {
+    let claimed = claim(daemon_id, client_id, command)?;
+    let result = run_command(gpio_manager, command);
+    let pin_num = command["pin"].as_u64().and_then(|pin_num| u8::try_from(pin_num).ok());
+    if (claimed && result.is_err()) || (command["cmd"] == "reset" && result.is_ok()) {
+        release(daemon_id, client_id, pin_num);
+    }
+    result
+}
+
+
+/// Serves a client until it disconnects or the daemon is stopped, sending it the edges of its pins and replying to
+/// its commands.
+fn serve_client(stream: UnixStream, frames: Receiver<String>, daemon_id: u64, client_id: u64, stopped: &AtomicBool) -> io::Result<()> {
+    let gpio_manager = GPIOManager::new_rust_reference();
+    stream.set_read_timeout(Some(POLL_INTERVAL))?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    while !stopped.load(Ordering::SeqCst) {
+        while let Ok(frame) = frames.try_recv() {
+            writeln!(writer, "{}", frame)?;
+        }
+        match reader.read_line(&mut line) {
+            Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+            Ok(_) if line.ends_with('\n') => {
+                let frame = std::mem::take(&mut line);
+                if frame.trim().is_empty() {
+                    continue;
+                }
+                let reply = match parse_frame(frame.trim()) {
+                    Ok(command) => reply_to(&command, run_client_command(&gpio_manager, daemon_id, client_id, &command)),
+                    Err(reply) => reply,
+                };
+                writeln!(writer, "{}", reply)?;
+            }
+            Ok(_) => {}
+            Err(e) if is_timeout(&e) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+
+/// Resets the pins of a client that disconnected, so they don't keep driving the hardware.
+fn clean_up_client(daemon_id: u64, client_id: u64) {
+    let gpio_manager = GPIOManager::new_rust_reference();
+    for pin_num in release(daemon_id, client_id, None) {
+        match gpio_manager.reset_pin(pin_num) {
+            Ok(()) => log_module::info(&format!("Reset GPIO {} of a daemon client that disconnected", pin_num)),
+            Err(e) => log_module::warning(&format!("Unable to reset GPIO {} of a daemon client that disconnected: {}", pin_num, e)),
+        }
+    }
+    if let Some(daemon) = DAEMON.lock().unwrap().as_mut().filter(|daemon| daemon.id == daemon_id) {
+        daemon.clients.remove(&client_id);
+    }
+}
+
+
+/// Removes the socket file left by a daemon that didn't stop cleanly, refusing to remove other files.
+fn remove_stale_socket(path: &str) -> PyResult<()> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => {
+            if UnixStream::connect(path).is_ok() {
+                return Err(PyErr::new::<pyo3::exceptions::PyOSError, _>(format!("A daemon is already listening on {}", path)));
+            }
+            fs::remove_file(path)?;
+            Ok(())
+        }
+        Ok(_) => Err(PyErr::new::<pyo3::exceptions::PyOSError, _>(format!("{} exists and is not a socket", path))),
+        Err(_) => Ok(()),
+    }
+}
+
+
+#[pyclass]
+/// Handle of the daemon started with serve_daemon.
+pub struct GPIODaemon {
+    id: u64,
+    path: String,
+    connected: Arc<AtomicUsize>,
+}
+
+
+#[pymethods]
+impl GPIODaemon {
+    /// The path of the socket the daemon listens on.
+    #[getter]
+    fn path(&self) -> String {
+        self.path.clone()
+    }
+
+    /// Returns the number of connected clients.
+    ///
+    /// Example usage:
+    /// ```daemon.client_count()```
+    ///
+    fn client_count(&self) -> usize {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    /// Stops the daemon, disconnecting its clients and resetting their pins, and removes its socket. Stopping a daemon
+    /// that was replaced or already stopped does nothing.
+    ///
+    /// Example usage:
+    /// ```daemon.stop()```
+    ///
+    fn stop(&self) {
+        let mut daemon = DAEMON.lock().unwrap();
+        if daemon.as_ref().is_some_and(|daemon| daemon.id == self.id) {
+            let stopped = daemon.take().unwrap();
+            stopped.stopped.store(true, Ordering::SeqCst);
+            drop(daemon);
+            let gpio_manager = GPIOManager::new_rust_reference();
+            for pin_num in stopped.owners.keys() {
+                let _ = gpio_manager.reset_pin(*pin_num);
+            }
+            gpio_manager.release_unobserved_pins();
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (path = DEFAULT_SOCKET, mode = 0o660))]
+/// Starts a daemon owning the GPIO hardware on behalf of other processes, which connect to its Unix socket with
+/// GPIOManager.connect("unix:<path>"). It runs in Rust threads so it keeps working while Python is busy. A daemon
+/// already running is replaced.
+///
+/// Every pin belongs to the client that set it up: the commands of the other clients on the pin are rejected, the
+/// edges of input pins are only sent to their owner, and the pins of a client are reset when it disconnects. I2C
+/// transfers of the clients are serialized by the daemon. Clients speak the protocol of the event server, see
+/// serve_events.
+///
+/// Parameters:
+/// - ```path``` (str): The path of the socket (default is "/run/gpio_manager.sock"). A socket left by a daemon that
+///   didn't stop cleanly is replaced.
+/// - ```mode``` (int): The permissions of the socket, the processes allowed to connect (default is 0o660, the owner
+///   and group of the daemon).
+///
+/// Returns:
+/// - ```GPIODaemon```: The handle of the daemon.
+///
+/// Input pins set up by the clients are debounced in hardware for 2 ms.
+///
+/// Example usage:
+/// ```python
+/// daemon = gpio_manager.serve_daemon()
+/// ```
+pub fn serve_daemon(path: &str, mode: u32) -> PyResult<GPIODaemon> {
+    remove_stale_socket(path)?;
+    let listener = UnixListener::bind(path)
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyOSError, _>(format!("Unable to listen on {}: {}", path, e)))?;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    listener.set_nonblocking(true)?;
+
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    let stopped = Arc::new(AtomicBool::new(false));
+    let connected = Arc::new(AtomicUsize::new(0));
+
+    let daemon = Daemon { id, clients: HashMap::new(), owners: HashMap::new(), stopped: Arc::clone(&stopped) };
+    let previous = DAEMON.lock().unwrap().replace(daemon);
+    if let Some(previous) = previous {
+        previous.stopped.store(true, Ordering::SeqCst);
+        let gpio_manager = GPIOManager::new_rust_reference();
+        for pin_num in previous.owners.keys() {
+            let _ = gpio_manager.reset_pin(*pin_num);
+        }
+    }
+
+    let thread_connected = Arc::clone(&connected);
+    let thread_stopped = Arc::clone(&stopped);
+    thread::spawn(move || {
+        let mut next_client_id = 0u64;
+        while !thread_stopped.load(Ordering::SeqCst) {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let client_id = next_client_id;
+                    next_client_id += 1;
+                    let (sender, frames) = sync_channel(CLIENT_CAPACITY);
+                    match DAEMON.lock().unwrap().as_mut().filter(|daemon| daemon.id == id) {
+                        Some(daemon) => daemon.clients.insert(client_id, sender),
+                        None => break,
+                    };
+                    let client_stopped = Arc::clone(&thread_stopped);
+                    let client_connected = Arc::clone(&thread_connected);
+                    thread::spawn(move || {
+                        let _ = stream.set_nonblocking(false);
+                        client_connected.fetch_add(1, Ordering::SeqCst);
+                        log_module::info(&format!("Daemon client {} connected", client_id));
+                        if let Err(e) = serve_client(stream, frames, id, client_id, &client_stopped) {
+                            log_module::info(&format!("Daemon client {} disconnected: {}", client_id, e));
+                        }
+                        clean_up_client(id, client_id);
+                        client_connected.fetch_sub(1, Ordering::SeqCst);
+                    });
+                }
+                Err(e) if is_timeout(&e) => thread::sleep(POLL_INTERVAL),
+                Err(e) => log_module::warning(&format!("The daemon failed to accept a client: {}", e)),
+            }
+        }
+    });
+    Ok(GPIODaemon { id, path: path.to_string(), connected })
+}
+
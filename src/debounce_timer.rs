@@ -0,0 +1,62 @@
+use crate::gpio_module::GPIOManager;
+use crate::timing::{self, TimedCondvar};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+
+/// Settles the debounce filters waiting for the line to hold its level, for all pins from a single thread. Every pin
+/// has at most one pending settle, the one of its latest edge.
+pub struct DebounceTimer {
+    pending: Mutex<HashMap<u8, (Instant, u64)>>,
+    changed: TimedCondvar,
+}
+
+
+pub static DEBOUNCE_TIMER: Lazy<Arc<DebounceTimer>> = Lazy::new(|| {
+    let timer = Arc::new(DebounceTimer {
+        pending: Mutex::new(HashMap::new()),
+        changed: TimedCondvar::default(),
+    });
+    let runner = Arc::clone(&timer);
+    timing::spawn(move || runner.run());
+    timer
+});
+
+
+impl DebounceTimer {
+    /// Settles the filter of a pin at the deadline unless a newer edge arrived, replacing its pending settle.
+    pub fn schedule(&self, pin_num: u8, generation: u64, deadline: Instant) {
+        self.pending.lock().unwrap().insert(pin_num, (deadline, generation));
+        self.changed.notify();
+    }
+
+    fn run(&self) {
+        loop {
+            let mut pending = self.pending.lock().unwrap();
+            let due = loop {
+                let now = timing::now();
+                let due: Vec<(u8, u64)> = pending.iter()
+                                                 .filter(|(_, (deadline, _))| *deadline <= now)
+                                                 .map(|(pin_num, (_, generation))| (*pin_num, *generation))
+                                                 .collect();
+                if !due.is_empty() {
+                    break due;
+                }
+                let deadline = pending.values().map(|(deadline, _)| *deadline).min();
+                pending = self.changed.wait(pending, deadline);
+            };
+            for (pin_num, _) in &due {
+                pending.remove(pin_num);
+            }
+            // The edges are dispatched without the pending settles locked, so the callbacks can't hold up new edges
+            drop(pending);
+
+            let manager = GPIOManager::new_rust_reference();
+            for (pin_num, generation) in due {
+                manager.settle_debounce(pin_num, generation);
+            }
+        }
+    }
+}
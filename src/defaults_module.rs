@@ -0,0 +1,63 @@
+use crate::gpio_module::GPIOManager;
+use crate::{log_module, metrics, EnumArg, InternPullResistorState, LogicLevel};
+use pyo3::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+
+// Whether the hook resetting the pins at exit is registered with atexit, it is only registered once
+static EXIT_HOOK_REGISTERED: AtomicBool = AtomicBool::new(false);
+
+
+#[pyfunction]
+#[pyo3(signature = (debounce_ms = None, pull = None, logic_level = None, reset_on_exit = None))]
+/// Sets the arguments the GPIOManager falls back to when they are omitted, the arguments left as None keep their
+/// current default. The defaults apply to the pins set up after the call.
+///
+/// Parameters:
+/// - ```debounce_ms``` (float): The debounce time of assign_callback in milliseconds (default is 2).
+/// - ```pull``` (InternPullResistorState): The pull resistor of add_input_pin and set_direction (default is AUTO).
+/// - ```logic_level``` (LogicLevel): The logic level of add_input_pin, add_output_pin and setup_pwm (default is HIGH).
+/// - ```reset_on_exit``` (bool): Whether the pins are reset with cleanup when the interpreter exits (default is False).
+///
+/// Example usage:
+/// ```gpio_manager.set_defaults(pull="pullup", logic_level="low", reset_on_exit=True)```
+pub fn set_defaults(py: Python, debounce_ms: Option<f64>, pull: Option<EnumArg<InternPullResistorState>>, logic_level: Option<EnumArg<LogicLevel>>,
+                    reset_on_exit: Option<bool>) -> PyResult<()> {
+    if let Some(debounce_ms) = debounce_ms {
+        if !debounce_ms.is_finite() || debounce_ms < 0f64 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Debounce time must be a positive number of milliseconds, The value {} does not meet this condition", debounce_ms)));
+        }
+    }
+    if reset_on_exit == Some(true) && !EXIT_HOOK_REGISTERED.swap(true, Ordering::SeqCst) {
+        py.import("atexit")?.call_method1("register", (wrap_pyfunction!(reset_at_exit, py)?,))?;
+    }
+    let manager = GPIOManager::new_rust_reference().get_manager();
+    let mut manager = metrics::lock(&manager);
+    let defaults = &mut manager.defaults;
+    if let Some(debounce_ms) = debounce_ms {
+        defaults.debounce_ms = debounce_ms;
+    }
+    if let Some(pull) = pull {
+        defaults.pull = pull.0;
+    }
+    if let Some(logic_level) = logic_level {
+        defaults.logic_level = logic_level.0;
+    }
+    if let Some(reset_on_exit) = reset_on_exit {
+        defaults.reset_on_exit = reset_on_exit;
+    }
+    Ok(())
+}
+
+
+#[pyfunction]
+/// Resets the pins when the interpreter exits, unless reset_on_exit was switched off again since the hook was registered.
+fn reset_at_exit() -> PyResult<()> {
+    let gpio_manager = GPIOManager::new_rust_reference();
+    if !metrics::lock(&gpio_manager.get_manager()).defaults.reset_on_exit {
+        return Ok(());
+    }
+    log_module::info("Resetting the pins at exit");
+    gpio_manager.cleanup()
+}
@@ -0,0 +1,177 @@
+use crate::backend;
+use crate::board_module::{board_info, Peripheral, MAX_HEADER_GPIO};
+use crate::gpio_module::GPIOManager;
+use crate::overlay_module::{enable_hint, interface_devices, interface_status};
+use crate::{metrics, pinctrl, release_gil};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fs;
+
+
+// The device nodes the library opens, by the prefix of their name in /dev, and the group that owns them on Raspberry Pi OS
+const DEVICE_GROUPS: [(&str, &str); 4] = [("gpiomem", "gpio"), ("gpiochip", "gpio"), ("i2c-", "i2c"), ("spidev", "spi")];
+
+
+/// Returns the names of the groups of the process, read from /proc/self/status and /etc/group.
+fn group_names() -> Vec<String> {
+    let status = fs::read_to_string("/proc/self/status").unwrap_or_default();
+    let mut gids: Vec<&str> = Vec::new();
+    for line in status.lines() {
+        if let Some(supplementary) = line.strip_prefix("Groups:") {
+            gids.extend(supplementary.split_whitespace());
+        } else if let Some(primary) = line.strip_prefix("Gid:").and_then(|ids| ids.split_whitespace().next()) {
+            // The line lists the real, effective, saved and filesystem group ids
+            gids.push(primary);
+        }
+    }
+    // /etc/group has lines such as "gpio:x:997:pi"
+    let groups = fs::read_to_string("/etc/group").unwrap_or_default();
+    groups.lines()
+          .filter_map(|line| {
+              let fields: Vec<&str> = line.split(':').collect();
+              (fields.len() > 2 && gids.contains(&fields[2])).then(|| fields[0].to_string())
+          })
+          .collect()
+}
+
+
+/// Returns whether the process can open a path with the access mode, one of the libc `R_OK` or `W_OK`.
+fn accessible(path: &str, mode: libc::c_int) -> bool {
+    match CString::new(path) {
+        Ok(path) => unsafe { libc::access(path.as_ptr(), mode) == 0 },
+        Err(_) => false,
+    }
+}
+
+
+/// Returns the paths of the PWM channels exported through sysfs.
+fn exported_pwm_channels() -> Vec<String> {
+    let mut channels = Vec::new();
+    for chip in interface_devices(Peripheral::Pwm) {
+        if let Ok(entries) = fs::read_dir(&chip) {
+            let mut names: Vec<String> = entries.filter_map(|entry| entry.ok())
+                                                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                                                .filter(|name| name.starts_with("pwm"))
+                                                .collect();
+            names.sort();
+            channels.extend(names.into_iter().map(|name| format!("{}/{}", chip, name)));
+        }
+    }
+    channels
+}
+
+
+#[pyfunction]
+/// Checks the environment the library runs in and returns a report, to find the setup issues that keep pins from
+/// working: an undetected board, missing permissions, an unusable pinctrl, disabled interfaces and pins held by the
+/// kernel or other processes.
+///
+/// Returns:
+/// - `dict`: The report, with the keys:
+///   - `board` (dict or None): The result of board_info, None when the board can't be detected.
+///   - `backend` (str): The backend in use.
+///   - `user` (dict): The `uid` and `groups` of the process.
+///   - `devices` (dict): For every GPIO, I2C and SPI device node by path, whether it is `readable` and `writable`.
+///   - `pinctrl` (dict): Whether the embedded pinctrl is `available`, with the `error` when it isn't.
+///   - `pwm` (dict): The PWM `chips` and the `exported` channels of the PWM sysfs interface.
+///   - `interfaces` (dict): The result of interface_status.
+///   - `conflicts` (dict): The claim of every header pin held by the kernel or another process, by pin number.
+///   - `problems` (list[str]): The issues found, with how to fix them, empty when the environment looks right.
+///
+/// Example usage:
+/// ```python
+/// for problem in gpio_manager.diagnose()["problems"]:
+///     print(problem)
+/// ```
+pub fn diagnose(py: Python) -> PyResult<Bound<PyDict>> {
+    let report = PyDict::new(py);
+    let mut problems = Vec::new();
+
+    match board_info(py) {
+        Ok(board) => report.set_item("board", board)?,
+        Err(e) => {
+            report.set_item("board", py.None())?;
+            problems.push(format!("The board couldn't be detected ({}), the rppal backend only runs on a Raspberry Pi", e));
+        }
+    }
+    report.set_item("backend", backend::current().name())?;
+
+    let uid = unsafe { libc::geteuid() };
+    let groups = group_names();
+    let user = PyDict::new(py);
+    user.set_item("uid", uid)?;
+    user.set_item("groups", groups.clone())?;
+    report.set_item("user", user)?;
+
+    let devices = PyDict::new(py);
+    let mut dev_names: Vec<String> = fs::read_dir("/dev").map(|entries| entries.filter_map(|entry| entry.ok())
+                                                                            .map(|entry| entry.file_name().to_string_lossy().to_string())
+                                                                            .collect())
+                                                         .unwrap_or_default();
+    dev_names.sort();
+    for name in dev_names {
+        let group = match DEVICE_GROUPS.iter().find(|(prefix, _)| name.starts_with(prefix)) {
+            Some((_, group)) => *group,
+            None => continue,
+        };
+        let path = format!("/dev/{}", name);
+        let (readable, writable) = (accessible(&path, libc::R_OK), accessible(&path, libc::W_OK));
+        if !(readable && writable) {
+            let fix = if uid != 0 && !groups.iter().any(|name| name == group) {
+                format!("add the user to the {} group with `sudo usermod -aG {} $USER` and log in again", group, group)
+            } else {
+                format!("check that {} is owned by the {} group and writable by it", path, group)
+            };
+            problems.push(format!("{} isn't accessible, {}", path, fix));
+        }
+        let device = PyDict::new(py);
+        device.set_item("readable", readable)?;
+        device.set_item("writable", writable)?;
+        devices.set_item(path, device)?;
+    }
+    report.set_item("devices", devices)?;
+
+    let pinctrl_status = PyDict::new(py);
+    match release_gil(|| pinctrl::query_pinctrl(&["get", "0"])) {
+        Ok(_) => {
+            pinctrl_status.set_item("available", true)?;
+            pinctrl_status.set_item("error", py.None())?;
+        }
+        Err(e) => {
+            pinctrl_status.set_item("available", false)?;
+            pinctrl_status.set_item("error", e.to_string())?;
+            problems.push(format!("The embedded pinctrl can't run ({}), alternate functions and pulls only work through the GPIO registers", e));
+        }
+    }
+    report.set_item("pinctrl", pinctrl_status)?;
+
+    let pwm_chips = interface_devices(Peripheral::Pwm);
+    if pwm_chips.is_empty() {
+        problems.push(format!("The PWM sysfs interface is missing, hardware PWM needs it: {}", enable_hint(Peripheral::Pwm)));
+    }
+    let pwm = PyDict::new(py);
+    pwm.set_item("chips", pwm_chips)?;
+    pwm.set_item("exported", exported_pwm_channels())?;
+    report.set_item("pwm", pwm)?;
+
+    report.set_item("interfaces", interface_status(py)?)?;
+
+    // The pins set up by this process are held by it, they aren't conflicts
+    let gpio_manager = GPIOManager::new_rust_reference();
+    let manager = gpio_manager.get_manager();
+    let own_pins: Vec<u8> = {
+        let manager = metrics::lock(&manager);
+        (0..=MAX_HEADER_GPIO).filter(|pin_num| gpio_manager.is_input_pin(*pin_num, &manager) || gpio_manager.is_output_pin(*pin_num, &manager))
+                             .collect()
+    };
+    let backend = backend::current();
+    let conflicts: HashMap<u8, String> = (0..=MAX_HEADER_GPIO).filter(|pin_num| !own_pins.contains(pin_num))
+                                                              .filter_map(|pin_num| backend.claimed_by(pin_num).map(|claim| (pin_num, claim)))
+                                                              .collect();
+    report.set_item("conflicts", conflicts)?;
+
+    report.set_item("problems", problems)?;
+    Ok(report)
+}
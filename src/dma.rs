@@ -0,0 +1,456 @@
+use crate::board_module::device_info;
+use crate::error::{GpioError, Result};
+use crate::pads::RegisterBlock;
+use libc::{c_ulong, ioctl};
+use once_cell::sync::Lazy;
+use rppal::system::SoC;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::sync::atomic::{fence, Ordering};
+use std::sync::{Mutex, Once};
+use std::thread::sleep;
+use std::time::Duration;
+
+
+// Waveforms are played by a DMA channel writing the GPIO set and clear registers, the way pigpio does. The delays are
+// paced by the PWM serializer, which takes one word from its FIFO per tick and holds the DMA back while the FIFO is
+// full, so the timing doesn't depend on the CPU.
+const PATH_DEV_MEM: &str = "/dev/mem";
+const PATH_DEV_VCIO: &str = "/dev/vcio";
+
+// Offsets of the peripherals from the peripheral base, the DMA controller reaches them on the VideoCore bus
+const BUS_PERIPHERAL_BASE: u32 = 0x7e00_0000;
+const DMA_OFFSET: usize = 0x0000_7000;
+const CLOCK_OFFSET: usize = 0x0010_1000;
+const GPIO_OFFSET: usize = 0x0020_0000;
+const PWM_OFFSET: usize = 0x0020_c000;
+const PERIPHERAL_LEN: usize = 0x1000;
+
+const GPSET0: usize = 0x1c;
+const GPCLR0: usize = 0x28;
+
+// A lite channel left free by the firmware and the kernel, lite channels transfer up to 65535 bytes per control block
+const DMA_CHANNEL: usize = 10;
+const DMA_CS: usize = DMA_CHANNEL * 0x100;
+const DMA_CONBLK_AD: usize = DMA_CHANNEL * 0x100 + 0x04;
+const DMA_DEBUG: usize = DMA_CHANNEL * 0x100 + 0x20;
+const DMA_ENABLE: usize = 0xff0;
+const DMA_CS_ACTIVE: u32 = 1 << 0;
+const DMA_CS_END: u32 = 1 << 1;
+const DMA_CS_INT: u32 = 1 << 2;
+const DMA_CS_PRIORITY: u32 = 8 << 16;
+const DMA_CS_PANIC_PRIORITY: u32 = 8 << 20;
+const DMA_CS_WAIT_FOR_WRITES: u32 = 1 << 28;
+const DMA_CS_RESET: u32 = 1 << 31;
+const DMA_DEBUG_CLEAR_ERRORS: u32 = 0x7;
+
+const TI_WAIT_RESP: u32 = 1 << 3;
+const TI_DEST_DREQ: u32 = 1 << 6;
+const TI_PERMAP_PWM: u32 = 5 << 16;
+const TI_NO_WIDE_BURSTS: u32 = 1 << 26;
+
+const PWM_CTL: usize = 0x00;
+const PWM_STA: usize = 0x04;
+const PWM_DMAC: usize = 0x08;
+const PWM_RNG1: usize = 0x10;
+const PWM_FIF1: usize = 0x18;
+const PWM_CTL_PWEN1: u32 = 1 << 0;
+const PWM_CTL_MODE1: u32 = 1 << 1;
+const PWM_CTL_USEF1: u32 = 1 << 5;
+const PWM_CTL_CLRF1: u32 = 1 << 6;
+const PWM_CTL_PWEN2: u32 = 1 << 8;
+const PWM_CTL_PACING: u32 = PWM_CTL_USEF1 | PWM_CTL_MODE1 | PWM_CTL_PWEN1;
+const PWM_DMAC_ENAB: u32 = 1 << 31;
+const PWM_DMAC_THRESHOLDS: u32 = (15 << 8) | 15;
+
+const CM_PWMCTL: usize = 0xa0;
+const CM_PWMDIV: usize = 0xa4;
+const CM_PASSWORD: u32 = 0x5a00_0000;
+const CM_SRC_PLLD: u32 = 6;
+const CM_ENAB: u32 = 1 << 4;
+const CM_KILL: u32 = 1 << 5;
+const CM_BUSY: u32 = 1 << 7;
+const CM_DIVIDER: u32 = 5;
+
+// Memory of the VideoCore, allocated through the mailbox so the DMA controller can read it without the CPU caches
+const TAG_ALLOCATE_MEMORY: u32 = 0x0003_000c;
+const TAG_LOCK_MEMORY: u32 = 0x0003_000d;
+const TAG_UNLOCK_MEMORY: u32 = 0x0003_000e;
+const TAG_RELEASE_MEMORY: u32 = 0x0003_000f;
+const MEM_FLAG_DIRECT: u32 = 0x04;
+const MEM_FLAG_L1_NONALLOCATING: u32 = 0x0c;
+const MBOX_SUCCESS: u32 = 0x8000_0000;
+const PAGE_SIZE: usize = 4096;
+
+/// The resolution of the delays of a DMA program.
+pub const TICK: Duration = Duration::from_nanos(200);
+
+const BLOCK_SIZE: usize = 32;
+const MAX_PACING_WORDS: u64 = 16383;
+// Control blocks of a program, 8 MB of VideoCore memory
+pub const MAX_BLOCKS: usize = 1 << 18;
+// Ticks paced before the program starts, so the FIFO is full and the first delay is as long as the following ones
+const PRIMING_TICKS: u64 = 32;
+
+
+/// The `_IOWR(100, 0, char *)` request of the mailbox property interface.
+fn mbox_property_request() -> c_ulong {
+    (3 << 30) | ((std::mem::size_of::<*mut u8>() as c_ulong) << 16) | (100 << 8)
+}
+
+
+struct Mailbox {
+    file: File,
+}
+
+
+impl Mailbox {
+    fn open() -> io::Result<Self> {
+        Ok(Self { file: OpenOptions::new().read(true).write(true).open(PATH_DEV_VCIO)? })
+    }
+
+    /// Sends a single tag to the firmware and returns the first word of its response.
+    fn property(&self, tag: u32, args: &[u32]) -> io::Result<u32> {
+        let mut buf = [0u32; 16];
+        let words = 6 + args.len();
+        buf[0] = (words * 4) as u32;
+        buf[2] = tag;
+        buf[3] = (args.len() * 4) as u32;
+        buf[4] = (args.len() * 4) as u32;
+        buf[5..5 + args.len()].copy_from_slice(args);
+        if unsafe { ioctl(self.file.as_raw_fd(), mbox_property_request() as _, buf.as_mut_ptr()) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if buf[1] != MBOX_SUCCESS {
+            return Err(io::Error::other(format!("The firmware rejected the mailbox request 0x{:x}", tag)));
+        }
+        Ok(buf[5])
+    }
+}
+
+
+/// A block of uncached VideoCore memory mapped into the process.
+struct GpuMemory {
+    mailbox: Mailbox,
+    handle: u32,
+    bus_address: u32,
+    mapped: Option<RegisterBlock>,
+}
+
+
+impl GpuMemory {
+    fn allocate(len: usize, soc: SoC) -> io::Result<Self> {
+        let mailbox = Mailbox::open()?;
+        // The BCM2835 has no uncached alias that bypasses its L2 cache
+        let flags = if soc == SoC::Bcm2835 { MEM_FLAG_L1_NONALLOCATING } else { MEM_FLAG_DIRECT };
+        let handle = mailbox.property(TAG_ALLOCATE_MEMORY, &[len as u32, PAGE_SIZE as u32, flags])?;
+        if handle == 0 {
+            return Err(io::Error::other(format!("The VideoCore couldn't allocate {} bytes, gpu_mem may be too small", len)));
+        }
+        let mut memory = Self { mailbox, handle, bus_address: 0, mapped: None };
+        memory.bus_address = memory.mailbox.property(TAG_LOCK_MEMORY, &[handle])?;
+        if memory.bus_address == 0 {
+            return Err(io::Error::other("The VideoCore couldn't lock the memory of the DMA program"));
+        }
+        memory.mapped = Some(RegisterBlock::map(PATH_DEV_MEM, (memory.bus_address & 0x3fff_ffff) as usize, len)?);
+        Ok(memory)
+    }
+
+    fn write(&self, offset: usize, value: u32) {
+        self.mapped.as_ref().unwrap().write(offset, value);
+    }
+}
+
+
+impl Drop for GpuMemory {
+    fn drop(&mut self) {
+        self.mapped.take();
+        if self.bus_address != 0 {
+            let _ = self.mailbox.property(TAG_UNLOCK_MEMORY, &[self.handle]);
+        }
+        let _ = self.mailbox.property(TAG_RELEASE_MEMORY, &[self.handle]);
+    }
+}
+
+
+/// The DMA, PWM and clock manager registers of the BCM2835 to BCM2711.
+struct Hardware {
+    dma: RegisterBlock,
+    pwm: RegisterBlock,
+    clock: RegisterBlock,
+    soc: SoC,
+}
+
+
+impl Hardware {
+    fn map() -> Result<Self> {
+        let soc = device_info()?.soc();
+        let base = match soc {
+            SoC::Bcm2835 => 0x2000_0000,
+            SoC::Bcm2836 | SoC::Bcm2837A1 | SoC::Bcm2837B0 => 0x3f00_0000,
+            SoC::Bcm2711 => 0xfe00_0000,
+            SoC::Bcm2712 => {
+                return Err(GpioError::Hardware("DMA waveforms need the DMA controller of the BCM2835 to BCM2711, the RP1 of the Raspberry Pi 5 can't \
+                                                drive its GPIO pins by DMA".to_string()));
+            }
+            soc => return Err(GpioError::Hardware(format!("DMA waveforms aren't supported on the {}", soc))),
+        };
+        let map = |offset: usize| {
+            RegisterBlock::map(PATH_DEV_MEM, base + offset, PERIPHERAL_LEN)
+                .map_err(|e| GpioError::Hardware(format!("Unable to access the DMA controller through {}, root access is required: {}", PATH_DEV_MEM, e)))
+        };
+        Ok(Self { dma: map(DMA_OFFSET)?, pwm: map(PWM_OFFSET)?, clock: map(CLOCK_OFFSET)?, soc })
+    }
+
+    /// Stops the channel, the control block being run is abandoned.
+    fn reset(&self) {
+        self.dma.write(DMA_CS, DMA_CS_RESET);
+        sleep(Duration::from_micros(10));
+    }
+
+    /// Starts the PWM serializer consuming one FIFO word per tick, fed by the DMA.
+    fn start_pacing(&self) -> Result<()> {
+        let ctl = self.pwm.read(PWM_CTL);
+        if ctl & (PWM_CTL_PWEN1 | PWM_CTL_PWEN2) != 0 && ctl != PWM_CTL_PACING {
+            return Err(GpioError::Hardware("The PWM is in use by hardware PWM or analog audio, DMA waveforms need it to pace their delays".to_string()));
+        }
+        self.pwm.write(PWM_CTL, 0);
+        sleep(Duration::from_micros(10));
+        self.pwm.write(PWM_STA, u32::MAX);
+
+        self.clock.write(CM_PWMCTL, CM_PASSWORD | CM_KILL);
+        while self.clock.read(CM_PWMCTL) & CM_BUSY != 0 {
+            sleep(Duration::from_micros(10));
+        }
+        self.clock.write(CM_PWMDIV, CM_PASSWORD | (CM_DIVIDER << 12));
+        self.clock.write(CM_PWMCTL, CM_PASSWORD | CM_SRC_PLLD);
+        self.clock.write(CM_PWMCTL, CM_PASSWORD | CM_SRC_PLLD | CM_ENAB);
+
+        // PLLD runs at 750 MHz on the BCM2711 and 500 MHz on the older SoCs, a word lasts one tick of serialized bits
+        let plld_hz: u64 = if self.soc == SoC::Bcm2711 { 750_000_000 } else { 500_000_000 };
+        let bits = plld_hz / CM_DIVIDER as u64 * TICK.as_nanos() as u64 / 1_000_000_000;
+        self.pwm.write(PWM_RNG1, bits as u32);
+        self.pwm.write(PWM_DMAC, PWM_DMAC_ENAB | PWM_DMAC_THRESHOLDS);
+        self.pwm.write(PWM_CTL, PWM_CTL_CLRF1);
+        sleep(Duration::from_micros(10));
+        self.pwm.write(PWM_CTL, PWM_CTL_PACING);
+        Ok(())
+    }
+
+    fn start(&self, first_block: u32) {
+        self.dma.write(DMA_ENABLE, self.dma.read(DMA_ENABLE) | 1 << DMA_CHANNEL);
+        self.reset();
+        self.dma.write(DMA_CS, DMA_CS_INT | DMA_CS_END);
+        self.dma.write(DMA_CONBLK_AD, first_block);
+        self.dma.write(DMA_DEBUG, DMA_DEBUG_CLEAR_ERRORS);
+        self.dma.write(DMA_CS, DMA_CS_WAIT_FOR_WRITES | DMA_CS_PANIC_PRIORITY | DMA_CS_PRIORITY | DMA_CS_ACTIVE);
+    }
+}
+
+
+static HARDWARE: Lazy<std::result::Result<Hardware, String>> = Lazy::new(|| Hardware::map().map_err(|e| e.to_string()));
+
+
+fn hardware() -> Result<&'static Hardware> {
+    HARDWARE.as_ref().map_err(|e| GpioError::Hardware(e.clone()))
+}
+
+
+/// Returns whether DMA programs can be played on this board, the RP1 of the Raspberry Pi 5 has no DMA access to its
+/// pins.
+pub fn available() -> Result<()> {
+    hardware().map(|_| ())
+}
+
+
+#[derive(Clone, Copy)]
+enum Block {
+    Set(u32),
+    Clear(u32),
+    Pace(u32),
+}
+
+
+/// A sequence of DMA control blocks setting and clearing GPIO 0 to 31, with the delays between them.
+#[derive(Default)]
+pub struct Program {
+    blocks: Vec<Block>,
+    loop_start: Option<usize>,
+    // The delays added so far and the ticks paced for them, so rounding to ticks doesn't drift
+    elapsed: Duration,
+    ticks: u64,
+}
+
+
+impl Program {
+    /// The number of control blocks, which is the index of the next block added.
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    fn push(&mut self, block: Block) -> Result<()> {
+        if self.blocks.len() >= MAX_BLOCKS {
+            return Err(GpioError::InvalidArgument(format!("The waveform is too long to be played by DMA, it needs more than {} control blocks. Loops \
+                                                           played a finite number of times are unrolled, play long loops forever and stop them instead",
+                                                          MAX_BLOCKS)));
+        }
+        self.blocks.push(block);
+        Ok(())
+    }
+
+    /// Appends a pulse: the pins of `on_mask` are set high, the other pins of `off_mask` low, then the delay follows.
+    pub fn push_pulse(&mut self, on_mask: u32, off_mask: u32, delay: Duration) -> Result<()> {
+        if on_mask != 0 {
+            self.push(Block::Set(on_mask))?;
+        }
+        if off_mask & !on_mask != 0 {
+            self.push(Block::Clear(off_mask & !on_mask))?;
+        }
+        self.elapsed += delay;
+        let ticks = ((self.elapsed.as_nanos() + TICK.as_nanos() / 2) / TICK.as_nanos()) as u64;
+        let mut remaining = ticks - self.ticks;
+        self.ticks = ticks;
+        while remaining > 0 {
+            let words = remaining.min(MAX_PACING_WORDS);
+            self.push(Block::Pace(words as u32))?;
+            remaining -= words;
+        }
+        Ok(())
+    }
+
+    /// Returns whether the blocks from `start` on have a delay, a loop without one would flood the bus.
+    pub fn has_delay_from(&self, start: usize) -> bool {
+        self.blocks[start..].iter().any(|block| matches!(block, Block::Pace(_)))
+    }
+
+    /// Makes the program jump back to the block at `start` after its last block, playing the blocks from there forever.
+    pub fn loop_from(&mut self, start: usize) {
+        self.loop_start = Some(start);
+    }
+}
+
+
+/// The program being played and the memory holding its control blocks.
+struct Transfer {
+    memory: GpuMemory,
+}
+
+
+static ACTIVE: Lazy<Mutex<Option<Transfer>>> = Lazy::new(|| Mutex::new(None));
+
+
+extern "C" fn stop_at_exit() {
+    // A transfer left running would keep toggling the pins from memory nobody frees
+    if let (Ok(mut active), Ok(hardware)) = (ACTIVE.try_lock(), hardware()) {
+        hardware.reset();
+        active.take();
+    }
+}
+
+
+/// Plays a program, replacing the one being played. Block 0 of the program starts after a short priming delay.
+pub fn start(program: &Program) -> Result<()> {
+    static REGISTER_EXIT: Once = Once::new();
+    let hardware = hardware()?;
+    let mut active = ACTIVE.lock().unwrap();
+    hardware.reset();
+    active.take();
+    hardware.start_pacing()?;
+    REGISTER_EXIT.call_once(|| unsafe {
+        libc::atexit(stop_at_exit);
+    });
+
+    let mut blocks = vec![Block::Pace(PRIMING_TICKS as u32)];
+    blocks.extend_from_slice(&program.blocks);
+    let len = (blocks.len() * BLOCK_SIZE).div_ceil(PAGE_SIZE) * PAGE_SIZE;
+    let memory = GpuMemory::allocate(len, hardware.soc)
+        .map_err(|e| GpioError::Hardware(format!("Unable to allocate the memory of the DMA program through {}: {}", PATH_DEV_VCIO, e)))?;
+
+    let block_address = |index: usize| memory.bus_address + (index * BLOCK_SIZE) as u32;
+    for (index, block) in blocks.iter().enumerate() {
+        let offset = index * BLOCK_SIZE;
+        // The word written or paced is kept in the reserved words at the end of the control block
+        let (ti, dest, length, data) = match *block {
+            Block::Set(mask) => (TI_NO_WIDE_BURSTS | TI_WAIT_RESP, BUS_PERIPHERAL_BASE + (GPIO_OFFSET + GPSET0) as u32, 4, mask),
+            Block::Clear(mask) => (TI_NO_WIDE_BURSTS | TI_WAIT_RESP, BUS_PERIPHERAL_BASE + (GPIO_OFFSET + GPCLR0) as u32, 4, mask),
+            Block::Pace(words) => (TI_NO_WIDE_BURSTS | TI_WAIT_RESP | TI_DEST_DREQ | TI_PERMAP_PWM, BUS_PERIPHERAL_BASE + (PWM_OFFSET + PWM_FIF1) as u32,
+                                   words * 4, 0),
+        };
+        let next = if index + 1 < blocks.len() {
+            block_address(index + 1)
+        } else {
+            program.loop_start.map_or(0, |start| block_address(start + 1))
+        };
+        for (word, value) in [ti, block_address(index) + 24, dest, length, 0, next, data, 0].into_iter().enumerate() {
+            memory.write(offset + word * 4, value);
+        }
+    }
+    fence(Ordering::SeqCst);
+    hardware.start(block_address(0));
+    *active = Some(Transfer { memory });
+    Ok(())
+}
+
+
+/// Returns the index of the block of the program being played, None once the program ended or was stopped.
+pub fn position() -> Option<usize> {
+    let active = ACTIVE.lock().unwrap();
+    let transfer = active.as_ref()?;
+    let address = hardware().ok()?.dma.read(DMA_CONBLK_AD);
+    if address == 0 {
+        return None;
+    }
+    // Block 0 of the program follows the priming block
+    Some(((address - transfer.memory.bus_address) as usize / BLOCK_SIZE).saturating_sub(1))
+}
+
+
+/// Stops the program being played, the pins keep their last level.
+pub fn stop() {
+    let mut active = ACTIVE.lock().unwrap();
+    if active.is_some() {
+        if let Ok(hardware) = hardware() {
+            hardware.reset();
+        }
+        active.take();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paced(program: &Program) -> Vec<u32> {
+        program.blocks.iter().filter_map(|block| if let Block::Pace(words) = block { Some(*words) } else { None }).collect()
+    }
+
+    #[test]
+    fn delays_are_rounded_without_drift() {
+        let mut program = Program::default();
+        for _ in 0..10 {
+            program.push_pulse(1, 0, Duration::from_nanos(300)).unwrap();
+        }
+        // 10 delays of 1.5 ticks are paced as 15 ticks, alternating between 2 and 1
+        assert_eq!(paced(&program).iter().sum::<u32>(), 15);
+        assert_eq!(&paced(&program)[..4], &[2, 1, 2, 1]);
+    }
+
+    #[test]
+    fn long_delays_are_split() {
+        let mut program = Program::default();
+        program.push_pulse(0, 0, Duration::from_millis(10)).unwrap();
+        assert_eq!(paced(&program), vec![16383, 16383, 16383, 851]);
+    }
+
+    #[test]
+    fn pins_set_high_are_not_cleared() {
+        let mut program = Program::default();
+        program.push_pulse(0b011, 0b110, Duration::ZERO).unwrap();
+        assert!(matches!(program.blocks[..], [Block::Set(0b011), Block::Clear(0b100)]));
+        assert!(!program.has_delay_from(0));
+        program.push_pulse(0, 0b001, TICK).unwrap();
+        assert!(program.has_delay_from(2));
+    }
+}
@@ -0,0 +1,265 @@
+use crate::i2c_module::{extract_bytes, with_buses, I2CBuses, I2CManager};
+use crate::ms_to_duration_checked;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use pyo3::{pyclass, pymethods, PyErr, PyResult, Python};
+use rppal::i2c::I2c;
+use std::sync::{Arc, Mutex};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+
+// Largest chunk read in a single combined transfer, i2c-dev rejects messages above 8192 bytes
+const MAX_READ_CHUNK: usize = 4096;
+
+
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Enum representing the supported AT24Cxx EEPROM models.
+pub enum EEPROMModel {
+    AT24C02,
+    AT24C04,
+    AT24C08,
+    AT24C16,
+    AT24C32,
+    AT24C64,
+    AT24C128,
+    AT24C256,
+    AT24C512,
+}
+
+
+impl EEPROMModel {
+    /// Returns the size in bytes, the page size in bytes and the number of memory address bytes of the model.
+    fn geometry(&self) -> (usize, usize, usize) {
+        match self {
+            EEPROMModel::AT24C02 => (256, 8, 1),
+            EEPROMModel::AT24C04 => (512, 16, 1),
+            EEPROMModel::AT24C08 => (1024, 16, 1),
+            EEPROMModel::AT24C16 => (2048, 16, 1),
+            EEPROMModel::AT24C32 => (4096, 32, 2),
+            EEPROMModel::AT24C64 => (8192, 32, 2),
+            EEPROMModel::AT24C128 => (16384, 64, 2),
+            EEPROMModel::AT24C256 => (32768, 64, 2),
+            EEPROMModel::AT24C512 => (65536, 128, 2),
+        }
+    }
+}
+
+
+#[pyclass]
+/// EEPROM provides page boundary aware access to AT24Cxx I2C EEPROMs on the bus opened by the I2CManager.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// i2c_manager = gpio_manager.I2CManager()
+/// i2c_manager.open(bus=1)
+/// eeprom = gpio_manager.EEPROM(gpio_manager.EEPROMModel.AT24C32, addr=0x50)
+/// eeprom.write(0x0010, b'hello world')
+/// data = eeprom.read(0x0010, 11)
+/// ```
+#[allow(clippy::upper_case_acronyms)]
+pub struct EEPROM {
+    i2c: Arc<Mutex<I2CBuses>>,
+    bus: Option<u8>,
+    addr: u16,
+    size: usize,
+    page_size: usize,
+    addr_bytes: usize,
+    write_timeout: Duration,
+}
+
+
+impl EEPROM {
+    /// Returns the device address and the memory address bytes for the given memory address.
+    ///
+    /// Models with a single address byte and more than 256 bytes of memory select the block using the low bits of the
+    /// device address.
+    fn address_for(&self, mem_addr: usize) -> (u16, Vec<u8>) {
+        if self.addr_bytes == 1 {
+            (self.addr | (mem_addr >> 8) as u16, vec![(mem_addr & 0xFF) as u8])
+        } else {
+            (self.addr, vec![(mem_addr >> 8) as u8, (mem_addr & 0xFF) as u8])
+        }
+    }
+
+    fn check_range(&self, mem_addr: usize, length: usize) -> PyResult<()> {
+        if mem_addr + length > self.size {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Access of {} bytes at address {:#06x} exceeds the EEPROM size of {} bytes",
+                                                                                length, mem_addr, self.size)));
+        }
+        Ok(())
+    }
+
+    /// Polls the device until it acknowledges its address again, signaling the internal write cycle has finished.
+    fn wait_for_write_cycle(&self, i2c: &mut I2c, addr_buf: &[u8]) -> PyResult<()> {
+        let start = Instant::now();
+        loop {
+            if i2c.write(addr_buf).is_ok() {
+                return Ok(());
+            }
+            if start.elapsed() > self.write_timeout {
+                return Err(PyErr::new::<pyo3::exceptions::PyTimeoutError, _>("EEPROM did not finish the write cycle before the timeout"));
+            }
+            sleep(Duration::from_micros(500));
+        }
+    }
+
+    fn read_bytes(&self, mem_addr: usize, length: usize) -> PyResult<Vec<u8>> {
+        self.check_range(mem_addr, length)?;
+        with_buses(&self.i2c, |i2c_lock| {
+            let retry = i2c_lock.retry_policy();
+            let mut data = vec![0u8; length];
+            let mut offset = 0;
+            while offset < length {
+                let current = mem_addr + offset;
+                let mut chunk = (length - offset).min(MAX_READ_CHUNK);
+                if self.addr_bytes == 1 {
+                    // The block select bits are part of the device address, so never read across a 256 byte block
+                    chunk = chunk.min(256 - (current & 0xFF));
+                }
+                let (dev_addr, addr_buf) = self.address_for(current);
+                let i2c = i2c_lock.select(self.bus, dev_addr)?;
+                retry.run(|| i2c.write_read(&addr_buf, &mut data[offset..offset + chunk]))
+                     .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to read data: {:?}", e)))?;
+                offset += chunk;
+            }
+            Ok(data)
+        })
+    }
+
+    fn write_bytes(&self, mem_addr: usize, data: &[u8]) -> PyResult<()> {
+        self.check_range(mem_addr, data.len())?;
+        with_buses(&self.i2c, |i2c_lock| {
+            let retry = i2c_lock.retry_policy();
+            let mut offset = 0;
+            while offset < data.len() {
+                let current = mem_addr + offset;
+                // Writes wrap around inside a page, so split the data at every page boundary
+                let chunk = (self.page_size - current % self.page_size).min(data.len() - offset);
+                let (dev_addr, addr_buf) = self.address_for(current);
+                let i2c = i2c_lock.select(self.bus, dev_addr)?;
+
+                let mut buf = addr_buf.clone();
+                buf.extend_from_slice(&data[offset..offset + chunk]);
+                retry.run(|| i2c.write(&buf))
+                     .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to write data: {:?}", e)))?;
+                self.wait_for_write_cycle(i2c, &addr_buf)?;
+                offset += chunk;
+            }
+            Ok(())
+        })
+    }
+}
+
+
+#[pymethods]
+impl EEPROM {
+    #[new]
+    /// Initializes a new EEPROM instance on the bus opened by the I2CManager.
+    ///
+    /// Parameters:
+    /// - `model` (EEPROMModel): The EEPROM model.
+    /// - `addr` (int): The I2C address of the EEPROM (default is 0x50).
+    /// - `write_timeout_ms` (float): The maximum time to wait for a write cycle to finish (default is 20).
+    /// - `bus` (int): The I2C bus the EEPROM is connected to (default is the first bus opened).
+    ///
+    /// Example usage:
+    /// ```python
+    /// eeprom = gpio_manager.EEPROM(gpio_manager.EEPROMModel.AT24C256, addr=0x50)
+    /// ```
+    #[pyo3(signature = (model, addr = 0x50, write_timeout_ms = 20f64, bus = None))]
+    fn new(model: EEPROMModel, addr: u16, write_timeout_ms: f64, bus: Option<u8>) -> PyResult<Self> {
+        let write_timeout = ms_to_duration_checked("Write timeout", write_timeout_ms)?;
+        let (size, page_size, addr_bytes) = model.geometry();
+        let i2c_manager = I2CManager::new_rust_reference();
+        let i2c = i2c_manager.lock().unwrap().get_buses();
+        Ok(Self {
+            i2c,
+            bus,
+            addr,
+            size,
+            page_size,
+            addr_bytes,
+            write_timeout,
+        })
+    }
+
+    /// Reads data from the EEPROM, reads may be of any length and cross page boundaries.
+    ///
+    /// Parameters:
+    /// - `mem_addr` (int): The memory address to start reading from.
+    /// - `length` (int): The number of bytes to read.
+    ///
+    /// Returns:
+    /// - `bytes`: The data read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// data = eeprom.read(0x0000, 64)
+    /// ```
+    #[pyo3(signature = (mem_addr, length))]
+    fn read<'py>(&self, py: Python<'py>, mem_addr: usize, length: usize) -> PyResult<Bound<'py, PyBytes>> {
+        let data = self.read_bytes(mem_addr, length)?;
+        Ok(PyBytes::new(py, &data))
+    }
+
+    /// Writes data to the EEPROM, the data is split at page boundaries and each page write waits for the write cycle to
+    /// finish.
+    ///
+    /// Parameters:
+    /// - `mem_addr` (int): The memory address to start writing to.
+    /// - `data` (bytes | bytearray | memoryview | list[int]): The data to write.
+    ///
+    /// Example usage:
+    /// ```python
+    /// eeprom.write(0x0000, b'\x01\x02\x03')
+    /// ```
+    #[pyo3(signature = (mem_addr, data))]
+    fn write(&self, mem_addr: usize, data: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.write_bytes(mem_addr, &extract_bytes(data)?)
+    }
+
+    /// Reads a single byte from the EEPROM.
+    ///
+    /// Parameters:
+    /// - `mem_addr` (int): The memory address to read.
+    ///
+    /// Returns:
+    /// - `int`: The byte read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// value = eeprom.read_byte(0x0010)
+    /// ```
+    #[pyo3(signature = (mem_addr))]
+    fn read_byte(&self, mem_addr: usize) -> PyResult<u8> {
+        Ok(self.read_bytes(mem_addr, 1)?[0])
+    }
+
+    /// Writes a single byte to the EEPROM.
+    ///
+    /// Parameters:
+    /// - `mem_addr` (int): The memory address to write.
+    /// - `data` (int): The byte to write.
+    ///
+    /// Example usage:
+    /// ```python
+    /// eeprom.write_byte(0x0010, 0xFF)
+    /// ```
+    #[pyo3(signature = (mem_addr, data))]
+    fn write_byte(&self, mem_addr: usize, data: u8) -> PyResult<()> {
+        self.write_bytes(mem_addr, &[data])
+    }
+
+    /// Returns the size of the EEPROM in bytes.
+    fn get_size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the page size of the EEPROM in bytes.
+    fn get_page_size(&self) -> usize {
+        self.page_size
+    }
+}
@@ -0,0 +1,75 @@
+use crate::backend;
+use crate::pinctrl::PinctrlError;
+use std::fmt;
+use std::io;
+
+
+/// The error of the Rust API, converted to the matching Python exception by the bindings.
+#[derive(Debug)]
+pub enum GpioError {
+    /// An argument is out of range, or the pin isn't in the state the operation needs. Raised as a ValueError.
+    InvalidArgument(String),
+    /// A system call failed, with its errno when there is one. Raised as an OSError.
+    Os { errno: Option<i32>, message: String },
+    /// The hardware or its driver failed. Raised as a RuntimeError.
+    Hardware(String),
+}
+
+
+pub type Result<T> = std::result::Result<T, GpioError>;
+
+
+impl fmt::Display for GpioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpioError::InvalidArgument(message) | GpioError::Os { message, .. } | GpioError::Hardware(message) => f.write_str(message),
+        }
+    }
+}
+
+
+impl std::error::Error for GpioError {}
+
+
+impl From<io::Error> for GpioError {
+    fn from(e: io::Error) -> Self {
+        GpioError::Os { errno: e.raw_os_error(), message: e.to_string() }
+    }
+}
+
+
+impl From<backend::Error> for GpioError {
+    fn from(e: backend::Error) -> Self {
+        GpioError::Hardware(e.to_string())
+    }
+}
+
+
+impl From<PinctrlError> for GpioError {
+    fn from(e: PinctrlError) -> Self {
+        GpioError::Os { errno: e.errno, message: e.message }
+    }
+}
+
+
+impl From<rppal::i2c::Error> for GpioError {
+    fn from(e: rppal::i2c::Error) -> Self {
+        match e {
+            rppal::i2c::Error::Io(e) => e.into(),
+            e => GpioError::Hardware(format!("{:?}", e)),
+        }
+    }
+}
+
+
+#[cfg(feature = "python")]
+impl From<GpioError> for pyo3::PyErr {
+    fn from(e: GpioError) -> Self {
+        match e {
+            GpioError::InvalidArgument(message) => pyo3::PyErr::new::<pyo3::exceptions::PyValueError, _>(message),
+            GpioError::Os { errno: Some(errno), message } => pyo3::PyErr::new::<pyo3::exceptions::PyOSError, _>((errno, message)),
+            GpioError::Os { errno: None, message } => pyo3::PyErr::new::<pyo3::exceptions::PyOSError, _>(message),
+            GpioError::Hardware(message) => pyo3::PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(message),
+        }
+    }
+}
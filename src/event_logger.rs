@@ -0,0 +1,167 @@
+use crate::log_module;
+use crate::TriggerEdge;
+use once_cell::sync::Lazy;
+use pyo3::{PyErr, PyResult};
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LogFormat {
+    Csv,
+    Jsonl,
+}
+
+
+/// Appends the edges of input pins to a file, the writes are buffered and flushed at a fixed interval.
+struct EventLogger {
+    writer: BufWriter<File>,
+    format: LogFormat,
+    /// The pins logged, every input pin for None.
+    pins: Option<HashSet<u8>>,
+    flush_interval: Duration,
+    last_flush: Instant,
+}
+
+
+impl EventLogger {
+    fn write(&mut self, pin_num: u8, edge: TriggerEdge, timestamp_ns: i128) -> std::io::Result<()> {
+        let edge = if edge == TriggerEdge::RISING { "rising" } else { "falling" };
+        let seconds = format!("{}.{:09}", timestamp_ns.div_euclid(1_000_000_000), timestamp_ns.rem_euclid(1_000_000_000));
+        match self.format {
+            LogFormat::Csv => writeln!(self.writer, "{},{},{}", seconds, pin_num, edge)?,
+            LogFormat::Jsonl => writeln!(self.writer, "{{\"timestamp\": {}, \"pin\": {}, \"edge\": \"{}\"}}", seconds, pin_num, edge)?,
+        }
+        if self.last_flush.elapsed() >= self.flush_interval {
+            self.writer.flush()?;
+            self.last_flush = Instant::now();
+        }
+        Ok(())
+    }
+}
+
+
+static EVENT_LOGGER: Lazy<Mutex<Option<EventLogger>>> = Lazy::new(|| Mutex::new(None));
+
+
+/// Starts logging the edges of the pins to the file, replacing the logger already running. CSV files get a header
+/// when they are empty.
+pub fn start(path: &str, pins: Option<Vec<u8>>, format: &str, flush_interval: Duration) -> PyResult<()> {
+    let format = match format {
+        "csv" => LogFormat::Csv,
+        "jsonl" => LogFormat::Jsonl,
+        _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Format must be \"csv\" or \"jsonl\", The value {} does not meet this condition", format))),
+    };
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut writer = BufWriter::new(file);
+    if format == LogFormat::Csv && writer.get_ref().metadata()?.len() == 0 {
+        writeln!(writer, "timestamp,pin,edge")?;
+    }
+    let logger = EventLogger {
+        writer,
+        format,
+        pins: pins.map(|pins| pins.into_iter().collect()),
+        flush_interval,
+        last_flush: Instant::now(),
+    };
+    if let Some(mut previous) = EVENT_LOGGER.lock().unwrap().replace(logger) {
+        previous.writer.flush()?;
+    }
+    Ok(())
+}
+
+
+/// Stops the logger and flushes what it buffered, returning whether it was running.
+pub fn stop() -> PyResult<bool> {
+    match EVENT_LOGGER.lock().unwrap().take() {
+        Some(mut logger) => {
+            logger.writer.flush()?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+
+/// Returns whether the edges of the pin are logged.
+pub fn is_logged(pin_num: u8) -> bool {
+    EVENT_LOGGER.lock().unwrap().as_ref().is_some_and(|logger| logger.pins.as_ref().is_none_or(|pins| pins.contains(&pin_num)))
+}
+
+
+/// Logs an edge of a pin if the logger is running and the pin is logged.
+pub fn log_event(pin_num: u8, edge: TriggerEdge, timestamp_ns: i128) {
+    let mut logger = EVENT_LOGGER.lock().unwrap();
+    if let Some(logger) = logger.as_mut() {
+        if logger.pins.as_ref().is_some_and(|pins| !pins.contains(&pin_num)) {
+            return;
+        }
+        if let Err(e) = logger.write(pin_num, edge, timestamp_ns) {
+            log_module::warning(&format!("Failed to log the edge of GPIO {}: {}", pin_num, e));
+        }
+    }
+}
+
+
+/// An edge read back from a file written by the logger.
+pub struct TraceEvent {
+    pub timestamp_ns: i128,
+    pub pin_num: u8,
+    pub rising: bool,
+}
+
+
+/// Parses a timestamp written by the logger, seconds with up to nine decimals, to nanoseconds without rounding. Like
+/// the logger writes them, the decimals of negative timestamps count up from the whole seconds.
+fn parse_timestamp(value: &str) -> Option<i128> {
+    let (seconds, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if fraction.len() > 9 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let seconds: i128 = seconds.parse().ok()?;
+    let fraction: i128 = format!("{:0<9}", fraction).parse().ok()?;
+    Some(seconds * 1_000_000_000 + fraction)
+}
+
+
+/// Returns the value of a field of a JSON object written by the logger, without its quotes.
+fn json_field<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let start = line.find(&format!("\"{}\":", key))? + key.len() + 3;
+    let value = &line[start..];
+    let end = value.find([',', '}']).unwrap_or(value.len());
+    Some(value[..end].trim().trim_matches('"'))
+}
+
+
+/// Reads the edges of a file written by the logger, in CSV or JSONL, sorted by time. Errors name the offending line.
+pub fn read_trace(path: &str) -> PyResult<Vec<TraceEvent>> {
+    let content = fs::read_to_string(path)?;
+    let mut events = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line == "timestamp,pin,edge" {
+            continue;
+        }
+        let fields = if line.starts_with('{') {
+            (json_field(line, "timestamp"), json_field(line, "pin"), json_field(line, "edge"))
+        } else {
+            let mut fields = line.split(',').map(str::trim);
+            (fields.next(), fields.next(), fields.next())
+        };
+        let event = match fields {
+            (Some(timestamp), Some(pin_num), Some(edge @ ("rising" | "falling"))) => parse_timestamp(timestamp)
+                .zip(pin_num.parse().ok())
+                .map(|(timestamp_ns, pin_num)| TraceEvent { timestamp_ns, pin_num, rising: edge == "rising" }),
+            _ => None,
+        };
+        match event {
+            Some(event) => events.push(event),
+            None => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Line {} of {} is not an edge written by the event logger: {}", index + 1, path, line))),
+        }
+    }
+    events.sort_by_key(|event| event.timestamp_ns);
+    Ok(events)
+}
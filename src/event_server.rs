@@ -0,0 +1,452 @@
+use crate::gpio_module::GPIOManager;
+use crate::i2c_ioctl::Segment;
+use crate::i2c_module::{with_buses, I2CManager};
+use crate::pwm_ramp_module::Easing;
+use crate::{log_module, InternPullResistorState, LogicLevel, OutputMode, PinState, TriggerEdge};
+use once_cell::sync::Lazy;
+use pyo3::{pyclass, pyfunction, pymethods, PyErr, PyResult};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tungstenite::{Message, WebSocket};
+
+
+// How often the threads check for new frames and for the server being stopped
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+// WebSocket clients send their handshake right after connecting, clients silent for longer are TCP clients
+const HANDSHAKE_WAIT: Duration = Duration::from_millis(500);
+// Frames buffered for every client, frames sent while the buffer is full are dropped
+const CLIENT_CAPACITY: usize = 1024;
+
+
+/// The running server, pin events are sent to its clients.
+struct Server {
+    id: u64,
+    /// The input pins streamed, every input pin for None.
+    pins: Option<HashSet<u8>>,
+    clients: Arc<Mutex<Vec<SyncSender<String>>>>,
+    stopped: Arc<AtomicBool>,
+}
+
+
+impl Server {
+    fn streams(&self, pin_num: u8) -> bool {
+        self.pins.as_ref().is_none_or(|pins| pins.contains(&pin_num))
+    }
+}
+
+
+static SERVER: Lazy<Mutex<Option<Server>>> = Lazy::new(|| Mutex::new(None));
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+
+/// Sends the frame to every client, forgetting the clients that disconnected.
+fn broadcast(clients: &Mutex<Vec<SyncSender<String>>>, frame: String) {
+    clients.lock().unwrap().retain(|client| match client.try_send(frame.clone()) {
+        Ok(()) => true,
+        Err(TrySendError::Full(_)) => {
+            log_module::debug("Dropped an event frame, a client is not keeping up");
+            true
+        }
+        Err(TrySendError::Disconnected(_)) => false,
+    });
+}
+
+
+/// Sends an edge of an input pin to the clients, if the server is running and the pin is streamed.
+pub fn publish_edge(pin_num: u8, edge: TriggerEdge, state: PinState, timestamp_ns: i128) {
+    let server = SERVER.lock().unwrap();
+    if let Some(server) = server.as_ref().filter(|server| server.streams(pin_num)) {
+        let frame = json!({
+            "event": "edge",
+            "pin": pin_num,
+            "edge": if edge == TriggerEdge::RISING { "rising" } else { "falling" },
+            "state": state == PinState::HIGH,
+            "timestamp": timestamp_ns as f64 / 1_000_000_000f64,
+        });
+        broadcast(&server.clients, frame.to_string());
+    }
+}
+
+
+/// Returns whether the edges of the pin are streamed by the server.
+pub fn is_streamed(pin_num: u8) -> bool {
+    SERVER.lock().unwrap().as_ref().is_some_and(|server| server.streams(pin_num))
+}
+
+
+fn command_error(message: String) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyValueError, _>(message)
+}
+
+
+fn pin_arg(command: &Value) -> PyResult<u8> {
+    command["pin"].as_u64()
+                  .and_then(|pin_num| u8::try_from(pin_num).ok())
+                  .ok_or_else(|| command_error(format!("The command must name a pin, The value {} does not meet this condition", command["pin"])))
+}
+
+
+fn state_arg(command: &Value) -> PyResult<PinState> {
+    match &command["state"] {
+        Value::Bool(high) => Ok(if *high { PinState::HIGH } else { PinState::LOW }),
+        Value::Number(level) if level.as_u64() == Some(1) => Ok(PinState::HIGH),
+        Value::Number(level) if level.as_u64() == Some(0) => Ok(PinState::LOW),
+        state => Err(command_error(format!("The state must be true, false, 1 or 0, The value {} does not meet this condition", state))),
+    }
+}
+
+
+fn logic_level_arg(command: &Value) -> PyResult<LogicLevel> {
+    match command["logic_level"].as_str() {
+        None | Some("high") => Ok(LogicLevel::HIGH),
+        Some("low") => Ok(LogicLevel::LOW),
+        Some(level) => Err(command_error(format!("The logic level must be \"high\" or \"low\", The value {} does not meet this condition", level))),
+    }
+}
+
+
+fn pull_arg(command: &Value) -> PyResult<InternPullResistorState> {
+    match command["pull"].as_str() {
+        None | Some("auto") => Ok(InternPullResistorState::AUTO),
+        Some("up") => Ok(InternPullResistorState::PULLUP),
+        Some("down") => Ok(InternPullResistorState::PULLDOWN),
+        Some("external") => Ok(InternPullResistorState::EXTERNAL),
+        Some(pull) => Err(command_error(format!("The pull must be \"auto\", \"up\", \"down\" or \"external\", The value {} does not meet this condition", pull))),
+    }
+}
+
+
+/// Whether a setup command takes a pin claimed by the kernel or another process, off unless `force` is true.
+fn force_arg(command: &Value) -> bool {
+    command["force"].as_bool().unwrap_or(false)
+}
+
+
+/// Runs a combined write and read on an I2C device, opening the bus first if needed. Returns the bytes read.
+fn i2c_command(command: &Value) -> PyResult<Value> {
+    let addr = command["addr"].as_u64()
+                              .and_then(|addr| u16::try_from(addr).ok())
+                              .ok_or_else(|| command_error(format!("The command must name an I2C address, The value {} does not meet this condition", command["addr"])))?;
+    let bus = match &command["bus"] {
+        Value::Null => 1,
+        bus => bus.as_u64()
+                  .and_then(|bus| u8::try_from(bus).ok())
+                  .ok_or_else(|| command_error(format!("The bus must be a number, The value {} does not meet this condition", bus)))?,
+    };
+    let write: Vec<u8> = match &command["write"] {
+        Value::Null => Vec::new(),
+        data => serde_json::from_value(data.clone())
+            .map_err(|_| command_error(format!("The data written must be a list of bytes, The value {} does not meet this condition", data)))?,
+    };
+    let read = command["read"].as_u64().unwrap_or_default() as usize;
+    let mut segments = Vec::new();
+    if !write.is_empty() {
+        segments.push(Segment::Write(write));
+    }
+    if read > 0 {
+        segments.push(Segment::Read(read));
+    }
+    if segments.is_empty() {
+        return Err(command_error("The command must write or read data".to_string()));
+    }
+    let buses = I2CManager::new_rust_reference().lock().unwrap().get_buses();
+    let data = with_buses(&buses, |buses| {
+        if !buses.is_open(bus) {
+            buses.open(bus)?;
+        }
+        buses.transaction(Some(bus), addr, &segments)
+    })?;
+    Ok(data.into_iter().next().filter(|_| read > 0).unwrap_or_default().into())
+}
+
+
+/// Runs a command of a client, returning the result of the reply.
+pub(crate) fn run_command(gpio_manager: &GPIOManager, command: &Value) -> PyResult<Value> {
+    match command["cmd"].as_str().unwrap_or_default() {
+        "add_input" => {
+            gpio_manager.add_input_pin(pin_arg(command)?, Some(pull_arg(command)?.into()), Some(logic_level_arg(command)?.into()), force_arg(command))?;
+            Ok(Value::Null)
+        }
+        "add_output" => {
+            let state = if command["state"].is_null() { PinState::LOW } else { state_arg(command)? };
+            gpio_manager.add_output_pin(pin_arg(command)?, state.into(), Some(logic_level_arg(command)?.into()), OutputMode::PUSH_PULL.into(), force_arg(command))?;
+            Ok(Value::Null)
+        }
+        "setup_pwm" => {
+            gpio_manager.setup_pwm(pin_arg(command)?, command["frequency_hz"].as_f64(), command["duty_cycle"].as_f64(), command["period_ms"].as_f64(),
+                                   command["pulse_width_ms"].as_f64(), Some(logic_level_arg(command)?.into()), force_arg(command))?;
+            Ok(Value::Null)
+        }
+        "start_pwm" => {
+            gpio_manager.start_pwm(pin_arg(command)?, None)?;
+            Ok(Value::Null)
+        }
+        "stop_pwm" => {
+            gpio_manager.stop_pwm(pin_arg(command)?)?;
+            Ok(Value::Null)
+        }
+        "reset" => {
+            gpio_manager.reset_pin(pin_arg(command)?)?;
+            Ok(Value::Null)
+        }
+        "set" => {
+            gpio_manager.set_output_pin(pin_arg(command)?, state_arg(command)?.into())?;
+            Ok(Value::Null)
+        }
+        "duty" => {
+            let duty_cycle = command["duty_cycle"].as_f64()
+                                                  .ok_or_else(|| command_error(format!("The duty cycle must be a number, The value {} does not meet this condition", command["duty_cycle"])))?;
+            gpio_manager.set_pwm_duty_cycle(pin_arg(command)?, duty_cycle, None, Easing::LINEAR)?;
+            Ok(Value::Null)
+        }
+        "get" => Ok(Value::Bool(gpio_manager.get_pin(pin_arg(command)?)? == PinState::HIGH)),
+        "i2c" => i2c_command(command),
+        cmd => Err(command_error(format!("Unknown command {:?}, the commands are add_input, add_output, setup_pwm, start_pwm, stop_pwm, reset, set, duty, get and i2c", cmd))),
+    }
+}
+
+
+/// Parses a frame of a client, returning the reply to send instead when it isn't valid JSON.
+pub(crate) fn parse_frame(frame: &str) -> Result<Value, String> {
+    serde_json::from_str(frame).map_err(|e| json!({"ok": false, "error": format!("Invalid JSON: {}", e)}).to_string())
+}
+
+
+/// Returns the reply to a command. The id of the command is echoed so clients can match replies to commands.
+pub(crate) fn reply_to(command: &Value, result: PyResult<Value>) -> String {
+    let mut reply = match result {
+        Ok(result) => json!({"ok": true, "result": result}),
+        Err(e) => json!({"ok": false, "error": e.to_string()}),
+    };
+    if let Some(id) = command.get("id") {
+        reply["id"] = id.clone();
+    }
+    reply.to_string()
+}
+
+
+/// Parses and runs a frame of a client, returning the reply.
+fn reply(gpio_manager: &GPIOManager, frame: &str) -> String {
+    match parse_frame(frame) {
+        Ok(command) => reply_to(&command, run_command(gpio_manager, &command)),
+        Err(reply) => reply,
+    }
+}
+
+
+/// A connection with a client, sending frames as JSON lines over TCP or as text messages over WebSocket.
+enum Connection {
+    Tcp(BufReader<TcpStream>, String),
+    WebSocket(WebSocket<TcpStream>),
+}
+
+
+pub(crate) fn is_timeout(e: &io::Error) -> bool {
+    matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
+
+
+impl Connection {
+    /// Tells a WebSocket client from a TCP client by the HTTP request starting the handshake.
+    fn accept(stream: TcpStream) -> io::Result<Self> {
+        stream.set_read_timeout(Some(HANDSHAKE_WAIT))?;
+        let mut start = [0u8; 4];
+        let websocket = match stream.peek(&mut start) {
+            Ok(read) => read == 4 && &start == b"GET ",
+            Err(e) if is_timeout(&e) => false,
+            Err(e) => return Err(e),
+        };
+        let connection = if websocket {
+            stream.set_read_timeout(None)?;
+            let websocket = tungstenite::accept(stream).map_err(|e| io::Error::new(ErrorKind::InvalidData, e.to_string()))?;
+            websocket.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+            Connection::WebSocket(websocket)
+        } else {
+            stream.set_read_timeout(Some(POLL_INTERVAL))?;
+            Connection::Tcp(BufReader::new(stream), String::new())
+        };
+        Ok(connection)
+    }
+
+    /// Returns the next frame of the client, or None if none arrived within the poll interval.
+    fn read(&mut self) -> io::Result<Option<String>> {
+        match self {
+            Connection::Tcp(reader, line) => match reader.read_line(line) {
+                Ok(0) => Err(ErrorKind::UnexpectedEof.into()),
+                Ok(_) if line.ends_with('\n') => Ok(Some(std::mem::take(line))),
+                Ok(_) => Ok(None),
+                Err(e) if is_timeout(&e) => Ok(None),
+                Err(e) => Err(e),
+            },
+            Connection::WebSocket(websocket) => match websocket.read() {
+                Ok(Message::Text(text)) => Ok(Some(text.to_string())),
+                Ok(Message::Close(_)) => Err(ErrorKind::UnexpectedEof.into()),
+                Ok(_) => Ok(None),
+                Err(tungstenite::Error::Io(e)) if is_timeout(&e) => Ok(None),
+                Err(e) => Err(io::Error::other(e.to_string())),
+            },
+        }
+    }
+
+    fn send(&mut self, frame: &str) -> io::Result<()> {
+        match self {
+            Connection::Tcp(reader, _) => {
+                let stream = reader.get_mut();
+                stream.write_all(frame.as_bytes())?;
+                stream.write_all(b"\n")
+            }
+            Connection::WebSocket(websocket) => websocket.send(Message::text(frame)).map_err(|e| io::Error::other(e.to_string())),
+        }
+    }
+}
+
+
+/// Serves a client until it disconnects or the server is stopped, sending it the queued frames and replying to its
+/// commands.
+fn serve_client(stream: TcpStream, frames: Receiver<String>, stopped: Arc<AtomicBool>) -> io::Result<()> {
+    let gpio_manager = GPIOManager::new_rust_reference();
+    let mut connection = Connection::accept(stream)?;
+    while !stopped.load(Ordering::SeqCst) {
+        while let Ok(frame) = frames.try_recv() {
+            connection.send(&frame)?;
+        }
+        if let Some(frame) = connection.read()? {
+            if !frame.trim().is_empty() {
+                connection.send(&reply(&gpio_manager, frame.trim()))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+
+#[pyclass]
+/// Handle of the event server started with serve_events.
+pub struct EventServer {
+    id: u64,
+    port: u16,
+    connected: Arc<AtomicUsize>,
+}
+
+
+#[pymethods]
+impl EventServer {
+    /// The port the server listens on, useful when it was started on port 0.
+    #[getter]
+    fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Returns the number of connected clients.
+    ///
+    /// Example usage:
+    /// ```server.client_count()```
+    ///
+    fn client_count(&self) -> usize {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    /// Stops the server and disconnects its clients. Stopping a server that was replaced or already stopped does
+    /// nothing.
+    ///
+    /// Example usage:
+    /// ```server.stop()```
+    ///
+    fn stop(&self) {
+        let mut server = SERVER.lock().unwrap();
+        if server.as_ref().is_some_and(|server| server.id == self.id) {
+            server.take().unwrap().stopped.store(true, Ordering::SeqCst);
+            drop(server);
+            GPIOManager::new_rust_reference().release_unobserved_pins();
+        }
+    }
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (port, host = "127.0.0.1", pins = None))]
+/// Starts a server streaming the edges of the input pins to its clients as JSON frames and running their commands,
+/// from Rust threads so it keeps working while Python is busy. A server already running is replaced.
+///
+/// Clients connect over TCP, exchanging one JSON object per line, or over WebSocket, exchanging one JSON object per
+/// text message. Every edge is sent as ```{"event": "edge", "pin": 17, "edge": "rising", "state": true,
+/// "timestamp": 1700000000.123}```, with the logical state after the edge and the time of the clock chosen with
+/// set_timestamp_clock. The commands are ```{"cmd": "set", "pin": 27, "state": true}```, ```{"cmd": "duty", "pin": 12,
+/// "duty_cycle": 50}```, ```{"cmd": "get", "pin": 17}```, ```{"cmd": "i2c", "addr": 72, "write": [0], "read": 2}``` and
+/// the setup commands add_input, add_output, setup_pwm, start_pwm, stop_pwm and reset used by GPIOManager.connect, each
+/// is answered with ```{"ok": true, "result": ...}``` or ```{"ok": false, "error": "..."}``` carrying the ```id``` of
+/// the command when it has one.
+///
+/// Parameters:
+/// - ```port``` (int): The port to listen on, 0 picks a free port.
+/// - ```host``` (str): The address to listen on, "0.0.0.0" accepts clients on every interface (default is "127.0.0.1",
+///   local clients only).
+/// - ```pins``` (list[int]): The input pins streamed, every input pin if None (default is None). Pins set up later are
+///   streamed as well.
+///
+/// Returns:
+/// - ```EventServer```: The handle of the server.
+///
+/// The server has no authentication, only expose it on trusted networks. Input pins without callbacks are debounced
+/// in hardware for 2 ms.
+///
+/// Example usage:
+/// ```python
+/// manager.add_input_pin(17)
+/// server = gpio_manager.serve_events(8765, host="0.0.0.0")
+/// ```
+pub fn serve_events(port: u16, host: &str, pins: Option<Vec<u8>>) -> PyResult<EventServer> {
+    let listener = TcpListener::bind((host, port))
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyOSError, _>(format!("Unable to listen on {}:{}: {}", host, port, e)))?;
+    let port = listener.local_addr()?.port();
+    listener.set_nonblocking(true)?;
+
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    let clients: Arc<Mutex<Vec<SyncSender<String>>>> = Arc::new(Mutex::new(Vec::new()));
+    let stopped = Arc::new(AtomicBool::new(false));
+    let connected = Arc::new(AtomicUsize::new(0));
+    let pins: Option<HashSet<u8>> = pins.map(|pins| pins.into_iter().collect());
+
+    let thread_clients = Arc::clone(&clients);
+    let thread_connected = Arc::clone(&connected);
+    let thread_stopped = Arc::clone(&stopped);
+    thread::spawn(move || {
+        while !thread_stopped.load(Ordering::SeqCst) {
+            match listener.accept() {
+                Ok((stream, address)) => {
+                    let (sender, frames) = sync_channel(CLIENT_CAPACITY);
+                    thread_clients.lock().unwrap().push(sender);
+                    let client_stopped = Arc::clone(&thread_stopped);
+                    let client_connected = Arc::clone(&thread_connected);
+                    thread::spawn(move || {
+                        let _ = stream.set_nonblocking(false);
+                        client_connected.fetch_add(1, Ordering::SeqCst);
+                        log_module::info(&format!("Event client {} connected", address));
+                        if let Err(e) = serve_client(stream, frames, client_stopped) {
+                            log_module::info(&format!("Event client {} disconnected: {}", address, e));
+                        }
+                        client_connected.fetch_sub(1, Ordering::SeqCst);
+                    });
+                }
+                Err(e) if is_timeout(&e) => thread::sleep(POLL_INTERVAL),
+                Err(e) => log_module::warning(&format!("The event server failed to accept a client: {}", e)),
+            }
+        }
+    });
+
+    let server = Server { id, pins: pins.clone(), clients, stopped };
+    if let Some(previous) = SERVER.lock().unwrap().replace(server) {
+        previous.stopped.store(true, Ordering::SeqCst);
+    }
+    let gpio_manager = GPIOManager::new_rust_reference();
+    gpio_manager.release_unobserved_pins();
+    gpio_manager.observe_pins(pins.map(|pins| pins.into_iter().collect()))?;
+    Ok(EventServer { id, port, connected })
+}
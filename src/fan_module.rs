@@ -0,0 +1,322 @@
+use crate::board_module::read_cpu_temperature;
+use crate::log_module;
+use crate::pwm_output_module::PWM;
+use crate::pwm_ramp_module::Easing;
+use crate::{ms_to_duration_checked, release_gil, EnumArg, LogicLevel};
+use pyo3::prelude::*;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+
+/// Where the controller reads the temperature from.
+enum Sensor {
+    Cpu,
+    Callback(PyObject),
+}
+
+
+impl Sensor {
+    fn read(&self) -> PyResult<f64> {
+        match self {
+            Sensor::Cpu => Ok(read_cpu_temperature()?),
+            Sensor::Callback(callback) => Python::with_gil(|py| callback.call0(py)?.extract::<f64>(py)),
+        }
+    }
+}
+
+
+fn check_curve(curve: &[(f64, f64)]) -> PyResult<()> {
+    if curve.is_empty() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("The fan curve must have at least one point"));
+    }
+    for (temperature, duty_cycle) in curve {
+        if !(0f64..=100f64).contains(duty_cycle) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Duty cycle must be between 0 and 100, The value {} at {} °C does not meet this condition", duty_cycle, temperature)));
+        }
+    }
+    if let Some(pair) = curve.windows(2).find(|pair| pair[0].0 >= pair[1].0) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "The temperatures of the fan curve must increase, {} °C is followed by {} °C", pair[0].0, pair[1].0)));
+    }
+    Ok(())
+}
+
+
+/// Returns the duty cycle of the curve at a temperature, interpolated between the points and held past the ends.
+fn curve_duty_cycle(curve: &[(f64, f64)], temperature: f64) -> f64 {
+    let (first, last) = (curve[0], curve[curve.len() - 1]);
+    if temperature <= first.0 {
+        return first.1;
+    }
+    if temperature >= last.0 {
+        return last.1;
+    }
+    let pair = curve.windows(2).find(|pair| temperature < pair[1].0).unwrap();
+    let ((t0, d0), (t1, d1)) = (pair[0], pair[1]);
+    d0 + (d1 - d0) * (temperature - t0) / (t1 - t0)
+}
+
+
+/// Settings of a controller, read by the control thread at every reading.
+struct FanSettings {
+    curve: Vec<(f64, f64)>,
+    hysteresis: f64,
+    min_duty_cycle: f64,
+    spin_up: Duration,
+}
+
+
+impl FanSettings {
+    /// Returns the duty cycle for a temperature, coming from the current duty cycle.
+    fn duty_cycle(&self, current: f64, temperature: f64) -> f64 {
+        let mut duty_cycle = curve_duty_cycle(&self.curve, temperature);
+        if duty_cycle < current {
+            // The fan only slows down once the temperature dropped by the hysteresis, so it doesn't hunt around a point
+            duty_cycle = curve_duty_cycle(&self.curve, temperature + self.hysteresis).min(current);
+        }
+        if duty_cycle > 0f64 && duty_cycle < self.min_duty_cycle {
+            duty_cycle = self.min_duty_cycle;
+        }
+        duty_cycle
+    }
+}
+
+
+/// The last reading of a controller.
+#[derive(Clone, Copy, Default)]
+struct FanState {
+    temperature: Option<f64>,
+    duty_cycle: f64,
+}
+
+
+/// Reads the temperature every interval and sets the duty cycle of the fan, until the stop channel is signaled or
+/// dropped. The fan runs at full speed while the temperature can't be read.
+fn control(pwm: Arc<PWM>, sensor: Arc<Sensor>, settings: Arc<Mutex<FanSettings>>, state: Arc<Mutex<FanState>>,
+           interval: Duration, stop: Receiver<()>) {
+    let stopped = |timeout: Duration| !matches!(stop.recv_timeout(timeout), Err(RecvTimeoutError::Timeout));
+    loop {
+        let current = state.lock().unwrap().duty_cycle;
+        let (temperature, duty_cycle, spin_up) = match sensor.read() {
+            Ok(temperature) => {
+                let settings = settings.lock().unwrap();
+                let duty_cycle = settings.duty_cycle(current, temperature);
+                (Some(temperature), duty_cycle, settings.spin_up)
+            }
+            Err(e) => {
+                log_module::exception("Failed to read the temperature of the fan controller, running the fan at full speed", &e);
+                (None, 100f64, Duration::ZERO)
+            }
+        };
+        // A stopped fan may not start at a low duty cycle, so it is kicked at full speed first
+        if current == 0f64 && duty_cycle > 0f64 && !spin_up.is_zero() {
+            if let Err(e) = pwm.set_duty_cycle(100f64, None, Easing::LINEAR) {
+                log_module::exception("Failed to spin up the fan", &e);
+            }
+            if stopped(spin_up) {
+                return;
+            }
+        }
+        if let Err(e) = pwm.set_duty_cycle(duty_cycle, None, Easing::LINEAR) {
+            log_module::exception("Failed to set the duty cycle of the fan", &e);
+        }
+        *state.lock().unwrap() = FanState { temperature, duty_cycle };
+        if stopped(interval) {
+            return;
+        }
+    }
+}
+
+
+#[pyclass]
+/// FanController drives a fan from a PWM pin following a temperature curve. A Rust thread reads the CPU temperature,
+/// or a sensor through a function, every interval and sets the duty cycle interpolated from the curve. The fan slows
+/// down only once the temperature dropped by the hysteresis, and a fan that is on never runs below the minimum duty
+/// cycle it needs to keep spinning.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// fan = gpio_manager.FanController(18, [(45, 0), (55, 40), (70, 100)], hysteresis_c=3, min_duty_cycle=25)
+/// fan.start()
+/// print(fan.get_temperature(), fan.get_duty_cycle())
+/// ```
+pub struct FanController {
+    pwm: Arc<PWM>,
+    sensor: Arc<Sensor>,
+    settings: Arc<Mutex<FanSettings>>,
+    state: Arc<Mutex<FanState>>,
+    interval: Duration,
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+
+impl FanController {
+    fn stop_thread(&mut self) {
+        // Dropping the sender wakes the thread up, the GIL is released as it may be waiting for it to read the sensor
+        self.stop = None;
+        if let Some(thread) = self.thread.take() {
+            release_gil(|| {
+                let _ = thread.join();
+            });
+        }
+    }
+}
+
+
+impl Drop for FanController {
+    fn drop(&mut self) {
+        self.stop_thread();
+    }
+}
+
+
+#[pymethods]
+impl FanController {
+    #[new]
+    /// Sets up the PWM of the fan, through the hardware PWM channel of the pin when it has one. The fan stays off until
+    /// the controller is started.
+    ///
+    /// Parameters:
+    /// - `pin` (int): The GPIO pin driving the fan.
+    /// - `curve` (list[tuple[float, float]]): The points of the curve as `(temperature_c, duty_cycle)` with increasing
+    ///   temperatures, the duty cycle (0 to 100) is interpolated between them and held below the first and above the
+    ///   last.
+    /// - `sensor` (Callable[[], float]): A function returning the temperature in degrees Celsius, for example read from
+    ///   an I2C sensor (default is None, which reads the CPU temperature).
+    /// - `interval_ms` (float): The time between two readings in milliseconds (default is 2000).
+    /// - `hysteresis_c` (float): How far in degrees Celsius the temperature must drop before the fan slows down
+    ///   (default is 2).
+    /// - `min_duty_cycle` (float): The lowest duty cycle the fan keeps spinning at, lower duty cycles of the curve
+    ///   other than 0 are raised to it (default is 0).
+    /// - `spin_up_ms` (float): The time the fan runs at full speed when it starts from a stop in milliseconds (default
+    ///   is 0).
+    /// - `frequency_hz` (float): The PWM frequency in Hertz, 4-pin PC fans expect 25000 (default is 1000).
+    /// - `logic_level` (LogicLevel): The logic level of the pin, LOW inverts the signal (default is HIGH).
+    ///
+    /// Example usage:
+    /// ```python
+    /// fan = gpio_manager.FanController(18, [(40, 0), (60, 100)], sensor=lambda: sensor.read_celsius(), interval_ms=5000)
+    /// ```
+    #[pyo3(signature = (pin, curve, sensor = None, interval_ms = 2000f64, hysteresis_c = 2f64, min_duty_cycle = 0f64,
+                        spin_up_ms = 0f64, frequency_hz = 1000f64, logic_level = EnumArg(LogicLevel::HIGH)))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(pin: u8, curve: Vec<(f64, f64)>, sensor: Option<PyObject>, interval_ms: f64, hysteresis_c: f64, min_duty_cycle: f64,
+           spin_up_ms: f64, frequency_hz: f64, logic_level: EnumArg<LogicLevel>) -> PyResult<Self> {
+        check_curve(&curve)?;
+        if interval_ms <= 0f64 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Interval must be greater than 0, The value {} does not meet this condition", interval_ms)));
+        }
+        let interval = ms_to_duration_checked("Interval", interval_ms)?;
+        if hysteresis_c < 0f64 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Hysteresis must be 0 or greater, The value {} does not meet this condition", hysteresis_c)));
+        }
+        if !(0f64..=100f64).contains(&min_duty_cycle) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Minimum duty cycle must be between 0 and 100, The value {} does not meet this condition", min_duty_cycle)));
+        }
+        let spin_up = ms_to_duration_checked("Spin up time", spin_up_ms)?;
+        let pwm = PWM::new(pin, Some(frequency_hz), Some(0f64), None, None, logic_level)?;
+        Ok(Self {
+            pwm: Arc::new(pwm),
+            sensor: Arc::new(sensor.map_or(Sensor::Cpu, Sensor::Callback)),
+            settings: Arc::new(Mutex::new(FanSettings {
+                curve,
+                hysteresis: hysteresis_c,
+                min_duty_cycle,
+                spin_up,
+            })),
+            state: Arc::new(Mutex::new(FanState::default())),
+            interval,
+            stop: None,
+            thread: None,
+        })
+    }
+
+    /// Starts the PWM signal and the control thread, the first reading is taken right away.
+    ///
+    /// Example usage:
+    /// ```python
+    /// fan.start()
+    /// ```
+    fn start(&mut self) -> PyResult<()> {
+        if self.thread.is_some() {
+            return Ok(());
+        }
+        self.pwm.start()?;
+        let (sender, receiver) = mpsc::channel();
+        let (pwm, sensor) = (Arc::clone(&self.pwm), Arc::clone(&self.sensor));
+        let (settings, state) = (Arc::clone(&self.settings), Arc::clone(&self.state));
+        let interval = self.interval;
+        self.stop = Some(sender);
+        self.thread = Some(thread::spawn(move || control(pwm, sensor, settings, state, interval, receiver)));
+        Ok(())
+    }
+
+    /// Stops the control thread and the PWM signal, the fan pin is driven to its inactive level.
+    ///
+    /// Example usage:
+    /// ```python
+    /// fan.stop()
+    /// ```
+    fn stop(&mut self) -> PyResult<()> {
+        self.stop_thread();
+        *self.state.lock().unwrap() = FanState::default();
+        self.pwm.stop()
+    }
+
+    /// Stops the controller and releases the pin.
+    ///
+    /// Example usage:
+    /// ```python
+    /// fan.close()
+    /// ```
+    fn close(&mut self) -> PyResult<()> {
+        self.stop_thread();
+        self.pwm.reset()
+    }
+
+    /// Replaces the curve, used from the next reading.
+    ///
+    /// Parameters:
+    /// - `curve` (list[tuple[float, float]]): The points of the curve as `(temperature_c, duty_cycle)` with increasing
+    ///   temperatures.
+    ///
+    /// Example usage:
+    /// ```python
+    /// fan.set_curve([(50, 0), (65, 60), (75, 100)])
+    /// ```
+    #[pyo3(signature = (curve))]
+    fn set_curve(&self, curve: Vec<(f64, f64)>) -> PyResult<()> {
+        check_curve(&curve)?;
+        self.settings.lock().unwrap().curve = curve;
+        Ok(())
+    }
+
+    /// Returns the duty cycle of the curve at a temperature, without the hysteresis and minimum duty cycle.
+    ///
+    /// Parameters:
+    /// - `temperature_c` (float): The temperature in degrees Celsius.
+    #[pyo3(signature = (temperature_c))]
+    fn curve_duty_cycle(&self, temperature_c: f64) -> f64 {
+        curve_duty_cycle(&self.settings.lock().unwrap().curve, temperature_c)
+    }
+
+    /// Returns the last temperature read in degrees Celsius, or None before the first reading or when it failed.
+    fn get_temperature(&self) -> Option<f64> {
+        self.state.lock().unwrap().temperature
+    }
+
+    /// Returns the duty cycle the fan runs at from 0 to 100.
+    fn get_duty_cycle(&self) -> f64 {
+        self.state.lock().unwrap().duty_cycle
+    }
+
+    /// Returns whether the control thread is running.
+    fn is_active(&self) -> bool {
+        self.thread.is_some()
+    }
+}
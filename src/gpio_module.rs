@@ -1,14 +1,39 @@
+use crate::capture_module::{capture, sample_pin};
+use crate::metrics::{self, PinCounter};
+#[cfg(feature = "server")]
+use crate::{daemon_module, event_server};
+#[cfg(feature = "mqtt")]
+use crate::mqtt_module;
+#[cfg(feature = "server")]
+use crate::remote_module::RemoteGPIOManager;
+use crate::{config_module, event_logger, log_module, timing};
+use crate::board_module::{alt_functions, device_info, AltFunction, Peripheral, MAX_HEADER_GPIO};
+use crate::pads::{drive_strengths, per_pin_pads, read_pad, update_pad};
+use crate::backend::{self, InputPin, OutputPin};
+use crate::pin_proxy_module::{GPIOPin, PinKey};
+use crate::pinctrl::{pin_function, set_alt_function};
+use crate::melody_module::{parse_melody, start_melody};
 use crate::pwm_module::PWMManager;
-use crate::{check_pwm_values, compute_pwm_values, Callback, InternPullResistorState, LogicLevel, Pin, PinManager, PinState, PinType, PwmConfig, TriggerEdge};
+use crate::pwm_ramp_module::{cancel_ramp, check_pattern, ramp_duration, start_pattern, start_ramp, Easing, RampTarget};
+use crate::scheduler_module::{monotonic_to_instant, ScheduledAction, PIN_SCHEDULER};
+use crate::soft_pwm_engine::SOFT_PWM_ENGINE;
+use crate::touch_module::{measure_charge_time, DISCHARGE_TIME};
+use crate::waveform_module::{send_pulse_train, stop_pulse_train};
+use crate::watchdog_module::{self, ACTIVITY_WATCHDOG};
+use crate::debounce_timer::DEBOUNCE_TIMER;
+use crate::{check_pwm_values, compute_pwm_values, ms_to_duration_checked, release_gil, us_to_duration_checked, EnumArg, Callback, Debounce, Defaults, GammaCurve, InternPullResistorState, LogicLevel, OutputMode, Pin, PinDirection, PinRegistry, PinState, PinStats,
+            PinType, PwmConfig, TimestampClock, TriggerEdge};
 use once_cell::sync::Lazy;
+use pyo3::buffer::PyBuffer;
 use pyo3::prelude::*;
-use pyo3::types::PyTuple;
+use pyo3::types::{PyByteArray, PyBytes, PyDict, PyIterator, PyList, PyTuple};
 use pyo3::PyObject;
-use pyo3::{pyclass, pymethods, Py, PyErr, PyResult, Python};
-use rppal::gpio::{Gpio, Trigger};
-use std::collections::HashMap;
+use pyo3::{pyclass, pymethods, IntoPyObjectExt, Py, PyErr, PyResult, Python};
+use rppal::gpio::{Bias, Trigger};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, Mutex, MutexGuard};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 
 // Singleton instance of GPIOManager
@@ -16,6 +41,9 @@ static GPIO_MANAGER: Lazy<Arc<Mutex<GPIOManager>>> = Lazy::new(|| {
     Arc::new(Mutex::new(GPIOManager::new_singleton().expect("Failed to initialize GPIOManager")))
 });
 
+// The mode and logical state of a pin in a snapshot
+type PinEntry = (String, Option<PinState>);
+
 
 #[pyclass]
 /// GPIOManager provides methods to manage GPIO pins and register callbacks.
@@ -32,7 +60,7 @@ static GPIO_MANAGER: Lazy<Arc<Mutex<GPIOManager>>> = Lazy::new(|| {
 /// ```manager.set_output_pin(25, gpio_manager.OPinState.HIGH)```
 ///
 pub struct GPIOManager {
-    gpio: Arc<Mutex<PinManager>>,
+    gpio: Arc<Mutex<PinRegistry>>,
 }
 
 
@@ -40,15 +68,21 @@ impl GPIOManager {
     /// Internal method to initialize the GPIOManager singleton.
     fn new_singleton() -> PyResult<Self> {
         Ok(Self {
-            gpio: Arc::new(Mutex::new(PinManager {
+            gpio: Arc::new(Mutex::new(PinRegistry {
                 input_pins: HashMap::new(),
                 output_pins: HashMap::new(),
                 callbacks: HashMap::new(),
+                debounce: HashMap::new(),
                 pwm_setup: HashMap::new(),
+                timestamp_clock: TimestampClock::REALTIME,
+                timestamp_ns: false,
+                aliases: HashMap::new(),
+                defaults: Defaults::default(),
+                stats: HashMap::new(),
             })),
         })
     }
-    pub fn get_manager(&self) -> Arc<Mutex<PinManager>> {
+    pub fn get_manager(&self) -> Arc<Mutex<PinRegistry>> {
         Arc::clone(&self.gpio)
     }
 
@@ -67,23 +101,156 @@ impl GPIOManager {
         }
     }
 
-    pub fn is_input_pin(&self, pin_num: u8, manager: &MutexGuard<PinManager>) -> bool {
+    pub fn is_input_pin(&self, pin_num: u8, manager: &MutexGuard<PinRegistry>) -> bool {
         manager.input_pins.get(&pin_num).is_some()
     }
 
-    pub fn is_output_pin(&self, pin_num: u8, manager: &MutexGuard<PinManager>) -> bool {
+    pub fn is_output_pin(&self, pin_num: u8, manager: &MutexGuard<PinRegistry>) -> bool {
         manager.output_pins.get(&pin_num).is_some()
     }
 
+    /// Names a pin for errors, with the alias load_config gave it, like "GPIO 18 ('door_sensor')".
+    pub(crate) fn pin_name(manager: &PinRegistry, pin_num: u8) -> String {
+        match manager.aliases.iter().find(|(_, alias_pin)| **alias_pin == pin_num) {
+            Some((alias, _)) => format!("GPIO {} ('{}')", pin_num, alias),
+            None => format!("GPIO {}", pin_num),
+        }
+    }
+
+    /// Returns what a pin is set up as, with the mode names of load_config, or None when it isn't set up.
+    pub(crate) fn pin_mode_name(&self, pin_num: u8) -> Option<&'static str> {
+        let manager = metrics::lock(&self.gpio);
+        if manager.pwm_setup.contains_key(&pin_num) {
+            Some("pwm")
+        } else if manager.input_pins.contains_key(&pin_num) {
+            Some("input")
+        } else if manager.output_pins.contains_key(&pin_num) {
+            Some("output")
+        } else {
+            None
+        }
+    }
+
+    /// Returns the numbers of the pins set up as inputs, outputs or PWM, in ascending order.
+    fn configured_pins(&self) -> Vec<u8> {
+        let manager = metrics::lock(&self.gpio);
+        let mut pins: Vec<u8> = manager.input_pins.keys().chain(manager.output_pins.keys()).copied().collect();
+        pins.sort_unstable();
+        pins
+    }
+
+    /// Returns the mode and logical state of every pin set up, PWM pins have no state as their level keeps changing. The
+    /// pins are read without counting as reads in their statistics.
+    fn pin_snapshot(&self) -> BTreeMap<u8, PinEntry> {
+        let pins = self.configured_pins();
+        let manager = metrics::lock(&self.gpio);
+        pins.into_iter().filter_map(|pin_num| {
+            let mode = if manager.pwm_setup.contains_key(&pin_num) { "pwm" } else if manager.input_pins.contains_key(&pin_num) { "input" } else { "output" };
+            let pin_arc = manager.input_pins.get(&pin_num).or_else(|| manager.output_pins.get(&pin_num))?.lock().unwrap();
+            let is_high = match &pin_arc.pin {
+                PinType::Input(pin) => pin.lock().unwrap().is_high(),
+                PinType::Output(pin) => pin.lock().unwrap().is_set_high(),
+            };
+            let state = (mode != "pwm").then_some(if is_high == (pin_arc.logic_level == LogicLevel::HIGH) { PinState::HIGH } else { PinState::LOW });
+            Some((pin_num, (mode.to_string(), state)))
+        }).collect()
+    }
+
+    /// Returns the configured pin a key of the mapping protocol names, if there is one.
+    fn resolve_key(&self, key: &PinKey) -> Option<u8> {
+        let manager = metrics::lock(&self.gpio);
+        let pin_num = match key {
+            PinKey::Number(pin_num) => *pin_num,
+            PinKey::Alias(alias) => *manager.aliases.get(alias)?,
+        };
+        (manager.input_pins.contains_key(&pin_num) || manager.output_pins.contains_key(&pin_num)).then_some(pin_num)
+    }
+
+    /// Describes what a pin is set up as for errors, like "configured as INPUT (pull-up)". The pins and the PWM manager
+    /// are only tried, since the callers may hold them while building the error.
+    fn pin_mode(manager: &PinRegistry, pin_num: u8) -> String {
+        let active_low = |logic_level: LogicLevel| if logic_level == LogicLevel::LOW { ", active low" } else { "" };
+        if PWMManager::new_rust_reference().try_lock().is_ok_and(|pwm| pwm.is_pin_pwm(pin_num)) {
+            return String::from("configured for hardware PWM");
+        }
+        if let Some(config) = manager.pwm_setup.get(&pin_num) {
+            return format!("configured as software PWM ({} Hz, {}% duty cycle, {}{})", config.frequency, config.duty_cycle,
+                           if config.is_active { "running" } else { "stopped" }, active_low(config.logic_level));
+        }
+        if let Some(pin_arc) = manager.input_pins.get(&pin_num) {
+            return match pin_arc.try_lock() {
+                Ok(pin) => {
+                    let pull = match (pin.pull, pin.logic_level) {
+                        (InternPullResistorState::PULLUP, _) => "pull-up",
+                        (InternPullResistorState::PULLDOWN, _) => "pull-down",
+                        (InternPullResistorState::EXTERNAL, _) => "no pull",
+                        (InternPullResistorState::AUTO, LogicLevel::HIGH) => "auto pull-down",
+                        (InternPullResistorState::AUTO, LogicLevel::LOW) => "auto pull-up",
+                    };
+                    format!("configured as INPUT ({}{})", pull, active_low(pin.logic_level))
+                }
+                Err(_) => String::from("configured as INPUT"),
+            };
+        }
+        if let Some(pin_arc) = manager.output_pins.get(&pin_num) {
+            return match pin_arc.try_lock() {
+                Ok(pin) => {
+                    let mode = match pin.output_mode {
+                        OutputMode::PUSH_PULL => "push-pull",
+                        OutputMode::OPEN_DRAIN => "open-drain",
+                        OutputMode::OPEN_SOURCE => "open-source",
+                    };
+                    format!("configured as OUTPUT ({}{})", mode, active_low(pin.logic_level))
+                }
+                Err(_) => String::from("configured as OUTPUT"),
+            };
+        }
+        String::from("not set up")
+    }
+
+    /// Returns the activity of a pin, started when a pin that isn't set up is first used.
+    fn stats(manager: &mut PinRegistry, pin_num: u8) -> &mut PinStats {
+        manager.stats.entry(pin_num).or_insert_with(|| PinStats::new(None))
+    }
+
+    /// Returns an error naming the claim when the kernel or another process uses a pin, unless the pin is forced. Users
+    /// otherwise break an enabled bus by taking its pins as plain GPIO.
+    fn check_claim(manager: &PinRegistry, pin_num: u8, operation: &str, force: bool) -> PyResult<()> {
+        if force {
+            return Ok(());
+        }
+        match backend::current().claimed_by(pin_num) {
+            Some(claim) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{} is {}; cannot {} unless force=True",
+                                                                                       Self::pin_name(manager, pin_num), claim, operation))),
+            None => Ok(()),
+        }
+    }
+
+    /// The error of an operation a pin isn't set up for, naming the pin, what it is set up as and the operation, like
+    /// "GPIO 18 ('door_sensor') is configured as INPUT (pull-up); cannot set_output_pin".
+    pub(crate) fn pin_error(manager: &PinRegistry, pin_num: u8, operation: &str) -> PyErr {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{} is {}; cannot {}", Self::pin_name(manager, pin_num), Self::pin_mode(manager, pin_num),
+                                                                operation))
+    }
+
     fn set_pwm(&self, pwm_pin: u8) -> PyResult<()> {
-        let manager = self.gpio.lock().unwrap();
+        let manager = metrics::lock(&self.gpio);
         if let Some(pwm_config) = manager.pwm_setup.get(&pwm_pin) {
             let pin;
             let opc = manager.output_pins.get(&pwm_pin).unwrap().lock().unwrap().pin.clone();
             if let PinType::Output(output_pin_arc) = opc {
                 pin = output_pin_arc;
             } else {
-                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in output pins (something bad must have have happened to call this function with an invalid pin)"));
+                return Err(Self::pin_error(&manager, pwm_pin, "update its PWM signal"));
+            }
+            // The engine is updated before the pin is locked, since the engine locks pins while holding its channels
+            match pwm_config.engine_phase() {
+                Some(phase) if pwm_config.is_active => {
+                    pin.lock().unwrap().clear_pwm().expect("Failed to clear pwm");
+                    SOFT_PWM_ENGINE.set_channel(pwm_pin, pin, pwm_config.frequency, pwm_config.pin_duty_cycle() / 100f64, phase);
+                    return Ok(());
+                }
+                _ => SOFT_PWM_ENGINE.remove_channel(pwm_pin),
             }
             let mut pin = pin.lock().unwrap();
             if !pwm_config.is_active {
@@ -95,93 +262,416 @@ impl GPIOManager {
                 }
                 return Ok(());
             }
-            let duty_cycle = if pwm_config.logic_level == LogicLevel::LOW
-            {
-                100f64 - pwm_config.duty_cycle
-            } else {
-                pwm_config.duty_cycle
-            };
-
-            pin.set_pwm_frequency(pwm_config.frequency, duty_cycle / 100f64).expect("Failed to set pwm frequency");
+            pin.set_pwm_frequency(pwm_config.frequency, pwm_config.pin_duty_cycle() / 100f64).expect("Failed to set pwm frequency");
             Ok(())
         } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not setup for pwm"))
+            Err(Self::pin_error(&manager, pwm_pin, "update its PWM signal"))
         }
     }
 
+    /// Reads a value from the software PWM configuration of a pin, for the operation named in the error of pins without
+    /// PWM.
+    fn read_pwm_config<T>(&self, pin_num: u8, operation: &str, read: impl FnOnce(&PwmConfig) -> T) -> PyResult<T> {
+        let manager = metrics::lock(&self.gpio);
+        manager.pwm_setup
+               .get(&pin_num)
+               .map(read)
+               .ok_or_else(|| Self::pin_error(&manager, pin_num, operation))
+    }
+
     fn is_pin_pwm(&self, pin_num: u8) -> bool {
         let pwm = PWMManager::new_rust_reference();
         let pwm = pwm.lock().unwrap();
         pwm.is_pin_pwm(pin_num)
     }
 
-    fn ms_to_duration(&self, ms: Option<f64>) -> Option<Duration> {
-         match ms {
-            None => None,
-            Some(ms) => {
-                if ms < 0f64 {
-                    None
-                } else {
-                    Some(Duration::from_secs_f64(ms / 1000f64))
+    fn write_pwm_duty_cycle(&self, pin_num: u8, duty_cycle: f64) -> PyResult<()> {
+        let mut manager = metrics::lock(&self.gpio);
+        if let Some(config) = manager.pwm_setup.get_mut(&pin_num) {
+            config.duty_cycle = duty_cycle;
+            drop(manager);
+            self.set_pwm(pin_num)
+        } else {
+            Err(Self::pin_error(&manager, pin_num, "set_pwm_duty_cycle"))
+        }
+    }
+
+    /// Plays a tone on a PWM pin at half duty cycle, or silences it for None.
+    fn write_pwm_tone(&self, pin_num: u8, frequency_hz: Option<f64>) -> PyResult<()> {
+        let mut manager = metrics::lock(&self.gpio);
+        if let Some(config) = manager.pwm_setup.get_mut(&pin_num) {
+            match frequency_hz {
+                Some(frequency_hz) => {
+                    config.frequency = frequency_hz;
+                    config.duty_cycle = 50f64;
+                }
+                None => config.duty_cycle = 0f64,
+            }
+            drop(manager);
+            self.set_pwm(pin_num)
+        } else {
+            Err(Self::pin_error(&manager, pin_num, "play_melody"))
+        }
+    }
+
+    /// Converts an optional time in milliseconds, None and negative times give None.
+    fn ms_to_duration(&self, name: &str, ms: Option<f64>) -> PyResult<Option<Duration>> {
+        match ms {
+            Some(ms) if ms < 0f64 => Ok(None),
+            ms => Ok(ms.map(|ms| ms_to_duration_checked(name, ms)).transpose()?),
+        }
+    }
+
+
+    /// Waits for an edge on an input pin, returning false if the timeout expired first.
+    /// Blocks until an edge is detected on an input pin, returning whether it was detected before the timeout. The GIL
+    /// and the manager are released while waiting, so other threads and the callbacks of other pins keep running.
+    pub(crate) fn wait_for_edge_detected(&self, pin_num: u8, trigger_edge: TriggerEdge, timeout_ms: Option<f64>, debounce_ms: Option<f64>) -> PyResult<bool> {
+        let timeout = self.ms_to_duration("Timeout", timeout_ms)?;
+
+        let debounce = self.ms_to_duration("Debounce time", debounce_ms)?;
+
+        let (input_pin, trigger) = self.input_pin_trigger(pin_num, trigger_edge, "wait_for_edge")?;
+
+        release_gil(|| {
+            let mut pin = input_pin.lock().unwrap();
+            pin.set_interrupt(trigger, debounce).expect("failed to setup interrupt");
+            let event = pin.poll_interrupt(false, timeout).expect("failed to poll interrupt");
+            pin.clear_interrupt().expect("failed to clear interrupt");
+            Ok(event.is_some())
+        })
+    }
+
+    /// Returns the handle of an input pin with the trigger of the edge for its logic level, for waiting on its edges.
+    fn input_pin_trigger(&self, pin_num: u8, trigger_edge: TriggerEdge, operation: &str) -> PyResult<(Arc<Mutex<InputPin>>, Trigger)> {
+        let manager = metrics::lock(&self.gpio);
+        if let Some(pin_arc) = manager.input_pins.get(&pin_num) {
+            let pin_arc = pin_arc.lock().unwrap();
+            let trigger = trigger_edge.trigger(pin_arc.logic_level);
+            if let PinType::Input(pin_arc) = &pin_arc.pin {
+                return Ok((Arc::clone(pin_arc), trigger));
+            }
+        }
+        Err(Self::pin_error(&manager, pin_num, operation))
+    }
+
+    /// Sets up a free pin as a low output and returns its handle, for outputs driven directly from Rust.
+    pub(crate) fn claim_output_pin(&self, pin_num: u8) -> PyResult<Arc<Mutex<OutputPin>>> {
+        {
+            let manager = metrics::lock(&self.gpio);
+            if self.is_input_pin(pin_num, &manager) || self.is_output_pin(pin_num, &manager) {
+                return Err(Self::pin_error(&manager, pin_num, "claim it as an output"));
+            }
+        }
+        self.add_output_pin(pin_num, PinState::LOW.into(), Some(LogicLevel::HIGH.into()), OutputMode::PUSH_PULL.into(), false)?;
+        let manager = metrics::lock(&self.gpio);
+        let pin_arc = manager.output_pins.get(&pin_num).unwrap().lock().unwrap();
+        match &pin_arc.pin {
+            PinType::Output(pin) => Ok(Arc::clone(pin)),
+            PinType::Input(_) => Err(Self::pin_error(&manager, pin_num, "claim it as an output")),
+        }
+    }
+
+    /// Sets up a free pin as an input and returns its handle, for inputs read directly from Rust.
+    pub(crate) fn claim_input_pin(&self, pin_num: u8, pull_resistor_state: InternPullResistorState) -> PyResult<Arc<Mutex<InputPin>>> {
+        {
+            let manager = metrics::lock(&self.gpio);
+            if self.is_input_pin(pin_num, &manager) || self.is_output_pin(pin_num, &manager) {
+                return Err(Self::pin_error(&manager, pin_num, "claim it as an input"));
+            }
+        }
+        self.add_input_pin(pin_num, Some(pull_resistor_state.into()), Some(LogicLevel::HIGH.into()), false)?;
+        let manager = metrics::lock(&self.gpio);
+        let pin_arc = manager.input_pins.get(&pin_num).unwrap().lock().unwrap();
+        match &pin_arc.pin {
+            PinType::Input(pin) => Ok(Arc::clone(pin)),
+            PinType::Output(_) => Err(Self::pin_error(&manager, pin_num, "claim it as an input")),
+        }
+    }
+
+    /// Returns the handle of an output pin, setting the pin up as a low output if it is free. Pins used as inputs or for
+    /// PWM are rejected.
+    pub(crate) fn get_or_claim_output_pin(&self, pin_num: u8) -> PyResult<Arc<Mutex<OutputPin>>> {
+        {
+            let manager = metrics::lock(&self.gpio);
+            if manager.pwm_setup.contains_key(&pin_num) || self.is_pin_pwm(pin_num) {
+                return Err(Self::pin_error(&manager, pin_num, "drive it directly"));
+            }
+            if let Some(pin_arc) = manager.output_pins.get(&pin_num) {
+                let pin_arc = pin_arc.lock().unwrap();
+                if pin_arc.output_mode != OutputMode::PUSH_PULL {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                        "{} is an open drain or open source output, which can only be set with set_output_pin; cannot drive it directly", Self::pin_name(&manager, pin_num))));
+                }
+                if let PinType::Output(pin) = &pin_arc.pin {
+                    return Ok(Arc::clone(pin));
+                }
+            }
+        }
+        self.claim_output_pin(pin_num)
+    }
+
+    /// Returns the state an output pin is driven to.
+    pub(crate) fn get_output_state(&self, pin_num: u8) -> PyResult<PinState> {
+        let manager = metrics::lock(&self.gpio);
+        if let Some(pin_arc) = manager.output_pins.get(&pin_num) {
+            let pin_arc = pin_arc.lock().unwrap();
+            if let PinType::Output(out_pin) = &pin_arc.pin {
+                let is_high = out_pin.lock().unwrap().is_set_high();
+                return Ok(if is_high == (pin_arc.logic_level == LogicLevel::HIGH) { PinState::HIGH } else { PinState::LOW });
+            }
+        }
+        Err(Self::pin_error(&manager, pin_num, "read its output state"))
+    }
+
+
+    /// Sets up the interrupt delivering the edges of an input pin to its callbacks.
+    fn arm_interrupt(pin: &mut InputPin, pin_num: u8, debounce: &Debounce) -> PyResult<()> {
+        pin.set_async_interrupt(Trigger::Both, debounce.kernel_period(), move |event| {
+            let manager = GPIOManager::new_rust_reference();
+            // Call input_callback using the locked manager
+            manager.input_callback(pin_num, event);
+        }).map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Error setting up async interrupt: {:?}", e)))
+    }
+
+    /// Returns whether the edges of a pin are observed from Rust, by the event logger, the activity watchdog, the MQTT
+    /// bridge, the event server or the daemon.
+    fn is_observed(pin_num: u8) -> bool {
+        #[cfg(feature = "mqtt")]
+        if mqtt_module::is_published(pin_num) {
+            return true;
+        }
+        #[cfg(feature = "server")]
+        if event_server::is_streamed(pin_num) || daemon_module::is_claimed(pin_num) {
+            return true;
+        }
+        event_logger::is_logged(pin_num) || watchdog_module::is_watched(pin_num)
+    }
+
+    /// Sets up the interrupt of an input pin without callbacks, so its edges reach the observers in Rust.
+    fn arm_for_observers(manager: &mut PinRegistry, pin_num: u8) -> PyResult<()> {
+        if manager.callbacks.contains_key(&pin_num) {
+            return Ok(());
+        }
+        let pin = match manager.input_pins.get(&pin_num).map(|pin_arc| pin_arc.lock().unwrap().pin.clone()) {
+            Some(PinType::Input(pin)) => pin,
+            _ => return Ok(()),
+        };
+        let debounce = Debounce::new(Duration::from_millis(2), "hardware")?;
+        GPIOManager::arm_interrupt(&mut pin.lock().unwrap(), pin_num, &debounce)?;
+        manager.callbacks.insert(pin_num, Vec::new());
+        manager.debounce.insert(pin_num, debounce);
+        Ok(())
+    }
+
+    /// Names a pin, the names are returned by get_aliases.
+    pub(crate) fn set_alias(&self, name: String, pin_num: u8) {
+        metrics::lock(&self.gpio).aliases.insert(name, pin_num);
+    }
+
+    /// Returns the numbers of the input pins.
+    #[cfg(any(feature = "mqtt", feature = "http"))]
+    pub(crate) fn input_pin_numbers(&self) -> Vec<u8> {
+        metrics::lock(&self.gpio).input_pins.keys().copied().collect()
+    }
+
+    /// Sets up the interrupts of the given input pins, or of every input pin for None, for observing them from Rust.
+    pub(crate) fn observe_pins(&self, pins: Option<Vec<u8>>) -> PyResult<()> {
+        let mut manager = metrics::lock(&self.gpio);
+        let observed: Vec<u8> = match pins {
+            Some(pins) => pins.into_iter().filter(|pin_num| manager.input_pins.contains_key(pin_num)).collect(),
+            None => manager.input_pins.keys().copied().collect(),
+        };
+        for pin_num in observed {
+            GPIOManager::arm_for_observers(&mut manager, pin_num)?;
+        }
+        Ok(())
+    }
+
+    /// Clears the interrupts of the pins without callbacks that are no longer observed from Rust.
+    pub(crate) fn release_unobserved_pins(&self) {
+        let mut manager = metrics::lock(&self.gpio);
+        let unobserved: Vec<u8> = manager.callbacks
+                                         .iter()
+                                         .filter(|(pin_num, callbacks)| callbacks.is_empty() && !GPIOManager::is_observed(**pin_num))
+                                         .map(|(pin_num, _)| *pin_num)
+                                         .collect();
+        for pin_num in unobserved {
+            if let Some(PinType::Input(pin)) = manager.input_pins.get(&pin_num).map(|pin_arc| pin_arc.lock().unwrap().pin.clone()) {
+                if let Err(e) = pin.lock().unwrap().clear_async_interrupt() {
+                    log_module::warning(&format!("Unable to clear the interrupt of GPIO {}: {}", pin_num, e));
                 }
             }
+            manager.callbacks.remove(&pin_num);
+            manager.debounce.remove(&pin_num);
         }
     }
 
+    /// Drives the physical level of an output pin the way its output mode allows, open drain pins release the line
+    /// instead of driving it high and open source pins release it instead of driving it low.
+    fn drive_output(pin_num: u8, output_mode: OutputMode, pin: &mut OutputPin, high: bool) -> PyResult<()> {
+        output_mode.drive(pin, high)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Unable to switch the output driver of GPIO {}: {}", pin_num, e)))
+    }
+
+    /// Returns an error naming the operation unless the pin is set up as an output without PWM.
+    fn check_plain_output(&self, pin_num: u8, operation: &str) -> PyResult<()> {
+        let manager = metrics::lock(&self.gpio);
+        if manager.pwm_setup.contains_key(&pin_num) {
+            return Err(Self::pin_error(&manager, pin_num, operation));
+        }
+        if !manager.output_pins.contains_key(&pin_num) {
+            return Err(Self::pin_error(&manager, pin_num, operation));
+        }
+        Ok(())
+    }
 
     fn input_callback(&self, pin_num: u8, event: rppal::gpio::Event) {
-        let manager = self.gpio.lock().unwrap();
-        let callbacks = manager.callbacks.get(&pin_num).unwrap();
-        let edge = match event.trigger {
-            Trigger::RisingEdge => TriggerEdge::RISING,
-            Trigger::FallingEdge => TriggerEdge::FALLING,
+        metrics::count_pin(PinCounter::Interrupts, pin_num);
+        let mut manager = metrics::lock(&self.gpio);
+        Self::stats(&mut manager, pin_num).interrupts += 1;
+        let rising = match event.trigger {
+            Trigger::RisingEdge => true,
+            Trigger::FallingEdge => false,
             _ => {
-                eprintln!("Unknown trigger event");
+                metrics::count_pin(PinCounter::DroppedEvents, pin_num);
+                log_module::warning(&format!("Dropped an edge of GPIO {} with an unknown trigger", pin_num));
                 return;
             }
         };
-        let trigger_time = {
-            let now = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .expect("System time should be after Unix epoch");
-            let boot_time = now.checked_sub(event.timestamp)
-                               .expect("Failed to calculate boot time");
-            let event_unix_time = boot_time + event.timestamp; // Add duration since boot to boot time
-            event_unix_time.as_secs_f64()
+        let (rising, timestamp) = match manager.debounce.get_mut(&pin_num) {
+            Some(debounce) => {
+                let filtered = debounce.edge(rising, event.timestamp);
+                if let Some(settle_after) = filtered.settle_after {
+                    DEBOUNCE_TIMER.schedule(pin_num, debounce.generation, timing::now() + settle_after);
+                }
+                match filtered.edge {
+                    Some(edge) => edge,
+                    None => {
+                        if filtered.settle_after.is_none() {
+                            metrics::count_pin(PinCounter::DroppedEvents, pin_num);
+                            log_module::debug(&format!("Dropped a bouncing edge of GPIO {}", pin_num));
+                        }
+                        return;
+                    }
+                }
+            }
+            None => (rising, event.timestamp),
+        };
+        self.dispatch_edge(manager, pin_num, rising, timestamp);
+    }
+
+    /// Reports the edge of a pin whose debounce filter settled once the line held its level.
+    pub(crate) fn settle_debounce(&self, pin_num: u8, generation: u64) {
+        let mut manager = metrics::lock(&self.gpio);
+        if let Some((rising, timestamp)) = manager.debounce.get_mut(&pin_num).and_then(|debounce| debounce.settle(generation)) {
+            self.dispatch_edge(manager, pin_num, rising, timestamp);
+        }
+    }
+
+    /// Reports a debounced edge of an input pin to its observers and calls its callbacks, unlocking the manager before
+    /// the callbacks run.
+    fn dispatch_edge(&self, mut manager: MutexGuard<PinRegistry>, pin_num: u8, rising: bool, timestamp: Duration) {
+        watchdog_module::note_edge(pin_num);
+        let edge = if rising { TriggerEdge::RISING } else { TriggerEdge::FALLING };
+        if let Some(logic_level) = manager.input_pins.get(&pin_num).map(|pin_arc| pin_arc.lock().unwrap().logic_level) {
+            let high = (edge == TriggerEdge::RISING) == (logic_level == LogicLevel::HIGH);
+            Self::stats(&mut manager, pin_num).observe(if high { PinState::HIGH } else { PinState::LOW });
+        }
+        let trigger_time_ns = manager.timestamp_clock.convert_monotonic(timestamp);
+        let timestamp_ns = manager.timestamp_ns;
+        event_logger::log_event(pin_num, edge, trigger_time_ns);
+        #[cfg(any(feature = "mqtt", feature = "server"))]
+        if let Some(pin_arc) = manager.input_pins.get(&pin_num) {
+            let high = (edge == TriggerEdge::RISING) == (pin_arc.lock().unwrap().logic_level == LogicLevel::HIGH);
+            let state = if high { PinState::HIGH } else { PinState::LOW };
+            #[cfg(feature = "mqtt")]
+            mqtt_module::publish_state(pin_num, state);
+            #[cfg(feature = "server")]
+            {
+                event_server::publish_edge(pin_num, edge, state, trigger_time_ns);
+                daemon_module::publish_edge(pin_num, edge, state, trigger_time_ns);
+            }
+        }
+        let callbacks: Vec<Callback> = manager.callbacks.get(&pin_num).unwrap().iter()
+                                              .filter(|callback| callback.trigger_edge == TriggerEdge::BOTH || callback.trigger_edge == edge)
+                                              .cloned()
+                                              .collect();
+        Self::stats(&mut manager, pin_num).callbacks += callbacks.len() as u64;
+        let state_pin = match manager.input_pins.get(&pin_num).map(|pin_arc| pin_arc.lock().unwrap().clone()) {
+            Some(Pin { pin: PinType::Input(pin), logic_level, .. }) if callbacks.iter().any(|callback| callback.send_state) => Some((pin, logic_level)),
+            _ => None,
         };
+        // The callbacks run without the manager locked, so they can use the manager and other threads aren't held up
+        // while they run, which the free-threaded build doesn't serialize through the GIL
+        drop(manager);
+        if callbacks.is_empty() {
+            return;
+        }
+        // The level is read once for all the callbacks, when they are dispatched rather than from the edge
+        let state = state_pin.map(|(pin, logic_level)| {
+            if pin.lock().unwrap().is_high() == (logic_level == LogicLevel::HIGH) { PinState::HIGH } else { PinState::LOW }
+        });
 
         // Re-acquire the GIL for calling the Python callback
+        let mut collected = Vec::new();
         Python::with_gil(|py| {
             for callback in callbacks {
-                if callback.trigger_edge != TriggerEdge::BOTH && callback.trigger_edge != edge {
-                    continue;
-                }
-                let cb = callback.callable.lock().unwrap().clone_ref(py);
+                let cb = match callback.target(py) {
+                    Ok(Some(cb)) => cb,
+                    Ok(None) => {
+                        collected.push(callback.callable);
+                        continue;
+                    }
+                    Err(e) => {
+                        log_module::exception(&format!("Unable to resolve a weak callback of GPIO {}", pin_num), &e);
+                        continue;
+                    }
+                };
                 let args = &callback.args.lock().unwrap();
 
                 // Prepare new arguments
-                let mut new_args: Vec<PyObject> = Vec::new();
-
-                if callback.send_time {
-                    new_args.push(trigger_time.to_object(py)); // Add timestamp as the first argument
-                }
-                if callback.send_edge {
-                    new_args.push(edge.into_py(py)); // Add edge as the second argument
-                }
-                if let Ok(py_tuple) = args.downcast_bound::<PyTuple>(py) {
-                    for item in py_tuple.iter() {
-                        new_args.push(item.to_object(py));
+                let call = || -> PyResult<PyObject> {
+                    let mut new_args: Vec<PyObject> = Vec::new();
+
+                    if callback.send_time {
+                        // Add timestamp as the first argument
+                        if timestamp_ns {
+                            new_args.push(trigger_time_ns.into_py_any(py)?);
+                        } else {
+                            new_args.push((trigger_time_ns as f64 / 1_000_000_000f64).into_py_any(py)?);
+                        }
+                    }
+                    if callback.send_edge {
+                        new_args.push(edge.into_py_any(py)?); // Add edge as the second argument
+                    }
+                    if let (true, Some(state)) = (callback.send_state, state) {
+                        new_args.push(state.into_py_any(py)?);
+                    }
+                    if let Ok(py_tuple) = args.downcast_bound::<PyTuple>(py) {
+                        new_args.extend(py_tuple.iter().map(|item| item.unbind()));
                     }
-                }
 
-                let new_args_tuple = PyTuple::new_bound(py, new_args);
+                    // Call the Python callback
+                    cb.call(py, PyTuple::new(py, new_args)?, callback.kwargs.as_ref().map(|kwargs| kwargs.bind(py)))
+                };
 
-                // Call the Python callback
-                if let Err(e) = cb.call1(py, new_args_tuple) {
-                    e.print(py);
+                metrics::count_pin(PinCounter::CallbackInvocations, pin_num);
+                if let Err(e) = call() {
+                    metrics::count_pin(PinCounter::CallbackErrors, pin_num);
+                    log_module::exception(&format!("Exception in a callback of GPIO {}", pin_num), &e);
                 }
             }
         });
+        // Weak callbacks whose object was garbage collected are unregistered, the interrupt stays set up like for a pin
+        // that is only observed from Rust
+        if !collected.is_empty() {
+            let mut manager = metrics::lock(&self.gpio);
+            if let Some(callbacks) = manager.callbacks.get_mut(&pin_num) {
+                callbacks.retain(|callback| !collected.iter().any(|callable| Arc::ptr_eq(callable, &callback.callable)));
+            }
+            log_module::debug(&format!("Unregistered {} garbage collected callbacks of GPIO {}", collected.len(), pin_num));
+        }
     }
 }
 
@@ -198,58 +688,99 @@ impl GPIOManager {
         GPIOManager::shared(py)
     }
 
+    /// Connects to a remote Pi running the event server (see serve_events), or to the daemon owning the GPIO of this
+    /// Pi (see serve_daemon), returning a manager driving its pins with the API of the GPIOManager.
+    ///
+    /// Parameters:
+    /// - ```address``` (str): The address of the server as "host:port", or the socket of the daemon as "unix:<path>".
+    /// - ```timeout_ms``` (float): The longest time to wait for the connection and for the reply to every command
+    ///   (default is 5000).
+    ///
+    /// Returns:
+    /// - ```RemoteGPIOManager```: The manager of the remote Pi.
+    ///
+    /// Example usage:
+    /// ```remote = gpio_manager.GPIOManager.connect("other-pi:8765")```
+    ///
+    #[cfg(feature = "server")]
+    #[staticmethod]
+    #[pyo3(signature = (address, timeout_ms = 5000f64))]
+    fn connect(py: Python, address: &str, timeout_ms: f64) -> PyResult<RemoteGPIOManager> {
+        if timeout_ms <= 0f64 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Timeout must be greater than 0, The value {} does not meet this condition", timeout_ms)));
+        }
+        let timeout = ms_to_duration_checked("Timeout", timeout_ms)?;
+        py.allow_threads(|| RemoteGPIOManager::connect(address, timeout))
+    }
+
 
     /// Sets up an input pin but does not assign a callback yet.
     ///
     /// Parameters:
     /// - ```pin_num``` (int): The GPIO pin to configure as input.
+    /// - ```force``` (bool): Takes the pin even when the kernel or another process claims it, such as the pins of an
+    ///   enabled I2C, SPI or UART bus (default is False).
     ///
     /// Example usage:
     /// ```manager.add_input_pin(18)```
     ///
-    #[pyo3(signature = (pin_num, pull_resistor_state = InternPullResistorState::AUTO, logic_level = LogicLevel::HIGH)
-    )]
-    fn add_input_pin(&self, pin_num: u8, pull_resistor_state: InternPullResistorState, logic_level: LogicLevel) -> PyResult<()> {
+    #[pyo3(signature = (pin_num, pull_resistor_state = None, logic_level = None, force = false))]
+    pub(crate) fn add_input_pin(&self, pin_num: u8, pull_resistor_state: Option<EnumArg<InternPullResistorState>>, logic_level: Option<EnumArg<LogicLevel>>,
+                                force: bool) -> PyResult<()> {
+        let defaults = metrics::lock(&self.gpio).defaults;
+        let pull_resistor_state = pull_resistor_state.map_or(defaults.pull, |pull_resistor_state| pull_resistor_state.0);
+        let logic_level = logic_level.map_or(defaults.logic_level, |logic_level| logic_level.0);
         if self.is_pin_pwm(pin_num) {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin configured for hardware PWM, please reset the pin to use as regular input pin"));
+            return Err(Self::pin_error(&metrics::lock(&self.gpio), pin_num, "add_input_pin"));
         }
-        let mut manager = self.gpio.lock().unwrap();
+        let mut manager = metrics::lock(&self.gpio);
         if self.is_output_pin(pin_num, &manager) {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin found in output pins (pin is already setup as an output pin"));
-        }
-        let gpio = Gpio::new().map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?;
-        let input_pin = match pull_resistor_state {
-            InternPullResistorState::PULLUP =>
-                gpio
-                    .get(pin_num)
-                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?
-                    .into_input_pullup(), // Return the input_pullup pin
-
-            InternPullResistorState::PULLDOWN => gpio
-                .get(pin_num)
-                .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?
-                .into_input_pulldown(), // Return the input_pulldown pin
-
-            InternPullResistorState::EXTERNAL => gpio.get(pin_num)
-                                                     .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?
-                .into_input(),
-            InternPullResistorState::AUTO => if logic_level == LogicLevel::HIGH {
-                gpio.get(pin_num)
-                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?
-                    .into_input_pulldown()
-            } else {
-                gpio.get(pin_num)
-                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?
-                    .into_input_pullup()
-            },
-        };
+            return Err(Self::pin_error(&manager, pin_num, "add_input_pin"));
+        }
+        Self::check_claim(&manager, pin_num, "add_input_pin", force)?;
+        let bias = pull_resistor_state.bias(logic_level);
+        let input_pin = InputPin::new(pin_num, bias)?;
         let input_pin = Pin {
             pin: PinType::Input(Arc::new(Mutex::new(input_pin))),
             logic_level,
+            output_mode: OutputMode::PUSH_PULL,
+            pull: pull_resistor_state,
         };
 
         manager.input_pins.insert(pin_num, Arc::new(Mutex::new(input_pin)));
+        manager.stats.insert(pin_num, PinStats::new(None));
+        if GPIOManager::is_observed(pin_num) {
+            GPIOManager::arm_for_observers(&mut manager, pin_num)?;
+        }
+
+        Ok(())
+    }
 
+    /// Changes the pull resistor of a configured input pin, its callbacks and interrupt stay set up.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    /// - ```pull_resistor_state``` (InternPullResistorState): The new pull resistor, AUTO pulls the pin to its inactive
+    ///   level and EXTERNAL disables the internal pull resistor.
+    ///
+    /// Example usage:
+    /// ```manager.set_pull(18, gpio_manager.InternPullResistorState.PULLUP)```
+    #[pyo3(signature = (pin_num, pull_resistor_state))]
+    fn set_pull(&self, pin_num: u8, pull_resistor_state: EnumArg<InternPullResistorState>) -> PyResult<()> {
+        let pull_resistor_state = pull_resistor_state.0;
+        let (pin, logic_level) = {
+            let manager = metrics::lock(&self.gpio);
+            let pin_arc = manager.input_pins.get(&pin_num).map(|pin_arc| pin_arc.lock().unwrap().clone());
+            match pin_arc {
+                Some(Pin { pin: PinType::Input(pin), logic_level, .. }) => (pin, logic_level),
+                _ => return Err(Self::pin_error(&manager, pin_num, "set_pull")),
+            }
+        };
+        let bias = pull_resistor_state.bias(logic_level);
+        pin.lock().unwrap().set_bias(bias).map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Unable to set the pull resistor of GPIO {}: {}", pin_num, e)))?;
+        if let Some(pin_arc) = metrics::lock(&self.gpio).input_pins.get(&pin_num) {
+            pin_arc.lock().unwrap().pull = pull_resistor_state;
+        }
         Ok(())
     }
 
@@ -260,46 +791,77 @@ impl GPIOManager {
     /// - ```trigger_edge``` (str): The edge trigger type ("rising", "falling", or "both").
     /// - ```callback``` (function): The callback function to be invoked on pin change.
     /// - ```args``` (tuple): The arguments to pass to the callback function.
-    /// - ```debounce_time_ms``` (int): The debounce time in milliseconds.
+    /// - ```kwargs``` (dict): The keyword arguments to pass to the callback function (default is None).
+    /// - ```include_pin_state``` (bool): Whether the callback receives the state of the pin read when it is called,
+    ///   HIGH when the pin is active for its logic level, after the trigger time and edge when they are included
+    ///   (default is False).
+    /// - ```priority``` (int): The callbacks of a pin are called from the highest priority to the lowest, callbacks of
+    ///   the same priority in the order they were assigned (default is 0).
+    /// - ```weak``` (bool): Whether the manager only holds a weak reference to the callback, so it doesn't keep the
+    ///   object of a bound method alive. Once the object is garbage collected the callback is unregistered at the next
+    ///   edge of the pin (default is False).
+    /// - ```debounce_time_ms``` (int): The debounce time in milliseconds (default is 2, changed with set_defaults).
+    /// - ```filter``` (str): "hardware" debounces in the gpiochip character device, so glitches never reach the
+    ///   process and an edge is reported once the line is stable for the debounce time. "software" delivers the
+    ///   first edge right away and drops the edges following it within the debounce time. "stable" reports an edge once
+    ///   the line held its new level for the debounce time, timed in Rust. "integrator" counts the time the line
+    ///   spends high up and the time it spends low down, between 0 and the debounce time, and reports an edge when the
+    ///   count reaches either end, so short glitches only delay an edge instead of restarting it (default is
+    ///   "hardware").
+    ///   The debounce time and filter of the first callback of a pin apply to all its callbacks.
     ///
     /// Example usage:
     /// ```manager.assign_callback(18, gpio_manager.TriggerEdge.FALLING, button_callback)```
     ///
-    #[pyo3(signature = (pin_num, callback, trigger_edge = TriggerEdge::BOTH, debounce_time_ms = 2f64, args = None, include_trigger_time = false,
-    include_trigger_edge = false))]
-    fn assign_callback(
+    #[pyo3(signature = (pin_num, callback, trigger_edge = EnumArg(TriggerEdge::BOTH), debounce_time_ms = None, args = None, include_trigger_time = false,
+    include_trigger_edge = false, filter = "hardware", kwargs = None, include_pin_state = false, priority = 0, weak = false))]
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn assign_callback(
         &self,
         py: Python,
         pin_num: u8,
         callback: PyObject,
-        trigger_edge: TriggerEdge,
-        debounce_time_ms: f64,
+        trigger_edge: EnumArg<TriggerEdge>,
+        debounce_time_ms: Option<f64>,
         args: Option<&Bound<'_, PyTuple>>, // Using Option to allow args to be None
         include_trigger_time: bool,
         include_trigger_edge: bool,
+        filter: &str,
+        kwargs: Option<&Bound<'_, PyDict>>,
+        include_pin_state: bool,
+        priority: i32,
+        weak: bool,
     ) -> PyResult<()> {
-        let manager = self.gpio.lock().unwrap();
+        let trigger_edge = trigger_edge.0;
+        let debounce_time_ms = debounce_time_ms.unwrap_or(metrics::lock(&self.gpio).defaults.debounce_ms);
+        let debounce = Debounce::new(ms_to_duration_checked("Debounce time", debounce_time_ms)?, filter)?;
+        let manager = metrics::lock(&self.gpio);
 
         if !self.is_input_pin(pin_num, &manager) {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in input pins (pin is either output or not setup)"));
+            return Err(Self::pin_error(&manager, pin_num, "assign_callback"));
         }
         drop(manager);
         let callable: &Bound<PyAny> = callback.bind(py);
         if !callable.is_callable() {
             return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>("Object is not callable"));
         }
-        let empty_tuple = PyTuple::empty_bound(py);
+        let empty_tuple = PyTuple::empty(py);
         let args = args.unwrap_or_else(|| &empty_tuple);
 
-        let args_arc = Arc::new(Mutex::new(args.to_object(py)));
+        let args_arc = Arc::new(Mutex::new(args.clone().into_any().unbind()));
 
         let manager_clone = Arc::clone(&self.gpio);
-        let callable = Python::with_gil(|py| {
+        let callable = if weak {
+            // Bound methods are created on every access, so they are referenced through the object and the function
+            let weakref = py.import("weakref")?;
+            let kind = if callable.hasattr("__self__")? && callable.hasattr("__func__")? { "WeakMethod" } else { "ref" };
+            weakref.getattr(kind)?.call1((callable,))?.unbind()
+        } else {
             callback.clone_ref(py)
-        });
+        };
 
         let trigger = {
-            let pin_logic_level = manager_clone.lock().unwrap().input_pins.get(&pin_num).unwrap().lock().unwrap().logic_level;
+            let pin_logic_level = metrics::lock(&manager_clone).input_pins.get(&pin_num).unwrap().lock().unwrap().logic_level;
             let trigger_event = match trigger_edge {
                 TriggerEdge::RISING => if pin_logic_level == LogicLevel::HIGH {
                     TriggerEdge::RISING
@@ -318,12 +880,12 @@ impl GPIOManager {
         };
 
         let pin_arc = {
-            let manager = manager_clone.lock().unwrap();
+            let manager = metrics::lock(&manager_clone);
             let pin = manager.input_pins.get(&pin_num).unwrap().clone().lock().unwrap().pin.clone();
             if let PinType::Input(pin_arc) = pin {
                 pin_arc
             } else {
-                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in input pins (pin is either output or not setup)"));
+                return Err(Self::pin_error(&manager, pin_num, "assign_callback"));
             }
         };
 
@@ -332,172 +894,1044 @@ impl GPIOManager {
             callable,
             trigger_edge: trigger,
             args: args_arc,
+            kwargs: kwargs.map(|kwargs| Arc::new(kwargs.clone().unbind())),
             send_time: include_trigger_time,
             send_edge: include_trigger_edge,
+            send_state: include_pin_state,
+            priority,
+            weak,
         };
 
-        let mut manager = manager_clone.lock().unwrap();
-        let callbacks_set = manager.callbacks.get(&pin_num).is_some();
+        let mut manager = metrics::lock(&manager_clone);
+        // Pins only logged by the event logger have no callbacks, their interrupt is set up again with the debounce time
+        let callbacks_set = manager.callbacks.get(&pin_num).is_some_and(|callbacks| !callbacks.is_empty());
 
         if let Some(callback_vec) = manager.callbacks.get_mut(&pin_num) {
-            callback_vec.push(callback);
+            let index = callback_vec.iter().position(|existing| existing.priority < priority).unwrap_or(callback_vec.len());
+            callback_vec.insert(index, callback);
         } else {
             manager.callbacks.insert(pin_num, vec![callback]);
         }
         if !callbacks_set {
-            let mut pin = pin_arc.lock().unwrap();
-            pin.set_async_interrupt(Trigger::Both, Some(Duration::from_secs_f64(debounce_time_ms / 1000f64)), move |event| {
-                let manager = GPIOManager::new_rust_reference();
-                // Call input_callback using the locked manager
-                manager.input_callback(pin_num, event);
-            }).expect("Error setting up async interrupt");
+            GPIOManager::arm_interrupt(&mut pin_arc.lock().unwrap(), pin_num, &debounce)?;
+            manager.debounce.insert(pin_num, debounce);
         }
         drop(manager);
         Ok(())
     }
 
-    /// Sets up an output pin.
+    /// Sets the clock and resolution of the trigger times passed to callbacks assigned with include_trigger_time.
     ///
     /// Parameters:
-    /// - ```pin_num``` (int): The GPIO pin to configure as output.
+    /// - ```clock``` (TimestampClock): MONOTONIC counts from an arbitrary point and never jumps, REALTIME is the
+    ///   time since the Unix epoch and follows changes of the system time, BOOTTIME is like MONOTONIC but keeps
+    ///   counting while the system is suspended (default is REALTIME).
+    /// - ```nanoseconds``` (bool): Whether the trigger time is an int in nanoseconds instead of a float in seconds
+    ///   (default is False).
     ///
     /// Example usage:
-    /// ```manager.add_output_pin(25)```
+    /// ```manager.set_timestamp_clock(gpio_manager.TimestampClock.MONOTONIC, nanoseconds=True)```
     ///
-    #[pyo3(signature = (pin_num, pin_state = PinState::LOW, logic_level = LogicLevel::HIGH))]
-    fn add_output_pin(&self, pin_num: u8, pin_state: PinState, logic_level: LogicLevel) -> PyResult<()> {
-        if self.is_pin_pwm(pin_num) {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin configured for hardware PWM, please reset the pin to use as regular input pin"));
-        }
-        let mut manager = self.gpio.lock().unwrap();
-        if self.is_input_pin(pin_num, &manager) {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin found in input pins (pin is already setup as an input pin)"));
-        }
-        let gpio = Gpio::new().map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?;
-        let mut output_pin = gpio.get(pin_num)
-                                 .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?
-            .into_output_high();
-
-        match pin_state {
-            PinState::HIGH => if logic_level == LogicLevel::HIGH {
-                output_pin.set_high();
-            } else {
-                output_pin.set_low();
-            },
-            PinState::LOW => if logic_level == LogicLevel::HIGH {
-                output_pin.set_low();
-            } else {
-                output_pin.set_high();
-            },
-        };
-        let output_pin = Pin {
-            pin: PinType::Output(Arc::new(Mutex::new(output_pin))),
-            logic_level,
-        };
+    #[pyo3(signature = (clock = EnumArg(TimestampClock::REALTIME), nanoseconds = false))]
+    fn set_timestamp_clock(&self, clock: EnumArg<TimestampClock>, nanoseconds: bool) {
+        let clock = clock.0;
+        let mut manager = metrics::lock(&self.gpio);
+        manager.timestamp_clock = clock;
+        manager.timestamp_ns = nanoseconds;
+    }
 
-        manager.output_pins.insert(pin_num, Arc::new(Mutex::new(output_pin)));
+    /// Starts logging the edges of input pins to a file from Rust, without calling into Python for every edge. The file
+    /// is appended to, and a logger already running is replaced.
+    ///
+    /// Parameters:
+    /// - ```path``` (str): The file the edges are written to.
+    /// - ```pins``` (list[int]): The input pins logged, every input pin if None (default is None). Pins set up later
+    ///   are logged as well.
+    /// - ```format``` (str): "csv" writes lines of timestamp,pin,edge under a header, "jsonl" writes a JSON object
+    ///   per line (default is "csv").
+    /// - ```flush_interval_ms``` (int): The time in ms between writes of the buffered lines to the file (default is
+    ///   1000).
+    ///
+    /// The timestamps are in seconds and follow the clock chosen with set_timestamp_clock, the edges are "rising" or
+    /// "falling". Pins without callbacks are debounced in hardware for 2 ms. The file can be replayed into the mock
+    /// backend with gpio_manager.mock.replay.
+    ///
+    /// Example usage:
+    /// ```manager.start_logger("edges.csv", [17, 27])```
+    ///
+    #[pyo3(signature = (path, pins = None, format = "csv", flush_interval_ms = 1000))]
+    fn start_logger(&self, path: &str, pins: Option<Vec<u8>>, format: &str, flush_interval_ms: u64) -> PyResult<()> {
+        event_logger::start(path, pins.clone(), format, Duration::from_millis(flush_interval_ms))?;
+        self.observe_pins(pins)
+    }
 
-        Ok(())
+    /// Stops the event logger and writes the lines it buffered to the file.
+    ///
+    /// Returns:
+    /// - ```bool```: Whether the logger was running.
+    ///
+    /// Example usage:
+    /// ```manager.stop_logger()```
+    ///
+    fn stop_logger(&self) -> PyResult<bool> {
+        let running = event_logger::stop()?;
+        self.release_unobserved_pins();
+        Ok(running)
     }
 
-    /// Sets up a PWM output pin.
+    /// Sets up the pins described by a configuration file or dict, so the wiring lives in a config file instead of
+    /// setup code. The whole configuration is validated before any pin is set up, errors name the offending entry,
+    /// and the pins already set up are reset when one fails.
+    ///
+    /// The configuration has a `pins` table of pins by name, the names become aliases of the pins. Every pin has a
+    /// `pin` number and a `mode` of "input" (default), "output" or "pwm". Inputs take a `pull` of "auto", "up", "down"
+    /// or "external", outputs a `state` of "high" or "low" and an `output_mode` of "push_pull", "open_drain" or
+    /// "open_source", and PWM pins the `frequency_hz`, `duty_cycle`, `period_ms` and `pulse_width_ms` of setup_pwm
+    /// and `start` to start the signal. Every pin takes a `logic_level` of "high" or "low". The `callbacks` list
+    /// written by export_config is accepted on inputs and ignored.
     ///
     /// Parameters:
-    /// - ```pin_num``` (int): The GPIO pin to configure as output.
-    /// - ```period_ms``` (int): The period of the PWM signal in milliseconds.
-    /// - ```pulse_width_us``` (int): The pulse width of the PWM signal in microseconds.
+    /// - ```config``` (str | os.PathLike | dict): The path of a .toml, .json, .yaml or .yml file, or the configuration
+    ///   as a dict. TOML needs Python 3.11 or the tomli package, YAML the PyYAML package.
+    ///
+    /// Returns:
+    /// - ```dict[str, int]```: The pins by name.
     ///
     /// Example usage:
-    /// ```manager.set_pwm(25, 20, 1200)```
-    #[pyo3(signature = (pin_num, frequency_hz = None, duty_cycle = None, period_ms = None, pulse_width_ms = None, logic_level = LogicLevel::HIGH)
-    )]
-    fn setup_pwm(&self, pin_num: u8, frequency_hz: Option<f64>, duty_cycle: Option<f64>, period_ms: Option<f64>, pulse_width_ms: Option<f64>, logic_level: LogicLevel) -> PyResult<()> {
-        if self.is_pin_pwm(pin_num) {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin configured for hardware PWM, please reset the pin to use as regular input pin"));
-        }
-        check_pwm_values(&frequency_hz, &duty_cycle, &period_ms, &pulse_width_ms)?;
+    /// ```python
+    /// # wiring.toml
+    /// # [pins.button]
+    /// # pin = 17
+    /// # pull = "up"
+    /// #
+    /// # [pins.led]
+    /// # pin = 27
+    /// # mode = "output"
+    /// pins = manager.load_config("wiring.toml")
+    /// manager.set_output_pin(pins["led"], gpio_manager.PinState.HIGH)
+    /// ```
+    #[pyo3(signature = (config))]
+    fn load_config<'py>(&self, py: Python<'py>, config: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyDict>> {
+        config_module::load_config(py, self, config)
+    }
 
-        let mut manager = self.gpio.lock().unwrap();
+    /// Returns the names given to the pins by load_config, a name is forgotten when its pin is reset.
+    ///
+    /// Returns:
+    /// - ```dict[str, int]```: The pins by name.
+    ///
+    /// Example usage:
+    /// ```led = manager.get_aliases()["led"]```
+    ///
+    fn get_aliases(&self) -> HashMap<String, u8> {
+        metrics::lock(&self.gpio).aliases.clone()
+    }
 
-        if let Some(_) = manager.pwm_setup.get(&pin_num) {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin already configured for PWM"));
-        } else if self.is_input_pin(pin_num, &manager) {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin found in input pins (pin is already setup as an input pin)"));
-        } else if self.is_output_pin(pin_num, &manager) {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin found in output pins (pin is already setup as an output pin)"));
-        } else {
-            drop(manager);
-            match logic_level {
-                LogicLevel::LOW => {
-                    self.add_output_pin(pin_num, PinState::LOW, logic_level)?;
-                }
-                LogicLevel::HIGH => {
-                    self.add_output_pin(pin_num, PinState::LOW, logic_level)?;
-                }
-            }
+    /// Returns whether a pin is set up, given by number or by alias.
+    ///
+    /// Example usage:
+    /// ```if 18 in manager: print("GPIO 18 is set up")```
+    fn __contains__(&self, key: PinKey) -> bool {
+        self.resolve_key(&key).is_some()
+    }
 
-            manager = self.gpio.lock().unwrap();
+    /// Returns a GPIOPin handle on a pin that is set up, given by number or by alias. Raises a KeyError for pins that
+    /// aren't set up.
+    ///
+    /// Example usage:
+    /// ```manager["led"].write(gpio_manager.PinState.HIGH)```
+    fn __getitem__(&self, key: PinKey) -> PyResult<GPIOPin> {
+        match self.resolve_key(&key) {
+            Some(pin_num) => Ok(GPIOPin::new(pin_num)),
+            None => Err(PyErr::new::<pyo3::exceptions::PyKeyError, _>(match key {
+                PinKey::Number(pin_num) => format!("GPIO {} is not set up", pin_num),
+                PinKey::Alias(alias) => format!("No pin set up is named '{}'", alias),
+            })),
         }
+    }
 
-        let (frequency, duty_cycle_percent) = compute_pwm_values(&frequency_hz, &duty_cycle, &period_ms, &pulse_width_ms);
+    /// Iterates over the numbers of the pins set up, in ascending order.
+    ///
+    /// Example usage:
+    /// ```for pin_num in manager: print(pin_num, manager[pin_num].mode)```
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+        PyList::new(py, self.configured_pins())?.as_any().try_iter()
+    }
 
-        if pulse_width_ms.is_some() && pulse_width_ms.unwrap() / 1000f64 > 1f64 / frequency {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pulse width must be less than period (pwm not setup"));
-        }
+    /// Returns the number of pins set up.
+    fn __len__(&self) -> usize {
+        self.configured_pins().len()
+    }
 
-        if self.is_output_pin(pin_num, &manager) {
-            manager.pwm_setup.insert(pin_num, PwmConfig {
-                frequency,
-                duty_cycle: duty_cycle_percent,
+    /// Captures the mode and logical state of every pin set up, to compare later with diff. PWM pins are captured with
+    /// a state of None, as their level keeps changing.
+    ///
+    /// Returns:
+    /// - ```dict[int, tuple[str, PinState | None]]```: The mode ("input", "output" or "pwm") and state of every pin.
+    ///
+    /// Example usage:
+    /// ```before = manager.snapshot()```
+    fn snapshot(&self) -> BTreeMap<u8, PinEntry> {
+        self.pin_snapshot()
+    }
+
+    /// Compares the pins with a snapshot, returning the pins whose mode or state changed since it was taken. Pins set
+    /// up since then have None as their old entry, and pins reset since then None as their new entry.
+    ///
+    /// Parameters:
+    /// - ```snapshot``` (dict[int, tuple[str, PinState | None]]): A snapshot returned by snapshot.
+    ///
+    /// Returns:
+    /// - ```dict[int, tuple[tuple[str, PinState | None] | None, tuple[str, PinState | None] | None]]```: The old and new
+    ///   entry of every pin that changed.
+    ///
+    /// Example usage:
+    /// ```before = manager.snapshot()```
+    ///
+    /// ```run_routine()```
+    ///
+    /// ```assert set(manager.diff(before)) <= {5, 6}```
+    #[pyo3(signature = (snapshot))]
+    fn diff(&self, snapshot: BTreeMap<u8, PinEntry>) -> BTreeMap<u8, (Option<PinEntry>, Option<PinEntry>)> {
+        let mut current = self.pin_snapshot();
+        let mut changes = BTreeMap::new();
+        for (pin_num, before) in snapshot {
+            let after = current.remove(&pin_num);
+            if after.as_ref() != Some(&before) {
+                changes.insert(pin_num, (Some(before), after));
+            }
+        }
+        changes.extend(current.into_iter().map(|(pin_num, after)| (pin_num, (None, Some(after)))));
+        changes
+    }
+
+    /// Returns the configuration of the pins set up, in the format of load_config, for saving a working setup and
+    /// restoring it with apply_config. Pins are named by their alias, or as "gpio<N>" without one. Outputs are saved
+    /// with the state they are driven to and PWM pins with their frequency, duty cycle and whether they run.
+    ///
+    /// Inputs with callbacks get a `callbacks` list describing them, with the name of the function, the trigger edge,
+    /// the debounce and the extra arguments asked for. Functions can't be saved, so the list is only informational and
+    /// the callbacks must be assigned again after restoring.
+    ///
+    /// Parameters:
+    /// - ```path``` (str | os.PathLike): The .toml, .json, .yaml or .yml file to write the configuration to, or None to
+    ///   only return it (default is None). YAML needs the PyYAML package.
+    ///
+    /// Returns:
+    /// - ```dict```: The configuration, with a `pins` table of pins by name.
+    ///
+    /// Example usage:
+    /// ```python
+    /// manager.export_config("wiring.toml")
+    /// ```
+    #[pyo3(signature = (path = None))]
+    fn export_config<'py>(&self, py: Python<'py>, path: Option<&Bound<'py, PyAny>>) -> PyResult<Bound<'py, PyDict>> {
+        config_module::export_config(py, self, path)
+    }
+
+    /// Replaces the pins set up with the pins of a configuration, as returned or written by export_config. Unlike
+    /// load_config, the pins currently set up are reset first, after the configuration is validated.
+    ///
+    /// Parameters:
+    /// - ```config``` (str | os.PathLike | dict): The path of a .toml, .json, .yaml or .yml file, or the configuration
+    ///   as a dict.
+    ///
+    /// Returns:
+    /// - ```dict[str, int]```: The pins by name.
+    ///
+    /// Example usage:
+    /// ```python
+    /// saved = manager.export_config()
+    /// # ... experiment with the pins
+    /// manager.apply_config(saved)
+    /// ```
+    #[pyo3(signature = (config))]
+    fn apply_config<'py>(&self, py: Python<'py>, config: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyDict>> {
+        config_module::apply_config(py, self, config)
+    }
+
+    /// Sets up an output pin.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin to configure as output.
+    /// - ```output_mode``` (OutputMode): How the pin drives its line, OPEN_DRAIN only pulls the line low and releases
+    ///   it for high, OPEN_SOURCE only pulls it high and releases it for low (default is PUSH_PULL).
+    /// - ```force``` (bool): Takes the pin even when the kernel or another process claims it, such as the pins of an
+    ///   enabled I2C, SPI or UART bus (default is False).
+    ///
+    /// Example usage:
+    /// ```manager.add_output_pin(25)```
+    ///
+    #[pyo3(signature = (pin_num, pin_state = EnumArg(PinState::LOW), logic_level = None, output_mode = EnumArg(OutputMode::PUSH_PULL), force = false))]
+    pub(crate) fn add_output_pin(&self, pin_num: u8, pin_state: EnumArg<PinState>, logic_level: Option<EnumArg<LogicLevel>>, output_mode: EnumArg<OutputMode>,
+                                 force: bool) -> PyResult<()> {
+        let pin_state = pin_state.0;
+        let logic_level = logic_level.map_or(metrics::lock(&self.gpio).defaults.logic_level, |logic_level| logic_level.0);
+        let output_mode = output_mode.0;
+        if self.is_pin_pwm(pin_num) {
+            return Err(Self::pin_error(&metrics::lock(&self.gpio), pin_num, "add_output_pin"));
+        }
+        let mut manager = metrics::lock(&self.gpio);
+        if self.is_input_pin(pin_num, &manager) {
+            return Err(Self::pin_error(&manager, pin_num, "add_output_pin"));
+        }
+        Self::check_claim(&manager, pin_num, "add_output_pin", force)?;
+        let high = (pin_state == PinState::HIGH) == (logic_level == LogicLevel::HIGH);
+        let mut output_pin = OutputPin::new(pin_num, output_mode.initial_level(high))?;
+        if output_mode != OutputMode::PUSH_PULL {
+            GPIOManager::drive_output(pin_num, output_mode, &mut output_pin, high)?;
+        }
+        let output_pin = Pin {
+            pin: PinType::Output(Arc::new(Mutex::new(output_pin))),
+            logic_level,
+            output_mode,
+            pull: InternPullResistorState::AUTO,
+        };
+
+        manager.output_pins.insert(pin_num, Arc::new(Mutex::new(output_pin)));
+        manager.stats.insert(pin_num, PinStats::new(Some(pin_state)));
+
+        Ok(())
+    }
+
+    /// Changes the logic level of a configured pin. Reads, writes, trigger edges and PWM duty cycles are interpreted
+    /// with the new level from then on, outputs keep their state and PWM pins their duty cycle, so their line flips.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    /// - ```logic_level``` (LogicLevel): The new logic level of the pin.
+    ///
+    /// Example usage:
+    /// ```manager.set_logic_level(25, gpio_manager.LogicLevel.LOW)```
+    #[pyo3(signature = (pin_num, logic_level))]
+    fn set_logic_level(&self, pin_num: u8, logic_level: EnumArg<LogicLevel>) -> PyResult<()> {
+        let logic_level = logic_level.0;
+        if self.is_pin_pwm(pin_num) {
+            let channel = PWMManager::hardware_channel(pin_num).expect("Hardware PWM pins have a channel");
+            return PWMManager::new_rust_reference().lock().unwrap().set_channel_polarity(channel, logic_level);
+        }
+        let mut manager = metrics::lock(&self.gpio);
+        if let Some(pin_arc) = manager.input_pins.get(&pin_num).cloned() {
+            let mut pin = pin_arc.lock().unwrap();
+            if pin.logic_level != logic_level {
+                pin.logic_level = logic_level;
+                // The callbacks are stored with the physical edge they trigger on
+                for callback in manager.callbacks.get_mut(&pin_num).into_iter().flatten() {
+                    callback.trigger_edge = match callback.trigger_edge {
+                        TriggerEdge::RISING => TriggerEdge::FALLING,
+                        TriggerEdge::FALLING => TriggerEdge::RISING,
+                        TriggerEdge::BOTH => TriggerEdge::BOTH,
+                    };
+                }
+            }
+            return Ok(());
+        }
+        let Some(pin_arc) = manager.output_pins.get(&pin_num).cloned() else {
+            return Err(Self::pin_error(&manager, pin_num, "set_logic_level"));
+        };
+        if let Some(config) = manager.pwm_setup.get_mut(&pin_num) {
+            config.logic_level = logic_level;
+            pin_arc.lock().unwrap().logic_level = logic_level;
+            drop(manager);
+            return self.set_pwm(pin_num);
+        }
+        drop(manager);
+        let state = self.get_output_state(pin_num)?;
+        pin_arc.lock().unwrap().logic_level = logic_level;
+        self.set_output_pin(pin_num, state.into())
+    }
+
+    /// Switches a configured pin between input and output without resetting it, for bidirectional protocols. The
+    /// logic level of the pin is kept. The callbacks of an input pin stay registered while it is an output and fire
+    /// again once it switches back to an input.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    /// - ```direction``` (PinDirection): The new direction of the pin.
+    /// - ```pin_state``` (PinState): The state a pin switching to an output is set to, None keeps the state of an
+    ///   output and sets a switching input low (default is None).
+    /// - ```pull_resistor_state``` (InternPullResistorState): The pull resistor of a pin switching to an input (default
+    ///   is AUTO, changed with set_defaults).
+    ///
+    /// Example usage:
+    /// ```manager.set_direction(4, gpio_manager.PinDirection.INPUT)```
+    #[pyo3(signature = (pin_num, direction, pin_state = None, pull_resistor_state = None))]
+    fn set_direction(&self, pin_num: u8, direction: EnumArg<PinDirection>, pin_state: Option<EnumArg<PinState>>, pull_resistor_state: Option<EnumArg<InternPullResistorState>>) -> PyResult<()> {
+        let direction = direction.0;
+        let pin_state = pin_state.map(|pin_state| pin_state.0);
+        let mut manager = metrics::lock(&self.gpio);
+        let pull_resistor_state = pull_resistor_state.map_or(manager.defaults.pull, |pull_resistor_state| pull_resistor_state.0);
+        if manager.pwm_setup.contains_key(&pin_num) {
+            return Err(Self::pin_error(&manager, pin_num, "set_direction"));
+        }
+        let (source, is_target) = match direction {
+            PinDirection::OUTPUT => (&manager.input_pins, self.is_output_pin(pin_num, &manager)),
+            PinDirection::INPUT => (&manager.output_pins, self.is_input_pin(pin_num, &manager)),
+        };
+        if is_target {
+            drop(manager);
+            if let (PinDirection::OUTPUT, Some(pin_state)) = (direction, pin_state) {
+                self.set_output_pin(pin_num, pin_state.into())?;
+            }
+            return Ok(());
+        }
+        let Some(pin_arc) = source.get(&pin_num) else {
+            return Err(Self::pin_error(&manager, pin_num, "set_direction"));
+        };
+        let logic_level = pin_arc.lock().unwrap().logic_level;
+        // The backend only hands out the pin again once every handle is dropped, so pins shared with a running signal stay
+        match &pin_arc.lock().unwrap().pin {
+            PinType::Input(pin) if Arc::strong_count(pin) == 1 => {
+                let mut pin = pin.lock().unwrap();
+                if let Err(e) = pin.clear_async_interrupt() {
+                    log_module::warning(&format!("Unable to clear the interrupt of GPIO {}: {}", pin_num, e));
+                }
+                pin.set_reset_on_drop(false);
+            }
+            PinType::Output(pin) if Arc::strong_count(pin) == 1 => pin.lock().unwrap().set_reset_on_drop(false),
+            _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{} is in use by a running signal; cannot set_direction",
+                                                                                    Self::pin_name(&manager, pin_num)))),
+        }
+
+        match direction {
+            PinDirection::OUTPUT => {
+                manager.input_pins.remove(&pin_num);
+                drop(manager);
+                self.add_output_pin(pin_num, pin_state.unwrap_or(PinState::LOW).into(), Some(logic_level.into()), OutputMode::PUSH_PULL.into(), true)
+            }
+            PinDirection::INPUT => {
+                manager.output_pins.remove(&pin_num);
+                drop(manager);
+                self.add_input_pin(pin_num, Some(pull_resistor_state.into()), Some(logic_level.into()), true)?;
+                let manager = metrics::lock(&self.gpio);
+                if let (Some(debounce), Some(pin_arc)) = (manager.debounce.get(&pin_num), manager.input_pins.get(&pin_num)) {
+                    if let PinType::Input(pin) = &pin_arc.lock().unwrap().pin {
+                        GPIOManager::arm_interrupt(&mut pin.lock().unwrap(), pin_num, debounce)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Sets up a PWM output pin.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin to configure as output.
+    /// - ```period_ms``` (int): The period of the PWM signal in milliseconds.
+    /// - ```pulse_width_us``` (int): The pulse width of the PWM signal in microseconds.
+    /// - ```force``` (bool): Takes the pin even when the kernel or another process claims it (default is False).
+    ///
+    /// Example usage:
+    /// ```manager.set_pwm(25, 20, 1200)```
+    #[pyo3(signature = (pin_num, frequency_hz = None, duty_cycle = None, period_ms = None, pulse_width_ms = None, logic_level = None, force = false))]
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn setup_pwm(&self, pin_num: u8, frequency_hz: Option<f64>, duty_cycle: Option<f64>, period_ms: Option<f64>, pulse_width_ms: Option<f64>,
+                            logic_level: Option<EnumArg<LogicLevel>>, force: bool) -> PyResult<()> {
+        let logic_level = logic_level.map_or(metrics::lock(&self.gpio).defaults.logic_level, |logic_level| logic_level.0);
+        if self.is_pin_pwm(pin_num) {
+            return Err(Self::pin_error(&metrics::lock(&self.gpio), pin_num, "setup_pwm"));
+        }
+        check_pwm_values(&frequency_hz, &duty_cycle, &period_ms, &pulse_width_ms)?;
+
+        let mut manager = metrics::lock(&self.gpio);
+
+        if manager.pwm_setup.contains_key(&pin_num) || self.is_input_pin(pin_num, &manager) || self.is_output_pin(pin_num, &manager) {
+            return Err(Self::pin_error(&manager, pin_num, "setup_pwm"));
+        } else {
+            drop(manager);
+            match logic_level {
+                LogicLevel::LOW => {
+                    self.add_output_pin(pin_num, PinState::LOW.into(), Some(logic_level.into()), OutputMode::PUSH_PULL.into(), force)?;
+                }
+                LogicLevel::HIGH => {
+                    self.add_output_pin(pin_num, PinState::LOW.into(), Some(logic_level.into()), OutputMode::PUSH_PULL.into(), force)?;
+                }
+            }
+
+            manager = metrics::lock(&self.gpio);
+        }
+
+        let (frequency, duty_cycle_percent) = compute_pwm_values(&frequency_hz, &duty_cycle, &period_ms, &pulse_width_ms);
+
+        if let Some(pulse_width_ms) = pulse_width_ms.filter(|pulse_width_ms| pulse_width_ms / 1000f64 > 1f64 / frequency) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "The pulse width of {} must be less than the period of {} ms, The value {} does not meet this condition; cannot setup_pwm",
+                Self::pin_name(&manager, pin_num), 1000f64 / frequency, pulse_width_ms)));
+        }
+
+        if self.is_output_pin(pin_num, &manager) {
+            manager.pwm_setup.insert(pin_num, PwmConfig {
+                frequency,
+                duty_cycle: duty_cycle_percent,
                 logic_level,
                 is_active: false,
+                gamma: None,
+                phase: None,
+                high_resolution: false,
             });
             Ok(())
         } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in output pins (pin is either input or not setup)"))
+            Err(Self::pin_error(&manager, pin_num, "setup_pwm"))
+        }
+    }
+
+
+    /// Sets the duty cycle of a PWM pin, either immediately or ramped over a duration.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    /// - ```duty_cycle``` (float): The duty cycle (0 to 100).
+    /// - ```ramp_ms``` (float): The time to ramp from the current duty cycle to the new one in milliseconds, the ramp
+    ///   runs in the background (default is None, which sets the duty cycle immediately).
+    /// - ```easing``` (Easing): The curve the ramp follows (default is LINEAR).
+    ///
+    /// Example usage:
+    /// ```manager.set_pwm_duty_cycle(12, 100, ramp_ms=2000, easing=Easing.EASE_IN_OUT)```
+    #[pyo3(signature = (pin_num, duty_cycle = 0f64, ramp_ms = None, easing = Easing::LINEAR))]
+    pub(crate) fn set_pwm_duty_cycle(&self, pin_num: u8, duty_cycle: f64, ramp_ms: Option<f64>, easing: Easing) -> PyResult<()> {
+        if duty_cycle > 100f64 || duty_cycle < 0f64 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Duty cycle must be between 0 and 100, The value {} does not meet this condition", duty_cycle)));
+        }
+        let current = self.read_pwm_config(pin_num, "set_pwm_duty_cycle", |config| config.duty_cycle)?;
+
+        let target = RampTarget::SoftwarePin(pin_num);
+        match ramp_duration(ramp_ms)? {
+            Some(duration) => {
+                let gpio = GPIOManager::new_rust_reference();
+                start_ramp(target, current, duty_cycle, duration, easing, move |value| gpio.write_pwm_duty_cycle(pin_num, value));
+                Ok(())
+            }
+            None => {
+                cancel_ramp(target);
+                self.write_pwm_duty_cycle(pin_num, duty_cycle)
+            }
+        }
+    }
+
+    /// Plays a duty cycle pattern on a PWM pin from a background thread, for example to make an LED breathe. Setting
+    /// the duty cycle, playing another pattern or resetting the pin stops the pattern.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin, which must be set up for PWM.
+    /// - ```pattern``` (list[tuple[float, float]]): The steps as ```(duty_cycle, hold_ms)```, the duty cycle (0 to 100) is
+    ///   held for the time in milliseconds before the next step.
+    /// - ```loop``` (bool): Whether the pattern starts over after the last step (default is False).
+    ///
+    /// Example usage:
+    /// ```manager.play_pwm_pattern(12, [(100, 200), (0, 200)], loop=True)```
+    #[pyo3(signature = (pin_num, pattern, r#loop = false))]
+    pub(crate) fn play_pwm_pattern(&self, pin_num: u8, pattern: Vec<(f64, f64)>, r#loop: bool) -> PyResult<()> {
+        let pattern = check_pattern(&pattern)?;
+        self.read_pwm_config(pin_num, "play_pwm_pattern", |_| ())?;
+        let gpio = GPIOManager::new_rust_reference();
+        start_pattern(RampTarget::SoftwarePin(pin_num), pattern, r#loop, move |value| gpio.write_pwm_duty_cycle(pin_num, value));
+        Ok(())
+    }
+
+    /// Plays a melody on a buzzer wired to a PWM pin, parsed and timed in Rust. The notes are separated by spaces and
+    /// written ```<note><octave>:<length>```, such as ```C4:8``` or ```F#5:4.```, where the length is the denominator of
+    /// the note value (4 for a quarter note, 8 for an eighth, default is 4), a trailing ```.``` makes a dotted note and
+    /// ```R``` is a rest. Every note is played at half duty cycle and ends with a short silence, the pin is silent after
+    /// the melody. Setting the duty cycle, playing a pattern or another melody or resetting the pin stops the melody.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin, which must be set up for PWM and started.
+    /// - ```melody``` (str): The notes of the melody.
+    /// - ```bpm``` (float): The tempo in quarter notes per minute (default is 120).
+    /// - ```non_blocking``` (bool): Whether the melody plays from a background thread, when False the call returns
+    ///   once the melody ended and the GIL is released while playing (default is True).
+    ///
+    /// Example usage:
+    /// ```manager.play_melody(12, "C4:8 E4:8 G4:8 C5:4. R:8 G4:8 C5:2", bpm=140)```
+    #[pyo3(signature = (pin_num, melody, bpm = 120f64, non_blocking = true))]
+    fn play_melody(&self, pin_num: u8, melody: &str, bpm: f64, non_blocking: bool) -> PyResult<()> {
+        let notes = parse_melody(melody, bpm)?;
+        self.read_pwm_config(pin_num, "play_melody", |_| ())?;
+        let gpio = GPIOManager::new_rust_reference();
+        let play = start_melody(RampTarget::SoftwarePin(pin_num), notes, move |frequency_hz| gpio.write_pwm_tone(pin_num, frequency_hz));
+        if non_blocking {
+            thread::spawn(play);
+        } else {
+            release_gil(play);
+        }
+        Ok(())
+    }
+
+    /// Sets a gamma curve for a PWM pin, so duty cycles are interpreted as perceived brightness. The curve is applied
+    /// before programming the pin, the getters keep returning the brightness.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    /// - ```gamma``` (float): The exponent of the curve, 2.2 suits most LEDs (default is None).
+    /// - ```lut``` (list[float]): Duty cycles (0 to 100) for brightness values evenly spaced from 0 to 100, used in place
+    ///   of the exponent (default is None).
+    ///
+    /// Passing neither disables the curve.
+    ///
+    /// Example usage:
+    /// ```manager.set_pwm_gamma(12, gamma=2.2)```
+    #[pyo3(signature = (pin_num, gamma = None, lut = None))]
+    pub(crate) fn set_pwm_gamma(&self, pin_num: u8, gamma: Option<f64>, lut: Option<Vec<f64>>) -> PyResult<()> {
+        let curve = GammaCurve::from_args(gamma, lut)?;
+        let mut manager = metrics::lock(&self.gpio);
+        if let Some(config) = manager.pwm_setup.get_mut(&pin_num) {
+            config.gamma = curve;
+            drop(manager);
+            self.set_pwm(pin_num)
+        } else {
+            Err(Self::pin_error(&manager, pin_num, "set_pwm_gamma"))
+        }
+    }
+
+    /// Sets the phase offset of a PWM pin. Pins with a phase offset are driven by a single engine thread from a common
+    /// time base, so pins sharing a frequency keep their offset, for example to stagger LED channels and reduce the peak
+    /// current.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    /// - ```phase_deg``` (float): The offset of the period in degrees from 0 to 360, None returns the pin to the
+    ///   regular software PWM (default is None).
+    ///
+    /// Example usage:
+    /// ```manager.set_pwm_phase(13, 180)```
+    #[pyo3(signature = (pin_num, phase_deg = None))]
+    pub(crate) fn set_pwm_phase(&self, pin_num: u8, phase_deg: Option<f64>) -> PyResult<()> {
+        if let Some(phase_deg) = phase_deg {
+            if !(0f64..=360f64).contains(&phase_deg) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Phase must be between 0 and 360, The value {} does not meet this condition", phase_deg)));
+            }
+        }
+        let mut manager = metrics::lock(&self.gpio);
+        if let Some(config) = manager.pwm_setup.get_mut(&pin_num) {
+            config.phase = phase_deg.map(|phase_deg| phase_deg / 360f64);
+            drop(manager);
+            self.set_pwm(pin_num)
+        } else {
+            Err(Self::pin_error(&manager, pin_num, "set_pwm_phase"))
+        }
+    }
+
+    /// Selects whether a PWM pin is driven by the high resolution engine of the GPIOManager instead of a thread per pin.
+    /// The engine drives all its pins from a dedicated thread that sleeps until shortly before every edge and
+    /// busy-waits for the rest, for frequencies up to tens of kHz and steady servo pulses. Pins with a phase offset
+    /// always use the engine.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    /// - ```enabled``` (bool): Whether the engine drives the pin (default is True).
+    ///
+    /// Example usage:
+    /// ```manager.set_pwm_high_resolution(12)```
+    #[pyo3(signature = (pin_num, enabled = true))]
+    pub(crate) fn set_pwm_high_resolution(&self, pin_num: u8, enabled: bool) -> PyResult<()> {
+        let mut manager = metrics::lock(&self.gpio);
+        if let Some(config) = manager.pwm_setup.get_mut(&pin_num) {
+            config.high_resolution = enabled;
+            drop(manager);
+            self.set_pwm(pin_num)
+        } else {
+            Err(Self::pin_error(&manager, pin_num, "set_pwm_high_resolution"))
+        }
+    }
+
+    /// Configures the thread of the high resolution PWM engine.
+    ///
+    /// Parameters:
+    /// - ```busy_wait_us``` (float): The time the engine busy-waits before every edge in microseconds, 0 only sleeps and
+    ///   saves CPU at the cost of jitter (default is 100).
+    /// - ```priority``` (int): The SCHED_FIFO real time priority of the thread from 1 to 99, which needs root or the
+    ///   CAP_SYS_NICE capability, None uses the normal scheduler (default is None).
+    ///
+    /// Example usage:
+    /// ```manager.configure_pwm_engine(busy_wait_us=50, priority=80)```
+    #[pyo3(signature = (busy_wait_us = 100f64, priority = None))]
+    fn configure_pwm_engine(&self, busy_wait_us: f64, priority: Option<i32>) -> PyResult<()> {
+        let busy_wait = us_to_duration_checked("Busy wait", busy_wait_us)?;
+        if let Some(priority) = priority {
+            if !(1..=99).contains(&priority) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Priority must be between 1 and 99, The value {} does not meet this condition", priority)));
+            }
+        }
+        SOFT_PWM_ENGINE.set_priority(priority)
+                       .map_err(|e| PyErr::new::<pyo3::exceptions::PyPermissionError, _>(format!("Failed to set the engine priority: {}", e)))?;
+        SOFT_PWM_ENGINE.set_busy_wait(busy_wait);
+        Ok(())
+    }
+
+    /// Gets the timing statistics of the high resolution PWM engine, the jitter is how late the edges were written.
+    ///
+    /// Returns:
+    /// - ```dict```: The number of edges as `edges`, and the mean and max jitter in microseconds as `mean_jitter_us` and
+    ///   `max_jitter_us`.
+    ///
+    /// Example usage:
+    /// ```print(manager.get_pwm_engine_stats()["max_jitter_us"])```
+    fn get_pwm_engine_stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let stats = SOFT_PWM_ENGINE.stats();
+        let result = PyDict::new(py);
+        result.set_item("edges", stats.edges)?;
+        let mean = if stats.edges > 0 { stats.total_lateness / stats.edges as f64 } else { 0f64 };
+        result.set_item("mean_jitter_us", mean * 1_000_000f64)?;
+        result.set_item("max_jitter_us", stats.max_lateness * 1_000_000f64)?;
+        Ok(result)
+    }
+
+    /// Resets the timing statistics of the high resolution PWM engine.
+    ///
+    /// Example usage:
+    /// ```manager.reset_pwm_engine_stats()```
+    fn reset_pwm_engine_stats(&self) {
+        SOFT_PWM_ENGINE.reset_stats();
+    }
+
+    /// Gets the alternate functions a pin of the header supports on the detected board.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin (0 to 27).
+    ///
+    /// Returns:
+    /// - ```dict```: The hardware PWM channel driving the pin with the PWMManager as `pwm_channel` (None if it has
+    ///   none), the PWM, I2C, SPI and UART functions of the pin as lists under `pwm`, `i2c`, `spi` and `uart`, and every
+    ///   alternate function by its pinctrl name (a0 to a8) as `alt_functions`.
+    ///
+    /// Example usage:
+    /// ```sda_pins = [pin for pin in range(28) if "SDA1" in manager.pin_capabilities(pin)["i2c"]]```
+    #[pyo3(signature = (pin_num))]
+    fn pin_capabilities<'py>(&self, py: Python<'py>, pin_num: u8) -> PyResult<Bound<'py, PyDict>> {
+        let functions = alt_functions(device_info()?.soc(), pin_num)?;
+        let of_peripheral = |peripheral: Peripheral| -> Vec<&str> {
+            functions.iter().filter(|(_, function)| Peripheral::of(function) == peripheral).map(|(_, function)| *function).collect()
+        };
+        let result = PyDict::new(py);
+        result.set_item("pwm_channel", PWMManager::hardware_channel(pin_num))?;
+        result.set_item("pwm", of_peripheral(Peripheral::Pwm))?;
+        result.set_item("i2c", of_peripheral(Peripheral::I2c))?;
+        result.set_item("spi", of_peripheral(Peripheral::Spi))?;
+        result.set_item("uart", of_peripheral(Peripheral::Uart))?;
+        result.set_item("alt_functions", functions.iter().cloned().collect::<HashMap<String, &str>>())?;
+        Ok(result)
+    }
+
+    /// Sets the drive strength of the pad of a pin. On the Raspberry Pi 4 and older GPIO 0 to 27 share their pad
+    /// settings, so the change applies to all of them. Needs root access to the pad control registers.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin (0 to 27).
+    /// - ```ma``` (int): The drive strength in mA, 2 to 16 in steps of 2 on the Raspberry Pi 4 and older, 2, 4, 8 or 12
+    ///   on the Raspberry Pi 5.
+    ///
+    /// Example usage:
+    /// ```manager.set_drive_strength(17, 12)```
+    #[pyo3(signature = (pin_num, ma))]
+    fn set_drive_strength(&self, pin_num: u8, ma: u8) -> PyResult<()> {
+        Ok(update_pad(pin_num, |pad| pad.drive_ma = ma)?)
+    }
+
+    /// Sets whether the slew rate of the pad of a pin is limited, fast edges suit high speed signals while slow edges
+    /// ring less on long cables. Shares the pad settings like set_drive_strength.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin (0 to 27).
+    /// - ```fast``` (bool): True for fast edges, False to limit the slew rate.
+    ///
+    /// Example usage:
+    /// ```manager.set_slew_rate(17, False)```
+    #[pyo3(signature = (pin_num, fast))]
+    fn set_slew_rate(&self, pin_num: u8, fast: bool) -> PyResult<()> {
+        Ok(update_pad(pin_num, |pad| pad.slew_fast = fast)?)
+    }
+
+    /// Enables or disables the input hysteresis (Schmitt trigger) of the pad of a pin, which keeps slow or noisy
+    /// edges from being read as several transitions. Shares the pad settings like set_drive_strength.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin (0 to 27).
+    /// - ```enabled``` (bool): Whether the hysteresis is enabled.
+    ///
+    /// Example usage:
+    /// ```manager.set_hysteresis(22, True)```
+    #[pyo3(signature = (pin_num, enabled))]
+    fn set_hysteresis(&self, pin_num: u8, enabled: bool) -> PyResult<()> {
+        Ok(update_pad(pin_num, |pad| pad.hysteresis = enabled)?)
+    }
+
+    /// Gets the pad settings of a pin.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin (0 to 27).
+    ///
+    /// Returns:
+    /// - ```dict```: The drive strength in mA as `drive_ma`, whether the slew rate is fast as `slew_fast`, whether the
+    ///   input hysteresis is enabled as `hysteresis`, the drive strengths the board supports as `drive_strengths` and
+    ///   whether the settings are shared by GPIO 0 to 27 as `shared`.
+    ///
+    /// Example usage:
+    /// ```print(manager.get_pad_config(17)["drive_ma"])```
+    #[pyo3(signature = (pin_num))]
+    fn get_pad_config<'py>(&self, py: Python<'py>, pin_num: u8) -> PyResult<Bound<'py, PyDict>> {
+        let pad = read_pad(pin_num)?;
+        let soc = device_info()?.soc();
+        let result = PyDict::new(py);
+        result.set_item("drive_ma", pad.drive_ma)?;
+        result.set_item("slew_fast", pad.slew_fast)?;
+        result.set_item("hysteresis", pad.hysteresis)?;
+        result.set_item("drive_strengths", drive_strengths(soc))?;
+        result.set_item("shared", !per_pin_pads(soc))?;
+        Ok(result)
+    }
+
+    /// Routes a pin to one of its alternate functions, for example to hand it to a UART, SPI, I2C or PWM peripheral.
+    /// The pin must not be in use by the manager.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin (0 to 27).
+    /// - ```alt``` (int | str): The alternate function by its number (0 to 5, or 0 to 8 on the Raspberry Pi 5), its
+    ///   pinctrl name such as "a0", or the name of the function such as "TXD0".
+    ///
+    /// Example usage:
+    /// ```manager.set_alt_function(14, "TXD0")```
+    #[pyo3(signature = (pin_num, alt))]
+    fn set_alt_function(&self, pin_num: u8, alt: AltFunction) -> PyResult<()> {
+        let alt = alt.resolve(device_info()?.soc(), pin_num)?;
+        {
+            let manager = metrics::lock(&self.gpio);
+            if self.is_input_pin(pin_num, &manager) || self.is_output_pin(pin_num, &manager) || manager.pwm_setup.contains_key(&pin_num) {
+                return Err(Self::pin_error(&manager, pin_num, "set_alt_function"));
+            }
+        }
+        Ok(release_gil(|| set_alt_function(pin_num, alt, None))?)
+    }
+
+    /// Gets the function a pin is routed to.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    ///
+    /// Returns:
+    /// - ```str```: "input" or "output" for a plain GPIO pin, the name of the alternate function such as "TXD0"
+    ///   otherwise, or "none" for a pin that isn't routed anywhere.
+    ///
+    /// Example usage:
+    /// ```print(manager.get_function(14))```
+    #[pyo3(signature = (pin_num))]
+    fn get_function(&self, pin_num: u8) -> PyResult<String> {
+        {
+            // The pins of the manager are held through rppal, so their registers can't be read through a second handle
+            let manager = metrics::lock(&self.gpio);
+            if self.is_input_pin(pin_num, &manager) {
+                return Ok("input".to_string());
+            }
+            if self.is_output_pin(pin_num, &manager) {
+                return Ok("output".to_string());
+            }
+        }
+        release_gil(|| pin_function(pin_num)).map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Unable to read the function of GPIO {}: {}", pin_num, e)))
+    }
+
+    /// Sends a pulse train on a pin from a dedicated real time thread, the call returns immediately. A pulse train
+    /// already running on the pin is stopped. The pin is set up as an output if it isn't in use.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin, which must be below 32.
+    /// - ```pulses``` (list[tuple[PinState, float]]): The states of the pin with their durations in microseconds.
+    /// - ```repeat``` (int): The number of times the pulses are sent, None repeats them until stop_pulses is called
+    ///   (default is 1).
+    /// - ```callback``` (Callable): Called without arguments once the pulse train ends (default is None).
+    ///
+    /// Example usage:
+    /// ```manager.send_pulses(17, [(PinState.HIGH, 10), (PinState.LOW, 40)], repeat=100, callback=done)```
+    #[pyo3(signature = (pin_num, pulses, repeat = Some(1), callback = None))]
+    fn send_pulses(&self, pin_num: u8, pulses: Vec<(PinState, f64)>, repeat: Option<u32>, callback: Option<PyObject>) -> PyResult<()> {
+        if pin_num > 31 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pulse trains are limited to GPIO 0 to 31"));
+        }
+        let durations = pulses.iter().map(|(_, duration_us)| us_to_duration_checked("Durations", *duration_us)).collect::<Result<Vec<_>, _>>()?;
+        let logic_level = {
+            let manager = metrics::lock(&self.gpio);
+            manager.output_pins.get(&pin_num).map_or(LogicLevel::HIGH, |pin| pin.lock().unwrap().logic_level)
+        };
+        let levels: Vec<(bool, Duration)> = pulses.iter()
+                                                  .zip(durations)
+                                                  .map(|((state, _), duration)| ((*state == PinState::HIGH) == (logic_level == LogicLevel::HIGH), duration))
+                                                  .collect();
+        send_pulse_train(pin_num, &levels, repeat, move || {
+            if let Some(callback) = callback {
+                Python::with_gil(|py| {
+                    if let Err(e) = callback.call0(py) {
+                        log_module::exception(&format!("Exception in the callback of the pulse train on GPIO {}", pin_num), &e);
+                    }
+                });
+            }
+        })
+    }
+
+    /// Stops the pulse train running on a pin, the pin keeps its last state.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    ///
+    /// Returns:
+    /// - ```bool```: Whether a pulse train was running on the pin.
+    ///
+    /// Example usage:
+    /// ```manager.stop_pulses(17)```
+    #[pyo3(signature = (pin_num))]
+    fn stop_pulses(&self, pin_num: u8) -> bool {
+        stop_pulse_train(pin_num)
+    }
+
+    /// Drives a heartbeat on an output pin from a Rust thread, so an external watchdog keeps seeing it while the Python
+    /// interpreter is busy or stuck. The heartbeat is a pulse train repeated until it is stopped, a heartbeat or pulse
+    /// train already running on the pin is replaced. The pin is set up as an output if it isn't in use.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin, which must be below 32.
+    /// - ```interval_ms``` (float): The time between two beats in milliseconds.
+    /// - ```pattern``` (str): "toggle" inverts the pin every interval, "pulse" sends a short high pulse every interval
+    ///   (default is "toggle").
+    /// - ```pulse_ms``` (float): The width of the pulses of the "pulse" pattern in milliseconds (default is a tenth of
+    ///   the interval).
+    ///
+    /// Example usage:
+    /// ```manager.start_heartbeat(4, 500, "pulse", pulse_ms=5)```
+    #[pyo3(signature = (pin_num, interval_ms, pattern = "toggle", pulse_ms = None))]
+    fn start_heartbeat(&self, pin_num: u8, interval_ms: f64, pattern: &str, pulse_ms: Option<f64>) -> PyResult<()> {
+        if !interval_ms.is_finite() || interval_ms <= 0f64 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "The interval must be a positive number of milliseconds, The value {} does not meet this condition", interval_ms)));
+        }
+        let pulses = match pattern {
+            "toggle" => vec![(PinState::HIGH, interval_ms * 1000f64), (PinState::LOW, interval_ms * 1000f64)],
+            "pulse" => {
+                let pulse_ms = pulse_ms.unwrap_or(interval_ms / 10f64);
+                if !(pulse_ms > 0f64 && pulse_ms < interval_ms) {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                        "The pulse width must be positive and shorter than the interval of {} ms, The value {} does not meet this condition", interval_ms, pulse_ms)));
+                }
+                vec![(PinState::HIGH, pulse_ms * 1000f64), (PinState::LOW, (interval_ms - pulse_ms) * 1000f64)]
+            }
+            _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Pattern must be \"toggle\" or \"pulse\", The value {} does not meet this condition", pattern))),
+        };
+        self.send_pulses(pin_num, pulses, None, None)
+    }
+
+    /// Stops the heartbeat of a pin, the pin keeps its last state.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    ///
+    /// Returns:
+    /// - ```bool```: Whether a heartbeat or pulse train was running on the pin.
+    ///
+    /// Example usage:
+    /// ```manager.stop_heartbeat(4)```
+    #[pyo3(signature = (pin_num))]
+    fn stop_heartbeat(&self, pin_num: u8) -> bool {
+        stop_pulse_train(pin_num)
+    }
+
+    /// Sets an output pin to a state at a time of the monotonic clock, the change is made by a timer thread so the call
+    /// returns immediately.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin, which must be set up as an output.
+    /// - ```state``` (PinState): The state to set the pin to.
+    /// - ```at_monotonic_time``` (float): The time in seconds of the clock used by time.monotonic(), times in the past
+    ///   are run immediately.
+    ///
+    /// Returns:
+    /// - ```ScheduledAction```: A handle to cancel the change.
+    ///
+    /// Example usage:
+    /// ```action = manager.schedule(17, PinState.HIGH, time.monotonic() + 5)```
+    #[pyo3(signature = (pin_num, state, at_monotonic_time))]
+    fn schedule(&self, pin_num: u8, state: EnumArg<PinState>, at_monotonic_time: f64) -> PyResult<ScheduledAction> {
+        let state = state.0;
+        if !at_monotonic_time.is_finite() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "The time must be a finite number of seconds, The value {} does not meet this condition", at_monotonic_time)));
         }
+        self.check_plain_output(pin_num, "schedule")?;
+        let id = PIN_SCHEDULER.schedule(pin_num, state, monotonic_to_instant(at_monotonic_time)?);
+        Ok(ScheduledAction::new(id, pin_num, state))
+    }
+
+    /// Sets an output pin to a state after a delay, the change is made by a timer thread so the call returns
+    /// immediately.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin, which must be set up as an output.
+    /// - ```state``` (PinState): The state to set the pin to.
+    /// - ```delay_ms``` (float): The delay in milliseconds.
+    ///
+    /// Returns:
+    /// - ```ScheduledAction```: A handle to cancel the change.
+    ///
+    /// Example usage:
+    /// ```action = manager.schedule_in(17, PinState.LOW, 10 * 60 * 1000)```
+    #[pyo3(signature = (pin_num, state, delay_ms))]
+    fn schedule_in(&self, pin_num: u8, state: EnumArg<PinState>, delay_ms: f64) -> PyResult<ScheduledAction> {
+        let state = state.0;
+        let delay = ms_to_duration_checked("Delay", delay_ms)?;
+        self.check_plain_output(pin_num, "schedule_in")?;
+        let id = PIN_SCHEDULER.schedule(pin_num, state, timing::now() + delay);
+        Ok(ScheduledAction::new(id, pin_num, state))
+    }
+
+    /// Cancels the scheduled changes of a pin, or of all pins.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin, None cancels the changes of all pins (default is None).
+    ///
+    /// Returns:
+    /// - ```int```: The number of changes cancelled.
+    ///
+    /// Example usage:
+    /// ```manager.cancel_scheduled(17)```
+    #[pyo3(signature = (pin_num = None))]
+    fn cancel_scheduled(&self, pin_num: Option<u8>) -> usize {
+        PIN_SCHEDULER.cancel_pin(pin_num)
     }
 
-
-    #[pyo3(signature = (pin_num, duty_cycle = 0f64))]
-    fn set_pwm_duty_cycle(&self, pin_num: u8, duty_cycle: f64) -> PyResult<()> {
-        if duty_cycle > 100f64 || duty_cycle < 0f64 {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Duty cycle must be between 0 and 100, The value {} does not meet this condition", duty_cycle)));
-        }
-
-        let mut manager = self.gpio.lock().unwrap();
-        if let Some(_) = manager.pwm_setup.get(&pin_num) {
-            manager.pwm_setup.get_mut(&pin_num).unwrap().duty_cycle = duty_cycle;
-            drop(manager);
-            self.set_pwm(pin_num)?;
-            Ok(())
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not setup for PWM"))
-        }
+    /// Gets the phase offset of a PWM pin.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    ///
+    /// Returns:
+    /// - ```float```: The phase offset in degrees, or None if the pin has no phase offset.
+    ///
+    /// Example usage:
+    /// ```phase = manager.get_pwm_phase(13)```
+    #[pyo3(signature = (pin_num))]
+    pub(crate) fn get_pwm_phase(&self, pin_num: u8) -> PyResult<Option<f64>> {
+        self.read_pwm_config(pin_num, "get_pwm_phase", |config| config.phase.map(|phase| phase * 360f64))
     }
 
     #[pyo3(signature = (pin_num, frequency_hz))]
-    fn set_pwm_frequency(&self, pin_num: u8, frequency_hz: f64) -> PyResult<()> {
+    pub(crate) fn set_pwm_frequency(&self, pin_num: u8, frequency_hz: f64) -> PyResult<()> {
         if frequency_hz < 0f64 {
             return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Frequency must be greater than 0, The value {} does not meet this condition", frequency_hz)));
         }
-        let mut manager = self.gpio.lock().unwrap();
+        let mut manager = metrics::lock(&self.gpio);
         if let Some(_) = manager.pwm_setup.get(&pin_num) {
             manager.pwm_setup.get_mut(&pin_num).unwrap().frequency = frequency_hz;
             drop(manager);
             self.set_pwm(pin_num)?;
             Ok(())
         } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not setup for pwm"))
+            Err(Self::pin_error(&manager, pin_num, "set_pwm_frequency"))
         }
     }
 
     #[pyo3(signature = (pin_num, period_ms))]
-    fn set_pwm_period(&self, pin_num: u8, period_ms: f64) -> PyResult<()> {
+    pub(crate) fn set_pwm_period(&self, pin_num: u8, period_ms: f64) -> PyResult<()> {
         if period_ms < 0f64 {
             return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("period must be greater than 0, The value {} does not meet this condition", period_ms)));
         }
-        let mut manager = self.gpio.lock().unwrap();
+        let mut manager = metrics::lock(&self.gpio);
         if let Some(_) = manager.pwm_setup.get(&pin_num) {
             let frequency_hz = 1f64 / (period_ms / 1000f64);
             manager.pwm_setup.get_mut(&pin_num).unwrap().frequency = frequency_hz;
@@ -505,61 +1939,201 @@ impl GPIOManager {
             self.set_pwm(pin_num)?;
             Ok(())
         } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not setup for pwm"))
+            Err(Self::pin_error(&manager, pin_num, "set_pwm_period"))
         }
     }
 
 
     #[pyo3(signature = (pin_num, pulse_width_ms))]
-    fn set_pwm_pulse_width(&self, pin_num: u8, pulse_width_ms: f64) -> PyResult<()> {
+    pub(crate) fn set_pwm_pulse_width(&self, pin_num: u8, pulse_width_ms: f64) -> PyResult<()> {
+        cancel_ramp(RampTarget::SoftwarePin(pin_num));
         if pulse_width_ms < 0f64 {
             return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("period must be greater than 0, The value {} does not meet this condition", pulse_width_ms)));
         }
-        let mut manager = self.gpio.lock().unwrap();
+        let mut manager = metrics::lock(&self.gpio);
         if let Some(pin) = manager.pwm_setup.get(&pin_num) {
             let frequency = pin.frequency;
             if pulse_width_ms / 1000f64 > 1f64 / frequency {
                 return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pulse width must be less than period"));
             }
             let duty_cycle = pulse_width_ms / ((1f64 / frequency) * 1000f64) * 100f64;
-            manager.pwm_setup.get_mut(&pin_num).unwrap().duty_cycle = duty_cycle;
+            let config = manager.pwm_setup.get_mut(&pin_num).unwrap();
+            config.duty_cycle = match &config.gamma {
+                Some(gamma) => gamma.invert(duty_cycle),
+                None => duty_cycle,
+            };
             drop(manager);
             self.set_pwm(pin_num)?;
             Ok(())
         } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not setup for pwm"))
+            Err(Self::pin_error(&manager, pin_num, "set_pwm_pulse_width"))
         }
     }
 
 
-    #[pyo3(signature = (pin_num))]
-    fn start_pwm(&self, pin_num: u8) -> PyResult<()> {
-        let mut manager = self.gpio.lock().unwrap();
-        if let Some(_) = manager.pwm_setup.get(&pin_num) {
-            manager.pwm_setup.get_mut(&pin_num).unwrap().is_active = true;
+    /// Starts the PWM signal of a pin, either at its duty cycle or ramped up to it from 0 for a soft start.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    /// - ```ramp_ms``` (float): The time to ramp from 0 to the configured duty cycle in milliseconds, the ramp runs in
+    ///   the background (default is None, which starts at the configured duty cycle).
+    ///
+    /// Example usage:
+    /// ```manager.start_pwm(12, ramp_ms=500)```
+    #[pyo3(signature = (pin_num, ramp_ms = None))]
+    pub(crate) fn start_pwm(&self, pin_num: u8, ramp_ms: Option<f64>) -> PyResult<()> {
+        let ramp = ramp_duration(ramp_ms)?;
+        let mut manager = metrics::lock(&self.gpio);
+        if let Some(config) = manager.pwm_setup.get_mut(&pin_num) {
+            config.is_active = true;
+            let duty_cycle = config.duty_cycle;
+            if ramp.is_some() {
+                config.duty_cycle = 0f64;
+            }
             drop(manager);
             self.set_pwm(pin_num)?;
+            if let Some(duration) = ramp {
+                let gpio = GPIOManager::new_rust_reference();
+                start_ramp(RampTarget::SoftwarePin(pin_num), 0f64, duty_cycle, duration, Easing::LINEAR,
+                           move |value| gpio.write_pwm_duty_cycle(pin_num, value));
+            }
             Ok(())
         } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in output pins (pin is either input or not setup)"))
+            Err(Self::pin_error(&manager, pin_num, "start_pwm"))
+        }
+    }
+
+
+    /// Starts the PWM signals of several pins back to back, so they start as close to simultaneously as possible. None
+    /// of the signals is started if a pin isn't setup for PWM.
+    ///
+    /// Parameters:
+    /// - ```pin_nums``` (list[int]): The GPIO pins.
+    ///
+    /// Example usage:
+    /// ```manager.start_pwm_group([12, 13])```
+    #[pyo3(signature = (pin_nums))]
+    pub(crate) fn start_pwm_group(&self, pin_nums: Vec<u8>) -> PyResult<()> {
+        let mut manager = metrics::lock(&self.gpio);
+        if let Some(pin_num) = pin_nums.iter().find(|pin_num| !manager.pwm_setup.contains_key(pin_num)) {
+            return Err(Self::pin_error(&manager, *pin_num, "start_pwm_group"));
+        }
+
+        let mut outputs = Vec::with_capacity(pin_nums.len());
+        for pin_num in &pin_nums {
+            let config = manager.pwm_setup.get_mut(pin_num).unwrap();
+            config.is_active = true;
+            let (frequency, duty_cycle, phase) = (config.frequency, config.pin_duty_cycle(), config.engine_phase());
+            if let PinType::Output(pin) = &manager.output_pins.get(pin_num).unwrap().lock().unwrap().pin {
+                match phase {
+                    // Pins with a phase are aligned by the common time base of the engine
+                    Some(phase) => SOFT_PWM_ENGINE.set_channel(*pin_num, Arc::clone(pin), frequency, duty_cycle / 100f64, phase),
+                    None => outputs.push((Arc::clone(pin), frequency, duty_cycle)),
+                }
+            }
+        }
+        drop(manager);
+
+        // Lock every pin first, so the signals are enabled without waiting on a lock in between
+        let mut pins: Vec<_> = outputs.iter().map(|(pin, _, _)| pin.lock().unwrap()).collect();
+        for (pin, (_, frequency, duty_cycle)) in pins.iter_mut().zip(&outputs) {
+            pin.set_pwm_frequency(*frequency, duty_cycle / 100f64)
+               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to start pwm: {:?}", e)))?;
         }
+        Ok(())
     }
 
 
     #[pyo3(signature = (pin_num))]
-    fn stop_pwm(&self, pin_num: u8) -> PyResult<()> {
-        let mut manager = self.gpio.lock().unwrap();
+    pub(crate) fn stop_pwm(&self, pin_num: u8) -> PyResult<()> {
+        let mut manager = metrics::lock(&self.gpio);
         if let Some(_) = manager.pwm_setup.get(&pin_num) {
             manager.pwm_setup.get_mut(&pin_num).unwrap().is_active = false;
             drop(manager);
             self.set_pwm(pin_num)?;
             Ok(())
         } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in output pins (pin is either input or not setup)"))
+            Err(Self::pin_error(&manager, pin_num, "stop_pwm"))
         }
     }
 
 
+    /// Gets the frequency of a software PWM pin.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    ///
+    /// Returns:
+    /// - ```float```: The frequency in Hertz.
+    ///
+    /// Example usage:
+    /// ```frequency = manager.get_pwm_frequency(12)```
+    #[pyo3(signature = (pin_num))]
+    pub(crate) fn get_pwm_frequency(&self, pin_num: u8) -> PyResult<f64> {
+        self.read_pwm_config(pin_num, "get_pwm_frequency", |config| config.frequency)
+    }
+
+    /// Gets the duty cycle of a software PWM pin, relative to the logic level of the pin.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    ///
+    /// Returns:
+    /// - ```float```: The duty cycle (0 to 100).
+    ///
+    /// Example usage:
+    /// ```duty_cycle = manager.get_pwm_duty_cycle(12)```
+    #[pyo3(signature = (pin_num))]
+    pub(crate) fn get_pwm_duty_cycle(&self, pin_num: u8) -> PyResult<f64> {
+        self.read_pwm_config(pin_num, "get_pwm_duty_cycle", |config| config.duty_cycle)
+    }
+
+    /// Gets the period of a software PWM pin.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    ///
+    /// Returns:
+    /// - ```float```: The period in milliseconds.
+    ///
+    /// Example usage:
+    /// ```period = manager.get_pwm_period(12)```
+    #[pyo3(signature = (pin_num))]
+    pub(crate) fn get_pwm_period(&self, pin_num: u8) -> PyResult<f64> {
+        self.read_pwm_config(pin_num, "get_pwm_period", |config| 1000f64 / config.frequency)
+    }
+
+    /// Gets the pulse width of a software PWM pin.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    ///
+    /// Returns:
+    /// - ```float```: The pulse width in milliseconds.
+    ///
+    /// Example usage:
+    /// ```pulse_width = manager.get_pwm_pulse_width(12)```
+    #[pyo3(signature = (pin_num))]
+    pub(crate) fn get_pwm_pulse_width(&self, pin_num: u8) -> PyResult<f64> {
+        self.read_pwm_config(pin_num, "get_pwm_pulse_width", |config| 1000f64 / config.frequency * config.output_duty_cycle() / 100f64)
+    }
+
+    /// Checks whether the PWM signal of a software PWM pin is started.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    ///
+    /// Returns:
+    /// - ```bool```: True if the signal is started, False if it is stopped.
+    ///
+    /// Example usage:
+    /// ```active = manager.is_pwm_active(12)```
+    #[pyo3(signature = (pin_num))]
+    pub(crate) fn is_pwm_active(&self, pin_num: u8) -> PyResult<bool> {
+        self.read_pwm_config(pin_num, "is_pwm_active", |config| config.is_active)
+    }
+
+
     /// Sets the state of an output pin.
     ///
     /// Parameters:
@@ -569,38 +2143,32 @@ impl GPIOManager {
     /// Example usage:
     /// ```manager.set_output_pin(25, True)```
     #[pyo3(signature = (pin_num, pin_state))]
-    fn set_output_pin(&self, pin_num: u8, pin_state: PinState) -> PyResult<()> {
-        let manager = self.gpio.lock().unwrap();
+    pub(crate) fn set_output_pin(&self, pin_num: u8, pin_state: EnumArg<PinState>) -> PyResult<()> {
+        let pin_state = pin_state.0;
+        let mut manager = metrics::lock(&self.gpio);
         if self.is_input_pin(pin_num, &manager) {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin found in input pins (pin is setup as an input pin)"));
+            return Err(Self::pin_error(&manager, pin_num, "set_output_pin"));
         }
         if let Some(_) = manager.pwm_setup.get(&pin_num) {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin configured for PWM, please reset the pin to use as regular output pin"));
+            return Err(Self::pin_error(&manager, pin_num, "set_output_pin"));
         }
-        if let Some(output_pin) = manager.output_pins.get(&pin_num) {
-            let output_pin = output_pin.lock().unwrap();
-            let mut pin;
-            if let PinType::Output(out_pin) = &output_pin.pin {
-                pin = out_pin.lock().unwrap();
-            } else {
-                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in output pins (pin is either input or not setup)"));
-            }
-            match pin_state {
-                PinState::HIGH => if output_pin.logic_level == LogicLevel::HIGH {
-                    pin.set_high();
-                } else {
-                    pin.set_low();
-                },
-                PinState::LOW => if output_pin.logic_level == LogicLevel::HIGH {
-                    pin.set_low();
-                } else {
-                    pin.set_high();
-                },
-            }
-            Ok(())
+        let pin_arc = match manager.output_pins.get(&pin_num) {
+            Some(pin_arc) => Arc::clone(pin_arc),
+            None => return Err(Self::pin_error(&manager, pin_num, "set_output_pin")),
+        };
+        let output_pin = pin_arc.lock().unwrap();
+        let mut pin;
+        if let PinType::Output(out_pin) = &output_pin.pin {
+            pin = out_pin.lock().unwrap();
         } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in output pins (pin is either input or not setup)"))
+            return Err(Self::pin_error(&manager, pin_num, "set_output_pin"));
         }
+        let high = (pin_state == PinState::HIGH) == (output_pin.logic_level == LogicLevel::HIGH);
+        GPIOManager::drive_output(pin_num, output_pin.output_mode, &mut pin, high)?;
+        let stats = Self::stats(&mut manager, pin_num);
+        stats.writes += 1;
+        stats.observe(pin_state);
+        Ok(())
     }
 
     /// Polls the current state of an input pin.
@@ -616,36 +2184,259 @@ impl GPIOManager {
     ///
 
     #[pyo3(signature = (pin_num))]
-    fn get_pin(&self, pin_num: u8) -> PyResult<PinState> {
-        let manager = self.gpio.lock().unwrap();
+    pub(crate) fn get_pin(&self, pin_num: u8) -> PyResult<PinState> {
+        let mut manager = metrics::lock(&self.gpio);
 
         if self.is_output_pin(pin_num, &manager) {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin found in output pins (pin is already setup as an output pin)"));
+            return Err(Self::pin_error(&manager, pin_num, "get_pin"));
         }
-        if let Some(pin_arc) = manager.input_pins.get(&pin_num) {
-            let pin_arc = pin_arc.lock().unwrap();
-            let pin;
-            if let PinType::Input(pin_arc) = &pin_arc.pin {
-                pin = pin_arc.lock().unwrap();
-            } else {
-                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in input pins (pin is either output or not setup)"));
-            }
-            if pin.is_high() {
-                if pin_arc.logic_level == LogicLevel::HIGH {
-                    Ok(PinState::HIGH)
-                } else {
-                    Ok(PinState::LOW)
-                }
-            } else {
-                if pin_arc.logic_level == LogicLevel::HIGH {
-                    Ok(PinState::LOW)
-                } else {
-                    Ok(PinState::HIGH)
-                }
-            }
+        let pin_arc = match manager.input_pins.get(&pin_num) {
+            Some(pin_arc) => Arc::clone(pin_arc),
+            None => return Err(Self::pin_error(&manager, pin_num, "get_pin")),
+        };
+        let pin_arc = pin_arc.lock().unwrap();
+        let pin;
+        if let PinType::Input(pin_arc) = &pin_arc.pin {
+            pin = pin_arc.lock().unwrap();
         } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in input pins (pin is either output or not setup)"))
+            return Err(Self::pin_error(&manager, pin_num, "get_pin"));
+        }
+        let state = if pin.is_high() == (pin_arc.logic_level == LogicLevel::HIGH) { PinState::HIGH } else { PinState::LOW };
+        let stats = Self::stats(&mut manager, pin_num);
+        stats.reads += 1;
+        stats.observe(state);
+        Ok(state)
+    }
+
+    /// Returns the activity of a pin since it was set up, to find inputs that chatter and outputs that are stuck in
+    /// long running deployments.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    /// - ```reset``` (bool): Zeroes the counters after they are returned, so the next call covers the time since this
+    ///   one (default is False).
+    ///
+    /// Returns:
+    /// - ```dict```: The number of `writes`, `reads`, `interrupts` and `callbacks` dispatched, the time in seconds the
+    ///   counters cover as `seconds`, the last known `state` (None until the pin is written, read or has an edge), the
+    ///   wall clock time of its last change as `last_change` and the seconds since as `seconds_since_change` (None
+    ///   without a change).
+    ///
+    /// Example usage:
+    /// ```python
+    /// stats = manager.pin_stats(18, reset=True)
+    /// if stats["interrupts"] / stats["seconds"] > 50:
+    ///     print("GPIO 18 is chattering")
+    /// ```
+    #[pyo3(signature = (pin_num, reset = false))]
+    fn pin_stats<'py>(&self, py: Python<'py>, pin_num: u8, reset: bool) -> PyResult<Bound<'py, PyDict>> {
+        let mut manager = metrics::lock(&self.gpio);
+        let stats = match manager.stats.get_mut(&pin_num) {
+            Some(stats) => stats,
+            None => return Err(Self::pin_error(&manager, pin_num, "pin_stats")),
+        };
+        let snapshot = *stats;
+        if reset {
+            *stats = PinStats { last_change: snapshot.last_change, ..PinStats::new(snapshot.state) };
+        }
+        drop(manager);
+        let since_change = snapshot.last_change.map(|last_change| last_change.elapsed());
+        let result = PyDict::new(py);
+        result.set_item("writes", snapshot.writes)?;
+        result.set_item("reads", snapshot.reads)?;
+        result.set_item("interrupts", snapshot.interrupts)?;
+        result.set_item("callbacks", snapshot.callbacks)?;
+        result.set_item("seconds", snapshot.since.elapsed().as_secs_f64())?;
+        result.set_item("state", snapshot.state)?;
+        result.set_item("last_change", since_change.map(|since_change| (SystemTime::now() - since_change).duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64()))?;
+        result.set_item("seconds_since_change", since_change.map(|since_change| since_change.as_secs_f64()))?;
+        Ok(result)
+    }
+
+    /// Reads the level of every GPIO of the header (0 to 27) at the same instant with a single register read, whether
+    /// the pins are set up or not. The physical levels are returned, the logic level of configured pins isn't applied.
+    ///
+    /// Parameters:
+    /// - ```as_dict``` (bool): Return a dict of the PinState of every pin instead of a bit mask (default is False).
+    ///
+    /// Returns:
+    /// - ```int```: A bit mask where bit N is set when GPIO N is high, or a ```dict``` from the pin to its PinState.
+    ///
+    /// Example usage:
+    /// ```levels = manager.read_all()```
+    ///
+    /// ```button_pressed = not levels & (1 << 18)```
+    #[pyo3(signature = (as_dict = false))]
+    fn read_all(&self, py: Python, as_dict: bool) -> PyResult<PyObject> {
+        let levels = backend::current().read_levels()
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Unable to read the GPIO levels: {}", e)))?
+            & ((1u32 << (MAX_HEADER_GPIO + 1)) - 1);
+        if !as_dict {
+            return levels.into_py_any(py);
+        }
+        let result = PyDict::new(py);
+        for pin_num in 0..=MAX_HEADER_GPIO {
+            let state = if levels & (1 << pin_num) != 0 { PinState::HIGH } else { PinState::LOW };
+            result.set_item(pin_num, state)?;
+        }
+        Ok(result.into_any().unbind())
+    }
+
+    /// Samples the levels of pins at a fixed rate like a logic analyzer, whether the pins are set up or not. The samples
+    /// are taken by a Rust loop with the GIL released and returned when the capture is done.
+    ///
+    /// Parameters:
+    /// - ```pins``` (list[int]): The GPIO pins to capture (0 to 27), bit N of every sample is the level of the Nth pin.
+    /// - ```sample_rate_hz``` (float): The number of samples per second.
+    /// - ```duration_ms``` (float): The length of the capture in milliseconds.
+    /// - ```trigger_pin``` (int): A pin whose edge starts the capture, the capture starts right away for None
+    ///   (default is None).
+    /// - ```trigger_edge``` (TriggerEdge): The physical edge on the trigger pin that starts the capture (default is BOTH).
+    /// - ```timeout_ms``` (float): The longest time to wait for the trigger, forever for None (default is None).
+    ///
+    /// Returns:
+    /// - ```bytes```: The samples, 1 byte each for up to 8 pins, 2 bytes for up to 16 pins and 4 bytes otherwise, little
+    ///   endian, so they can be loaded with ```numpy.frombuffer(data, dtype=numpy.uint8)``` (or uint16, uint32).
+    ///
+    /// Example usage:
+    /// ```data = manager.capture([2, 3], 100_000, 50, trigger_pin=3, trigger_edge=gpio_manager.TriggerEdge.FALLING)```
+    #[pyo3(signature = (pins, sample_rate_hz, duration_ms, trigger_pin = None, trigger_edge = EnumArg(TriggerEdge::BOTH), timeout_ms = None))]
+    #[allow(clippy::too_many_arguments)]
+    fn capture<'py>(&self, py: Python<'py>, pins: Vec<u8>, sample_rate_hz: f64, duration_ms: f64, trigger_pin: Option<u8>, trigger_edge: EnumArg<TriggerEdge>,
+                    timeout_ms: Option<f64>) -> PyResult<Bound<'py, PyBytes>> {
+        let trigger_edge = trigger_edge.0;
+        if duration_ms < 0f64 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Duration must be greater than 0, The value {} does not meet this condition", duration_ms)));
+        }
+        let count = (sample_rate_hz * duration_ms / 1000f64).round() as usize;
+        let data = capture(&pins, sample_rate_hz, count, trigger_pin.map(|pin_num| (pin_num, trigger_edge)), self.ms_to_duration("Timeout", timeout_ms)?)?;
+        Ok(PyBytes::new(py, &data))
+    }
+
+    /// Samples the level of a single pin at a fixed rate into a buffer, every byte is set to 1 for high and 0 for low.
+    /// A buffer passed in is filled in place without allocating, so it can be reused to sample continuously. The GIL is
+    /// released while sampling.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin to sample (0 to 27).
+    /// - ```rate_hz``` (float): The number of samples per second.
+    /// - ```n_samples``` (int): The number of samples to take.
+    /// - ```buffer``` (bytearray | memoryview | numpy.ndarray): A writable contiguous buffer of bytes with room for the
+    ///   samples, a new bytearray is returned for None (default is None).
+    ///
+    /// Returns:
+    /// - The buffer holding the samples.
+    ///
+    /// Example usage:
+    /// ```samples = bytearray(10_000)```
+    ///
+    /// ```manager.sample_pin(17, 50_000, len(samples), samples)```
+    #[pyo3(signature = (pin_num, rate_hz, n_samples, buffer = None))]
+    fn sample_pin(&self, py: Python, pin_num: u8, rate_hz: f64, n_samples: usize, buffer: Option<Bound<'_, PyAny>>) -> PyResult<PyObject> {
+        let buffer = match buffer {
+            Some(buffer) => buffer,
+            None => PyByteArray::new(py, &vec![0u8; n_samples]).into_any(),
+        };
+        let view = PyBuffer::<u8>::get(&buffer)?;
+        if view.readonly() {
+            return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>("The buffer must be writable"));
+        }
+        if !view.is_c_contiguous() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("The buffer must be contiguous"));
+        }
+        if view.item_count() < n_samples {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The buffer must hold at least {} samples, it holds {}", n_samples,
+                                                                                view.item_count())));
+        }
+        // The buffer can't be resized or freed while the view is held, so it can be written without the GIL
+        let samples = unsafe { std::slice::from_raw_parts_mut(view.buf_ptr() as *mut u8, n_samples) };
+        sample_pin(pin_num, rate_hz, samples)?;
+        view.release(py);
+        Ok(buffer.unbind())
+    }
+
+    /// Measures the capacitance of an electrode wired to a pin, such as a piece of foil, as the time it takes to charge
+    /// through the pull-up resistor. For every sample the pin is driven low to discharge the electrode, then released
+    /// and timed until it reads high. A finger on the electrode makes the charge time longer. A free pin is set up as an
+    /// output. The GIL is released while measuring.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin wired to the electrode (0 to 27).
+    /// - ```samples``` (int): The number of charge times averaged (default is 10).
+    /// - ```pull_resistor_state``` (InternPullResistorState): PULLUP charges the electrode through the internal pull-up,
+    ///   EXTERNAL through a resistor to 3.3V (default is PULLUP).
+    /// - ```timeout_us``` (float): The longest charge time measured in microseconds, samples that take longer count as
+    ///   the timeout (default is 10000).
+    ///
+    /// Returns:
+    /// - ```float```: The average charge time in microseconds.
+    ///
+    /// Example usage:
+    /// ```charge_time_us = manager.touch_read(4, samples=20)```
+    #[pyo3(signature = (pin_num, samples = 10, pull_resistor_state = EnumArg(InternPullResistorState::PULLUP), timeout_us = 10_000f64))]
+    fn touch_read(&self, pin_num: u8, samples: usize, pull_resistor_state: EnumArg<InternPullResistorState>, timeout_us: f64) -> PyResult<f64> {
+        if timeout_us <= 0f64 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Timeout must be greater than 0, The value {} does not meet this condition", timeout_us)));
+        }
+        let timeout = us_to_duration_checked("Timeout", timeout_us)?;
+        let pin = self.get_or_claim_output_pin(pin_num)?;
+        measure_charge_time(&pin, pin_num, samples, pull_resistor_state.0.bias(LogicLevel::HIGH), DISCHARGE_TIME, timeout)
+    }
+
+    /// Reads an analog sensor without an ADC by RC timing: a resistive sensor, such as a photoresistor or a thermistor,
+    /// charges a capacitor wired from the pin to ground. For every sample the pin is driven low to empty the capacitor,
+    /// then released and timed until it reads high, a higher resistance gives a longer time. A free pin is set up as an
+    /// output. The GIL is released while measuring.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin wired to the capacitor and the sensor (0 to 27).
+    /// - ```timeout_ms``` (float): The longest time measured in milliseconds, samples that take longer count as the
+    ///   timeout (default is 100).
+    /// - ```samples``` (int): The number of times averaged (default is 1).
+    /// - ```discharge_ms``` (float): The time the capacitor is emptied for before every sample in milliseconds
+    ///   (default is 10).
+    ///
+    /// Returns:
+    /// - ```float```: The average charge time in microseconds.
+    ///
+    /// Example usage:
+    /// ```light_us = manager.rc_read(4, timeout_ms=50)```
+    #[pyo3(signature = (pin_num, timeout_ms = 100f64, samples = 1, discharge_ms = 10f64))]
+    fn rc_read(&self, pin_num: u8, timeout_ms: f64, samples: usize, discharge_ms: f64) -> PyResult<f64> {
+        if timeout_ms <= 0f64 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Timeout must be greater than 0, The value {} does not meet this condition", timeout_ms)));
+        }
+        let timeout = ms_to_duration_checked("Timeout", timeout_ms)?;
+        let discharge = ms_to_duration_checked("Discharge time", discharge_ms)?;
+        let pin = self.get_or_claim_output_pin(pin_num)?;
+        measure_charge_time(&pin, pin_num, samples, Bias::Off, discharge, timeout)
+    }
+
+    /// Reads an analog sensor by RC timing like rc_read and scales the time between two calibrated times to a value
+    /// between 0 and 1. The calibrated times are read with rc_read at both ends of the range, such as in the dark and
+    /// in bright light, and the low end may take longer than the high end.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin wired to the capacitor and the sensor (0 to 27).
+    /// - ```low_us``` (float): The charge time in microseconds that reads as 0.
+    /// - ```high_us``` (float): The charge time in microseconds that reads as 1.
+    /// - ```timeout_ms``` (float): The longest time measured in milliseconds (default is 100).
+    /// - ```samples``` (int): The number of times averaged (default is 1).
+    /// - ```discharge_ms``` (float): The time the capacitor is emptied for before every sample in milliseconds
+    ///   (default is 10).
+    ///
+    /// Returns:
+    /// - ```float```: The reading between 0 and 1, times outside the calibrated range are clamped.
+    ///
+    /// Example usage:
+    /// ```brightness = manager.rc_read_normalized(4, low_us=25000, high_us=300)```
+    #[pyo3(signature = (pin_num, low_us, high_us, timeout_ms = 100f64, samples = 1, discharge_ms = 10f64))]
+    fn rc_read_normalized(&self, pin_num: u8, low_us: f64, high_us: f64, timeout_ms: f64, samples: usize, discharge_ms: f64) -> PyResult<f64> {
+        if low_us == high_us {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The calibrated times must differ, both are {}", low_us)));
         }
+        let charge_time_us = self.rc_read(pin_num, timeout_ms, samples, discharge_ms)?;
+        Ok(((charge_time_us - low_us) / (high_us - low_us)).clamp(0f64, 1f64))
     }
 
     /// Unassigns a callback from an input pin.
@@ -657,11 +2448,16 @@ impl GPIOManager {
     /// ```state = manager.poll_pin(18)```
     ///
     #[pyo3(signature = (pin_num))]
-    fn unassign_callbacks(&self, pin_num: u8) -> PyResult<()> {
-        let mut manager = self.gpio.lock().unwrap();
+    pub(crate) fn unassign_callbacks(&self, pin_num: u8) -> PyResult<()> {
+        let mut manager = metrics::lock(&self.gpio);
 
         if !self.is_input_pin(pin_num, &manager) {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in input pins (pin is either output or not setup)"));
+            return Err(Self::pin_error(&manager, pin_num, "unassign_callbacks"));
+        }
+        // The interrupt of an observed pin is kept, so the observers in Rust still receive its edges
+        if GPIOManager::is_observed(pin_num) {
+            manager.callbacks.insert(pin_num, Vec::new());
+            return Ok(());
         }
         if let Some(pin_arc) = manager.input_pins.get(&pin_num) {
             let pin_arc = pin_arc.lock().unwrap();
@@ -669,36 +2465,67 @@ impl GPIOManager {
                 let mut pin = pin_arc.lock().unwrap();
                 pin.clear_async_interrupt().expect("failed to clear interrupt");
             } else {
-                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in input pins (pin is either output or not setup)"));
+                return Err(Self::pin_error(&manager, pin_num, "unassign_callbacks"));
             }
         }
 
         manager.callbacks.remove(&pin_num);
+        manager.debounce.remove(&pin_num);
         Ok(())
     }
 
 
+    /// Returns the callbacks of an input pin in the order they are called, from the highest priority to the lowest.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    ///
+    /// Returns:
+    /// - ```list[tuple[Callable, TriggerEdge, int]]```: The callback, the edge it is assigned to and its priority.
+    ///
+    /// Example usage:
+    /// ```for callback, edge, priority in manager.get_callbacks(18): print(callback.__name__, priority)```
+    #[pyo3(signature = (pin_num))]
+    fn get_callbacks(&self, py: Python, pin_num: u8) -> PyResult<Vec<(PyObject, TriggerEdge, i32)>> {
+        let manager = metrics::lock(&self.gpio);
+        let logic_level = match manager.input_pins.get(&pin_num) {
+            Some(pin_arc) => pin_arc.lock().unwrap().logic_level,
+            None => return Err(Self::pin_error(&manager, pin_num, "get_callbacks")),
+        };
+        let callbacks = manager.callbacks.get(&pin_num).map(Vec::as_slice).unwrap_or_default();
+        let mut listed = Vec::with_capacity(callbacks.len());
+        for callback in callbacks {
+            // Weak callbacks whose object was garbage collected are left out
+            let Some(target) = callback.target(py)? else { continue };
+            // The stored edge is the edge of the line, the edge asked for is inverted for active low pins
+            let trigger_edge = match (callback.trigger_edge, logic_level) {
+                (TriggerEdge::RISING, LogicLevel::LOW) => TriggerEdge::FALLING,
+                (TriggerEdge::FALLING, LogicLevel::LOW) => TriggerEdge::RISING,
+                (trigger_edge, _) => trigger_edge,
+            };
+            listed.push((target, trigger_edge, callback.priority));
+        }
+        Ok(listed)
+    }
+
     #[pyo3(signature = (pin_num, callback))]
     fn unassign_callback(&self, py: Python, pin_num: u8, callback: PyObject) -> PyResult<()> {
-        let mut manager = self.gpio.lock().unwrap();
+        let mut manager = metrics::lock(&self.gpio);
         let callable: &Bound<PyAny> = callback.bind(py);
         if !callable.is_callable() {
             return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>("Object is not callable"));
         }
 
         if !self.is_input_pin(pin_num, &manager) {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in input pins (pin is either output or not setup)"));
+            return Err(Self::pin_error(&manager, pin_num, "unassign_callback"));
         }
         let callbacks = manager.callbacks.get_mut(&pin_num).unwrap();
         let mut index = 0;
-        for (i, callable) in callbacks.iter().enumerate() {
-            let cb = callable.callable.lock().unwrap(); // Unlock the mutex to access the PyObject
-            Python::with_gil(|_| {
-                if cb.is(&callback) {
-                    index = i;
-                    return;
-                }
-            });
+        for (i, registered) in callbacks.iter().enumerate() {
+            if registered.calls(py, &callback)? {
+                index = i;
+                break;
+            }
         }
         callbacks.remove(index);
         if callbacks.is_empty() {
@@ -709,53 +2536,125 @@ impl GPIOManager {
         Ok(())
     }
 
-    /// wait for an edge on the assigned pin
-    #[pyo3(signature = (pin_num, trigger_edge = TriggerEdge::BOTH, timeout_ms = None, debounce_ms = 2f64))]
-    fn wait_for_edge(&self, pin_num: u8, trigger_edge: TriggerEdge, timeout_ms: Option<f64>, debounce_ms: Option<f64>) -> PyResult<()> {
-        let manager = self.gpio.lock().unwrap();
-
+    /// Calls a function from Rust when an input pin has no edge for longer than a window, such as a fan tachometer
+    /// going silent or a heartbeat line dying. The function is called once per silence, the next edge starts a new
+    /// window. Every pin is watched from the same thread.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The input pin.
+    /// - ```max_silence_ms``` (float): The longest time without an edge in milliseconds, the window starts now.
+    /// - ```callback``` (callable): The function to call, with the args.
+    /// - ```args``` (tuple): The arguments to pass to the function (default is no arguments).
+    ///
+    /// Example usage:
+    /// ```manager.watch_activity(17, 2000, lambda: print("The fan stopped"))```
+    #[pyo3(signature = (pin_num, max_silence_ms, callback, args = None))]
+    fn watch_activity(&self, py: Python, pin_num: u8, max_silence_ms: f64, callback: PyObject, args: Option<&Bound<'_, PyTuple>>) -> PyResult<()> {
+        if max_silence_ms <= 0f64 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "The silence must be a positive number of milliseconds, The value {} does not meet this condition", max_silence_ms)));
+        }
+        let max_silence = ms_to_duration_checked("The silence", max_silence_ms)?;
+        if !callback.bind(py).is_callable() {
+            return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>("Object is not callable"));
+        }
+        let mut manager = metrics::lock(&self.gpio);
         if !self.is_input_pin(pin_num, &manager) {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in input pins (pin is either output or not setup)"));
+            return Err(Self::pin_error(&manager, pin_num, "watch_activity"));
         }
+        let args = args.map_or_else(|| PyTuple::empty(py).unbind(), |args| args.clone().unbind());
+        ACTIVITY_WATCHDOG.watch(pin_num, max_silence, callback, args);
+        GPIOManager::arm_for_observers(&mut manager, pin_num)
+    }
 
-        let timeout = self.ms_to_duration(timeout_ms);
+    /// Stops watching the activity of a pin.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    ///
+    /// Returns:
+    /// - ```bool```: Whether the pin was watched.
+    ///
+    /// Example usage:
+    /// ```manager.unwatch_activity(17)```
+    fn unwatch_activity(&self, pin_num: u8) -> bool {
+        let watched = watchdog_module::unwatch(pin_num);
+        self.release_unobserved_pins();
+        watched
+    }
 
-        let debounce = self.ms_to_duration(debounce_ms);
+    /// wait for an edge on the assigned pin
+    #[pyo3(signature = (pin_num, trigger_edge = EnumArg(TriggerEdge::BOTH), timeout_ms = None, debounce_ms = 2f64))]
+    fn wait_for_edge(&self, pin_num: u8, trigger_edge: EnumArg<TriggerEdge>, timeout_ms: Option<f64>, debounce_ms: Option<f64>) -> PyResult<()> {
+        let trigger_edge = trigger_edge.0;
+        self.wait_for_edge_detected(pin_num, trigger_edge, timeout_ms, debounce_ms)?;
+        Ok(())
+    }
 
-        if let Some(pin_arc) = manager.input_pins.get(&pin_num) {
-            let pin_arc = pin_arc.lock().unwrap();
-            let trigger = match trigger_edge {
-                TriggerEdge::RISING => if pin_arc.logic_level == LogicLevel::HIGH {
-                    Trigger::RisingEdge
-                } else {
-                    Trigger::FallingEdge
-                },
-                TriggerEdge::FALLING => if pin_arc.logic_level == LogicLevel::HIGH {
-                    Trigger::FallingEdge
-                } else {
-                    Trigger::RisingEdge
-                },
-                TriggerEdge::BOTH => Trigger::Both,
-            };
-            if let PinType::Input(pin_arc) = &pin_arc.pin {
-                let mut pin = pin_arc.lock().unwrap();
-                pin.set_interrupt(trigger, debounce).expect("failed to setup interrupt");
-                pin.poll_interrupt(false, timeout).expect("failed to poll interrupt");
-                pin.clear_interrupt().expect("failed to clear interrupt");
-            } else {
-                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in input pins (pin is either output or not setup)"));
+    /// Waits for a number of edges on an input pin and returns when each of them occurred, for characterizing pulse
+    /// trains or the jitter of an encoder. The edges are queued by the kernel and collected in Rust, so none is missed
+    /// between two of them. The GIL is released while waiting.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    /// - ```count``` (int): The number of edges to wait for.
+    /// - ```trigger_edge``` (TriggerEdge): The edges collected (default is BOTH).
+    /// - ```timeout_ms``` (float): The longest time to wait for all the edges in milliseconds, the edges collected so
+    ///   far are returned when it expires (default is None, which waits without limit).
+    /// - ```debounce_ms``` (float): The debounce time in milliseconds (default is None, which keeps every edge).
+    /// - ```intervals``` (bool): Whether the times between consecutive edges are returned instead of the timestamps
+    ///   (default is False).
+    ///
+    /// Returns:
+    /// - ```list[float]```: The timestamps of the edges, following the clock set with set_timestamp_clock, or the
+    ///   intervals between them. Both are in seconds, or ints in nanoseconds when set_timestamp_clock chose
+    ///   nanoseconds.
+    ///
+    /// Example usage:
+    /// ```intervals = manager.wait_for_edges(17, 100, TriggerEdge.RISING, timeout_ms=1000, intervals=True)```
+    #[pyo3(signature = (pin_num, count, trigger_edge = EnumArg(TriggerEdge::BOTH), timeout_ms = None, debounce_ms = None, intervals = false))]
+    #[allow(clippy::too_many_arguments)]
+    fn wait_for_edges(&self, py: Python, pin_num: u8, count: usize, trigger_edge: EnumArg<TriggerEdge>, timeout_ms: Option<f64>, debounce_ms: Option<f64>,
+                      intervals: bool) -> PyResult<PyObject> {
+        let (input_pin, trigger) = self.input_pin_trigger(pin_num, trigger_edge.0, "wait_for_edges")?;
+        let (clock, nanoseconds) = {
+            let manager = metrics::lock(&self.gpio);
+            (manager.timestamp_clock, manager.timestamp_ns)
+        };
+        let deadline = self.ms_to_duration("Timeout", timeout_ms)?.map(|timeout| Instant::now() + timeout);
+        let debounce = self.ms_to_duration("Debounce time", debounce_ms)?;
+
+        let timestamps = release_gil(|| {
+            let mut pin = input_pin.lock().unwrap();
+            pin.set_interrupt(trigger, debounce).expect("failed to setup interrupt");
+            let mut timestamps = Vec::with_capacity(count);
+            while timestamps.len() < count {
+                let timeout = deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+                match pin.poll_interrupt(false, timeout).expect("failed to poll interrupt") {
+                    Some(event) => timestamps.push(clock.convert_monotonic(event.timestamp)),
+                    None => break,
+                }
             }
+            pin.clear_interrupt().expect("failed to clear interrupt");
+            timestamps
+        });
+        let values: Vec<i128> = if intervals {
+            timestamps.windows(2).map(|pair| pair[1] - pair[0]).collect()
+        } else {
+            timestamps
+        };
+        if nanoseconds {
+            values.into_py_any(py)
         } else {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in input pins (pin is either output or not setup)"));
+            values.into_iter().map(|value| value as f64 / 1_000_000_000f64).collect::<Vec<f64>>().into_py_any(py)
         }
-        Ok(())
     }
 
     /// Reset the gpio_pin allowing it to be remapped to input or output
     #[pyo3(signature = (pin_num))]
-    fn reset_pin(&self, pin_num: u8) -> PyResult<()> {
+    pub(crate) fn reset_pin(&self, pin_num: u8) -> PyResult<()> {
         // Lock the manager to start
-        let manager = self.gpio.lock().unwrap();
+        let manager = metrics::lock(&self.gpio);
 
         // Temporary variable to hold the pin if it's found
         let input_pin_arc = manager.input_pins.get(&pin_num).cloned();
@@ -765,20 +2664,25 @@ impl GPIOManager {
 
         // Handle input pins
         if let Some(_) = input_pin_arc {
+            watchdog_module::unwatch(pin_num);
             self.unassign_callbacks(pin_num)?;
-            // Re-lock manager to remove the input pin
-            let mut manager = self.gpio.lock().unwrap();
+            // Re-lock manager to remove the input pin, dropping it clears an interrupt kept for the event logger
+            let mut manager = metrics::lock(&self.gpio);
             manager.input_pins.remove(&pin_num);
+            manager.callbacks.remove(&pin_num);
+            manager.debounce.remove(&pin_num);
         }
         // Handle output pins
         else if let Some(pin_arc) = output_pin_arc {
             let pin_arc = pin_arc.lock().unwrap();
             // Check if this pin has a PWM setup and reset PWM if necessary
             let pwm_exists = {
-                let manager = self.gpio.lock().unwrap();
+                let manager = metrics::lock(&self.gpio);
                 manager.pwm_setup.get(&pin_num).is_some()
             };
             if pwm_exists {
+                cancel_ramp(RampTarget::SoftwarePin(pin_num));
+                SOFT_PWM_ENGINE.remove_channel(pin_num);
                 if let PinType::Output(out_pin) = &pin_arc.pin {
                     let mut pin = out_pin.lock().unwrap();
                     pin.clear_pwm().expect("Failed to clear pwm");
@@ -790,32 +2694,37 @@ impl GPIOManager {
                 self.stop_pwm(pin_num)?;
 
                 // Re-lock the manager to remove the pin from PWM setup
-                let mut manager = self.gpio.lock().unwrap();
+                let mut manager = metrics::lock(&self.gpio);
                 manager.pwm_setup.remove(&pin_num);
             } else {
                 let pin = &pin_arc.pin;
                 if let PinType::Output(_) = pin {
                     drop(pin_arc);
-                    self.set_output_pin(pin_num, PinState::LOW)?;
+                    stop_pulse_train(pin_num);
+                    PIN_SCHEDULER.cancel_pin(Some(pin_num));
+                    self.set_output_pin(pin_num, PinState::LOW.into())?;
                 } else {
-                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in output pins (Something really bad happened to get to this point)"));
+                    return Err(Self::pin_error(&metrics::lock(&self.gpio), pin_num, "reset_pin"));
                 }
             }
 
-            // Re-lock manager to remove the output pin
-            let mut manager = self.gpio.lock().unwrap();
+            // Re-lock manager to remove the output pin, along with the callbacks it kept from being an input
+            let mut manager = metrics::lock(&self.gpio);
             manager.output_pins.remove(&pin_num);
+            manager.callbacks.remove(&pin_num);
+            manager.debounce.remove(&pin_num);
         }
+        metrics::lock(&self.gpio).aliases.retain(|_, alias_pin| *alias_pin != pin_num);
 
         Ok(())
     }
 
     /// Cleanup the GPIO pins by setting all outputs to low and clearing all interrupts
     #[pyo3(signature = ())]
-    fn cleanup(&self) -> PyResult<()> {
+    pub(crate) fn cleanup(&self) -> PyResult<()> {
         // Lock the manager and collect the necessary data
 
-        let manager = self.gpio.lock().unwrap();
+        let manager = metrics::lock(&self.gpio);
 
         // Clone the `Arc<Mutex<Pin>>` references, so we can release the manager lock
         let output_pins: Vec<(u8, Arc<Mutex<Pin>>)> = manager
@@ -835,12 +2744,14 @@ impl GPIOManager {
 
         // Iterate over input pins and reset them
         for (pin_num, _pin_arc) in input_pins {
+            log_module::info(&format!("Resetting input GPIO {} during cleanup", pin_num));
             self.reset_pin(pin_num)?;
         }
 
         // Iterate over output pins and reset them
         for (pin_num, _pin_arc) in output_pins {
             // If the pin is configured for PWM, set PWM to zero
+            log_module::info(&format!("Resetting output GPIO {} during cleanup", pin_num));
             self.reset_pin(pin_num)?;
         }
 
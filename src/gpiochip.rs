@@ -0,0 +1,361 @@
+use libc::{c_ulong, ioctl, poll, pollfd, POLLIN};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read};
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::time::Duration;
+
+
+// Sizes, flags and requests of the v2 GPIO character device ABI from linux/gpio.h
+const GPIO_MAX_NAME_SIZE: usize = 32;
+const GPIO_V2_LINES_MAX: usize = 64;
+const GPIO_V2_LINE_NUM_ATTRS_MAX: usize = 10;
+
+const GPIO_V2_LINE_FLAG_USED: u64 = 1 << 0;
+const GPIO_V2_LINE_FLAG_ACTIVE_LOW: u64 = 1 << 1;
+const GPIO_V2_LINE_FLAG_INPUT: u64 = 1 << 2;
+const GPIO_V2_LINE_FLAG_OUTPUT: u64 = 1 << 3;
+const GPIO_V2_LINE_FLAG_EDGE_RISING: u64 = 1 << 4;
+const GPIO_V2_LINE_FLAG_EDGE_FALLING: u64 = 1 << 5;
+const GPIO_V2_LINE_FLAG_OPEN_DRAIN: u64 = 1 << 6;
+const GPIO_V2_LINE_FLAG_OPEN_SOURCE: u64 = 1 << 7;
+const GPIO_V2_LINE_FLAG_BIAS_PULL_UP: u64 = 1 << 8;
+const GPIO_V2_LINE_FLAG_BIAS_PULL_DOWN: u64 = 1 << 9;
+const GPIO_V2_LINE_FLAG_BIAS_DISABLED: u64 = 1 << 10;
+
+const GPIO_V2_LINE_ATTR_ID_OUTPUT_VALUES: u32 = 2;
+const GPIO_V2_LINE_ATTR_ID_DEBOUNCE: u32 = 3;
+
+const GPIO_V2_LINE_EVENT_RISING_EDGE: u32 = 1;
+
+
+/// Encodes an ioctl request of the GPIO character device, as _IOR and _IOWR of asm-generic/ioctl.h.
+const fn ioc(direction: c_ulong, nr: c_ulong, size: usize) -> c_ulong {
+    (direction << 30) | ((size as c_ulong) << 16) | (0xB4 << 8) | nr
+}
+
+const GPIO_GET_CHIPINFO_IOCTL: c_ulong = ioc(2, 0x01, size_of::<GpioChipInfo>());
+const GPIO_V2_GET_LINEINFO_IOCTL: c_ulong = ioc(3, 0x05, size_of::<GpioV2LineInfo>());
+const GPIO_V2_GET_LINE_IOCTL: c_ulong = ioc(3, 0x07, size_of::<GpioV2LineRequest>());
+const GPIO_V2_LINE_SET_CONFIG_IOCTL: c_ulong = ioc(3, 0x0D, size_of::<GpioV2LineConfig>());
+const GPIO_V2_LINE_GET_VALUES_IOCTL: c_ulong = ioc(3, 0x0E, size_of::<GpioV2LineValues>());
+const GPIO_V2_LINE_SET_VALUES_IOCTL: c_ulong = ioc(3, 0x0F, size_of::<GpioV2LineValues>());
+
+
+#[repr(C)]
+struct GpioChipInfo {
+    name: [u8; GPIO_MAX_NAME_SIZE],
+    label: [u8; GPIO_MAX_NAME_SIZE],
+    lines: u32,
+}
+
+
+/// An attribute of a line, the value is the union of the flags, the output values and the debounce period.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct GpioV2LineAttribute {
+    id: u32,
+    padding: u32,
+    value: u64,
+}
+
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct GpioV2LineConfigAttribute {
+    attr: GpioV2LineAttribute,
+    mask: u64,
+}
+
+
+#[repr(C)]
+#[derive(Default)]
+struct GpioV2LineConfig {
+    flags: u64,
+    num_attrs: u32,
+    padding: [u32; 5],
+    attrs: [GpioV2LineConfigAttribute; GPIO_V2_LINE_NUM_ATTRS_MAX],
+}
+
+
+#[repr(C)]
+struct GpioV2LineRequest {
+    offsets: [u32; GPIO_V2_LINES_MAX],
+    consumer: [u8; GPIO_MAX_NAME_SIZE],
+    config: GpioV2LineConfig,
+    num_lines: u32,
+    event_buffer_size: u32,
+    padding: [u32; 5],
+    fd: i32,
+}
+
+
+#[repr(C)]
+#[derive(Default)]
+struct GpioV2LineValues {
+    bits: u64,
+    mask: u64,
+}
+
+
+#[repr(C)]
+struct GpioV2LineInfo {
+    name: [u8; GPIO_MAX_NAME_SIZE],
+    consumer: [u8; GPIO_MAX_NAME_SIZE],
+    offset: u32,
+    num_attrs: u32,
+    flags: u64,
+    attrs: [GpioV2LineAttribute; GPIO_V2_LINE_NUM_ATTRS_MAX],
+    padding: [u32; 4],
+}
+
+
+#[repr(C)]
+struct GpioV2LineEvent {
+    timestamp_ns: u64,
+    id: u32,
+    offset: u32,
+    seqno: u32,
+    line_seqno: u32,
+    padding: [u32; 6],
+}
+
+
+/// Returns the string of a NUL padded name of the ABI.
+fn name_string(name: &[u8]) -> String {
+    let end = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+    String::from_utf8_lossy(&name[..end]).into_owned()
+}
+
+
+fn check(result: i32) -> io::Result<()> {
+    if result < 0 { Err(io::Error::last_os_error()) } else { Ok(()) }
+}
+
+
+/// The bias of a requested line.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Bias {
+    /// The bias of the line is left as it is.
+    AsIs,
+    PullUp,
+    PullDown,
+    Disabled,
+}
+
+
+/// How a requested output line drives its line.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Drive {
+    PushPull,
+    OpenDrain,
+    OpenSource,
+}
+
+
+/// The settings of a requested line. Values and edges are logical, the kernel inverts them for active low lines.
+#[derive(Clone, Copy)]
+pub struct LineSettings {
+    pub output: bool,
+    /// The initial value of an output.
+    pub value: bool,
+    pub active_low: bool,
+    pub bias: Bias,
+    pub drive: Drive,
+    /// Whether the edges of an input are reported as events.
+    pub edges: bool,
+    /// The period the kernel debounces the edges of an input with.
+    pub debounce: Option<Duration>,
+}
+
+
+impl LineSettings {
+    fn config(&self) -> GpioV2LineConfig {
+        let mut config = GpioV2LineConfig {
+            flags: if self.output { GPIO_V2_LINE_FLAG_OUTPUT } else { GPIO_V2_LINE_FLAG_INPUT },
+            ..Default::default()
+        };
+        if self.active_low {
+            config.flags |= GPIO_V2_LINE_FLAG_ACTIVE_LOW;
+        }
+        config.flags |= match self.bias {
+            Bias::AsIs => 0,
+            Bias::PullUp => GPIO_V2_LINE_FLAG_BIAS_PULL_UP,
+            Bias::PullDown => GPIO_V2_LINE_FLAG_BIAS_PULL_DOWN,
+            Bias::Disabled => GPIO_V2_LINE_FLAG_BIAS_DISABLED,
+        };
+        if self.output {
+            config.flags |= match self.drive {
+                Drive::PushPull => 0,
+                Drive::OpenDrain => GPIO_V2_LINE_FLAG_OPEN_DRAIN,
+                Drive::OpenSource => GPIO_V2_LINE_FLAG_OPEN_SOURCE,
+            };
+            config.attrs[0] = GpioV2LineConfigAttribute {
+                attr: GpioV2LineAttribute { id: GPIO_V2_LINE_ATTR_ID_OUTPUT_VALUES, padding: 0, value: self.value as u64 },
+                mask: 1,
+            };
+            config.num_attrs = 1;
+        } else if self.edges {
+            config.flags |= GPIO_V2_LINE_FLAG_EDGE_RISING | GPIO_V2_LINE_FLAG_EDGE_FALLING;
+            if let Some(debounce) = self.debounce {
+                config.attrs[0] = GpioV2LineConfigAttribute {
+                    attr: GpioV2LineAttribute { id: GPIO_V2_LINE_ATTR_ID_DEBOUNCE, padding: 0, value: debounce.as_micros().min(u32::MAX as u128) as u64 },
+                    mask: 1,
+                };
+                config.num_attrs = 1;
+            }
+        }
+        config
+    }
+}
+
+
+/// The description of a GPIO chip.
+pub struct ChipInfo {
+    pub name: String,
+    pub label: String,
+    pub lines: u32,
+}
+
+
+/// The description of a line of a GPIO chip.
+pub struct LineInfo {
+    pub offset: u32,
+    pub name: String,
+    /// The consumer that requested the line, empty when the line is free.
+    pub consumer: String,
+    pub used: bool,
+    pub output: bool,
+    pub active_low: bool,
+}
+
+
+impl LineInfo {
+    /// Describes who holds the line for errors, None when it is free. The kernel reports the lines its drivers took
+    /// through pin muxing as used without a consumer.
+    pub fn claim(&self) -> Option<String> {
+        if !self.used {
+            None
+        } else if self.consumer.is_empty() {
+            Some(String::from("claimed by a kernel driver"))
+        } else {
+            Some(format!("requested by '{}'", self.consumer))
+        }
+    }
+}
+
+
+/// An edge of a requested input line.
+pub struct LineEvent {
+    /// The time of the edge on the monotonic clock.
+    pub timestamp: Duration,
+    /// Whether the logical value rose.
+    pub rising: bool,
+}
+
+
+/// A raw handle to a GPIO character device, used on boards rppal doesn't support.
+pub struct Chip {
+    file: File,
+}
+
+
+impl Chip {
+    /// Opens the character device of a GPIO chip, like /dev/gpiochip0.
+    pub fn open(path: &str) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        Ok(Self { file })
+    }
+
+    pub fn info(&self) -> io::Result<ChipInfo> {
+        let mut info = GpioChipInfo { name: [0; GPIO_MAX_NAME_SIZE], label: [0; GPIO_MAX_NAME_SIZE], lines: 0 };
+        check(unsafe { ioctl(self.file.as_raw_fd(), GPIO_GET_CHIPINFO_IOCTL as _, &mut info) })?;
+        Ok(ChipInfo { name: name_string(&info.name), label: name_string(&info.label), lines: info.lines })
+    }
+
+    pub fn line_info(&self, offset: u32) -> io::Result<LineInfo> {
+        let mut info = GpioV2LineInfo {
+            name: [0; GPIO_MAX_NAME_SIZE],
+            consumer: [0; GPIO_MAX_NAME_SIZE],
+            offset,
+            num_attrs: 0,
+            flags: 0,
+            attrs: [GpioV2LineAttribute::default(); GPIO_V2_LINE_NUM_ATTRS_MAX],
+            padding: [0; 4],
+        };
+        check(unsafe { ioctl(self.file.as_raw_fd(), GPIO_V2_GET_LINEINFO_IOCTL as _, &mut info) })?;
+        Ok(LineInfo {
+            offset,
+            name: name_string(&info.name),
+            consumer: name_string(&info.consumer),
+            used: info.flags & GPIO_V2_LINE_FLAG_USED != 0,
+            output: info.flags & GPIO_V2_LINE_FLAG_OUTPUT != 0,
+            active_low: info.flags & GPIO_V2_LINE_FLAG_ACTIVE_LOW != 0,
+        })
+    }
+
+    /// Requests a line, which is held until the returned request is dropped.
+    pub fn request_line(&self, offset: u32, consumer: &str, settings: &LineSettings) -> io::Result<LineRequest> {
+        let mut request = GpioV2LineRequest {
+            offsets: [0; GPIO_V2_LINES_MAX],
+            consumer: [0; GPIO_MAX_NAME_SIZE],
+            config: settings.config(),
+            num_lines: 1,
+            event_buffer_size: 0,
+            padding: [0; 5],
+            fd: -1,
+        };
+        request.offsets[0] = offset;
+        let consumer = consumer.as_bytes();
+        let length = consumer.len().min(GPIO_MAX_NAME_SIZE - 1);
+        request.consumer[..length].copy_from_slice(&consumer[..length]);
+        check(unsafe { ioctl(self.file.as_raw_fd(), GPIO_V2_GET_LINE_IOCTL as _, &mut request) })?;
+        // The kernel hands over a new file descriptor owning the line
+        Ok(LineRequest { file: unsafe { File::from_raw_fd(request.fd) } })
+    }
+}
+
+
+/// A requested line, released when dropped.
+pub struct LineRequest {
+    file: File,
+}
+
+
+impl LineRequest {
+    /// Returns the logical value of the line.
+    pub fn get_value(&self) -> io::Result<bool> {
+        let mut values = GpioV2LineValues { bits: 0, mask: 1 };
+        check(unsafe { ioctl(self.file.as_raw_fd(), GPIO_V2_LINE_GET_VALUES_IOCTL as _, &mut values) })?;
+        Ok(values.bits & 1 != 0)
+    }
+
+    /// Sets the logical value of an output line.
+    pub fn set_value(&self, value: bool) -> io::Result<()> {
+        let mut values = GpioV2LineValues { bits: value as u64, mask: 1 };
+        check(unsafe { ioctl(self.file.as_raw_fd(), GPIO_V2_LINE_SET_VALUES_IOCTL as _, &mut values) })
+    }
+
+    /// Changes the settings of the line without releasing it.
+    pub fn reconfigure(&self, settings: &LineSettings) -> io::Result<()> {
+        let mut config = settings.config();
+        check(unsafe { ioctl(self.file.as_raw_fd(), GPIO_V2_LINE_SET_CONFIG_IOCTL as _, &mut config) })
+    }
+
+    /// Waits for the next edge of a line requested with edges, None when none came within the timeout.
+    pub fn read_event(&self, timeout: Duration) -> io::Result<Option<LineEvent>> {
+        let mut fds = pollfd { fd: self.file.as_raw_fd(), events: POLLIN, revents: 0 };
+        let ready = unsafe { poll(&mut fds, 1, timeout.as_millis().min(i32::MAX as u128) as i32) };
+        if ready < 0 {
+            let e = io::Error::last_os_error();
+            return if e.kind() == io::ErrorKind::Interrupted { Ok(None) } else { Err(e) };
+        }
+        if ready == 0 {
+            return Ok(None);
+        }
+        let mut buffer = [0u8; size_of::<GpioV2LineEvent>()];
+        (&self.file).read_exact(&mut buffer)?;
+        // The buffer has no alignment, so the event is read unaligned
+        let event: GpioV2LineEvent = unsafe { std::ptr::read_unaligned(buffer.as_ptr() as *const GpioV2LineEvent) };
+        Ok(Some(LineEvent { timestamp: Duration::from_nanos(event.timestamp_ns), rising: event.id == GPIO_V2_LINE_EVENT_RISING_EDGE }))
+    }
+}
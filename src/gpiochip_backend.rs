@@ -0,0 +1,427 @@
+use crate::backend::{Backend, Error, InputLine, InterruptCallback, OutputLine, PwmLine, Result};
+use crate::gpiochip::{self, Chip, Drive, LineEvent, LineRequest, LineSettings};
+use crate::log_module;
+use crate::timing::wait_until;
+use rppal::gpio::{Bias, Event, Level, Trigger};
+use rppal::pwm::Polarity;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+
+// How long the thread of an interrupt waits for an edge before checking for being stopped
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+// The name the lines are requested with, shown by gpioinfo
+const CONSUMER: &str = "gpio_manager";
+// The PWM chip the channels are exported from
+const PWM_CHIP: &str = "/sys/class/pwm/pwmchip0";
+
+
+/// The backend driving the lines of a GPIO chip through the character device, on any board with a GPIO driver.
+pub struct GpiochipBackend {
+    chip: Chip,
+}
+
+
+impl GpiochipBackend {
+    pub fn open(path: &str) -> io::Result<Self> {
+        Ok(Self { chip: Chip::open(path)? })
+    }
+}
+
+
+fn line_bias(bias: Bias) -> gpiochip::Bias {
+    match bias {
+        Bias::PullUp => gpiochip::Bias::PullUp,
+        Bias::PullDown => gpiochip::Bias::PullDown,
+        Bias::Off => gpiochip::Bias::Disabled,
+    }
+}
+
+
+impl Backend for GpiochipBackend {
+    fn name(&self) -> &'static str {
+        "gpiochip"
+    }
+
+    fn input(&self, pin_num: u8, bias: Bias) -> Result<Box<dyn InputLine>> {
+        let settings = LineSettings {
+            output: false,
+            value: false,
+            active_low: false,
+            bias: line_bias(bias),
+            drive: Drive::PushPull,
+            edges: false,
+            debounce: None,
+        };
+        let request = self.chip.request_line(pin_num as u32, CONSUMER, &settings)?;
+        Ok(Box::new(ChipInput { request: Arc::new(request), settings, trigger: None, seqno: 0, watcher: None }))
+    }
+
+    fn output(&self, pin_num: u8, level: Level) -> Result<Box<dyn OutputLine>> {
+        let settings = LineSettings {
+            output: true,
+            value: level == Level::High,
+            active_low: false,
+            bias: gpiochip::Bias::AsIs,
+            drive: Drive::PushPull,
+            edges: false,
+            debounce: None,
+        };
+        let request = self.chip.request_line(pin_num as u32, CONSUMER, &settings)?;
+        Ok(Box::new(ChipOutput { request: Arc::new(request), settings, pwm: None }))
+    }
+
+    fn pwm(&self, channel_num: u8, frequency: f64, duty_cycle: f64, polarity: Polarity, enabled: bool) -> Result<Box<dyn PwmLine>> {
+        let pwm = SysfsPwm::export(channel_num)?;
+        let _ = pwm.disable();
+        pwm.set_frequency(frequency, duty_cycle)?;
+        pwm.set_polarity(polarity)?;
+        if enabled {
+            pwm.enable()?;
+        }
+        Ok(Box::new(pwm))
+    }
+
+    fn read_levels(&self) -> Result<u32> {
+        Err(Error::new("Reading every pin at once isn't supported by the gpiochip backend"))
+    }
+
+    fn claimed_by(&self, pin_num: u8) -> Option<String> {
+        self.chip.line_info(pin_num as u32).ok()?.claim()
+    }
+}
+
+
+/// Turns an edge of a line into the event rppal would report, None when the interrupt doesn't trigger on it.
+fn to_event(event: LineEvent, trigger: Trigger, seqno: &mut u32) -> Option<Event> {
+    let triggered = match trigger {
+        Trigger::RisingEdge => event.rising,
+        Trigger::FallingEdge => !event.rising,
+        Trigger::Both => true,
+        Trigger::Disabled => false,
+    };
+    if !triggered {
+        return None;
+    }
+    *seqno = seqno.wrapping_add(1);
+    Some(Event {
+        timestamp: event.timestamp,
+        seqno: *seqno,
+        trigger: if event.rising { Trigger::RisingEdge } else { Trigger::FallingEdge },
+    })
+}
+
+
+/// Stops a thread started for a line and waits for it.
+fn stop_thread(thread: Option<(Arc<AtomicBool>, JoinHandle<()>)>) {
+    if let Some((stopped, handle)) = thread {
+        stopped.store(true, Ordering::SeqCst);
+        let _ = handle.join();
+    }
+}
+
+
+/// An input line, its edges are requested from the kernel while an interrupt is set up.
+struct ChipInput {
+    request: Arc<LineRequest>,
+    settings: LineSettings,
+    /// The edges polled by poll_interrupt, None without a synchronous interrupt.
+    trigger: Option<Trigger>,
+    seqno: u32,
+    /// The thread of the asynchronous interrupt and its stop flag.
+    watcher: Option<(Arc<AtomicBool>, JoinHandle<()>)>,
+}
+
+
+impl ChipInput {
+    fn set_edges(&mut self, edges: bool, debounce: Option<Duration>) -> Result<()> {
+        self.settings.edges = edges;
+        self.settings.debounce = debounce;
+        Ok(self.request.reconfigure(&self.settings)?)
+    }
+}
+
+
+impl InputLine for ChipInput {
+    fn read(&self) -> Level {
+        match self.request.get_value() {
+            Ok(true) => Level::High,
+            Ok(false) => Level::Low,
+            Err(e) => {
+                log_module::warning(&format!("Unable to read a line: {}", e));
+                Level::Low
+            }
+        }
+    }
+
+    fn set_bias(&mut self, bias: Bias) -> Result<()> {
+        self.settings.bias = line_bias(bias);
+        Ok(self.request.reconfigure(&self.settings)?)
+    }
+
+    fn set_interrupt(&mut self, trigger: Trigger, debounce: Option<Duration>) -> Result<()> {
+        self.clear_async_interrupt()?;
+        self.set_edges(true, debounce)?;
+        self.trigger = Some(trigger);
+        Ok(())
+    }
+
+    fn clear_interrupt(&mut self) -> Result<()> {
+        self.trigger = None;
+        self.set_edges(false, None)
+    }
+
+    fn poll_interrupt(&mut self, reset: bool, timeout: Option<Duration>) -> Result<Option<Event>> {
+        let Some(trigger) = self.trigger else {
+            return Err(Error::new("No interrupt is set up on the pin"));
+        };
+        if reset {
+            while self.request.read_event(Duration::ZERO)?.is_some() {}
+        }
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            let wait = match deadline {
+                Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+                None => POLL_INTERVAL,
+            };
+            if let Some(event) = self.request.read_event(wait)? {
+                if let Some(event) = to_event(event, trigger, &mut self.seqno) {
+                    return Ok(Some(event));
+                }
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return Ok(None);
+            }
+        }
+    }
+
+    fn set_async_interrupt(&mut self, trigger: Trigger, debounce: Option<Duration>, mut callback: InterruptCallback) -> Result<()> {
+        self.trigger = None;
+        stop_thread(self.watcher.take());
+        self.set_edges(true, debounce)?;
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread_stopped = Arc::clone(&stopped);
+        let request = Arc::clone(&self.request);
+        let handle = thread::spawn(move || {
+            let mut seqno = 0;
+            while !thread_stopped.load(Ordering::SeqCst) {
+                match request.read_event(POLL_INTERVAL) {
+                    Ok(Some(event)) => {
+                        if let Some(event) = to_event(event, trigger, &mut seqno) {
+                            callback(event);
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        log_module::warning(&format!("Unable to read the edges of a line: {}", e));
+                        return;
+                    }
+                }
+            }
+        });
+        self.watcher = Some((stopped, handle));
+        Ok(())
+    }
+
+    fn clear_async_interrupt(&mut self) -> Result<()> {
+        if self.watcher.is_none() {
+            return Ok(());
+        }
+        stop_thread(self.watcher.take());
+        self.set_edges(false, None)
+    }
+
+    fn set_reset_on_drop(&mut self, _reset_on_drop: bool) {
+        // The kernel leaves a released line as it is, there is nothing to reset
+    }
+}
+
+
+impl Drop for ChipInput {
+    fn drop(&mut self) {
+        stop_thread(self.watcher.take());
+    }
+}
+
+
+/// An output line, software PWM toggles it from a thread.
+struct ChipOutput {
+    request: Arc<LineRequest>,
+    settings: LineSettings,
+    /// The thread of the software PWM and its stop flag.
+    pwm: Option<(Arc<AtomicBool>, JoinHandle<()>)>,
+}
+
+
+impl OutputLine for ChipOutput {
+    fn write(&mut self, level: Level) {
+        self.settings.value = level == Level::High;
+        if let Err(e) = self.request.set_value(self.settings.value) {
+            log_module::warning(&format!("Unable to set a line: {}", e));
+        }
+    }
+
+    fn is_set_high(&self) -> bool {
+        self.request.get_value().unwrap_or(self.settings.value)
+    }
+
+    fn set_pwm_frequency(&mut self, frequency: f64, duty_cycle: f64) -> Result<()> {
+        stop_thread(self.pwm.take());
+        if frequency <= 0.0 {
+            return Ok(());
+        }
+        let period = Duration::from_secs_f64(1.0 / frequency);
+        let pulse_width = period.mul_f64(duty_cycle.clamp(0.0, 1.0));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let thread_stopped = Arc::clone(&stopped);
+        let request = Arc::clone(&self.request);
+        let handle = thread::spawn(move || {
+            let mut start = Instant::now();
+            while !thread_stopped.load(Ordering::SeqCst) {
+                if !pulse_width.is_zero() {
+                    let _ = request.set_value(true);
+                    wait_until(start + pulse_width);
+                }
+                if pulse_width < period {
+                    let _ = request.set_value(false);
+                }
+                start += period;
+                wait_until(start);
+            }
+        });
+        self.pwm = Some((stopped, handle));
+        Ok(())
+    }
+
+    fn clear_pwm(&mut self) -> Result<()> {
+        stop_thread(self.pwm.take());
+        Ok(())
+    }
+
+    fn set_output_enabled(&mut self, enabled: bool) -> Result<()> {
+        // A disabled output is turned into an input, which releases the line
+        self.settings.output = enabled;
+        Ok(self.request.reconfigure(&self.settings)?)
+    }
+
+    fn set_reset_on_drop(&mut self, _reset_on_drop: bool) {
+        // The kernel leaves a released line as it is, there is nothing to reset
+    }
+}
+
+
+impl Drop for ChipOutput {
+    fn drop(&mut self) {
+        stop_thread(self.pwm.take());
+    }
+}
+
+
+/// A channel of the first PWM chip, driven through the PWM sysfs interface.
+struct SysfsPwm {
+    path: PathBuf,
+}
+
+
+impl SysfsPwm {
+    fn export(channel_num: u8) -> io::Result<Self> {
+        let path = PathBuf::from(PWM_CHIP).join(format!("pwm{}", channel_num));
+        if !path.exists() {
+            fs::write(PathBuf::from(PWM_CHIP).join("export"), channel_num.to_string())?;
+        }
+        // The attributes appear once udev has set their permissions
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while fs::metadata(path.join("period")).map_or(true, |metadata| metadata.permissions().readonly()) {
+            if Instant::now() >= deadline {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, format!("{} didn't appear", path.display())));
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        Ok(Self { path })
+    }
+
+    fn read_ns(&self, attribute: &str) -> Result<u64> {
+        let value = fs::read_to_string(self.path.join(attribute))?;
+        value.trim().parse().map_err(|_| Error::new(format!("Invalid {} {}", attribute, value.trim())))
+    }
+
+    fn write(&self, attribute: &str, value: impl ToString) -> Result<()> {
+        Ok(fs::write(self.path.join(attribute), value.to_string())?)
+    }
+}
+
+
+impl PwmLine for SysfsPwm {
+    fn period(&self) -> Result<Duration> {
+        Ok(Duration::from_nanos(self.read_ns("period")?))
+    }
+
+    fn set_period(&self, period: Duration) -> Result<()> {
+        self.write("period", period.as_nanos())
+    }
+
+    fn pulse_width(&self) -> Result<Duration> {
+        Ok(Duration::from_nanos(self.read_ns("duty_cycle")?))
+    }
+
+    fn set_pulse_width(&self, pulse_width: Duration) -> Result<()> {
+        self.write("duty_cycle", pulse_width.as_nanos())
+    }
+
+    fn frequency(&self) -> Result<f64> {
+        let period = self.read_ns("period")?;
+        Ok(if period == 0 { 0.0 } else { 1e9 / period as f64 })
+    }
+
+    fn set_frequency(&self, frequency: f64, duty_cycle: f64) -> Result<()> {
+        let period = if frequency <= 0.0 { 0 } else { (1e9 / frequency) as u64 };
+        // The pulse width has to stay below the period, so it is lowered first
+        self.write("duty_cycle", 0)?;
+        self.write("period", period)?;
+        self.write("duty_cycle", (period as f64 * duty_cycle.clamp(0.0, 1.0)) as u64)
+    }
+
+    fn duty_cycle(&self) -> Result<f64> {
+        let period = self.read_ns("period")?;
+        Ok(if period == 0 { 0.0 } else { self.read_ns("duty_cycle")? as f64 / period as f64 })
+    }
+
+    fn set_duty_cycle(&self, duty_cycle: f64) -> Result<()> {
+        let period = self.read_ns("period")?;
+        self.write("duty_cycle", (period as f64 * duty_cycle.clamp(0.0, 1.0)) as u64)
+    }
+
+    fn set_polarity(&self, polarity: Polarity) -> Result<()> {
+        self.write("polarity", match polarity {
+            Polarity::Normal => "normal",
+            Polarity::Inverse => "inversed",
+        })
+    }
+
+    fn is_enabled(&self) -> Result<bool> {
+        Ok(self.read_ns("enable")? != 0)
+    }
+
+    fn enable(&self) -> Result<()> {
+        self.write("enable", 1)
+    }
+
+    fn disable(&self) -> Result<()> {
+        self.write("enable", 0)
+    }
+}
+
+
+impl Drop for SysfsPwm {
+    fn drop(&mut self) {
+        let _ = self.disable();
+        if let Some(channel) = self.path.file_name().and_then(|name| name.to_str()).and_then(|name| name.strip_prefix("pwm")) {
+            let _ = fs::write(PathBuf::from(PWM_CHIP).join("unexport"), channel);
+        }
+    }
+}
@@ -0,0 +1,413 @@
+use crate::gpiochip::{Bias, Chip, Drive, LineRequest, LineSettings};
+use crate::{log_module, ms_to_duration_checked, EnumArg, InternPullResistorState, LogicLevel, OutputMode, PinState, TimestampClock, TriggerEdge};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple};
+use pyo3::IntoPyObjectExt;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+
+// How long the thread of a line waits for an edge before checking for being stopped
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+// The name the lines are requested with, shown by gpioinfo
+const CONSUMER: &str = "gpio_manager";
+
+
+/// A callback assigned to a line.
+struct ChipCallback {
+    callable: PyObject,
+    trigger_edge: TriggerEdge,
+    args: Py<PyTuple>,
+    send_time: bool,
+    send_edge: bool,
+}
+
+
+/// A line requested through the manager.
+struct ChipPin {
+    request: Arc<LineRequest>,
+    settings: LineSettings,
+    /// The thread delivering the edges of the line to its callbacks and its stop flag, None without callbacks.
+    watcher: Option<(Arc<AtomicBool>, JoinHandle<()>)>,
+}
+
+
+impl ChipPin {
+    /// Stops the thread of the line, returning it so it can be joined without holding the GIL.
+    fn stop_watcher(&mut self) -> Option<JoinHandle<()>> {
+        let (stopped, handle) = self.watcher.take()?;
+        stopped.store(true, Ordering::SeqCst);
+        Some(handle)
+    }
+}
+
+
+fn pin_not_input() -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in input pins (pin is either output or not setup)")
+}
+
+
+fn pin_not_output() -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in output pins (pin is either input or not setup)")
+}
+
+
+/// Calls the callbacks of a line for an edge.
+fn dispatch(py: Python, callbacks: &Mutex<HashMap<u32, Vec<ChipCallback>>>, pin_num: u32, edge: TriggerEdge, timestamp: Duration) {
+    let matching: Vec<(PyObject, Py<PyTuple>, bool, bool)> = match callbacks.lock().unwrap().get(&pin_num) {
+        Some(callbacks) => callbacks.iter()
+                                    .filter(|callback| callback.trigger_edge == TriggerEdge::BOTH || callback.trigger_edge == edge)
+                                    .map(|callback| (callback.callable.clone_ref(py), callback.args.clone_ref(py), callback.send_time, callback.send_edge))
+                                    .collect(),
+        None => return,
+    };
+    let trigger_time = TimestampClock::REALTIME.convert_monotonic(timestamp) as f64 / 1_000_000_000f64;
+    for (callable, args, send_time, send_edge) in matching {
+        let call = || -> PyResult<PyObject> {
+            let mut call_args: Vec<PyObject> = Vec::new();
+            if send_time {
+                call_args.push(trigger_time.into_py_any(py)?);
+            }
+            if send_edge {
+                call_args.push(edge.into_py_any(py)?);
+            }
+            call_args.extend(args.bind(py).iter().map(|item| item.unbind()));
+            callable.call1(py, PyTuple::new(py, call_args)?)
+        };
+        if let Err(e) = call() {
+            log_module::exception(&format!("Exception in a callback of line {}", pin_num), &e);
+        }
+    }
+}
+
+
+#[pyclass]
+/// Drives the lines of a GPIO chip through the GPIO character device with the API of the GPIOManager, for the boards
+/// rppal doesn't support, like the Orange Pi, Rock Pi and BeagleBone. Pins are the line offsets of the chip.
+pub struct GPIOChipManager {
+    path: String,
+    chip: Chip,
+    pins: Mutex<HashMap<u32, ChipPin>>,
+    callbacks: Arc<Mutex<HashMap<u32, Vec<ChipCallback>>>>,
+}
+
+
+impl GPIOChipManager {
+    fn line_error(&self, pin_num: u32, e: std::io::Error) -> PyErr {
+        PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Unable to request line {} of {}: {}", pin_num, self.path, e))
+    }
+
+    /// Requests a line, releasing the line first when the manager already holds it.
+    fn request(&self, py: Python, pin_num: u32, settings: LineSettings) -> PyResult<()> {
+        self.reset_pin(py, pin_num);
+        let request = self.chip.request_line(pin_num, CONSUMER, &settings).map_err(|e| self.line_error(pin_num, e))?;
+        self.pins.lock().unwrap().insert(pin_num, ChipPin { request: Arc::new(request), settings, watcher: None });
+        Ok(())
+    }
+}
+
+
+#[pymethods]
+impl GPIOChipManager {
+    /// Opens a GPIO chip.
+    ///
+    /// Parameters:
+    /// - ```chip``` (str): The character device of the chip (default is "/dev/gpiochip0").
+    ///
+    /// Example usage:
+    /// ```chip = gpio_manager.GPIOChipManager("/dev/gpiochip1")```
+    ///
+    #[new]
+    #[pyo3(signature = (chip = "/dev/gpiochip0"))]
+    fn new(chip: &str) -> PyResult<Self> {
+        let opened = Chip::open(chip).map_err(|e| PyErr::new::<pyo3::exceptions::PyOSError, _>(format!("Unable to open {}: {}", chip, e)))?;
+        Ok(GPIOChipManager { path: chip.to_string(), chip: opened, pins: Mutex::new(HashMap::new()), callbacks: Arc::new(Mutex::new(HashMap::new())) })
+    }
+
+    /// Returns the name, label and number of lines of the chip.
+    ///
+    /// Returns:
+    /// - ```dict```: The `name`, `label` and `lines` of the chip.
+    ///
+    /// Example usage:
+    /// ```print(chip.chip_info()["label"])```
+    ///
+    fn chip_info<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let info = self.chip.info()?;
+        let dict = PyDict::new(py);
+        dict.set_item("name", info.name)?;
+        dict.set_item("label", info.label)?;
+        dict.set_item("lines", info.lines)?;
+        Ok(dict)
+    }
+
+    /// Returns the lines of the chip, like gpioinfo.
+    ///
+    /// Returns:
+    /// - ```list[dict]```: The `offset`, `name`, `consumer`, `used`, `output` and `active_low` of every line.
+    ///
+    /// Example usage:
+    /// ```free = [line["offset"] for line in chip.get_lines() if not line["used"]]```
+    ///
+    fn get_lines<'py>(&self, py: Python<'py>) -> PyResult<Vec<Bound<'py, PyDict>>> {
+        let mut lines = Vec::new();
+        for offset in 0..self.chip.info()?.lines {
+            let info = self.chip.line_info(offset)?;
+            let dict = PyDict::new(py);
+            dict.set_item("offset", info.offset)?;
+            dict.set_item("name", info.name)?;
+            dict.set_item("consumer", info.consumer)?;
+            dict.set_item("used", info.used)?;
+            dict.set_item("output", info.output)?;
+            dict.set_item("active_low", info.active_low)?;
+            lines.push(dict);
+        }
+        Ok(lines)
+    }
+
+    /// Returns the offset of the line with a name, as named by the device tree of the board.
+    ///
+    /// Parameters:
+    /// - ```name``` (str): The name of the line.
+    ///
+    /// Returns:
+    /// - ```int```: The offset of the line.
+    ///
+    /// Example usage:
+    /// ```led = chip.find_line("PA12")```
+    ///
+    fn find_line(&self, name: &str) -> PyResult<u32> {
+        for offset in 0..self.chip.info()?.lines {
+            if self.chip.line_info(offset)?.name == name {
+                return Ok(offset);
+            }
+        }
+        Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{} has no line named {}", self.path, name)))
+    }
+
+    /// Sets up an input line.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The line offset.
+    /// - ```pull_resistor_state``` (InternPullResistorState): The bias of the line, AUTO leaves it as it is (default is
+    ///   AUTO).
+    /// - ```logic_level``` (LogicLevel): The logic level of the line (default is HIGH).
+    ///
+    /// Example usage:
+    /// ```chip.add_input_pin(17, gpio_manager.InternPullResistorState.PULLUP)```
+    ///
+    #[pyo3(signature = (pin_num, pull_resistor_state = EnumArg(InternPullResistorState::AUTO), logic_level = EnumArg(LogicLevel::HIGH)))]
+    fn add_input_pin(&self, py: Python, pin_num: u32, pull_resistor_state: EnumArg<InternPullResistorState>, logic_level: EnumArg<LogicLevel>) -> PyResult<()> {
+        let pull_resistor_state = pull_resistor_state.0;
+        let logic_level = logic_level.0;
+        let bias = match pull_resistor_state {
+            InternPullResistorState::AUTO => Bias::AsIs,
+            InternPullResistorState::PULLUP => Bias::PullUp,
+            InternPullResistorState::PULLDOWN => Bias::PullDown,
+            InternPullResistorState::EXTERNAL => Bias::Disabled,
+        };
+        self.request(py, pin_num, LineSettings {
+            output: false,
+            value: false,
+            active_low: logic_level == LogicLevel::LOW,
+            bias,
+            drive: Drive::PushPull,
+            edges: false,
+            debounce: None,
+        })
+    }
+
+    /// Sets up an output line.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The line offset.
+    /// - ```pin_state``` (PinState): The initial state of the line (default is LOW).
+    /// - ```logic_level``` (LogicLevel): The logic level of the line (default is HIGH).
+    /// - ```output_mode``` (OutputMode): How the line is driven (default is PUSH_PULL).
+    ///
+    /// Example usage:
+    /// ```chip.add_output_pin(27)```
+    ///
+    #[pyo3(signature = (pin_num, pin_state = EnumArg(PinState::LOW), logic_level = EnumArg(LogicLevel::HIGH), output_mode = EnumArg(OutputMode::PUSH_PULL)))]
+    fn add_output_pin(&self, py: Python, pin_num: u32, pin_state: EnumArg<PinState>, logic_level: EnumArg<LogicLevel>, output_mode: EnumArg<OutputMode>) -> PyResult<()> {
+        let pin_state = pin_state.0;
+        let logic_level = logic_level.0;
+        let output_mode = output_mode.0;
+        let drive = match output_mode {
+            OutputMode::PUSH_PULL => Drive::PushPull,
+            OutputMode::OPEN_DRAIN => Drive::OpenDrain,
+            OutputMode::OPEN_SOURCE => Drive::OpenSource,
+        };
+        self.request(py, pin_num, LineSettings {
+            output: true,
+            value: pin_state == PinState::HIGH,
+            active_low: logic_level == LogicLevel::LOW,
+            bias: Bias::AsIs,
+            drive,
+            edges: false,
+            debounce: None,
+        })
+    }
+
+    /// Sets the state of an output line.
+    ///
+    /// Example usage:
+    /// ```chip.set_output_pin(27, gpio_manager.PinState.HIGH)```
+    ///
+    fn set_output_pin(&self, pin_num: u32, pin_state: EnumArg<PinState>) -> PyResult<()> {
+        let pin_state = pin_state.0;
+        let pins = self.pins.lock().unwrap();
+        match pins.get(&pin_num) {
+            Some(pin) if pin.settings.output => pin.request.set_value(pin_state == PinState::HIGH).map_err(|e| self.line_error(pin_num, e)),
+            _ => Err(pin_not_output()),
+        }
+    }
+
+    /// Reads the logical state of a line, inputs read the line and outputs the state they are driven to.
+    ///
+    /// Example usage:
+    /// ```state = chip.get_pin(17)```
+    ///
+    fn get_pin(&self, pin_num: u32) -> PyResult<PinState> {
+        let pins = self.pins.lock().unwrap();
+        let pin = pins.get(&pin_num)
+                      .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin not found in input or output pins (pin is not setup)"))?;
+        let high = pin.request.get_value().map_err(|e| self.line_error(pin_num, e))?;
+        Ok(if high { PinState::HIGH } else { PinState::LOW })
+    }
+
+    /// Assigns a callback to an input line, called from a thread of the line. The edges are debounced by the kernel.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The line offset.
+    /// - ```callback``` (function): The callback function to be invoked on pin change.
+    /// - ```trigger_edge``` (TriggerEdge): The edges calling the callback (default is BOTH).
+    /// - ```debounce_time_ms``` (float): The debounce time in milliseconds, set by the first callback of the line
+    ///   (default is 2).
+    /// - ```args``` (tuple): The arguments to pass to the callback function (default is None).
+    /// - ```include_trigger_time``` (bool): Whether the time of the edge is passed first, in seconds since the Unix
+    ///   epoch (default is False).
+    /// - ```include_trigger_edge``` (bool): Whether the edge is passed after the time (default is False).
+    ///
+    /// Example usage:
+    /// ```chip.assign_callback(17, button_callback, gpio_manager.TriggerEdge.FALLING)```
+    ///
+    #[pyo3(signature = (pin_num, callback, trigger_edge = EnumArg(TriggerEdge::BOTH), debounce_time_ms = 2f64, args = None, include_trigger_time = false,
+    include_trigger_edge = false))]
+    #[allow(clippy::too_many_arguments)]
+    fn assign_callback(&self, py: Python, pin_num: u32, callback: PyObject, trigger_edge: EnumArg<TriggerEdge>, debounce_time_ms: f64, args: Option<&Bound<'_, PyTuple>>,
+                       include_trigger_time: bool, include_trigger_edge: bool) -> PyResult<()> {
+        let trigger_edge = trigger_edge.0;
+        if !callback.bind(py).is_callable() {
+            return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>("Object is not callable"));
+        }
+        let mut pins = self.pins.lock().unwrap();
+        let pin = match pins.get_mut(&pin_num) {
+            Some(pin) if !pin.settings.output => pin,
+            _ => return Err(pin_not_input()),
+        };
+        if pin.watcher.is_none() {
+            let debounce = ms_to_duration_checked("Debounce time", debounce_time_ms)?;
+            let mut settings = pin.settings;
+            settings.edges = true;
+            settings.debounce = if debounce.is_zero() { None } else { Some(debounce) };
+            pin.request.reconfigure(&settings).map_err(|e| self.line_error(pin_num, e))?;
+            pin.settings = settings;
+
+            let stopped = Arc::new(AtomicBool::new(false));
+            let thread_stopped = Arc::clone(&stopped);
+            let request = Arc::clone(&pin.request);
+            let callbacks = Arc::clone(&self.callbacks);
+            let handle = thread::spawn(move || {
+                while !thread_stopped.load(Ordering::SeqCst) {
+                    match request.read_event(POLL_INTERVAL) {
+                        Ok(Some(event)) if !thread_stopped.load(Ordering::SeqCst) => {
+                            let edge = if event.rising { TriggerEdge::RISING } else { TriggerEdge::FALLING };
+                            Python::with_gil(|py| dispatch(py, &callbacks, pin_num, edge, event.timestamp));
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            log_module::warning(&format!("Stopped reading the edges of line {}: {}", pin_num, e));
+                            break;
+                        }
+                    }
+                }
+            });
+            pin.watcher = Some((stopped, handle));
+        }
+        let args = match args {
+            Some(args) => args.clone().unbind(),
+            None => PyTuple::empty(py).unbind(),
+        };
+        self.callbacks.lock().unwrap().entry(pin_num).or_default().push(ChipCallback {
+            callable: callback,
+            trigger_edge,
+            args,
+            send_time: include_trigger_time,
+            send_edge: include_trigger_edge,
+        });
+        Ok(())
+    }
+
+    /// Removes the callbacks of an input line.
+    ///
+    /// Example usage:
+    /// ```chip.unassign_callbacks(17)```
+    ///
+    fn unassign_callbacks(&self, py: Python, pin_num: u32) -> PyResult<()> {
+        self.callbacks.lock().unwrap().remove(&pin_num);
+        let mut pins = self.pins.lock().unwrap();
+        let pin = match pins.get_mut(&pin_num) {
+            Some(pin) if !pin.settings.output => pin,
+            _ => return Err(pin_not_input()),
+        };
+        let Some(handle) = pin.stop_watcher() else {
+            return Ok(());
+        };
+        pin.settings.edges = false;
+        pin.settings.debounce = None;
+        let reconfigured = pin.request.reconfigure(&pin.settings).map_err(|e| self.line_error(pin_num, e));
+        drop(pins);
+        let _ = py.allow_threads(|| handle.join());
+        reconfigured
+    }
+
+    /// Releases a line, which keeps its last state.
+    ///
+    /// Example usage:
+    /// ```chip.reset_pin(27)```
+    ///
+    fn reset_pin(&self, py: Python, pin_num: u32) {
+        self.callbacks.lock().unwrap().remove(&pin_num);
+        let pin = self.pins.lock().unwrap().remove(&pin_num);
+        // The thread of the line holds the request, the line is released once it is joined
+        if let Some(handle) = pin.and_then(|mut pin| pin.stop_watcher()) {
+            let _ = py.allow_threads(|| handle.join());
+        }
+    }
+
+    /// Releases the lines set up through this manager.
+    ///
+    /// Example usage:
+    /// ```chip.cleanup()```
+    ///
+    fn cleanup(&self, py: Python) {
+        let pins: Vec<u32> = self.pins.lock().unwrap().keys().copied().collect();
+        for pin_num in pins {
+            self.reset_pin(py, pin_num);
+        }
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __exit__(&self, py: Python, _args: &Bound<'_, PyTuple>) -> bool {
+        self.cleanup(py);
+        false
+    }
+}
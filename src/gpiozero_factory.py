@@ -0,0 +1,167 @@
+"""
+gpiozero pin factory backed by the GPIOManager, loaded by gpio_manager.gpiozero the first time it is used.
+
+Select it with the environment variable GPIOZERO_PIN_FACTORY=gpio_manager, or in code with
+Device.pin_factory = GPIOManagerFactory().
+"""
+from gpiozero.pins.local import LocalPiFactory, LocalPiPin
+from gpiozero.exc import PinInvalidFunction, PinSetInput, PinFixedPull, PinInvalidPull, PinInvalidBounce, PinInvalidEdges
+
+import gpio_manager
+from gpio_manager import InternPullResistorState, PinState, TriggerEdge
+
+
+class GPIOManagerFactory(LocalPiFactory):
+    """Pin factory using the GPIOManager for pins, edge detection and software PWM."""
+
+    def __init__(self):
+        super().__init__()
+        self.pin_class = GPIOManagerPin
+        self._manager = gpio_manager.GPIOManager()
+
+
+class GPIOManagerPin(LocalPiPin):
+    """A single pin of the GPIOManagerFactory."""
+
+    GPIO_PULL_UPS = {
+        'up': InternPullResistorState.PULLUP,
+        'down': InternPullResistorState.PULLDOWN,
+        'floating': InternPullResistorState.EXTERNAL,
+    }
+
+    GPIO_EDGES = {
+        'both': TriggerEdge.BOTH,
+        'rising': TriggerEdge.RISING,
+        'falling': TriggerEdge.FALLING,
+    }
+
+    def __init__(self, factory, info):
+        super().__init__(factory, info)
+        self._manager = factory._manager
+        self._gpio = getattr(self, '_number', None)
+        if self._gpio is None:
+            self._gpio = int(info.name[4:])
+        self._pull = info.pull or 'floating'
+        self._function = 'input'
+        self._state = False
+        self._frequency = None
+        self._duty_cycle = 0.0
+        self._bounce = None
+        self._edges = 'both'
+        self._manager.add_input_pin(self._gpio, self.GPIO_PULL_UPS[self._pull])
+
+    def close(self):
+        self.frequency = None
+        self.when_changed = None
+        self._manager.reset_pin(self._gpio)
+
+    def _get_function(self):
+        return self._function
+
+    def _set_function(self, value):
+        if value not in ('input', 'output'):
+            raise PinInvalidFunction(f'invalid function "{value}" for pin {self!r}')
+        if value == self._function:
+            return
+        self.frequency = None
+        self._manager.reset_pin(self._gpio)
+        if value == 'input':
+            self._manager.add_input_pin(self._gpio, self.GPIO_PULL_UPS[self._pull])
+        else:
+            self._state = False
+            self._manager.add_output_pin(self._gpio, PinState.LOW)
+        self._function = value
+
+    def _get_state(self):
+        if self._frequency is not None:
+            return self._duty_cycle
+        if self._function == 'output':
+            return self._state
+        return self._manager.get_pin(self._gpio) == PinState.HIGH
+
+    def _set_state(self, value):
+        if self._frequency is not None:
+            self._duty_cycle = float(value)
+            self._manager.set_pwm_duty_cycle(self._gpio, self._duty_cycle * 100)
+        elif self._function == 'input':
+            raise PinSetInput(f'cannot set state of pin {self!r}')
+        else:
+            self._state = bool(value)
+            self._manager.set_output_pin(self._gpio, PinState.HIGH if value else PinState.LOW)
+
+    def _get_pull(self):
+        return self._pull
+
+    def _set_pull(self, value):
+        if self._function != 'input':
+            raise PinFixedPull(f'cannot set pull on non-input pin {self!r}')
+        if value != 'up' and self.info.pull == 'up':
+            raise PinFixedPull(f'{self!r} has a physical pull-up resistor')
+        if value not in self.GPIO_PULL_UPS:
+            raise PinInvalidPull(f'invalid pull "{value}" for pin {self!r}')
+        when_changed = self.when_changed
+        self.when_changed = None
+        self._manager.reset_pin(self._gpio)
+        self._manager.add_input_pin(self._gpio, self.GPIO_PULL_UPS[value])
+        self._pull = value
+        self.when_changed = when_changed
+
+    def _get_frequency(self):
+        return self._frequency
+
+    def _set_frequency(self, value):
+        if value == self._frequency:
+            return
+        if value is None:
+            self._manager.reset_pin(self._gpio)
+            self._manager.add_output_pin(self._gpio, PinState.LOW)
+            self._state = False
+            self._function = 'output'
+        elif self._frequency is None:
+            self._manager.reset_pin(self._gpio)
+            self._duty_cycle = 0.0
+            self._manager.setup_pwm(self._gpio, frequency_hz=value, duty_cycle=0.0)
+            self._manager.start_pwm(self._gpio)
+            self._function = 'output'
+        else:
+            self._manager.set_pwm_frequency(self._gpio, value)
+        self._frequency = value
+
+    def _get_bounce(self):
+        return self._bounce
+
+    def _set_bounce(self, value):
+        if value is not None and value < 0:
+            raise PinInvalidBounce('bounce must be 0 or greater')
+        self._bounce = value
+        self._restart_event_detect()
+
+    def _get_edges(self):
+        return self._edges
+
+    def _set_edges(self, value):
+        if value not in self.GPIO_EDGES and value != 'none':
+            raise PinInvalidEdges(f'invalid edge specification "{value}" for pin {self!r}')
+        self._edges = value
+        self._restart_event_detect()
+
+    def _restart_event_detect(self):
+        if self.when_changed is not None:
+            self._disable_event_detect()
+            self._enable_event_detect()
+
+    def _changed(self):
+        self._call_when_changed()
+
+    def _enable_event_detect(self):
+        if self._edges == 'none':
+            return
+        bounce_ms = 0.0 if self._bounce is None else self._bounce * 1000
+        self._manager.assign_callback(self._gpio, self._changed, self.GPIO_EDGES[self._edges], bounce_ms)
+
+    def _disable_event_detect(self):
+        try:
+            self._manager.unassign_callbacks(self._gpio)
+        except ValueError:
+            # The pin is no longer an input
+            pass
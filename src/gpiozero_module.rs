@@ -0,0 +1,33 @@
+use pyo3::prelude::*;
+use pyo3::ffi::c_str;
+use pyo3::sync::GILOnceCell;
+use pyo3::{pyfunction, PyObject, PyResult, Python};
+
+
+// The factory subclasses the gpiozero pin classes, so it is written in Python and only loaded once it is used
+static FACTORY_MODULE: GILOnceCell<Py<PyModule>> = GILOnceCell::new();
+
+
+#[pyfunction]
+#[pyo3(signature = (name))]
+/// Loads the pin factory the first time one of its classes is accessed, so gpiozero is only imported when it is used.
+fn __getattr__(py: Python, name: &str) -> PyResult<PyObject> {
+    let module = FACTORY_MODULE.get_or_try_init(py, || {
+        PyModule::from_code(py, c_str!(include_str!("gpiozero_factory.py")), c_str!("gpiozero_factory.py"), c_str!("gpio_manager.gpiozero_factory"))
+            .map(|module| module.unbind())
+    })?;
+    Ok(module.bind(py).getattr(name)?.unbind())
+}
+
+
+/// Adds the `gpiozero` module providing `GPIOManagerFactory`, and registers it in `sys.modules` so gpiozero can load
+/// the factory through its `gpiozero_pin_factories` entry point.
+pub fn add_gpiozero_module(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = parent.py();
+    let gpiozero = PyModule::new(py, "gpiozero")?;
+    gpiozero.gil_used(false)?;
+    gpiozero.add_function(wrap_pyfunction!(__getattr__, &gpiozero)?)?;
+    parent.add_submodule(&gpiozero)?;
+    py.import("sys")?.getattr("modules")?.set_item("gpio_manager.gpiozero", &gpiozero)?;
+    Ok(())
+}
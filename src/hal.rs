@@ -0,0 +1,421 @@
+//! The Rust API of the library, returning a [`GpioError`] where the Python bindings raise an exception.
+//!
+//! [`PwmManager`] is also the core of the PWMManager of the bindings. [`PinManager`] and [`I2cBus`] are separate from
+//! the GPIOManager and the I2CManager, which keep their own pins and buses, so a pin or bus can't be shared between
+//! the Rust API and the bindings in one process.
+//!
+//! Depend on the crate with `default-features = false` to build it without PyO3:
+//!
+//! ```toml
+//! gpio_manager = { version = "3", default-features = false }
+//! ```
+//!
+//! Example usage:
+//!
+//! ```no_run
+//! use gpio_manager::hal::{InternPullResistorState, LogicLevel, OutputMode, PinManager, PinState};
+//!
+//! let mut manager = PinManager::new();
+//! manager.add_output_pin(25, PinState::LOW, LogicLevel::HIGH, OutputMode::PUSH_PULL)?;
+//! manager.add_input_pin(18, InternPullResistorState::AUTO, LogicLevel::HIGH)?;
+//! if manager.get_pin(18)? == PinState::HIGH {
+//!     manager.set_output_pin(25, PinState::HIGH)?;
+//! }
+//! # Ok::<(), gpio_manager::GpioError>(())
+//! ```
+use crate::backend::{self, InputPin, OutputPin, Pwm};
+use crate::pinctrl::{self, PinctrlError};
+use crate::{check_pwm_values, mock_backend};
+use rppal::gpio::{Bias, Trigger};
+use rppal::i2c::I2c;
+use rppal::system::{DeviceInfo, Model};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+pub use crate::error::{GpioError, Result};
+pub use crate::{InternPullResistorState, LogicLevel, OutputMode, PinState, TriggerEdge};
+
+
+// GPIO pin each hardware PWM channel is routed to, the same on every board
+pub(crate) const PWM_CHANNEL_PINS: [(u8, u8); 2] = [(0, 18), (1, 19)];
+
+
+/// Routes the pin of a hardware PWM channel to the channel, with a pull down. Other backends than rppal leave the
+/// routing of the pin to the device tree.
+pub(crate) fn route_pwm_pin(pin_num: u8) -> std::result::Result<(), PinctrlError> {
+    if !backend::is_rppal() {
+        return Ok(());
+    }
+    // The channels are alternate function a3 on the Raspberry Pi 5 and a5 on the earlier models
+    let alt = match DeviceInfo::new().map(|info| info.model()) {
+        Ok(Model::RaspberryPi5) => 3,
+        _ => 5,
+    };
+    pinctrl::set_alt_function(pin_num, alt, Some(Bias::PullDown))
+}
+
+
+/// Selects how the pins are driven, "rppal" (the default), "gpiochip" for the lines of the given character device or
+/// "mock" for simulated pins. Pins taken before the call keep their backend.
+pub fn set_backend(name: &str, chip: &str) -> Result<()> {
+    backend::set_current(backend::open(name, chip)?);
+    Ok(())
+}
+
+
+/// Returns the name of the backend new pins are taken from.
+pub fn current_backend() -> &'static str {
+    backend::current().name()
+}
+
+
+/// Drives the external level of a pin of the mock backend, the edge reaches the callbacks of the pin if it is an input.
+pub fn mock_set_input(pin_num: u8, pin_state: PinState) -> Result<()> {
+    Ok(mock_backend::set_input(pin_num, pin_state == PinState::HIGH)?)
+}
+
+
+/// Returns the level of a pin of the mock backend, the level driven by an output or the external level of an input.
+pub fn mock_get_level(pin_num: u8) -> PinState {
+    if mock_backend::get_level(pin_num) { PinState::HIGH } else { PinState::LOW }
+}
+
+
+struct Input {
+    pin: InputPin,
+    logic_level: LogicLevel,
+}
+
+
+struct Output {
+    pin: OutputPin,
+    logic_level: LogicLevel,
+    output_mode: OutputMode,
+}
+
+
+/// Owns a set of input and output pins, the pins are released when it is dropped.
+#[derive(Default)]
+pub struct PinManager {
+    input_pins: HashMap<u8, Input>,
+    output_pins: HashMap<u8, Output>,
+}
+
+
+impl PinManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn check_free(&self, pin_num: u8) -> Result<()> {
+        if self.input_pins.contains_key(&pin_num) {
+            return Err(GpioError::InvalidArgument("Pin found in input pins (pin is already setup as an input pin)".to_string()));
+        }
+        if self.output_pins.contains_key(&pin_num) {
+            return Err(GpioError::InvalidArgument("Pin found in output pins (pin is already setup as an output pin)".to_string()));
+        }
+        Ok(())
+    }
+
+    fn input(&mut self, pin_num: u8) -> Result<&mut Input> {
+        self.input_pins.get_mut(&pin_num)
+            .ok_or_else(|| GpioError::InvalidArgument("Pin not found in input pins (pin is either output or not setup)".to_string()))
+    }
+
+    /// Sets up a pin as an input, AUTO pulls the pin to its inactive level.
+    pub fn add_input_pin(&mut self, pin_num: u8, pull_resistor_state: InternPullResistorState, logic_level: LogicLevel) -> Result<()> {
+        self.check_free(pin_num)?;
+        let pin = InputPin::new(pin_num, pull_resistor_state.bias(logic_level))?;
+        self.input_pins.insert(pin_num, Input { pin, logic_level });
+        Ok(())
+    }
+
+    /// Sets up a pin as an output in the given state.
+    pub fn add_output_pin(&mut self, pin_num: u8, pin_state: PinState, logic_level: LogicLevel, output_mode: OutputMode) -> Result<()> {
+        self.check_free(pin_num)?;
+        let high = (pin_state == PinState::HIGH) == (logic_level == LogicLevel::HIGH);
+        let mut pin = OutputPin::new(pin_num, output_mode.initial_level(high))?;
+        if output_mode != OutputMode::PUSH_PULL {
+            output_mode.drive(&mut pin, high)?;
+        }
+        self.output_pins.insert(pin_num, Output { pin, logic_level, output_mode });
+        Ok(())
+    }
+
+    /// Sets the state of an output pin.
+    pub fn set_output_pin(&mut self, pin_num: u8, pin_state: PinState) -> Result<()> {
+        let output = self.output_pins.get_mut(&pin_num)
+                         .ok_or_else(|| GpioError::InvalidArgument("Pin not found in output pins (pin is either input or not setup)".to_string()))?;
+        let high = (pin_state == PinState::HIGH) == (output.logic_level == LogicLevel::HIGH);
+        Ok(output.output_mode.drive(&mut output.pin, high)?)
+    }
+
+    /// Reads the state of an input pin.
+    pub fn get_pin(&mut self, pin_num: u8) -> Result<PinState> {
+        let input = self.input(pin_num)?;
+        Ok(if input.pin.is_high() == (input.logic_level == LogicLevel::HIGH) { PinState::HIGH } else { PinState::LOW })
+    }
+
+    /// Calls a function from a thread of the backend on every edge of an input pin, with the edge and the time of the
+    /// edge on CLOCK_MONOTONIC. The edges are those of the logic level of the pin, and the debounce time is filtered by
+    /// the gpiochip character device. A pin has a single callback, assigning one replaces the previous one.
+    pub fn assign_callback(&mut self, pin_num: u8, trigger_edge: TriggerEdge, debounce: Option<Duration>,
+                           mut callback: impl FnMut(TriggerEdge, Duration) + Send + 'static) -> Result<()> {
+        let input = self.input(pin_num)?;
+        let active_high = input.logic_level == LogicLevel::HIGH;
+        input.pin.set_async_interrupt(trigger_edge.trigger(input.logic_level), debounce, move |event| {
+            let edge = if (event.trigger == Trigger::RisingEdge) == active_high { TriggerEdge::RISING } else { TriggerEdge::FALLING };
+            callback(edge, event.timestamp);
+        })?;
+        Ok(())
+    }
+
+    /// Removes the callback of an input pin.
+    pub fn unassign_callback(&mut self, pin_num: u8) -> Result<()> {
+        Ok(self.input(pin_num)?.pin.clear_async_interrupt()?)
+    }
+
+    /// Blocks until an edge of an input pin, returning false if the timeout expired first. Pins with a callback can't
+    /// be waited on.
+    pub fn wait_for_edge(&mut self, pin_num: u8, trigger_edge: TriggerEdge, timeout: Option<Duration>, debounce: Option<Duration>) -> Result<bool> {
+        let input = self.input(pin_num)?;
+        input.pin.set_interrupt(trigger_edge.trigger(input.logic_level), debounce)?;
+        let event = input.pin.poll_interrupt(false, timeout);
+        input.pin.clear_interrupt()?;
+        Ok(event?.is_some())
+    }
+
+    /// Releases a pin, returning it to the state it had before it was set up.
+    pub fn reset_pin(&mut self, pin_num: u8) -> Result<()> {
+        if self.input_pins.remove(&pin_num).is_none() && self.output_pins.remove(&pin_num).is_none() {
+            return Err(GpioError::InvalidArgument(format!("Pin {} is not setup", pin_num)));
+        }
+        Ok(())
+    }
+
+    /// Releases every pin.
+    pub fn cleanup(&mut self) {
+        self.input_pins.clear();
+        self.output_pins.clear();
+    }
+}
+
+
+/// Owns the hardware PWM channels, channel 0 drives GPIO 18 and channel 1 drives GPIO 19. The PWMManager of the Python
+/// bindings is built on it.
+#[derive(Default)]
+pub struct PwmManager {
+    // Shared with the threads ramping the duty cycle of the channels
+    channels: HashMap<u8, Arc<Mutex<Pwm>>>,
+}
+
+
+impl PwmManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn channel(&self, channel_num: u8) -> Result<MutexGuard<'_, Pwm>> {
+        Ok(self.handle(channel_num)?.lock().unwrap())
+    }
+
+    fn handle(&self, channel_num: u8) -> Result<&Arc<Mutex<Pwm>>> {
+        self.channels.get(&channel_num).ok_or_else(|| GpioError::InvalidArgument("PWM channel not initialized".to_string()))
+    }
+
+    /// Returns the GPIO pin the given hardware PWM channel is routed to.
+    pub fn channel_pin(channel_num: u8) -> Result<u8> {
+        PWM_CHANNEL_PINS.iter()
+                        .find(|(channel, _)| *channel == channel_num)
+                        .map(|(_, pin)| *pin)
+                        .ok_or_else(|| GpioError::InvalidArgument("Invalid PWM channel number".to_string()))
+    }
+
+    /// Returns whether a channel is set up.
+    pub fn is_setup(&self, channel_num: u8) -> bool {
+        self.channels.contains_key(&channel_num)
+    }
+
+    /// Returns the channels set up.
+    pub fn channel_nums(&self) -> Vec<u8> {
+        self.channels.keys().copied().collect()
+    }
+
+    /// Sets up a PWM channel, stopped, with the duty cycle from 0 to 100. A LOW logic level inverts the polarity.
+    pub fn setup_pwm_channel(&mut self, channel_num: u8, frequency_hz: f64, duty_cycle: f64, logic_level: LogicLevel) -> Result<()> {
+        check_pwm_values(&Some(frequency_hz), &Some(duty_cycle), &None, &None)?;
+        let pin_num = PwmManager::channel_pin(channel_num)?;
+        if self.is_setup(channel_num) {
+            return Err(GpioError::InvalidArgument("PWM channel already initialized".to_string()));
+        }
+        route_pwm_pin(pin_num)?;
+        self.open_channel(channel_num, frequency_hz, duty_cycle, logic_level)
+    }
+
+    /// Sets up a PWM channel like setup_pwm_channel, without routing its pin.
+    pub(crate) fn open_channel(&mut self, channel_num: u8, frequency_hz: f64, duty_cycle: f64, logic_level: LogicLevel) -> Result<()> {
+        if self.is_setup(channel_num) {
+            return Err(GpioError::InvalidArgument("PWM channel already initialized".to_string()));
+        }
+        let pwm = Pwm::with_frequency(channel_num, frequency_hz, duty_cycle / 100f64, logic_level.polarity(), false)?;
+        self.channels.insert(channel_num, Arc::new(Mutex::new(pwm)));
+        Ok(())
+    }
+
+    pub fn start_pwm_channel(&self, channel_num: u8) -> Result<()> {
+        Ok(self.channel(channel_num)?.enable()?)
+    }
+
+    /// Starts several channels back to back. The sysfs interface has no way to enable channels at once, so they are
+    /// all locked first.
+    pub fn start_pwm_channels(&self, channel_nums: &[u8]) -> Result<()> {
+        let handles = channel_nums.iter()
+                                  .map(|channel_num| {
+                                      self.handle(*channel_num)
+                                          .map_err(|_| GpioError::InvalidArgument(format!("PWM channel {} not initialized", channel_num)))
+                                  })
+                                  .collect::<Result<Vec<_>>>()?;
+        let pwms: Vec<_> = handles.iter().map(|pwm| pwm.lock().unwrap()).collect();
+        for pwm in &pwms {
+            pwm.enable()?;
+        }
+        Ok(())
+    }
+
+    pub fn stop_pwm_channel(&self, channel_num: u8) -> Result<()> {
+        Ok(self.channel(channel_num)?.disable()?)
+    }
+
+    /// Sets the duty cycle of a channel, from 0 to 100.
+    pub fn set_duty_cycle(&self, channel_num: u8, duty_cycle: f64) -> Result<()> {
+        check_pwm_values(&None, &Some(duty_cycle), &None, &None)?;
+        Ok(self.channel(channel_num)?.set_duty_cycle(duty_cycle / 100f64)?)
+    }
+
+    /// Sets the frequency of a channel, keeping its duty cycle.
+    pub fn set_frequency(&self, channel_num: u8, frequency_hz: f64) -> Result<()> {
+        if frequency_hz <= 0f64 {
+            return Err(GpioError::InvalidArgument(format!("Frequency must be greater than 0, The value {} does not meet this condition", frequency_hz)));
+        }
+        let pwm = self.channel(channel_num)?;
+        Ok(pwm.set_frequency(frequency_hz, pwm.duty_cycle()?)?)
+    }
+
+    pub fn set_period(&self, channel_num: u8, period: Duration) -> Result<()> {
+        Ok(self.channel(channel_num)?.set_period(period)?)
+    }
+
+    /// Sets the pulse width of a channel, which can't be longer than its period.
+    pub fn set_pulse_width(&self, channel_num: u8, pulse_width: Duration) -> Result<()> {
+        let pwm = self.channel(channel_num)?;
+        let period = pwm.period()?;
+        if pulse_width > period {
+            return Err(GpioError::InvalidArgument(format!("Pulse width must be less than the period, The value {:?} does not meet this condition \
+                                                           period: {:?}", pulse_width, period)));
+        }
+        Ok(pwm.set_pulse_width(pulse_width)?)
+    }
+
+    /// Changes the polarity of a channel to match a logic level, the duty cycle keeps its meaning.
+    pub fn set_logic_level(&self, channel_num: u8, logic_level: LogicLevel) -> Result<()> {
+        let pwm = self.channel(channel_num)?;
+        // The kernel only accepts a new polarity while the channel is disabled
+        let enabled = pwm.is_enabled()?;
+        if enabled {
+            pwm.disable()?;
+        }
+        pwm.set_polarity(logic_level.polarity())?;
+        if enabled {
+            pwm.enable()?;
+        }
+        Ok(())
+    }
+
+    pub fn get_frequency(&self, channel_num: u8) -> Result<f64> {
+        Ok(self.channel(channel_num)?.frequency()?)
+    }
+
+    /// Returns the duty cycle of a channel, from 0 to 100.
+    pub fn get_duty_cycle(&self, channel_num: u8) -> Result<f64> {
+        Ok(self.channel(channel_num)?.duty_cycle()? * 100f64)
+    }
+
+    pub fn get_period(&self, channel_num: u8) -> Result<Duration> {
+        Ok(self.channel(channel_num)?.period()?)
+    }
+
+    pub fn get_pulse_width(&self, channel_num: u8) -> Result<Duration> {
+        Ok(self.channel(channel_num)?.pulse_width()?)
+    }
+
+    pub fn is_active(&self, channel_num: u8) -> Result<bool> {
+        Ok(self.channel(channel_num)?.is_enabled()?)
+    }
+
+    /// Stops a channel and releases it.
+    pub fn reset_pwm_channel(&mut self, channel_num: u8) -> Result<()> {
+        self.stop_pwm_channel(channel_num)?;
+        self.channels.remove(&channel_num);
+        Ok(())
+    }
+}
+
+
+/// An I2C bus, the address of the device is given with every transfer.
+pub struct I2cBus {
+    i2c: I2c,
+}
+
+
+impl I2cBus {
+    /// Opens /dev/i2c-N.
+    pub fn open(bus: u8) -> Result<Self> {
+        Ok(Self { i2c: I2c::with_bus(bus)? })
+    }
+
+    pub fn bus(&self) -> u8 {
+        self.i2c.bus()
+    }
+
+    fn select(&mut self, addr: u16) -> Result<&mut I2c> {
+        self.i2c.set_slave_address(addr)?;
+        Ok(&mut self.i2c)
+    }
+
+    pub fn write(&mut self, addr: u16, data: &[u8]) -> Result<()> {
+        self.select(addr)?.write(data)?;
+        Ok(())
+    }
+
+    pub fn read(&mut self, addr: u16, length: usize) -> Result<Vec<u8>> {
+        let mut buffer = vec![0u8; length];
+        self.select(addr)?.read(&mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Writes data and reads the reply in a single transaction, with a repeated start in between.
+    pub fn write_read(&mut self, addr: u16, data: &[u8], read_length: usize) -> Result<Vec<u8>> {
+        let mut buffer = vec![0u8; read_length];
+        self.select(addr)?.write_read(data, &mut buffer)?;
+        Ok(buffer)
+    }
+
+    /// Returns the 7 bit addresses from 0x08 to 0x77 that acknowledge. EEPROMs and the addresses some devices only
+    /// answer reads on are probed with a read, the others with a quick write.
+    pub fn scan(&mut self) -> Result<Vec<u16>> {
+        let mut found = Vec::new();
+        for addr in 0x08..=0x77 {
+            let i2c = self.select(addr)?;
+            let responded = if (0x30..=0x37).contains(&addr) || (0x50..=0x5F).contains(&addr) {
+                i2c.read(&mut [0u8; 1]).is_ok()
+            } else {
+                i2c.smbus_quick_command(false).is_ok()
+            };
+            if responded {
+                found.push(addr);
+            }
+        }
+        Ok(found)
+    }
+}
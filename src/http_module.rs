@@ -0,0 +1,185 @@
+use crate::event_server::{reply_to, run_command};
+use crate::gpio_module::GPIOManager;
+use crate::{log_module, PinState};
+use pyo3::{pyclass, pyfunction, pymethods, PyErr, PyResult};
+use serde_json::{json, Map, Value};
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+
+// How often the server thread checks for being stopped
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+// Bodies are small JSON objects, larger bodies are rejected
+const MAX_BODY: u64 = 64 * 1024;
+
+
+/// Parses the JSON object of a request body, an empty body is an empty object.
+fn read_body(request: &mut Request) -> Result<Map<String, Value>, String> {
+    let mut body = String::new();
+    request.as_reader()
+           .take(MAX_BODY)
+           .read_to_string(&mut body)
+           .map_err(|e| format!("Unable to read the body: {}", e))?;
+    if body.trim().is_empty() {
+        return Ok(Map::new());
+    }
+    match serde_json::from_str(&body) {
+        Ok(Value::Object(body)) => Ok(body),
+        Ok(_) => Err("The body must be a JSON object".to_string()),
+        Err(e) => Err(format!("Invalid JSON: {}", e)),
+    }
+}
+
+
+/// Returns the states of the input pins, by pin number.
+fn input_states(gpio_manager: &GPIOManager) -> PyResult<Value> {
+    let mut states = Map::new();
+    for pin_num in gpio_manager.input_pin_numbers() {
+        states.insert(pin_num.to_string(), Value::Bool(gpio_manager.get_pin(pin_num)? == PinState::HIGH));
+    }
+    Ok(Value::Object(states))
+}
+
+
+/// Turns a request into the command of the event server it stands for, None when no endpoint matches.
+fn route(method: &Method, path: &str, mut body: Map<String, Value>) -> Option<Value> {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let number = |segment: &str| segment.parse::<u64>().ok().map(Value::from);
+    let cmd = match (method, segments.as_slice()) {
+        (Method::Get, ["pins", pin]) => {
+            body.insert("pin".to_string(), number(pin)?);
+            "get"
+        }
+        (Method::Put | Method::Post, ["pins", pin]) => {
+            body.insert("pin".to_string(), number(pin)?);
+            "set"
+        }
+        (Method::Put | Method::Post, ["pins", pin, "pwm"]) => {
+            body.insert("pin".to_string(), number(pin)?);
+            "duty"
+        }
+        (Method::Post, ["i2c", bus, addr]) => {
+            body.insert("bus".to_string(), number(bus)?);
+            let addr = match addr.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16).ok().map(Value::from)?,
+                None => number(addr)?,
+            };
+            body.insert("addr".to_string(), addr);
+            "i2c"
+        }
+        _ => return None,
+    };
+    body.insert("cmd".to_string(), cmd.into());
+    Some(Value::Object(body))
+}
+
+
+/// Answers a request with the reply format of the event server, 400 for failed commands and 404 for unknown
+/// endpoints.
+fn respond(gpio_manager: &GPIOManager, mut request: Request) {
+    let path = request.url().split('?').next().unwrap_or_default().to_string();
+    let method = request.method().clone();
+    let (status, reply) = match read_body(&mut request) {
+        Err(e) => (400, json!({"ok": false, "error": e}).to_string()),
+        Ok(_) if method == Method::Get && path.trim_end_matches('/') == "/pins" => {
+            let result = input_states(gpio_manager);
+            (if result.is_ok() { 200 } else { 400 }, reply_to(&Value::Null, result))
+        }
+        Ok(body) => match route(&method, &path, body) {
+            Some(command) => {
+                let result = run_command(gpio_manager, &command);
+                (if result.is_ok() { 200 } else { 400 }, reply_to(&Value::Null, result))
+            }
+            None => (404, json!({"ok": false, "error": format!("No endpoint for {} {}", method, path)}).to_string()),
+        },
+    };
+    let response = Response::from_string(reply)
+        .with_status_code(status)
+        .with_header("Content-Type: application/json".parse::<Header>().unwrap());
+    if let Err(e) = request.respond(response) {
+        log_module::debug(&format!("Unable to answer an HTTP request: {}", e));
+    }
+}
+
+
+#[pyclass]
+/// Handle of the HTTP server started with serve_http.
+pub struct HTTPServer {
+    port: u16,
+    stopped: Arc<AtomicBool>,
+}
+
+
+#[pymethods]
+impl HTTPServer {
+    /// The port the server listens on, useful when it was started on port 0.
+    #[getter]
+    fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Stops the server.
+    ///
+    /// Example usage:
+    /// ```server.stop()```
+    ///
+    fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (port, host = "127.0.0.1"))]
+/// Starts an HTTP server exposing a JSON API for reading the inputs, setting the outputs, adjusting PWM and performing
+/// I2C transactions. It runs in a Rust thread, so it answers while Python code is blocked.
+///
+/// The endpoints are:
+/// - ```GET /pins```: The logical states of the input pins, as ```{"17": true}```.
+/// - ```GET /pins/<pin>```: The logical state of an input pin.
+/// - ```PUT /pins/<pin>``` with ```{"state": true}```: Sets an output pin.
+/// - ```PUT /pins/<pin>/pwm``` with ```{"duty_cycle": 50}```: Sets the duty cycle of a PWM pin.
+/// - ```POST /i2c/<bus>/<addr>``` with ```{"write": [0], "read": 2}```: Writes the bytes to an I2C device and reads
+///   its response in one transaction, the address is decimal or hexadecimal with 0x.
+///
+/// Every endpoint answers with ```{"ok": true, "result": ...}```, or with ```{"ok": false, "error": "..."}``` and the
+/// status 400 when the command fails.
+///
+/// Parameters:
+/// - ```port``` (int): The port to listen on, 0 picks a free port.
+/// - ```host``` (str): The address to listen on (default is "127.0.0.1", local clients only).
+///
+/// Returns:
+/// - ```HTTPServer```: The handle of the server.
+///
+/// The server has no authentication, only expose it on trusted networks.
+///
+/// Example usage:
+/// ```python
+/// manager.add_output_pin(27)
+/// server = gpio_manager.serve_http(8080)
+/// # curl -X PUT -d '{"state": true}' http://localhost:8080/pins/27
+/// ```
+pub fn serve_http(port: u16, host: &str) -> PyResult<HTTPServer> {
+    let server = Server::http((host, port))
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyOSError, _>(format!("Unable to listen on {}:{}: {}", host, port, e)))?;
+    let port = server.server_addr().to_ip().map(|address| address.port()).unwrap_or(port);
+    let stopped = Arc::new(AtomicBool::new(false));
+
+    let thread_stopped = Arc::clone(&stopped);
+    thread::spawn(move || {
+        let gpio_manager = GPIOManager::new_rust_reference();
+        while !thread_stopped.load(Ordering::SeqCst) {
+            match server.recv_timeout(POLL_INTERVAL) {
+                Ok(Some(request)) => respond(&gpio_manager, request),
+                Ok(None) => {}
+                Err(e) => log_module::warning(&format!("The HTTP server failed to receive a request: {}", e)),
+            }
+        }
+    });
+    Ok(HTTPServer { port, stopped })
+}
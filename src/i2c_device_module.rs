@@ -0,0 +1,296 @@
+use crate::i2c_module::{extract_bytes, extract_segments, with_buses, I2CBuses};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use pyo3::{pyclass, pymethods, PyErr, PyResult, Python};
+use rppal::i2c::I2c;
+use std::sync::{Arc, Mutex};
+
+
+#[pyclass]
+#[derive(Clone)]
+/// I2CDevice provides methods to communicate with a single device, created with `I2CManager.device`.
+///
+/// The slave address is only selected again when another device was used on the bus in the meantime.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// i2c_manager = gpio_manager.I2CManager()
+/// i2c_manager.open(bus=1)
+/// sensor = i2c_manager.device(0x48)
+/// sensor.write_register(0x01, 0x60)
+/// temperature = sensor.read_word(0x00, big_endian=True)
+/// ```
+pub struct I2CDevice {
+    i2c: Arc<Mutex<I2CBuses>>,
+    bus: u8,
+    addr: u16,
+}
+
+
+impl I2CDevice {
+    pub(crate) fn new(i2c: Arc<Mutex<I2CBuses>>, bus: u8, addr: u16) -> Self {
+        Self { i2c, bus, addr }
+    }
+
+    /// Runs the operation on the device, retrying it according to the retry policy of the I2CManager.
+    fn with_i2c<T: Send>(&self, action: &str, mut op: impl FnMut(&mut I2c) -> rppal::i2c::Result<T> + Send) -> PyResult<T> {
+        with_buses(&self.i2c, |i2c_lock| {
+            let retry = i2c_lock.retry_policy();
+            let i2c = i2c_lock.select(Some(self.bus), self.addr)?;
+            retry.run(|| op(i2c))
+                 .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to {}: {:?}", action, e)))
+        })
+    }
+
+    /// Reads consecutive registers of the device, starting at the given register.
+    pub(crate) fn read_block(&self, register: u8, length: usize) -> PyResult<Vec<u8>> {
+        let mut buf = vec![0u8; length];
+        self.with_i2c("read registers", |i2c| i2c.write_read(&[register], &mut buf))?;
+        Ok(buf)
+    }
+
+    /// Writes consecutive registers of the device, starting at the given register.
+    pub(crate) fn write_block(&self, register: u8, data: &[u8]) -> PyResult<()> {
+        let mut buf = vec![register];
+        buf.extend_from_slice(data);
+        self.with_i2c("write registers", |i2c| i2c.write(&buf))?;
+        Ok(())
+    }
+}
+
+
+#[pymethods]
+impl I2CDevice {
+    /// Reads data from the device.
+    ///
+    /// Parameters:
+    /// - `length` (int): The number of bytes to read.
+    ///
+    /// Returns:
+    /// - `bytes`: The data read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// data = device.read(4)
+    /// ```
+    #[pyo3(signature = (length))]
+    fn read<'py>(&self, py: Python<'py>, length: usize) -> PyResult<Bound<'py, PyBytes>> {
+        let mut buf = vec![0u8; length];
+        self.with_i2c("read data", |i2c| i2c.read(&mut buf))?;
+        Ok(PyBytes::new(py, &buf))
+    }
+
+    /// Writes data to the device.
+    ///
+    /// Parameters:
+    /// - `data` (bytes | bytearray | memoryview | list[int]): The data to write.
+    ///
+    /// Example usage:
+    /// ```python
+    /// device.write(b'\x01\x02')
+    /// ```
+    #[pyo3(signature = (data))]
+    fn write(&self, data: &Bound<'_, PyAny>) -> PyResult<()> {
+        let data = extract_bytes(data)?;
+        self.with_i2c("write data", |i2c| i2c.write(&data))?;
+        Ok(())
+    }
+
+    /// Reads a single byte from the device.
+    ///
+    /// Returns:
+    /// - `int`: The byte read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// value = device.read_byte()
+    /// ```
+    fn read_byte(&self) -> PyResult<u8> {
+        let mut buf = [0u8; 1];
+        self.with_i2c("read byte", |i2c| i2c.read(&mut buf))?;
+        Ok(buf[0])
+    }
+
+    /// Writes a single byte to the device.
+    ///
+    /// Parameters:
+    /// - `value` (int): The byte to write.
+    ///
+    /// Example usage:
+    /// ```python
+    /// device.write_byte(0xFF)
+    /// ```
+    #[pyo3(signature = (value))]
+    fn write_byte(&self, value: u8) -> PyResult<()> {
+        self.with_i2c("write byte", |i2c| i2c.write(&[value]))?;
+        Ok(())
+    }
+
+    /// Reads an 8 bit register of the device.
+    ///
+    /// Parameters:
+    /// - `register` (int): The register to read.
+    ///
+    /// Returns:
+    /// - `int`: The value of the register.
+    ///
+    /// Example usage:
+    /// ```python
+    /// value = device.read_register(0x0F)
+    /// ```
+    #[pyo3(signature = (register))]
+    fn read_register(&self, register: u8) -> PyResult<u8> {
+        let mut buf = [0u8; 1];
+        self.with_i2c("read register", |i2c| i2c.write_read(&[register], &mut buf))?;
+        Ok(buf[0])
+    }
+
+    /// Writes an 8 bit register of the device.
+    ///
+    /// Parameters:
+    /// - `register` (int): The register to write.
+    /// - `value` (int): The value to write.
+    ///
+    /// Example usage:
+    /// ```python
+    /// device.write_register(0x20, 0x47)
+    /// ```
+    #[pyo3(signature = (register, value))]
+    fn write_register(&self, register: u8, value: u8) -> PyResult<()> {
+        self.with_i2c("write register", |i2c| i2c.write(&[register, value]))?;
+        Ok(())
+    }
+
+    /// Reads consecutive registers of the device, starting at the given register.
+    ///
+    /// Parameters:
+    /// - `register` (int): The first register to read.
+    /// - `length` (int): The number of bytes to read.
+    ///
+    /// Returns:
+    /// - `bytes`: The data read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// data = device.read_registers(0x28, 6)
+    /// ```
+    #[pyo3(signature = (register, length))]
+    fn read_registers<'py>(&self, py: Python<'py>, register: u8, length: usize) -> PyResult<Bound<'py, PyBytes>> {
+        let buf = self.read_block(register, length)?;
+        Ok(PyBytes::new(py, &buf))
+    }
+
+    /// Writes consecutive registers of the device, starting at the given register.
+    ///
+    /// Parameters:
+    /// - `register` (int): The first register to write.
+    /// - `data` (bytes | bytearray | memoryview | list[int]): The data to write.
+    ///
+    /// Example usage:
+    /// ```python
+    /// device.write_registers(0x20, b'\x47\x00')
+    /// ```
+    #[pyo3(signature = (register, data))]
+    fn write_registers(&self, register: u8, data: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.write_block(register, &extract_bytes(data)?)
+    }
+
+    /// Reads a 16 bit word from a register of the device using a single SMBus transaction.
+    ///
+    /// Parameters:
+    /// - `register` (int): The register to read.
+    /// - `big_endian` (bool): Treat the high byte as the first byte on the bus, SMBus devices send the low byte first
+    ///   (default is False).
+    ///
+    /// Returns:
+    /// - `int`: The word read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// value = device.read_word(0x00, big_endian=True)
+    /// ```
+    #[pyo3(signature = (register, big_endian = false))]
+    fn read_word(&self, register: u8, big_endian: bool) -> PyResult<u16> {
+        self.with_i2c("read word", |i2c| {
+            if big_endian {
+                i2c.smbus_read_word_swapped(register)
+            } else {
+                i2c.smbus_read_word(register)
+            }
+        })
+    }
+
+    /// Writes a 16 bit word to a register of the device using a single SMBus transaction.
+    ///
+    /// Parameters:
+    /// - `register` (int): The register to write.
+    /// - `value` (int): The word to write.
+    /// - `big_endian` (bool): Send the high byte first, SMBus devices expect the low byte first (default is False).
+    ///
+    /// Example usage:
+    /// ```python
+    /// device.write_word(0x01, 0x6080, big_endian=True)
+    /// ```
+    #[pyo3(signature = (register, value, big_endian = false))]
+    fn write_word(&self, register: u8, value: u16, big_endian: bool) -> PyResult<()> {
+        self.with_i2c("write word", |i2c| {
+            if big_endian {
+                i2c.smbus_write_word_swapped(register, value)
+            } else {
+                i2c.smbus_write_word(register, value)
+            }
+        })
+    }
+
+    /// Performs a write followed by a read operation.
+    ///
+    /// Parameters:
+    /// - `write_data` (bytes | bytearray | memoryview | list[int]): The data to write.
+    /// - `read_length` (int): The number of bytes to read.
+    ///
+    /// Returns:
+    /// - `bytes`: The data read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// data = device.write_read(b'\x00', 2)
+    /// ```
+    #[pyo3(signature = (write_data, read_length))]
+    fn write_read<'py>(&self, py: Python<'py>, write_data: &Bound<'py, PyAny>, read_length: usize) -> PyResult<Bound<'py, PyBytes>> {
+        let write_data = extract_bytes(write_data)?;
+        let mut buf = vec![0u8; read_length];
+        self.with_i2c("write data", |i2c| i2c.write_read(&write_data, &mut buf))?;
+        Ok(PyBytes::new(py, &buf))
+    }
+
+    /// Performs a combined transaction, the segments are sent with a repeated start between them and a single stop at
+    /// the end.
+    ///
+    /// Parameters:
+    /// - `segments` (list[I2CWrite | I2CRead]): The segments of the transaction, up to 42 segments can be used.
+    ///
+    /// Returns:
+    /// - `list[bytes]`: The data read by every read segment, in order.
+    ///
+    /// Example usage:
+    /// ```python
+    /// [data] = device.transaction([gpio_manager.I2CWrite(b'\x00\x10'), gpio_manager.I2CRead(16)])
+    /// ```
+    #[pyo3(signature = (segments))]
+    fn transaction<'py>(&self, py: Python<'py>, segments: Vec<Bound<'py, PyAny>>) -> PyResult<Vec<Bound<'py, PyBytes>>> {
+        let segments = extract_segments(&segments)?;
+        let data = with_buses(&self.i2c, |i2c_lock| i2c_lock.transaction(Some(self.bus), self.addr, &segments))?;
+        Ok(data.iter().map(|buf| PyBytes::new(py, buf)).collect())
+    }
+
+    /// Returns the I2C address of the device.
+    fn get_address(&self) -> u16 {
+        self.addr
+    }
+
+    /// Returns the I2C bus the device is connected to.
+    fn get_bus(&self) -> u8 {
+        self.bus
+    }
+}
@@ -0,0 +1,170 @@
+use libc::{c_int, c_ulong, ioctl};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::unix::io::AsRawFd;
+
+
+// Requests and constants from linux/i2c-dev.h and linux/i2c.h
+const I2C_SLAVE: c_ulong = 0x0703;
+const I2C_TENBIT: c_ulong = 0x0704;
+const I2C_RDWR: c_ulong = 0x0707;
+const I2C_PEC: c_ulong = 0x0708;
+const I2C_SMBUS: c_ulong = 0x0720;
+
+const I2C_SMBUS_WRITE: u8 = 0;
+const I2C_SMBUS_BLOCK_PROC_CALL: u32 = 7;
+const I2C_SMBUS_BLOCK_MAX: usize = 32;
+
+const I2C_M_RD: u16 = 0x0001;
+const I2C_M_TEN: u16 = 0x0010;
+const I2C_RDWR_IOCTL_MAX_MSGS: usize = 42;
+
+
+/// Data buffer of an SMBus transfer, the first byte holds the block length.
+#[repr(C)]
+struct SmbusData {
+    block: [u8; I2C_SMBUS_BLOCK_MAX + 2],
+}
+
+
+#[repr(C)]
+struct SmbusIoctlData {
+    read_write: u8,
+    command: u8,
+    size: u32,
+    data: *mut SmbusData,
+}
+
+
+#[repr(C)]
+struct I2cMsg {
+    addr: u16,
+    flags: u16,
+    len: u16,
+    buf: *mut u8,
+}
+
+
+#[repr(C)]
+struct I2cRdwrIoctlData {
+    msgs: *mut I2cMsg,
+    nmsgs: u32,
+}
+
+
+/// A single segment of a combined transfer.
+pub enum Segment {
+    Write(Vec<u8>),
+    Read(usize),
+}
+
+
+/// A message of a combined transfer, read messages are filled with the data received.
+pub struct Message {
+    pub addr: u16,
+    pub read: bool,
+    pub buf: Vec<u8>,
+}
+
+
+/// A raw handle to an I2C character device, used for the transfers rppal doesn't expose.
+pub struct I2cDev {
+    file: File,
+}
+
+
+impl I2cDev {
+    /// Opens the character device of the given bus.
+    pub fn open(bus: u8) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(format!("/dev/i2c-{}", bus))?;
+        Ok(Self { file })
+    }
+
+    /// Selects the slave address, addressing mode and packet error checking mode used by SMBus transfers.
+    pub fn set_slave(&self, addr: u16, addr_10bit: bool, pec: bool) -> io::Result<()> {
+        self.request(I2C_TENBIT, addr_10bit as c_ulong)?;
+        // Force is not used, a device claimed by a kernel driver is reported as busy
+        self.request(I2C_SLAVE, addr as c_ulong)?;
+        self.request(I2C_PEC, pec as c_ulong)
+    }
+
+    fn request(&self, request: c_ulong, value: c_ulong) -> io::Result<()> {
+        if unsafe { ioctl(self.file.as_raw_fd(), request as _, value) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn smbus(&self, read_write: u8, command: u8, size: u32, data: &mut SmbusData) -> io::Result<()> {
+        let mut args = SmbusIoctlData {
+            read_write,
+            command,
+            size,
+            data,
+        };
+        let result: c_int = unsafe { ioctl(self.file.as_raw_fd(), I2C_SMBUS as _, &mut args) };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Sends a block of up to 32 bytes and reads back the block the device responds with.
+    pub fn smbus_block_process_call(&self, command: u8, data: &[u8]) -> io::Result<Vec<u8>> {
+        if data.len() > I2C_SMBUS_BLOCK_MAX {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("SMBus blocks are limited to {} bytes", I2C_SMBUS_BLOCK_MAX)));
+        }
+        let mut buf = SmbusData { block: [0u8; I2C_SMBUS_BLOCK_MAX + 2] };
+        buf.block[0] = data.len() as u8;
+        buf.block[1..=data.len()].copy_from_slice(data);
+        self.smbus(I2C_SMBUS_WRITE, command, I2C_SMBUS_BLOCK_PROC_CALL, &mut buf)?;
+
+        // The kernel replaces the buffer with the response, clamp the length in case the device sent garbage
+        let length = (buf.block[0] as usize).min(I2C_SMBUS_BLOCK_MAX);
+        Ok(buf.block[1..=length].to_vec())
+    }
+
+    /// Performs the segments as a single combined transfer, with a repeated start between segments and a single stop
+    /// at the end. Returns the data of every read segment in order.
+    pub fn transfer(&self, addr: u16, addr_10bit: bool, segments: &[Segment]) -> io::Result<Vec<Vec<u8>>> {
+        let mut messages: Vec<Message> = segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Write(data) => Message { addr, read: false, buf: data.clone() },
+                Segment::Read(length) => Message { addr, read: true, buf: vec![0u8; *length] },
+            })
+            .collect();
+        self.rdwr(addr_10bit, &mut messages)?;
+        Ok(messages.into_iter().filter(|message| message.read).map(|message| message.buf).collect())
+    }
+
+    /// Performs the messages as a single combined transfer, filling the buffers of the read messages. Every message
+    /// can address a different device.
+    pub fn rdwr(&self, addr_10bit: bool, messages: &mut [Message]) -> io::Result<()> {
+        if messages.is_empty() || messages.len() > I2C_RDWR_IOCTL_MAX_MSGS {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("A transfer must have between 1 and {} segments", I2C_RDWR_IOCTL_MAX_MSGS)));
+        }
+        if messages.iter().any(|message| message.buf.len() > u16::MAX as usize) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Segments are limited to {} bytes", u16::MAX)));
+        }
+
+        let addr_flag = if addr_10bit { I2C_M_TEN } else { 0 };
+        let mut msgs: Vec<I2cMsg> = messages
+            .iter_mut()
+            .map(|message| I2cMsg {
+                addr: message.addr,
+                flags: addr_flag | if message.read { I2C_M_RD } else { 0 },
+                len: message.buf.len() as u16,
+                buf: message.buf.as_mut_ptr(),
+            })
+            .collect();
+        let mut args = I2cRdwrIoctlData {
+            msgs: msgs.as_mut_ptr(),
+            nmsgs: msgs.len() as u32,
+        };
+        if unsafe { ioctl(self.file.as_raw_fd(), I2C_RDWR as _, &mut args) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
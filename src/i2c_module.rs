@@ -1,9 +1,401 @@
 use once_cell::sync::Lazy;
+use pyo3::buffer::PyBuffer;
 use pyo3::prelude::*;
-use pyo3::types::PyBytes;
-use pyo3::{pyclass, pymethods, Py, PyErr, PyResult, Python};
+use pyo3::types::{PyBytes, PyTuple};
+use pyo3::{pyclass, pymethods, Py, PyErr, PyObject, PyResult, Python};
+use rppal::gpio::{Gpio, IoPin, Mode};
 use rppal::i2c::I2c;
+use crate::board_module::Peripheral;
+use crate::i2c_device_module::I2CDevice;
+use crate::i2c_ioctl::{I2cDev, Segment};
+use crate::{log_module, metrics, ms_to_duration_checked, overlay_module, release_gil};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::thread;
+use std::thread::{sleep, JoinHandle};
+use std::time::{Duration, Instant};
+
+
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+/// Enum representing how an address is probed when scanning the I2C bus.
+pub enum I2CProbeMode {
+    AUTO,
+    QUICK_WRITE,
+    READ,
+}
+
+
+#[pyclass]
+/// A write segment of a combined I2C transaction.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// segment = gpio_manager.I2CWrite(b'\x00\x10')
+/// ```
+pub struct I2CWrite {
+    data: Vec<u8>,
+}
+
+
+#[pymethods]
+impl I2CWrite {
+    #[new]
+    /// Initializes a new write segment.
+    ///
+    /// Parameters:
+    /// - `data` (bytes | bytearray | memoryview | list[int]): The data to write.
+    #[pyo3(signature = (data))]
+    fn new(data: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Ok(Self { data: extract_bytes(data)?.into_owned() })
+    }
+}
+
+
+#[pyclass]
+/// A read segment of a combined I2C transaction.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// segment = gpio_manager.I2CRead(4)
+/// ```
+pub struct I2CRead {
+    length: usize,
+}
+
+
+#[pymethods]
+impl I2CRead {
+    #[new]
+    /// Initializes a new read segment.
+    ///
+    /// Parameters:
+    /// - `length` (int): The number of bytes to read.
+    #[pyo3(signature = (length))]
+    fn new(length: usize) -> Self {
+        Self { length }
+    }
+}
+
+
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+/// Enum representing the kinds of bus errors that can be retried.
+pub enum I2CErrorKind {
+    NACK,
+    ARBITRATION_LOST,
+    TIMEOUT,
+}
+
+
+/// Errors that can be classified by the retry policy.
+pub trait I2CErrorCode {
+    fn errno(&self) -> Option<i32>;
+}
+
+
+impl I2CErrorCode for io::Error {
+    fn errno(&self) -> Option<i32> {
+        self.raw_os_error()
+    }
+}
+
+
+impl I2CErrorCode for rppal::i2c::Error {
+    fn errno(&self) -> Option<i32> {
+        match self {
+            rppal::i2c::Error::Io(e) => e.raw_os_error(),
+            _ => None,
+        }
+    }
+}
+
+
+/// Controls how often failed transfers are retried and which errors are retried.
+#[derive(Clone, Copy)]
+pub struct RetryPolicy {
+    retries: u32,
+    backoff: Duration,
+    nack: bool,
+    arbitration_lost: bool,
+    timeout: bool,
+}
+
+
+impl RetryPolicy {
+    fn kind_of(errno: i32) -> Option<I2CErrorKind> {
+        // See Documentation/i2c/fault-codes.rst, i2c-bcm2835 reports clock stretch timeouts as EIO
+        match errno {
+            libc::ENXIO | libc::EREMOTEIO => Some(I2CErrorKind::NACK),
+            libc::EAGAIN => Some(I2CErrorKind::ARBITRATION_LOST),
+            libc::ETIMEDOUT | libc::EIO => Some(I2CErrorKind::TIMEOUT),
+            _ => None,
+        }
+    }
+
+    fn is_retryable<E: I2CErrorCode>(&self, error: &E) -> bool {
+        match error.errno().and_then(RetryPolicy::kind_of) {
+            Some(I2CErrorKind::NACK) => self.nack,
+            Some(I2CErrorKind::ARBITRATION_LOST) => self.arbitration_lost,
+            Some(I2CErrorKind::TIMEOUT) => self.timeout,
+            None => false,
+        }
+    }
+
+    /// Runs the operation, retrying it while it fails with a retryable error and retries are left. The delay between
+    /// attempts doubles after every retry.
+    pub fn run<T, E: I2CErrorCode>(&self, mut op: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+        let mut delay = self.backoff;
+        let mut attempt = 0;
+        loop {
+            match op() {
+                Err(e) if attempt < self.retries && self.is_retryable(&e) => {
+                    attempt += 1;
+                    sleep(delay);
+                    // Saturated, since many retries or a long backoff would overflow the Duration
+                    delay = delay.saturating_mul(2);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+
+/// The I2C buses opened by the I2CManager.
+///
+/// Operations that don't specify a bus use the default bus, which is the first bus that was opened.
+pub struct I2CBuses {
+    buses: HashMap<u8, I2c>,
+    default_bus: Option<u8>,
+    slave_addresses: HashMap<u8, u16>,
+    addr_10bit_buses: HashSet<u8>,
+    pec_buses: HashSet<u8>,
+    retry_policy: RetryPolicy,
+}
+
+
+impl I2CBuses {
+    /// Returns the handle for the given bus, or for the default bus if no bus is given.
+    pub fn get(&mut self, bus: Option<u8>) -> PyResult<&mut I2c> {
+        let bus_num = match bus.or(self.default_bus) {
+            Some(bus_num) => bus_num,
+            None => return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("I2C bus is not opened")),
+        };
+        self.buses
+            .get_mut(&bus_num)
+            .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("I2C bus {} is not opened", bus_num)))
+    }
+
+    /// Opens the given bus, the first bus opened becomes the default bus.
+    pub fn open(&mut self, bus: u8) -> PyResult<()> {
+        let i2c = I2c::with_bus(bus)
+            .map_err(|e| match overlay_module::disabled_reason(Peripheral::I2c) {
+                Some(reason) => PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to open I2C bus {}: {:?}, {}", bus, e, reason)),
+                None => PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to open I2C bus {}: {:?}", bus, e)),
+            })?;
+        self.buses.insert(bus, i2c);
+        if self.default_bus.is_none() {
+            self.default_bus = Some(bus);
+        }
+        Ok(())
+    }
+
+    /// Returns whether the given bus is open.
+    pub fn is_open(&self, bus: u8) -> bool {
+        self.buses.contains_key(&bus)
+    }
+
+    /// Returns the handle for the given bus with the slave address selected. The address is only set when it differs
+    /// from the address last selected on the bus.
+    pub fn select(&mut self, bus: Option<u8>, addr: u16) -> PyResult<&mut I2c> {
+        let bus_num = self.get(bus)?.bus();
+        let i2c = self.buses.get_mut(&bus_num).unwrap();
+        if self.slave_addresses.get(&bus_num) != Some(&addr) {
+            self.slave_addresses.remove(&bus_num);
+            i2c.set_slave_address(addr)
+               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to set slave address: {:?}", e)))?;
+            self.slave_addresses.insert(bus_num, addr);
+        }
+        Ok(i2c)
+    }
+
+    /// Performs a combined transaction on the given bus, returning the data of every read segment.
+    pub fn transaction(&mut self, bus: Option<u8>, addr: u16, segments: &[Segment]) -> PyResult<Vec<Vec<u8>>> {
+        let retry = self.retry_policy;
+        let bus_num = self.get(bus)?.bus();
+        // rppal only supports a single write followed by a read, so the transfer is sent through a separate handle
+        let addr_10bit = self.addr_10bit_enabled(bus_num);
+        let dev = I2cDev::open(bus_num)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to open I2C bus {}: {:?}", bus_num, e)))?;
+        retry.run(|| dev.transfer(addr, addr_10bit, segments))
+             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to perform transaction: {:?}", e)))
+    }
+
+    /// Sends a block to a register and reads the block the device responds with, returning the block received.
+    pub fn block_process_call(&mut self, bus: Option<u8>, addr: u16, command: u8, data: &[u8]) -> PyResult<Vec<u8>> {
+        let retry = self.retry_policy;
+        let bus_num = self.get(bus)?.bus();
+        let pec = self.pec_enabled(bus_num);
+        let addr_10bit = self.addr_10bit_enabled(bus_num);
+        // rppal doesn't implement the block process call, so it is sent through a separate handle to the same bus
+        let dev = I2cDev::open(bus_num)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to open I2C bus {}: {:?}", bus_num, e)))?;
+        dev.set_slave(addr, addr_10bit, pec)
+           .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to set slave address: {:?}", e)))?;
+        retry.run(|| dev.smbus_block_process_call(command, data))
+             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to perform block process call: {:?}", e)))
+    }
+
+    /// Enables or disables packet error checking on the given bus.
+    pub fn set_pec(&mut self, bus: Option<u8>, enabled: bool) -> PyResult<()> {
+        let i2c = self.get(bus)?;
+        i2c.set_smbus_pec(enabled)
+           .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to set packet error checking: {:?}", e)))?;
+        let bus_num = i2c.bus();
+        if enabled {
+            self.pec_buses.insert(bus_num);
+        } else {
+            self.pec_buses.remove(&bus_num);
+        }
+        Ok(())
+    }
+
+    /// Returns the retry policy used for transfers on every bus.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    /// Returns whether packet error checking is enabled on the given bus.
+    pub fn pec_enabled(&self, bus_num: u8) -> bool {
+        self.pec_buses.contains(&bus_num)
+    }
+
+    /// Returns whether 10-bit addressing is enabled on the given bus.
+    pub fn addr_10bit_enabled(&self, bus_num: u8) -> bool {
+        self.addr_10bit_buses.contains(&bus_num)
+    }
+}
+
+
+/// Locks the buses and runs the operation on them with the GIL released, so other Python threads keep running during
+/// the transfer. The lock is only taken once the GIL is released, so a thread holding the lock never waits for the GIL.
+pub fn with_buses<T: Send>(buses: &Mutex<I2CBuses>, op: impl FnOnce(&mut I2CBuses) -> PyResult<T> + Send) -> PyResult<T> {
+    release_gil(|| {
+        let result = op(&mut metrics::lock(buses));
+        metrics::count_i2c(&result);
+        result
+    })
+}
+
+
+/// Converts the data passed to a write method into bytes, accepting any object implementing the buffer protocol
+/// (bytes, bytearray, memoryview, ...) or a list of ints.
+///
+/// Bytes objects are borrowed instead of copied.
+pub fn extract_bytes<'a>(data: &'a Bound<'_, PyAny>) -> PyResult<Cow<'a, [u8]>> {
+    if let Ok(bytes) = data.downcast::<PyBytes>() {
+        return Ok(Cow::Borrowed(bytes.as_bytes()));
+    }
+    if let Ok(buffer) = PyBuffer::<u8>::get(data) {
+        return Ok(Cow::Owned(buffer.to_vec(data.py())?));
+    }
+    data.extract::<Vec<u8>>().map(Cow::Owned).map_err(|_| {
+        PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!("Expected a bytes-like object or a list of ints in the range 0 - 255, got {}",
+                                                              data.get_type().name().map(|name| name.to_string()).unwrap_or_default()))
+    })
+}
+
+
+/// Clocks SCL until the slave holding SDA low has shifted out the rest of its byte, then generates a STOP condition.
+/// The pins are driven open drain, by switching between an output driven low and an input released to the pull-up.
+/// Returns whether SDA is released afterwards.
+fn clock_out_stuck_slave(scl: &mut IoPin, sda: &mut IoPin) -> bool {
+    // Half of a 100 kHz clock period
+    const HALF_PERIOD: Duration = Duration::from_micros(5);
+    let release = |pin: &mut IoPin| pin.set_mode(Mode::Input);
+    let pull_low = |pin: &mut IoPin| {
+        pin.set_low();
+        pin.set_mode(Mode::Output);
+    };
+
+    release(scl);
+    release(sda);
+    sleep(HALF_PERIOD);
+    for _ in 0..9 {
+        if sda.is_high() {
+            break;
+        }
+        pull_low(scl);
+        sleep(HALF_PERIOD);
+        release(scl);
+        sleep(HALF_PERIOD);
+    }
+
+    // STOP: SDA rises while SCL is high
+    pull_low(scl);
+    sleep(HALF_PERIOD);
+    pull_low(sda);
+    sleep(HALF_PERIOD);
+    release(scl);
+    sleep(HALF_PERIOD);
+    release(sda);
+    sleep(HALF_PERIOD);
+    sda.is_high()
+}
+
+
+/// A background thread periodically reading a device, started by `I2CManager.poll`.
+struct I2CPoller {
+    bus: u8,
+    stop: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+
+impl I2CPoller {
+    /// Signals the thread to stop and waits for it to finish, unless it is stopped from its own callback.
+    fn stop(self, py: Python) {
+        self.stop.store(true, Ordering::SeqCst);
+        self.handle.thread().unpark();
+        if thread::current().id() != self.handle.thread().id() {
+            py.allow_threads(|| {
+                let _ = self.handle.join();
+            });
+        }
+    }
+}
+
+
+/// The pollers started by the I2CManager.
+struct I2CPollers {
+    pollers: HashMap<u32, I2CPoller>,
+    next_id: u32,
+}
+
+
+/// Converts a list of I2CWrite and I2CRead objects into transaction segments.
+pub fn extract_segments(segments: &[Bound<'_, PyAny>]) -> PyResult<Vec<Segment>> {
+    segments
+        .iter()
+        .map(|segment| {
+            if let Ok(write) = segment.downcast::<I2CWrite>() {
+                Ok(Segment::Write(write.borrow().data.clone()))
+            } else if let Ok(read) = segment.downcast::<I2CRead>() {
+                Ok(Segment::Read(read.borrow().length))
+            } else {
+                Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>("Transaction segments must be I2CWrite or I2CRead instances"))
+            }
+        })
+        .collect()
+}
 
 
 static I2C_MANAGER: Lazy<Arc<Mutex<I2CManager>>> = Lazy::new(|| {
@@ -24,7 +416,8 @@ static I2C_MANAGER: Lazy<Arc<Mutex<I2CManager>>> = Lazy::new(|| {
 /// i2c_manager.close()
 /// ```
 pub struct I2CManager {
-    i2c: Arc<Mutex<Option<I2c>>>,
+    i2c: Arc<Mutex<I2CBuses>>,
+    pollers: Arc<Mutex<I2CPollers>>,
 }
 
 
@@ -32,7 +425,24 @@ impl I2CManager {
     /// Internal method to initialize the I2CManager singleton.
     fn new_singleton() -> PyResult<Self> {
         Ok(Self {
-            i2c: Arc::new(Mutex::new(None)),
+            i2c: Arc::new(Mutex::new(I2CBuses {
+                buses: HashMap::new(),
+                default_bus: None,
+                slave_addresses: HashMap::new(),
+                addr_10bit_buses: HashSet::new(),
+                pec_buses: HashSet::new(),
+                retry_policy: RetryPolicy {
+                    retries: 0,
+                    backoff: Duration::ZERO,
+                    nack: true,
+                    arbitration_lost: true,
+                    timeout: true,
+                },
+            })),
+            pollers: Arc::new(Mutex::new(I2CPollers {
+                pollers: HashMap::new(),
+                next_id: 0,
+            })),
         })
     }
 
@@ -40,6 +450,25 @@ impl I2CManager {
         let manager = I2C_MANAGER.lock().unwrap();
         Py::new(py, I2CManager {
             i2c: Arc::clone(&manager.i2c),
+            pollers: Arc::clone(&manager.pollers),
+        })
+    }
+
+    pub fn new_rust_reference() -> Arc<Mutex<I2CManager>> {
+        Arc::clone(&I2C_MANAGER)
+    }
+
+    pub fn get_buses(&self) -> Arc<Mutex<I2CBuses>> {
+        Arc::clone(&self.i2c)
+    }
+
+    /// Runs the operation on a device with the GIL released, retrying it according to the retry policy.
+    fn with_i2c<T: Send>(&self, bus: Option<u8>, addr: u16, action: &str, mut op: impl FnMut(&mut I2c) -> rppal::i2c::Result<T> + Send) -> PyResult<T> {
+        with_buses(&self.i2c, |i2c_lock| {
+            let retry = i2c_lock.retry_policy();
+            let i2c = i2c_lock.select(bus, addr)?;
+            retry.run(|| op(i2c))
+                 .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to {}: {:?}", action, e)))
         })
     }
 }
@@ -58,7 +487,8 @@ impl I2CManager {
         I2CManager::shared(py)
     }
 
-    /// Opens the I2C bus.
+    /// Opens an I2C bus. More than one bus can be open at the same time, the first bus opened is the default bus used
+    /// by operations that don't specify a bus.
     ///
     /// Parameters:
     /// - `bus` (int): The I2C bus number to open (default is 1).
@@ -68,79 +498,184 @@ impl I2CManager {
     /// i2c_manager.open(bus=1)
     /// ```
     #[pyo3(signature = (bus = 1))]
-    fn open(&self, bus: u8) -> PyResult<()> {
+    pub(crate) fn open(&self, bus: u8) -> PyResult<()> {
         let mut i2c_lock = self.i2c.lock().unwrap();
-        if i2c_lock.is_some() {
-            return Err(PyErr::new::<pyo3::exceptions::PyConnectionRefusedError, _>("I2C bus already opened"));
+        if i2c_lock.is_open(bus) {
+            return Err(PyErr::new::<pyo3::exceptions::PyConnectionRefusedError, _>(format!("I2C bus {} already opened", bus)));
         }
-
-        let i2c = I2c::with_bus(bus)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to open I2C bus {}: {:?}", bus, e)))?;
-        *i2c_lock = Some(i2c);
-        Ok(())
+        i2c_lock.open(bus)
     }
 
-    /// Closes the I2C bus.
+    /// Closes an I2C bus, or every open bus if no bus is given. Pollers reading from a closed bus are stopped.
+    ///
+    /// Parameters:
+    /// - `bus` (int): The I2C bus number to close (default is None).
     ///
     /// Example usage:
     /// ```python
     /// i2c_manager.close()
     /// ```
-    fn close(&self) -> PyResult<()> {
-        let mut i2c_lock = self.i2c.lock().unwrap();
-        if i2c_lock.is_none() {
+    #[pyo3(signature = (bus = None))]
+    fn close(&self, py: Python, bus: Option<u8>) -> PyResult<()> {
+        if self.i2c.lock().unwrap().buses.is_empty() {
             return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("I2C bus is not opened"));
         }
-        *i2c_lock = None;
+        // Stop the pollers first, they need the bus lock to finish their current read
+        let stopped: Vec<I2CPoller> = {
+            let mut pollers_lock = self.pollers.lock().unwrap();
+            let ids: Vec<u32> = pollers_lock.pollers
+                                            .iter()
+                                            .filter(|(_, poller)| bus.is_none() || bus == Some(poller.bus))
+                                            .map(|(id, _)| *id)
+                                            .collect();
+            ids.iter().filter_map(|id| pollers_lock.pollers.remove(id)).collect()
+        };
+        for poller in stopped {
+            poller.stop(py);
+        }
+
+        let mut i2c_lock = self.i2c.lock().unwrap();
+        match bus {
+            Some(bus) => {
+                if i2c_lock.buses.remove(&bus).is_none() {
+                    return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("I2C bus {} is not opened", bus)));
+                }
+                i2c_lock.slave_addresses.remove(&bus);
+                i2c_lock.addr_10bit_buses.remove(&bus);
+                i2c_lock.pec_buses.remove(&bus);
+                if i2c_lock.default_bus == Some(bus) {
+                    i2c_lock.default_bus = i2c_lock.buses.keys().min().cloned();
+                }
+            }
+            None => {
+                i2c_lock.buses.clear();
+                i2c_lock.slave_addresses.clear();
+                i2c_lock.addr_10bit_buses.clear();
+                i2c_lock.pec_buses.clear();
+                i2c_lock.default_bus = None;
+            }
+        }
         Ok(())
     }
 
-    /// Writes a single byte to the I2C slave device.
+    /// Gets the I2C buses that are currently open.
+    ///
+    /// Returns:
+    /// - `list[int]`: The open bus numbers.
+    ///
+    /// Example usage:
+    /// ```python
+    /// buses = i2c_manager.get_open_buses()
+    /// ```
+    fn get_open_buses(&self) -> Vec<u8> {
+        let i2c_lock = self.i2c.lock().unwrap();
+        let mut buses: Vec<u8> = i2c_lock.buses.keys().cloned().collect();
+        buses.sort();
+        buses
+    }
+
+    /// Creates a handle bound to a single device, so the address doesn't have to be passed to every call.
     ///
     /// Parameters:
     /// - `addr` (int): The I2C slave address.
-    /// - `data` (int): The byte to write.
+    /// - `bus` (int): The I2C bus the device is connected to (default is the first bus opened). The bus must be open.
+    ///
+    /// Returns:
+    /// - `I2CDevice`: The device handle.
     ///
     /// Example usage:
     /// ```python
-    /// i2c_manager.write_byte(0x20, 0xFF)
+    /// sensor = i2c_manager.device(0x48)
+    /// ```
+    #[pyo3(signature = (addr, bus = None))]
+    fn device(&self, addr: u16, bus: Option<u8>) -> PyResult<I2CDevice> {
+        let bus_num = self.i2c.lock().unwrap().get(bus)?.bus();
+        Ok(I2CDevice::new(Arc::clone(&self.i2c), bus_num, addr))
+    }
+
+    /// Sets how failed transfers are retried on every bus, including transfers made by the EEPROM and RTC helpers. Bus
+    /// scans are never retried.
+    ///
+    /// Parameters:
+    /// - `retries` (int): The number of times a failed transfer is retried, 0 disables retrying (default is 0).
+    /// - `backoff_ms` (float): The delay before the first retry in milliseconds, doubled after every retry (default is 0).
+    /// - `retry_on` (list[I2CErrorKind]): The kinds of errors that are retried, all kinds are retried if it is None
+    ///   (default is None).
+    ///
+    /// Example usage:
+    /// ```python
+    /// i2c_manager.set_retry_policy(retries=3, backoff_ms=1, retry_on=[gpio_manager.I2CErrorKind.NACK])
     /// ```
-    #[pyo3(signature = (addr, data))]
-    fn write_byte(&self, addr: u16, data: u8) -> PyResult<()> {
+    #[pyo3(signature = (retries = 0, backoff_ms = 0f64, retry_on = None))]
+    fn set_retry_policy(&self, retries: u32, backoff_ms: f64, retry_on: Option<Vec<I2CErrorKind>>) -> PyResult<()> {
+        let backoff = ms_to_duration_checked("Backoff", backoff_ms)?;
+        let retry_on = retry_on.unwrap_or_else(|| vec![I2CErrorKind::NACK, I2CErrorKind::ARBITRATION_LOST, I2CErrorKind::TIMEOUT]);
         let mut i2c_lock = self.i2c.lock().unwrap();
-        if let Some(ref mut i2c) = *i2c_lock {
-            i2c.set_slave_address(addr)
-               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to set slave address: {:?}", e)))?;
+        i2c_lock.retry_policy = RetryPolicy {
+            retries,
+            backoff,
+            nack: retry_on.contains(&I2CErrorKind::NACK),
+            arbitration_lost: retry_on.contains(&I2CErrorKind::ARBITRATION_LOST),
+            timeout: retry_on.contains(&I2CErrorKind::TIMEOUT),
+        };
+        Ok(())
+    }
 
-            // Send command and data
-            i2c.write(&[data])
-               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to write byte: {:?}", e)))?;
-            Ok(())
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("I2C bus is not opened"))
+    /// Gets the retry policy.
+    ///
+    /// Returns:
+    /// - `tuple[int, float, list[I2CErrorKind]]`: The number of retries, the initial backoff in milliseconds and the
+    ///   kinds of errors that are retried.
+    ///
+    /// Example usage:
+    /// ```python
+    /// retries, backoff_ms, retry_on = i2c_manager.get_retry_policy()
+    /// ```
+    fn get_retry_policy(&self) -> (u32, f64, Vec<I2CErrorKind>) {
+        let policy = self.i2c.lock().unwrap().retry_policy;
+        let mut retry_on = Vec::new();
+        if policy.nack {
+            retry_on.push(I2CErrorKind::NACK);
+        }
+        if policy.arbitration_lost {
+            retry_on.push(I2CErrorKind::ARBITRATION_LOST);
         }
+        if policy.timeout {
+            retry_on.push(I2CErrorKind::TIMEOUT);
+        }
+        (policy.retries, policy.backoff.as_secs_f64() * 1000f64, retry_on)
     }
 
-    #[pyo3(signature = (addr, command, data))]
-    fn block_write_byte(&self, addr: u16, command: u8, data: u8) -> PyResult<()> {
-        let mut i2c_lock = self.i2c.lock().unwrap();
-        if let Some(ref mut i2c) = *i2c_lock {
-            i2c.set_slave_address(addr)
-               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to set slave address: {:?}", e)))?;
+    /// Writes a single byte to the I2C slave device.
+    ///
+    /// Parameters:
+    /// - `addr` (int): The I2C slave address.
+    /// - `data` (int): The byte to write.
+    /// - `bus` (int): The I2C bus to use (default is the first bus opened).
+    ///
+    /// Example usage:
+    /// ```python
+    /// i2c_manager.write_byte(0x20, 0xFF)
+    /// ```
+    #[pyo3(signature = (addr, data, bus = None))]
+    fn write_byte(&self, addr: u16, data: u8, bus: Option<u8>) -> PyResult<()> {
+        // Send command and data
+        self.with_i2c(bus, addr, "write byte", |i2c| i2c.write(&[data]))?;
+        Ok(())
+    }
 
-            // Send command and data
-            i2c.block_write(command, &[data])
-               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to write byte: {:?}", e)))?;
-            Ok(())
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("I2C bus is not opened"))
-        }
+    #[pyo3(signature = (addr, command, data, bus = None))]
+    fn block_write_byte(&self, addr: u16, command: u8, data: u8, bus: Option<u8>) -> PyResult<()> {
+        // Send command and data
+        self.with_i2c(bus, addr, "write byte", |i2c| i2c.block_write(command, &[data]))?;
+        Ok(())
     }
 
     /// Reads a single byte from the I2C slave device.
     ///
     /// Parameters:
     /// - `addr` (int): The I2C slave address.
+    /// - `bus` (int): The I2C bus to use (default is the first bus opened).
     ///
     /// Returns:
     /// - `int`: The byte read.
@@ -149,73 +684,44 @@ impl I2CManager {
     /// ```python
     /// data = i2c_manager.read_byte(0x20)
     /// ```
-    #[pyo3(signature = (addr, command))]
-    fn block_read_byte(&self, addr: u16, command: u8) -> PyResult<u8> {
-        let mut i2c_lock = self.i2c.lock().unwrap();
-        if let Some(ref mut i2c) = *i2c_lock {
-            i2c.set_slave_address(addr)
-               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to set slave address: {:?}", e)))?;
-            let mut buf = [0u8; 1];
-            i2c.block_read(command, &mut buf)
-               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to read byte: {:?}", e)))?;
-            Ok(buf[0])
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("I2C bus is not opened"))
-        }
+    #[pyo3(signature = (addr, command, bus = None))]
+    fn block_read_byte(&self, addr: u16, command: u8, bus: Option<u8>) -> PyResult<u8> {
+        let mut buf = [0u8; 1];
+        self.with_i2c(bus, addr, "read byte", |i2c| i2c.block_read(command, &mut buf))?;
+        Ok(buf[0])
     }
 
-    #[pyo3(signature = (addr))]
-    fn read_byte(&self, addr: u16) -> PyResult<u8> {
-        let mut i2c_lock = self.i2c.lock().unwrap();
-        if let Some(ref mut i2c) = *i2c_lock {
-            i2c.set_slave_address(addr)
-               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to set slave address: {:?}", e)))?;
-            let mut buf = [0u8; 1];
-            i2c.read(&mut buf)
-               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to read byte: {:?}", e)))?;
-            Ok(buf[0])
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("I2C bus is not opened"))
-        }
+    #[pyo3(signature = (addr, bus = None))]
+    fn read_byte(&self, addr: u16, bus: Option<u8>) -> PyResult<u8> {
+        let mut buf = [0u8; 1];
+        self.with_i2c(bus, addr, "read byte", |i2c| i2c.read(&mut buf))?;
+        Ok(buf[0])
     }
 
     /// Writes data to the I2C slave device.
     ///
     /// Parameters:
     /// - `addr` (int): The I2C slave address.
-    /// - `data` (bytes): The data to write.
+    /// - `data` (bytes | bytearray | memoryview | list[int]): The data to write.
+    /// - `bus` (int): The I2C bus to use (default is the first bus opened).
     ///
     /// Example usage:
     /// ```python
     /// i2c_manager.write( b'\x01\x02\x03')
     /// ```
-    #[pyo3(signature = (addr, data))]
-    fn write(&self, addr: u16, data: &Bound<'_, PyBytes>) -> PyResult<()> {
-        let mut i2c_lock = self.i2c.lock().unwrap();
-        if let Some(ref mut i2c) = *i2c_lock {
-            i2c.set_slave_address(addr)
-               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to set slave address: {:?}", e)))?;
-            i2c.write(data.as_bytes())
-               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to write data: {:?}", e)))?;
-            Ok(())
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("I2C bus is not opened"))
-        }
+    #[pyo3(signature = (addr, data, bus = None))]
+    fn write(&self, addr: u16, data: &Bound<'_, PyAny>, bus: Option<u8>) -> PyResult<()> {
+        let data = extract_bytes(data)?;
+        self.with_i2c(bus, addr, "write data", |i2c| i2c.write(&data))?;
+        Ok(())
     }
 
 
-    #[pyo3(signature = (addr, command, data))]
-    fn block_write(&self, addr: u16, command: u8, data: &Bound<'_, PyBytes>) -> PyResult<()> {
-        let mut i2c_lock = self.i2c.lock().unwrap();
-        if let Some(ref mut i2c) = *i2c_lock {
-            i2c.set_slave_address(addr)
-               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to set slave address: {:?}", e)))?;
-            i2c.block_write(command, data.as_bytes())
-               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to write data: {:?}", e)))?;
-            Ok(())
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("I2C bus is not opened"))
-        }
+    #[pyo3(signature = (addr, command, data, bus = None))]
+    fn block_write(&self, addr: u16, command: u8, data: &Bound<'_, PyAny>, bus: Option<u8>) -> PyResult<()> {
+        let data = extract_bytes(data)?;
+        self.with_i2c(bus, addr, "write data", |i2c| i2c.block_write(command, &data))?;
+        Ok(())
     }
 
 
@@ -224,6 +730,7 @@ impl I2CManager {
     /// Parameters:
     /// - `addr` (int): The I2C slave address.
     /// - `length` (int): The number of bytes to read.
+    /// - `bus` (int): The I2C bus to use (default is the first bus opened).
     ///
     /// Returns:
     /// - `bytes`: The data read.
@@ -232,47 +739,30 @@ impl I2CManager {
     /// ```python
     /// data = i2c_manager.read(0x20, 3)
     /// ```
-    #[pyo3(signature = (addr, command, length))]
-    fn block_read<'py>(&self, py: Python<'py>, addr: u16, command: u8, length: usize) -> PyResult<Bound<'py, PyBytes>> {
-        let mut i2c_lock = self.i2c.lock().unwrap();
-        if let Some(ref mut i2c) = *i2c_lock {
-            i2c.set_slave_address(addr)
-               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to set slave address: {:?}", e)))?;
+    #[pyo3(signature = (addr, command, length, bus = None))]
+    fn block_read<'py>(&self, py: Python<'py>, addr: u16, command: u8, length: usize, bus: Option<u8>) -> PyResult<Bound<'py, PyBytes>> {
+        let mut buf = vec![0u8; length];
+        self.with_i2c(bus, addr, "read data", |i2c| i2c.block_read(command, &mut buf))?;
 
-            let mut buf = vec![0u8; length];
-            i2c.block_read(command, &mut buf)
-               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to read data: {:?}", e)))?;
-
-            Ok(PyBytes::new_bound(py, &buf))
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("I2C bus is not opened"))
-        }
+        Ok(PyBytes::new(py, &buf))
     }
 
 
-    #[pyo3(signature = (addr, length))]
-    fn read<'py>(&self, py: Python<'py>, addr: u16, length: usize) -> PyResult<Bound<'py, PyBytes>> {
-        let mut i2c_lock = self.i2c.lock().unwrap();
-        if let Some(ref mut i2c) = *i2c_lock {
-            i2c.set_slave_address(addr)
-               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to set slave address: {:?}", e)))?;
+    #[pyo3(signature = (addr, length, bus = None))]
+    fn read<'py>(&self, py: Python<'py>, addr: u16, length: usize, bus: Option<u8>) -> PyResult<Bound<'py, PyBytes>> {
+        let mut buf = vec![0u8; length];
+        self.with_i2c(bus, addr, "read data", |i2c| i2c.read(&mut buf))?;
 
-            let mut buf = vec![0u8; length];
-            i2c.read(&mut buf)
-               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to read data: {:?}", e)))?;
-
-            Ok(PyBytes::new_bound(py, &buf))
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("I2C bus is not opened"))
-        }
+        Ok(PyBytes::new(py, &buf))
     }
 
     /// Performs a write followed by a read operation.
     ///
     /// Parameters:
     /// - `addr` (int): The I2C slave address.
-    /// - `write_data` (bytes): The data to write.
+    /// - `write_data` (bytes | bytearray | memoryview | list[int]): The data to write.
     /// - `read_length` (int): The number of bytes to read.
+    /// - `bus` (int): The I2C bus to use (default is the first bus opened).
     ///
     /// Returns:
     /// - `bytes`: The data read.
@@ -281,25 +771,461 @@ impl I2CManager {
     /// ```python
     /// data = i2c_manager.write_read(0x20, b'\x01\x02', 3)
     /// ```
-    #[pyo3(signature = (addr, write_data, read_length))]
-    fn write_read<'py>(&self, py: Python<'py>, addr: u16, write_data: &Bound<'py, PyBytes>, read_length: usize) -> PyResult<Bound<'py, PyBytes>> {
+    #[pyo3(signature = (addr, write_data, read_length, bus = None))]
+    fn write_read<'py>(&self, py: Python<'py>, addr: u16, write_data: &Bound<'py, PyAny>, read_length: usize, bus: Option<u8>) -> PyResult<Bound<'py,
+        PyBytes>> {
+        let write_data = extract_bytes(write_data)?;
+        let mut buf = vec![0u8; read_length];
+        self.with_i2c(bus, addr, "write data", |i2c| i2c.write_read(&write_data, &mut buf))?;
+        Ok(PyBytes::new(py, &buf))
+    }
+
+    #[pyo3(signature = (addr, command, write_data, read_length, bus = None))]
+    fn block_write_read<'py>(&self, py: Python<'py>, addr: u16, command: u8, write_data: &Bound<'py, PyAny>, read_length: usize, bus: Option<u8>) ->
+    PyResult<Bound<'py, PyBytes>> {
+        self.block_write(addr, command, write_data, bus)?;
+        self.block_read(py, addr, extract_bytes(write_data)?[0], read_length, bus)
+    }
+
+    /// Reads a 16 bit word from a register of the I2C slave device using a single SMBus transaction.
+    ///
+    /// Parameters:
+    /// - `addr` (int): The I2C slave address.
+    /// - `command` (int): The register to read.
+    /// - `big_endian` (bool): Treat the high byte as the first byte on the bus, SMBus devices send the low byte first
+    ///   (default is False).
+    /// - `bus` (int): The I2C bus to use (default is the first bus opened).
+    ///
+    /// Returns:
+    /// - `int`: The word read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// value = i2c_manager.read_word(0x48, 0x00, big_endian=True)
+    /// ```
+    #[pyo3(signature = (addr, command, big_endian = false, bus = None))]
+    fn read_word(&self, addr: u16, command: u8, big_endian: bool, bus: Option<u8>) -> PyResult<u16> {
+        self.with_i2c(bus, addr, "read word", |i2c| {
+            if big_endian {
+                i2c.smbus_read_word_swapped(command)
+            } else {
+                i2c.smbus_read_word(command)
+            }
+        })
+    }
+
+    /// Writes a 16 bit word to a register of the I2C slave device using a single SMBus transaction.
+    ///
+    /// Parameters:
+    /// - `addr` (int): The I2C slave address.
+    /// - `command` (int): The register to write.
+    /// - `value` (int): The word to write.
+    /// - `big_endian` (bool): Send the high byte first, SMBus devices expect the low byte first (default is False).
+    /// - `bus` (int): The I2C bus to use (default is the first bus opened).
+    ///
+    /// Example usage:
+    /// ```python
+    /// i2c_manager.write_word(0x48, 0x01, 0x6080, big_endian=True)
+    /// ```
+    #[pyo3(signature = (addr, command, value, big_endian = false, bus = None))]
+    fn write_word(&self, addr: u16, command: u8, value: u16, big_endian: bool, bus: Option<u8>) -> PyResult<()> {
+        self.with_i2c(bus, addr, "write word", |i2c| {
+            if big_endian {
+                i2c.smbus_write_word_swapped(command, value)
+            } else {
+                i2c.smbus_write_word(command, value)
+            }
+        })
+    }
+
+    /// Sends an SMBus quick command, which transfers a single bit in place of the read/write bit of the address.
+    ///
+    /// Parameters:
+    /// - `addr` (int): The I2C slave address.
+    /// - `read` (bool): The value of the read/write bit (default is False).
+    /// - `bus` (int): The I2C bus to use (default is the first bus opened).
+    ///
+    /// Example usage:
+    /// ```python
+    /// i2c_manager.quick_command(0x20)
+    /// ```
+    #[pyo3(signature = (addr, read = false, bus = None))]
+    fn quick_command(&self, addr: u16, read: bool, bus: Option<u8>) -> PyResult<()> {
+        self.with_i2c(bus, addr, "send quick command", |i2c| i2c.smbus_quick_command(read))
+    }
+
+    /// Sends a 16 bit word to a register and reads the 16 bit word the device responds with in a single SMBus transaction.
+    ///
+    /// Parameters:
+    /// - `addr` (int): The I2C slave address.
+    /// - `command` (int): The register to send the word to.
+    /// - `value` (int): The word to send.
+    /// - `big_endian` (bool): Transfer the high byte of both words first, SMBus devices use the low byte first
+    ///   (default is False).
+    /// - `bus` (int): The I2C bus to use (default is the first bus opened).
+    ///
+    /// Returns:
+    /// - `int`: The word received.
+    ///
+    /// Example usage:
+    /// ```python
+    /// response = i2c_manager.process_call(0x0B, 0x00, 0x0001)
+    /// ```
+    #[pyo3(signature = (addr, command, value, big_endian = false, bus = None))]
+    fn process_call(&self, addr: u16, command: u8, value: u16, big_endian: bool, bus: Option<u8>) -> PyResult<u16> {
+        self.with_i2c(bus, addr, "perform process call", |i2c| {
+            if big_endian {
+                i2c.smbus_process_call_swapped(command, value)
+            } else {
+                i2c.smbus_process_call(command, value)
+            }
+        })
+    }
+
+    /// Sends a block of up to 32 bytes to a register and reads the block the device responds with in a single SMBus
+    /// transaction.
+    ///
+    /// Parameters:
+    /// - `addr` (int): The I2C slave address.
+    /// - `command` (int): The register to send the block to.
+    /// - `data` (bytes | bytearray | memoryview | list[int]): The block to send.
+    /// - `bus` (int): The I2C bus to use (default is the first bus opened).
+    ///
+    /// Returns:
+    /// - `bytes`: The block received.
+    ///
+    /// Example usage:
+    /// ```python
+    /// response = i2c_manager.block_process_call(0x0B, 0x2F, b'\x01\x02')
+    /// ```
+    #[pyo3(signature = (addr, command, data, bus = None))]
+    fn block_process_call<'py>(&self, py: Python<'py>, addr: u16, command: u8, data: &Bound<'py, PyAny>, bus: Option<u8>) -> PyResult<Bound<'py,
+        PyBytes>> {
+        let data = extract_bytes(data)?;
+        let buf = with_buses(&self.i2c, |i2c_lock| i2c_lock.block_process_call(bus, addr, command, &data))?;
+        Ok(PyBytes::new(py, &buf))
+    }
+
+    /// Enables or disables SMBus packet error checking, which appends a CRC-8 byte to every SMBus transaction except
+    /// the quick command.
+    ///
+    /// Parameters:
+    /// - `enabled` (bool): Whether packet error checking is used.
+    /// - `bus` (int): The I2C bus to configure (default is the first bus opened).
+    ///
+    /// Example usage:
+    /// ```python
+    /// i2c_manager.set_pec(True)
+    /// ```
+    #[pyo3(signature = (enabled, bus = None))]
+    fn set_pec(&self, enabled: bool, bus: Option<u8>) -> PyResult<()> {
+        self.i2c.lock().unwrap().set_pec(bus, enabled)
+    }
+
+    /// Gets whether SMBus packet error checking is enabled.
+    ///
+    /// Parameters:
+    /// - `bus` (int): The I2C bus to check (default is the first bus opened).
+    ///
+    /// Returns:
+    /// - `bool`: True if packet error checking is enabled.
+    ///
+    /// Example usage:
+    /// ```python
+    /// enabled = i2c_manager.get_pec()
+    /// ```
+    #[pyo3(signature = (bus = None))]
+    fn get_pec(&self, bus: Option<u8>) -> PyResult<bool> {
         let mut i2c_lock = self.i2c.lock().unwrap();
-        if let Some(ref mut i2c) = *i2c_lock {
-            i2c.set_slave_address(addr)
-               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to set slave address: {:?}", e)))?;
-            let mut buf = vec![0u8; read_length];
-            i2c.write_read(write_data.as_bytes(), &mut buf)
-               .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to write data: {:?}", e)))?;
-            Ok(PyBytes::new_bound(py, &buf))
+        let bus_num = i2c_lock.get(bus)?.bus();
+        Ok(i2c_lock.pec_enabled(bus_num))
+    }
+
+    /// Enables or disables 10-bit addressing. While it is enabled every address on the bus is sent as a 10-bit address,
+    /// so devices with 7-bit addresses can't be reached.
+    ///
+    /// Parameters:
+    /// - `enabled` (bool): Whether 10-bit addresses are used.
+    /// - `bus` (int): The I2C bus to configure (default is the first bus opened).
+    ///
+    /// Example usage:
+    /// ```python
+    /// i2c_manager.set_addr_10bit(True)
+    /// i2c_manager.write_byte(0x2A5, 0x01)
+    /// ```
+    #[pyo3(signature = (enabled, bus = None))]
+    fn set_addr_10bit(&self, enabled: bool, bus: Option<u8>) -> PyResult<()> {
+        let mut i2c_lock = self.i2c.lock().unwrap();
+        let i2c = i2c_lock.get(bus)?;
+        i2c.set_addr_10bit(enabled)
+           .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to set the addressing mode: {:?}", e)))?;
+        let bus_num = i2c.bus();
+        // The selected address has to be sent again in the new mode
+        i2c_lock.slave_addresses.remove(&bus_num);
+        if enabled {
+            i2c_lock.addr_10bit_buses.insert(bus_num);
         } else {
-            Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("I2C bus is not opened"))
+            i2c_lock.addr_10bit_buses.remove(&bus_num);
         }
+        Ok(())
     }
 
-    #[pyo3(signature = (addr, command, write_data, read_length))]
-    fn block_write_read<'py>(&self, py: Python<'py>, addr: u16, command: u8, write_data: &Bound<'py, PyBytes>, read_length: usize) -> PyResult<Bound<'py,
-        PyBytes>> {
-        self.block_write(addr, command, write_data)?;
-        self.block_read(py, addr, write_data.as_bytes()[0], read_length)
+    /// Gets whether 10-bit addressing is enabled.
+    ///
+    /// Parameters:
+    /// - `bus` (int): The I2C bus to check (default is the first bus opened).
+    ///
+    /// Returns:
+    /// - `bool`: True if 10-bit addressing is enabled.
+    ///
+    /// Example usage:
+    /// ```python
+    /// enabled = i2c_manager.get_addr_10bit()
+    /// ```
+    #[pyo3(signature = (bus = None))]
+    fn get_addr_10bit(&self, bus: Option<u8>) -> PyResult<bool> {
+        let mut i2c_lock = self.i2c.lock().unwrap();
+        let bus_num = i2c_lock.get(bus)?.bus();
+        Ok(i2c_lock.addr_10bit_enabled(bus_num))
+    }
+
+    /// Performs a combined transaction, the segments are sent with a repeated start between them and a single stop at
+    /// the end, so no other master can access the device in the middle of the transaction.
+    ///
+    /// Parameters:
+    /// - `addr` (int): The I2C slave address.
+    /// - `segments` (list[I2CWrite | I2CRead]): The segments of the transaction, up to 42 segments can be used.
+    /// - `bus` (int): The I2C bus to use (default is the first bus opened).
+    ///
+    /// Returns:
+    /// - `list[bytes]`: The data read by every read segment, in order.
+    ///
+    /// Example usage:
+    /// ```python
+    /// [data] = i2c_manager.transaction(0x50, [gpio_manager.I2CWrite(b'\x00\x10'), gpio_manager.I2CRead(16)])
+    /// ```
+    #[pyo3(signature = (addr, segments, bus = None))]
+    fn transaction<'py>(&self, py: Python<'py>, addr: u16, segments: Vec<Bound<'py, PyAny>>, bus: Option<u8>) -> PyResult<Vec<Bound<'py, PyBytes>>> {
+        let segments = extract_segments(&segments)?;
+        let data = with_buses(&self.i2c, |i2c_lock| i2c_lock.transaction(bus, addr, &segments))?;
+        Ok(data.iter().map(|buf| PyBytes::new(py, buf)).collect())
+    }
+
+    /// Starts a background thread that periodically reads a register block of a device and passes the data to the
+    /// callback. The reads run in Rust, so the GIL is only taken to call the callback.
+    ///
+    /// Parameters:
+    /// - `addr` (int): The I2C slave address.
+    /// - `command` (int): The register to read.
+    /// - `length` (int): The number of bytes to read.
+    /// - `interval_ms` (float): The time between reads in milliseconds.
+    /// - `callback` (callable): The function called with the data read as its first argument.
+    /// - `args` (tuple): Additional arguments passed to the callback after the data (default is None).
+    /// - `on_change_only` (bool): Only call the callback when the data differs from the previous read
+    ///   (default is True).
+    /// - `bus` (int): The I2C bus to use (default is the first bus opened). The bus must be open.
+    ///
+    /// Returns:
+    /// - `int`: The id of the poller, used to stop it.
+    ///
+    /// Example usage:
+    /// ```python
+    /// def on_data(data):
+    ///     print(data)
+    ///
+    /// poll_id = i2c_manager.poll(0x48, 0x00, 2, 10, on_data)
+    /// ```
+    #[pyo3(signature = (addr, command, length, interval_ms, callback, args = None, on_change_only = true, bus = None))]
+    #[allow(clippy::too_many_arguments)]
+    fn poll(&self, py: Python, addr: u16, command: u8, length: usize, interval_ms: f64, callback: PyObject, args: Option<&Bound<'_, PyTuple>>,
+            on_change_only: bool, bus: Option<u8>) -> PyResult<u32> {
+        if interval_ms <= 0f64 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Interval must be greater than 0, The value {} does not meet this condition",
+                                                                                interval_ms)));
+        }
+        let interval = ms_to_duration_checked("Interval", interval_ms)?;
+        if !callback.bind(py).is_callable() {
+            return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>("Object is not callable"));
+        }
+        let bus_num = self.i2c.lock().unwrap().get(bus)?.bus();
+        let args: Py<PyTuple> = match args {
+            Some(args) => args.clone().unbind(),
+            None => PyTuple::empty(py).unbind(),
+        };
+        let buses = Arc::clone(&self.i2c);
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+
+        let handle = thread::spawn(move || {
+            let mut last: Option<Vec<u8>> = None;
+            let mut next = Instant::now();
+            while !thread_stop.load(Ordering::SeqCst) {
+                let result = {
+                    let mut i2c_lock = metrics::lock(&buses);
+                    let retry = i2c_lock.retry_policy();
+                    i2c_lock.select(Some(bus_num), addr).and_then(|i2c| {
+                        let mut buf = vec![0u8; length];
+                        retry.run(|| i2c.block_read(command, &mut buf))
+                             .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to read data: {:?}", e)))?;
+                        Ok(buf)
+                    })
+                };
+                metrics::count_i2c(&result);
+                match result {
+                    Ok(data) => {
+                        if !on_change_only || last.as_ref() != Some(&data) {
+                            Python::with_gil(|py| {
+                                let mut call_args: Vec<PyObject> = vec![PyBytes::new(py, &data).into_any().unbind()];
+                                call_args.extend(args.bind(py).iter().map(|item| item.unbind()));
+                                if let Err(e) = PyTuple::new(py, call_args).and_then(|call_args| callback.call1(py, call_args)) {
+                                    log_module::exception(&format!("Exception in the poll callback of I2C device 0x{:02x}", addr), &e);
+                                }
+                            });
+                        }
+                        last = Some(data);
+                    }
+                    Err(e) => log_module::warning(&format!("Polling I2C device 0x{:02x} failed: {}", addr, e)),
+                }
+
+                // Keep a fixed rate, but don't try to catch up on reads that were missed
+                next += interval;
+                let now = Instant::now();
+                if next < now {
+                    next = now;
+                }
+                while !thread_stop.load(Ordering::SeqCst) && Instant::now() < next {
+                    thread::park_timeout(next - Instant::now());
+                }
+            }
+        });
+
+        let mut pollers_lock = self.pollers.lock().unwrap();
+        let id = pollers_lock.next_id;
+        pollers_lock.next_id += 1;
+        pollers_lock.pollers.insert(id, I2CPoller {
+            bus: bus_num,
+            stop,
+            handle,
+        });
+        Ok(id)
+    }
+
+    /// Stops a poller started with `poll`.
+    ///
+    /// Parameters:
+    /// - `poll_id` (int): The id returned by `poll`.
+    ///
+    /// Example usage:
+    /// ```python
+    /// i2c_manager.stop_poll(poll_id)
+    /// ```
+    #[pyo3(signature = (poll_id))]
+    fn stop_poll(&self, py: Python, poll_id: u32) -> PyResult<()> {
+        let poller = self.pollers.lock().unwrap().pollers.remove(&poll_id);
+        match poller {
+            Some(poller) => {
+                poller.stop(py);
+                Ok(())
+            }
+            None => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Poller {} is not running", poll_id))),
+        }
+    }
+
+    /// Recovers a bus where a slave is holding SDA low, for example after the master was interrupted in the middle of a
+    /// read. The pins are temporarily taken as GPIO, up to 9 clock pulses are sent until the slave releases SDA, then a
+    /// STOP condition is issued and the I2C function of the pins is restored.
+    ///
+    /// Parameters:
+    /// - `bus` (int): The I2C bus to recover (default is the first bus opened).
+    /// - `scl_pin` (int): The GPIO pin used as SCL (default is 3 for bus 1 and 1 for bus 0).
+    /// - `sda_pin` (int): The GPIO pin used as SDA (default is 2 for bus 1 and 0 for bus 0).
+    ///
+    /// Returns:
+    /// - `bool`: True if SDA was released.
+    ///
+    /// Example usage:
+    /// ```python
+    /// if not i2c_manager.recover_bus(bus=1):
+    ///     print("SDA is still held low")
+    /// ```
+    #[pyo3(signature = (bus = None, scl_pin = None, sda_pin = None))]
+    fn recover_bus(&self, bus: Option<u8>, scl_pin: Option<u8>, sda_pin: Option<u8>) -> PyResult<bool> {
+        // Holding the lock keeps other threads from using the bus during the recovery
+        with_buses(&self.i2c, |i2c_lock| {
+            let bus_num = match bus {
+                Some(bus_num) => bus_num,
+                None => i2c_lock.get(None)?.bus(),
+            };
+            let (default_scl, default_sda) = match bus_num {
+                0 => (Some(1), Some(0)),
+                1 => (Some(3), Some(2)),
+                _ => (None, None),
+            };
+            let (scl_pin, sda_pin) = match (scl_pin.or(default_scl), sda_pin.or(default_sda)) {
+                (Some(scl_pin), Some(sda_pin)) => (scl_pin, sda_pin),
+                _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The SCL and SDA pins of bus {} must be given", bus_num))),
+            };
+
+            let gpio = Gpio::new().map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?;
+            let get_pin = |pin: u8| {
+                gpio.get(pin)
+                    .map(|pin| pin.into_io(Mode::Input))
+                    .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to take pin {} as GPIO: {:?}", pin, e)))
+            };
+            // The pins switch back to their I2C alternate function when they are dropped
+            let mut scl = get_pin(scl_pin)?;
+            let mut sda = get_pin(sda_pin)?;
+            Ok(clock_out_stuck_slave(&mut scl, &mut sda))
+        })
+    }
+
+    /// Scans the I2C bus for devices by probing every address in the given range.
+    ///
+    /// Parameters:
+    /// - `start` (int): The first address to probe (default is 0x03).
+    /// - `end` (int): The last address to probe, up to 0x3FF when 10-bit addressing is enabled (default is 0x77).
+    /// - `probe_mode` (I2CProbeMode): How each address is probed. AUTO uses a read for the EEPROM (0x50 - 0x5F) and
+    ///   0x30 - 0x37 ranges and a quick write everywhere else, like i2cdetect (default is AUTO).
+    /// - `bus` (int): The I2C bus to scan (default is the first bus opened).
+    ///
+    /// Returns:
+    /// - `list[int]`: The addresses of the devices that responded.
+    ///
+    /// Example usage:
+    /// ```python
+    /// devices = i2c_manager.scan()
+    /// ```
+    #[pyo3(signature = (start = 0x03, end = 0x77, probe_mode = I2CProbeMode::AUTO, bus = None))]
+    pub(crate) fn scan(&self, start: u16, end: u16, probe_mode: I2CProbeMode, bus: Option<u8>) -> PyResult<Vec<u16>> {
+        with_buses(&self.i2c, |i2c_lock| {
+            let bus_num = i2c_lock.get(bus)?.bus();
+            let max_addr = if i2c_lock.addr_10bit_enabled(bus_num) { 0x3FF } else { 0x7F };
+            if start > end || end > max_addr {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid address range {:#04x} - {:#04x}", start, end)));
+            }
+            let mut found = Vec::new();
+            for addr in start..=end {
+                // Reserved addresses are rejected by the kernel, so they can never respond
+                let i2c = match i2c_lock.select(bus, addr) {
+                    Ok(i2c) => i2c,
+                    Err(_) => continue,
+                };
+                let use_read = match probe_mode {
+                    I2CProbeMode::AUTO => (0x30..=0x37).contains(&addr) || (0x50..=0x5F).contains(&addr),
+                    I2CProbeMode::QUICK_WRITE => false,
+                    I2CProbeMode::READ => true,
+                };
+                let responded = if use_read {
+                    let mut buf = [0u8; 1];
+                    i2c.read(&mut buf).is_ok()
+                } else {
+                    i2c.smbus_quick_command(false).is_ok()
+                };
+                if responded {
+                    found.push(addr);
+                }
+            }
+            Ok(found)
+        })
     }
 }
@@ -1,14 +1,135 @@
+// Without the python feature only the parts of the library reached by the hal module are used
+#![cfg_attr(not(feature = "python"), allow(dead_code))]
+
+pub mod error;
+pub mod hal;
+#[cfg(feature = "python")]
 mod gpio_module;
+mod board_module;
+mod overlay_module;
+#[cfg(feature = "python")]
 mod pwm_module;
+#[cfg(feature = "python")]
+mod pwm_output_module;
+#[cfg(feature = "python")]
+mod pwm_ramp_module;
+#[cfg(feature = "python")]
+mod complementary_pwm_module;
+#[cfg(feature = "python")]
+mod fan_module;
+#[cfg(feature = "python")]
+mod servo_module;
+#[cfg(feature = "python")]
+mod pid_module;
+#[cfg(feature = "python")]
+mod touch_module;
+#[cfg(feature = "python")]
+mod melody_module;
+#[cfg(feature = "python")]
+mod pin_proxy_module;
+mod timing;
+#[cfg(feature = "python")]
+mod soft_pwm_engine;
+#[cfg(feature = "python")]
+mod waveform_module;
+#[cfg(feature = "python")]
+mod scheduler_module;
+#[cfg(feature = "python")]
+mod capture_module;
+#[cfg(feature = "python")]
+mod event_logger;
+mod log_module;
+#[cfg(feature = "python")]
+mod metrics;
+#[cfg(feature = "mqtt")]
+mod mqtt_module;
+#[cfg(feature = "server")]
+mod event_server;
+#[cfg(feature = "server")]
+mod remote_module;
+#[cfg(feature = "server")]
+mod daemon_module;
+#[cfg(feature = "http")]
+mod http_module;
+#[cfg(feature = "python")]
 mod i2c_module;
+mod i2c_ioctl;
+#[cfg(feature = "python")]
+mod i2c_device_module;
+#[cfg(feature = "python")]
+mod soft_i2c_module;
+#[cfg(feature = "python")]
+mod soft_spi_module;
+#[cfg(feature = "python")]
+mod spi_device_module;
+#[cfg(feature = "python")]
+mod soft_uart_module;
+#[cfg(feature = "python")]
+mod modbus_module;
+#[cfg(feature = "python")]
+mod register_map_module;
+#[cfg(feature = "python")]
+mod smbus_module;
 mod pinctrl;
+mod pads;
+#[cfg(feature = "python")]
+mod dma;
+#[cfg(feature = "python")]
+mod eeprom_module;
+#[cfg(feature = "python")]
+mod rtc_module;
+#[cfg(feature = "python")]
+mod rpi_gpio_module;
+#[cfg(feature = "python")]
+mod gpiozero_module;
+#[cfg(feature = "python")]
+mod cli_module;
+#[cfg(feature = "python")]
+mod config_module;
+mod gpiochip;
+#[cfg(feature = "python")]
+mod gpiochip_module;
+#[cfg(feature = "python")]
+mod backend_module;
+mod backend;
+mod rppal_backend;
+mod gpiochip_backend;
+mod mock_backend;
+#[cfg(feature = "python")]
+mod mock_module;
+#[cfg(feature = "python")]
+mod shortcut_module;
+#[cfg(feature = "python")]
+mod defaults_module;
+#[cfg(feature = "python")]
+mod diagnose_module;
+#[cfg(feature = "python")]
+mod bench_module;
+#[cfg(feature = "python")]
+mod watchdog_module;
+#[cfg(feature = "python")]
+mod debounce_timer;
 
 
+pub use error::GpioError;
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
+#[cfg(feature = "python")]
 use pyo3::PyObject;
-use rppal::gpio::{InputPin, OutputPin};
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+use backend::OutputPin;
+#[cfg(feature = "python")]
+use backend::InputPin;
+use rppal::gpio::{Bias, Level, Trigger};
+use rppal::pwm::Polarity;
+#[cfg(feature = "python")]
 use std::collections::HashMap;
+#[cfg(feature = "python")]
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+#[cfg(feature = "python")]
+use std::time::Instant;
 
 pub fn compute_pwm_values(frequency_hz: &Option<f64>, duty_cycle: &Option<f64>, period_ms: &Option<f64>, pulse_width_ms: &Option<f64>) -> (f64, f64) {
     let frequency = match period_ms {
@@ -56,50 +177,473 @@ pub fn compute_pwm_values(frequency_hz: &Option<f64>, duty_cycle: &Option<f64>,
 
     (frequency, duty_cycle_percent)
 }
-pub fn check_pwm_values(frequency_hz: &Option<f64>, duty_cycle: &Option<f64>, period_ms: &Option<f64>, pulse_width_ms: &Option<f64>) -> PyResult<()> {
+pub fn check_pwm_values(frequency_hz: &Option<f64>, duty_cycle: &Option<f64>, period_ms: &Option<f64>, pulse_width_ms: &Option<f64>) -> Result<(), GpioError> {
     if duty_cycle.is_some() && (duty_cycle.unwrap() > 100f64 || duty_cycle.unwrap() < 0f64) {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Duty cycle must be between 0 and 100, The value {} does not meet this condition", duty_cycle.unwrap())));
+        return Err(GpioError::InvalidArgument(format!("Duty cycle must be between 0 and 100, The value {} does not meet this condition", duty_cycle.unwrap())));
     }
     if period_ms.is_some() && period_ms.unwrap() < 0f64 {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Period must be greater than 0, The value {} does not meet this condition", period_ms.unwrap())));
+        return Err(GpioError::InvalidArgument(format!("Period must be greater than 0, The value {} does not meet this condition", period_ms.unwrap())));
     }
     if pulse_width_ms.is_some() && pulse_width_ms.unwrap() < 0f64 {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Pulse width must be greater than 0, The value {} does not meet this condition", pulse_width_ms.unwrap())));
+        return Err(GpioError::InvalidArgument(format!("Pulse width must be greater than 0, The value {} does not meet this condition", pulse_width_ms.unwrap())));
     }
     if pulse_width_ms.is_some() && period_ms.is_some() && pulse_width_ms.unwrap() > period_ms.unwrap() {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Pulse width must be less than the period, The value {} does not meet this condition", pulse_width_ms.unwrap())));
+        return Err(GpioError::InvalidArgument(format!("Pulse width must be less than the period, The value {} does not meet this condition", pulse_width_ms.unwrap())));
     }
     if frequency_hz.is_some() && frequency_hz.unwrap() < 0f64 {
-        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Frequency must be greater than 0, The value {} does not meet this condition", frequency_hz.unwrap())));
+        return Err(GpioError::InvalidArgument(format!("Frequency must be greater than 0, The value {} does not meet this condition", frequency_hz.unwrap())));
     }
     Ok(())
 }
 
-#[derive(Debug)]
+/// Runs a blocking operation with the GIL released, so other Python threads keep running while it waits on the hardware
+/// or on a child process.
+#[cfg(feature = "python")]
+pub fn release_gil<T: Send>(op: impl FnOnce() -> T + Send) -> T {
+    Python::with_gil(|py| py.allow_threads(op))
+}
+
+/// Runs a blocking operation, without Python there is no GIL to release.
+#[cfg(not(feature = "python"))]
+pub fn release_gil<T: Send>(op: impl FnOnce() -> T + Send) -> T {
+    op()
+}
+
+// The longest time accepted by the API, so adding it to the current Instant never overflows
+#[cfg(feature = "python")]
+const MAX_TIME: Duration = Duration::from_secs(u32::MAX as u64);
+
+/// Converts a time given to the API to a Duration, `per_second` being the number of its units in a second. The time
+/// must be a finite number of 0 or more up to MAX_TIME, Duration::from_secs_f64 panics on NaN, infinite and negative
+/// values and on values that don't fit in a Duration.
+#[cfg(feature = "python")]
+fn checked_duration(name: &str, value: f64, per_second: f64) -> Result<Duration, GpioError> {
+    if !value.is_finite() || value < 0f64 {
+        return Err(GpioError::InvalidArgument(format!("{} must be a finite number of 0 or more, The value {} does not meet this condition", name, value)));
+    }
+    match Duration::try_from_secs_f64(value / per_second) {
+        Ok(duration) if duration <= MAX_TIME => Ok(duration),
+        _ => Err(GpioError::InvalidArgument(format!("{} must be at most {} seconds, The value {} does not meet this condition", name, MAX_TIME.as_secs(), value))),
+    }
+}
+
+/// Converts a time in milliseconds given to the API to a Duration, the error names the parameter.
+#[cfg(feature = "python")]
+pub(crate) fn ms_to_duration_checked(name: &str, ms: f64) -> Result<Duration, GpioError> {
+    checked_duration(name, ms, 1000f64)
+}
+
+/// Converts a time in microseconds given to the API to a Duration, the error names the parameter.
+#[cfg(feature = "python")]
+pub(crate) fn us_to_duration_checked(name: &str, us: f64) -> Result<Duration, GpioError> {
+    checked_duration(name, us, 1_000_000f64)
+}
+
+/// Converts a frequency given to the API to its period. The frequency must be a finite number greater than 0, whose
+/// period is at most MAX_TIME.
+#[cfg(feature = "python")]
+pub(crate) fn frequency_to_period_checked(name: &str, frequency_hz: f64) -> Result<Duration, GpioError> {
+    if !frequency_hz.is_finite() || frequency_hz <= 0f64 {
+        return Err(GpioError::InvalidArgument(format!("{} must be a finite number greater than 0, The value {} does not meet this condition", name, frequency_hz)));
+    }
+    match Duration::try_from_secs_f64(1f64 / frequency_hz) {
+        Ok(period) if period <= MAX_TIME => Ok(period),
+        _ => Err(GpioError::InvalidArgument(format!("{} must have a period of at most {} seconds, The value {} does not meet this condition", name, MAX_TIME.as_secs(), frequency_hz))),
+    }
+}
+
+#[cfg(feature = "python")]
+#[derive(Clone, Debug)]
 struct Callback {
     callable: Arc<Mutex<PyObject>>,
     trigger_edge: TriggerEdge,
     args: Arc<Mutex<PyObject>>,
+    kwargs: Option<Arc<Py<PyDict>>>,
     send_time: bool,
     send_edge: bool,
+    send_state: bool,
+    /// Callbacks with a higher priority are called first, callbacks of the same priority in the order they were added.
+    priority: i32,
+    /// Whether `callable` is a weak reference to the function, so the callback doesn't keep its object alive.
+    weak: bool,
+}
+
+
+#[cfg(feature = "python")]
+impl Callback {
+    /// Returns the function to call, or None once the object of a weak callback was garbage collected.
+    fn target(&self, py: Python) -> PyResult<Option<PyObject>> {
+        let callable = self.callable.lock().unwrap().clone_ref(py);
+        if !self.weak {
+            return Ok(Some(callable));
+        }
+        let target = callable.call0(py)?;
+        Ok(if target.is_none(py) { None } else { Some(target) })
+    }
+
+    /// Returns whether the callback calls the function. A weak callback matches functions that compare equal, since
+    /// every access to a bound method creates a new method object.
+    fn calls(&self, py: Python, function: &PyObject) -> PyResult<bool> {
+        if !self.weak {
+            return Ok(self.callable.lock().unwrap().is(function));
+        }
+        match self.target(py)? {
+            Some(target) => target.bind(py).eq(function),
+            None => Ok(false),
+        }
+    }
+}
+
+/// How the edges of a pin with callbacks are filtered.
+#[cfg(feature = "python")]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum DebounceFilter {
+    /// The gpiochip character device filters the edges, in the GPIO controller where it supports debouncing and in the
+    /// kernel otherwise.
+    Hardware,
+    /// The first edge is reported right away and the edges following it within the period are dropped.
+    DeadTime,
+    /// An edge is reported once the line held its new level for the period.
+    Stable,
+    /// The time the line spends high counts up and the time it spends low counts down, between 0 and the period. The
+    /// level reported turns high when the count reaches the period and low when it reaches 0, so short glitches only
+    /// delay an edge instead of restarting it.
+    Integrator,
+}
+
+
+#[cfg(feature = "python")]
+impl DebounceFilter {
+    fn name(self) -> &'static str {
+        match self {
+            DebounceFilter::Hardware => "hardware",
+            DebounceFilter::DeadTime => "software",
+            DebounceFilter::Stable => "stable",
+            DebounceFilter::Integrator => "integrator",
+        }
+    }
+}
+
+
+/// How the edges of a pin with callbacks are debounced. The levels are the levels of the line, edges are given as
+/// whether the line rose and the time of the edge on the monotonic clock.
+#[cfg(feature = "python")]
+#[derive(Clone, Copy, Debug)]
+struct Debounce {
+    period: Duration,
+    filter: DebounceFilter,
+    last_event: Option<Duration>,
+    /// Level reported to the callbacks, unknown until the first edge.
+    level: Option<bool>,
+    raw_level: bool,
+    /// Count of the integrator, and the time it was last brought up to date.
+    count: Duration,
+    counted_at: Duration,
+    /// Generation of the last edge, a pending settle is dropped once a newer edge arrived.
+    generation: u64,
+}
+
+
+/// What the debounce filter makes of an edge of the line.
+#[cfg(feature = "python")]
+struct Filtered {
+    /// The edge reported to the callbacks right away.
+    edge: Option<(bool, Duration)>,
+    /// The time after which the filter settles when no other edge arrives, its edge is then taken with `settle`.
+    settle_after: Option<Duration>,
+}
+
+
+#[cfg(feature = "python")]
+impl Debounce {
+    fn new(period: Duration, filter: &str) -> PyResult<Self> {
+        let filter = match filter {
+            "hardware" => DebounceFilter::Hardware,
+            "software" => DebounceFilter::DeadTime,
+            "stable" => DebounceFilter::Stable,
+            "integrator" => DebounceFilter::Integrator,
+            _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Filter must be \"hardware\", \"software\", \"stable\" or \"integrator\", The value {} does not meet this condition", filter))),
+        };
+        Ok(Self { period, filter, last_event: None, level: None, raw_level: false, count: Duration::ZERO, counted_at: Duration::ZERO, generation: 0 })
+    }
+
+    /// The debounce time handed to the character device.
+    fn kernel_period(&self) -> Option<Duration> {
+        if self.filter == DebounceFilter::Hardware { Some(self.period) } else { None }
+    }
+
+    /// Brings the integrator up to the timestamp, returning the edge of the reported level when the count reached a
+    /// bound.
+    fn integrate(&mut self, timestamp: Duration) -> Option<(bool, Duration)> {
+        let elapsed = timestamp.saturating_sub(self.counted_at);
+        self.counted_at = self.counted_at.max(timestamp);
+        let (count, bound_at) = if self.raw_level {
+            ((self.count + elapsed).min(self.period), timestamp - elapsed + (self.period - self.count))
+        } else {
+            (self.count.saturating_sub(elapsed), timestamp - elapsed + self.count)
+        };
+        self.count = count;
+        let reached = if self.raw_level { count == self.period } else { count.is_zero() };
+        if reached && self.level != Some(self.raw_level) {
+            self.level = Some(self.raw_level);
+            return Some((self.raw_level, bound_at));
+        }
+        None
+    }
+
+    /// Filters an edge of the line.
+    fn edge(&mut self, rising: bool, timestamp: Duration) -> Filtered {
+        self.generation += 1;
+        match self.filter {
+            DebounceFilter::Hardware => Filtered { edge: Some((rising, timestamp)), settle_after: None },
+            DebounceFilter::DeadTime => {
+                if self.last_event.is_some_and(|last_event| timestamp.saturating_sub(last_event) < self.period) {
+                    return Filtered { edge: None, settle_after: None };
+                }
+                self.last_event = Some(timestamp);
+                Filtered { edge: Some((rising, timestamp)), settle_after: None }
+            }
+            DebounceFilter::Stable => {
+                // The line was at the other level before its first edge
+                let level = *self.level.get_or_insert(!rising);
+                self.raw_level = rising;
+                self.last_event = Some(timestamp);
+                Filtered { edge: None, settle_after: (level != rising).then_some(self.period) }
+            }
+            DebounceFilter::Integrator => {
+                let edge = match self.level {
+                    Some(_) => self.integrate(timestamp),
+                    None => {
+                        // The line held the other level before its first edge, so the count starts at its bound
+                        self.level = Some(!rising);
+                        self.count = if rising { Duration::ZERO } else { self.period };
+                        self.counted_at = timestamp;
+                        None
+                    }
+                };
+                self.raw_level = rising;
+                let settle_after = match self.level {
+                    Some(level) if level != rising => Some(if rising { self.period - self.count } else { self.count }),
+                    _ => None,
+                };
+                Filtered { edge, settle_after }
+            }
+        }
+    }
+
+    /// Returns the edge of the reported level once the filter settled after the edge of the generation, or None when
+    /// a newer edge arrived since.
+    fn settle(&mut self, generation: u64) -> Option<(bool, Duration)> {
+        if generation != self.generation {
+            return None;
+        }
+        match self.filter {
+            DebounceFilter::Stable => {
+                if self.level == Some(self.raw_level) {
+                    return None;
+                }
+                self.level = Some(self.raw_level);
+                self.last_event.map(|timestamp| (self.raw_level, timestamp))
+            }
+            DebounceFilter::Integrator => {
+                let bound_at = self.counted_at + if self.raw_level { self.period - self.count } else { self.count };
+                self.integrate(bound_at)
+            }
+            DebounceFilter::Hardware | DebounceFilter::DeadTime => None,
+        }
+    }
 }
 
-pub struct PinManager {
+
+/// The pins of the GPIOManager and everything it tracks about them, shared by its handles.
+#[cfg(feature = "python")]
+pub struct PinRegistry {
     input_pins: HashMap<u8, Arc<Mutex<Pin>>>,
     output_pins: HashMap<u8, Arc<Mutex<Pin>>>,
     callbacks: HashMap<u8, Vec<Callback>>,
+    /// Debouncing of the interrupt of pins with callbacks, kept so the interrupt can be set up again when a pin
+    /// switches back to an input.
+    debounce: HashMap<u8, Debounce>,
     pwm_setup: HashMap<u8, PwmConfig>,
+    /// Clock and resolution of the trigger times passed to callbacks.
+    timestamp_clock: TimestampClock,
+    timestamp_ns: bool,
+    /// Names of the pins set up by load_config.
+    aliases: HashMap<String, u8>,
+    defaults: Defaults,
+    /// Activity of the pins since they were set up, returned by pin_stats.
+    stats: HashMap<u8, PinStats>,
+}
+
+
+/// The arguments the methods of the GPIOManager fall back to when they are omitted, changed with set_defaults.
+#[cfg(feature = "python")]
+#[derive(Clone, Copy)]
+struct Defaults {
+    debounce_ms: f64,
+    pull: InternPullResistorState,
+    logic_level: LogicLevel,
+    /// Whether the pins are reset when the interpreter exits.
+    reset_on_exit: bool,
+}
+
+
+#[cfg(feature = "python")]
+impl Default for Defaults {
+    fn default() -> Self {
+        Defaults { debounce_ms: 2f64, pull: InternPullResistorState::AUTO, logic_level: LogicLevel::HIGH, reset_on_exit: false }
+    }
+}
+
+
+/// The activity of a pin, for finding chattering inputs and stuck outputs.
+#[cfg(feature = "python")]
+#[derive(Clone, Copy)]
+struct PinStats {
+    writes: u64,
+    reads: u64,
+    interrupts: u64,
+    callbacks: u64,
+    /// The last state written, read or reported by an edge, and when the pin changed to it.
+    state: Option<PinState>,
+    last_change: Option<Instant>,
+    /// When the pin was set up or its counters were last reset.
+    since: Instant,
 }
 
 
+#[cfg(feature = "python")]
+impl PinStats {
+    fn new(state: Option<PinState>) -> Self {
+        let now = Instant::now();
+        PinStats { writes: 0, reads: 0, interrupts: 0, callbacks: 0, state, last_change: state.map(|_| now), since: now }
+    }
+
+    /// Records a state of the pin, the time of the change is kept when it differs from the last state.
+    fn observe(&mut self, state: PinState) {
+        if self.state != Some(state) {
+            self.state = Some(state);
+            self.last_change = Some(Instant::now());
+        }
+    }
+}
+
+
+#[cfg(feature = "python")]
 struct PwmConfig {
     frequency: f64,
     duty_cycle: f64,
     logic_level: LogicLevel,
     is_active: bool,
+    gamma: Option<GammaCurve>,
+    /// Phase offset as a fraction of the period, pins with a phase are driven by the soft PWM engine.
+    phase: Option<f64>,
+    /// Whether the pin is driven by the soft PWM engine instead of rppal.
+    high_resolution: bool,
+}
+
+
+#[cfg(feature = "python")]
+impl PwmConfig {
+    /// The phase offset of pins driven by the soft PWM engine, None for pins driven by rppal.
+    fn engine_phase(&self) -> Option<f64> {
+        match self.phase {
+            Some(phase) => Some(phase),
+            None if self.high_resolution => Some(0f64),
+            None => None,
+        }
+    }
+
+    /// The duty cycle driven on the pin, with the gamma curve applied.
+    fn output_duty_cycle(&self) -> f64 {
+        match &self.gamma {
+            Some(gamma) => gamma.apply(self.duty_cycle),
+            None => self.duty_cycle,
+        }
+    }
+
+    /// The duty cycle programmed into the pin, inverted for pins with a low logic level.
+    fn pin_duty_cycle(&self) -> f64 {
+        if self.logic_level == LogicLevel::LOW {
+            100f64 - self.output_duty_cycle()
+        } else {
+            self.output_duty_cycle()
+        }
+    }
 }
 
 
+/// Curve mapping a perceived brightness to the duty cycle programmed into the hardware, both from 0 to 100.
+#[cfg(feature = "python")]
+#[derive(Clone, Debug)]
+pub enum GammaCurve {
+    Exponent(f64),
+    /// Duty cycles for brightness values evenly spaced from 0 to 100, interpolated in between.
+    Table(Vec<f64>),
+}
+
+
+#[cfg(feature = "python")]
+impl GammaCurve {
+    /// Builds a curve from an exponent or a lookup table, returning None if neither is given.
+    pub fn from_args(gamma: Option<f64>, lut: Option<Vec<f64>>) -> PyResult<Option<Self>> {
+        match (gamma, lut) {
+            (None, None) => Ok(None),
+            (Some(_), Some(_)) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Only one of gamma and lut can be set")),
+            (Some(gamma), None) => {
+                if gamma <= 0f64 {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Gamma must be greater than 0, The value {} does not meet this condition", gamma)));
+                }
+                Ok(Some(GammaCurve::Exponent(gamma)))
+            }
+            (None, Some(lut)) => {
+                if lut.len() < 2 {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("The lookup table must have at least 2 entries"));
+                }
+                if lut.iter().any(|duty_cycle| !(0f64..=100f64).contains(duty_cycle)) {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("The lookup table entries must be between 0 and 100"));
+                }
+                if lut.windows(2).any(|pair| pair[1] < pair[0]) {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("The lookup table entries must not decrease"));
+                }
+                Ok(Some(GammaCurve::Table(lut)))
+            }
+        }
+    }
+
+    /// Converts a brightness into the duty cycle to program.
+    pub fn apply(&self, brightness: f64) -> f64 {
+        match self {
+            GammaCurve::Exponent(gamma) => 100f64 * (brightness / 100f64).powf(*gamma),
+            GammaCurve::Table(lut) => {
+                let position = brightness / 100f64 * (lut.len() - 1) as f64;
+                let index = (position.floor() as usize).min(lut.len() - 2);
+                lut[index] + (lut[index + 1] - lut[index]) * (position - index as f64)
+            }
+        }
+    }
+
+    /// Converts a programmed duty cycle back into the brightness producing it.
+    pub fn invert(&self, duty_cycle: f64) -> f64 {
+        match self {
+            GammaCurve::Exponent(gamma) => 100f64 * (duty_cycle / 100f64).powf(1f64 / *gamma),
+            GammaCurve::Table(lut) => {
+                let step = 100f64 / (lut.len() - 1) as f64;
+                match lut.windows(2).position(|pair| duty_cycle <= pair[1]) {
+                    Some(index) if lut[index + 1] > lut[index] => {
+                        let fraction = ((duty_cycle - lut[index]) / (lut[index + 1] - lut[index])).max(0f64);
+                        step * (index as f64 + fraction)
+                    }
+                    Some(index) => step * index as f64,
+                    None => 100f64,
+                }
+            }
+        }
+    }
+}
+
+
+#[cfg(feature = "python")]
 #[derive(Clone)]
 enum PinType {
     Input(Arc<Mutex<InputPin>>),
@@ -107,14 +651,18 @@ enum PinType {
 }
 
 
+#[cfg(feature = "python")]
 #[derive(Clone)]
 struct Pin {
     pin: PinType,
     logic_level: LogicLevel,
+    output_mode: OutputMode,
+    /// The pull resistor asked for, AUTO for output pins.
+    pull: InternPullResistorState,
 }
 
 
-#[pyclass(eq)]
+#[cfg_attr(feature = "python", pyclass(eq))]
 #[derive(Clone, Copy, Debug, PartialEq)]
 /// Enum representing the GPIO pin state types.
 pub enum InternPullResistorState {
@@ -125,7 +673,20 @@ pub enum InternPullResistorState {
 }
 
 
-#[pyclass(eq)]
+impl InternPullResistorState {
+    /// The pull resistor of a pin with the given logic level, AUTO pulls the pin to its inactive level.
+    pub(crate) fn bias(self, logic_level: LogicLevel) -> Bias {
+        match self {
+            InternPullResistorState::PULLUP => Bias::PullUp,
+            InternPullResistorState::PULLDOWN => Bias::PullDown,
+            InternPullResistorState::EXTERNAL => Bias::Off,
+            InternPullResistorState::AUTO => if logic_level == LogicLevel::HIGH { Bias::PullDown } else { Bias::PullUp },
+        }
+    }
+}
+
+
+#[cfg_attr(feature = "python", pyclass(eq))]
 #[derive(Clone, Copy, Debug, PartialEq)]
 /// Enum representing the GPIO pin state types.
 pub enum PinState {
@@ -134,7 +695,7 @@ pub enum PinState {
 }
 
 
-#[pyclass(eq)]
+#[cfg_attr(feature = "python", pyclass(eq))]
 #[derive(Clone, Copy, Debug, PartialEq)]
 /// Enum representing the GPIO pin state types.
 pub enum LogicLevel {
@@ -143,7 +704,68 @@ pub enum LogicLevel {
 }
 
 
-#[pyclass(eq)]
+impl LogicLevel {
+    /// The polarity of a PWM channel with the given logic level, LOW inverts the signal.
+    pub(crate) fn polarity(self) -> Polarity {
+        match self {
+            LogicLevel::HIGH => Polarity::Normal,
+            LogicLevel::LOW => Polarity::Inverse,
+        }
+    }
+}
+
+
+#[cfg_attr(feature = "python", pyclass(eq))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Enum representing the direction of a pin.
+pub enum PinDirection {
+    INPUT,
+    OUTPUT,
+}
+
+
+#[cfg_attr(feature = "python", pyclass(eq))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(non_camel_case_types)]
+/// Enum representing how an output pin drives its line.
+pub enum OutputMode {
+    PUSH_PULL,
+    OPEN_DRAIN,
+    OPEN_SOURCE,
+}
+
+
+impl OutputMode {
+    /// The level an output pin is taken with, the latch of open pins holds the only level they drive and the driver is
+    /// switched on and off from then on.
+    pub(crate) fn initial_level(self, high: bool) -> Level {
+        match self {
+            OutputMode::PUSH_PULL => if high { Level::High } else { Level::Low },
+            OutputMode::OPEN_DRAIN => Level::Low,
+            OutputMode::OPEN_SOURCE => Level::High,
+        }
+    }
+
+    /// Drives the physical level of an output pin the way the mode allows, open drain pins release the line instead of
+    /// driving it high and open source pins release it instead of driving it low.
+    pub(crate) fn drive(self, pin: &mut OutputPin, high: bool) -> backend::Result<()> {
+        let released = match self {
+            OutputMode::PUSH_PULL => {
+                pin.write(if high { Level::High } else { Level::Low });
+                return Ok(());
+            }
+            OutputMode::OPEN_DRAIN => high,
+            OutputMode::OPEN_SOURCE => !high,
+        };
+        if !released {
+            pin.write(if high { Level::High } else { Level::Low });
+        }
+        pin.set_output_enabled(!released)
+    }
+}
+
+
+#[cfg_attr(feature = "python", pyclass(eq))]
 #[derive(Clone, Copy, Debug, PartialEq)]
 /// Enum representing the GPIO pin state types.
 pub enum TriggerEdge {
@@ -153,15 +775,374 @@ pub enum TriggerEdge {
 }
 
 
-#[pymodule]
+impl TriggerEdge {
+    /// The physical edges of a pin with the given logic level matching the edge.
+    pub(crate) fn trigger(self, logic_level: LogicLevel) -> Trigger {
+        match (self, logic_level) {
+            (TriggerEdge::RISING, LogicLevel::HIGH) | (TriggerEdge::FALLING, LogicLevel::LOW) => Trigger::RisingEdge,
+            (TriggerEdge::FALLING, LogicLevel::HIGH) | (TriggerEdge::RISING, LogicLevel::LOW) => Trigger::FallingEdge,
+            (TriggerEdge::BOTH, _) => Trigger::Both,
+        }
+    }
+}
+
+
+#[cfg_attr(feature = "python", pyclass(eq))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Enum representing the clock of the trigger times passed to callbacks.
+pub enum TimestampClock {
+    MONOTONIC,
+    REALTIME,
+    BOOTTIME,
+}
+
+
+impl TimestampClock {
+    fn clock_id(self) -> libc::clockid_t {
+        match self {
+            TimestampClock::MONOTONIC => libc::CLOCK_MONOTONIC,
+            TimestampClock::REALTIME => libc::CLOCK_REALTIME,
+            TimestampClock::BOOTTIME => libc::CLOCK_BOOTTIME,
+        }
+    }
+
+    fn now_ns(self) -> i128 {
+        let mut now = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+        unsafe { libc::clock_gettime(self.clock_id(), &mut now) };
+        now.tv_sec as i128 * 1_000_000_000 + now.tv_nsec as i128
+    }
+
+    /// Converts an edge timestamp of the character device, which is taken from CLOCK_MONOTONIC, to nanoseconds of
+    /// this clock. Only the offset between the clocks is
+    /// read at the time of the call, so the result keeps the nanosecond resolution of the timestamp.
+    fn convert_monotonic(self, timestamp: Duration) -> i128 {
+        let timestamp = timestamp.as_nanos() as i128;
+        if self == TimestampClock::MONOTONIC {
+            return timestamp;
+        }
+        // The clock is read between two reads of the monotonic clock, to halve the error of the offset
+        let before = TimestampClock::MONOTONIC.now_ns();
+        let now = self.now_ns();
+        let after = TimestampClock::MONOTONIC.now_ns();
+        timestamp + now - (before + after) / 2
+    }
+}
+
+
+/// An enum the Python methods also accept by name, the names are compared ignoring case, underscores and dashes.
+#[cfg(feature = "python")]
+pub(crate) trait ArgEnum: Copy + 'static {
+    /// Describes the argument in errors, like "Pin state".
+    const DESCRIPTION: &'static str;
+    const NAMES: &'static [(&'static str, Self)];
+
+    /// Converts an int or a bool, only the enums of a level accept them.
+    fn from_int(_value: i64) -> Option<Self> {
+        None
+    }
+}
+
+
+/// An argument of a Python method taking an enum, which accepts the enum itself, one of its names or, for the enums of
+/// a level, an int or a bool, the way RPi.GPIO takes them.
+#[cfg(feature = "python")]
+#[derive(Clone, Copy, Debug)]
+pub struct EnumArg<T>(pub T);
+
+
+#[cfg(feature = "python")]
+impl<T> From<T> for EnumArg<T> {
+    fn from(value: T) -> Self {
+        EnumArg(value)
+    }
+}
+
+
+#[cfg(feature = "python")]
+impl<'py, T: ArgEnum + pyo3::PyClass> FromPyObject<'py> for EnumArg<T> {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        if let Ok(value) = ob.downcast::<T>() {
+            return Ok(EnumArg(*value.borrow()));
+        }
+        let names = T::NAMES.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ");
+        if let Ok(name) = ob.extract::<String>() {
+            let normalized = name.to_ascii_lowercase().replace(['_', '-'], "");
+            return T::NAMES.iter()
+                           .find(|(candidate, _)| candidate.replace('_', "") == normalized)
+                           .map(|(_, value)| EnumArg(*value))
+                           .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{} must be one of {}, The value {} does not meet this condition",
+                                                                                                   T::DESCRIPTION, names, name)));
+        }
+        if let Ok(number) = ob.extract::<i64>() {
+            return T::from_int(number)
+                .map(EnumArg)
+                .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{} must be one of {}, The value {} does not meet this condition",
+                                                                                        T::DESCRIPTION, names, number)));
+        }
+        Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>(format!("{} must be a {} or one of {}, The value {} does not meet this condition",
+                                                                   T::DESCRIPTION, T::NAME, names, ob)))
+    }
+}
+
+
+#[cfg(feature = "python")]
+impl ArgEnum for InternPullResistorState {
+    const DESCRIPTION: &'static str = "Pull resistor state";
+    const NAMES: &'static [(&'static str, Self)] = &[("pullup", InternPullResistorState::PULLUP), ("pulldown", InternPullResistorState::PULLDOWN),
+        ("external", InternPullResistorState::EXTERNAL), ("off", InternPullResistorState::EXTERNAL), ("auto", InternPullResistorState::AUTO)];
+}
+
+
+#[cfg(feature = "python")]
+impl ArgEnum for PinState {
+    const DESCRIPTION: &'static str = "Pin state";
+    const NAMES: &'static [(&'static str, Self)] = &[("high", PinState::HIGH), ("low", PinState::LOW)];
+
+    fn from_int(value: i64) -> Option<Self> {
+        match value {
+            1 => Some(PinState::HIGH),
+            0 => Some(PinState::LOW),
+            _ => None,
+        }
+    }
+}
+
+
+#[cfg(feature = "python")]
+impl ArgEnum for LogicLevel {
+    const DESCRIPTION: &'static str = "Logic level";
+    const NAMES: &'static [(&'static str, Self)] = &[("high", LogicLevel::HIGH), ("low", LogicLevel::LOW)];
+
+    fn from_int(value: i64) -> Option<Self> {
+        match value {
+            1 => Some(LogicLevel::HIGH),
+            0 => Some(LogicLevel::LOW),
+            _ => None,
+        }
+    }
+}
+
+
+#[cfg(feature = "python")]
+impl ArgEnum for PinDirection {
+    const DESCRIPTION: &'static str = "Direction";
+    const NAMES: &'static [(&'static str, Self)] = &[("input", PinDirection::INPUT), ("in", PinDirection::INPUT), ("output", PinDirection::OUTPUT),
+        ("out", PinDirection::OUTPUT)];
+}
+
+
+#[cfg(feature = "python")]
+impl ArgEnum for OutputMode {
+    const DESCRIPTION: &'static str = "Output mode";
+    const NAMES: &'static [(&'static str, Self)] = &[("push_pull", OutputMode::PUSH_PULL), ("open_drain", OutputMode::OPEN_DRAIN),
+        ("open_source", OutputMode::OPEN_SOURCE)];
+}
+
+
+#[cfg(feature = "python")]
+impl ArgEnum for TriggerEdge {
+    const DESCRIPTION: &'static str = "Trigger edge";
+    const NAMES: &'static [(&'static str, Self)] = &[("rising", TriggerEdge::RISING), ("falling", TriggerEdge::FALLING), ("both", TriggerEdge::BOTH)];
+}
+
+
+#[cfg(feature = "python")]
+impl ArgEnum for TimestampClock {
+    const DESCRIPTION: &'static str = "Timestamp clock";
+    const NAMES: &'static [(&'static str, Self)] = &[("monotonic", TimestampClock::MONOTONIC), ("realtime", TimestampClock::REALTIME),
+        ("boottime", TimestampClock::BOOTTIME)];
+}
+
+
+#[cfg(feature = "python")]
+#[pymodule(gil_used = false, name = "_gpio_manager")]
 fn gpio_manager(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<gpio_module::GPIOManager>()?;
     m.add_class::<pwm_module::PWMManager>()?;
+    m.add_class::<pwm_output_module::PWM>()?;
+    m.add_class::<complementary_pwm_module::ComplementaryPWM>()?;
+    m.add_class::<fan_module::FanController>()?;
+    m.add_class::<servo_module::ServoGroup>()?;
+    m.add_class::<pid_module::PIDController>()?;
+    m.add_class::<touch_module::TouchButton>()?;
+    m.add_class::<waveform_module::WaveformManager>()?;
+    m.add_class::<scheduler_module::ScheduledAction>()?;
+    m.add_class::<pin_proxy_module::GPIOPin>()?;
     m.add_class::<i2c_module::I2CManager>()?;
+    m.add_class::<i2c_module::I2CProbeMode>()?;
+    m.add_class::<i2c_module::I2CErrorKind>()?;
+    m.add_class::<i2c_module::I2CWrite>()?;
+    m.add_class::<i2c_module::I2CRead>()?;
+    m.add_class::<soft_i2c_module::SoftI2C>()?;
+    m.add_class::<soft_spi_module::SPIMode>()?;
+    m.add_class::<soft_spi_module::SoftSPI>()?;
+    m.add_class::<spi_device_module::SPIDevice>()?;
+    m.add_class::<soft_uart_module::SoftUART>()?;
+    m.add_class::<modbus_module::ModbusRTU>()?;
+    m.add_class::<i2c_device_module::I2CDevice>()?;
+    m.add_class::<register_map_module::Register>()?;
+    m.add_class::<register_map_module::RegisterMap>()?;
+    m.add_class::<smbus_module::SMBus>()?;
+    m.add_class::<smbus_module::I2CMsg>()?;
+    m.add_class::<eeprom_module::EEPROM>()?;
+    m.add_class::<eeprom_module::EEPROMModel>()?;
+    m.add_class::<rtc_module::RTC>()?;
+    m.add_class::<rtc_module::RTCModel>()?;
+    m.add_class::<gpiochip_module::GPIOChipManager>()?;
     m.add_class::<InternPullResistorState>()?;
     m.add_class::<PinState>()?;
     m.add_class::<LogicLevel>()?;
+    m.add_class::<OutputMode>()?;
+    m.add_class::<PinDirection>()?;
     m.add_class::<TriggerEdge>()?;
+    m.add_class::<TimestampClock>()?;
+    m.add_class::<pwm_ramp_module::Easing>()?;
+    m.add_function(wrap_pyfunction!(board_module::board_info, m)?)?;
+    m.add_function(wrap_pyfunction!(board_module::pinout, m)?)?;
+    m.add_function(wrap_pyfunction!(board_module::cpu_temperature, m)?)?;
+    m.add_function(wrap_pyfunction!(overlay_module::active_overlays, m)?)?;
+    m.add_function(wrap_pyfunction!(overlay_module::interface_status, m)?)?;
+    m.add_function(wrap_pyfunction!(diagnose_module::diagnose, m)?)?;
+    m.add_function(wrap_pyfunction!(bench_module::benchmark_latency, m)?)?;
+    m.add_function(wrap_pyfunction!(log_module::set_log_level, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::get_metrics, m)?)?;
+    m.add_function(wrap_pyfunction!(backend_module::set_backend, m)?)?;
+    m.add_function(wrap_pyfunction!(backend_module::current_backend, m)?)?;
+    m.add_function(wrap_pyfunction!(shortcut_module::read, m)?)?;
+    m.add_function(wrap_pyfunction!(shortcut_module::write, m)?)?;
+    m.add_function(wrap_pyfunction!(shortcut_module::on_edge, m)?)?;
+    m.add_function(wrap_pyfunction!(defaults_module::set_defaults, m)?)?;
+    #[cfg(feature = "mqtt")]
+    {
+        m.add_class::<mqtt_module::MQTTBridge>()?;
+        m.add_function(wrap_pyfunction!(mqtt_module::mqtt_bridge, m)?)?;
+    }
+    #[cfg(feature = "server")]
+    {
+        m.add_class::<event_server::EventServer>()?;
+        m.add_class::<remote_module::RemoteGPIOManager>()?;
+        m.add_function(wrap_pyfunction!(event_server::serve_events, m)?)?;
+        m.add_class::<daemon_module::GPIODaemon>()?;
+        m.add_function(wrap_pyfunction!(daemon_module::serve_daemon, m)?)?;
+    }
+    #[cfg(feature = "http")]
+    {
+        m.add_class::<http_module::HTTPServer>()?;
+        m.add_function(wrap_pyfunction!(http_module::serve_http, m)?)?;
+    }
+    rpi_gpio_module::add_compat_module(m)?;
+    gpiozero_module::add_gpiozero_module(m)?;
+    cli_module::add_cli_module(m)?;
+    mock_module::add_mock_module(m)?;
     Ok(())
 }
 
+
+
+#[cfg(all(test, feature = "python"))]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    // Built without Debounce::new, whose error needs the Python interpreter the extension module doesn't link
+    fn debounce(filter: DebounceFilter) -> Debounce {
+        Debounce { period: ms(10), filter, last_event: None, level: None, raw_level: false, count: Duration::ZERO, counted_at: Duration::ZERO, generation: 0 }
+    }
+
+    #[test]
+    fn hardware_filter_passes_edges_to_the_kernel() {
+        let mut debounce = debounce(DebounceFilter::Hardware);
+        assert_eq!(debounce.kernel_period(), Some(ms(10)));
+        assert_eq!(debounce.edge(true, ms(1)).edge, Some((true, ms(1))));
+        assert_eq!(debounce.edge(false, ms(2)).edge, Some((false, ms(2))));
+    }
+
+    #[test]
+    fn dead_time_drops_edges_within_the_period() {
+        let mut debounce = debounce(DebounceFilter::DeadTime);
+        assert_eq!(debounce.kernel_period(), None);
+        assert_eq!(debounce.edge(true, ms(100)).edge, Some((true, ms(100))));
+        assert_eq!(debounce.edge(false, ms(103)).edge, None);
+        assert_eq!(debounce.edge(true, ms(109)).edge, None);
+        assert_eq!(debounce.edge(false, ms(110)).edge, Some((false, ms(110))));
+    }
+
+    #[test]
+    fn stable_reports_the_last_change_once_the_line_settled() {
+        let mut debounce = debounce(DebounceFilter::Stable);
+        let first = debounce.edge(true, ms(100));
+        assert_eq!((first.edge, first.settle_after), (None, Some(ms(10))));
+        let first_generation = debounce.generation;
+        // Bouncing back to the reported level cancels the edge, the next rise restarts the period
+        assert_eq!(debounce.edge(false, ms(102)).settle_after, None);
+        assert_eq!(debounce.edge(true, ms(104)).settle_after, Some(ms(10)));
+        assert_eq!(debounce.settle(first_generation), None);
+        assert_eq!(debounce.settle(debounce.generation), Some((true, ms(104))));
+        assert_eq!(debounce.settle(debounce.generation), None);
+    }
+
+    #[test]
+    fn stable_glitch_shorter_than_the_period_is_dropped() {
+        let mut debounce = debounce(DebounceFilter::Stable);
+        debounce.edge(true, ms(100));
+        debounce.edge(false, ms(101));
+        assert_eq!(debounce.settle(debounce.generation), None);
+    }
+
+    #[test]
+    fn first_edge_starts_from_the_other_level() {
+        let mut stable = debounce(DebounceFilter::Stable);
+        assert_eq!(stable.edge(false, ms(50)).settle_after, Some(ms(10)));
+        assert_eq!(stable.level, Some(true));
+        assert_eq!(stable.settle(stable.generation), Some((false, ms(50))));
+
+        let mut integrator = debounce(DebounceFilter::Integrator);
+        let first = integrator.edge(false, ms(50));
+        assert_eq!((first.edge, first.settle_after), (None, Some(ms(10))));
+        assert_eq!(integrator.settle(integrator.generation), Some((false, ms(60))));
+    }
+
+    #[test]
+    fn integrator_glitch_delays_the_edge() {
+        let mut debounce = debounce(DebounceFilter::Integrator);
+        assert_eq!(debounce.edge(true, ms(0)).settle_after, Some(ms(10)));
+        // 4 ms high counts up to 4, the 1 ms glitch low counts back down to 3
+        let glitch = debounce.edge(false, ms(4));
+        assert_eq!((glitch.edge, glitch.settle_after), (None, None));
+        assert_eq!(debounce.edge(true, ms(5)).settle_after, Some(ms(7)));
+        assert_eq!(debounce.settle(debounce.generation), Some((true, ms(12))));
+    }
+
+    #[test]
+    fn integrator_edge_is_reported_by_the_next_edge() {
+        let mut debounce = debounce(DebounceFilter::Integrator);
+        debounce.edge(true, ms(0));
+        // The count reached the period at 10 ms, before the line fell again
+        let filtered = debounce.edge(false, ms(15));
+        assert_eq!(filtered.edge, Some((true, ms(10))));
+        assert_eq!(filtered.settle_after, Some(ms(10)));
+        assert_eq!(debounce.settle(debounce.generation), Some((false, ms(25))));
+    }
+
+    #[test]
+    fn times_are_converted_in_their_unit() {
+        assert_eq!(ms_to_duration_checked("Delay", 1.5).unwrap(), Duration::from_micros(1500));
+        assert_eq!(us_to_duration_checked("Delay", 250f64).unwrap(), Duration::from_micros(250));
+        assert_eq!(ms_to_duration_checked("Delay", 0f64).unwrap(), Duration::ZERO);
+        assert_eq!(frequency_to_period_checked("Frequency", 1000f64).unwrap(), ms(1));
+    }
+
+    #[test]
+    fn times_that_panic_from_secs_f64_are_rejected() {
+        assert_eq!(ms_to_duration_checked("Delay", MAX_TIME.as_secs_f64() * 1000f64).unwrap(), MAX_TIME);
+        for ms in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, -1f64, 1e300, MAX_TIME.as_secs_f64() * 2000f64] {
+            assert!(matches!(ms_to_duration_checked("Delay", ms), Err(GpioError::InvalidArgument(_))), "{}", ms);
+        }
+        for frequency_hz in [f64::NAN, f64::INFINITY, 0f64, -1f64, 1e-10, 1e-320] {
+            assert!(matches!(frequency_to_period_checked("Frequency", frequency_hz), Err(GpioError::InvalidArgument(_))), "{}", frequency_hz);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::sync::GILOnceCell;
+#[cfg(feature = "python")]
+use pyo3::types::PyDict;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+
+// Levels of the logging module
+pub const DEBUG: i32 = 10;
+pub const INFO: i32 = 20;
+pub const WARNING: i32 = 30;
+pub const ERROR: i32 = 40;
+
+/// The lowest level logged, messages below it are dropped before the GIL is taken.
+static LOG_LEVEL: AtomicI32 = AtomicI32::new(WARNING);
+#[cfg(feature = "python")]
+static LOGGER: GILOnceCell<PyObject> = GILOnceCell::new();
+
+
+#[cfg(feature = "python")]
+fn logger<'py>(py: Python<'py>) -> PyResult<&'py Bound<'py, PyAny>> {
+    LOGGER.get_or_try_init(py, || Ok(py.import("logging")?.call_method1("getLogger", ("gpio_manager",))?.unbind()))
+          .map(|logger| logger.bind(py))
+}
+
+
+/// Passes a message to the `gpio_manager` logger of the logging module, with the traceback of the error when given.
+/// Errors of the logging module itself are printed, so the message is never lost.
+#[cfg(feature = "python")]
+fn log(level: i32, message: &str, error: Option<&PyErr>) {
+    if level < LOG_LEVEL.load(Ordering::Relaxed) {
+        return;
+    }
+    Python::with_gil(|py| {
+        let logged = logger(py).and_then(|logger| {
+            let kwargs = PyDict::new(py);
+            if let Some(error) = error {
+                kwargs.set_item("exc_info", error.value(py))?;
+            }
+            logger.call_method("log", (level, message), Some(&kwargs))?;
+            Ok(())
+        });
+        if let Err(e) = logged {
+            eprintln!("{}", message);
+            e.print(py);
+        }
+    });
+}
+
+
+/// Prints a message to stderr, the library logs there when it is used without Python.
+#[cfg(not(feature = "python"))]
+fn log(level: i32, message: &str, _error: Option<&dyn std::error::Error>) {
+    if level < LOG_LEVEL.load(Ordering::Relaxed) {
+        return;
+    }
+    eprintln!("gpio_manager: {}", message);
+}
+
+
+pub fn debug(message: &str) {
+    log(DEBUG, message, None);
+}
+
+
+pub fn info(message: &str) {
+    log(INFO, message, None);
+}
+
+
+pub fn warning(message: &str) {
+    log(WARNING, message, None);
+}
+
+
+/// Logs an error raised by Python code called from Rust, like a callback, with its traceback.
+#[cfg(feature = "python")]
+pub fn exception(message: &str, error: &PyErr) {
+    log(ERROR, message, Some(error));
+}
+
+
+#[cfg(feature = "python")]
+#[pyfunction]
+/// Sets the level of the `gpio_manager` logger of the logging module, which receives the warnings of the library
+/// (pinctrl failures, dropped events, callback errors and cleanup actions). Messages below the level are dropped
+/// without entering Python.
+///
+/// Parameters:
+/// - ```level``` (int | str): A level of the logging module, like `logging.DEBUG` or "DEBUG" (default of the library
+///   is WARNING).
+///
+/// Example usage:
+/// ```python
+/// import logging
+/// logging.basicConfig()
+/// gpio_manager.set_log_level(logging.DEBUG)
+/// ```
+pub fn set_log_level(py: Python, level: &Bound<'_, PyAny>) -> PyResult<()> {
+    let logger = logger(py)?;
+    logger.call_method1("setLevel", (level,))?;
+    LOG_LEVEL.store(logger.getattr("level")?.extract()?, Ordering::Relaxed);
+    Ok(())
+}
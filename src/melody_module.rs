@@ -0,0 +1,112 @@
+use crate::pwm_ramp_module::{is_current, next_generation, RampTarget};
+use crate::ms_to_duration_checked;
+use crate::timing::wait_until_with_margin;
+use pyo3::{PyErr, PyResult};
+use std::time::{Duration, Instant};
+
+
+// Fraction of every note left silent at its end, so repeated notes are heard separately
+const NOTE_GAP: f64 = 0.1;
+
+
+/// A note of a melody, a rest has no frequency.
+pub struct Note {
+    frequency_hz: Option<f64>,
+    duration: Duration,
+}
+
+
+fn melody_error(token: &str, reason: &str) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid note '{}' in the melody: {}", token, reason))
+}
+
+
+/// Returns the frequency of a note name such as `C4`, `F#5` or `Bb3`, in equal temperament with A4 at 440 Hz. The
+/// octave defaults to 4.
+fn note_frequency(name: &str, token: &str) -> PyResult<f64> {
+    let mut chars = name.chars();
+    let semitone = match chars.next().map(|c| c.to_ascii_uppercase()) {
+        Some('C') => 0,
+        Some('D') => 2,
+        Some('E') => 4,
+        Some('F') => 5,
+        Some('G') => 7,
+        Some('A') => 9,
+        Some('B') => 11,
+        _ => return Err(melody_error(token, "the note must be A to G or R for a rest")),
+    };
+    let rest = chars.as_str();
+    let (semitone, octave) = match rest.strip_prefix('#') {
+        Some(octave) => (semitone + 1, octave),
+        None => match rest.strip_prefix('b') {
+            Some(octave) => (semitone - 1, octave),
+            None => (semitone, rest),
+        },
+    };
+    let octave: i32 = if octave.is_empty() { 4 } else { octave.parse().map_err(|_| melody_error(token, "the octave must be a number"))? };
+    let midi = 12 * (octave + 1) + semitone;
+    Ok(440f64 * 2f64.powf((midi - 69) as f64 / 12f64))
+}
+
+
+/// Parses a melody of notes separated by whitespace, each written `<note><octave>:<length>`. The length is the
+/// denominator of the note value, 4 for a quarter note and 8 for an eighth, and defaults to 4. A trailing `.` makes a
+/// dotted note, and `R` is a rest.
+pub fn parse_melody(melody: &str, bpm: f64) -> PyResult<Vec<Note>> {
+    if !bpm.is_finite() || bpm <= 0f64 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Tempo must be greater than 0, The value {} does not meet this condition", bpm)));
+    }
+    let quarter = 60f64 / bpm;
+    let notes: Vec<Note> = melody.split_whitespace()
+                                 .map(|token| {
+                                     let (name, length) = token.split_once(':').unwrap_or((token, "4"));
+                                     let (length, dotted) = match length.strip_suffix('.') {
+                                         Some(length) => (length, true),
+                                         None => (length, false),
+                                     };
+                                     let length: f64 = length.parse().map_err(|_| melody_error(token, "the length must be a number"))?;
+                                     if !length.is_finite() || length <= 0f64 {
+                                         return Err(melody_error(token, "the length must be greater than 0"));
+                                     }
+                                     let beats = 4f64 / length * if dotted { 1.5 } else { 1f64 };
+                                     let frequency_hz = match name {
+                                         "R" | "r" => None,
+                                         name => Some(note_frequency(name, token)?),
+                                     };
+                                     let duration = ms_to_duration_checked("Note length", quarter * beats * 1000f64)?;
+                                     Ok(Note { frequency_hz, duration })
+                                 })
+                                 .collect::<PyResult<_>>()?;
+    if notes.is_empty() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("The melody must have at least one note"));
+    }
+    Ok(notes)
+}
+
+
+/// Starts a melody on the output, replacing any ramp, pattern or melody running on it, and returns a function that
+/// plays it. The function plays the notes through `play_tone`, which sets the frequency of a tone or silences the
+/// output for None, until the melody ends or is replaced like a ramp. Every note ends silent, so the output is silent
+/// after the melody. Notes are timed from absolute deadlines so long melodies don't drift.
+pub fn start_melody(target: RampTarget, notes: Vec<Note>, play_tone: impl Fn(Option<f64>) -> PyResult<()> + Send + 'static)
+                    -> impl FnOnce() + Send + 'static {
+    let generation = next_generation(target);
+
+    move || {
+        let mut deadline = Instant::now();
+        for note in &notes {
+            if !is_current(target, generation) || play_tone(note.frequency_hz).is_err() {
+                return;
+            }
+            let end = deadline + note.duration;
+            if note.frequency_hz.is_some() {
+                wait_until_with_margin(deadline + note.duration.mul_f64(1f64 - NOTE_GAP), Duration::ZERO);
+                if !is_current(target, generation) || play_tone(None).is_err() {
+                    return;
+                }
+            }
+            deadline = end;
+            wait_until_with_margin(deadline, Duration::ZERO);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use pyo3::PyResult;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, TryLockError};
+use std::time::Instant;
+
+
+// Enough for the GPIO of every SoC rppal supports
+const PINS: usize = 64;
+
+
+/// The counters kept for every pin.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PinCounter {
+    Interrupts,
+    CallbackInvocations,
+    CallbackErrors,
+    DroppedEvents,
+}
+
+
+impl PinCounter {
+    const ALL: [PinCounter; 4] = [PinCounter::Interrupts, PinCounter::CallbackInvocations, PinCounter::CallbackErrors, PinCounter::DroppedEvents];
+
+    fn name(self) -> &'static str {
+        match self {
+            PinCounter::Interrupts => "interrupts",
+            PinCounter::CallbackInvocations => "callback_invocations",
+            PinCounter::CallbackErrors => "callback_errors",
+            PinCounter::DroppedEvents => "dropped_events",
+        }
+    }
+}
+
+
+static PIN_COUNTERS: [[AtomicU64; PINS]; 4] = [const { [const { AtomicU64::new(0) }; PINS] }; 4];
+static I2C_TRANSACTIONS: AtomicU64 = AtomicU64::new(0);
+static I2C_FAILURES: AtomicU64 = AtomicU64::new(0);
+static LOCK_CONTENTIONS: AtomicU64 = AtomicU64::new(0);
+static LOCK_WAIT_NS: AtomicU64 = AtomicU64::new(0);
+
+
+pub fn count_pin(counter: PinCounter, pin_num: u8) {
+    if let Some(count) = PIN_COUNTERS[counter as usize].get(pin_num as usize) {
+        count.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+
+/// Counts an I2C transaction along with its failure.
+pub fn count_i2c<T>(result: &PyResult<T>) {
+    I2C_TRANSACTIONS.fetch_add(1, Ordering::Relaxed);
+    if result.is_err() {
+        I2C_FAILURES.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+
+/// Locks the mutex, adding the time spent waiting for it to the lock contention when it is held by another thread.
+pub fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.try_lock() {
+        Ok(guard) => guard,
+        Err(TryLockError::WouldBlock) => {
+            let start = Instant::now();
+            let guard = mutex.lock().unwrap();
+            LOCK_CONTENTIONS.fetch_add(1, Ordering::Relaxed);
+            LOCK_WAIT_NS.fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+            guard
+        }
+        Err(TryLockError::Poisoned(e)) => panic!("{}", e),
+    }
+}
+
+
+#[pyfunction]
+/// Returns the counters the library keeps since it was loaded, for exporting to monitoring systems like Prometheus.
+///
+/// Returns:
+/// - `dict`: `interrupts`, `callback_invocations`, `callback_errors` and `dropped_events` map the pins to their
+///   counts, pins without events are left out. `dropped_events` counts the edges dropped by the software debounce or
+///   with an unknown trigger. `i2c_transactions` and `i2c_failures` count the I2C operations and the ones that
+///   failed, `lock_contentions` and `lock_wait_ns` count the times a thread waited for the lock of the GPIO manager or
+///   of the I2C buses and the total time it waited in nanoseconds.
+///
+/// Example usage:
+/// ```python
+/// metrics = gpio_manager.get_metrics()
+/// print(f"GPIO 17 had {metrics['interrupts'].get(17, 0)} interrupts")
+/// ```
+pub fn get_metrics(py: Python) -> PyResult<Bound<PyDict>> {
+    let metrics = PyDict::new(py);
+    for counter in PinCounter::ALL {
+        let pins = PyDict::new(py);
+        for (pin_num, count) in PIN_COUNTERS[counter as usize].iter().enumerate() {
+            let count = count.load(Ordering::Relaxed);
+            if count > 0 {
+                pins.set_item(pin_num, count)?;
+            }
+        }
+        metrics.set_item(counter.name(), pins)?;
+    }
+    metrics.set_item("i2c_transactions", I2C_TRANSACTIONS.load(Ordering::Relaxed))?;
+    metrics.set_item("i2c_failures", I2C_FAILURES.load(Ordering::Relaxed))?;
+    metrics.set_item("lock_contentions", LOCK_CONTENTIONS.load(Ordering::Relaxed))?;
+    metrics.set_item("lock_wait_ns", LOCK_WAIT_NS.load(Ordering::Relaxed))?;
+    Ok(metrics)
+}
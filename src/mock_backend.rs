@@ -0,0 +1,396 @@
+use crate::backend::{Backend, Error, InputLine, InterruptCallback, OutputLine, PwmLine, Result};
+use crate::timing;
+use once_cell::sync::Lazy;
+use rppal::gpio::{Bias, Event, Level, Trigger};
+use rppal::pwm::Polarity;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+
+/// What a simulated pin is taken as.
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Free,
+    Input,
+    Output,
+}
+
+
+/// The state of a simulated pin.
+struct MockLine {
+    mode: Mode,
+    bias: Bias,
+    /// The level an output drives while its driver is enabled.
+    latch: bool,
+    enabled: bool,
+    /// The level set with set_input, the pull resistor decides the level until then.
+    external: Option<bool>,
+    /// The edges polled by poll_interrupt, None without a synchronous interrupt.
+    trigger: Option<Trigger>,
+    pending: VecDeque<Event>,
+    callback: Option<(Trigger, Arc<Mutex<InterruptCallback>>)>,
+    seqno: u32,
+}
+
+
+impl MockLine {
+    fn new() -> Self {
+        MockLine {
+            mode: Mode::Free,
+            bias: Bias::Off,
+            latch: false,
+            enabled: true,
+            external: None,
+            trigger: None,
+            pending: VecDeque::new(),
+            callback: None,
+            seqno: 0,
+        }
+    }
+
+    fn level(&self) -> bool {
+        match self.mode {
+            Mode::Output if self.enabled => self.latch,
+            _ => self.external.unwrap_or(self.bias == Bias::PullUp),
+        }
+    }
+}
+
+
+// The simulated pins, kept after their handles are dropped so the levels set with set_input stay
+static LINES: Lazy<Mutex<HashMap<u8, MockLine>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+// Wakes up poll_interrupt when an edge is queued
+static EDGE_QUEUED: Condvar = Condvar::new();
+
+
+fn triggers(trigger: Trigger, rising: bool) -> bool {
+    match trigger {
+        Trigger::RisingEdge => rising,
+        Trigger::FallingEdge => !rising,
+        Trigger::Both => true,
+        Trigger::Disabled => false,
+    }
+}
+
+
+/// Changes a simulated pin and delivers the edge it causes to the interrupts of the pin. The callback of an
+/// asynchronous interrupt is called on the calling thread once the pins are unlocked.
+fn update(pin_num: u8, change: impl FnOnce(&mut MockLine) -> Result<()>) -> Result<()> {
+    let dispatch = {
+        let mut lines = LINES.lock().unwrap();
+        let line = lines.entry(pin_num).or_insert_with(MockLine::new);
+        let (was_input, before) = (line.mode == Mode::Input, line.level());
+        change(line)?;
+        let rising = line.level();
+        if !was_input || line.mode != Mode::Input || before == rising {
+            return Ok(());
+        }
+        line.seqno = line.seqno.wrapping_add(1);
+        let event = Event {
+            timestamp: Duration::from_nanos(timing::monotonic_ns() as u64),
+            seqno: line.seqno,
+            trigger: if rising { Trigger::RisingEdge } else { Trigger::FallingEdge },
+        };
+        if line.trigger.is_some_and(|trigger| triggers(trigger, rising)) {
+            line.pending.push_back(event);
+            EDGE_QUEUED.notify_all();
+        }
+        match &line.callback {
+            Some((trigger, callback)) if triggers(*trigger, rising) => Some((Arc::clone(callback), event)),
+            _ => None,
+        }
+    };
+    if let Some((callback, event)) = dispatch {
+        (callback.lock().unwrap())(event);
+    }
+    Ok(())
+}
+
+
+/// The backend simulating the pins, for running without hardware.
+pub struct MockBackend;
+
+
+impl Backend for MockBackend {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn input(&self, pin_num: u8, bias: Bias) -> Result<Box<dyn InputLine>> {
+        take(pin_num, Mode::Input, |line| line.bias = bias)?;
+        Ok(Box::new(MockInput { pin_num }))
+    }
+
+    fn output(&self, pin_num: u8, level: Level) -> Result<Box<dyn OutputLine>> {
+        take(pin_num, Mode::Output, |line| {
+            line.latch = level == Level::High;
+            line.enabled = true;
+        })?;
+        Ok(Box::new(MockOutput { pin_num, pwm: None }))
+    }
+
+    fn pwm(&self, _channel_num: u8, frequency: f64, duty_cycle: f64, polarity: Polarity, enabled: bool) -> Result<Box<dyn PwmLine>> {
+        let pwm = MockPwm { state: Mutex::new(PwmState { period: Duration::ZERO, pulse_width: Duration::ZERO, polarity, enabled }) };
+        pwm.set_frequency(frequency, duty_cycle)?;
+        Ok(Box::new(pwm))
+    }
+
+    fn read_levels(&self) -> Result<u32> {
+        let lines = LINES.lock().unwrap();
+        Ok(lines.iter().filter(|(pin_num, line)| **pin_num < 32 && line.level()).fold(0, |levels, (pin_num, _)| levels | 1 << pin_num))
+    }
+}
+
+
+/// Takes a free simulated pin.
+fn take(pin_num: u8, mode: Mode, setup: impl FnOnce(&mut MockLine)) -> Result<()> {
+    update(pin_num, |line| {
+        if line.mode != Mode::Free {
+            return Err(Error::new(format!("Pin {} is busy", pin_num)));
+        }
+        line.mode = mode;
+        setup(line);
+        Ok(())
+    })
+}
+
+
+/// Frees a simulated pin, its interrupts are cleared.
+fn release(pin_num: u8) {
+    if let Some(line) = LINES.lock().unwrap().get_mut(&pin_num) {
+        line.mode = Mode::Free;
+        line.trigger = None;
+        line.pending.clear();
+        line.callback = None;
+    }
+}
+
+
+fn with_line<T>(pin_num: u8, op: impl FnOnce(&mut MockLine) -> T) -> T {
+    op(LINES.lock().unwrap().entry(pin_num).or_insert_with(MockLine::new))
+}
+
+
+struct MockInput {
+    pin_num: u8,
+}
+
+
+impl InputLine for MockInput {
+    fn read(&self) -> Level {
+        if with_line(self.pin_num, |line| line.level()) { Level::High } else { Level::Low }
+    }
+
+    fn set_bias(&mut self, bias: Bias) -> Result<()> {
+        update(self.pin_num, |line| {
+            line.bias = bias;
+            Ok(())
+        })
+    }
+
+    fn set_interrupt(&mut self, trigger: Trigger, _debounce: Option<Duration>) -> Result<()> {
+        with_line(self.pin_num, |line| {
+            line.callback = None;
+            line.trigger = Some(trigger);
+            line.pending.clear();
+        });
+        Ok(())
+    }
+
+    fn clear_interrupt(&mut self) -> Result<()> {
+        with_line(self.pin_num, |line| {
+            line.trigger = None;
+            line.pending.clear();
+        });
+        Ok(())
+    }
+
+    fn poll_interrupt(&mut self, reset: bool, timeout: Option<Duration>) -> Result<Option<Event>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut lines = LINES.lock().unwrap();
+        if reset {
+            lines.entry(self.pin_num).or_insert_with(MockLine::new).pending.clear();
+        }
+        loop {
+            let line = lines.entry(self.pin_num).or_insert_with(MockLine::new);
+            if line.trigger.is_none() {
+                return Err(Error::new("No interrupt is set up on the pin"));
+            }
+            if let Some(event) = line.pending.pop_front() {
+                return Ok(Some(event));
+            }
+            lines = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Ok(None);
+                    }
+                    EDGE_QUEUED.wait_timeout(lines, deadline - now).unwrap().0
+                }
+                None => EDGE_QUEUED.wait(lines).unwrap(),
+            };
+        }
+    }
+
+    fn set_async_interrupt(&mut self, trigger: Trigger, _debounce: Option<Duration>, callback: InterruptCallback) -> Result<()> {
+        with_line(self.pin_num, |line| {
+            line.trigger = None;
+            line.pending.clear();
+            line.callback = Some((trigger, Arc::new(Mutex::new(callback))));
+        });
+        Ok(())
+    }
+
+    fn clear_async_interrupt(&mut self) -> Result<()> {
+        with_line(self.pin_num, |line| line.callback = None);
+        Ok(())
+    }
+
+    fn set_reset_on_drop(&mut self, _reset_on_drop: bool) {}
+}
+
+
+impl Drop for MockInput {
+    fn drop(&mut self) {
+        release(self.pin_num);
+    }
+}
+
+
+struct MockOutput {
+    pin_num: u8,
+    /// The frequency and duty cycle of the software PWM, which is recorded but doesn't toggle the pin.
+    pwm: Option<(f64, f64)>,
+}
+
+
+impl OutputLine for MockOutput {
+    fn write(&mut self, level: Level) {
+        with_line(self.pin_num, |line| line.latch = level == Level::High);
+    }
+
+    fn is_set_high(&self) -> bool {
+        with_line(self.pin_num, |line| line.latch)
+    }
+
+    fn set_pwm_frequency(&mut self, frequency: f64, duty_cycle: f64) -> Result<()> {
+        self.pwm = Some((frequency, duty_cycle));
+        Ok(())
+    }
+
+    fn clear_pwm(&mut self) -> Result<()> {
+        self.pwm = None;
+        Ok(())
+    }
+
+    fn set_output_enabled(&mut self, enabled: bool) -> Result<()> {
+        with_line(self.pin_num, |line| line.enabled = enabled);
+        Ok(())
+    }
+
+    fn set_reset_on_drop(&mut self, _reset_on_drop: bool) {}
+}
+
+
+impl Drop for MockOutput {
+    fn drop(&mut self) {
+        release(self.pin_num);
+    }
+}
+
+
+struct PwmState {
+    period: Duration,
+    pulse_width: Duration,
+    polarity: Polarity,
+    enabled: bool,
+}
+
+
+/// A simulated hardware PWM channel, which only records its settings.
+struct MockPwm {
+    state: Mutex<PwmState>,
+}
+
+
+impl PwmLine for MockPwm {
+    fn period(&self) -> Result<Duration> {
+        Ok(self.state.lock().unwrap().period)
+    }
+
+    fn set_period(&self, period: Duration) -> Result<()> {
+        self.state.lock().unwrap().period = period;
+        Ok(())
+    }
+
+    fn pulse_width(&self) -> Result<Duration> {
+        Ok(self.state.lock().unwrap().pulse_width)
+    }
+
+    fn set_pulse_width(&self, pulse_width: Duration) -> Result<()> {
+        self.state.lock().unwrap().pulse_width = pulse_width;
+        Ok(())
+    }
+
+    fn frequency(&self) -> Result<f64> {
+        let period = self.state.lock().unwrap().period;
+        Ok(if period.is_zero() { 0.0 } else { 1.0 / period.as_secs_f64() })
+    }
+
+    fn set_frequency(&self, frequency: f64, duty_cycle: f64) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.period = if frequency <= 0.0 { Duration::ZERO } else { Duration::from_secs_f64(1.0 / frequency) };
+        state.pulse_width = state.period.mul_f64(duty_cycle.clamp(0.0, 1.0));
+        Ok(())
+    }
+
+    fn duty_cycle(&self) -> Result<f64> {
+        let state = self.state.lock().unwrap();
+        Ok(if state.period.is_zero() { 0.0 } else { state.pulse_width.as_secs_f64() / state.period.as_secs_f64() })
+    }
+
+    fn set_duty_cycle(&self, duty_cycle: f64) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        state.pulse_width = state.period.mul_f64(duty_cycle.clamp(0.0, 1.0));
+        Ok(())
+    }
+
+    fn set_polarity(&self, polarity: Polarity) -> Result<()> {
+        self.state.lock().unwrap().polarity = polarity;
+        Ok(())
+    }
+
+    fn is_enabled(&self) -> Result<bool> {
+        Ok(self.state.lock().unwrap().enabled)
+    }
+
+    fn enable(&self) -> Result<()> {
+        self.state.lock().unwrap().enabled = true;
+        Ok(())
+    }
+
+    fn disable(&self) -> Result<()> {
+        self.state.lock().unwrap().enabled = false;
+        Ok(())
+    }
+}
+
+
+/// Drives a simulated pin from outside, like a button or a sensor would. The edge it causes reaches the callbacks of
+/// the pin before the function returns.
+pub fn set_input(pin_num: u8, high: bool) -> Result<()> {
+    update(pin_num, |line| {
+        if line.mode == Mode::Output && line.enabled {
+            return Err(Error::new(format!("Pin {} is driven as an output", pin_num)));
+        }
+        line.external = Some(high);
+        Ok(())
+    })
+}
+
+
+/// Returns the physical level of a simulated pin, the level an output drives or the level of an input.
+pub fn get_level(pin_num: u8) -> bool {
+    with_line(pin_num, |line| line.level())
+}
@@ -0,0 +1,159 @@
+use crate::backend;
+use crate::event_logger;
+use crate::mock_backend;
+use crate::timing::{self, wait_until};
+use crate::{ms_to_duration_checked, EnumArg, PinState};
+use pyo3::prelude::*;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+
+#[pyfunction]
+/// Drives a simulated pin from outside, like a button or a sensor would. The edge it causes reaches the callbacks of
+/// the pin before the function returns.
+///
+/// Parameters:
+/// - ```pin_num``` (int): The GPIO pin.
+/// - ```pin_state``` (PinState): The physical level, the logic level of the pin isn't applied.
+///
+/// Example usage:
+/// ```gpio_manager.mock.set_input(17, gpio_manager.PinState.HIGH)```
+pub fn set_input(py: Python, pin_num: u8, pin_state: EnumArg<PinState>) -> PyResult<()> {
+    let pin_state = pin_state.0;
+    // The callbacks lock the manager and take the GIL again, so the GIL is released first
+    py.allow_threads(|| mock_backend::set_input(pin_num, pin_state == PinState::HIGH))?;
+    Ok(())
+}
+
+
+#[pyfunction]
+/// Returns the physical level of a simulated pin, the level an output drives or the level of an input.
+///
+/// Parameters:
+/// - ```pin_num``` (int): The GPIO pin.
+///
+/// Returns:
+/// - ```PinState```: The level of the pin.
+///
+/// Example usage:
+/// ```assert gpio_manager.mock.get_level(25) == gpio_manager.PinState.HIGH```
+pub fn get_level(pin_num: u8) -> PinState {
+    if mock_backend::get_level(pin_num) { PinState::HIGH } else { PinState::LOW }
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (path, speed = 1f64, pins = None))]
+/// Replays a trace of edges recorded with GPIOManager.start_logger into the simulated pins, with the relative timing
+/// of the recording, so the callbacks see the same sequence of edges again. The function returns once the last edge
+/// was delivered, the GIL is released while it waits.
+///
+/// Under the virtual clock the trace advances it by the time it covers instead of taking that time.
+///
+/// Before the first edge every pin is brought to the level it had before its first recorded edge, which calls the
+/// callbacks of that edge when the pin was at the other level.
+///
+/// Parameters:
+/// - ```path``` (str): The CSV or JSONL file written by the event logger.
+/// - ```speed``` (float): How much faster than recorded the trace is replayed, 2 replays it in half the time (default
+///   is 1).
+/// - ```pins``` (list[int]): The pins replayed, every pin of the trace if None (default is None).
+///
+/// Returns:
+/// - ```int```: The number of edges replayed.
+///
+/// Example usage:
+/// ```gpio_manager.mock.replay("glitch.csv")```
+pub fn replay(py: Python, path: &str, speed: f64, pins: Option<Vec<u8>>) -> PyResult<usize> {
+    if backend::current().name() != "mock" {
+        return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Traces are replayed into the mock backend, select it with set_backend(\"mock\")"));
+    }
+    if speed <= 0f64 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Speed must be greater than 0, The value {} does not meet this condition", speed)));
+    }
+    let pins: Option<HashSet<u8>> = pins.map(|pins| pins.into_iter().collect());
+    let events: Vec<_> = event_logger::read_trace(path)?.into_iter()
+                                                         .filter(|event| pins.as_ref().is_none_or(|pins| pins.contains(&event.pin_num)))
+                                                         .collect();
+    let Some(first) = events.first() else {
+        return Ok(0);
+    };
+    let first_ns = first.timestamp_ns;
+    // The callbacks lock the manager and take the GIL again, so the GIL is released first
+    py.allow_threads(|| {
+        let mut primed = HashSet::new();
+        for event in &events {
+            if primed.insert(event.pin_num) {
+                mock_backend::set_input(event.pin_num, !event.rising)?;
+            }
+        }
+        let start = Instant::now();
+        let mut replayed = Duration::ZERO;
+        for event in &events {
+            let offset = Duration::from_nanos(((event.timestamp_ns - first_ns) as f64 / speed) as u64);
+            // Under the virtual clock the trace advances it instead of waiting in real time
+            if !timing::advance(offset.saturating_sub(replayed)) {
+                wait_until(start + offset);
+            }
+            replayed = offset;
+            mock_backend::set_input(event.pin_num, event.rising)?;
+        }
+        Ok::<_, PyErr>(events.len())
+    })
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (enabled = true))]
+/// Runs the timed features on a virtual clock that only moves with advance_time, so debounce filters, scheduled
+/// changes, PWM ramps and patterns, software PWM and activity watchdogs can be tested without waiting on the wall clock.
+/// The virtual clock starts at the current time, stopping it returns to the real time.
+///
+/// Parameters:
+/// - ```enabled``` (bool): Whether the virtual clock runs (default is True).
+///
+/// Example usage:
+/// ```gpio_manager.mock.set_virtual_clock()```
+pub fn set_virtual_clock(py: Python, enabled: bool) -> PyResult<()> {
+    if enabled && backend::current().name() != "mock" {
+        return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("The virtual clock runs with the mock backend, select it with set_backend(\"mock\")"));
+    }
+    // Stopping the clock wakes up the threads waiting on it, which may need the GIL to finish a callback
+    py.allow_threads(|| timing::set_virtual(enabled));
+    Ok(())
+}
+
+
+#[pyfunction]
+/// Moves the virtual clock forward. Everything due in that time runs in order before the function returns, including
+/// the callbacks it causes, every deadline is reached at its exact virtual time. The GIL is released while the clock
+/// advances, and it must not be called from a callback.
+///
+/// Parameters:
+/// - ```ms``` (float): The time to advance the clock by in milliseconds.
+///
+/// Example usage:
+/// ```gpio_manager.mock.advance_time(50)```
+pub fn advance_time(py: Python, ms: f64) -> PyResult<()> {
+    let time = ms_to_duration_checked("Time", ms)?;
+    if !py.allow_threads(|| timing::advance(time)) {
+        return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("The virtual clock isn't running, start it with set_virtual_clock"));
+    }
+    Ok(())
+}
+
+
+/// Adds the `mock` module driving the simulated pins of the mock backend.
+pub fn add_mock_module(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = parent.py();
+    let mock = PyModule::new(py, "mock")?;
+    mock.gil_used(false)?;
+    mock.add_function(wrap_pyfunction!(set_input, &mock)?)?;
+    mock.add_function(wrap_pyfunction!(get_level, &mock)?)?;
+    mock.add_function(wrap_pyfunction!(replay, &mock)?)?;
+    mock.add_function(wrap_pyfunction!(set_virtual_clock, &mock)?)?;
+    mock.add_function(wrap_pyfunction!(advance_time, &mock)?)?;
+    parent.add_submodule(&mock)?;
+    py.import("sys")?.getattr("modules")?.set_item("gpio_manager.mock", &mock)?;
+    Ok(())
+}
@@ -0,0 +1,333 @@
+use crate::{ms_to_duration_checked, release_gil};
+use crate::soft_uart_module::SoftUART;
+use crate::timing::wait_until;
+use pyo3::prelude::*;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+
+// The silence between frames is fixed above 19200 baud instead of following the character time
+const FIXED_FRAME_GAP: Duration = Duration::from_micros(1750);
+// How long the bus is left to the slaves after a broadcast, which isn't answered
+const BROADCAST_TURNAROUND: Duration = Duration::from_millis(100);
+
+const READ_HOLDING_REGISTERS: u8 = 0x03;
+const READ_INPUT_REGISTERS: u8 = 0x04;
+const WRITE_SINGLE_REGISTER: u8 = 0x06;
+const WRITE_MULTIPLE_REGISTERS: u8 = 0x10;
+
+
+/// Computes the CRC-16 of a Modbus RTU frame, the polynomial 0xA001 starting at 0xFFFF. It is sent low byte first.
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0xFFFF, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u16, |crc, _| if crc & 1 != 0 { crc >> 1 ^ 0xA001 } else { crc >> 1 })
+    })
+}
+
+
+/// The silence between two frames, 3.5 characters at the bit period of the port but never less than the fixed gap.
+fn frame_gap(bit_period: Duration) -> Duration {
+    // A character is 11 bits long in Modbus RTU
+    (bit_period * 11 * 7 / 2).max(FIXED_FRAME_GAP)
+}
+
+
+/// Why a request failed, a missing response is retried.
+enum Failure {
+    NoResponse,
+    Error(PyErr),
+}
+
+
+impl From<PyErr> for Failure {
+    fn from(e: PyErr) -> Self {
+        Failure::Error(e)
+    }
+}
+
+
+fn exception_name(code: u8) -> &'static str {
+    match code {
+        0x01 => "illegal function",
+        0x02 => "illegal data address",
+        0x03 => "illegal data value",
+        0x04 => "slave device failure",
+        0x05 => "acknowledge",
+        0x06 => "slave device busy",
+        0x08 => "memory parity error",
+        0x0A => "gateway path unavailable",
+        0x0B => "gateway target device failed to respond",
+        _ => "unknown exception",
+    }
+}
+
+
+fn check_slave(slave: u8, broadcast: bool) -> PyResult<()> {
+    let first = if broadcast { 0 } else { 1 };
+    if !(first..=247).contains(&slave) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Slave address must be between {} and 247, The value {} does not meet this condition", first, slave)));
+    }
+    Ok(())
+}
+
+
+fn check_count(count: usize, max: usize) -> PyResult<()> {
+    if !(1..=max).contains(&count) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Register count must be between 1 and {}, The value {} does not meet this condition", max, count)));
+    }
+    Ok(())
+}
+
+
+#[pyclass]
+/// ModbusRTU is a Modbus RTU master on a SoftUART, for the industrial sensors and power meters speaking Modbus over
+/// RS-485. It frames the requests with their CRC, keeps the silence of 3.5 characters between frames and checks the
+/// responses. Put the SoftUART in RS-485 mode with enable_rs485 for a half duplex transceiver.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// uart = gpio_manager.SoftUART(tx_pin=23, rx_pin=24, baud_rate=9600)
+/// uart.enable_rs485(de_pin=18)
+/// modbus = gpio_manager.ModbusRTU(uart)
+/// voltage, current = modbus.read_holding_registers(1, 0x0000, 2)
+/// modbus.write_single_register(1, 0x0010, 500)
+/// ```
+pub struct ModbusRTU {
+    uart: Py<SoftUART>,
+    timeout: Duration,
+    retries: u32,
+    // The end of the last frame on the bus, the next request waits for the silence after it
+    last_frame: Mutex<Option<Instant>>,
+}
+
+
+impl ModbusRTU {
+    /// Sends a request and returns the data of the response after the function code, retrying as configured. The fixed
+    /// length of that data is given, or 0 when a byte count follows the function code. Broadcasts to address 0 aren't
+    /// answered, so None is returned for them.
+    fn request(&self, py: Python<'_>, slave: u8, function: u8, payload: &[u8], response_length: usize) -> PyResult<Option<Vec<u8>>> {
+        let mut frame = vec![slave, function];
+        frame.extend_from_slice(payload);
+        frame.extend_from_slice(&crc16(&frame).to_le_bytes());
+        let uart = self.uart.borrow(py);
+        let uart: &SoftUART = &uart;
+        release_gil(|| {
+            let mut last_frame = self.last_frame.lock().unwrap();
+            let mut attempt = 0;
+            loop {
+                match self.exchange(uart, &mut last_frame, &frame, response_length) {
+                    Err(Failure::NoResponse) if attempt < self.retries => attempt += 1,
+                    Err(Failure::NoResponse) => {
+                        return Err(PyErr::new::<pyo3::exceptions::PyTimeoutError, _>(format!("Slave {} didn't respond within the timeout", slave)));
+                    }
+                    Err(Failure::Error(e)) => return Err(e),
+                    Ok(data) => return Ok(data),
+                }
+            }
+        })
+    }
+
+    /// Sends a frame once the bus was silent long enough and reads the response.
+    fn exchange(&self, uart: &SoftUART, last_frame: &mut Option<Instant>, frame: &[u8], response_length: usize) -> Result<Option<Vec<u8>>, Failure> {
+        if let Some(last_frame) = *last_frame {
+            wait_until(last_frame + frame_gap(uart.bit_period()));
+        }
+        // Bytes left from an earlier response that timed out would be taken as the start of this one
+        uart.clear_input();
+        uart.send(frame)?;
+        let (slave, function) = (frame[0], frame[1]);
+        if slave == 0 {
+            *last_frame = Some(Instant::now() + BROADCAST_TURNAROUND);
+            return Ok(None);
+        }
+        let deadline = Instant::now() + self.timeout;
+        let result = (|| {
+            // The address, the function code and the exception code, byte count or first byte of the data
+            let mut response = uart.read_exact(3, deadline)?;
+            if response.len() < 3 {
+                return Err(Failure::NoResponse);
+            }
+            let remaining = match response[1] {
+                code if code == function | 0x80 => 2,
+                code if code == function && response_length == 0 => response[2] as usize + 2,
+                code if code == function => response_length + 1,
+                code => return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Slave {} responded with function code 0x{:02x} to a request with 0x{:02x}", slave, code, function)).into()),
+            };
+            response.extend(uart.read_exact(remaining, deadline)?);
+            if response.len() < 3 + remaining {
+                return Err(Failure::NoResponse);
+            }
+            let (body, crc) = response.split_at(response.len() - 2);
+            if crc16(body).to_le_bytes() != crc {
+                return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("The response of slave {} has a wrong CRC", slave)).into());
+            }
+            if body[0] != slave {
+                return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Slave {} responded to a request to slave {}", body[0], slave)).into());
+            }
+            if body[1] & 0x80 != 0 {
+                return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Slave {} responded with exception 0x{:02x} ({})", slave, body[2], exception_name(body[2]))).into());
+            }
+            Ok(Some(body[2..].to_vec()))
+        })();
+        *last_frame = Some(Instant::now());
+        result
+    }
+
+    fn read_registers(&self, py: Python<'_>, function: u8, slave: u8, address: u16, count: usize) -> PyResult<Vec<u16>> {
+        check_slave(slave, false)?;
+        check_count(count, 125)?;
+        let mut payload = address.to_be_bytes().to_vec();
+        payload.extend_from_slice(&(count as u16).to_be_bytes());
+        let data = self.request(py, slave, function, &payload, 0)?.unwrap_or_default();
+        if data.len() != 1 + count * 2 || data[0] as usize != count * 2 {
+            return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Slave {} returned {} bytes for {} registers", slave, data.len().saturating_sub(1), count)));
+        }
+        Ok(data[1..].chunks(2).map(|word| u16::from_be_bytes([word[0], word[1]])).collect())
+    }
+}
+
+
+#[pymethods]
+impl ModbusRTU {
+    #[new]
+    /// Initializes a Modbus RTU master on a SoftUART with an RX and a TX pin. The port is shared, so it shouldn't be
+    /// read by anything else while requests are made.
+    ///
+    /// Parameters:
+    /// - `uart` (SoftUART): The serial port of the bus, 8N1 frames are accepted by most slaves.
+    /// - `timeout_ms` (float): How long a slave has to respond in milliseconds (default is 1000).
+    /// - `retries` (int): How many times a request without a response is sent again (default is 0).
+    ///
+    /// Example usage:
+    /// ```python
+    /// modbus = gpio_manager.ModbusRTU(uart, timeout_ms=500, retries=2)
+    /// ```
+    #[pyo3(signature = (uart, timeout_ms = 1000f64, retries = 0))]
+    fn new(uart: Py<SoftUART>, timeout_ms: f64, retries: u32) -> PyResult<Self> {
+        if timeout_ms <= 0f64 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Timeout must be greater than 0, The value {} does not meet this condition", timeout_ms)));
+        }
+        let timeout = ms_to_duration_checked("Timeout", timeout_ms)?;
+        Ok(Self { uart, timeout, retries, last_frame: Mutex::new(None) })
+    }
+
+    /// Reads consecutive holding registers of a slave (function code 0x03).
+    ///
+    /// Parameters:
+    /// - `slave` (int): The address of the slave (1 to 247).
+    /// - `address` (int): The address of the first register.
+    /// - `count` (int): The number of registers to read (1 to 125).
+    ///
+    /// Returns:
+    /// - `list[int]`: The values of the registers.
+    ///
+    /// Example usage:
+    /// ```python
+    /// values = modbus.read_holding_registers(1, 0x0000, 2)
+    /// ```
+    #[pyo3(signature = (slave, address, count = 1))]
+    fn read_holding_registers(&self, py: Python<'_>, slave: u8, address: u16, count: usize) -> PyResult<Vec<u16>> {
+        self.read_registers(py, READ_HOLDING_REGISTERS, slave, address, count)
+    }
+
+    /// Reads consecutive input registers of a slave (function code 0x04).
+    ///
+    /// Parameters:
+    /// - `slave` (int): The address of the slave (1 to 247).
+    /// - `address` (int): The address of the first register.
+    /// - `count` (int): The number of registers to read (1 to 125).
+    ///
+    /// Returns:
+    /// - `list[int]`: The values of the registers.
+    ///
+    /// Example usage:
+    /// ```python
+    /// values = modbus.read_input_registers(1, 0x0000, 4)
+    /// ```
+    #[pyo3(signature = (slave, address, count = 1))]
+    fn read_input_registers(&self, py: Python<'_>, slave: u8, address: u16, count: usize) -> PyResult<Vec<u16>> {
+        self.read_registers(py, READ_INPUT_REGISTERS, slave, address, count)
+    }
+
+    /// Writes a single holding register of a slave (function code 0x06). Slave 0 broadcasts the write to every slave,
+    /// which don't respond.
+    ///
+    /// Parameters:
+    /// - `slave` (int): The address of the slave (0 to 247).
+    /// - `address` (int): The address of the register.
+    /// - `value` (int): The value to write.
+    ///
+    /// Example usage:
+    /// ```python
+    /// modbus.write_single_register(1, 0x0010, 500)
+    /// ```
+    #[pyo3(signature = (slave, address, value))]
+    fn write_single_register(&self, py: Python<'_>, slave: u8, address: u16, value: u16) -> PyResult<()> {
+        check_slave(slave, true)?;
+        let mut payload = address.to_be_bytes().to_vec();
+        payload.extend_from_slice(&value.to_be_bytes());
+        // The response echoes the request
+        if let Some(data) = self.request(py, slave, WRITE_SINGLE_REGISTER, &payload, 4)? {
+            if data != payload {
+                return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Slave {} didn't echo the written register", slave)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes consecutive holding registers of a slave (function code 0x10). Slave 0 broadcasts the write to every
+    /// slave, which don't respond.
+    ///
+    /// Parameters:
+    /// - `slave` (int): The address of the slave (0 to 247).
+    /// - `address` (int): The address of the first register.
+    /// - `values` (list[int]): The values to write (1 to 123 registers).
+    ///
+    /// Example usage:
+    /// ```python
+    /// modbus.write_multiple_registers(1, 0x0020, [100, 200, 300])
+    /// ```
+    #[pyo3(signature = (slave, address, values))]
+    fn write_multiple_registers(&self, py: Python<'_>, slave: u8, address: u16, values: Vec<u16>) -> PyResult<()> {
+        check_slave(slave, true)?;
+        check_count(values.len(), 123)?;
+        let mut payload = address.to_be_bytes().to_vec();
+        payload.extend_from_slice(&(values.len() as u16).to_be_bytes());
+        payload.push((values.len() * 2) as u8);
+        payload.extend(values.iter().flat_map(|value| value.to_be_bytes()));
+        // The response holds the address and count of the registers written
+        if let Some(data) = self.request(py, slave, WRITE_MULTIPLE_REGISTERS, &payload, 4)? {
+            if data != payload[..4] {
+                return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Slave {} acknowledged other registers than the ones written", slave)));
+            }
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc_of_a_request() {
+        assert_eq!(crc16(&[0x01, 0x03, 0x00, 0x00, 0x00, 0x01]), 0x0A84);
+        assert_eq!(crc16(&[0x01, 0x03, 0x00, 0x00, 0x00, 0x01, 0x84, 0x0A]), 0);
+        assert_eq!(crc16(&[]), 0xFFFF);
+    }
+
+    #[test]
+    fn frame_gap_follows_slow_baud_rates() {
+        assert_eq!(frame_gap(Duration::from_micros(100)), Duration::from_micros(3850));
+        assert_eq!(frame_gap(Duration::from_secs_f64(1f64 / 300f64)), Duration::from_secs_f64(1f64 / 300f64) * 77 / 2);
+    }
+
+    #[test]
+    fn frame_gap_is_fixed_above_19200_baud() {
+        assert_eq!(frame_gap(Duration::from_secs_f64(1f64 / 38400f64)), FIXED_FRAME_GAP);
+        assert_eq!(frame_gap(Duration::from_secs_f64(1f64 / 115200f64)), FIXED_FRAME_GAP);
+        assert!(frame_gap(Duration::from_secs_f64(1f64 / 19200f64)) > FIXED_FRAME_GAP);
+    }
+}
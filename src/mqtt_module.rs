@@ -0,0 +1,254 @@
+use crate::gpio_module::GPIOManager;
+use crate::pwm_ramp_module::Easing;
+use crate::{log_module, PinState};
+use once_cell::sync::Lazy;
+use pyo3::{pyclass, pyfunction, pymethods, PyErr, PyResult};
+use rumqttc::{Client, Event, LastWill, MqttOptions, Outgoing, Packet, QoS};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+// Requests buffered for the client thread, edges published while the buffer is full are dropped
+const REQUEST_CAPACITY: usize = 256;
+
+
+/// The running bridge, edges of input pins are published through its client.
+struct Bridge {
+    id: u64,
+    client: Client,
+    prefix: String,
+    /// The input pins published, every input pin for None.
+    pins: Option<HashSet<u8>>,
+    stopped: Arc<AtomicBool>,
+}
+
+
+impl Bridge {
+    fn publishes(&self, pin_num: u8) -> bool {
+        self.pins.as_ref().is_none_or(|pins| pins.contains(&pin_num))
+    }
+
+    /// Returns the topic the bridge publishes "online" and its last will "offline" to.
+    fn status_topic(prefix: &str) -> String {
+        format!("{}/status", prefix)
+    }
+}
+
+
+static BRIDGE: Lazy<Mutex<Option<Bridge>>> = Lazy::new(|| Mutex::new(None));
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+
+fn state_payload(state: PinState) -> &'static str {
+    if state == PinState::HIGH { "1" } else { "0" }
+}
+
+
+fn parse_state(payload: &str) -> Option<PinState> {
+    match payload.trim().to_ascii_uppercase().as_str() {
+        "1" | "ON" | "HIGH" | "TRUE" => Some(PinState::HIGH),
+        "0" | "OFF" | "LOW" | "FALSE" => Some(PinState::LOW),
+        _ => None,
+    }
+}
+
+
+/// Publishes the state of an input pin after an edge, if the bridge is running and the pin is published.
+pub fn publish_state(pin_num: u8, state: PinState) {
+    let bridge = BRIDGE.lock().unwrap();
+    if let Some(bridge) = bridge.as_ref().filter(|bridge| bridge.publishes(pin_num)) {
+        let topic = format!("{}/{}/state", bridge.prefix, pin_num);
+        if bridge.client.try_publish(topic, QoS::AtLeastOnce, true, state_payload(state)).is_err() {
+            log_module::debug(&format!("Dropped the MQTT state of GPIO {}, the client is not keeping up", pin_num));
+        }
+    }
+}
+
+
+/// Returns whether the edges of the pin are published by the bridge.
+pub fn is_published(pin_num: u8) -> bool {
+    BRIDGE.lock().unwrap().as_ref().is_some_and(|bridge| bridge.publishes(pin_num))
+}
+
+
+/// Runs a command received on `<prefix>/<pin>/set` or `<prefix>/<pin>/pwm/set`.
+fn run_command(gpio_manager: &GPIOManager, prefix: &str, topic: &str, payload: &[u8]) -> PyResult<()> {
+    let payload = String::from_utf8_lossy(payload);
+    let command = topic.strip_prefix(prefix).and_then(|topic| topic.strip_prefix('/')).unwrap_or(topic);
+    let (pin, action) = command.split_once('/').unwrap_or((command, ""));
+    let pin_num: u8 = pin.parse().map_err(|_| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The topic {} does not name a GPIO pin", topic)))?;
+    match action {
+        "set" => match parse_state(&payload) {
+            Some(state) => gpio_manager.set_output_pin(pin_num, state.into()),
+            None => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The state must be 1, 0, ON, OFF, HIGH, LOW, TRUE or FALSE, The value {} does not meet this condition", payload))),
+        },
+        "pwm/set" => match payload.trim().parse::<f64>() {
+            Ok(duty_cycle) => gpio_manager.set_pwm_duty_cycle(pin_num, duty_cycle, None, Easing::LINEAR),
+            Err(_) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The duty cycle must be a number, The value {} does not meet this condition", payload))),
+        },
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The topic {} is not a command", topic))),
+    }
+}
+
+
+/// Publishes the current state of the published input pins, so retained states are correct after a reconnect.
+fn publish_states(gpio_manager: &GPIOManager, client: &Client, prefix: &str, pins: &Option<HashSet<u8>>) {
+    for pin_num in gpio_manager.input_pin_numbers() {
+        if pins.as_ref().is_some_and(|pins| !pins.contains(&pin_num)) {
+            continue;
+        }
+        if let Ok(state) = gpio_manager.get_pin(pin_num) {
+            let _ = client.try_publish(format!("{}/{}/state", prefix, pin_num), QoS::AtLeastOnce, true, state_payload(state));
+        }
+    }
+}
+
+
+#[pyclass]
+/// Handle of the MQTT bridge started with mqtt_bridge.
+pub struct MQTTBridge {
+    id: u64,
+    connected: Arc<AtomicBool>,
+}
+
+
+#[pymethods]
+impl MQTTBridge {
+    /// Returns whether the bridge is connected to the broker.
+    ///
+    /// Example usage:
+    /// ```bridge.is_connected()```
+    ///
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    /// Stops the bridge, publishing "offline" as its status and disconnecting from the broker. Stopping a bridge that
+    /// was replaced or already stopped does nothing.
+    ///
+    /// Example usage:
+    /// ```bridge.stop()```
+    ///
+    fn stop(&self) {
+        let mut bridge = BRIDGE.lock().unwrap();
+        if bridge.as_ref().is_some_and(|bridge| bridge.id == self.id) {
+            stop_bridge(bridge.take().unwrap());
+            drop(bridge);
+            GPIOManager::new_rust_reference().release_unobserved_pins();
+        }
+    }
+}
+
+
+/// Stops a bridge, publishing "offline" as its status before disconnecting.
+fn stop_bridge(bridge: Bridge) {
+    bridge.stopped.store(true, Ordering::SeqCst);
+    let _ = bridge.client.try_publish(Bridge::status_topic(&bridge.prefix), QoS::AtLeastOnce, true, "offline");
+    let _ = bridge.client.try_disconnect();
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (broker, topic_prefix, port = 1883, client_id = None, username = None, password = None, pins = None))]
+/// Starts a bridge between the pins and an MQTT broker, run by a Rust client thread so it keeps working while Python
+/// is busy. A bridge already running is replaced.
+///
+/// The state of the input pins is published to `<topic_prefix>/<pin>/state` as "1" or "0" after every edge, retained
+/// so new subscribers get the current state. Output pins are set by publishing 1, 0, ON, OFF, HIGH, LOW, TRUE or FALSE
+/// to `<topic_prefix>/<pin>/set`, and the duty cycle of PWM pins by publishing 0 to 100 to
+/// `<topic_prefix>/<pin>/pwm/set`. The bridge publishes "online" to `<topic_prefix>/status` and the broker publishes
+/// "offline" when the connection is lost.
+///
+/// Parameters:
+/// - ```broker``` (str): The host name or address of the broker.
+/// - ```topic_prefix``` (str): The prefix of the topics of the bridge.
+/// - ```port``` (int): The port of the broker (default is 1883).
+/// - ```client_id``` (str): The client id, derived from the prefix if None (default is None).
+/// - ```username``` (str): The user name for brokers requiring a login (default is None).
+/// - ```password``` (str): The password of the user (default is None).
+/// - ```pins``` (list[int]): The input pins published, every input pin if None (default is None). Pins set up later
+///   are published as well.
+///
+/// Returns:
+/// - ```MQTTBridge```: The handle of the bridge.
+///
+/// Input pins without callbacks are debounced in hardware for 2 ms.
+///
+/// Example usage:
+/// ```python
+/// manager.add_input_pin(17)
+/// manager.add_output_pin(27)
+/// bridge = gpio_manager.mqtt_bridge("localhost", "home/pi")
+/// ```
+pub fn mqtt_bridge(broker: &str, topic_prefix: &str, port: u16, client_id: Option<String>, username: Option<String>, password: Option<String>,
+                   pins: Option<Vec<u8>>) -> PyResult<MQTTBridge> {
+    let prefix = topic_prefix.trim_end_matches('/').to_string();
+    if prefix.is_empty() || prefix.contains(['+', '#']) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The topic prefix must not be empty or contain wildcards, The value {} does not meet this condition", topic_prefix)));
+    }
+    let client_id = client_id.unwrap_or_else(|| format!("gpio_manager-{}", prefix.replace('/', "-")));
+    let mut options = MqttOptions::new(client_id, broker, port);
+    options.set_keep_alive(KEEP_ALIVE);
+    options.set_last_will(LastWill::new(Bridge::status_topic(&prefix), "offline", QoS::AtLeastOnce, true));
+    if let Some(username) = username {
+        options.set_credentials(username, password.unwrap_or_default());
+    }
+    let (client, mut connection) = Client::new(options, REQUEST_CAPACITY);
+
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    let connected = Arc::new(AtomicBool::new(false));
+    let stopped = Arc::new(AtomicBool::new(false));
+    let pins: Option<HashSet<u8>> = pins.map(|pins| pins.into_iter().collect());
+
+    let thread_client = client.clone();
+    let thread_prefix = prefix.clone();
+    let thread_pins = pins.clone();
+    let thread_connected = Arc::clone(&connected);
+    let thread_stopped = Arc::clone(&stopped);
+    thread::spawn(move || {
+        let gpio_manager = GPIOManager::new_rust_reference();
+        for event in connection.iter() {
+            match event {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    thread_connected.store(true, Ordering::SeqCst);
+                    log_module::info(&format!("Connected to the MQTT broker, bridging the pins under {}", thread_prefix));
+                    let _ = thread_client.try_subscribe(format!("{}/+/set", thread_prefix), QoS::AtLeastOnce);
+                    let _ = thread_client.try_subscribe(format!("{}/+/pwm/set", thread_prefix), QoS::AtLeastOnce);
+                    let _ = thread_client.try_publish(Bridge::status_topic(&thread_prefix), QoS::AtLeastOnce, true, "online");
+                    publish_states(&gpio_manager, &thread_client, &thread_prefix, &thread_pins);
+                }
+                Ok(Event::Incoming(Packet::Publish(publish))) => {
+                    if let Err(e) = run_command(&gpio_manager, &thread_prefix, &publish.topic, &publish.payload) {
+                        log_module::warning(&format!("The MQTT command on {} failed: {}", publish.topic, e));
+                    }
+                }
+                Ok(Event::Outgoing(Outgoing::Disconnect)) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    if thread_stopped.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if thread_connected.swap(false, Ordering::SeqCst) {
+                        log_module::warning(&format!("Lost the connection to the MQTT broker, reconnecting: {}", e));
+                    }
+                    thread::sleep(RECONNECT_DELAY);
+                }
+            }
+        }
+        thread_connected.store(false, Ordering::SeqCst);
+    });
+
+    let bridge = Bridge { id, client, prefix, pins: pins.clone(), stopped };
+    if let Some(previous) = BRIDGE.lock().unwrap().replace(bridge) {
+        stop_bridge(previous);
+    }
+    let gpio_manager = GPIOManager::new_rust_reference();
+    gpio_manager.release_unobserved_pins();
+    gpio_manager.observe_pins(pins.map(|pins| pins.into_iter().collect()))?;
+    Ok(MQTTBridge { id, connected })
+}
@@ -0,0 +1,167 @@
+use crate::board_module::Peripheral;
+#[cfg(feature = "python")]
+use pyo3::prelude::*;
+#[cfg(feature = "python")]
+use pyo3::types::{PyDict, PyList};
+#[cfg(feature = "python")]
+use pyo3::{pyfunction, PyResult, Python};
+use std::fs;
+use std::path::Path;
+
+
+// The firmware lists the overlays it applied at boot as the properties of this node
+const CHOSEN_OVERLAYS: &str = "/proc/device-tree/chosen/overlays";
+// The overlays applied at runtime with dtoverlay
+const RUNTIME_OVERLAYS: &str = "/sys/kernel/config/device-tree/overlays";
+// Where the firmware reads config.txt from, /boot/firmware since Bookworm and /boot before
+const CONFIG_PATHS: [&str; 2] = ["/boot/firmware/config.txt", "/boot/config.txt"];
+// The interfaces reported by interface_status, in the order they are reported
+pub const INTERFACES: [Peripheral; 4] = [Peripheral::I2c, Peripheral::Spi, Peripheral::Uart, Peripheral::Pwm];
+
+
+/// Returns the path of config.txt, or None when it can't be found.
+fn config_path() -> Option<&'static str> {
+    CONFIG_PATHS.iter().copied().find(|path| Path::new(path).exists())
+}
+
+
+/// Returns the settings of config.txt as `(key, value)`, such as `("dtoverlay", "pwm-2chan")`, without the comments. The conditional
+/// sections are not evaluated, so the overlays of every model are returned.
+fn config_entries() -> Vec<(String, String)> {
+    let config = match config_path().and_then(|path| fs::read_to_string(path).ok()) {
+        Some(config) => config,
+        None => return Vec::new(),
+    };
+    config.lines()
+          .map(|line| line.split('#').next().unwrap_or("").trim())
+          .filter_map(|line| line.split_once('='))
+          .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+          .collect()
+}
+
+
+/// Returns the names of the entries of a directory, or nothing when it doesn't exist.
+fn entry_names(path: &str) -> Vec<String> {
+    let mut names: Vec<String> = match fs::read_dir(path) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok())
+                              .map(|entry| entry.file_name().to_string_lossy().to_string())
+                              .collect(),
+        Err(_) => Vec::new(),
+    };
+    names.sort();
+    names
+}
+
+
+/// Returns the device tree overlays in use: the ones the firmware applied at boot, the ones loaded at runtime and the
+/// ones named in config.txt, each once and without their parameters.
+pub fn overlays_in_use() -> Vec<String> {
+    let mut overlays = Vec::new();
+    let booted = entry_names(CHOSEN_OVERLAYS).into_iter().filter(|name| name != "name");
+    let configured = config_entries().into_iter()
+                                     .filter(|(key, _)| key == "dtoverlay")
+                                     .filter_map(|(_, value)| value.split(',').next().map(|name| name.trim().to_string()));
+    for overlay in booted.chain(entry_names(RUNTIME_OVERLAYS)).chain(configured) {
+        if !overlay.is_empty() && !overlays.contains(&overlay) {
+            overlays.push(overlay);
+        }
+    }
+    overlays
+}
+
+
+/// Returns the name of an interface as used in the reports.
+pub fn interface_name(interface: Peripheral) -> &'static str {
+    match interface {
+        Peripheral::I2c => "i2c",
+        Peripheral::Spi => "spi",
+        Peripheral::Uart => "uart",
+        Peripheral::Pwm => "pwm",
+        Peripheral::Other => "other",
+    }
+}
+
+
+/// Returns the devices the kernel created for an interface, which only exist while it is enabled.
+pub fn interface_devices(interface: Peripheral) -> Vec<String> {
+    let (dir, prefix) = match interface {
+        Peripheral::I2c => ("/dev", "i2c-"),
+        Peripheral::Spi => ("/dev", "spidev"),
+        Peripheral::Uart => ("/dev", "serial"),
+        Peripheral::Pwm => ("/sys/class/pwm", "pwmchip"),
+        Peripheral::Other => return Vec::new(),
+    };
+    entry_names(dir).into_iter()
+                    .filter(|name| name.starts_with(prefix))
+                    .map(|name| format!("{}/{}", dir, name))
+                    .collect()
+}
+
+
+/// Returns how to enable an interface, naming the line to add to config.txt.
+pub fn enable_hint(interface: Peripheral) -> String {
+    let line = match interface {
+        Peripheral::I2c => "dtparam=i2c_arm=on",
+        Peripheral::Spi => "dtparam=spi=on",
+        Peripheral::Uart => "enable_uart=1",
+        Peripheral::Pwm => "dtoverlay=pwm-2chan",
+        Peripheral::Other => return String::new(),
+    };
+    format!("enable {} in {} and reboot", line, config_path().unwrap_or(CONFIG_PATHS[0]))
+}
+
+
+/// Returns why an operation on an interface failed when the interface isn't enabled, to append to the error of the
+/// operation. None when the interface is enabled, so the error has another cause.
+pub fn disabled_reason(interface: Peripheral) -> Option<String> {
+    if !interface_devices(interface).is_empty() {
+        return None;
+    }
+    Some(format!("the {} interface isn't enabled, {}", interface_name(interface).to_uppercase(), enable_hint(interface)))
+}
+
+
+#[cfg(feature = "python")]
+#[pyfunction]
+/// Returns the device tree overlays in use, the ones applied at boot, loaded at runtime or named in config.txt.
+///
+/// Returns:
+/// - `list[str]`: The names of the overlays, without their parameters.
+///
+/// Example usage:
+/// ```python
+/// if "pwm-2chan" not in gpio_manager.active_overlays():
+///     print("Hardware PWM is not enabled")
+/// ```
+pub fn active_overlays(py: Python) -> PyResult<Bound<PyList>> {
+    PyList::new(py, overlays_in_use())
+}
+
+
+#[cfg(feature = "python")]
+#[pyfunction]
+/// Returns whether the I2C, SPI, UART and PWM interfaces are enabled, found by the devices the kernel created for
+/// them.
+///
+/// Returns:
+/// - `dict`: For every interface by name (`i2c`, `spi`, `uart` and `pwm`) a dict with `enabled` (bool), its
+///   `devices` (list of paths) and a `hint` on how to enable it, None when it is enabled.
+///
+/// Example usage:
+/// ```python
+/// status = gpio_manager.interface_status()
+/// if not status["i2c"]["enabled"]:
+///     print(status["i2c"]["hint"])
+/// ```
+pub fn interface_status(py: Python) -> PyResult<Bound<PyDict>> {
+    let result = PyDict::new(py);
+    for interface in INTERFACES {
+        let devices = interface_devices(interface);
+        let status = PyDict::new(py);
+        status.set_item("enabled", !devices.is_empty())?;
+        status.set_item("hint", if devices.is_empty() { Some(enable_hint(interface)) } else { None })?;
+        status.set_item("devices", devices)?;
+        result.set_item(interface_name(interface), status)?;
+    }
+    Ok(result)
+}
@@ -0,0 +1,194 @@
+use crate::board_module::{device_info, MAX_HEADER_GPIO};
+use crate::error::{GpioError, Result};
+use rppal::system::SoC;
+use std::fs::OpenOptions;
+use std::io;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::ptr;
+
+
+// Pad control registers of the BCM2835, BCM2836, BCM2837 and BCM2711, reached through /dev/mem. GPIO 0 to 27 share
+// the register of bank 0, so its settings apply to all of them.
+const PATH_DEV_MEM: &str = "/dev/mem";
+const PADS_OFFSET: usize = 0x0010_0000;
+const PADS_LEN: usize = 0x1000;
+const PADS_BANK0: usize = 0x2c;
+const BCM_PASSWORD: u32 = 0x5a00_0000;
+const BCM_DRIVE_MASK: u32 = 0x07;
+const BCM_HYSTERESIS: u32 = 0x08;
+const BCM_SLEW_UNLIMITED: u32 = 0x10;
+
+// Pad control registers of the RP1 of the Raspberry Pi 5, one register per pin
+const PATH_DEV_GPIOMEM0: &str = "/dev/gpiomem0";
+const RP1_MEM_LEN: usize = 0x30000;
+const RP1_PADS_BANK0: usize = 0x20000;
+const RP1_SLEW_FAST: u32 = 0x01;
+const RP1_SCHMITT: u32 = 0x02;
+const RP1_DRIVE_MASK: u32 = 0x30;
+const RP1_DRIVE_LSB: u32 = 4;
+const RP1_DRIVE_MA: [u8; 4] = [2, 4, 8, 12];
+
+
+/// A memory mapped block of 32 bit registers.
+pub struct RegisterBlock {
+    ptr: *mut u32,
+    len: usize,
+}
+
+
+impl RegisterBlock {
+    /// Maps `len` bytes of a memory device starting at `offset`, which must be page aligned.
+    pub fn map(path: &str, offset: usize, len: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).custom_flags(libc::O_SYNC).open(path)?;
+        let ptr = unsafe {
+            libc::mmap(ptr::null_mut(), len, libc::PROT_READ | libc::PROT_WRITE, libc::MAP_SHARED, file.as_raw_fd(),
+                       offset as libc::off_t)
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { ptr: ptr as *mut u32, len })
+    }
+
+    /// Reads the register at a byte offset of the block.
+    pub fn read(&self, offset: usize) -> u32 {
+        assert!(offset + 4 <= self.len);
+        unsafe { ptr::read_volatile(self.ptr.add(offset / 4)) }
+    }
+
+    /// Writes the register at a byte offset of the block.
+    pub fn write(&self, offset: usize, value: u32) {
+        assert!(offset + 4 <= self.len);
+        unsafe { ptr::write_volatile(self.ptr.add(offset / 4), value) }
+    }
+}
+
+
+// The registers are only accessed through volatile reads and writes
+unsafe impl Send for RegisterBlock {}
+unsafe impl Sync for RegisterBlock {}
+
+
+impl Drop for RegisterBlock {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.ptr as *mut libc::c_void, self.len) };
+    }
+}
+
+
+/// Electrical settings of the pad of a pin.
+#[derive(Clone, Copy, Debug)]
+pub struct PadConfig {
+    pub drive_ma: u8,
+    pub slew_fast: bool,
+    pub hysteresis: bool,
+}
+
+
+fn peripheral_base(soc: SoC) -> usize {
+    match soc {
+        SoC::Bcm2835 => 0x2000_0000,
+        SoC::Bcm2711 => 0xfe00_0000,
+        _ => 0x3f00_0000,
+    }
+}
+
+
+fn map_error(path: &str, e: io::Error) -> GpioError {
+    GpioError::Hardware(format!("Unable to access the pad control registers through {}, root access may be required: {}", path, e))
+}
+
+
+/// Returns whether the pads of the SoC are configured per pin, the older SoCs configure GPIO 0 to 27 together.
+pub fn per_pin_pads(soc: SoC) -> bool {
+    soc == SoC::Bcm2712
+}
+
+
+/// Returns the drive strengths in mA the pads of the SoC support.
+pub fn drive_strengths(soc: SoC) -> Vec<u8> {
+    if per_pin_pads(soc) {
+        RP1_DRIVE_MA.to_vec()
+    } else {
+        (1..=8).map(|step| step * 2).collect()
+    }
+}
+
+
+fn check_pin(pin_num: u8) -> Result<()> {
+    if pin_num > MAX_HEADER_GPIO {
+        return Err(GpioError::InvalidArgument(format!("Pad control is limited to GPIO 0 to {}, The value {} does not meet this condition", MAX_HEADER_GPIO, pin_num)));
+    }
+    Ok(())
+}
+
+
+fn decode(soc: SoC, value: u32) -> PadConfig {
+    if per_pin_pads(soc) {
+        PadConfig {
+            drive_ma: RP1_DRIVE_MA[((value & RP1_DRIVE_MASK) >> RP1_DRIVE_LSB) as usize],
+            slew_fast: value & RP1_SLEW_FAST != 0,
+            hysteresis: value & RP1_SCHMITT != 0,
+        }
+    } else {
+        PadConfig {
+            drive_ma: (((value & BCM_DRIVE_MASK) + 1) * 2) as u8,
+            slew_fast: value & BCM_SLEW_UNLIMITED != 0,
+            hysteresis: value & BCM_HYSTERESIS != 0,
+        }
+    }
+}
+
+
+fn encode(soc: SoC, value: u32, config: &PadConfig) -> Result<u32> {
+    let strengths = drive_strengths(soc);
+    let Some(drive) = strengths.iter().position(|ma| *ma == config.drive_ma) else {
+        return Err(GpioError::InvalidArgument(format!("The drive strength must be one of {:?} mA on the {}, The value {} does not meet this condition",
+                                                      strengths, soc, config.drive_ma)));
+    };
+    let drive = drive as u32;
+    let flag = |set: bool, bit: u32| if set { bit } else { 0 };
+    if per_pin_pads(soc) {
+        Ok(value & !(RP1_DRIVE_MASK | RP1_SLEW_FAST | RP1_SCHMITT)
+            | drive << RP1_DRIVE_LSB
+            | flag(config.slew_fast, RP1_SLEW_FAST)
+            | flag(config.hysteresis, RP1_SCHMITT))
+    } else {
+        Ok(BCM_PASSWORD | drive | flag(config.slew_fast, BCM_SLEW_UNLIMITED) | flag(config.hysteresis, BCM_HYSTERESIS))
+    }
+}
+
+
+/// Maps the pad control registers of the SoC, returning them with the offset of the register of the pin.
+fn pad_register(soc: SoC, pin_num: u8) -> Result<(RegisterBlock, usize)> {
+    if per_pin_pads(soc) {
+        let registers = RegisterBlock::map(PATH_DEV_GPIOMEM0, 0, RP1_MEM_LEN).map_err(|e| map_error(PATH_DEV_GPIOMEM0, e))?;
+        Ok((registers, RP1_PADS_BANK0 + 4 + pin_num as usize * 4))
+    } else {
+        let registers = RegisterBlock::map(PATH_DEV_MEM, peripheral_base(soc) + PADS_OFFSET, PADS_LEN).map_err(|e| map_error(PATH_DEV_MEM, e))?;
+        Ok((registers, PADS_BANK0))
+    }
+}
+
+
+/// Reads the pad settings of a pin of the header.
+pub fn read_pad(pin_num: u8) -> Result<PadConfig> {
+    check_pin(pin_num)?;
+    let soc = device_info()?.soc();
+    let (registers, offset) = pad_register(soc, pin_num)?;
+    Ok(decode(soc, registers.read(offset)))
+}
+
+
+/// Changes the pad settings of a pin of the header, on SoCs without per pin pads the change applies to GPIO 0 to 27.
+pub fn update_pad(pin_num: u8, update: impl FnOnce(&mut PadConfig)) -> Result<()> {
+    check_pin(pin_num)?;
+    let soc = device_info()?.soc();
+    let (registers, offset) = pad_register(soc, pin_num)?;
+    let value = registers.read(offset);
+    let mut config = decode(soc, value);
+    update(&mut config);
+    registers.write(offset, encode(soc, value, &config)?);
+    Ok(())
+}
@@ -0,0 +1,417 @@
+use crate::backend::InputPin;
+use crate::gpio_module::GPIOManager;
+use crate::log_module;
+use crate::pwm_output_module::PWM;
+use crate::pwm_ramp_module::Easing;
+use crate::{frequency_to_period_checked, release_gil, EnumArg, InternPullResistorState, LogicLevel};
+use pyo3::prelude::*;
+use rppal::gpio::Trigger;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+
+// Time without a pulse after which a tachometer reads 0 RPM
+const STALL_TIMEOUT: Duration = Duration::from_secs(1);
+
+
+/// The pulses counted on the input of a tachometer.
+#[derive(Clone, Copy, Default)]
+struct TachEdges {
+    count: u64,
+    last_edge: Option<Instant>,
+}
+
+
+/// Measures a speed in RPM from the pulses of a tachometer, from the time between the pulses so the speed isn't
+/// rounded to the pulses that fit in a period of the loop.
+struct Tachometer {
+    pin_num: u8,
+    // The pin is held so its interrupt keeps counting
+    _pin: Arc<Mutex<InputPin>>,
+    edges: Arc<Mutex<TachEdges>>,
+    pulses_per_revolution: f64,
+    // The pulses and speed of the previous reading
+    previous: Mutex<(TachEdges, f64)>,
+}
+
+
+impl Tachometer {
+    fn new(pin_num: u8, pulses_per_revolution: f64) -> PyResult<Self> {
+        // Fan tachometers are open collector outputs, so the pin is pulled up
+        let pin = GPIOManager::new_rust_reference().claim_input_pin(pin_num, InternPullResistorState::PULLUP)?;
+        let edges = Arc::new(Mutex::new(TachEdges::default()));
+        let counter = Arc::clone(&edges);
+        pin.lock().unwrap().set_async_interrupt(Trigger::FallingEdge, None, move |_| {
+            let mut edges = counter.lock().unwrap();
+            edges.count += 1;
+            edges.last_edge = Some(Instant::now());
+        }).map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Error setting up async interrupt: {:?}", e)))?;
+        Ok(Self { pin_num, _pin: pin, edges, pulses_per_revolution, previous: Mutex::new((TachEdges::default(), 0f64)) })
+    }
+
+    fn rpm(&self) -> f64 {
+        let edges = *self.edges.lock().unwrap();
+        let mut previous = self.previous.lock().unwrap();
+        let (previous_edges, previous_rpm) = *previous;
+        let rpm = match (previous_edges.last_edge, edges.last_edge) {
+            (Some(from), Some(to)) if edges.count > previous_edges.count => {
+                let revolutions = (edges.count - previous_edges.count) as f64 / self.pulses_per_revolution;
+                revolutions / (to - from).as_secs_f64() * 60f64
+            }
+            (_, Some(last_edge)) if last_edge.elapsed() < STALL_TIMEOUT => previous_rpm,
+            _ => 0f64,
+        };
+        *previous = (edges, rpm);
+        rpm
+    }
+}
+
+
+/// Where the controller reads its measurement from.
+enum Source {
+    Callback(PyObject),
+    Tachometer(Tachometer),
+}
+
+
+impl Source {
+    fn read(&self) -> PyResult<f64> {
+        match self {
+            Source::Callback(callback) => Python::with_gil(|py| callback.call0(py)?.extract::<f64>(py)),
+            Source::Tachometer(tachometer) => Ok(tachometer.rpm()),
+        }
+    }
+}
+
+
+/// The tunable settings of a controller, read by the loop at every step.
+#[derive(Clone, Copy)]
+struct PidSettings {
+    kp: f64,
+    ki: f64,
+    kd: f64,
+    setpoint: f64,
+    output_min: f64,
+    output_max: f64,
+}
+
+
+/// The state of the loop between two steps.
+#[derive(Clone, Copy, Default)]
+struct PidState {
+    integral: f64,
+    measurement: Option<f64>,
+    output: f64,
+}
+
+
+impl PidState {
+    /// Runs a step of the loop for a measurement taken `dt` seconds after the previous one, returning the output.
+    fn step(&mut self, settings: &PidSettings, measurement: f64, dt: f64) -> f64 {
+        let error = settings.setpoint - measurement;
+        // The derivative of the measurement rather than of the error, so changing the setpoint doesn't kick the output
+        let derivative = match self.measurement {
+            Some(previous) if dt > 0f64 => -(measurement - previous) / dt,
+            _ => 0f64,
+        };
+        let integral = self.integral + error * dt;
+        let unclamped = settings.kp * error + settings.ki * integral + settings.kd * derivative;
+        let output = unclamped.clamp(settings.output_min, settings.output_max);
+        // Anti-windup: the integral only grows while the output isn't saturated, or when the error brings it back
+        if unclamped == output || (unclamped > output) != (error > 0f64) {
+            self.integral = integral;
+        }
+        self.measurement = Some(measurement);
+        self.output = output;
+        output
+    }
+}
+
+
+/// Runs the loop at the rate until the stop channel is signaled or dropped. The output is held while the measurement
+/// can't be read.
+fn control(pwm: Arc<PWM>, source: Arc<Source>, settings: Arc<Mutex<PidSettings>>, state: Arc<Mutex<PidState>>,
+           period: Duration, stop: Receiver<()>) {
+    let mut deadline = Instant::now();
+    let mut previous_step: Option<Instant> = None;
+    loop {
+        match source.read() {
+            Ok(measurement) => {
+                let now = Instant::now();
+                let dt = previous_step.map_or(0f64, |previous| (now - previous).as_secs_f64());
+                previous_step = Some(now);
+                let settings = *settings.lock().unwrap();
+                let output = state.lock().unwrap().step(&settings, measurement, dt);
+                if let Err(e) = pwm.set_duty_cycle(output, None, Easing::LINEAR) {
+                    log_module::exception("Failed to set the output of the PID controller", &e);
+                }
+            }
+            Err(e) => log_module::exception("Failed to read the measurement of the PID controller, holding the output", &e),
+        }
+        // Steps are timed from absolute deadlines so the rate doesn't drift, a late loop starts over from now
+        deadline += period;
+        let now = Instant::now();
+        if deadline < now {
+            deadline = now;
+        }
+        if !matches!(stop.recv_timeout(deadline - now), Err(RecvTimeoutError::Timeout)) {
+            return;
+        }
+    }
+}
+
+
+fn check_limits(output_min: f64, output_max: f64) -> PyResult<()> {
+    if !(0f64..=100f64).contains(&output_min) || !(0f64..=100f64).contains(&output_max) || output_min >= output_max {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "The output limits must be between 0 and 100 with the minimum below the maximum, {} and {} do not meet this condition", output_min, output_max)));
+    }
+    Ok(())
+}
+
+
+#[pyclass]
+/// PIDController holds a measurement at a setpoint by driving a PWM output, for example the speed of a fan or motor.
+/// The loop runs at a fixed rate on a Rust thread, reading the speed of a tachometer from the pulses on an input pin or
+/// any value, such as an ADC reading, through a function. The integral stops growing while the output is saturated,
+/// so the controller recovers quickly when the setpoint can be reached again.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// fan = gpio_manager.PIDController(18, kp=0.02, ki=0.05, setpoint=1500, tach_pin=24, frequency_hz=25000)
+/// fan.start()
+/// print(fan.get_measurement(), fan.get_output())
+/// ```
+pub struct PIDController {
+    pwm: Arc<PWM>,
+    source: Arc<Source>,
+    settings: Arc<Mutex<PidSettings>>,
+    state: Arc<Mutex<PidState>>,
+    period: Duration,
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+
+impl PIDController {
+    fn stop_thread(&mut self) {
+        // Dropping the sender wakes the thread up, the GIL is released as it may be waiting for it to read the source
+        self.stop = None;
+        if let Some(thread) = self.thread.take() {
+            release_gil(|| {
+                let _ = thread.join();
+            });
+        }
+    }
+}
+
+
+impl Drop for PIDController {
+    fn drop(&mut self) {
+        self.stop_thread();
+    }
+}
+
+
+#[pymethods]
+impl PIDController {
+    #[new]
+    /// Sets up the PWM output, through the hardware PWM channel of the pin when it has one, and the measurement source.
+    /// The output stays off until the controller is started.
+    ///
+    /// Parameters:
+    /// - `output_pin` (int): The GPIO pin of the PWM output.
+    /// - `kp` (float): The proportional gain, in duty cycle percent per unit of error.
+    /// - `ki` (float): The integral gain, per unit of error and second (default is 0).
+    /// - `kd` (float): The derivative gain, per unit of error per second (default is 0).
+    /// - `setpoint` (float): The value the measurement is held at (default is 0).
+    /// - `measure` (Callable[[], float]): A function returning the measurement, for example an ADC reading (default is
+    ///   None).
+    /// - `tach_pin` (int): A free pin receiving the pulses of a tachometer, the measurement is its speed in RPM, used
+    ///   in place of a function (default is None).
+    /// - `pulses_per_revolution` (float): The pulses of the tachometer per revolution, PC fans give 2 (default is 2).
+    /// - `rate_hz` (float): The rate of the loop in Hertz (default is 100).
+    /// - `output_min` (float): The lowest duty cycle of the output (default is 0).
+    /// - `output_max` (float): The highest duty cycle of the output (default is 100).
+    /// - `frequency_hz` (float): The PWM frequency in Hertz (default is 1000).
+    ///
+    /// Example usage:
+    /// ```python
+    /// heater = gpio_manager.PIDController(12, kp=8, ki=0.5, setpoint=45, measure=lambda: sensor.read_celsius(), rate_hz=10)
+    /// ```
+    #[pyo3(signature = (output_pin, kp, ki = 0f64, kd = 0f64, setpoint = 0f64, measure = None, tach_pin = None,
+                        pulses_per_revolution = 2f64, rate_hz = 100f64, output_min = 0f64, output_max = 100f64, frequency_hz = 1000f64))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(output_pin: u8, kp: f64, ki: f64, kd: f64, setpoint: f64, measure: Option<PyObject>, tach_pin: Option<u8>,
+           pulses_per_revolution: f64, rate_hz: f64, output_min: f64, output_max: f64, frequency_hz: f64) -> PyResult<Self> {
+        let period = frequency_to_period_checked("Rate", rate_hz)?;
+        if pulses_per_revolution <= 0f64 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Pulses per revolution must be greater than 0, The value {} does not meet this condition", pulses_per_revolution)));
+        }
+        check_limits(output_min, output_max)?;
+        let source = match (measure, tach_pin) {
+            (Some(measure), None) => Source::Callback(measure),
+            (None, Some(tach_pin)) => Source::Tachometer(Tachometer::new(tach_pin, pulses_per_revolution)?),
+            _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Either a measure function or a tachometer pin must be given")),
+        };
+        let pwm = match PWM::new(output_pin, Some(frequency_hz), Some(0f64), None, None, EnumArg(LogicLevel::HIGH)) {
+            Ok(pwm) => pwm,
+            Err(e) => {
+                if let Source::Tachometer(tachometer) = &source {
+                    let _ = GPIOManager::new_rust_reference().reset_pin(tachometer.pin_num);
+                }
+                return Err(e);
+            }
+        };
+        Ok(Self {
+            pwm: Arc::new(pwm),
+            source: Arc::new(source),
+            settings: Arc::new(Mutex::new(PidSettings { kp, ki, kd, setpoint, output_min, output_max })),
+            state: Arc::new(Mutex::new(PidState::default())),
+            period,
+            stop: None,
+            thread: None,
+        })
+    }
+
+    /// Starts the PWM output and the loop from a cleared integral.
+    ///
+    /// Example usage:
+    /// ```python
+    /// controller.start()
+    /// ```
+    fn start(&mut self) -> PyResult<()> {
+        if self.thread.is_some() {
+            return Ok(());
+        }
+        *self.state.lock().unwrap() = PidState::default();
+        self.pwm.start()?;
+        let (sender, receiver) = mpsc::channel();
+        let (pwm, source) = (Arc::clone(&self.pwm), Arc::clone(&self.source));
+        let (settings, state) = (Arc::clone(&self.settings), Arc::clone(&self.state));
+        let period = self.period;
+        self.stop = Some(sender);
+        self.thread = Some(thread::spawn(move || control(pwm, source, settings, state, period, receiver)));
+        Ok(())
+    }
+
+    /// Stops the loop and the PWM signal, the output pin is driven to its inactive level.
+    ///
+    /// Example usage:
+    /// ```python
+    /// controller.stop()
+    /// ```
+    fn stop(&mut self) -> PyResult<()> {
+        self.stop_thread();
+        self.state.lock().unwrap().output = 0f64;
+        self.pwm.stop()
+    }
+
+    /// Stops the controller and releases its pins.
+    ///
+    /// Example usage:
+    /// ```python
+    /// controller.close()
+    /// ```
+    fn close(&mut self) -> PyResult<()> {
+        self.stop_thread();
+        self.pwm.reset()?;
+        if let Source::Tachometer(tachometer) = self.source.as_ref() {
+            GPIOManager::new_rust_reference().reset_pin(tachometer.pin_num)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the value the measurement is held at, used from the next step.
+    ///
+    /// Parameters:
+    /// - `setpoint` (float): The setpoint.
+    ///
+    /// Example usage:
+    /// ```python
+    /// controller.set_setpoint(2000)
+    /// ```
+    #[pyo3(signature = (setpoint))]
+    fn set_setpoint(&self, setpoint: f64) {
+        self.settings.lock().unwrap().setpoint = setpoint;
+    }
+
+    /// Returns the setpoint.
+    fn get_setpoint(&self) -> f64 {
+        self.settings.lock().unwrap().setpoint
+    }
+
+    /// Sets the gains while the loop runs, the gains not given are kept.
+    ///
+    /// Parameters:
+    /// - `kp` (float): The proportional gain (default is None).
+    /// - `ki` (float): The integral gain (default is None).
+    /// - `kd` (float): The derivative gain (default is None).
+    ///
+    /// Example usage:
+    /// ```python
+    /// controller.set_gains(ki=0.1)
+    /// ```
+    #[pyo3(signature = (kp = None, ki = None, kd = None))]
+    fn set_gains(&self, kp: Option<f64>, ki: Option<f64>, kd: Option<f64>) {
+        let mut settings = self.settings.lock().unwrap();
+        settings.kp = kp.unwrap_or(settings.kp);
+        settings.ki = ki.unwrap_or(settings.ki);
+        settings.kd = kd.unwrap_or(settings.kd);
+    }
+
+    /// Returns the gains as `(kp, ki, kd)`.
+    fn get_gains(&self) -> (f64, f64, f64) {
+        let settings = self.settings.lock().unwrap();
+        (settings.kp, settings.ki, settings.kd)
+    }
+
+    /// Sets the range of duty cycles of the output.
+    ///
+    /// Parameters:
+    /// - `output_min` (float): The lowest duty cycle (0 to 100).
+    /// - `output_max` (float): The highest duty cycle (0 to 100).
+    ///
+    /// Example usage:
+    /// ```python
+    /// controller.set_output_limits(20, 100)
+    /// ```
+    #[pyo3(signature = (output_min, output_max))]
+    fn set_output_limits(&self, output_min: f64, output_max: f64) -> PyResult<()> {
+        check_limits(output_min, output_max)?;
+        let mut settings = self.settings.lock().unwrap();
+        settings.output_min = output_min;
+        settings.output_max = output_max;
+        Ok(())
+    }
+
+    /// Clears the integral and the previous measurement, for example after the load changed.
+    ///
+    /// Example usage:
+    /// ```python
+    /// controller.reset()
+    /// ```
+    fn reset(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.integral = 0f64;
+        state.measurement = None;
+    }
+
+    /// Returns the last measurement, or None before the first step.
+    fn get_measurement(&self) -> Option<f64> {
+        self.state.lock().unwrap().measurement
+    }
+
+    /// Returns the duty cycle of the output from 0 to 100.
+    fn get_output(&self) -> f64 {
+        self.state.lock().unwrap().output
+    }
+
+    /// Returns whether the loop is running.
+    fn is_active(&self) -> bool {
+        self.thread.is_some()
+    }
+}
@@ -0,0 +1,91 @@
+use crate::gpio_module::GPIOManager;
+use crate::{EnumArg, PinState};
+use pyo3::prelude::*;
+
+
+/// A pin given to the mapping protocol of GPIOManager, by number or by the alias load_config gave it.
+#[derive(FromPyObject)]
+pub enum PinKey {
+    Number(u8),
+    Alias(String),
+}
+
+
+#[pyclass]
+/// GPIOPin is a handle on a configured pin, returned by indexing the GPIOManager with a pin number or alias. It reads
+/// and writes the pin through the manager, so it follows the pin when it is set up again.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// manager = gpio_manager.GPIOManager()
+/// manager.add_output_pin(18)
+/// led = manager[18]
+/// led.write(gpio_manager.PinState.HIGH)
+/// print(led.mode, led.read())
+/// ```
+pub struct GPIOPin {
+    pin_num: u8,
+}
+
+
+impl GPIOPin {
+    pub fn new(pin_num: u8) -> Self {
+        Self { pin_num }
+    }
+}
+
+
+#[pymethods]
+impl GPIOPin {
+    /// Reads the state of the pin, the level of an input or the state an output is driven to.
+    ///
+    /// Returns:
+    /// - `PinState`: HIGH when the pin is active for its logic level.
+    ///
+    /// Example usage:
+    /// ```python
+    /// state = manager[17].read()
+    /// ```
+    fn read(&self) -> PyResult<PinState> {
+        let gpio_manager = GPIOManager::new_rust_reference();
+        if gpio_manager.pin_mode_name(self.pin_num) == Some("input") {
+            gpio_manager.get_pin(self.pin_num)
+        } else {
+            gpio_manager.get_output_state(self.pin_num)
+        }
+    }
+
+    /// Drives an output pin.
+    ///
+    /// Parameters:
+    /// - `state` (PinState): The state to drive the pin to.
+    ///
+    /// Example usage:
+    /// ```python
+    /// manager[18].write(gpio_manager.PinState.HIGH)
+    /// ```
+    #[pyo3(signature = (state))]
+    fn write(&self, state: EnumArg<PinState>) -> PyResult<()> {
+        GPIOManager::new_rust_reference().set_output_pin(self.pin_num, state)
+    }
+
+    /// The GPIO pin.
+    #[getter]
+    fn pin(&self) -> u8 {
+        self.pin_num
+    }
+
+    /// What the pin is set up as, "input", "output" or "pwm", or None once it was reset.
+    #[getter]
+    fn mode(&self) -> Option<&'static str> {
+        GPIOManager::new_rust_reference().pin_mode_name(self.pin_num)
+    }
+
+    fn __repr__(&self) -> String {
+        match self.mode() {
+            Some(mode) => format!("GPIOPin(pin={}, mode='{}')", self.pin_num, mode),
+            None => format!("GPIOPin(pin={}, mode=None)", self.pin_num),
+        }
+    }
+}
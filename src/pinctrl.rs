@@ -1,9 +1,16 @@
+use crate::board_module::{alt_functions, device_info};
+use crate::pads::RegisterBlock;
+use once_cell::sync::Lazy;
 use libc::{ftruncate, memfd_create, off_t, MFD_ALLOW_SEALING, MFD_CLOEXEC};
+use rppal::gpio::{Bias, Gpio, Mode};
+use rppal::system::SoC;
 use std::ffi::CString;
 use std::fs::File;
 use std::io::Write;
+use std::fmt;
 use std::os::unix::io::{FromRawFd, IntoRawFd};
-use std::process::Command;
+use std::process::{Command, Output};
+use std::sync::{Mutex, MutexGuard};
 
 
 /// Creates an in-memory file with the contents of the `pinctrl` binary.
@@ -34,29 +41,303 @@ fn load_pinctrl_in_memory() -> std::io::Result<File> {
     Ok(mem_file)
 }
 
-/// Executes the `pin ctrl` binary loaded in memory with the given arguments.
-pub fn execute_pinctrl(args: &[&str]) -> std::io::Result<()> {
+/// Runs the `pin ctrl` binary loaded in memory with the given arguments, capturing its output.
+fn run_pinctrl(args: &[&str]) -> std::io::Result<Output> {
     // Load pin ctrl binary into memory
     let mem_file = load_pinctrl_in_memory()?;
 
     // Use `into_raw_fd` to take ownership of the file descriptor
     let fd = mem_file.into_raw_fd();
 
-    // Execute the in-memory binary
-    let status = Command::new(format!("/proc/self/fd/{}", fd))
+    // Execute the in-memory binary. The GIL isn't released here, pins are routed while their locks are held, so the
+    // callers that hold no lock release it around the whole operation
+    let output = Command::new(format!("/proc/self/fd/{}", fd))
         .args(args)
-        .status();
+        .output();
 
     // Close the file descriptor manually since we used `into_raw_fd`
     unsafe { libc::close(fd) };
 
-    // Check the status of the command execution
-    match status {
-        Ok(exit_status) if exit_status.success() => Ok(()),
-        Ok(exit_status) => Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("pinctrl exited with status: {:?}", exit_status),
-        )),
+    check_output("pinctrl", output)
+}
+
+/// Turns a failed exit status into an error carrying what the tool printed to stderr.
+fn check_output(tool: &str, output: std::io::Result<Output>) -> std::io::Result<Output> {
+    match output {
+        Ok(output) if output.status.success() => Ok(output),
+        Ok(output) => Err(std::io::Error::other(format!("{} exited with {}: {}", tool, output.status,
+                                                        String::from_utf8_lossy(&output.stderr).trim()))),
         Err(e) => Err(e),
     }
 }
+
+/// Executes the `pin ctrl` binary loaded in memory with the given arguments.
+pub fn execute_pinctrl(args: &[&str]) -> std::io::Result<()> {
+    run_pinctrl(args).map(|_| ())
+}
+
+/// Executes the `pin ctrl` binary loaded in memory with the given arguments and returns what it printed.
+pub fn query_pinctrl(args: &[&str]) -> std::io::Result<String> {
+    let output = run_pinctrl(args)?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+const ALT_MODES: [Mode; 9] = [Mode::Alt0, Mode::Alt1, Mode::Alt2, Mode::Alt3, Mode::Alt4, Mode::Alt5, Mode::Alt6, Mode::Alt7, Mode::Alt8];
+
+
+fn rppal_error(e: rppal::gpio::Error) -> std::io::Error {
+    match e {
+        rppal::gpio::Error::Io(e) => e,
+        e => std::io::Error::other(e.to_string()),
+    }
+}
+
+
+/// Routes a pin to an alternate function and sets its pull resistor directly through the GPIO registers, which is
+/// what pinctrl does without the cost of starting a process.
+fn set_alt_function_native(pin: u8, alt: u8, bias: Option<Bias>) -> std::io::Result<()> {
+    let mut io_pin = Gpio::new().map_err(rppal_error)?.get(pin).map_err(rppal_error)?.into_io(ALT_MODES[alt as usize]);
+    if let Some(bias) = bias {
+        io_pin.set_bias(bias);
+    }
+    // Keep the function once the handle is dropped
+    io_pin.set_reset_on_drop(false);
+    Ok(())
+}
+
+/// The failure of every way of routing a pin to a function, with the errno of the first attempt that reported one.
+#[derive(Debug)]
+pub struct PinctrlError {
+    pub errno: Option<i32>,
+    pub message: String,
+}
+
+
+impl fmt::Display for PinctrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+
+#[cfg(feature = "python")]
+impl From<PinctrlError> for pyo3::PyErr {
+    fn from(e: PinctrlError) -> Self {
+        match e.errno {
+            Some(errno) => pyo3::PyErr::new::<pyo3::exceptions::PyOSError, _>((errno, e.message)),
+            None => pyo3::PyErr::new::<pyo3::exceptions::PyOSError, _>(e.message),
+        }
+    }
+}
+
+
+/// Routes a pin to an alternate function (0 to 8) and sets its pull resistor, or leaves the pull resistor as it is for
+/// None. The GPIO registers are written directly, the embedded pinctrl binary and then the pinctrl or raspi-gpio tools
+/// installed on the system are only used when they can't be accessed.
+pub fn set_alt_function(pin: u8, alt: u8, bias: Option<Bias>) -> Result<(), PinctrlError> {
+    if alt as usize >= ALT_MODES.len() {
+        return Err(PinctrlError { errno: Some(libc::EINVAL), message: format!("Alternate function a{} does not exist", alt) });
+    }
+    let mut failures = Vec::new();
+    match set_alt_function_native(pin, alt, bias) {
+        Ok(()) => return Ok(()),
+        Err(e) => failures.push(("direct register access", e)),
+    }
+
+    let (pin_arg, alt_arg) = (pin.to_string(), format!("a{}", alt));
+    let mut args = vec!["set", &pin_arg, &alt_arg];
+    match bias {
+        Some(Bias::PullDown) => args.push("pd"),
+        Some(Bias::PullUp) => args.push("pu"),
+        Some(Bias::Off) => args.push("pn"),
+        None => {}
+    }
+    match execute_pinctrl(&args) {
+        Ok(()) => return Ok(()),
+        Err(e) => failures.push(("embedded pinctrl", e)),
+    }
+    // raspi-gpio takes the same arguments as pinctrl, but only knows the boards before the Raspberry Pi 5
+    for tool in ["pinctrl", "raspi-gpio"] {
+        match check_output(tool, Command::new(tool).args(&args).output()) {
+            Ok(_) => return Ok(()),
+            Err(e) => failures.push((tool, e)),
+        }
+    }
+
+    Err(PinctrlError {
+        errno: failures.iter().find_map(|(_, e)| e.raw_os_error()),
+        message: format!("Unable to route GPIO {} to {}: {}", pin, alt_arg,
+                         failures.iter().map(|(method, e)| format!("{} failed ({})", method, e)).collect::<Vec<_>>().join(", ")),
+    })
+}
+
+/// Reads the function of a pin directly from the GPIO registers.
+fn pin_function_native(pin: u8) -> std::io::Result<String> {
+    let mode = Gpio::new().map_err(rppal_error)?.get(pin).map_err(rppal_error)?.mode();
+    Ok(match mode {
+        Mode::Input => "input".to_string(),
+        Mode::Output => "output".to_string(),
+        Mode::Null => "none".to_string(),
+        mode => {
+            let alt = format!("a{}", ALT_MODES.iter().position(|alt_mode| *alt_mode == mode).unwrap_or_default());
+            // Functions missing from the tables, such as the PIO functions of the RP1, are named by their pinctrl name
+            device_info().ok()
+                         .and_then(|info| alt_functions(info.soc(), pin).ok())
+                         .and_then(|functions| functions.into_iter().find(|(name, _)| *name == alt))
+                         .map_or(alt, |(_, function)| function.to_string())
+        }
+    })
+}
+
+/// Returns the name of the function a pin is routed to, such as `input`, `output`, `TXD0` or `none`. The GPIO registers
+/// are read directly, the embedded pinctrl binary is only used when they can't be accessed.
+pub fn pin_function(pin: u8) -> std::io::Result<String> {
+    if let Ok(function) = pin_function_native(pin) {
+        return Ok(function);
+    }
+    // pinctrl prints a line such as "14: a0    pn | hi // GPIO14 = TXD0"
+    let output = query_pinctrl(&["get", &pin.to_string()])?;
+    output.lines()
+          .find_map(|line| line.split_once(" = ").map(|(_, function)| function.trim().to_string()))
+          .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Unexpected pinctrl output: {}", output.trim())))
+}
+
+
+// GPIO registers used to change a pin that is held by rppal
+const PATH_DEV_GPIOMEM: &str = "/dev/gpiomem";
+const GPIOMEM_LEN: usize = 0x1000;
+const FSEL_MASK: u32 = 0b111;
+const FSEL_OUTPUT: u32 = 0b001;
+const GPPUD: usize = 0x94;
+const GPPUDCLK0: usize = 0x98;
+const GPPUD_CNTRL_REG0: usize = 0xe4;
+const GPLEV0: usize = 0x34;
+const PATH_DEV_GPIOMEM0: &str = "/dev/gpiomem0";
+const RP1_MEM_LEN: usize = 0x30000;
+const RP1_RIO_OE_SET: usize = 0x10000 + 0x2000 + 0x04;
+const RP1_RIO_OE_CLR: usize = 0x10000 + 0x3000 + 0x04;
+const RP1_RIO_IN: usize = 0x10000 + 0x08;
+const RP1_PADS_GPIO: usize = 0x20000 + 0x04;
+const RP1_PADS_BIAS_MASK: u32 = 0x0c;
+const RP1_PADS_BIAS_LSB: u32 = 2;
+
+
+struct GpioRegisters {
+    registers: RegisterBlock,
+    soc: SoC,
+}
+
+
+static GPIO_REGISTERS: Lazy<Result<Mutex<GpioRegisters>, String>> = Lazy::new(|| {
+    let soc = device_info().map_err(|e| e.to_string())?.soc();
+    let (path, len) = if soc == SoC::Bcm2712 { (PATH_DEV_GPIOMEM0, RP1_MEM_LEN) } else { (PATH_DEV_GPIOMEM, GPIOMEM_LEN) };
+    let registers = RegisterBlock::map(path, 0, len).map_err(|e| format!("Unable to map {}: {}", path, e))?;
+    Ok(Mutex::new(GpioRegisters { registers, soc }))
+});
+
+
+fn gpio_registers() -> std::io::Result<MutexGuard<'static, GpioRegisters>> {
+    let registers = GPIO_REGISTERS.as_ref().map_err(|e| std::io::Error::other(e.clone()))?;
+    Ok(registers.lock().unwrap())
+}
+
+
+/// Enables or disables the output driver of a pin without releasing it, the level it drives is kept. Used to emulate
+/// open drain and open source outputs.
+pub fn set_output_enabled(pin: u8, enabled: bool) -> std::io::Result<()> {
+    let gpio = gpio_registers()?;
+    if gpio.soc == SoC::Bcm2712 {
+        // The RP1 has atomic set and clear aliases of the output enable register
+        gpio.registers.write(if enabled { RP1_RIO_OE_SET } else { RP1_RIO_OE_CLR }, 1 << pin);
+    } else {
+        let (offset, shift) = ((pin as usize / 10) * 4, (pin as u32 % 10) * 3);
+        let value = gpio.registers.read(offset) & !(FSEL_MASK << shift);
+        gpio.registers.write(offset, value | if enabled { FSEL_OUTPUT << shift } else { 0 });
+    }
+    Ok(())
+}
+
+
+/// Reads the levels of the pins repeatedly, with the register looked up once.
+pub struct LevelReader {
+    gpio: &'static Mutex<GpioRegisters>,
+    offset: usize,
+}
+
+
+impl LevelReader {
+    /// Reads the level of GPIO 0 to 31 in a single register read, bit N is the level of GPIO N. The levels are read
+    /// from the pads, so they are reported whatever the function of the pins.
+    pub fn read(&self) -> u32 {
+        self.gpio.lock().unwrap().registers.read(self.offset)
+    }
+}
+
+
+pub fn level_reader() -> std::io::Result<LevelReader> {
+    let gpio = GPIO_REGISTERS.as_ref().map_err(|e| std::io::Error::other(e.clone()))?;
+    let offset = if gpio.lock().unwrap().soc == SoC::Bcm2712 { RP1_RIO_IN } else { GPLEV0 };
+    Ok(LevelReader { gpio, offset })
+}
+
+
+/// Reads the level of GPIO 0 to 31 in a single register read, bit N is the level of GPIO N.
+pub fn read_levels() -> std::io::Result<u32> {
+    Ok(level_reader()?.read())
+}
+
+
+/// Sets the pull resistor of a pin without releasing it. The GPIO registers are written directly, the embedded pinctrl
+/// binary is only used when they can't be accessed.
+pub fn set_bias(pin: u8, bias: Bias) -> std::io::Result<()> {
+    let gpio = match gpio_registers() {
+        Ok(gpio) => gpio,
+        Err(_) => {
+            let pull = match bias {
+                Bias::PullDown => "pd",
+                Bias::PullUp => "pu",
+                Bias::Off => "pn",
+            };
+            return execute_pinctrl(&["set", &pin.to_string(), pull]);
+        }
+    };
+    match gpio.soc {
+        SoC::Bcm2712 => {
+            let offset = RP1_PADS_GPIO + pin as usize * 4;
+            let bits = match bias {
+                Bias::Off => 0,
+                Bias::PullDown => 1,
+                Bias::PullUp => 2,
+            };
+            let value = gpio.registers.read(offset) & !RP1_PADS_BIAS_MASK;
+            gpio.registers.write(offset, value | bits << RP1_PADS_BIAS_LSB);
+        }
+        SoC::Bcm2711 => {
+            // Pull up and pull down have the reverse bit pattern of the older SoCs
+            let (offset, shift) = (GPPUD_CNTRL_REG0 + (pin as usize / 16) * 4, (pin as u32 % 16) * 2);
+            let bits = match bias {
+                Bias::Off => 0b00,
+                Bias::PullDown => 0b10,
+                Bias::PullUp => 0b01,
+            };
+            let value = gpio.registers.read(offset) & !(0b11 << shift);
+            gpio.registers.write(offset, value | bits << shift);
+        }
+        _ => {
+            // The control signal is clocked into the pin, with at least 150 cycles of set-up and hold time
+            let bits = match bias {
+                Bias::Off => 0b00,
+                Bias::PullDown => 0b01,
+                Bias::PullUp => 0b10,
+            };
+            let clock = GPPUDCLK0 + (pin as usize / 32) * 4;
+            gpio.registers.write(GPPUD, bits);
+            std::thread::sleep(std::time::Duration::from_micros(5));
+            gpio.registers.write(clock, 1 << (pin % 32));
+            std::thread::sleep(std::time::Duration::from_micros(5));
+            gpio.registers.write(GPPUD, 0);
+            gpio.registers.write(clock, 0);
+        }
+    }
+    Ok(())
+}
@@ -1,30 +1,17 @@
+use crate::board_module::{device_info, Peripheral};
+use crate::capture_module::measure_pwm;
 use crate::gpio_module::GPIOManager;
-use crate::{check_pwm_values, pinctrl};
-use crate::{compute_pwm_values, LogicLevel};
+use crate::error::GpioError;
+use crate::hal::{self, route_pwm_pin, PWM_CHANNEL_PINS};
+use crate::overlay_module;
+use crate::pwm_ramp_module::{cancel_ramp, check_pattern, ramp_duration, start_pattern, start_ramp, Easing, RampTarget};
+use crate::{check_pwm_values, log_module, ms_to_duration_checked, release_gil, GammaCurve};
+use crate::{compute_pwm_values, EnumArg, InternPullResistorState, LogicLevel};
 use once_cell::sync::Lazy;
 use pyo3::{pyclass, pymethods, Py, PyErr, PyResult, Python};
-use rppal::pwm::{Channel, Polarity, Pwm};
-use rppal::system::{DeviceInfo, Model};
+use rppal::pwm::Polarity;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
-
-
-fn set_gpio_to_pwm_pi5(pin: usize) -> std::io::Result<()> {
-    // Set GPIO18 to alternate function `a3` with pull-down
-    hw_pwm_setup(pin, "a3")
-}
-
-
-fn set_gpio_to_pwm_other(pin: usize) -> std::io::Result<()> {
-    // Set GPIO18 to alternate function `a3` with pull-down
-    hw_pwm_setup(pin, "a5")
-}
-
-
-fn hw_pwm_setup(pin: usize, command: &str) -> std::io::Result<()> {
-    pinctrl::execute_pinctrl(&["set", &pin.to_string(), command, "pd"])
-}
 
 
 #[pyclass(eq, eq_int)]
@@ -65,7 +52,8 @@ static PWM_MANAGER: Lazy<Arc<Mutex<PWMManager>>> = Lazy::new(|| {
 /// pwm_manager.remove_pwm_channel(0)
 /// ```
 pub struct PWMManager {
-    pwm_channels: Arc<Mutex<HashMap<u8, Arc<Mutex<Pwm>>>>>,
+    channels: Arc<Mutex<hal::PwmManager>>,
+    gamma_curves: Arc<Mutex<HashMap<u8, GammaCurve>>>,
 }
 
 
@@ -73,14 +61,16 @@ impl PWMManager {
     /// Internal method to initialize the PWMManager singleton.
     fn new_singleton() -> PyResult<Self> {
         Ok(Self {
-            pwm_channels: Arc::new(Mutex::new(HashMap::new())),
+            channels: Arc::new(Mutex::new(hal::PwmManager::new())),
+            gamma_curves: Arc::new(Mutex::new(HashMap::new())),
         })
     }
 
     fn shared(py: Python) -> PyResult<Py<PWMManager>> {
         let manager = PWM_MANAGER.lock().unwrap();
         Py::new(py, PWMManager {
-            pwm_channels: Arc::clone(&manager.pwm_channels),
+            channels: Arc::clone(&manager.channels),
+            gamma_curves: Arc::clone(&manager.gamma_curves),
         })
     }
 
@@ -88,13 +78,36 @@ impl PWMManager {
         Arc::clone(&PWM_MANAGER)
     }
 
+    /// Returns the hardware PWM channel driving the given pin, if there is one.
+    pub fn hardware_channel(pin_num: u8) -> Option<u8> {
+        PWM_CHANNEL_PINS.iter().find(|(_, pin)| *pin == pin_num).map(|(channel, _)| *channel)
+    }
+
+    /// Returns the GPIO pin the given hardware PWM channel is routed to.
+    pub fn channel_pin(channel_num: u8) -> PyResult<u8> {
+        Ok(hal::PwmManager::channel_pin(channel_num)?)
+    }
+
+    fn write_duty_cycle(&self, channel_num: u8, duty_cycle: f64) -> PyResult<()> {
+        let duty_cycle = match self.gamma_curves.lock().unwrap().get(&channel_num) {
+            Some(gamma) => gamma.apply(duty_cycle),
+            None => duty_cycle,
+        };
+        Ok(self.channels.lock().unwrap().set_duty_cycle(channel_num, duty_cycle)?)
+    }
+
+    /// Returns whether any channel is set up.
+    pub(crate) fn has_channels(&self) -> bool {
+        !self.channels.lock().unwrap().channel_nums().is_empty()
+    }
+
     pub fn is_pin_pwm(&self, pin_num: u8) -> bool {
-        let pwm_channels = self.pwm_channels.lock().unwrap();
-        match pin_num {
-            18 => { if let Some(_) = pwm_channels.get(&0) { true } else { false } }
-            19 => { if let Some(_) = pwm_channels.get(&1) { true } else { false } }
-            _ => false,
-        }
+        PWMManager::hardware_channel(pin_num).is_some_and(|channel_num| self.channels.lock().unwrap().is_setup(channel_num))
+    }
+
+    /// Changes the polarity of a set up channel to match a logic level, the duty cycle keeps its meaning.
+    pub(crate) fn set_channel_polarity(&self, channel_num: u8, logic_level: LogicLevel) -> PyResult<()> {
+        Ok(self.channels.lock().unwrap().set_logic_level(channel_num, logic_level)?)
     }
 }
 
@@ -119,24 +132,24 @@ impl PWMManager {
     /// - `frequency_hz` (float): The frequency in Hertz.
     /// - `duty_cycle` (int): The duty cycle (0 to 100).
     /// - `polarity` (PWMPolarity): The polarity of the PWM signal.
+    /// - `strict` (bool): Whether a failure to route the pin to the PWM peripheral raises an OSError, when False a
+    ///   warning is logged and the channel is set up anyway (default is True).
     ///
     /// Example usage:
     /// ```python
     /// pwm_manager.setup_pwm_channel(0, frequency_hz=100, duty_cycle=0.5, polarity=pwm_manager.PWMPolarity.NORMAL)
     /// ```
-    #[pyo3(signature = (channel_num, frequency_hz = None, duty_cycle = None, period_ms = None, pulse_width_ms = None, logic_level = LogicLevel::HIGH)
-    )]
-    fn setup_pwm_channel(&self, channel_num: u8, frequency_hz: Option<f64>, duty_cycle: Option<f64>, period_ms: Option<f64>, pulse_width_ms:
-    Option<f64>, logic_level: LogicLevel) -> PyResult<()> {
+    #[pyo3(signature = (channel_num, frequency_hz = None, duty_cycle = None, period_ms = None, pulse_width_ms = None, logic_level = EnumArg(LogicLevel::HIGH),
+    strict = true))]
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn setup_pwm_channel(&self, channel_num: u8, frequency_hz: Option<f64>, duty_cycle: Option<f64>, period_ms: Option<f64>, pulse_width_ms:
+    Option<f64>, logic_level: EnumArg<LogicLevel>, strict: bool) -> PyResult<()> {
+        let logic_level = logic_level.0;
         let gpio_manager = GPIOManager::new_rust_reference();
         let manager = gpio_manager.get_manager();
         let manager = manager.lock().unwrap();
 
-        let pin_num = match channel_num {
-            0 => 18,
-            1 => 19,
-            _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid PWM channel number")),
-        };
+        let pin_num = PWMManager::channel_pin(channel_num)?;
         if gpio_manager.is_input_pin(pin_num, &manager) {
             return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin is already in use as an input pin"));
         } else if gpio_manager.is_output_pin(pin_num, &manager) {
@@ -146,82 +159,80 @@ impl PWMManager {
         drop(gpio_manager);
 
         check_pwm_values(&frequency_hz, &duty_cycle, &period_ms, &pulse_width_ms)?;
-        let mut pwm_channels = self.pwm_channels.lock().unwrap();
-
-        if pwm_channels.contains_key(&channel_num) {
+        if self.channels.lock().unwrap().is_setup(channel_num) {
             return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("PWM channel already initialized"));
         }
 
-        let channel = match channel_num {
-            0 => Channel::Pwm0,
-            1 => Channel::Pwm1,
-            _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid PWM channel number")),
-        };
-
         let (frequency, duty_cycle_percent) = compute_pwm_values(&frequency_hz, &duty_cycle, &period_ms, &pulse_width_ms);
 
         if pulse_width_ms.is_some() && pulse_width_ms.unwrap() / 1000f64 > 1f64 / frequency {
             return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pulse width must be less than period (pwm not setup"));
         }
 
-        let polarity = match logic_level {
-            LogicLevel::HIGH => Polarity::Normal,
-            LogicLevel::LOW => Polarity::Inverse,
-        };
-
-        match DeviceInfo::new().unwrap().model() {
-            Model::RaspberryPi5 => match channel_num {
-                0 => match set_gpio_to_pwm_pi5(18) {
-                    Ok(_) => {}
-                    Err(_) => { println!("an error occurred, pin state is unknown, make sure you user is in the gpio group") }
-                },
-                1 => match set_gpio_to_pwm_pi5(19) {
-                    Ok(_) => {}
-                    Err(_) => { println!("an error occurred, pin state is unknown, make sure you user is in the gpio group") }
-                },
-                _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid PWM channel number")),
-            },
-
-            _ => match channel_num {
-                0 => match set_gpio_to_pwm_other(18) {
-                    Ok(_) => {}
-                    Err(_) => {}
-                },
-                1 => match set_gpio_to_pwm_other(18) {
-                    Ok(_) => {}
-                    Err(_) => {}
-                },
-                _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid PWM channel number")),
-            },
+        // The pin is routed with the GIL released before the channels are locked, a thread holding the lock must never
+        // wait for the GIL
+        if let Err(e) = release_gil(|| route_pwm_pin(pin_num)) {
+            if strict {
+                return Err(e.into());
+            }
+            log_module::warning(&format!("{}, the PWM channel is set up but the pin may not output it", e));
         }
 
-        let pwm = Pwm::with_frequency(channel, frequency, duty_cycle_percent / 100f64, polarity, false)
-            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?;
-
-        pwm_channels.insert(channel_num, Arc::new(Mutex::new(pwm)));
-
-        Ok(())
+        self.channels.lock().unwrap().open_channel(channel_num, frequency, duty_cycle_percent, logic_level)
+            .map_err(|e| match (e, overlay_module::disabled_reason(Peripheral::Pwm)) {
+                (GpioError::Hardware(message), Some(reason)) => PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{}, {}", message, reason)),
+                (e, _) => e.into(),
+            })
     }
 
-    /// Starts the PWM signal on the specified channel.
+    /// Starts the PWM signal on the specified channel, either at its duty cycle or ramped up to it from 0 for a soft
+    /// start.
     ///
     /// Parameters:
     /// - `channel_num` (int): The PWM channel number (0 or 1).
+    /// - `ramp_ms` (float): The time to ramp from 0 to the configured duty cycle in milliseconds, the ramp runs in the
+    ///   background (default is None, which starts at the configured duty cycle).
     ///
     /// Example usage:
     /// ```python
     /// pwm_manager.start_pwm_channel(0)
+    /// pwm_manager.start_pwm_channel(1, ramp_ms=500)
     /// ```
-    #[pyo3(signature = (channel_num))]
-    fn start_pwm_channel(&self, channel_num: u8) -> PyResult<()> {
-        let pwm_channels = self.pwm_channels.lock().unwrap();
-        if let Some(pwm_arc) = pwm_channels.get(&channel_num) {
-            let pwm = pwm_arc.lock().unwrap();
-            pwm.enable().map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?;
-            Ok(())
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("PWM channel not initialized"))
+    #[pyo3(signature = (channel_num, ramp_ms = None))]
+    pub(crate) fn start_pwm_channel(&self, channel_num: u8, ramp_ms: Option<f64>) -> PyResult<()> {
+        let ramp = match ramp_duration(ramp_ms)? {
+            Some(duration) => {
+                let duty_cycle = self.get_duty_cycle(channel_num)?;
+                self.write_duty_cycle(channel_num, 0f64)?;
+                Some((duty_cycle, duration))
+            }
+            None => None,
+        };
+        self.channels.lock().unwrap().start_pwm_channel(channel_num)?;
+        if let Some((duty_cycle, duration)) = ramp {
+            let pwm_manager = PWMManager {
+                channels: Arc::clone(&self.channels),
+                gamma_curves: Arc::clone(&self.gamma_curves),
+            };
+            start_ramp(RampTarget::HardwareChannel(channel_num), 0f64, duty_cycle, duration, Easing::LINEAR,
+                       move |value| pwm_manager.write_duty_cycle(channel_num, value));
         }
+        Ok(())
+    }
+
+    /// Starts the PWM signals of several channels back to back under a single lock, so they start as close to
+    /// simultaneously as possible. None of the channels is started if one isn't initialized.
+    ///
+    /// Parameters:
+    /// - `channel_nums` (list[int]): The PWM channel numbers.
+    ///
+    /// Example usage:
+    /// ```python
+    /// pwm_manager.start_channels([0, 1])
+    /// ```
+    #[pyo3(signature = (channel_nums))]
+    pub(crate) fn start_channels(&self, channel_nums: Vec<u8>) -> PyResult<()> {
+        Ok(self.channels.lock().unwrap().start_pwm_channels(&channel_nums)?)
     }
 
     /// Stops the PWM signal on the specified channel.
@@ -234,15 +245,8 @@ impl PWMManager {
     /// pwm_manager.stop_pwm_channel(0)
     /// ```
     #[pyo3(signature = (channel_num))]
-    fn stop_pwm_channel(&self, channel_num: u8) -> PyResult<()> {
-        let pwm_channels = self.pwm_channels.lock().unwrap();
-        if let Some(pwm_arc) = pwm_channels.get(&channel_num) {
-            let pwm = pwm_arc.lock().unwrap();
-            pwm.disable().map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?;
-            Ok(())
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("PWM channel not initialized"))
-        }
+    pub(crate) fn stop_pwm_channel(&self, channel_num: u8) -> PyResult<()> {
+        Ok(self.channels.lock().unwrap().stop_pwm_channel(channel_num)?)
     }
 
     /// Removes the PWM channel from the manager.
@@ -255,44 +259,106 @@ impl PWMManager {
     /// pwm_manager.remove_pwm_channel(0)
     /// ```
     #[pyo3(signature = (channel_num))]
-    fn reset_pwm_channel(&self, channel_num: u8) -> PyResult<()> {
-        self.stop_pwm_channel(channel_num)?;
-
-        let mut pwm_channels = self.pwm_channels.lock().unwrap();
-        if pwm_channels.remove(&channel_num).is_some() {
-            Ok(())
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("PWM channel not initialized"))
-        }
+    pub(crate) fn reset_pwm_channel(&self, channel_num: u8) -> PyResult<()> {
+        cancel_ramp(RampTarget::HardwareChannel(channel_num));
+        self.channels.lock().unwrap().reset_pwm_channel(channel_num)?;
+        self.gamma_curves.lock().unwrap().remove(&channel_num);
+        Ok(())
     }
 
-    /// Sets the duty cycle for the specified PWM channel.
+    /// Sets the duty cycle for the specified PWM channel, either immediately or ramped over a duration.
     ///
     /// Parameters:
     /// - `channel_num` (int): The PWM channel number (0 or 1).
     /// - `duty_cycle` (float): The new duty cycle (0 to 100).
+    /// - `ramp_ms` (float): The time to ramp from the current duty cycle to the new one in milliseconds, the ramp runs
+    ///   in the background (default is None, which sets the duty cycle immediately).
+    /// - `easing` (Easing): The curve the ramp follows (default is LINEAR).
     ///
     /// Example usage:
     /// ```python
     /// pwm_manager.set_duty_cycle(0, 75)
+    /// pwm_manager.set_duty_cycle(0, 0, ramp_ms=500, easing=gpio_manager.Easing.EASE_OUT)
     /// ```
-    #[pyo3(signature = (channel_num, duty_cycle))]
-    fn set_duty_cycle(&self, channel_num: u8, duty_cycle: f64) -> PyResult<()> {
+    #[pyo3(signature = (channel_num, duty_cycle, ramp_ms = None, easing = Easing::LINEAR))]
+    pub(crate) fn set_duty_cycle(&self, channel_num: u8, duty_cycle: f64, ramp_ms: Option<f64>, easing: Easing) -> PyResult<()> {
         if duty_cycle > 100f64 || duty_cycle < 0f64 {
             return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Duty cycle must be between 0 and 100, current value {} does not meet this condition", duty_cycle)));
         }
 
-        let pwm_channels = self.pwm_channels.lock().unwrap();
-        if let Some(pwm_arc) = pwm_channels.get(&channel_num) {
-            let pwm = pwm_arc.lock().unwrap();
-
-            pwm.set_duty_cycle(duty_cycle / 100f64).map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?;
-            Ok(())
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("PWM channel not initialized"))
+        let target = RampTarget::HardwareChannel(channel_num);
+        match ramp_duration(ramp_ms)? {
+            Some(duration) => {
+                let current = self.get_duty_cycle(channel_num)?;
+                let pwm_manager = PWMManager {
+                    channels: Arc::clone(&self.channels),
+                    gamma_curves: Arc::clone(&self.gamma_curves),
+                };
+                start_ramp(target, current, duty_cycle, duration, easing,
+                           move |value| pwm_manager.write_duty_cycle(channel_num, value));
+                Ok(())
+            }
+            None => {
+                cancel_ramp(target);
+                self.write_duty_cycle(channel_num, duty_cycle)
+            }
         }
     }
 
+    /// Plays a duty cycle pattern on the specified PWM channel from a background thread, for example to make an LED
+    /// breathe. Setting the duty cycle, playing another pattern or resetting the channel stops the pattern.
+    ///
+    /// Parameters:
+    /// - `channel_num` (int): The PWM channel number (0 or 1).
+    /// - `pattern` (list[tuple[float, float]]): The steps as `(duty_cycle, hold_ms)`, the duty cycle (0 to 100) is held
+    ///   for the time in milliseconds before the next step.
+    /// - `loop` (bool): Whether the pattern starts over after the last step (default is False).
+    ///
+    /// Example usage:
+    /// ```python
+    /// pwm_manager.play_pattern(0, [(20, 500), (80, 500)], loop=True)
+    /// ```
+    #[pyo3(signature = (channel_num, pattern, r#loop = false))]
+    pub(crate) fn play_pattern(&self, channel_num: u8, pattern: Vec<(f64, f64)>, r#loop: bool) -> PyResult<()> {
+        let pattern = check_pattern(&pattern)?;
+        self.get_duty_cycle(channel_num)?;
+        let pwm_manager = PWMManager {
+            channels: Arc::clone(&self.channels),
+            gamma_curves: Arc::clone(&self.gamma_curves),
+        };
+        start_pattern(RampTarget::HardwareChannel(channel_num), pattern, r#loop, move |value| pwm_manager.write_duty_cycle(channel_num, value));
+        Ok(())
+    }
+
+    /// Sets a gamma curve for the specified PWM channel, so duty cycles are interpreted as perceived brightness. The
+    /// curve is applied before programming the channel, `get_duty_cycle` keeps returning the brightness.
+    ///
+    /// Parameters:
+    /// - `channel_num` (int): The PWM channel number (0 or 1).
+    /// - `gamma` (float): The exponent of the curve, 2.2 suits most LEDs (default is None).
+    /// - `lut` (list[float]): Duty cycles (0 to 100) for brightness values evenly spaced from 0 to 100, used in place of
+    ///   the exponent (default is None).
+    ///
+    /// Passing neither disables the curve.
+    ///
+    /// Example usage:
+    /// ```python
+    /// pwm_manager.set_gamma(0, gamma=2.2)
+    /// pwm_manager.set_gamma(1, lut=[0, 1, 4, 10, 22, 40, 65, 100])
+    /// ```
+    #[pyo3(signature = (channel_num, gamma = None, lut = None))]
+    pub(crate) fn set_gamma(&self, channel_num: u8, gamma: Option<f64>, lut: Option<Vec<f64>>) -> PyResult<()> {
+        let curve = GammaCurve::from_args(gamma, lut)?;
+        let brightness = self.get_duty_cycle(channel_num)?;
+        let mut gamma_curves = self.gamma_curves.lock().unwrap();
+        match curve {
+            Some(curve) => gamma_curves.insert(channel_num, curve),
+            None => gamma_curves.remove(&channel_num),
+        };
+        drop(gamma_curves);
+        self.write_duty_cycle(channel_num, brightness)
+    }
+
     /// Sets the frequency for the specified PWM channel.
     ///
     /// Parameters:
@@ -304,53 +370,25 @@ impl PWMManager {
     /// pwm_manager.set_frequency(0, 500)
     /// ```
     #[pyo3(signature = (channel_num, frequency_hz))]
-    fn set_frequency(&self, channel_num: u8, frequency_hz: f64) -> PyResult<()> {
+    pub(crate) fn set_frequency(&self, channel_num: u8, frequency_hz: f64) -> PyResult<()> {
         if frequency_hz <= 0f64 {
             return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Frequency must be greater than 0"));
         }
-        let pwm_channels = self.pwm_channels.lock().unwrap();
-        if let Some(pwm_arc) = pwm_channels.get(&channel_num) {
-            let pwm = pwm_arc.lock().unwrap();
-            let current_duty_cycle = pwm.duty_cycle().map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?;
-            pwm.set_frequency(frequency_hz, current_duty_cycle).map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?;
-            Ok(())
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("PWM channel not initialized"))
-        }
+        Ok(self.channels.lock().unwrap().set_frequency(channel_num, frequency_hz)?)
     }
 
 
     #[pyo3(signature = (channel_num, period_ms))]
-    fn set_period(&self, channel_num: u8, period_ms: f64) -> PyResult<()> {
-        let pwm_channels = self.pwm_channels.lock().unwrap();
-        if period_ms < 0f64 {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Period must be greater than 0"));
-        }
-        if let Some(pwm_arc) = pwm_channels.get(&channel_num) {
-            let pwm = pwm_arc.lock().unwrap();
-            pwm.set_period(Duration::from_secs_f64(period_ms / 1000f64)).map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?;
-            Ok(())
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("PWM channel not initialized"))
-        }
+    pub(crate) fn set_period(&self, channel_num: u8, period_ms: f64) -> PyResult<()> {
+        let period = ms_to_duration_checked("Period", period_ms)?;
+        Ok(self.channels.lock().unwrap().set_period(channel_num, period)?)
     }
 
     #[pyo3(signature = (channel_num, pulse_width_ms))]
-    fn set_pulse_width(&self, channel_num: u8, pulse_width_ms: f64) -> PyResult<()> {
-        let pwm_channels = self.pwm_channels.lock().unwrap();
-        if let Some(pwm_arc) = pwm_channels.get(&channel_num) {
-            let pwm = pwm_arc.lock().unwrap();
-            let current_period = pwm.period().map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?;
-            let pulse_width = Duration::from_secs_f64(pulse_width_ms / 1000f64);
-            if pulse_width > current_period {
-                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Pulse width must be less than the period, The value {} does not meet this
-                 condition period: {:?}", pulse_width_ms, current_period)));
-            }
-            pwm.set_pulse_width(pulse_width).map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{:?}", e)))?;
-            Ok(())
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("PWM channel not initialized"))
-        }
+    pub(crate) fn set_pulse_width(&self, channel_num: u8, pulse_width_ms: f64) -> PyResult<()> {
+        let pulse_width = ms_to_duration_checked("Pulse width", pulse_width_ms)?;
+        cancel_ramp(RampTarget::HardwareChannel(channel_num));
+        Ok(self.channels.lock().unwrap().set_pulse_width(channel_num, pulse_width)?)
     }
 
 
@@ -367,52 +405,19 @@ impl PWMManager {
     /// frequency = pwm_manager.get_frequency(0)
     /// ```
     #[pyo3(signature = (channel_num))]
-    fn get_frequency(&self, channel_num: u8) -> PyResult<f64> {
-        let pwm_channels = self.pwm_channels.lock().unwrap();
-
-        if let Some(pwm_arc) = pwm_channels.get(&channel_num) {
-            let pwm = pwm_arc.lock().unwrap();
-            if let Ok(frequency) = pwm.frequency() {
-                Ok(frequency)
-            } else {
-                Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Failed to get PWM frequency"))
-            }
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("PWM channel not initialized"))
-        }
+    pub(crate) fn get_frequency(&self, channel_num: u8) -> PyResult<f64> {
+        Ok(self.channels.lock().unwrap().get_frequency(channel_num)?)
     }
 
     #[pyo3(signature = (channel_num))]
-    fn get_period(&self, channel_num: u8) -> PyResult<f64> {
-        let pwm_channels = self.pwm_channels.lock().unwrap();
-
-        if let Some(pwm_arc) = pwm_channels.get(&channel_num) {
-            let pwm = pwm_arc.lock().unwrap();
-            if let Ok(period) = pwm.period() {
-                Ok(period.as_secs_f64() * 1000f64)
-            } else {
-                Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Failed to get PWM frequency"))
-            }
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("PWM channel not initialized"))
-        }
+    pub(crate) fn get_period(&self, channel_num: u8) -> PyResult<f64> {
+        Ok(self.channels.lock().unwrap().get_period(channel_num)?.as_secs_f64() * 1000f64)
     }
 
 
     #[pyo3(signature = (channel_num))]
-    fn get_pulse_width(&self, channel_num: u8) -> PyResult<f64> {
-        let pwm_channels = self.pwm_channels.lock().unwrap();
-
-        if let Some(pwm_arc) = pwm_channels.get(&channel_num) {
-            let pwm = pwm_arc.lock().unwrap();
-            if let Ok(pulse_width) = pwm.pulse_width() {
-                Ok(pulse_width.as_secs_f64())
-            } else {
-                Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Failed to get PWM frequency"))
-            }
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("PWM channel not initialized"))
-        }
+    pub(crate) fn get_pulse_width(&self, channel_num: u8) -> PyResult<f64> {
+        Ok(self.channels.lock().unwrap().get_pulse_width(channel_num)?.as_secs_f64())
     }
 
     /// Gets the current duty cycle of the specified PWM channel.
@@ -428,35 +433,106 @@ impl PWMManager {
     /// duty_cycle = pwm_manager.get_duty_cycle(0)
     /// ```
     #[pyo3(signature = (channel_num))]
-    fn get_duty_cycle(&self, channel_num: u8) -> PyResult<f64> {
-        let pwm_channels = self.pwm_channels.lock().unwrap();
-
-        if let Some(pwm_arc) = pwm_channels.get(&channel_num) {
-            let pwm = pwm_arc.lock().unwrap();
-            if let Ok(duty_cycle) = pwm.duty_cycle() {
-                Ok(duty_cycle * 100f64)
-            } else {
-                Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Failed to get PWM duty cycle"))
+    pub(crate) fn get_duty_cycle(&self, channel_num: u8) -> PyResult<f64> {
+        let duty_cycle = self.channels.lock().unwrap().get_duty_cycle(channel_num)?;
+        match self.gamma_curves.lock().unwrap().get(&channel_num) {
+            Some(gamma) => Ok(gamma.invert(duty_cycle)),
+            None => Ok(duty_cycle),
+        }
+    }
+
+    /// Measures the frequency and the duty cycle of an external PWM signal fed into the pin of a channel, to check the
+    /// signal of a PWM device such as an ESC without a scope. The SoC can't capture with its PWM peripheral, so the pin
+    /// is sampled as fast as possible from Rust for the duration, which resolves signals up to a few kHz. The channel
+    /// must not be set up, a free pin is set up as an input without pull resistor for the capture and released after.
+    /// The GIL is released while measuring.
+    ///
+    /// Parameters:
+    /// - `channel_num` (int): The PWM channel whose pin the signal is fed into.
+    /// - `duration_ms` (float): The time to measure for in milliseconds, it should span several periods (default is
+    ///   100).
+    ///
+    /// Returns:
+    /// - `tuple[float, float]`: The frequency in Hertz and the duty cycle (0 to 100), averaged over the periods seen. A
+    ///   signal without a full period reads as 0 Hz with a duty cycle of 0 or 100 for its level.
+    ///
+    /// Example usage:
+    /// ```python
+    /// frequency_hz, duty_cycle = pwm_manager.capture(0, duration_ms=200)
+    /// ```
+    #[pyo3(signature = (channel_num, duration_ms = 100f64))]
+    fn capture(&self, channel_num: u8, duration_ms: f64) -> PyResult<(f64, f64)> {
+        if duration_ms <= 0f64 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Duration must be greater than 0, The value {} does not meet this condition", duration_ms)));
+        }
+        let duration = ms_to_duration_checked("Duration", duration_ms)?;
+        let pin_num = PWMManager::channel_pin(channel_num)?;
+        if self.channels.lock().unwrap().is_setup(channel_num) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("PWM channel {} is set up to output a signal, reset it before capturing", channel_num)));
+        }
+        let gpio_manager = GPIOManager::new_rust_reference();
+        let claimed = {
+            let manager = gpio_manager.get_manager();
+            let manager = manager.lock().unwrap();
+            if gpio_manager.is_output_pin(pin_num, &manager) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Pin is already in use as an output pin"));
             }
-        } else {
-            Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("PWM channel not initialized"))
+            !gpio_manager.is_input_pin(pin_num, &manager)
+        };
+        if claimed {
+            gpio_manager.claim_input_pin(pin_num, InternPullResistorState::EXTERNAL)?;
         }
+        let result = measure_pwm(pin_num, duration);
+        if claimed {
+            gpio_manager.reset_pin(pin_num)?;
+        }
+        result
     }
 
+    /// Returns the hardware PWM channel a GPIO pin is driven by.
+    ///
+    /// Parameters:
+    /// - `pin_num` (int): The GPIO pin.
+    ///
+    /// Returns:
+    /// - `int`: The PWM channel number, or None if the pin has no hardware PWM channel.
+    ///
+    /// Example usage:
+    /// ```python
+    /// channel = pwm_manager.channel_for_pin(18)
+    /// ```
+    #[staticmethod]
+    #[pyo3(signature = (pin_num))]
+    fn channel_for_pin(pin_num: u8) -> Option<u8> {
+        PWMManager::hardware_channel(pin_num)
+    }
+
+    /// Returns the hardware PWM channels of the detected board with the GPIO pin each one is routed to. The channels
+    /// use GPIO 18 and 19 on every board, with alternate function a3 on the Raspberry Pi 5 and a5 on earlier models.
+    ///
+    /// Returns:
+    /// - `dict[int, int]`: The GPIO pin of every PWM channel.
+    ///
+    /// Example usage:
+    /// ```python
+    /// for channel, pin in pwm_manager.available_channels().items():
+    ///     print(f"PWM channel {channel} is on GPIO {pin}")
+    /// ```
+    #[staticmethod]
+    fn available_channels() -> PyResult<HashMap<u8, u8>> {
+        device_info()?;
+        Ok(PWM_CHANNEL_PINS.iter().copied().collect())
+    }
 
     #[pyo3(signature = ())]
     fn cleanup(&self) -> PyResult<()> {
-        let pwm_channels = self.pwm_channels.lock().unwrap();
-        let channel_nums: Vec<u8> = pwm_channels.keys().cloned().collect();
-        drop(pwm_channels);
+        let channel_nums = self.channels.lock().unwrap().channel_nums();
 
         // Stop all PWM channels that are active
         for pin_num in channel_nums {
+            log_module::info(&format!("Stopping PWM channel {} during cleanup", pin_num));
             self.reset_pwm_channel(pin_num)?;
         }
-
-        let mut pwm_channels = self.pwm_channels.lock().unwrap();
-        pwm_channels.clear();
         Ok(())
     }
 }
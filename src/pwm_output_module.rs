@@ -0,0 +1,243 @@
+use crate::gpio_module::GPIOManager;
+use crate::pwm_module::PWMManager;
+use crate::pwm_ramp_module::Easing;
+use crate::{EnumArg, LogicLevel};
+use pyo3::{pyclass, pymethods, PyResult};
+
+
+/// The implementation driving a PWM output.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum PWMBackend {
+    Hardware(u8),
+    Software,
+}
+
+
+#[pyclass]
+/// PWM drives a PWM signal on a pin with one set of methods, using the hardware PWM channel of the pin when it has one
+/// and software PWM through the GPIOManager otherwise.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// led = gpio_manager.PWM(18, frequency_hz=1000, duty_cycle=25)
+/// led.start()
+/// led.set_duty_cycle(75)
+/// print(led.is_hardware())
+/// ```
+#[allow(clippy::upper_case_acronyms)]
+pub struct PWM {
+    pin: u8,
+    backend: PWMBackend,
+}
+
+
+#[pymethods]
+impl PWM {
+    #[new]
+    /// Sets up PWM on a pin. The hardware PWM channel is used when the pin has one and it can be set up, for example
+    /// when the PWM overlay is enabled, otherwise the pin falls back to software PWM.
+    ///
+    /// Parameters:
+    /// - `pin` (int): The GPIO pin.
+    /// - `frequency_hz` (float): The frequency in Hertz (default is 1000).
+    /// - `duty_cycle` (float): The duty cycle from 0 to 100 (default is 0).
+    /// - `period_ms` (float): The period in milliseconds, used in place of the frequency (default is None).
+    /// - `pulse_width_ms` (float): The pulse width in milliseconds, used in place of the duty cycle (default is None).
+    /// - `logic_level` (LogicLevel): The logic level of the pin, LOW inverts the signal (default is HIGH).
+    ///
+    /// Example usage:
+    /// ```python
+    /// servo = gpio_manager.PWM(12, period_ms=20, pulse_width_ms=1.5)
+    /// ```
+    #[pyo3(signature = (pin, frequency_hz = None, duty_cycle = None, period_ms = None, pulse_width_ms = None, logic_level = EnumArg(LogicLevel::HIGH)))]
+    pub(crate) fn new(pin: u8, frequency_hz: Option<f64>, duty_cycle: Option<f64>, period_ms: Option<f64>, pulse_width_ms: Option<f64>,
+           logic_level: EnumArg<LogicLevel>) -> PyResult<Self> {
+        let logic_level = logic_level.0;
+        if let Some(channel) = PWMManager::hardware_channel(pin) {
+            let pwm_manager = PWMManager::new_rust_reference();
+            let pwm_manager = pwm_manager.lock().unwrap();
+            if pwm_manager.setup_pwm_channel(channel, frequency_hz, duty_cycle, period_ms, pulse_width_ms, logic_level.into(), true).is_ok() {
+                return Ok(Self {
+                    pin,
+                    backend: PWMBackend::Hardware(channel),
+                });
+            }
+        }
+        GPIOManager::new_rust_reference().setup_pwm(pin, frequency_hz, duty_cycle, period_ms, pulse_width_ms, Some(logic_level.into()), false)?;
+        Ok(Self {
+            pin,
+            backend: PWMBackend::Software,
+        })
+    }
+
+    /// Starts the PWM signal.
+    ///
+    /// Example usage:
+    /// ```python
+    /// pwm.start()
+    /// ```
+    pub(crate) fn start(&self) -> PyResult<()> {
+        match self.backend {
+            PWMBackend::Hardware(channel) => PWMManager::new_rust_reference().lock().unwrap().start_pwm_channel(channel, None),
+            PWMBackend::Software => GPIOManager::new_rust_reference().start_pwm(self.pin, None),
+        }
+    }
+
+    /// Stops the PWM signal, the pin is driven to its inactive level.
+    ///
+    /// Example usage:
+    /// ```python
+    /// pwm.stop()
+    /// ```
+    pub(crate) fn stop(&self) -> PyResult<()> {
+        match self.backend {
+            PWMBackend::Hardware(channel) => PWMManager::new_rust_reference().lock().unwrap().stop_pwm_channel(channel),
+            PWMBackend::Software => GPIOManager::new_rust_reference().stop_pwm(self.pin),
+        }
+    }
+
+    /// Sets the duty cycle, either immediately or ramped over a duration.
+    ///
+    /// Parameters:
+    /// - `duty_cycle` (float): The duty cycle from 0 to 100.
+    /// - `ramp_ms` (float): The time to ramp from the current duty cycle to the new one in milliseconds, the ramp runs
+    ///   in the background (default is None, which sets the duty cycle immediately).
+    /// - `easing` (Easing): The curve the ramp follows (default is LINEAR).
+    ///
+    /// Example usage:
+    /// ```python
+    /// pwm.set_duty_cycle(75)
+    /// pwm.set_duty_cycle(0, ramp_ms=1000)
+    /// ```
+    #[pyo3(signature = (duty_cycle, ramp_ms = None, easing = Easing::LINEAR))]
+    pub(crate) fn set_duty_cycle(&self, duty_cycle: f64, ramp_ms: Option<f64>, easing: Easing) -> PyResult<()> {
+        match self.backend {
+            PWMBackend::Hardware(channel) => PWMManager::new_rust_reference().lock().unwrap().set_duty_cycle(channel, duty_cycle, ramp_ms, easing),
+            PWMBackend::Software => GPIOManager::new_rust_reference().set_pwm_duty_cycle(self.pin, duty_cycle, ramp_ms, easing),
+        }
+    }
+
+    /// Plays a duty cycle pattern from a background thread, for example to make an LED breathe. Setting the duty cycle,
+    /// playing another pattern or resetting the output stops the pattern.
+    ///
+    /// Parameters:
+    /// - `pattern` (list[tuple[float, float]]): The steps as `(duty_cycle, hold_ms)`, the duty cycle (0 to 100) is held
+    ///   for the time in milliseconds before the next step.
+    /// - `loop` (bool): Whether the pattern starts over after the last step (default is False).
+    ///
+    /// Example usage:
+    /// ```python
+    /// pwm.play_pattern([(duty, 20) for duty in [*range(0, 100, 5), *range(100, 0, -5)]], loop=True)
+    /// ```
+    #[pyo3(signature = (pattern, r#loop = false))]
+    fn play_pattern(&self, pattern: Vec<(f64, f64)>, r#loop: bool) -> PyResult<()> {
+        match self.backend {
+            PWMBackend::Hardware(channel) => PWMManager::new_rust_reference().lock().unwrap().play_pattern(channel, pattern, r#loop),
+            PWMBackend::Software => GPIOManager::new_rust_reference().play_pwm_pattern(self.pin, pattern, r#loop),
+        }
+    }
+
+    /// Sets a gamma curve, so duty cycles are interpreted as perceived brightness.
+    ///
+    /// Parameters:
+    /// - `gamma` (float): The exponent of the curve, 2.2 suits most LEDs (default is None).
+    /// - `lut` (list[float]): Duty cycles (0 to 100) for brightness values evenly spaced from 0 to 100, used in place of
+    ///   the exponent (default is None).
+    ///
+    /// Passing neither disables the curve.
+    ///
+    /// Example usage:
+    /// ```python
+    /// pwm.set_gamma(gamma=2.2)
+    /// ```
+    #[pyo3(signature = (gamma = None, lut = None))]
+    fn set_gamma(&self, gamma: Option<f64>, lut: Option<Vec<f64>>) -> PyResult<()> {
+        match self.backend {
+            PWMBackend::Hardware(channel) => PWMManager::new_rust_reference().lock().unwrap().set_gamma(channel, gamma, lut),
+            PWMBackend::Software => GPIOManager::new_rust_reference().set_pwm_gamma(self.pin, gamma, lut),
+        }
+    }
+
+    /// Sets the frequency, keeping the duty cycle.
+    ///
+    /// Parameters:
+    /// - `frequency_hz` (float): The frequency in Hertz.
+    ///
+    /// Example usage:
+    /// ```python
+    /// pwm.set_frequency(500)
+    /// ```
+    #[pyo3(signature = (frequency_hz))]
+    fn set_frequency(&self, frequency_hz: f64) -> PyResult<()> {
+        match self.backend {
+            PWMBackend::Hardware(channel) => PWMManager::new_rust_reference().lock().unwrap().set_frequency(channel, frequency_hz),
+            PWMBackend::Software => GPIOManager::new_rust_reference().set_pwm_frequency(self.pin, frequency_hz),
+        }
+    }
+
+    /// Sets the period.
+    ///
+    /// Parameters:
+    /// - `period_ms` (float): The period in milliseconds.
+    ///
+    /// Example usage:
+    /// ```python
+    /// pwm.set_period(20)
+    /// ```
+    #[pyo3(signature = (period_ms))]
+    fn set_period(&self, period_ms: f64) -> PyResult<()> {
+        match self.backend {
+            PWMBackend::Hardware(channel) => PWMManager::new_rust_reference().lock().unwrap().set_period(channel, period_ms),
+            PWMBackend::Software => GPIOManager::new_rust_reference().set_pwm_period(self.pin, period_ms),
+        }
+    }
+
+    /// Sets the pulse width, which must be less than the period.
+    ///
+    /// Parameters:
+    /// - `pulse_width_ms` (float): The pulse width in milliseconds.
+    ///
+    /// Example usage:
+    /// ```python
+    /// pwm.set_pulse_width(1.5)
+    /// ```
+    #[pyo3(signature = (pulse_width_ms))]
+    pub(crate) fn set_pulse_width(&self, pulse_width_ms: f64) -> PyResult<()> {
+        match self.backend {
+            PWMBackend::Hardware(channel) => PWMManager::new_rust_reference().lock().unwrap().set_pulse_width(channel, pulse_width_ms),
+            PWMBackend::Software => GPIOManager::new_rust_reference().set_pwm_pulse_width(self.pin, pulse_width_ms),
+        }
+    }
+
+    /// Stops the PWM signal and releases the pin, so it can be set up again.
+    ///
+    /// Example usage:
+    /// ```python
+    /// pwm.reset()
+    /// ```
+    pub(crate) fn reset(&self) -> PyResult<()> {
+        match self.backend {
+            PWMBackend::Hardware(channel) => PWMManager::new_rust_reference().lock().unwrap().reset_pwm_channel(channel),
+            PWMBackend::Software => GPIOManager::new_rust_reference().reset_pin(self.pin),
+        }
+    }
+
+    /// Returns whether the signal is generated by a hardware PWM channel.
+    fn is_hardware(&self) -> bool {
+        matches!(self.backend, PWMBackend::Hardware(_))
+    }
+
+    /// Returns the hardware PWM channel driving the pin, or None for software PWM.
+    fn get_channel(&self) -> Option<u8> {
+        match self.backend {
+            PWMBackend::Hardware(channel) => Some(channel),
+            PWMBackend::Software => None,
+        }
+    }
+
+    /// Returns the GPIO pin.
+    pub(crate) fn get_pin(&self) -> u8 {
+        self.pin
+    }
+}
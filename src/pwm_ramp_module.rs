@@ -0,0 +1,145 @@
+use crate::{ms_to_duration_checked, timing};
+use once_cell::sync::Lazy;
+use pyo3::{pyclass, PyErr, PyResult};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+
+// Time between two duty cycle updates of a ramp
+const RAMP_STEP: Duration = Duration::from_millis(10);
+
+
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+/// Enum representing the easing curves used to ramp a duty cycle.
+pub enum Easing {
+    LINEAR,
+    EASE_IN,
+    EASE_OUT,
+    EASE_IN_OUT,
+}
+
+
+impl Easing {
+    /// Maps the elapsed fraction of a ramp to the fraction of the duty cycle change applied.
+    pub fn apply(&self, t: f64) -> f64 {
+        match self {
+            Easing::LINEAR => t,
+            Easing::EASE_IN => t * t,
+            Easing::EASE_OUT => t * (2f64 - t),
+            Easing::EASE_IN_OUT => {
+                if t < 0.5 {
+                    2f64 * t * t
+                } else {
+                    1f64 - 2f64 * (1f64 - t) * (1f64 - t)
+                }
+            }
+        }
+    }
+}
+
+
+/// The output a ramp is running on.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RampTarget {
+    SoftwarePin(u8),
+    HardwareChannel(u8),
+}
+
+
+// Generation of the latest ramp or pattern of every output, a ramp stops once a newer one replaces it
+static RAMPS: Lazy<Mutex<HashMap<RampTarget, u64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+
+/// Stops the ramp or pattern running on the output, if there is one.
+pub fn cancel_ramp(target: RampTarget) {
+    next_generation(target);
+}
+
+
+pub fn next_generation(target: RampTarget) -> u64 {
+    let mut ramps = RAMPS.lock().unwrap();
+    let generation = ramps.entry(target).or_insert(0);
+    *generation += 1;
+    *generation
+}
+
+
+pub fn is_current(target: RampTarget, generation: u64) -> bool {
+    RAMPS.lock().unwrap().get(&target) == Some(&generation)
+}
+
+
+/// Converts the ramp time given to the API, None and times of 0 or less set the duty cycle right away.
+pub fn ramp_duration(ramp_ms: Option<f64>) -> PyResult<Option<Duration>> {
+    match ramp_ms {
+        Some(ramp_ms) if ramp_ms <= 0f64 => Ok(None),
+        ramp_ms => Ok(ramp_ms.map(|ramp_ms| ms_to_duration_checked("Ramp time", ramp_ms)).transpose()?),
+    }
+}
+
+
+/// Ramps the duty cycle of the output from `from` to `to` over the duration in a background thread, replacing any
+/// ramp already running on the output. The ramp stops early if setting the duty cycle fails, for example because the
+/// output was reset.
+pub fn start_ramp(target: RampTarget, from: f64, to: f64, duration: Duration, easing: Easing,
+                  set_duty_cycle: impl Fn(f64) -> PyResult<()> + Send + 'static) {
+    let generation = next_generation(target);
+
+    timing::spawn(move || {
+        let start = timing::now();
+        loop {
+            if !is_current(target, generation) {
+                return;
+            }
+            let t = ((timing::now() - start).as_secs_f64() / duration.as_secs_f64()).min(1f64);
+            if set_duty_cycle(from + (to - from) * easing.apply(t)).is_err() || t >= 1f64 {
+                return;
+            }
+            timing::sleep_until(timing::now() + RAMP_STEP, Duration::ZERO);
+        }
+    });
+}
+
+
+/// Checks the steps of a duty cycle pattern given as `(duty_cycle, hold_ms)` and converts the hold times.
+pub fn check_pattern(pattern: &[(f64, f64)]) -> PyResult<Vec<(f64, Duration)>> {
+    if pattern.is_empty() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("The pattern must have at least one step"));
+    }
+    pattern.iter()
+           .map(|(duty_cycle, hold_ms)| {
+               if !(0f64..=100f64).contains(duty_cycle) {
+                   return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Duty cycle must be between 0 and 100, The value {} does not meet this condition", duty_cycle)));
+               }
+               Ok((*duty_cycle, ms_to_duration_checked("Hold time", *hold_ms)?))
+           })
+           .collect()
+}
+
+
+/// Plays a duty cycle pattern on the output in a background thread, once or until it is replaced when `looped`. The
+/// pattern replaces any ramp or pattern running on the output, and is itself replaced by the next one or cancelled
+/// like a ramp. Steps are timed from absolute deadlines so long patterns don't drift.
+pub fn start_pattern(target: RampTarget, pattern: Vec<(f64, Duration)>, looped: bool,
+                     set_duty_cycle: impl Fn(f64) -> PyResult<()> + Send + 'static) {
+    let generation = next_generation(target);
+
+    timing::spawn(move || {
+        let mut deadline = timing::now();
+        loop {
+            for (duty_cycle, hold) in &pattern {
+                if !is_current(target, generation) || set_duty_cycle(*duty_cycle).is_err() {
+                    return;
+                }
+                deadline += *hold;
+                timing::sleep_until(deadline, Duration::ZERO);
+            }
+            if !looped {
+                return;
+            }
+        }
+    });
+}
@@ -0,0 +1,292 @@
+use crate::i2c_device_module::I2CDevice;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use pyo3::{pyclass, pymethods, IntoPyObjectExt, PyErr, PyObject, PyResult, Python};
+use std::collections::HashMap;
+
+
+#[pyclass]
+#[derive(Clone)]
+/// Register describes a single register of a device for use in a RegisterMap.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// temperature = gpio_manager.Register("temperature", 0x00, width=2, big_endian=True, signed=True, scale=0.0078125)
+/// ```
+pub struct Register {
+    name: String,
+    address: u8,
+    width: usize,
+    big_endian: bool,
+    signed: bool,
+    scale: Option<f64>,
+}
+
+
+impl Register {
+    /// Returns the smallest and largest raw value that fits in the register.
+    fn raw_range(&self) -> (i128, i128) {
+        let bits = self.width as u32 * 8;
+        if self.signed {
+            (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1)
+        } else {
+            (0, (1i128 << bits) - 1)
+        }
+    }
+
+    fn unpack(&self, data: &[u8]) -> i128 {
+        let mut raw: u64 = 0;
+        for i in 0..self.width {
+            let byte = if self.big_endian { data[i] } else { data[self.width - 1 - i] };
+            raw = (raw << 8) | byte as u64;
+        }
+        let bits = self.width as u32 * 8;
+        if self.signed && raw >> (bits - 1) & 1 == 1 {
+            raw as i128 - (1i128 << bits)
+        } else {
+            raw as i128
+        }
+    }
+
+    fn pack(&self, raw: i128) -> Vec<u8> {
+        let mut data: Vec<u8> = (0..self.width).map(|i| (raw >> (i * 8)) as u8).collect();
+        if self.big_endian {
+            data.reverse();
+        }
+        data
+    }
+}
+
+
+#[pymethods]
+impl Register {
+    #[new]
+    /// Initializes a new register description.
+    ///
+    /// Parameters:
+    /// - `name` (str): The name used to access the register.
+    /// - `address` (int): The address of the first byte of the register.
+    /// - `width` (int): The width of the register in bytes, from 1 to 8 (default is 1).
+    /// - `big_endian` (bool): The first byte holds the most significant byte (default is False).
+    /// - `signed` (bool): The register holds a two's complement value (default is False).
+    /// - `scale` (float): The value of one raw count, registers with a scale are read and written as floats
+    ///   (default is None).
+    ///
+    /// Example usage:
+    /// ```python
+    /// config = gpio_manager.Register("config", 0x01, width=2, big_endian=True)
+    /// ```
+    #[pyo3(signature = (name, address, width = 1, big_endian = false, signed = false, scale = None))]
+    fn new(name: String, address: u8, width: usize, big_endian: bool, signed: bool, scale: Option<f64>) -> PyResult<Self> {
+        if !(1..=8).contains(&width) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Register width must be between 1 and 8 bytes, The value {} does not meet this condition",
+                                                                                width)));
+        }
+        if scale == Some(0f64) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Register scale must not be 0"));
+        }
+        Ok(Self {
+            name,
+            address,
+            width,
+            big_endian,
+            signed,
+            scale,
+        })
+    }
+
+    /// Returns the name of the register.
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Returns the address of the register.
+    fn get_address(&self) -> u8 {
+        self.address
+    }
+}
+
+
+#[pyclass]
+/// RegisterMap reads and writes the registers of an I2CDevice by name, packing and unpacking the values in Rust.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// sensor = i2c_manager.device(0x48)
+/// registers = gpio_manager.RegisterMap(sensor, [
+///     gpio_manager.Register("temperature", 0x00, width=2, big_endian=True, signed=True, scale=0.0078125),
+///     gpio_manager.Register("config", 0x01, width=2, big_endian=True),
+/// ])
+/// print(registers.read("temperature"))
+/// registers.write("config", 0x60A0)
+/// ```
+pub struct RegisterMap {
+    device: I2CDevice,
+    registers: HashMap<String, Register>,
+    order: Vec<String>,
+}
+
+
+impl RegisterMap {
+    fn register(&self, name: &str) -> PyResult<&Register> {
+        self.registers
+            .get(name)
+            .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>(format!("Register {} is not in the register map", name)))
+    }
+
+    fn read_register(&self, py: Python, register: &Register) -> PyResult<PyObject> {
+        let data = self.device.read_block(register.address, register.width)?;
+        let raw = register.unpack(&data);
+        Ok(match register.scale {
+            Some(scale) => (raw as f64 * scale).into_py_any(py)?,
+            None => raw.into_py_any(py)?,
+        })
+    }
+}
+
+
+#[pymethods]
+impl RegisterMap {
+    #[new]
+    /// Initializes a new register map for a device.
+    ///
+    /// Parameters:
+    /// - `device` (I2CDevice): The device the registers belong to.
+    /// - `registers` (list[Register]): The registers of the device.
+    ///
+    /// Example usage:
+    /// ```python
+    /// registers = gpio_manager.RegisterMap(sensor, [gpio_manager.Register("who_am_i", 0x0F)])
+    /// ```
+    #[pyo3(signature = (device, registers))]
+    fn new(device: PyRef<I2CDevice>, registers: Vec<Register>) -> PyResult<Self> {
+        let mut map = HashMap::new();
+        let mut order = Vec::new();
+        for register in registers {
+            if map.contains_key(&register.name) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Register {} is declared more than once", register.name)));
+            }
+            order.push(register.name.clone());
+            map.insert(register.name.clone(), register);
+        }
+        Ok(Self {
+            device: device.clone(),
+            registers: map,
+            order,
+        })
+    }
+
+    /// Reads a register by name.
+    ///
+    /// Parameters:
+    /// - `name` (str): The name of the register.
+    ///
+    /// Returns:
+    /// - `int | float`: The value of the register, scaled registers return a float.
+    ///
+    /// Example usage:
+    /// ```python
+    /// temperature = registers.read("temperature")
+    /// ```
+    #[pyo3(signature = (name))]
+    fn read(&self, py: Python, name: &str) -> PyResult<PyObject> {
+        let register = self.register(name)?;
+        self.read_register(py, register)
+    }
+
+    /// Writes a register by name.
+    ///
+    /// Parameters:
+    /// - `name` (str): The name of the register.
+    /// - `value` (int | float): The value to write, the value of scaled registers is rounded to the nearest raw count.
+    ///
+    /// Example usage:
+    /// ```python
+    /// registers.write("config", 0x60A0)
+    /// ```
+    #[pyo3(signature = (name, value))]
+    fn write(&self, name: &str, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        let register = self.register(name)?;
+        let raw = match register.scale {
+            Some(scale) => (value.extract::<f64>()? / scale).round() as i128,
+            None => value.extract::<i128>()?,
+        };
+        let (min, max) = register.raw_range();
+        if raw < min || raw > max {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The value {} does not fit in register {}", value, name)));
+        }
+        self.device.write_block(register.address, &register.pack(raw))
+    }
+
+    /// Reads every register in the order they were declared.
+    ///
+    /// Returns:
+    /// - `dict[str, int | float]`: The value of every register by name.
+    ///
+    /// Example usage:
+    /// ```python
+    /// values = registers.read_all()
+    /// ```
+    fn read_all<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let values = PyDict::new(py);
+        for name in &self.order {
+            values.set_item(name, self.read_register(py, &self.registers[name])?)?;
+        }
+        Ok(values)
+    }
+
+    /// Returns the names of the registers in the order they were declared.
+    fn get_names(&self) -> Vec<String> {
+        self.order.clone()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn register(width: usize, big_endian: bool, signed: bool) -> Register {
+        Register { name: "test".to_string(), address: 0, width, big_endian, signed, scale: None }
+    }
+
+    #[test]
+    fn raw_range_of_8_byte_registers() {
+        assert_eq!(register(8, false, false).raw_range(), (0, u64::MAX as i128));
+        assert_eq!(register(8, true, true).raw_range(), (i64::MIN as i128, i64::MAX as i128));
+        assert_eq!(register(1, false, true).raw_range(), (-128, 127));
+    }
+
+    #[test]
+    fn unsigned_8_byte_values_above_i64_max() {
+        let raw = u64::MAX as i128 - 1;
+        let little = register(8, false, false);
+        assert_eq!(little.pack(raw), vec![0xFE, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(little.unpack(&little.pack(raw)), raw);
+        let big = register(8, true, false);
+        assert_eq!(big.pack(raw), vec![0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFE]);
+        assert_eq!(big.unpack(&big.pack(raw)), raw);
+    }
+
+    #[test]
+    fn signed_8_byte_values() {
+        for big_endian in [false, true] {
+            let register = register(8, big_endian, true);
+            for raw in [i64::MIN as i128, -1, 0, 1, i64::MAX as i128] {
+                assert_eq!(register.unpack(&register.pack(raw)), raw);
+            }
+        }
+        assert_eq!(register(8, true, true).unpack(&[0x80, 0, 0, 0, 0, 0, 0, 0]), i64::MIN as i128);
+        assert_eq!(register(8, false, true).unpack(&[0xFF; 8]), -1);
+    }
+
+    #[test]
+    fn byte_order() {
+        assert_eq!(register(2, true, false).pack(0x1234), vec![0x12, 0x34]);
+        assert_eq!(register(2, false, false).pack(0x1234), vec![0x34, 0x12]);
+        assert_eq!(register(2, true, true).unpack(&[0xFF, 0x38]), -200);
+        assert_eq!(register(2, false, true).unpack(&[0x38, 0xFF]), -200);
+    }
+}
@@ -0,0 +1,447 @@
+use crate::i2c_module::extract_bytes;
+use crate::{log_module, EnumArg, InternPullResistorState, LogicLevel, PinState, TriggerEdge};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyTuple};
+use pyo3::IntoPyObjectExt;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{channel, sync_channel, RecvTimeoutError, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+
+/// A callback assigned to a pin of the remote Pi.
+struct RemoteCallback {
+    callable: PyObject,
+    trigger_edge: TriggerEdge,
+    args: Py<PyTuple>,
+    send_time: bool,
+    send_edge: bool,
+}
+
+
+/// A stream to an event server over TCP, or to a daemon over its Unix socket.
+enum Stream {
+    Tcp(TcpStream),
+    Unix(UnixStream),
+}
+
+
+impl Stream {
+    fn try_clone(&self) -> io::Result<Stream> {
+        match self {
+            Stream::Tcp(stream) => stream.try_clone().map(Stream::Tcp),
+            Stream::Unix(stream) => stream.try_clone().map(Stream::Unix),
+        }
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.shutdown(Shutdown::Both),
+            Stream::Unix(stream) => stream.shutdown(Shutdown::Both),
+        }
+    }
+}
+
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.read(buf),
+            Stream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.write(buf),
+            Stream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.flush(),
+            Stream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+
+/// The connection to an event server, shared with the thread reading its frames.
+struct Connection {
+    stream: Mutex<Stream>,
+    /// The commands waiting for their reply by id.
+    pending: Mutex<HashMap<u64, SyncSender<Value>>>,
+    callbacks: Mutex<HashMap<u8, Vec<RemoteCallback>>>,
+    next_id: AtomicU64,
+    closed: AtomicBool,
+}
+
+
+impl Connection {
+    /// Calls the callbacks of the pin of an edge event.
+    fn dispatch(&self, py: Python, event: &Value) {
+        let Some(pin_num) = event["pin"].as_u64().and_then(|pin_num| u8::try_from(pin_num).ok()) else {
+            return;
+        };
+        // The edge is taken from the logical state, like the trigger edges of the callbacks
+        let edge = if event["state"].as_bool().unwrap_or_default() { TriggerEdge::RISING } else { TriggerEdge::FALLING };
+        let timestamp = event["timestamp"].as_f64().unwrap_or_default();
+        let callbacks: Vec<(PyObject, Py<PyTuple>, bool, bool)> = match self.callbacks.lock().unwrap().get(&pin_num) {
+            Some(callbacks) => callbacks.iter()
+                                        .filter(|callback| callback.trigger_edge == TriggerEdge::BOTH || callback.trigger_edge == edge)
+                                        .map(|callback| (callback.callable.clone_ref(py), callback.args.clone_ref(py), callback.send_time, callback.send_edge))
+                                        .collect(),
+            None => return,
+        };
+        for (callable, args, send_time, send_edge) in callbacks {
+            let call = || -> PyResult<PyObject> {
+                let mut call_args: Vec<PyObject> = Vec::new();
+                if send_time {
+                    call_args.push(timestamp.into_py_any(py)?);
+                }
+                if send_edge {
+                    call_args.push(edge.into_py_any(py)?);
+                }
+                call_args.extend(args.bind(py).iter().map(|item| item.unbind()));
+                callable.call1(py, PyTuple::new(py, call_args)?)
+            };
+            if let Err(e) = call() {
+                log_module::exception(&format!("Exception in a callback of remote GPIO {}", pin_num), &e);
+            }
+        }
+    }
+
+    /// Reads the frames of the server until the connection closes, passing replies to their commands and edges to the
+    /// callback thread. The callbacks run on their own thread so they can send commands, whose replies this thread
+    /// reads.
+    fn read_frames(&self, stream: Stream, events: Sender<Value>) {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let Ok(frame) = serde_json::from_str::<Value>(&line) else {
+                continue;
+            };
+            if frame["event"] == "edge" {
+                let _ = events.send(frame);
+            } else if let Some(reply) = frame["id"].as_u64().and_then(|id| self.pending.lock().unwrap().remove(&id)) {
+                let _ = reply.send(frame);
+            }
+        }
+        self.closed.store(true, Ordering::SeqCst);
+        // Dropping the senders wakes the commands still waiting
+        self.pending.lock().unwrap().clear();
+    }
+}
+
+
+fn connection_error(message: String) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyConnectionError, _>(message)
+}
+
+
+#[pyclass]
+/// Drives the pins of a remote Pi running the event server, or of the daemon owning the GPIO of this Pi, with the API
+/// of the GPIOManager.
+pub struct RemoteGPIOManager {
+    address: String,
+    connection: Arc<Connection>,
+    timeout: Duration,
+    /// The pins set up through this manager, reset by cleanup.
+    pins: Mutex<HashSet<u8>>,
+}
+
+
+impl RemoteGPIOManager {
+    /// Connects to the event server at `host:port`, or to the daemon listening on the socket of `unix:<path>`.
+    pub fn connect(address: &str, timeout: Duration) -> PyResult<Self> {
+        let stream = match address.strip_prefix("unix:") {
+            Some(path) => Stream::Unix(UnixStream::connect(path).map_err(|e| connection_error(format!("Unable to connect to {}: {}", path, e)))?),
+            None => {
+                let socket_address = address.to_socket_addrs()
+                                            .map_err(|e| connection_error(format!("Unable to resolve {}: {}", address, e)))?
+                                            .next()
+                                            .ok_or_else(|| connection_error(format!("Unable to resolve {}", address)))?;
+                let stream = TcpStream::connect_timeout(&socket_address, timeout)
+                    .map_err(|e| connection_error(format!("Unable to connect to {}: {}", address, e)))?;
+                stream.set_nodelay(true)?;
+                Stream::Tcp(stream)
+            }
+        };
+        let reader = stream.try_clone()?;
+        let connection = Arc::new(Connection {
+            stream: Mutex::new(stream),
+            pending: Mutex::new(HashMap::new()),
+            callbacks: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+            closed: AtomicBool::new(false),
+        });
+        let (events, received) = channel::<Value>();
+        let thread_connection = Arc::clone(&connection);
+        thread::spawn(move || thread_connection.read_frames(reader, events));
+        let callback_connection = Arc::clone(&connection);
+        thread::spawn(move || {
+            for event in received {
+                Python::with_gil(|py| callback_connection.dispatch(py, &event));
+            }
+        });
+        Ok(RemoteGPIOManager { address: address.to_string(), connection, timeout, pins: Mutex::new(HashSet::new()) })
+    }
+
+    /// Sends a command and waits for its reply without holding the GIL, returning its result.
+    fn request(&self, py: Python, mut command: Value) -> PyResult<Value> {
+        let connection = &self.connection;
+        if connection.closed.load(Ordering::SeqCst) {
+            return Err(connection_error(format!("The connection to {} is closed", self.address)));
+        }
+        let id = connection.next_id.fetch_add(1, Ordering::SeqCst);
+        command["id"] = id.into();
+        let (sender, reply) = sync_channel(1);
+        connection.pending.lock().unwrap().insert(id, sender);
+        let sent = writeln!(connection.stream.lock().unwrap(), "{}", command);
+        if let Err(e) = sent {
+            connection.pending.lock().unwrap().remove(&id);
+            return Err(connection_error(format!("Unable to send the command to {}: {}", self.address, e)));
+        }
+        let timeout = self.timeout;
+        let reply = py.allow_threads(move || reply.recv_timeout(timeout));
+        let reply = match reply {
+            Ok(reply) => reply,
+            Err(RecvTimeoutError::Timeout) => {
+                connection.pending.lock().unwrap().remove(&id);
+                return Err(PyErr::new::<pyo3::exceptions::PyTimeoutError, _>(format!("{} did not reply to the command in time", self.address)));
+            }
+            Err(RecvTimeoutError::Disconnected) => return Err(connection_error(format!("The connection to {} is closed", self.address))),
+        };
+        if reply["ok"].as_bool().unwrap_or_default() {
+            Ok(reply["result"].clone())
+        } else {
+            Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("{}: {}", self.address, reply["error"].as_str().unwrap_or("unknown error"))))
+        }
+    }
+
+    fn logic_level_name(logic_level: LogicLevel) -> &'static str {
+        if logic_level == LogicLevel::HIGH { "high" } else { "low" }
+    }
+}
+
+
+#[pymethods]
+impl RemoteGPIOManager {
+    /// Sets up an input pin of the remote Pi, its edges are streamed to this manager.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin to configure as input.
+    /// - ```pull_resistor_state``` (InternPullResistorState): The pull resistor of the pin (default is AUTO).
+    /// - ```logic_level``` (LogicLevel): The logic level of the pin (default is HIGH).
+    ///
+    /// Example usage:
+    /// ```remote.add_input_pin(17)```
+    ///
+    #[pyo3(signature = (pin_num, pull_resistor_state = EnumArg(InternPullResistorState::AUTO), logic_level = EnumArg(LogicLevel::HIGH)))]
+    fn add_input_pin(&self, py: Python, pin_num: u8, pull_resistor_state: EnumArg<InternPullResistorState>, logic_level: EnumArg<LogicLevel>) -> PyResult<()> {
+        let pull_resistor_state = pull_resistor_state.0;
+        let logic_level = logic_level.0;
+        let pull = match pull_resistor_state {
+            InternPullResistorState::PULLUP => "up",
+            InternPullResistorState::PULLDOWN => "down",
+            InternPullResistorState::EXTERNAL => "external",
+            InternPullResistorState::AUTO => "auto",
+        };
+        self.request(py, json!({"cmd": "add_input", "pin": pin_num, "pull": pull, "logic_level": Self::logic_level_name(logic_level)}))?;
+        self.pins.lock().unwrap().insert(pin_num);
+        Ok(())
+    }
+
+    /// Sets up an output pin of the remote Pi.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin to configure as output.
+    /// - ```pin_state``` (PinState): The initial state of the pin (default is LOW).
+    /// - ```logic_level``` (LogicLevel): The logic level of the pin (default is HIGH).
+    ///
+    /// Example usage:
+    /// ```remote.add_output_pin(27)```
+    ///
+    #[pyo3(signature = (pin_num, pin_state = EnumArg(PinState::LOW), logic_level = EnumArg(LogicLevel::HIGH)))]
+    fn add_output_pin(&self, py: Python, pin_num: u8, pin_state: EnumArg<PinState>, logic_level: EnumArg<LogicLevel>) -> PyResult<()> {
+        let pin_state = pin_state.0;
+        let logic_level = logic_level.0;
+        self.request(py, json!({"cmd": "add_output", "pin": pin_num, "state": pin_state == PinState::HIGH, "logic_level": Self::logic_level_name(logic_level)}))?;
+        self.pins.lock().unwrap().insert(pin_num);
+        Ok(())
+    }
+
+    /// Sets the state of an output pin of the remote Pi.
+    ///
+    /// Example usage:
+    /// ```remote.set_output_pin(27, gpio_manager.PinState.HIGH)```
+    ///
+    fn set_output_pin(&self, py: Python, pin_num: u8, pin_state: EnumArg<PinState>) -> PyResult<()> {
+        let pin_state = pin_state.0;
+        self.request(py, json!({"cmd": "set", "pin": pin_num, "state": pin_state == PinState::HIGH}))?;
+        Ok(())
+    }
+
+    /// Reads the state of an input pin of the remote Pi.
+    ///
+    /// Example usage:
+    /// ```state = remote.get_pin(17)```
+    ///
+    fn get_pin(&self, py: Python, pin_num: u8) -> PyResult<PinState> {
+        let high = self.request(py, json!({"cmd": "get", "pin": pin_num}))?;
+        Ok(if high.as_bool().unwrap_or_default() { PinState::HIGH } else { PinState::LOW })
+    }
+
+    /// Sets up a software PWM pin of the remote Pi, see GPIOManager.setup_pwm.
+    ///
+    /// Example usage:
+    /// ```remote.setup_pwm(12, frequency_hz=1000, duty_cycle=50)```
+    ///
+    #[pyo3(signature = (pin_num, frequency_hz = None, duty_cycle = None, period_ms = None, pulse_width_ms = None, logic_level = EnumArg(LogicLevel::HIGH)))]
+    #[allow(clippy::too_many_arguments)]
+    fn setup_pwm(&self, py: Python, pin_num: u8, frequency_hz: Option<f64>, duty_cycle: Option<f64>, period_ms: Option<f64>, pulse_width_ms: Option<f64>,
+                 logic_level: EnumArg<LogicLevel>) -> PyResult<()> {
+        let logic_level = logic_level.0;
+        self.request(py, json!({"cmd": "setup_pwm", "pin": pin_num, "frequency_hz": frequency_hz, "duty_cycle": duty_cycle, "period_ms": period_ms,
+                                "pulse_width_ms": pulse_width_ms, "logic_level": Self::logic_level_name(logic_level)}))?;
+        self.pins.lock().unwrap().insert(pin_num);
+        Ok(())
+    }
+
+    /// Starts the PWM signal of a pin of the remote Pi.
+    fn start_pwm(&self, py: Python, pin_num: u8) -> PyResult<()> {
+        self.request(py, json!({"cmd": "start_pwm", "pin": pin_num}))?;
+        Ok(())
+    }
+
+    /// Stops the PWM signal of a pin of the remote Pi.
+    fn stop_pwm(&self, py: Python, pin_num: u8) -> PyResult<()> {
+        self.request(py, json!({"cmd": "stop_pwm", "pin": pin_num}))?;
+        Ok(())
+    }
+
+    /// Sets the duty cycle (0 to 100) of a PWM pin of the remote Pi.
+    ///
+    /// Example usage:
+    /// ```remote.set_pwm_duty_cycle(12, 75)```
+    ///
+    fn set_pwm_duty_cycle(&self, py: Python, pin_num: u8, duty_cycle: f64) -> PyResult<()> {
+        self.request(py, json!({"cmd": "duty", "pin": pin_num, "duty_cycle": duty_cycle}))?;
+        Ok(())
+    }
+
+    /// Writes data to an I2C device and reads its response in one transaction on the remote Pi, opening the bus if
+    /// needed.
+    ///
+    /// Parameters:
+    /// - ```addr``` (int): The I2C slave address.
+    /// - ```write_data``` (bytes | bytearray | memoryview | list[int]): The data to write, empty for a plain read.
+    /// - ```read_length``` (int): The number of bytes to read, 0 for a plain write.
+    /// - ```bus``` (int): The I2C bus to use (default is 1).
+    ///
+    /// Returns:
+    /// - ```bytes```: The data read.
+    ///
+    /// Example usage:
+    /// ```data = remote.i2c_write_read(0x48, b'\x00', 2)```
+    ///
+    #[pyo3(signature = (addr, write_data, read_length, bus = 1))]
+    fn i2c_write_read<'py>(&self, py: Python<'py>, addr: u16, write_data: &Bound<'py, PyAny>, read_length: usize, bus: u8) -> PyResult<Bound<'py, PyBytes>> {
+        let write_data = extract_bytes(write_data)?;
+        let data = self.request(py, json!({"cmd": "i2c", "addr": addr, "bus": bus, "write": write_data.as_ref(), "read": read_length}))?;
+        let data: Vec<u8> = serde_json::from_value(data).map_err(|e| connection_error(format!("{} sent invalid I2C data: {}", self.address, e)))?;
+        Ok(PyBytes::new(py, &data))
+    }
+
+    /// Assigns a callback to an input pin of the remote Pi, called from the callback thread of the connection. The
+    /// edges are debounced by the server.
+    ///
+    /// Parameters:
+    /// - ```pin_num``` (int): The GPIO pin.
+    /// - ```callback``` (function): The callback function to be invoked on pin change.
+    /// - ```trigger_edge``` (TriggerEdge): The edges calling the callback (default is BOTH).
+    /// - ```args``` (tuple): The arguments to pass to the callback function (default is None).
+    /// - ```include_trigger_time``` (bool): Whether the time of the edge on the remote Pi is passed first (default is
+    ///   False).
+    /// - ```include_trigger_edge``` (bool): Whether the edge is passed after the time (default is False).
+    ///
+    /// Example usage:
+    /// ```remote.assign_callback(17, button_callback, gpio_manager.TriggerEdge.FALLING)```
+    ///
+    #[pyo3(signature = (pin_num, callback, trigger_edge = EnumArg(TriggerEdge::BOTH), args = None, include_trigger_time = false, include_trigger_edge = false))]
+    #[allow(clippy::too_many_arguments)]
+    fn assign_callback(&self, py: Python, pin_num: u8, callback: PyObject, trigger_edge: EnumArg<TriggerEdge>, args: Option<&Bound<'_, PyTuple>>,
+                       include_trigger_time: bool, include_trigger_edge: bool) -> PyResult<()> {
+        let trigger_edge = trigger_edge.0;
+        if !callback.bind(py).is_callable() {
+            return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>("Object is not callable"));
+        }
+        let args = match args {
+            Some(args) => args.clone().unbind(),
+            None => PyTuple::empty(py).unbind(),
+        };
+        self.connection.callbacks.lock().unwrap().entry(pin_num).or_default().push(RemoteCallback {
+            callable: callback,
+            trigger_edge,
+            args,
+            send_time: include_trigger_time,
+            send_edge: include_trigger_edge,
+        });
+        Ok(())
+    }
+
+    /// Removes the callbacks of a pin of the remote Pi.
+    fn unassign_callbacks(&self, pin_num: u8) {
+        self.connection.callbacks.lock().unwrap().remove(&pin_num);
+    }
+
+    /// Resets a pin of the remote Pi.
+    fn reset_pin(&self, py: Python, pin_num: u8) -> PyResult<()> {
+        self.connection.callbacks.lock().unwrap().remove(&pin_num);
+        self.request(py, json!({"cmd": "reset", "pin": pin_num}))?;
+        self.pins.lock().unwrap().remove(&pin_num);
+        Ok(())
+    }
+
+    /// Resets the pins of the remote Pi set up through this manager.
+    fn cleanup(&self, py: Python) -> PyResult<()> {
+        let pins: Vec<u8> = self.pins.lock().unwrap().iter().copied().collect();
+        for pin_num in pins {
+            self.reset_pin(py, pin_num)?;
+        }
+        Ok(())
+    }
+
+    /// Closes the connection to the remote Pi. The pins keep their state on an event server, while a daemon resets
+    /// the pins of the clients that disconnect.
+    fn close(&self) {
+        let _ = self.connection.stream.lock().unwrap().shutdown();
+    }
+
+    /// Returns whether the connection to the remote Pi is open.
+    fn is_connected(&self) -> bool {
+        !self.connection.closed.load(Ordering::SeqCst)
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __exit__(&self, py: Python, _args: &Bound<'_, PyTuple>) -> PyResult<bool> {
+        let cleaned = self.cleanup(py);
+        self.close();
+        cleaned.map(|_| false)
+    }
+}
@@ -0,0 +1,384 @@
+use crate::board_module::board_to_bcm;
+use crate::gpio_module::GPIOManager;
+use crate::pwm_ramp_module::Easing;
+use crate::{InternPullResistorState, LogicLevel, OutputMode, PinState, TriggerEdge};
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use pyo3::types::{PyList, PyTuple};
+use pyo3::{pyclass, pyfunction, pymethods, PyErr, PyObject, PyResult, Python};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+
+// Constants with the same values as RPi.GPIO
+const BOARD: u8 = 10;
+const BCM: u8 = 11;
+const OUT: u8 = 0;
+const IN: u8 = 1;
+const LOW: u8 = 0;
+const HIGH: u8 = 1;
+const PUD_OFF: u8 = 20;
+const PUD_DOWN: u8 = 21;
+const PUD_UP: u8 = 22;
+const RISING: u8 = 31;
+const FALLING: u8 = 32;
+const BOTH: u8 = 33;
+
+
+// Numbering mode selected with setmode
+static MODE: Lazy<Mutex<Option<u8>>> = Lazy::new(|| Mutex::new(None));
+
+// Edge detected on every BCM pin with edge detection enabled
+static EDGES: Lazy<Mutex<HashMap<u8, TriggerEdge>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+// BCM pins with an edge detected since the last call to event_detected
+static EVENTS: Lazy<Mutex<HashSet<u8>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+
+/// Converts a channel in the selected numbering mode to its BCM GPIO number.
+fn to_bcm(channel: u8) -> PyResult<u8> {
+    match *MODE.lock().unwrap() {
+        Some(BCM) if channel <= 27 => Ok(channel),
+        Some(BOARD) => board_to_bcm(channel)
+            .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The channel {} is not a GPIO pin of the header", channel))),
+        Some(_) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The channel {} is not a valid BCM GPIO pin", channel))),
+        None => Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Please set pin numbering mode using GPIO.setmode(GPIO.BOARD) or GPIO.setmode(GPIO.BCM)")),
+    }
+}
+
+
+/// Accepts a single value or a list/tuple of values, like RPi.GPIO does for channels and output values.
+fn extract_list<'py, T: FromPyObject<'py>>(value: &Bound<'py, PyAny>) -> PyResult<Vec<T>> {
+    if value.downcast::<PyList>().is_ok() || value.downcast::<PyTuple>().is_ok() {
+        value.extract()
+    } else {
+        Ok(vec![value.extract()?])
+    }
+}
+
+
+fn to_trigger_edge(edge: u8) -> PyResult<TriggerEdge> {
+    match edge {
+        RISING => Ok(TriggerEdge::RISING),
+        FALLING => Ok(TriggerEdge::FALLING),
+        BOTH => Ok(TriggerEdge::BOTH),
+        _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("The edge must be set to RISING, FALLING or BOTH")),
+    }
+}
+
+
+/// Releases a pin that was set up before, so it can be set up again with another direction.
+fn release_pin(manager: &GPIOManager, pin: u8) -> PyResult<()> {
+    let gpio = manager.get_manager();
+    let is_setup = {
+        let gpio = gpio.lock().unwrap();
+        manager.is_input_pin(pin, &gpio) || manager.is_output_pin(pin, &gpio)
+    };
+    if is_setup {
+        manager.reset_pin(pin)?;
+    }
+    Ok(())
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (mode))]
+/// Sets the pin numbering mode, BOARD for the physical pins of the header or BCM for the GPIO numbers.
+fn setmode(mode: u8) -> PyResult<()> {
+    if mode != BOARD && mode != BCM {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("An invalid mode was passed to setmode()"));
+    }
+    let mut current = MODE.lock().unwrap();
+    if current.is_some() && *current != Some(mode) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("A different mode has already been set!"));
+    }
+    *current = Some(mode);
+    Ok(())
+}
+
+
+#[pyfunction]
+/// Returns the pin numbering mode, or None if it wasn't set.
+fn getmode() -> Option<u8> {
+    *MODE.lock().unwrap()
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (flag))]
+#[allow(unused_variables)]
+/// Accepted for compatibility, pins that are already in use are set up again without a warning.
+fn setwarnings(flag: bool) {}
+
+
+#[pyfunction]
+#[pyo3(signature = (channel, direction, pull_up_down = PUD_OFF, initial = None))]
+/// Sets up one or more channels as an input or an output.
+fn setup(channel: &Bound<'_, PyAny>, direction: u8, pull_up_down: u8, initial: Option<u8>) -> PyResult<()> {
+    let manager = GPIOManager::new_rust_reference();
+    for channel in extract_list::<u8>(channel)? {
+        let pin = to_bcm(channel)?;
+        release_pin(&manager, pin)?;
+        match direction {
+            IN => {
+                let pull = match pull_up_down {
+                    PUD_UP => InternPullResistorState::PULLUP,
+                    PUD_DOWN => InternPullResistorState::PULLDOWN,
+                    PUD_OFF => InternPullResistorState::EXTERNAL,
+                    _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid value for pull_up_down - should be either PUD_OFF, PUD_UP or PUD_DOWN")),
+                };
+                manager.add_input_pin(pin, Some(pull.into()), Some(LogicLevel::HIGH.into()), false)?;
+            }
+            OUT => {
+                let state = if initial == Some(HIGH) { PinState::HIGH } else { PinState::LOW };
+                manager.add_output_pin(pin, state.into(), Some(LogicLevel::HIGH.into()), OutputMode::PUSH_PULL.into(), false)?;
+            }
+            _ => return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("An invalid direction was passed to setup()")),
+        }
+    }
+    Ok(())
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (channel))]
+/// Reads the level of a channel, output channels return the level they are driven to.
+fn input(channel: u8) -> PyResult<u8> {
+    let pin = to_bcm(channel)?;
+    let manager = GPIOManager::new_rust_reference();
+    let is_output = manager.is_output_pin(pin, &manager.get_manager().lock().unwrap());
+    let state = if is_output { manager.get_output_state(pin)? } else { manager.get_pin(pin)? };
+    Ok(if state == PinState::HIGH { HIGH } else { LOW })
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (channel, value))]
+/// Sets the level of one or more output channels, a list of values sets every channel to its own value.
+fn output(channel: &Bound<'_, PyAny>, value: &Bound<'_, PyAny>) -> PyResult<()> {
+    let channels = extract_list::<u8>(channel)?;
+    let values = extract_list::<i64>(value)?;
+    if values.len() != 1 && values.len() != channels.len() {
+        return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Number of channels != number of values"));
+    }
+    let manager = GPIOManager::new_rust_reference();
+    for (i, channel) in channels.iter().enumerate() {
+        let value = if values.len() == 1 { values[0] } else { values[i] };
+        manager.set_output_pin(to_bcm(*channel)?, if value != 0 { PinState::HIGH } else { PinState::LOW }.into())?;
+    }
+    Ok(())
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (pin))]
+/// Records that an edge was detected, registered by add_event_detect for event_detected.
+fn record_event(pin: u8) {
+    EVENTS.lock().unwrap().insert(pin);
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (channel, edge, callback = None, bouncetime = None))]
+/// Enables edge detection on an input channel, optionally calling a callback with the channel on every edge.
+fn add_event_detect(py: Python, channel: u8, edge: u8, callback: Option<PyObject>, bouncetime: Option<f64>) -> PyResult<()> {
+    let pin = to_bcm(channel)?;
+    let edge = to_trigger_edge(edge)?;
+    if EDGES.lock().unwrap().contains_key(&pin) {
+        return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Conflicting edge detection already enabled for this GPIO channel"));
+    }
+    let bouncetime = bouncetime.unwrap_or(0f64);
+    let manager = GPIOManager::new_rust_reference();
+    let record = wrap_pyfunction!(record_event, py)?;
+    manager.assign_callback(py, pin, record.into_any().unbind(), edge.into(), Some(bouncetime), Some(&PyTuple::new(py, [pin])?), false, false, "hardware", None, false, 0, false)?;
+    if let Some(callback) = callback {
+        manager.assign_callback(py, pin, callback, edge.into(), Some(bouncetime), Some(&PyTuple::new(py, [channel])?), false, false, "hardware", None, false, 0, false)?;
+    }
+    EDGES.lock().unwrap().insert(pin, edge);
+    Ok(())
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (channel, callback))]
+/// Adds a callback to a channel with edge detection enabled, the callback is called with the channel.
+fn add_event_callback(py: Python, channel: u8, callback: PyObject) -> PyResult<()> {
+    let pin = to_bcm(channel)?;
+    let edge = match EDGES.lock().unwrap().get(&pin) {
+        Some(edge) => *edge,
+        None => return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Add event detection using add_event_detect first before adding a callback")),
+    };
+    GPIOManager::new_rust_reference().assign_callback(py, pin, callback, edge.into(), Some(0f64), Some(&PyTuple::new(py, [channel])?), false, false, "hardware", None, false, 0, false)
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (channel))]
+/// Disables edge detection on a channel and removes its callbacks.
+fn remove_event_detect(channel: u8) -> PyResult<()> {
+    let pin = to_bcm(channel)?;
+    GPIOManager::new_rust_reference().unassign_callbacks(pin)?;
+    EDGES.lock().unwrap().remove(&pin);
+    EVENTS.lock().unwrap().remove(&pin);
+    Ok(())
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (channel))]
+/// Returns whether an edge was detected on the channel since the last call.
+fn event_detected(channel: u8) -> PyResult<bool> {
+    let pin = to_bcm(channel)?;
+    Ok(EVENTS.lock().unwrap().remove(&pin))
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (channel, edge, bouncetime = None, timeout = None))]
+/// Blocks until an edge is detected on the channel, returning the channel or None if the timeout in milliseconds
+/// expired first.
+fn wait_for_edge(channel: u8, edge: u8, bouncetime: Option<f64>, timeout: Option<f64>) -> PyResult<Option<u8>> {
+    let pin = to_bcm(channel)?;
+    let edge = to_trigger_edge(edge)?;
+    let manager = GPIOManager::new_rust_reference();
+    let detected = manager.wait_for_edge_detected(pin, edge, timeout, bouncetime)?;
+    Ok(if detected { Some(channel) } else { None })
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (channel = None))]
+/// Releases one or more channels, or every channel and the numbering mode if no channel is given.
+fn cleanup(channel: Option<&Bound<'_, PyAny>>) -> PyResult<()> {
+    let manager = GPIOManager::new_rust_reference();
+    match channel {
+        Some(channel) => {
+            for channel in extract_list::<u8>(channel)? {
+                let pin = to_bcm(channel)?;
+                release_pin(&manager, pin)?;
+                EDGES.lock().unwrap().remove(&pin);
+                EVENTS.lock().unwrap().remove(&pin);
+            }
+        }
+        None => {
+            manager.cleanup()?;
+            EDGES.lock().unwrap().clear();
+            EVENTS.lock().unwrap().clear();
+            *MODE.lock().unwrap() = None;
+        }
+    }
+    Ok(())
+}
+
+
+#[pyclass(name = "PWM", module = "gpio_manager.compat.RPiGPIO")]
+/// Software PWM on a channel, with the same methods as RPi.GPIO.PWM.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// GPIO.setup(18, GPIO.OUT)
+/// pwm = GPIO.PWM(18, 50)
+/// pwm.start(7.5)
+/// pwm.ChangeDutyCycle(10)
+/// pwm.stop()
+/// ```
+pub struct RPiPWM {
+    pin: u8,
+}
+
+
+#[pymethods]
+impl RPiPWM {
+    #[new]
+    /// Sets up PWM on a channel.
+    ///
+    /// Parameters:
+    /// - `channel` (int): The channel in the selected numbering mode.
+    /// - `frequency` (float): The frequency in Hz.
+    #[pyo3(signature = (channel, frequency))]
+    fn new(channel: u8, frequency: f64) -> PyResult<Self> {
+        if frequency <= 0f64 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("frequency must be greater than 0.0"));
+        }
+        let pin = to_bcm(channel)?;
+        let manager = GPIOManager::new_rust_reference();
+        release_pin(&manager, pin)?;
+        manager.setup_pwm(pin, Some(frequency), None, None, None, Some(LogicLevel::HIGH.into()), false)?;
+        Ok(Self { pin })
+    }
+
+    /// Starts the PWM output.
+    ///
+    /// Parameters:
+    /// - `dutycycle` (float): The duty cycle from 0 to 100.
+    #[pyo3(signature = (dutycycle))]
+    fn start(&self, dutycycle: f64) -> PyResult<()> {
+        let manager = GPIOManager::new_rust_reference();
+        manager.set_pwm_duty_cycle(self.pin, dutycycle, None, Easing::LINEAR)?;
+        manager.start_pwm(self.pin, None)
+    }
+
+    /// Changes the duty cycle.
+    ///
+    /// Parameters:
+    /// - `dutycycle` (float): The duty cycle from 0 to 100.
+    #[pyo3(name = "ChangeDutyCycle", signature = (dutycycle))]
+    fn change_duty_cycle(&self, dutycycle: f64) -> PyResult<()> {
+        GPIOManager::new_rust_reference().set_pwm_duty_cycle(self.pin, dutycycle, None, Easing::LINEAR)
+    }
+
+    /// Changes the frequency.
+    ///
+    /// Parameters:
+    /// - `frequency` (float): The frequency in Hz.
+    #[pyo3(name = "ChangeFrequency", signature = (frequency))]
+    fn change_frequency(&self, frequency: f64) -> PyResult<()> {
+        if frequency <= 0f64 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("frequency must be greater than 0.0"));
+        }
+        GPIOManager::new_rust_reference().set_pwm_frequency(self.pin, frequency)
+    }
+
+    /// Stops the PWM output.
+    fn stop(&self) -> PyResult<()> {
+        GPIOManager::new_rust_reference().stop_pwm(self.pin)
+    }
+}
+
+
+/// Adds the `compat` package with the `RPiGPIO` module, and registers both in `sys.modules` so
+/// `import gpio_manager.compat.RPiGPIO as GPIO` works.
+pub fn add_compat_module(parent: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = parent.py();
+    let compat = PyModule::new(py, "compat")?;
+    compat.gil_used(false)?;
+    let rpi_gpio = PyModule::new(py, "RPiGPIO")?;
+    rpi_gpio.gil_used(false)?;
+
+    for (name, value) in [("BOARD", BOARD), ("BCM", BCM), ("OUT", OUT), ("IN", IN), ("LOW", LOW), ("HIGH", HIGH), ("PUD_OFF", PUD_OFF),
+                          ("PUD_DOWN", PUD_DOWN), ("PUD_UP", PUD_UP), ("RISING", RISING), ("FALLING", FALLING), ("BOTH", BOTH)] {
+        rpi_gpio.add(name, value)?;
+    }
+    rpi_gpio.add_function(wrap_pyfunction!(setmode, &rpi_gpio)?)?;
+    rpi_gpio.add_function(wrap_pyfunction!(getmode, &rpi_gpio)?)?;
+    rpi_gpio.add_function(wrap_pyfunction!(setwarnings, &rpi_gpio)?)?;
+    rpi_gpio.add_function(wrap_pyfunction!(setup, &rpi_gpio)?)?;
+    rpi_gpio.add_function(wrap_pyfunction!(input, &rpi_gpio)?)?;
+    rpi_gpio.add_function(wrap_pyfunction!(output, &rpi_gpio)?)?;
+    rpi_gpio.add_function(wrap_pyfunction!(add_event_detect, &rpi_gpio)?)?;
+    rpi_gpio.add_function(wrap_pyfunction!(add_event_callback, &rpi_gpio)?)?;
+    rpi_gpio.add_function(wrap_pyfunction!(remove_event_detect, &rpi_gpio)?)?;
+    rpi_gpio.add_function(wrap_pyfunction!(event_detected, &rpi_gpio)?)?;
+    rpi_gpio.add_function(wrap_pyfunction!(wait_for_edge, &rpi_gpio)?)?;
+    rpi_gpio.add_function(wrap_pyfunction!(cleanup, &rpi_gpio)?)?;
+    rpi_gpio.add_class::<RPiPWM>()?;
+
+    compat.add_submodule(&rpi_gpio)?;
+    parent.add_submodule(&compat)?;
+
+    let modules = py.import("sys")?.getattr("modules")?;
+    modules.set_item("gpio_manager.compat", &compat)?;
+    modules.set_item("gpio_manager.compat.RPiGPIO", &rpi_gpio)?;
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use crate::backend::{Backend, Error, InputLine, InterruptCallback, OutputLine, PwmLine, Result};
+use crate::board_module::Peripheral;
+use crate::gpiochip::Chip;
+use crate::pinctrl;
+use rppal::gpio::{Bias, Event, Gpio, InputPin, Level, OutputPin, Trigger};
+use rppal::pwm::{Channel, Polarity, Pwm};
+use std::fs;
+use std::time::Duration;
+
+
+/// The backend driving the pins through rppal, which maps the GPIO registers of the Raspberry Pi.
+pub struct RppalBackend;
+
+
+impl Backend for RppalBackend {
+    fn name(&self) -> &'static str {
+        "rppal"
+    }
+
+    fn input(&self, pin_num: u8, bias: Bias) -> Result<Box<dyn InputLine>> {
+        let pin = Gpio::new()?.get(pin_num)?;
+        Ok(Box::new(match bias {
+            Bias::PullUp => pin.into_input_pullup(),
+            Bias::PullDown => pin.into_input_pulldown(),
+            Bias::Off => pin.into_input(),
+        }))
+    }
+
+    fn output(&self, pin_num: u8, level: Level) -> Result<Box<dyn OutputLine>> {
+        let pin = Gpio::new()?.get(pin_num)?;
+        Ok(Box::new(match level {
+            Level::High => pin.into_output_high(),
+            Level::Low => pin.into_output_low(),
+        }))
+    }
+
+    fn pwm(&self, channel_num: u8, frequency: f64, duty_cycle: f64, polarity: Polarity, enabled: bool) -> Result<Box<dyn PwmLine>> {
+        let channel = match channel_num {
+            0 => Channel::Pwm0,
+            1 => Channel::Pwm1,
+            _ => return Err(Error::new("Invalid PWM channel number")),
+        };
+        Ok(Box::new(Pwm::with_frequency(channel, frequency, duty_cycle, polarity, enabled)?))
+    }
+
+    fn read_levels(&self) -> Result<u32> {
+        Ok(pinctrl::read_levels()?)
+    }
+
+    fn claimed_by(&self, pin_num: u8) -> Option<String> {
+        // rppal drives the registers directly, so nothing stops it from taking the pins of an enabled bus
+        if let Ok(function) = pinctrl::pin_function(pin_num) {
+            let peripheral = match Peripheral::of(&function) {
+                Peripheral::I2c => "I2C",
+                Peripheral::Spi => "SPI",
+                Peripheral::Uart => "UART",
+                Peripheral::Pwm | Peripheral::Other => "",
+            };
+            if !peripheral.is_empty() {
+                return Some(format!("routed to {} ({})", function, peripheral));
+            }
+        }
+        header_chip()?.line_info(pin_num as u32).ok()?.claim()
+    }
+}
+
+
+/// Opens the GPIO chip of the header, found by the label of its driver. The RP1 of the Pi 5 is preferred, since older
+/// kernels also expose the GPIO pins of the SoC itself.
+fn header_chip() -> Option<Chip> {
+    let mut chips = fs::read_dir("/dev").ok()?
+                                        .filter_map(|entry| entry.ok())
+                                        .filter(|entry| entry.file_name().to_string_lossy().starts_with("gpiochip"))
+                                        .filter_map(|entry| {
+                                            let chip = Chip::open(entry.path().to_str()?).ok()?;
+                                            let label = chip.info().ok()?.label;
+                                            Some((label, chip))
+                                        })
+                                        .filter(|(label, _)| label.starts_with("pinctrl-"))
+                                        .collect::<Vec<_>>();
+    chips.sort_by_key(|(label, _)| label != "pinctrl-rp1");
+    chips.into_iter().next().map(|(_, chip)| chip)
+}
+
+
+impl InputLine for InputPin {
+    fn read(&self) -> Level {
+        InputPin::read(self)
+    }
+
+    fn set_bias(&mut self, bias: Bias) -> Result<()> {
+        // rppal only sets the pull resistor when taking the pin, it is changed through the registers afterwards
+        Ok(pinctrl::set_bias(self.pin(), bias)?)
+    }
+
+    fn set_interrupt(&mut self, trigger: Trigger, debounce: Option<Duration>) -> Result<()> {
+        Ok(InputPin::set_interrupt(self, trigger, debounce)?)
+    }
+
+    fn clear_interrupt(&mut self) -> Result<()> {
+        Ok(InputPin::clear_interrupt(self)?)
+    }
+
+    fn poll_interrupt(&mut self, reset: bool, timeout: Option<Duration>) -> Result<Option<Event>> {
+        Ok(InputPin::poll_interrupt(self, reset, timeout)?)
+    }
+
+    fn set_async_interrupt(&mut self, trigger: Trigger, debounce: Option<Duration>, callback: InterruptCallback) -> Result<()> {
+        Ok(InputPin::set_async_interrupt(self, trigger, debounce, callback)?)
+    }
+
+    fn clear_async_interrupt(&mut self) -> Result<()> {
+        Ok(InputPin::clear_async_interrupt(self)?)
+    }
+
+    fn set_reset_on_drop(&mut self, reset_on_drop: bool) {
+        InputPin::set_reset_on_drop(self, reset_on_drop)
+    }
+}
+
+
+impl OutputLine for OutputPin {
+    fn write(&mut self, level: Level) {
+        OutputPin::write(self, level)
+    }
+
+    fn is_set_high(&self) -> bool {
+        OutputPin::is_set_high(self)
+    }
+
+    fn set_pwm_frequency(&mut self, frequency: f64, duty_cycle: f64) -> Result<()> {
+        Ok(OutputPin::set_pwm_frequency(self, frequency, duty_cycle)?)
+    }
+
+    fn clear_pwm(&mut self) -> Result<()> {
+        Ok(OutputPin::clear_pwm(self)?)
+    }
+
+    fn set_output_enabled(&mut self, enabled: bool) -> Result<()> {
+        // rppal has no way of floating an output, so the output enable is switched through the registers
+        Ok(pinctrl::set_output_enabled(self.pin(), enabled)?)
+    }
+
+    fn set_reset_on_drop(&mut self, reset_on_drop: bool) {
+        OutputPin::set_reset_on_drop(self, reset_on_drop)
+    }
+}
+
+
+impl PwmLine for Pwm {
+    fn period(&self) -> Result<Duration> {
+        Ok(Pwm::period(self)?)
+    }
+
+    fn set_period(&self, period: Duration) -> Result<()> {
+        Ok(Pwm::set_period(self, period)?)
+    }
+
+    fn pulse_width(&self) -> Result<Duration> {
+        Ok(Pwm::pulse_width(self)?)
+    }
+
+    fn set_pulse_width(&self, pulse_width: Duration) -> Result<()> {
+        Ok(Pwm::set_pulse_width(self, pulse_width)?)
+    }
+
+    fn frequency(&self) -> Result<f64> {
+        Ok(Pwm::frequency(self)?)
+    }
+
+    fn set_frequency(&self, frequency: f64, duty_cycle: f64) -> Result<()> {
+        Ok(Pwm::set_frequency(self, frequency, duty_cycle)?)
+    }
+
+    fn duty_cycle(&self) -> Result<f64> {
+        Ok(Pwm::duty_cycle(self)?)
+    }
+
+    fn set_duty_cycle(&self, duty_cycle: f64) -> Result<()> {
+        Ok(Pwm::set_duty_cycle(self, duty_cycle)?)
+    }
+
+    fn set_polarity(&self, polarity: Polarity) -> Result<()> {
+        Ok(Pwm::set_polarity(self, polarity)?)
+    }
+
+    fn is_enabled(&self) -> Result<bool> {
+        Ok(Pwm::is_enabled(self)?)
+    }
+
+    fn enable(&self) -> Result<()> {
+        Ok(Pwm::enable(self)?)
+    }
+
+    fn disable(&self) -> Result<()> {
+        Ok(Pwm::disable(self)?)
+    }
+}
@@ -0,0 +1,431 @@
+use crate::gpio_module::GPIOManager;
+use crate::i2c_module::{with_buses, I2CBuses, I2CManager};
+use crate::{InternPullResistorState, LogicLevel, TriggerEdge};
+use pyo3::prelude::*;
+use pyo3::types::{PyCFunction, PyDateTime, PyDict, PyTuple};
+use pyo3::{pyclass, pymethods, PyErr, PyObject, PyResult, Python};
+use std::sync::{Arc, Mutex};
+
+
+// Register addresses shared by the DS1307 and the DS3231
+const REG_TIME: u8 = 0x00;
+// DS3231 registers
+const REG_ALARM1: u8 = 0x07;
+const REG_ALARM2: u8 = 0x0B;
+const REG_DS3231_CONTROL: u8 = 0x0E;
+const REG_DS3231_STATUS: u8 = 0x0F;
+// DS1307 registers
+const REG_DS1307_CONTROL: u8 = 0x07;
+
+const ALARM_MASK: u8 = 0x80;
+const DAY_IS_WEEKDAY: u8 = 0x40;
+const CONTROL_INTCN: u8 = 0x04;
+const STATUS_OSF: u8 = 0x80;
+
+
+fn bcd_to_dec(value: u8) -> u8 {
+    (value >> 4) * 10 + (value & 0x0F)
+}
+
+
+fn dec_to_bcd(value: u8) -> u8 {
+    ((value / 10) << 4) | (value % 10)
+}
+
+
+/// Checks that an alarm field is in its range, dec_to_bcd would encode garbage otherwise.
+fn check_alarm_field(name: &str, value: Option<u8>, min: u8, max: u8) -> PyResult<()> {
+    match value {
+        Some(value) if !(min..=max).contains(&value) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "The {} must be between {} and {}, The value {} does not meet this condition", name, min, max, value))),
+        _ => Ok(()),
+    }
+}
+
+
+/// Decodes an hour register, handling both the 12 hour and the 24 hour mode.
+fn decode_hour(value: u8) -> u8 {
+    if value & 0x40 != 0 {
+        let hour = bcd_to_dec(value & 0x1F) % 12;
+        if value & 0x20 != 0 { hour + 12 } else { hour }
+    } else {
+        bcd_to_dec(value & 0x3F)
+    }
+}
+
+
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// Enum representing the supported real time clock models.
+pub enum RTCModel {
+    DS1307,
+    DS3231,
+}
+
+
+#[pyclass]
+/// RTC provides methods to read and set the time, alarms and square wave output of DS1307 and DS3231 real time clocks on
+/// the bus opened by the I2CManager.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// i2c_manager = gpio_manager.I2CManager()
+/// i2c_manager.open(bus=1)
+/// rtc = gpio_manager.RTC(gpio_manager.RTCModel.DS3231)
+/// rtc.set_time(datetime.datetime.now())
+/// print(rtc.get_time())
+/// ```
+#[allow(clippy::upper_case_acronyms)]
+pub struct RTC {
+    i2c: Arc<Mutex<I2CBuses>>,
+    bus: Option<u8>,
+    model: RTCModel,
+    addr: u16,
+}
+
+
+impl RTC {
+    fn read_registers(&self, reg: u8, length: usize) -> PyResult<Vec<u8>> {
+        with_buses(&self.i2c, |i2c_lock| {
+            let retry = i2c_lock.retry_policy();
+            let i2c = i2c_lock.select(self.bus, self.addr)?;
+            let mut buf = vec![0u8; length];
+            retry.run(|| i2c.write_read(&[reg], &mut buf))
+                 .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to read data: {:?}", e)))?;
+            Ok(buf)
+        })
+    }
+
+    fn write_registers(&self, reg: u8, data: &[u8]) -> PyResult<()> {
+        with_buses(&self.i2c, |i2c_lock| {
+            let retry = i2c_lock.retry_policy();
+            let i2c = i2c_lock.select(self.bus, self.addr)?;
+            let mut buf = vec![reg];
+            buf.extend_from_slice(data);
+            retry.run(|| i2c.write(&buf))
+                 .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to write data: {:?}", e)))?;
+            Ok(())
+        })
+    }
+
+    fn update_register(&self, reg: u8, clear: u8, set: u8) -> PyResult<()> {
+        let value = self.read_registers(reg, 1)?[0];
+        self.write_registers(reg, &[(value & !clear) | set])
+    }
+
+    fn require_ds3231(&self) -> PyResult<()> {
+        if self.model != RTCModel::DS3231 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Alarms are only supported by the DS3231"));
+        }
+        Ok(())
+    }
+
+    fn alarm_bit(alarm_num: u8) -> PyResult<u8> {
+        match alarm_num {
+            1 => Ok(0x01),
+            2 => Ok(0x02),
+            _ => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid alarm number, the DS3231 has alarms 1 and 2")),
+        }
+    }
+
+    fn clear_alarm_flag(&self, alarm_num: u8) -> PyResult<()> {
+        let bit = RTC::alarm_bit(alarm_num)?;
+        self.update_register(REG_DS3231_STATUS, bit, 0)
+    }
+}
+
+
+#[pymethods]
+impl RTC {
+    #[new]
+    /// Initializes a new RTC instance on the bus opened by the I2CManager.
+    ///
+    /// Parameters:
+    /// - `model` (RTCModel): The real time clock model.
+    /// - `addr` (int): The I2C address of the real time clock (default is 0x68).
+    /// - `bus` (int): The I2C bus the real time clock is connected to (default is the first bus opened).
+    ///
+    /// Example usage:
+    /// ```python
+    /// rtc = gpio_manager.RTC(gpio_manager.RTCModel.DS1307)
+    /// ```
+    #[pyo3(signature = (model, addr = 0x68, bus = None))]
+    fn new(model: RTCModel, addr: u16, bus: Option<u8>) -> PyResult<Self> {
+        let i2c_manager = I2CManager::new_rust_reference();
+        let i2c = i2c_manager.lock().unwrap().get_buses();
+        Ok(Self {
+            i2c,
+            bus,
+            model,
+            addr,
+        })
+    }
+
+    /// Reads the current time from the real time clock.
+    ///
+    /// Returns:
+    /// - `datetime.datetime`: The time stored in the clock (without timezone information).
+    ///
+    /// Example usage:
+    /// ```python
+    /// now = rtc.get_time()
+    /// ```
+    fn get_time<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDateTime>> {
+        let regs = self.read_registers(REG_TIME, 7)?;
+        let second = bcd_to_dec(regs[0] & 0x7F);
+        let minute = bcd_to_dec(regs[1] & 0x7F);
+        let hour = decode_hour(regs[2]);
+        let day = bcd_to_dec(regs[4] & 0x3F);
+        let month = bcd_to_dec(regs[5] & 0x1F);
+        let mut year = 2000 + bcd_to_dec(regs[6]) as i32;
+        if self.model == RTCModel::DS3231 && regs[5] & 0x80 != 0 {
+            year += 100;
+        }
+        PyDateTime::new(py, year, month, day, hour, minute, second, 0, None)
+    }
+
+    /// Sets the time of the real time clock and starts the oscillator.
+    ///
+    /// Parameters:
+    /// - `time` (datetime.datetime): The time to set, the current system time is used if it is None.
+    ///
+    /// Example usage:
+    /// ```python
+    /// rtc.set_time(datetime.datetime(2024, 1, 1, 12, 0, 0))
+    /// ```
+    #[pyo3(signature = (time = None))]
+    fn set_time(&self, py: Python, time: Option<&Bound<'_, PyDateTime>>) -> PyResult<()> {
+        let time = match time {
+            Some(time) => time.clone(),
+            None => py.import("datetime")?.getattr("datetime")?.call_method0("now")?.downcast_into::<PyDateTime>()?,
+        };
+        let year: i32 = time.getattr("year")?.extract()?;
+        if !(2000..2200).contains(&year) || (self.model == RTCModel::DS1307 && year >= 2100) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The year {} is outside of the range supported by the clock", year)));
+        }
+        let month: u8 = time.getattr("month")?.extract()?;
+        let century = if year >= 2100 { 0x80 } else { 0x00 };
+        let regs = [
+            dec_to_bcd(time.getattr("second")?.extract()?), // Clears the clock halt bit on the DS1307
+            dec_to_bcd(time.getattr("minute")?.extract()?),
+            dec_to_bcd(time.getattr("hour")?.extract()?), // Always stored in 24 hour mode
+            time.call_method0("isoweekday")?.extract()?,
+            dec_to_bcd(time.getattr("day")?.extract()?),
+            dec_to_bcd(month) | century,
+            dec_to_bcd((year % 100) as u8),
+        ];
+        self.write_registers(REG_TIME, &regs)?;
+        if self.model == RTCModel::DS3231 {
+            self.update_register(REG_DS3231_STATUS, STATUS_OSF, 0)?;
+        }
+        Ok(())
+    }
+
+    /// Checks if the clock stopped since the time was last set (for example because the backup battery is empty).
+    ///
+    /// Returns:
+    /// - `bool`: True if the time stored in the clock is not valid.
+    ///
+    /// Example usage:
+    /// ```python
+    /// if rtc.lost_power():
+    ///     rtc.set_time()
+    /// ```
+    fn lost_power(&self) -> PyResult<bool> {
+        match self.model {
+            RTCModel::DS1307 => Ok(self.read_registers(REG_TIME, 1)?[0] & 0x80 != 0),
+            RTCModel::DS3231 => Ok(self.read_registers(REG_DS3231_STATUS, 1)?[0] & STATUS_OSF != 0),
+        }
+    }
+
+    /// Sets an alarm on the DS3231 and enables its interrupt on the INT/SQW pin. Fields that are None match any value,
+    /// once a field is set all smaller fields must be set as well (for example setting the hour requires the minute).
+    ///
+    /// Parameters:
+    /// - `alarm_num` (int): The alarm to set (1 or 2, alarm 2 has no seconds).
+    /// - `second` (int): The second the alarm triggers at (0 to 59).
+    /// - `minute` (int): The minute the alarm triggers at (0 to 59).
+    /// - `hour` (int): The hour the alarm triggers at (0 to 23).
+    /// - `day` (int): The day of the month the alarm triggers at (1 to 31).
+    /// - `weekday` (int): The day of the week the alarm triggers at (1 = Monday, 7 = Sunday), can't be used with `day`.
+    ///
+    /// Example usage:
+    /// ```python
+    /// rtc.set_alarm(1, second=0, minute=30, hour=7)
+    /// ```
+    #[pyo3(signature = (alarm_num, second = None, minute = None, hour = None, day = None, weekday = None))]
+    fn set_alarm(&self, alarm_num: u8, second: Option<u8>, minute: Option<u8>, hour: Option<u8>, day: Option<u8>, weekday: Option<u8>) -> PyResult<()> {
+        self.require_ds3231()?;
+        let bit = RTC::alarm_bit(alarm_num)?;
+        if day.is_some() && weekday.is_some() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Only one of day and weekday can be set"));
+        }
+        if alarm_num == 2 && second.is_some() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Alarm 2 does not support seconds"));
+        }
+        check_alarm_field("second", second, 0, 59)?;
+        check_alarm_field("minute", minute, 0, 59)?;
+        check_alarm_field("hour", hour, 0, 23)?;
+        check_alarm_field("day", day, 1, 31)?;
+        check_alarm_field("weekday", weekday, 1, 7)?;
+
+        let day_reg = match (day, weekday) {
+            (Some(day), None) => Some(dec_to_bcd(day)),
+            (None, Some(weekday)) => Some(dec_to_bcd(weekday) | DAY_IS_WEEKDAY),
+            _ => None,
+        };
+        let mut fields = vec![minute.map(dec_to_bcd), hour.map(dec_to_bcd), day_reg];
+        if alarm_num == 1 {
+            fields.insert(0, second.map(dec_to_bcd));
+        }
+        // The hardware can only match a contiguous set of fields starting at the smallest one
+        if let Some(first_set) = fields.iter().position(|field| field.is_some()) {
+            if first_set != 0 || fields.iter().skip_while(|field| field.is_some()).any(|field| field.is_some()) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("Alarm fields must be set from the smallest unit upwards without gaps"));
+            }
+        }
+        let regs: Vec<u8> = fields.iter().map(|field| field.unwrap_or(ALARM_MASK)).collect();
+
+        let reg = if alarm_num == 1 { REG_ALARM1 } else { REG_ALARM2 };
+        self.write_registers(reg, &regs)?;
+        self.clear_alarm_flag(alarm_num)?;
+        self.update_register(REG_DS3231_CONTROL, 0, CONTROL_INTCN | bit)
+    }
+
+    /// Disables the interrupt of an alarm on the DS3231.
+    ///
+    /// Parameters:
+    /// - `alarm_num` (int): The alarm to disable (1 or 2).
+    ///
+    /// Example usage:
+    /// ```python
+    /// rtc.disable_alarm(1)
+    /// ```
+    #[pyo3(signature = (alarm_num))]
+    fn disable_alarm(&self, alarm_num: u8) -> PyResult<()> {
+        self.require_ds3231()?;
+        let bit = RTC::alarm_bit(alarm_num)?;
+        self.update_register(REG_DS3231_CONTROL, bit, 0)
+    }
+
+    /// Checks if an alarm on the DS3231 has triggered.
+    ///
+    /// Parameters:
+    /// - `alarm_num` (int): The alarm to check (1 or 2).
+    ///
+    /// Returns:
+    /// - `bool`: True if the alarm has triggered since its flag was last cleared.
+    ///
+    /// Example usage:
+    /// ```python
+    /// if rtc.alarm_triggered(1):
+    ///     rtc.clear_alarm(1)
+    /// ```
+    #[pyo3(signature = (alarm_num))]
+    fn alarm_triggered(&self, alarm_num: u8) -> PyResult<bool> {
+        self.require_ds3231()?;
+        let bit = RTC::alarm_bit(alarm_num)?;
+        Ok(self.read_registers(REG_DS3231_STATUS, 1)?[0] & bit != 0)
+    }
+
+    /// Clears the triggered flag of an alarm on the DS3231, releasing the INT/SQW pin.
+    ///
+    /// Parameters:
+    /// - `alarm_num` (int): The alarm to clear (1 or 2).
+    ///
+    /// Example usage:
+    /// ```python
+    /// rtc.clear_alarm(1)
+    /// ```
+    #[pyo3(signature = (alarm_num))]
+    fn clear_alarm(&self, alarm_num: u8) -> PyResult<()> {
+        self.require_ds3231()?;
+        self.clear_alarm_flag(alarm_num)
+    }
+
+    /// Enables or disables the square wave output. On the DS3231 enabling the square wave disables the alarm
+    /// interrupts since both share the INT/SQW pin.
+    ///
+    /// Parameters:
+    /// - `frequency_hz` (int): The frequency of the square wave, None disables the output. The DS1307 supports 1, 4096,
+    ///   8192 and 32768 Hz, the DS3231 supports 1, 1024, 4096 and 8192 Hz.
+    ///
+    /// Example usage:
+    /// ```python
+    /// rtc.set_square_wave(1)
+    /// ```
+    #[pyo3(signature = (frequency_hz = None))]
+    fn set_square_wave(&self, frequency_hz: Option<u32>) -> PyResult<()> {
+        let invalid = |frequency: u32| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The frequency {} Hz is not supported by the {:?}",
+                                                                                                 frequency, self.model));
+        match self.model {
+            RTCModel::DS1307 => {
+                let control = match frequency_hz {
+                    None => 0x00,
+                    Some(1) => 0x10,
+                    Some(4096) => 0x11,
+                    Some(8192) => 0x12,
+                    Some(32768) => 0x13,
+                    Some(frequency) => return Err(invalid(frequency)),
+                };
+                self.write_registers(REG_DS1307_CONTROL, &[control])
+            }
+            RTCModel::DS3231 => {
+                let rate = match frequency_hz {
+                    None => return self.update_register(REG_DS3231_CONTROL, 0, CONTROL_INTCN),
+                    Some(1) => 0x00,
+                    Some(1024) => 0x08,
+                    Some(4096) => 0x10,
+                    Some(8192) => 0x18,
+                    Some(frequency) => return Err(invalid(frequency)),
+                };
+                self.update_register(REG_DS3231_CONTROL, CONTROL_INTCN | 0x18, rate)
+            }
+        }
+    }
+
+    /// Wires the INT/SQW pin of the clock to a GPIO pin and assigns a callback that is invoked when an alarm
+    /// triggers. The pin is set up as an input with a pull-up since the INT/SQW output is open drain and active low.
+    ///
+    /// Parameters:
+    /// - `pin_num` (int): The GPIO pin connected to the INT/SQW pin.
+    /// - `callback` (function): The callback function to be invoked when an alarm triggers.
+    /// - `args` (tuple): The arguments to pass to the callback function.
+    /// - `clear_alarms` (bool): Whether to clear the alarm flags before invoking the callback so the pin is released
+    ///   for the next alarm (default is True).
+    ///
+    /// Example usage:
+    /// ```python
+    /// rtc.assign_alarm_callback(17, alarm_callback)
+    /// ```
+    #[pyo3(signature = (pin_num, callback, args = None, clear_alarms = true))]
+    fn assign_alarm_callback(&self, py: Python, pin_num: u8, callback: PyObject, args: Option<&Bound<'_, PyTuple>>, clear_alarms: bool) -> PyResult<()> {
+        self.require_ds3231()?;
+        if !callback.bind(py).is_callable() {
+            return Err(PyErr::new::<pyo3::exceptions::PyTypeError, _>("Object is not callable"));
+        }
+        let gpio_manager = GPIOManager::new_rust_reference();
+        let manager = gpio_manager.get_manager();
+        let is_input = gpio_manager.is_input_pin(pin_num, &manager.lock().unwrap());
+        if !is_input {
+            gpio_manager.add_input_pin(pin_num, Some(InternPullResistorState::PULLUP.into()), Some(LogicLevel::HIGH.into()), false)?;
+        }
+
+        let rtc = RTC {
+            i2c: Arc::clone(&self.i2c),
+            bus: self.bus,
+            model: self.model,
+            addr: self.addr,
+        };
+        let dispatch = PyCFunction::new_closure(py, None, None, move |call_args: &Bound<'_, PyTuple>, _kwargs: Option<&Bound<'_, PyDict>>| -> PyResult<()> {
+            if clear_alarms {
+                rtc.clear_alarm_flag(1)?;
+                rtc.clear_alarm_flag(2)?;
+            }
+            callback.call1(call_args.py(), call_args)?;
+            Ok(())
+        })?;
+        gpio_manager.assign_callback(py, pin_num, dispatch.into_any().unbind(), TriggerEdge::FALLING.into(), Some(0f64), args, false, false, "hardware", None, false, 0, false)
+    }
+}
@@ -0,0 +1,183 @@
+use crate::error::GpioError;
+use crate::gpio_module::GPIOManager;
+use crate::log_module;
+use crate::timing::{self, TimedCondvar};
+use crate::PinState;
+use once_cell::sync::Lazy;
+use pyo3::{pyclass, pymethods};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+
+/// A pin change waiting for its deadline.
+struct Action {
+    pin_num: u8,
+    state: PinState,
+}
+
+
+struct SchedulerState {
+    // Deadlines of the actions, cancelled actions are skipped when their deadline comes up
+    queue: BinaryHeap<Reverse<(Instant, u64)>>,
+    actions: HashMap<u64, Action>,
+    next_id: u64,
+}
+
+
+/// Runs scheduled pin changes from a single timer thread, so no Python thread has to stay alive until a change is due.
+pub struct PinScheduler {
+    state: Mutex<SchedulerState>,
+    changed: TimedCondvar,
+}
+
+
+pub static PIN_SCHEDULER: Lazy<Arc<PinScheduler>> = Lazy::new(|| {
+    let scheduler = Arc::new(PinScheduler {
+        state: Mutex::new(SchedulerState {
+            queue: BinaryHeap::new(),
+            actions: HashMap::new(),
+            next_id: 0,
+        }),
+        changed: TimedCondvar::default(),
+    });
+    let runner = Arc::clone(&scheduler);
+    timing::spawn(move || runner.run());
+    scheduler
+});
+
+
+/// Converts a finite time of the monotonic clock used by Python's time.monotonic() to an Instant, following the virtual
+/// clock while it runs. A time in the past is due right away, a time too far in the future to be an Instant is rejected.
+pub fn monotonic_to_instant(monotonic_s: f64) -> Result<Instant, GpioError> {
+    let now = timing::now();
+    let from_now_s = monotonic_s - timing::monotonic_ns() as f64 / 1_000_000_000f64;
+    if from_now_s >= 0f64 {
+        Duration::try_from_secs_f64(from_now_s).ok()
+                                               .and_then(|delay| now.checked_add(delay))
+                                               .ok_or_else(|| GpioError::InvalidArgument(format!(
+                                                   "The time is too far in the future, The value {} does not meet this condition", monotonic_s)))
+    } else {
+        Ok(Duration::try_from_secs_f64(-from_now_s).ok().and_then(|ago| now.checked_sub(ago)).unwrap_or(now))
+    }
+}
+
+
+impl PinScheduler {
+    /// Schedules a pin change at the deadline, returning the id of the action.
+    pub fn schedule(&self, pin_num: u8, state: PinState, deadline: Instant) -> u64 {
+        let mut scheduler = self.state.lock().unwrap();
+        let id = scheduler.next_id;
+        scheduler.next_id += 1;
+        scheduler.actions.insert(id, Action { pin_num, state });
+        scheduler.queue.push(Reverse((deadline, id)));
+        self.changed.notify();
+        id
+    }
+
+    /// Cancels an action, returning whether it was still pending.
+    pub fn cancel(&self, id: u64) -> bool {
+        self.state.lock().unwrap().actions.remove(&id).is_some()
+    }
+
+    /// Cancels the pending actions of a pin, or of all pins for None, returning the number of actions cancelled.
+    pub fn cancel_pin(&self, pin_num: Option<u8>) -> usize {
+        let mut scheduler = self.state.lock().unwrap();
+        let count = scheduler.actions.len();
+        scheduler.actions.retain(|_, action| pin_num.is_some_and(|pin_num| action.pin_num != pin_num));
+        count - scheduler.actions.len()
+    }
+
+    pub fn is_pending(&self, id: u64) -> bool {
+        self.state.lock().unwrap().actions.contains_key(&id)
+    }
+
+    fn run(&self) {
+        let gpio_manager = GPIOManager::new_rust_reference();
+        loop {
+            let mut scheduler = self.state.lock().unwrap();
+            let due = loop {
+                match scheduler.queue.peek() {
+                    None => scheduler = self.changed.wait(scheduler, None),
+                    Some(Reverse((deadline, _))) => {
+                        let deadline = *deadline;
+                        if deadline <= timing::now() {
+                            let Reverse((_, id)) = scheduler.queue.pop().unwrap();
+                            break scheduler.actions.remove(&id);
+                        }
+                        scheduler = self.changed.wait(scheduler, Some(deadline));
+                    }
+                }
+            };
+            drop(scheduler);
+
+            if let Some(action) = due {
+                if let Err(e) = gpio_manager.set_output_pin(action.pin_num, action.state.into()) {
+                    log_module::warning(&format!("The scheduled change of GPIO {} failed: {}", action.pin_num, e));
+                }
+            }
+        }
+    }
+}
+
+
+#[pyclass]
+/// ScheduledAction is the handle of a pin change scheduled with GPIOManager.schedule or GPIOManager.schedule_in, it
+/// cancels the change.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// action = manager.schedule_in(17, gpio_manager.PinState.LOW, 60_000)
+/// action.cancel()
+/// ```
+pub struct ScheduledAction {
+    id: u64,
+    pin_num: u8,
+    state: PinState,
+}
+
+
+impl ScheduledAction {
+    pub fn new(id: u64, pin_num: u8, state: PinState) -> Self {
+        Self { id, pin_num, state }
+    }
+}
+
+
+#[pymethods]
+impl ScheduledAction {
+    /// Cancels the pin change.
+    ///
+    /// Returns:
+    /// - `bool`: Whether the change was still pending.
+    ///
+    /// Example usage:
+    /// ```python
+    /// action.cancel()
+    /// ```
+    fn cancel(&self) -> bool {
+        PIN_SCHEDULER.cancel(self.id)
+    }
+
+    /// Returns whether the pin change is still waiting for its time.
+    fn is_pending(&self) -> bool {
+        PIN_SCHEDULER.is_pending(self.id)
+    }
+
+    /// Returns the GPIO pin of the change.
+    fn get_pin(&self) -> u8 {
+        self.pin_num
+    }
+
+    /// Returns the state the pin is set to.
+    fn get_state(&self) -> PinState {
+        self.state
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ScheduledAction(pin={}, state={:?}, pending={})", self.pin_num, self.state, self.is_pending())
+    }
+}
+
@@ -0,0 +1,373 @@
+use crate::log_module;
+use crate::pwm_output_module::PWM;
+use crate::pwm_ramp_module::Easing;
+use crate::{ms_to_duration_checked, release_gil};
+use crate::timing::wait_until_with_margin;
+use crate::{EnumArg, LogicLevel};
+use pyo3::prelude::*;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+
+// Time between two position updates of a move, one period of the usual 50 Hz servo signal
+const SERVO_TICK: Duration = Duration::from_millis(20);
+
+
+/// A servo of a group and the pulse widths of the ends of its range.
+struct Servo {
+    pwm: PWM,
+    min_pulse_us: f64,
+    max_pulse_us: f64,
+}
+
+
+/// The range of angles shared by the servos of a group.
+#[derive(Clone, Copy)]
+struct AngleRange {
+    min: f64,
+    max: f64,
+}
+
+
+impl AngleRange {
+    fn check(&self, angle: f64) -> PyResult<()> {
+        if !(self.min..=self.max).contains(&angle) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Angle must be between {} and {}, The value {} does not meet this condition",
+                                                                               self.min, self.max, angle)));
+        }
+        Ok(())
+    }
+}
+
+
+struct MotionState {
+    angles: Vec<f64>,
+    // Generation of the latest move, a move stops once a newer one replaces it
+    generation: u64,
+    moving: bool,
+}
+
+
+/// The servos of a group and where they are, shared with the thread of the running move.
+struct Motion {
+    servos: Vec<Servo>,
+    range: AngleRange,
+    state: Mutex<MotionState>,
+    finished: Condvar,
+}
+
+
+impl Motion {
+    /// Writes the pulse width of every servo for the angles.
+    fn write(&self, angles: &[f64]) -> PyResult<()> {
+        for (servo, angle) in self.servos.iter().zip(angles) {
+            let fraction = (angle - self.range.min) / (self.range.max - self.range.min);
+            let pulse_us = servo.min_pulse_us + (servo.max_pulse_us - servo.min_pulse_us) * fraction;
+            servo.pwm.set_pulse_width(pulse_us / 1000f64)?;
+        }
+        Ok(())
+    }
+
+    /// Starts a new generation, stopping the running move, and returns it with the current angles. `moving` tells
+    /// whether a new move starts with it, otherwise the threads waiting for the move are woken up.
+    fn next_generation(&self, moving: bool) -> (u64, Vec<f64>) {
+        let mut state = self.state.lock().unwrap();
+        state.generation += 1;
+        state.moving = moving;
+        self.finished.notify_all();
+        (state.generation, state.angles.clone())
+    }
+
+    /// Moves every servo from `from` to `to` over the duration, updating all of them on the same ticks. The callback
+    /// is called once the move completes, not when it is replaced or stopped.
+    fn run(&self, generation: u64, from: Vec<f64>, to: Vec<f64>, duration: Duration, easing: Easing, callback: Option<PyObject>) {
+        let start = Instant::now();
+        let mut tick = start;
+        loop {
+            let t = if duration.is_zero() { 1f64 } else { (start.elapsed().as_secs_f64() / duration.as_secs_f64()).min(1f64) };
+            let eased = easing.apply(t);
+            let angles: Vec<f64> = from.iter().zip(&to).map(|(from, to)| from + (to - from) * eased).collect();
+            {
+                // The servos are written under the lock, so a replaced move can't write after the next one started
+                let mut state = self.state.lock().unwrap();
+                if state.generation != generation {
+                    return;
+                }
+                if let Err(e) = self.write(&angles) {
+                    log_module::warning(&format!("The servo group stopped moving: {}", e));
+                    state.moving = false;
+                    self.finished.notify_all();
+                    return;
+                }
+                state.angles = angles;
+                if t >= 1f64 {
+                    state.moving = false;
+                    self.finished.notify_all();
+                    break;
+                }
+            }
+            tick += SERVO_TICK;
+            wait_until_with_margin(tick, Duration::ZERO);
+        }
+        if let Some(callback) = callback {
+            Python::with_gil(|py| {
+                if let Err(e) = callback.call0(py) {
+                    log_module::exception("Exception in the completion callback of a servo group move", &e);
+                }
+            });
+        }
+    }
+}
+
+
+#[pyclass]
+/// ServoGroup moves several servos together, for robot arms and other mechanisms that need coordinated motion. A move
+/// brings every servo to its target angle over the same duration, one Rust thread updates all the servos on the same
+/// ticks so they start and arrive together, then calls the completion callback.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// arm = gpio_manager.ServoGroup([12, 13, 18])
+/// arm.move_to([45, 120, 90], duration_ms=1500, easing=gpio_manager.Easing.EASE_IN_OUT, callback=lambda: print("done"))
+/// arm.wait()
+/// ```
+pub struct ServoGroup {
+    pins: Vec<u8>,
+    motion: Arc<Motion>,
+}
+
+
+impl ServoGroup {
+    /// Resolves the targets of a move, None keeps a servo where it is.
+    fn targets(&self, angles: Vec<Option<f64>>, current: &[f64]) -> PyResult<Vec<f64>> {
+        if angles.len() != self.pins.len() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The group has {} servos, {} angles were given",
+                                                                               self.pins.len(), angles.len())));
+        }
+        angles.into_iter()
+              .zip(current)
+              .map(|(angle, current)| match angle {
+                  Some(angle) => self.motion.range.check(angle).map(|_| angle),
+                  None => Ok(*current),
+              })
+              .collect()
+    }
+}
+
+
+impl Drop for ServoGroup {
+    fn drop(&mut self) {
+        self.motion.next_generation(false);
+    }
+}
+
+
+#[pymethods]
+impl ServoGroup {
+    #[new]
+    /// Sets up the servos of the group, each on a free pin through its hardware PWM channel when it has one. The
+    /// signals start right away at the initial angle.
+    ///
+    /// Parameters:
+    /// - `pins` (list[int]): The GPIO pins of the servos, the angles of the moves are given in this order.
+    /// - `min_pulse_us` (float | list[float]): The pulse width at the minimum angle in microseconds, for all servos or
+    ///   for each (default is 1000).
+    /// - `max_pulse_us` (float | list[float]): The pulse width at the maximum angle in microseconds, for all servos or
+    ///   for each (default is 2000).
+    /// - `min_angle` (float): The minimum angle in degrees (default is 0).
+    /// - `max_angle` (float): The maximum angle in degrees (default is 180).
+    /// - `initial_angle` (float): The angle the servos start at (default is None, which is the middle of the range).
+    /// - `frequency_hz` (float): The frequency of the servo signals in Hertz (default is 50).
+    ///
+    /// Example usage:
+    /// ```python
+    /// arm = gpio_manager.ServoGroup([12, 13], min_pulse_us=[500, 1000], max_pulse_us=[2500, 2000])
+    /// ```
+    #[pyo3(signature = (pins, min_pulse_us = PulseWidths::All(1000f64), max_pulse_us = PulseWidths::All(2000f64),
+                        min_angle = 0f64, max_angle = 180f64, initial_angle = None, frequency_hz = 50f64))]
+    fn new(pins: Vec<u8>, min_pulse_us: PulseWidths, max_pulse_us: PulseWidths, min_angle: f64, max_angle: f64,
+           initial_angle: Option<f64>, frequency_hz: f64) -> PyResult<Self> {
+        if pins.is_empty() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("A servo group needs at least one pin"));
+        }
+        if let Some(pin) = pins.iter().find(|pin| pins.iter().filter(|other| other == pin).count() > 1) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("GPIO {} is given more than once", pin)));
+        }
+        if min_angle >= max_angle {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The minimum angle must be less than the maximum angle, {} is not less than {}",
+                                                                               min_angle, max_angle)));
+        }
+        let min_pulse_us = min_pulse_us.resolve(pins.len(), "min_pulse_us")?;
+        let max_pulse_us = max_pulse_us.resolve(pins.len(), "max_pulse_us")?;
+        let range = AngleRange { min: min_angle, max: max_angle };
+        let initial_angle = initial_angle.unwrap_or((min_angle + max_angle) / 2f64);
+        range.check(initial_angle)?;
+
+        let mut servos: Vec<Servo> = Vec::with_capacity(pins.len());
+        for (i, pin) in pins.iter().enumerate() {
+            match PWM::new(*pin, Some(frequency_hz), Some(0f64), None, None, EnumArg(LogicLevel::HIGH)) {
+                Ok(pwm) => servos.push(Servo { pwm, min_pulse_us: min_pulse_us[i], max_pulse_us: max_pulse_us[i] }),
+                Err(e) => {
+                    // The servos already set up are released, so the group can be created again
+                    for servo in &servos {
+                        let _ = servo.pwm.reset();
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        let angles = vec![initial_angle; pins.len()];
+        let motion = Motion {
+            servos,
+            range,
+            state: Mutex::new(MotionState { angles: angles.clone(), generation: 0, moving: false }),
+            finished: Condvar::new(),
+        };
+        motion.write(&angles)?;
+        for servo in &motion.servos {
+            servo.pwm.start()?;
+        }
+        Ok(Self { pins, motion: Arc::new(motion) })
+    }
+
+    /// Moves the servos to their target angles over the duration in the background, replacing the running move. All
+    /// the servos start and arrive together.
+    ///
+    /// Parameters:
+    /// - `angles` (list[float | None]): The target angle of every servo in the order of the pins, None keeps a servo
+    ///   where it is.
+    /// - `duration_ms` (float): The duration of the move in milliseconds (default is 1000).
+    /// - `easing` (Easing): The curve the move follows (default is LINEAR).
+    /// - `callback` (Callable[[], None]): A function called once the servos reached their targets, it isn't called when
+    ///   the move is replaced or stopped (default is None).
+    ///
+    /// Example usage:
+    /// ```python
+    /// arm.move_to([None, 30, 150], duration_ms=800, callback=lambda: print("arrived"))
+    /// ```
+    #[pyo3(signature = (angles, duration_ms = 1000f64, easing = Easing::LINEAR, callback = None))]
+    fn move_to(&self, angles: Vec<Option<f64>>, duration_ms: f64, easing: Easing, callback: Option<PyObject>) -> PyResult<()> {
+        let duration = ms_to_duration_checked("Duration", duration_ms)?;
+        let current = self.motion.state.lock().unwrap().angles.clone();
+        let targets = self.targets(angles, &current)?;
+        let (generation, from) = self.motion.next_generation(true);
+        let motion = Arc::clone(&self.motion);
+        thread::spawn(move || motion.run(generation, from, targets, duration, easing, callback));
+        Ok(())
+    }
+
+    /// Sets the angles of the servos immediately, stopping the running move.
+    ///
+    /// Parameters:
+    /// - `angles` (list[float | None]): The angle of every servo in the order of the pins, None keeps a servo where it
+    ///   is.
+    ///
+    /// Example usage:
+    /// ```python
+    /// arm.set_angles([90, 90, 90])
+    /// ```
+    #[pyo3(signature = (angles))]
+    fn set_angles(&self, angles: Vec<Option<f64>>) -> PyResult<()> {
+        let (_, current) = self.motion.next_generation(false);
+        let targets = self.targets(angles, &current)?;
+        let mut state = self.motion.state.lock().unwrap();
+        self.motion.write(&targets)?;
+        state.angles = targets;
+        Ok(())
+    }
+
+    /// Stops the running move, the servos hold the angles they reached.
+    ///
+    /// Example usage:
+    /// ```python
+    /// arm.stop()
+    /// ```
+    fn stop(&self) {
+        self.motion.next_generation(false);
+    }
+
+    /// Blocks until the running move completes. The GIL is released while waiting.
+    ///
+    /// Parameters:
+    /// - `timeout_ms` (float): The maximum time to wait in milliseconds (default is None, which waits without limit).
+    ///
+    /// Returns:
+    /// - `bool`: Whether no move is running anymore, False when the timeout expired first.
+    ///
+    /// Example usage:
+    /// ```python
+    /// arm.move_to([0, 0, 0], duration_ms=2000)
+    /// arm.wait()
+    /// ```
+    #[pyo3(signature = (timeout_ms = None))]
+    fn wait(&self, timeout_ms: Option<f64>) -> PyResult<bool> {
+        let timeout = timeout_ms.map(|timeout_ms| ms_to_duration_checked("Timeout", timeout_ms.max(0f64))).transpose()?;
+        let motion = Arc::clone(&self.motion);
+        Ok(release_gil(move || {
+            let state = motion.state.lock().unwrap();
+            match timeout {
+                None => !motion.finished.wait_while(state, |state| state.moving).unwrap().moving,
+                Some(timeout) => !motion.finished.wait_timeout_while(state, timeout, |state| state.moving).unwrap().0.moving,
+            }
+        }))
+    }
+
+    /// Stops the group and releases the pins of its servos.
+    ///
+    /// Example usage:
+    /// ```python
+    /// arm.close()
+    /// ```
+    fn close(&self) -> PyResult<()> {
+        self.motion.next_generation(false);
+        for servo in &self.motion.servos {
+            servo.pwm.reset()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the current angle of every servo in the order of the pins.
+    fn get_angles(&self) -> Vec<f64> {
+        self.motion.state.lock().unwrap().angles.clone()
+    }
+
+    /// Returns the GPIO pins of the servos.
+    fn get_pins(&self) -> Vec<u8> {
+        self.pins.clone()
+    }
+
+    /// Returns whether a move is running.
+    fn is_moving(&self) -> bool {
+        self.motion.state.lock().unwrap().moving
+    }
+}
+
+
+/// Pulse widths given for all the servos of a group at once or for each of them.
+#[derive(FromPyObject)]
+pub enum PulseWidths {
+    All(f64),
+    Each(Vec<f64>),
+}
+
+
+impl PulseWidths {
+    fn resolve(self, count: usize, name: &str) -> PyResult<Vec<f64>> {
+        let widths = match self {
+            PulseWidths::All(width) => vec![width; count],
+            PulseWidths::Each(widths) => {
+                if widths.len() != count {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{} must have one value per servo, {} were given for {} servos",
+                                                                                       name, widths.len(), count)));
+                }
+                widths
+            }
+        };
+        if let Some(width) = widths.iter().find(|width| **width <= 0f64) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{} must be greater than 0, The value {} does not meet this condition", name, width)));
+        }
+        Ok(widths)
+    }
+}
@@ -0,0 +1,96 @@
+use crate::gpio_module::GPIOManager;
+use crate::{metrics, EnumArg, OutputMode, PinState, TriggerEdge};
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+
+
+/// Which way a pin is set up in the GPIOManager, used to set up the pins the shortcuts are first called on.
+enum Setup {
+    Input,
+    Output,
+    None,
+}
+
+
+fn setup_of(gpio_manager: &GPIOManager, pin_num: u8) -> Setup {
+    let manager = gpio_manager.get_manager();
+    let manager = metrics::lock(&manager);
+    if gpio_manager.is_input_pin(pin_num, &manager) {
+        Setup::Input
+    } else if gpio_manager.is_output_pin(pin_num, &manager) {
+        Setup::Output
+    } else {
+        Setup::None
+    }
+}
+
+
+#[pyfunction]
+/// Reads a pin through the shared GPIOManager. A pin that isn't set up is added as an input with the default pull
+/// resistor, and an output pin returns the state it is driven to.
+///
+/// Parameters:
+/// - ```pin_num``` (int): The GPIO pin.
+///
+/// Returns:
+/// - ```bool```: True when the pin is logic high.
+///
+/// Example usage:
+/// ```if gpio_manager.read(18): print("pressed")```
+pub fn read(pin_num: u8) -> PyResult<bool> {
+    let gpio_manager = GPIOManager::new_rust_reference();
+    let state = match setup_of(&gpio_manager, pin_num) {
+        Setup::Output => gpio_manager.get_output_state(pin_num)?,
+        Setup::Input => gpio_manager.get_pin(pin_num)?,
+        Setup::None => {
+            gpio_manager.add_input_pin(pin_num, None, None, false)?;
+            gpio_manager.get_pin(pin_num)?
+        }
+    };
+    Ok(state == PinState::HIGH)
+}
+
+
+#[pyfunction]
+/// Drives a pin through the shared GPIOManager. A pin that isn't set up is added as a push-pull output starting in the
+/// given state.
+///
+/// Parameters:
+/// - ```pin_num``` (int): The GPIO pin.
+/// - ```pin_state``` (PinState, str, int or bool): The state to drive the pin to.
+///
+/// Example usage:
+/// ```gpio_manager.write(25, True)```
+pub fn write(pin_num: u8, pin_state: EnumArg<PinState>) -> PyResult<()> {
+    let gpio_manager = GPIOManager::new_rust_reference();
+    match setup_of(&gpio_manager, pin_num) {
+        Setup::Input => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Pin {} is set up as an input pin, reset it before writing to it", pin_num))),
+        Setup::Output => gpio_manager.set_output_pin(pin_num, pin_state),
+        Setup::None => gpio_manager.add_output_pin(pin_num, pin_state, None, OutputMode::PUSH_PULL.into(), false),
+    }
+}
+
+
+#[pyfunction]
+#[pyo3(signature = (pin_num, callback, trigger_edge = EnumArg(TriggerEdge::BOTH), debounce_time_ms = None, args = None))]
+/// Calls a function on the edges of a pin through the shared GPIOManager. A pin that isn't set up is added as an input
+/// with the default pull resistor.
+///
+/// Parameters:
+/// - ```pin_num``` (int): The GPIO pin.
+/// - ```callback``` (callable): The function to call, with the args.
+/// - ```trigger_edge``` (TriggerEdge or str): The edges to call the function on (default is both).
+/// - ```debounce_time_ms``` (float): The debounce time in milliseconds (default is 2, changed with set_defaults).
+/// - ```args``` (tuple): The arguments to pass to the function.
+///
+/// Example usage:
+/// ```gpio_manager.on_edge(18, lambda: print("pressed"), "falling")```
+pub fn on_edge(py: Python, pin_num: u8, callback: PyObject, trigger_edge: EnumArg<TriggerEdge>, debounce_time_ms: Option<f64>,
+               args: Option<&Bound<'_, PyTuple>>) -> PyResult<()> {
+    let gpio_manager = GPIOManager::new_rust_reference();
+    if let Setup::None = setup_of(&gpio_manager, pin_num) {
+        gpio_manager.add_input_pin(pin_num, None, None, false)?;
+    }
+    gpio_manager.assign_callback(py, pin_num, callback, trigger_edge, debounce_time_ms, args, false, false, "hardware", None, false, 0, false)
+}
@@ -0,0 +1,597 @@
+use crate::i2c_ioctl::{I2cDev, Message};
+use crate::i2c_module::{extract_bytes, with_buses, I2CBuses, I2CManager};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyIterator, PyList, PyString, PyTuple};
+use pyo3::{pyclass, pymethods, PyErr, PyResult, Python};
+use rppal::i2c::I2c;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+
+// Largest block of an SMBus block transfer
+const SMBUS_BLOCK_MAX: usize = 32;
+
+
+/// Converts a bus number or a device path such as `/dev/i2c-1` into a bus number.
+fn extract_bus(bus: &Bound<'_, PyAny>) -> PyResult<u8> {
+    if let Ok(bus) = bus.extract::<u8>() {
+        return Ok(bus);
+    }
+    let path: String = bus.extract()?;
+    path.strip_prefix("/dev/i2c-")
+        .and_then(|bus| bus.parse::<u8>().ok())
+        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid I2C bus {}", path)))
+}
+
+
+/// Converts an I/O error into the OSError smbus2 raises, such as `[Errno 121] Remote I/O error` when a device doesn't
+/// acknowledge.
+fn os_error(e: &io::Error) -> PyErr {
+    match e.raw_os_error() {
+        Some(errno) => {
+            let message = e.to_string();
+            let message = message.strip_suffix(&format!(" (os error {})", errno)).unwrap_or(&message).to_string();
+            PyErr::new::<pyo3::exceptions::PyOSError, _>((errno, message))
+        }
+        None => PyErr::new::<pyo3::exceptions::PyOSError, _>(e.to_string()),
+    }
+}
+
+
+fn check_block_length(length: usize) -> PyResult<()> {
+    if length > SMBUS_BLOCK_MAX {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Data length cannot exceed {} bytes", SMBUS_BLOCK_MAX)));
+    }
+    Ok(())
+}
+
+
+#[pyclass]
+/// SMBus is a drop-in replacement for the `SMBus` class of smbus2, so existing sensor libraries can use the I2CManager
+/// without code changes. The bus is shared with the I2CManager, so the retry policy and PEC setting apply to both.
+///
+/// The `force` arguments are accepted for compatibility but ignored, devices claimed by a kernel driver are reported as
+/// busy.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// with gpio_manager.SMBus(1) as bus:
+///     bus.write_byte_data(0x48, 0x01, 0x60)
+///     data = bus.read_i2c_block_data(0x48, 0x00, 2)
+/// ```
+pub struct SMBus {
+    i2c: Arc<Mutex<I2CBuses>>,
+    bus: Option<u8>,
+}
+
+
+impl SMBus {
+    fn bus(&self) -> PyResult<u8> {
+        self.bus.ok_or_else(|| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("SMBus is not opened"))
+    }
+
+    /// Runs the operation on the device, retrying it according to the retry policy of the I2CManager.
+    fn with_i2c<T: Send>(&self, addr: u16, action: &str, mut op: impl FnMut(&mut I2c) -> rppal::i2c::Result<T> + Send) -> PyResult<T> {
+        let bus = self.bus()?;
+        with_buses(&self.i2c, |i2c_lock| {
+            let retry = i2c_lock.retry_policy();
+            let i2c = i2c_lock.select(Some(bus), addr)?;
+            retry.run(|| op(i2c)).map_err(|e| match e {
+                rppal::i2c::Error::Io(e) => os_error(&e),
+                e => PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to {}: {:?}", action, e)),
+            })
+        })
+    }
+
+    /// Runs the operation on a separate handle to the bus, for the transfers rppal doesn't implement. The operation is
+    /// passed whether 10 bit addressing and packet error checking are enabled on the bus.
+    fn with_dev<T: Send>(&self, mut op: impl FnMut(&I2cDev, bool, bool) -> io::Result<T> + Send) -> PyResult<T> {
+        let bus = self.bus()?;
+        with_buses(&self.i2c, |i2c_lock| {
+            let retry = i2c_lock.retry_policy();
+            let addr_10bit = i2c_lock.addr_10bit_enabled(bus);
+            let pec = i2c_lock.pec_enabled(bus);
+            let dev = I2cDev::open(bus).map_err(|e| os_error(&e))?;
+            retry.run(|| op(&dev, addr_10bit, pec)).map_err(|e| os_error(&e))
+        })
+    }
+}
+
+
+#[pymethods]
+impl SMBus {
+    #[new]
+    /// Initializes a new SMBus instance, opening the bus if one is given.
+    ///
+    /// Parameters:
+    /// - `bus` (int | str): The I2C bus number or device path such as `/dev/i2c-1` (default is None).
+    /// - `force` (bool): Ignored, accepted for compatibility with smbus2 (default is False).
+    ///
+    /// Example usage:
+    /// ```python
+    /// bus = gpio_manager.SMBus(1)
+    /// ```
+    #[pyo3(signature = (bus = None, force = false))]
+    #[allow(unused_variables)]
+    fn new(bus: Option<&Bound<'_, PyAny>>, force: bool) -> PyResult<Self> {
+        let i2c_manager = I2CManager::new_rust_reference();
+        let mut smbus = Self {
+            i2c: i2c_manager.lock().unwrap().get_buses(),
+            bus: None,
+        };
+        if let Some(bus) = bus {
+            smbus.open(bus)?;
+        }
+        Ok(smbus)
+    }
+
+    /// Opens an I2C bus, the bus is opened in the I2CManager if it isn't open already.
+    ///
+    /// Parameters:
+    /// - `bus` (int | str): The I2C bus number or device path such as `/dev/i2c-1`.
+    ///
+    /// Example usage:
+    /// ```python
+    /// bus.open(1)
+    /// ```
+    #[pyo3(signature = (bus))]
+    fn open(&mut self, bus: &Bound<'_, PyAny>) -> PyResult<()> {
+        let bus = extract_bus(bus)?;
+        let mut i2c_lock = self.i2c.lock().unwrap();
+        if !i2c_lock.is_open(bus) {
+            i2c_lock.open(bus)?;
+        }
+        self.bus = Some(bus);
+        Ok(())
+    }
+
+    /// Detaches from the bus. The bus stays open in the I2CManager, since it may be used by other objects.
+    ///
+    /// Example usage:
+    /// ```python
+    /// bus.close()
+    /// ```
+    fn close(&mut self) {
+        self.bus = None;
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __exit__(&mut self, _args: &Bound<'_, PyTuple>) -> bool {
+        self.close();
+        false
+    }
+
+    /// Whether SMBus packet error checking is enabled on the bus.
+    #[getter]
+    fn get_pec(&self) -> PyResult<bool> {
+        let bus = self.bus()?;
+        Ok(self.i2c.lock().unwrap().pec_enabled(bus))
+    }
+
+    #[setter]
+    fn set_pec(&self, enabled: bool) -> PyResult<()> {
+        let bus = self.bus()?;
+        self.i2c.lock().unwrap().set_pec(Some(bus), enabled)
+    }
+
+    /// Enables or disables SMBus packet error checking.
+    ///
+    /// Parameters:
+    /// - `enable` (bool): Whether packet error checking is used (default is True).
+    ///
+    /// Example usage:
+    /// ```python
+    /// bus.enable_pec()
+    /// ```
+    #[pyo3(signature = (enable = true))]
+    fn enable_pec(&self, enable: bool) -> PyResult<()> {
+        self.set_pec(enable)
+    }
+
+    /// Sends a quick command with the write bit set.
+    ///
+    /// Parameters:
+    /// - `i2c_addr` (int): The I2C slave address.
+    /// - `force` (bool): Ignored (default is None).
+    ///
+    /// Example usage:
+    /// ```python
+    /// bus.write_quick(0x48)
+    /// ```
+    #[pyo3(signature = (i2c_addr, force = None))]
+    #[allow(unused_variables)]
+    fn write_quick(&self, i2c_addr: u16, force: Option<bool>) -> PyResult<()> {
+        self.with_i2c(i2c_addr, "send quick command", |i2c| i2c.smbus_quick_command(false))
+    }
+
+    /// Reads a single byte from a device.
+    ///
+    /// Parameters:
+    /// - `i2c_addr` (int): The I2C slave address.
+    /// - `force` (bool): Ignored (default is None).
+    ///
+    /// Returns:
+    /// - `int`: The byte read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// value = bus.read_byte(0x48)
+    /// ```
+    #[pyo3(signature = (i2c_addr, force = None))]
+    #[allow(unused_variables)]
+    fn read_byte(&self, i2c_addr: u16, force: Option<bool>) -> PyResult<u8> {
+        self.with_i2c(i2c_addr, "read byte", |i2c| i2c.smbus_receive_byte())
+    }
+
+    /// Writes a single byte to a device.
+    ///
+    /// Parameters:
+    /// - `i2c_addr` (int): The I2C slave address.
+    /// - `value` (int): The byte to write.
+    /// - `force` (bool): Ignored (default is None).
+    ///
+    /// Example usage:
+    /// ```python
+    /// bus.write_byte(0x48, 0x01)
+    /// ```
+    #[pyo3(signature = (i2c_addr, value, force = None))]
+    #[allow(unused_variables)]
+    fn write_byte(&self, i2c_addr: u16, value: u8, force: Option<bool>) -> PyResult<()> {
+        self.with_i2c(i2c_addr, "write byte", |i2c| i2c.smbus_send_byte(value))
+    }
+
+    /// Reads a single byte from a register.
+    ///
+    /// Parameters:
+    /// - `i2c_addr` (int): The I2C slave address.
+    /// - `register` (int): The register to read.
+    /// - `force` (bool): Ignored (default is None).
+    ///
+    /// Returns:
+    /// - `int`: The byte read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// value = bus.read_byte_data(0x48, 0x01)
+    /// ```
+    #[pyo3(signature = (i2c_addr, register, force = None))]
+    #[allow(unused_variables)]
+    fn read_byte_data(&self, i2c_addr: u16, register: u8, force: Option<bool>) -> PyResult<u8> {
+        self.with_i2c(i2c_addr, "read byte", |i2c| i2c.smbus_read_byte(register))
+    }
+
+    /// Writes a single byte to a register.
+    ///
+    /// Parameters:
+    /// - `i2c_addr` (int): The I2C slave address.
+    /// - `register` (int): The register to write.
+    /// - `value` (int): The byte to write.
+    /// - `force` (bool): Ignored (default is None).
+    ///
+    /// Example usage:
+    /// ```python
+    /// bus.write_byte_data(0x48, 0x01, 0x60)
+    /// ```
+    #[pyo3(signature = (i2c_addr, register, value, force = None))]
+    #[allow(unused_variables)]
+    fn write_byte_data(&self, i2c_addr: u16, register: u8, value: u8, force: Option<bool>) -> PyResult<()> {
+        self.with_i2c(i2c_addr, "write byte", |i2c| i2c.smbus_write_byte(register, value))
+    }
+
+    /// Reads a 16 bit word from a register, the low byte is received first.
+    ///
+    /// Parameters:
+    /// - `i2c_addr` (int): The I2C slave address.
+    /// - `register` (int): The register to read.
+    /// - `force` (bool): Ignored (default is None).
+    ///
+    /// Returns:
+    /// - `int`: The word read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// value = bus.read_word_data(0x48, 0x00)
+    /// ```
+    #[pyo3(signature = (i2c_addr, register, force = None))]
+    #[allow(unused_variables)]
+    fn read_word_data(&self, i2c_addr: u16, register: u8, force: Option<bool>) -> PyResult<u16> {
+        self.with_i2c(i2c_addr, "read word", |i2c| i2c.smbus_read_word(register))
+    }
+
+    /// Writes a 16 bit word to a register, the low byte is sent first.
+    ///
+    /// Parameters:
+    /// - `i2c_addr` (int): The I2C slave address.
+    /// - `register` (int): The register to write.
+    /// - `value` (int): The word to write.
+    /// - `force` (bool): Ignored (default is None).
+    ///
+    /// Example usage:
+    /// ```python
+    /// bus.write_word_data(0x48, 0x01, 0x6080)
+    /// ```
+    #[pyo3(signature = (i2c_addr, register, value, force = None))]
+    #[allow(unused_variables)]
+    fn write_word_data(&self, i2c_addr: u16, register: u8, value: u16, force: Option<bool>) -> PyResult<()> {
+        self.with_i2c(i2c_addr, "write word", |i2c| i2c.smbus_write_word(register, value))
+    }
+
+    /// Sends a 16 bit word to a register and reads the word the device responds with.
+    ///
+    /// Parameters:
+    /// - `i2c_addr` (int): The I2C slave address.
+    /// - `register` (int): The register to send the word to.
+    /// - `value` (int): The word to send.
+    /// - `force` (bool): Ignored (default is None).
+    ///
+    /// Returns:
+    /// - `int`: The word received.
+    ///
+    /// Example usage:
+    /// ```python
+    /// response = bus.process_call(0x0B, 0x22, 0x1234)
+    /// ```
+    #[pyo3(signature = (i2c_addr, register, value, force = None))]
+    #[allow(unused_variables)]
+    fn process_call(&self, i2c_addr: u16, register: u8, value: u16, force: Option<bool>) -> PyResult<u16> {
+        self.with_i2c(i2c_addr, "perform process call", |i2c| i2c.smbus_process_call(register, value))
+    }
+
+    /// Reads an SMBus block of up to 32 bytes from a register, the device sends the length of the block first.
+    ///
+    /// Parameters:
+    /// - `i2c_addr` (int): The I2C slave address.
+    /// - `register` (int): The register to read.
+    /// - `force` (bool): Ignored (default is None).
+    ///
+    /// Returns:
+    /// - `list[int]`: The block read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// data = bus.read_block_data(0x0B, 0x20)
+    /// ```
+    #[pyo3(signature = (i2c_addr, register, force = None))]
+    #[allow(unused_variables)]
+    fn read_block_data<'py>(&self, py: Python<'py>, i2c_addr: u16, register: u8, force: Option<bool>) -> PyResult<Bound<'py, PyList>> {
+        let mut buf = [0u8; SMBUS_BLOCK_MAX];
+        let length = self.with_i2c(i2c_addr, "read block", |i2c| i2c.smbus_block_read(register, &mut buf))?;
+        PyList::new(py, &buf[..length.min(SMBUS_BLOCK_MAX)])
+    }
+
+    /// Writes an SMBus block of up to 32 bytes to a register, the length of the block is sent first.
+    ///
+    /// Parameters:
+    /// - `i2c_addr` (int): The I2C slave address.
+    /// - `register` (int): The register to write.
+    /// - `data` (list[int] | bytes): The block to write.
+    /// - `force` (bool): Ignored (default is None).
+    ///
+    /// Example usage:
+    /// ```python
+    /// bus.write_block_data(0x0B, 0x20, [0x01, 0x02])
+    /// ```
+    #[pyo3(signature = (i2c_addr, register, data, force = None))]
+    #[allow(unused_variables)]
+    fn write_block_data(&self, i2c_addr: u16, register: u8, data: &Bound<'_, PyAny>, force: Option<bool>) -> PyResult<()> {
+        let data = extract_bytes(data)?;
+        check_block_length(data.len())?;
+        self.with_i2c(i2c_addr, "write block", |i2c| i2c.smbus_block_write(register, &data))
+    }
+
+    /// Sends an SMBus block to a register and reads the block the device responds with.
+    ///
+    /// Parameters:
+    /// - `i2c_addr` (int): The I2C slave address.
+    /// - `register` (int): The register to send the block to.
+    /// - `data` (list[int] | bytes): The block to send.
+    /// - `force` (bool): Ignored (default is None).
+    ///
+    /// Returns:
+    /// - `list[int]`: The block received.
+    ///
+    /// Example usage:
+    /// ```python
+    /// response = bus.block_process_call(0x0B, 0x2F, [0x01, 0x02])
+    /// ```
+    #[pyo3(signature = (i2c_addr, register, data, force = None))]
+    #[allow(unused_variables)]
+    fn block_process_call<'py>(&self, py: Python<'py>, i2c_addr: u16, register: u8, data: &Bound<'py, PyAny>, force: Option<bool>) -> PyResult<Bound<'py,
+        PyList>> {
+        let data = extract_bytes(data)?;
+        let buf = self.with_dev(|dev, addr_10bit, pec| {
+            dev.set_slave(i2c_addr, addr_10bit, pec)?;
+            dev.smbus_block_process_call(register, &data)
+        })?;
+        PyList::new(py, &buf)
+    }
+
+    /// Reads a block of up to 32 bytes starting at a register, without a length byte.
+    ///
+    /// Parameters:
+    /// - `i2c_addr` (int): The I2C slave address.
+    /// - `register` (int): The first register to read.
+    /// - `length` (int): The number of bytes to read.
+    /// - `force` (bool): Ignored (default is None).
+    ///
+    /// Returns:
+    /// - `list[int]`: The block read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// data = bus.read_i2c_block_data(0x68, 0x3B, 6)
+    /// ```
+    #[pyo3(signature = (i2c_addr, register, length, force = None))]
+    #[allow(unused_variables)]
+    fn read_i2c_block_data<'py>(&self, py: Python<'py>, i2c_addr: u16, register: u8, length: usize, force: Option<bool>) -> PyResult<Bound<'py,
+        PyList>> {
+        check_block_length(length)?;
+        let mut buf = vec![0u8; length];
+        self.with_i2c(i2c_addr, "read block", |i2c| i2c.block_read(register, &mut buf))?;
+        PyList::new(py, &buf)
+    }
+
+    /// Writes a block of up to 32 bytes starting at a register, without a length byte.
+    ///
+    /// Parameters:
+    /// - `i2c_addr` (int): The I2C slave address.
+    /// - `register` (int): The first register to write.
+    /// - `data` (list[int] | bytes): The block to write.
+    /// - `force` (bool): Ignored (default is None).
+    ///
+    /// Example usage:
+    /// ```python
+    /// bus.write_i2c_block_data(0x68, 0x6B, [0x00])
+    /// ```
+    #[pyo3(signature = (i2c_addr, register, data, force = None))]
+    #[allow(unused_variables)]
+    fn write_i2c_block_data(&self, i2c_addr: u16, register: u8, data: &Bound<'_, PyAny>, force: Option<bool>) -> PyResult<()> {
+        let data = extract_bytes(data)?;
+        check_block_length(data.len())?;
+        self.with_i2c(i2c_addr, "write block", |i2c| i2c.block_write(register, &data))
+    }
+
+    /// Performs the messages as a single combined transaction, with a repeated start between messages and a single
+    /// stop at the end. The read messages are filled with the data received.
+    ///
+    /// Parameters:
+    /// - `i2c_msgs` (i2c_msg): The messages of the transaction, up to 42 messages can be used.
+    ///
+    /// Example usage:
+    /// ```python
+    /// write = gpio_manager.i2c_msg.write(0x50, [0x00, 0x10])
+    /// read = gpio_manager.i2c_msg.read(0x50, 16)
+    /// bus.i2c_rdwr(write, read)
+    /// data = list(read)
+    /// ```
+    #[pyo3(signature = (*i2c_msgs))]
+    fn i2c_rdwr(&self, i2c_msgs: &Bound<'_, PyTuple>) -> PyResult<()> {
+        let msgs = i2c_msgs
+            .iter()
+            .map(|msg| msg.downcast_into::<I2CMsg>().map_err(PyErr::from))
+            .collect::<PyResult<Vec<_>>>()?;
+        let mut messages: Vec<Message> = msgs
+            .iter()
+            .map(|msg| {
+                let msg = msg.borrow();
+                Message { addr: msg.addr, read: msg.read, buf: msg.buf.clone() }
+            })
+            .collect();
+        self.with_dev(|dev, addr_10bit, _| dev.rdwr(addr_10bit, &mut messages))?;
+        for (msg, message) in msgs.iter().zip(messages) {
+            if message.read {
+                msg.borrow_mut().buf = message.buf;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+#[pyclass(name = "i2c_msg")]
+/// i2c_msg is a message of a combined transaction performed by `SMBus.i2c_rdwr`, matching the `i2c_msg` class of
+/// smbus2. Messages are created with `i2c_msg.read` and `i2c_msg.write`, iterating a message yields its bytes.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// read = gpio_manager.i2c_msg.read(0x50, 16)
+/// bus.i2c_rdwr(gpio_manager.i2c_msg.write(0x50, [0x00, 0x10]), read)
+/// data = bytes(read)
+/// ```
+pub struct I2CMsg {
+    addr: u16,
+    read: bool,
+    buf: Vec<u8>,
+}
+
+
+#[pymethods]
+impl I2CMsg {
+    /// Creates a message reading bytes from a device.
+    ///
+    /// Parameters:
+    /// - `address` (int): The I2C slave address.
+    /// - `length` (int): The number of bytes to read.
+    ///
+    /// Returns:
+    /// - `i2c_msg`: The message.
+    ///
+    /// Example usage:
+    /// ```python
+    /// read = gpio_manager.i2c_msg.read(0x50, 16)
+    /// ```
+    #[staticmethod]
+    #[pyo3(signature = (address, length))]
+    fn read(address: u16, length: usize) -> Self {
+        Self { addr: address, read: true, buf: vec![0u8; length] }
+    }
+
+    /// Creates a message writing bytes to a device.
+    ///
+    /// Parameters:
+    /// - `address` (int): The I2C slave address.
+    /// - `buf` (list[int] | bytes | str): The bytes to write.
+    ///
+    /// Returns:
+    /// - `i2c_msg`: The message.
+    ///
+    /// Example usage:
+    /// ```python
+    /// write = gpio_manager.i2c_msg.write(0x50, [0x00, 0x10])
+    /// ```
+    #[staticmethod]
+    #[pyo3(signature = (address, buf))]
+    fn write(address: u16, buf: &Bound<'_, PyAny>) -> PyResult<Self> {
+        let buf = match buf.downcast::<PyString>() {
+            Ok(text) => text.to_str()?.as_bytes().to_vec(),
+            Err(_) => extract_bytes(buf)?.to_vec(),
+        };
+        Ok(Self { addr: address, read: false, buf })
+    }
+
+    /// The I2C slave address of the message.
+    #[getter]
+    fn addr(&self) -> u16 {
+        self.addr
+    }
+
+    /// The flags of the message, 1 (I2C_M_RD) for a read.
+    #[getter]
+    fn flags(&self) -> u16 {
+        self.read as u16
+    }
+
+    /// The number of bytes of the message.
+    #[getter]
+    fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// The bytes of the message, the data received for a read performed already.
+    #[getter]
+    fn buf<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.buf)
+    }
+
+    fn __len__(&self) -> usize {
+        self.buf.len()
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+        PyList::new(py, &self.buf)?.try_iter()
+    }
+
+    fn __bytes__<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.buf)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!("i2c_msg({},{},{})", self.addr, self.flags(), PyBytes::new(py, &self.buf).repr()?))
+    }
+}
@@ -0,0 +1,587 @@
+use crate::backend::{self, Backend, OutputPin};
+use crate::gpio_module::GPIOManager;
+use crate::i2c_ioctl::Segment;
+use crate::i2c_module::{extract_bytes, extract_segments, I2CProbeMode};
+use crate::pinctrl::set_bias;
+use crate::timing::wait_until;
+use crate::{frequency_to_period_checked, ms_to_duration_checked, release_gil};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use rppal::gpio::Bias;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+
+/// Why a transfer on a software bus failed.
+enum BusError {
+    /// The device didn't acknowledge its address.
+    AddressNack(u8),
+    /// The device didn't acknowledge the byte at the index of the write segment.
+    DataNack(usize),
+    /// SDA was low while the master released it, another master or a stuck device drives the bus.
+    ArbitrationLost,
+    /// SCL stayed low for longer than the clock stretch timeout.
+    Timeout,
+    Io(String),
+}
+
+
+impl fmt::Display for BusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BusError::AddressNack(addr) => write!(f, "The device at 0x{:02x} didn't acknowledge its address", addr),
+            BusError::DataNack(index) => write!(f, "The device didn't acknowledge byte {}", index),
+            BusError::ArbitrationLost => write!(f, "Arbitration lost, SDA is held low"),
+            BusError::Timeout => write!(f, "SCL was held low for longer than the clock stretch timeout"),
+            BusError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+
+impl From<backend::Error> for BusError {
+    fn from(e: backend::Error) -> Self {
+        BusError::Io(e.to_string())
+    }
+}
+
+
+/// An I2C bus bit-banged on two pins. The lines are driven open drain: the output of a pin is enabled to pull its line
+/// low and disabled to release it to the pull-up, and the levels are read back from the pads.
+struct SoftBus {
+    scl_pin: u8,
+    sda_pin: u8,
+    scl: Arc<Mutex<OutputPin>>,
+    sda: Arc<Mutex<OutputPin>>,
+    backend: Arc<dyn Backend>,
+    half_period: Duration,
+    stretch_timeout: Duration,
+}
+
+
+impl SoftBus {
+    fn delay(&self) {
+        wait_until(Instant::now() + self.half_period);
+    }
+
+    fn is_high(&self, pin_num: u8) -> Result<bool, BusError> {
+        Ok(self.backend.read_levels()? & (1 << pin_num) != 0)
+    }
+
+    fn drive(pin: &Mutex<OutputPin>, low: bool) -> Result<(), BusError> {
+        Ok(pin.lock().unwrap().set_output_enabled(low)?)
+    }
+
+    /// Releases SCL and waits for the device to stop stretching the clock.
+    fn release_scl(&self) -> Result<(), BusError> {
+        Self::drive(&self.scl, false)?;
+        let deadline = Instant::now() + self.stretch_timeout;
+        while !self.is_high(self.scl_pin)? {
+            if Instant::now() >= deadline {
+                return Err(BusError::Timeout);
+            }
+        }
+        Ok(())
+    }
+
+    /// Generates a START condition, or a repeated START in the middle of a transaction: SDA falls while SCL is high.
+    fn start(&self) -> Result<(), BusError> {
+        Self::drive(&self.sda, false)?;
+        self.delay();
+        self.release_scl()?;
+        if !self.is_high(self.sda_pin)? {
+            return Err(BusError::ArbitrationLost);
+        }
+        self.delay();
+        Self::drive(&self.sda, true)?;
+        self.delay();
+        Self::drive(&self.scl, true)
+    }
+
+    /// Generates a STOP condition: SDA rises while SCL is high.
+    fn stop(&self) -> Result<(), BusError> {
+        Self::drive(&self.sda, true)?;
+        self.delay();
+        self.release_scl()?;
+        self.delay();
+        Self::drive(&self.sda, false)?;
+        self.delay();
+        Ok(())
+    }
+
+    fn write_bit(&self, bit: bool) -> Result<(), BusError> {
+        Self::drive(&self.sda, !bit)?;
+        self.delay();
+        self.release_scl()?;
+        // A released SDA that reads low is driven by another master
+        if bit && !self.is_high(self.sda_pin)? {
+            Self::drive(&self.scl, true)?;
+            Self::drive(&self.sda, false)?;
+            return Err(BusError::ArbitrationLost);
+        }
+        self.delay();
+        Self::drive(&self.scl, true)
+    }
+
+    fn read_bit(&self) -> Result<bool, BusError> {
+        Self::drive(&self.sda, false)?;
+        self.delay();
+        self.release_scl()?;
+        let bit = self.is_high(self.sda_pin)?;
+        self.delay();
+        Self::drive(&self.scl, true)?;
+        Ok(bit)
+    }
+
+    /// Writes a byte, returning whether the device acknowledged it.
+    fn write_byte(&self, byte: u8) -> Result<bool, BusError> {
+        for bit in (0..8).rev() {
+            self.write_bit(byte & (1 << bit) != 0)?;
+        }
+        Ok(!self.read_bit()?)
+    }
+
+    /// Reads a byte, acknowledging it when more bytes are read after it.
+    fn read_byte(&self, ack: bool) -> Result<u8, BusError> {
+        let mut byte = 0u8;
+        for _ in 0..8 {
+            byte = byte << 1 | self.read_bit()? as u8;
+        }
+        self.write_bit(!ack)?;
+        Ok(byte)
+    }
+
+    fn address(&self, addr: u8, read: bool) -> Result<(), BusError> {
+        if self.write_byte(addr << 1 | read as u8)? {
+            Ok(())
+        } else {
+            Err(BusError::AddressNack(addr))
+        }
+    }
+
+    /// Performs the segments with a repeated START between them and a single STOP at the end, returning the data of
+    /// every read segment. Without segments only the address is sent, with the read bit given.
+    fn transfer(&self, addr: u8, segments: &[Segment], read: bool) -> Result<Vec<Vec<u8>>, BusError> {
+        let result = (|| {
+            let mut data = Vec::new();
+            if segments.is_empty() {
+                self.start()?;
+                self.address(addr, read)?;
+            }
+            for segment in segments {
+                self.start()?;
+                match segment {
+                    Segment::Write(bytes) => {
+                        self.address(addr, false)?;
+                        for (index, byte) in bytes.iter().enumerate() {
+                            if !self.write_byte(*byte)? {
+                                return Err(BusError::DataNack(index));
+                            }
+                        }
+                    }
+                    Segment::Read(length) => {
+                        self.address(addr, true)?;
+                        data.push((0..*length).map(|index| self.read_byte(index + 1 < *length)).collect::<Result<_, _>>()?);
+                    }
+                }
+            }
+            Ok(data)
+        })();
+        // The bus is left idle after a failure as well, a lost arbitration leaves it to the other master
+        match result {
+            Err(BusError::ArbitrationLost) => {
+                Self::drive(&self.scl, false)?;
+                Self::drive(&self.sda, false)?;
+            }
+            _ => self.stop()?,
+        }
+        result
+    }
+}
+
+
+#[pyclass]
+/// SoftI2C is an I2C master bit-banged on any two GPIO pins, with the API of the I2CManager for a single bus. It adds
+/// buses beside the hardware ones, for example for a second device with the same fixed address. The devices may
+/// stretch the clock.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// i2c = gpio_manager.SoftI2C(scl_pin=5, sda_pin=6)
+/// i2c.write_byte(0x48, 0x00)
+/// data = i2c.read(0x48, 2)
+/// i2c.close()
+/// ```
+pub struct SoftI2C {
+    bus: Mutex<Option<Arc<SoftBus>>>,
+}
+
+
+impl SoftI2C {
+    fn bus(&self) -> PyResult<Arc<SoftBus>> {
+        self.bus.lock().unwrap().clone().ok_or_else(|| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("The software I2C bus is closed"))
+    }
+
+    /// Runs the segments on the bus with the GIL released.
+    fn transfer(&self, addr: u16, segments: Vec<Segment>, action: &str) -> PyResult<Vec<Vec<u8>>> {
+        if addr > 0x7F {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Address must be between 0x00 and 0x7f, The value {:#04x} does not meet this condition", addr)));
+        }
+        let bus = self.bus()?;
+        release_gil(|| bus.transfer(addr as u8, &segments, false))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to {}: {}", action, e)))
+    }
+
+    fn read_segments(&self, addr: u16, segments: Vec<Segment>, action: &str) -> PyResult<Vec<u8>> {
+        Ok(self.transfer(addr, segments, action)?.pop().unwrap_or_default())
+    }
+}
+
+
+#[pymethods]
+impl SoftI2C {
+    #[new]
+    /// Takes two free pins as the SCL and SDA lines of a software I2C bus and releases them to the idle level. The
+    /// bus needs pull-up resistors, the internal pull-ups of around 50 kΩ are only enough for short wires at low
+    /// speeds.
+    ///
+    /// Parameters:
+    /// - `scl_pin` (int): The GPIO pin of the clock line (0 to 27).
+    /// - `sda_pin` (int): The GPIO pin of the data line (0 to 27).
+    /// - `frequency_hz` (float): The clock frequency, the time the pins take to switch makes the actual clock slower
+    ///   (default is 100000).
+    /// - `stretch_timeout_ms` (float): How long a device may hold SCL low to stretch the clock before the transfer
+    ///   fails (default is 25).
+    /// - `pull_up` (bool): Enables the internal pull-ups of the pins, with the rppal backend (default is True).
+    ///
+    /// Example usage:
+    /// ```python
+    /// i2c = gpio_manager.SoftI2C(5, 6, frequency_hz=400_000)
+    /// ```
+    #[pyo3(signature = (scl_pin, sda_pin, frequency_hz = 100_000f64, stretch_timeout_ms = 25f64, pull_up = true))]
+    fn new(scl_pin: u8, sda_pin: u8, frequency_hz: f64, stretch_timeout_ms: f64, pull_up: bool) -> PyResult<Self> {
+        if scl_pin == sda_pin {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("SCL and SDA must be different pins, The value {} does not meet this condition", sda_pin)));
+        }
+        let half_period = frequency_to_period_checked("Frequency", frequency_hz)? / 2;
+        let stretch_timeout = ms_to_duration_checked("Clock stretch timeout", stretch_timeout_ms)?;
+        let backend = backend::current();
+        backend.read_levels().map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Software I2C reads the levels of the pins: {}", e)))?;
+        let gpio_manager = GPIOManager::new_rust_reference();
+        // SCL is taken first, so SDA never falls while SCL is high and no START is seen on the bus
+        let scl = gpio_manager.claim_output_pin(scl_pin)?;
+        let sda = match gpio_manager.claim_output_pin(sda_pin) {
+            Ok(sda) => sda,
+            Err(e) => {
+                gpio_manager.reset_pin(scl_pin)?;
+                return Err(e);
+            }
+        };
+        let bus = SoftBus {
+            scl_pin,
+            sda_pin,
+            scl,
+            sda,
+            backend,
+            half_period,
+            stretch_timeout,
+        };
+        let released = (|| {
+            for pin_num in [sda_pin, scl_pin] {
+                if pull_up && backend::is_rppal() {
+                    set_bias(pin_num, Bias::PullUp).map_err(|e| BusError::Io(e.to_string()))?;
+                }
+                SoftBus::drive(if pin_num == sda_pin { &bus.sda } else { &bus.scl }, false)?;
+            }
+            Ok::<_, BusError>(())
+        })();
+        if let Err(e) = released {
+            gpio_manager.reset_pin(sda_pin)?;
+            gpio_manager.reset_pin(scl_pin)?;
+            return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to set up the software I2C bus: {}", e)));
+        }
+        Ok(Self { bus: Mutex::new(Some(Arc::new(bus))) })
+    }
+
+    /// Releases the pins of the bus.
+    ///
+    /// Example usage:
+    /// ```python
+    /// i2c.close()
+    /// ```
+    fn close(&self) -> PyResult<()> {
+        if let Some(bus) = self.bus.lock().unwrap().take() {
+            let gpio_manager = GPIOManager::new_rust_reference();
+            gpio_manager.reset_pin(bus.sda_pin)?;
+            gpio_manager.reset_pin(bus.scl_pin)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the SCL and SDA pins.
+    fn get_pins(&self) -> PyResult<(u8, u8)> {
+        let bus = self.bus()?;
+        Ok((bus.scl_pin, bus.sda_pin))
+    }
+
+    /// Writes a single byte to the I2C slave device.
+    ///
+    /// Parameters:
+    /// - `addr` (int): The I2C slave address.
+    /// - `data` (int): The byte to write.
+    ///
+    /// Example usage:
+    /// ```python
+    /// i2c.write_byte(0x20, 0xFF)
+    /// ```
+    #[pyo3(signature = (addr, data))]
+    fn write_byte(&self, addr: u16, data: u8) -> PyResult<()> {
+        self.transfer(addr, vec![Segment::Write(vec![data])], "write byte")?;
+        Ok(())
+    }
+
+    #[pyo3(signature = (addr, command, data))]
+    fn block_write_byte(&self, addr: u16, command: u8, data: u8) -> PyResult<()> {
+        self.transfer(addr, vec![Segment::Write(vec![command, data])], "write byte")?;
+        Ok(())
+    }
+
+    /// Reads a single byte from the I2C slave device.
+    ///
+    /// Parameters:
+    /// - `addr` (int): The I2C slave address.
+    ///
+    /// Returns:
+    /// - `int`: The byte read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// data = i2c.read_byte(0x20)
+    /// ```
+    #[pyo3(signature = (addr))]
+    fn read_byte(&self, addr: u16) -> PyResult<u8> {
+        Ok(self.read_segments(addr, vec![Segment::Read(1)], "read byte")?[0])
+    }
+
+    #[pyo3(signature = (addr, command))]
+    fn block_read_byte(&self, addr: u16, command: u8) -> PyResult<u8> {
+        Ok(self.read_segments(addr, vec![Segment::Write(vec![command]), Segment::Read(1)], "read byte")?[0])
+    }
+
+    /// Writes data to the I2C slave device.
+    ///
+    /// Parameters:
+    /// - `addr` (int): The I2C slave address.
+    /// - `data` (bytes | bytearray | memoryview | list[int]): The data to write.
+    ///
+    /// Example usage:
+    /// ```python
+    /// i2c.write(0x20, b'\x01\x02\x03')
+    /// ```
+    #[pyo3(signature = (addr, data))]
+    fn write(&self, addr: u16, data: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.transfer(addr, vec![Segment::Write(extract_bytes(data)?.into_owned())], "write data")?;
+        Ok(())
+    }
+
+    #[pyo3(signature = (addr, command, data))]
+    fn block_write(&self, addr: u16, command: u8, data: &Bound<'_, PyAny>) -> PyResult<()> {
+        let mut bytes = vec![command];
+        bytes.extend_from_slice(&extract_bytes(data)?);
+        self.transfer(addr, vec![Segment::Write(bytes)], "write data")?;
+        Ok(())
+    }
+
+    /// Reads data from the I2C slave device.
+    ///
+    /// Parameters:
+    /// - `addr` (int): The I2C slave address.
+    /// - `length` (int): The number of bytes to read.
+    ///
+    /// Returns:
+    /// - `bytes`: The data read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// data = i2c.read(0x20, 3)
+    /// ```
+    #[pyo3(signature = (addr, length))]
+    fn read<'py>(&self, py: Python<'py>, addr: u16, length: usize) -> PyResult<Bound<'py, PyBytes>> {
+        Ok(PyBytes::new(py, &self.read_segments(addr, vec![Segment::Read(length)], "read data")?))
+    }
+
+    #[pyo3(signature = (addr, command, length))]
+    fn block_read<'py>(&self, py: Python<'py>, addr: u16, command: u8, length: usize) -> PyResult<Bound<'py, PyBytes>> {
+        Ok(PyBytes::new(py, &self.read_segments(addr, vec![Segment::Write(vec![command]), Segment::Read(length)], "read data")?))
+    }
+
+    /// Performs a write followed by a read with a repeated start between them.
+    ///
+    /// Parameters:
+    /// - `addr` (int): The I2C slave address.
+    /// - `write_data` (bytes | bytearray | memoryview | list[int]): The data to write.
+    /// - `read_length` (int): The number of bytes to read.
+    ///
+    /// Returns:
+    /// - `bytes`: The data read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// data = i2c.write_read(0x20, b'\x01\x02', 3)
+    /// ```
+    #[pyo3(signature = (addr, write_data, read_length))]
+    fn write_read<'py>(&self, py: Python<'py>, addr: u16, write_data: &Bound<'py, PyAny>, read_length: usize) -> PyResult<Bound<'py, PyBytes>> {
+        let segments = vec![Segment::Write(extract_bytes(write_data)?.into_owned()), Segment::Read(read_length)];
+        Ok(PyBytes::new(py, &self.read_segments(addr, segments, "write data")?))
+    }
+
+    /// Reads a 16 bit word from a register of the I2C slave device.
+    ///
+    /// Parameters:
+    /// - `addr` (int): The I2C slave address.
+    /// - `command` (int): The register to read.
+    /// - `big_endian` (bool): Treat the high byte as the first byte on the bus, SMBus devices send the low byte first
+    ///   (default is False).
+    ///
+    /// Returns:
+    /// - `int`: The word read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// value = i2c.read_word(0x48, 0x00, big_endian=True)
+    /// ```
+    #[pyo3(signature = (addr, command, big_endian = false))]
+    fn read_word(&self, addr: u16, command: u8, big_endian: bool) -> PyResult<u16> {
+        let data = self.read_segments(addr, vec![Segment::Write(vec![command]), Segment::Read(2)], "read word")?;
+        let bytes = [data[0], data[1]];
+        Ok(if big_endian { u16::from_be_bytes(bytes) } else { u16::from_le_bytes(bytes) })
+    }
+
+    /// Writes a 16 bit word to a register of the I2C slave device.
+    ///
+    /// Parameters:
+    /// - `addr` (int): The I2C slave address.
+    /// - `command` (int): The register to write.
+    /// - `value` (int): The word to write.
+    /// - `big_endian` (bool): Send the high byte first, SMBus devices expect the low byte first (default is False).
+    ///
+    /// Example usage:
+    /// ```python
+    /// i2c.write_word(0x48, 0x01, 0x6080, big_endian=True)
+    /// ```
+    #[pyo3(signature = (addr, command, value, big_endian = false))]
+    fn write_word(&self, addr: u16, command: u8, value: u16, big_endian: bool) -> PyResult<()> {
+        let bytes = if big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+        self.transfer(addr, vec![Segment::Write(vec![command, bytes[0], bytes[1]])], "write word")?;
+        Ok(())
+    }
+
+    /// Sends a quick command, which only sends the address with a single bit in place of the read/write bit.
+    ///
+    /// Parameters:
+    /// - `addr` (int): The I2C slave address.
+    /// - `read` (bool): The value of the read/write bit (default is False).
+    ///
+    /// Example usage:
+    /// ```python
+    /// i2c.quick_command(0x20)
+    /// ```
+    #[pyo3(signature = (addr, read = false))]
+    fn quick_command(&self, addr: u16, read: bool) -> PyResult<()> {
+        if addr > 0x7F {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Address must be between 0x00 and 0x7f, The value {:#04x} does not meet this condition", addr)));
+        }
+        let bus = self.bus()?;
+        release_gil(|| bus.transfer(addr as u8, &[], read))
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to send quick command: {}", e)))?;
+        Ok(())
+    }
+
+    /// Sends a 16 bit word to a register and reads the 16 bit word the device responds with, with a repeated start
+    /// between them.
+    ///
+    /// Parameters:
+    /// - `addr` (int): The I2C slave address.
+    /// - `command` (int): The register to send the word to.
+    /// - `value` (int): The word to send.
+    /// - `big_endian` (bool): Transfer the high byte of both words first, SMBus devices use the low byte first
+    ///   (default is False).
+    ///
+    /// Returns:
+    /// - `int`: The word received.
+    ///
+    /// Example usage:
+    /// ```python
+    /// response = i2c.process_call(0x0B, 0x00, 0x0001)
+    /// ```
+    #[pyo3(signature = (addr, command, value, big_endian = false))]
+    fn process_call(&self, addr: u16, command: u8, value: u16, big_endian: bool) -> PyResult<u16> {
+        let bytes = if big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+        let data = self.read_segments(addr, vec![Segment::Write(vec![command, bytes[0], bytes[1]]), Segment::Read(2)], "perform process call")?;
+        let bytes = [data[0], data[1]];
+        Ok(if big_endian { u16::from_be_bytes(bytes) } else { u16::from_le_bytes(bytes) })
+    }
+
+    /// Performs a combined transaction, the segments are sent with a repeated start between them and a single stop at
+    /// the end.
+    ///
+    /// Parameters:
+    /// - `addr` (int): The I2C slave address.
+    /// - `segments` (list[I2CWrite | I2CRead]): The segments of the transaction.
+    ///
+    /// Returns:
+    /// - `list[bytes]`: The data read by every read segment, in order.
+    ///
+    /// Example usage:
+    /// ```python
+    /// [data] = i2c.transaction(0x50, [gpio_manager.I2CWrite(b'\x00\x10'), gpio_manager.I2CRead(16)])
+    /// ```
+    #[pyo3(signature = (addr, segments))]
+    fn transaction<'py>(&self, py: Python<'py>, addr: u16, segments: Vec<Bound<'py, PyAny>>) -> PyResult<Vec<Bound<'py, PyBytes>>> {
+        let segments = extract_segments(&segments)?;
+        let data = self.transfer(addr, segments, "perform transaction")?;
+        Ok(data.iter().map(|buf| PyBytes::new(py, buf)).collect())
+    }
+
+    /// Scans the bus for devices by probing every address in the given range.
+    ///
+    /// Parameters:
+    /// - `start` (int): The first address to probe (default is 0x03).
+    /// - `end` (int): The last address to probe (default is 0x77).
+    /// - `probe_mode` (I2CProbeMode): How each address is probed. AUTO uses a read for the EEPROM (0x50 - 0x5F) and
+    ///   0x30 - 0x37 ranges and a quick write everywhere else, like i2cdetect (default is AUTO).
+    ///
+    /// Returns:
+    /// - `list[int]`: The addresses of the devices that responded.
+    ///
+    /// Example usage:
+    /// ```python
+    /// devices = i2c.scan()
+    /// ```
+    #[pyo3(signature = (start = 0x03, end = 0x77, probe_mode = I2CProbeMode::AUTO))]
+    fn scan(&self, start: u16, end: u16, probe_mode: I2CProbeMode) -> PyResult<Vec<u16>> {
+        if start > end || end > 0x7F {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Invalid address range {:#04x} - {:#04x}", start, end)));
+        }
+        let bus = self.bus()?;
+        release_gil(|| {
+            let mut found = Vec::new();
+            for addr in start..=end {
+                let use_read = match probe_mode {
+                    I2CProbeMode::AUTO => (0x30..=0x37).contains(&addr) || (0x50..=0x5F).contains(&addr),
+                    I2CProbeMode::QUICK_WRITE => false,
+                    I2CProbeMode::READ => true,
+                };
+                let result = if use_read { bus.transfer(addr as u8, &[Segment::Read(1)], true) } else { bus.transfer(addr as u8, &[], false) };
+                match result {
+                    Ok(_) => found.push(addr),
+                    Err(BusError::AddressNack(_)) => {}
+                    Err(e) => return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Failed to scan the bus: {}", e))),
+                }
+            }
+            Ok(found)
+        })
+    }
+}
@@ -0,0 +1,174 @@
+use crate::backend::OutputPin;
+use crate::timing::{self, TimedCondvar};
+use once_cell::sync::Lazy;
+use rppal::gpio::Level;
+use std::collections::HashMap;
+use std::io;
+use std::os::unix::thread::JoinHandleExt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+
+// Longest time the engine waits before picking up changed channels
+const MAX_WAIT: Duration = Duration::from_millis(10);
+
+// Default time the engine busy-waits before every edge
+const DEFAULT_BUSY_WAIT_NS: u64 = 100_000;
+
+
+/// A pin driven by the engine. The level of every channel is computed from the common time base, so channels with
+/// the same frequency keep their phase offset.
+struct Channel {
+    pin: Arc<Mutex<OutputPin>>,
+    period: f64,
+    duty_cycle: f64,
+    phase: f64,
+    level: Option<Level>,
+}
+
+
+impl Channel {
+    /// Returns the level of the channel and the time until its next edge, both in seconds since the epoch.
+    fn level_at(&self, elapsed: f64) -> (Level, f64) {
+        let position = (elapsed - self.phase * self.period).rem_euclid(self.period);
+        let high_time = self.duty_cycle * self.period;
+        if position < high_time {
+            (Level::High, high_time - position)
+        } else {
+            (Level::Low, self.period - position)
+        }
+    }
+}
+
+
+/// How late the engine was at the edges it drove, in seconds.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EngineStats {
+    pub edges: u64,
+    pub total_lateness: f64,
+    pub max_lateness: f64,
+}
+
+
+/// Software PWM engine driving all its channels from a single thread and a common time base. The thread sleeps until
+/// shortly before every edge and busy-waits for the rest, so edges are written within microseconds of their deadline.
+pub struct SoftPwmEngine {
+    channels: Mutex<HashMap<u8, Channel>>,
+    changed: TimedCondvar,
+    epoch: Instant,
+    busy_wait_ns: AtomicU64,
+    stats: Mutex<EngineStats>,
+    thread: Mutex<Option<libc::pthread_t>>,
+}
+
+
+pub static SOFT_PWM_ENGINE: Lazy<Arc<SoftPwmEngine>> = Lazy::new(|| {
+    let engine = Arc::new(SoftPwmEngine {
+        channels: Mutex::new(HashMap::new()),
+        changed: TimedCondvar::default(),
+        epoch: timing::now(),
+        busy_wait_ns: AtomicU64::new(DEFAULT_BUSY_WAIT_NS),
+        stats: Mutex::new(EngineStats::default()),
+        thread: Mutex::new(None),
+    });
+    let runner = Arc::clone(&engine);
+    let handle = timing::spawn(move || runner.run());
+    *engine.thread.lock().unwrap() = Some(handle.as_pthread_t());
+    engine
+});
+
+
+impl SoftPwmEngine {
+    /// Adds or updates the channel of a pin.
+    ///
+    /// Parameters:
+    /// - `frequency` is in Hertz.
+    /// - `duty_cycle` is the fraction of the period the pin is high, from 0 to 1.
+    /// - `phase` is the offset of the period from the common time base, as a fraction of the period from 0 to 1.
+    pub fn set_channel(&self, pin_num: u8, pin: Arc<Mutex<OutputPin>>, frequency: f64, duty_cycle: f64, phase: f64) {
+        let mut channels = self.channels.lock().unwrap();
+        let level = channels.get(&pin_num).and_then(|channel| channel.level);
+        channels.insert(pin_num, Channel {
+            pin,
+            period: 1f64 / frequency,
+            duty_cycle,
+            phase,
+            level,
+        });
+        self.changed.notify();
+    }
+
+    /// Stops driving the pin, it keeps its current level.
+    pub fn remove_channel(&self, pin_num: u8) {
+        self.channels.lock().unwrap().remove(&pin_num);
+    }
+
+    /// Sets the time the engine busy-waits before every edge, zero only sleeps.
+    pub fn set_busy_wait(&self, busy_wait: Duration) {
+        self.busy_wait_ns.store(busy_wait.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Runs the engine thread with the SCHED_FIFO real time policy at the given priority from 1 to 99, or with the
+    /// normal policy for None. Real time priorities need root or the CAP_SYS_NICE capability.
+    pub fn set_priority(&self, priority: Option<i32>) -> io::Result<()> {
+        let thread = self.thread.lock().unwrap().expect("The engine thread is started with the engine");
+        let (policy, priority) = match priority {
+            Some(priority) => (libc::SCHED_FIFO, priority),
+            None => (libc::SCHED_OTHER, 0),
+        };
+        let param = libc::sched_param { sched_priority: priority };
+        let result = unsafe { libc::pthread_setschedparam(thread, policy, &param) };
+        if result != 0 {
+            return Err(io::Error::from_raw_os_error(result));
+        }
+        Ok(())
+    }
+
+    pub fn stats(&self) -> EngineStats {
+        *self.stats.lock().unwrap()
+    }
+
+    pub fn reset_stats(&self) {
+        *self.stats.lock().unwrap() = EngineStats::default();
+    }
+
+    fn run(&self) {
+        let mut deadline: Option<Instant> = None;
+        loop {
+            let mut channels = self.channels.lock().unwrap();
+            while channels.is_empty() {
+                channels = self.changed.wait(channels, None);
+            }
+
+            let now = timing::now();
+            let elapsed = (now - self.epoch).as_secs_f64();
+            let mut next_edge = MAX_WAIT.as_secs_f64();
+            let mut wrote_edge = false;
+            for channel in channels.values_mut() {
+                let (level, until_edge) = channel.level_at(elapsed);
+                if channel.level != Some(level) {
+                    channel.pin.lock().unwrap().write(level);
+                    channel.level = Some(level);
+                    wrote_edge = true;
+                }
+                next_edge = next_edge.min(until_edge);
+            }
+            drop(channels);
+
+            if let (true, Some(deadline)) = (wrote_edge, deadline) {
+                let lateness = now.saturating_duration_since(deadline).as_secs_f64();
+                let mut stats = self.stats.lock().unwrap();
+                stats.edges += 1;
+                stats.total_lateness += lateness;
+                stats.max_lateness = stats.max_lateness.max(lateness);
+            }
+
+            // An edge time rounded down to now would wake the engine at the same instant again, which the virtual clock
+            // never moves past
+            let next_deadline = (self.epoch + Duration::from_secs_f64(elapsed + next_edge)).max(now + Duration::from_nanos(1));
+            deadline = Some(next_deadline);
+            timing::sleep_until(next_deadline, Duration::from_nanos(self.busy_wait_ns.load(Ordering::Relaxed)));
+        }
+    }
+}
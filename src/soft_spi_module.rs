@@ -0,0 +1,450 @@
+use crate::backend::{InputPin, OutputPin};
+use crate::gpio_module::GPIOManager;
+use crate::i2c_module::extract_bytes;
+use crate::spi_device_module::SPIDevice;
+use crate::timing::wait_until;
+use crate::{frequency_to_period_checked, release_gil, us_to_duration_checked, InternPullResistorState};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use rppal::gpio::Level;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+
+/// The SCLK, MOSI, MISO and CS pins of a bus.
+type SPIPins = (u8, Option<u8>, Option<u8>, Option<u8>);
+
+
+#[pyclass(eq, eq_int)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[allow(non_camel_case_types)]
+/// Enum representing the clock polarity (CPOL) and phase (CPHA) of an SPI bus.
+pub enum SPIMode {
+    /// The clock idles low and data is sampled on the rising edge.
+    MODE0,
+    /// The clock idles low and data is sampled on the falling edge.
+    MODE1,
+    /// The clock idles high and data is sampled on the falling edge.
+    MODE2,
+    /// The clock idles high and data is sampled on the rising edge.
+    MODE3,
+}
+
+
+impl SPIMode {
+    /// Whether the clock idles high.
+    fn cpol(self) -> bool {
+        matches!(self, SPIMode::MODE2 | SPIMode::MODE3)
+    }
+
+    /// Whether data is sampled on the trailing edge of the clock pulse.
+    fn cpha(self) -> bool {
+        matches!(self, SPIMode::MODE1 | SPIMode::MODE3)
+    }
+}
+
+
+/// A chip select line, driven around every transfer to its device.
+pub(crate) struct ChipSelect {
+    pub(crate) pin_num: u8,
+    pin: Arc<Mutex<OutputPin>>,
+    active_high: bool,
+    // The delays from selecting the device to the first clock edge and from the last clock edge to deselecting it, half
+    // a clock period when None
+    setup: Option<Duration>,
+    hold: Option<Duration>,
+}
+
+
+impl ChipSelect {
+    /// Wraps a claimed pin and moves it to the deselected level, since pins are claimed low.
+    pub(crate) fn new(pin_num: u8, pin: Arc<Mutex<OutputPin>>, active_high: bool, setup: Option<Duration>, hold: Option<Duration>) -> Self {
+        let cs = Self { pin_num, pin, active_high, setup, hold };
+        cs.select(false);
+        cs
+    }
+
+    fn select(&self, selected: bool) {
+        self.pin.lock().unwrap().write(if selected == self.active_high { Level::High } else { Level::Low });
+    }
+}
+
+
+/// An SPI bus bit-banged on plain output and input pins.
+pub(crate) struct SoftBus {
+    sclk_pin: u8,
+    mosi_pin: Option<u8>,
+    miso_pin: Option<u8>,
+    sclk: Arc<Mutex<OutputPin>>,
+    mosi: Option<Arc<Mutex<OutputPin>>>,
+    miso: Option<Arc<Mutex<InputPin>>>,
+    // The chip select given to the bus itself, used by the transfers of the SoftSPI
+    cs: Option<ChipSelect>,
+    // The chip selects of the devices created with SoftSPI.device, released with the bus
+    pub(crate) device_pins: Vec<u8>,
+    mode: SPIMode,
+    half_period: Duration,
+    lsb_first: bool,
+}
+
+
+/// A bus shared by a SoftSPI and its devices, None once the bus is closed.
+pub(crate) type SharedBus = Arc<Mutex<Option<SoftBus>>>;
+
+
+impl SoftBus {
+    /// Shifts the bytes out on MOSI while shifting as many bytes in from MISO, without touching the chip select. The
+    /// edges are timed from the start of the transfer, so a late edge doesn't stretch the rest of the clock.
+    fn exchange(&self, data: &[u8]) -> Vec<u8> {
+        let mut sclk = self.sclk.lock().unwrap();
+        let mut mosi = self.mosi.as_ref().map(|mosi| mosi.lock().unwrap());
+        let miso = self.miso.as_ref().map(|miso| miso.lock().unwrap());
+        let idle = self.mode.cpol();
+        let level = |high: bool| if high { Level::High } else { Level::Low };
+        let mut edge = Instant::now();
+        let mut received = Vec::with_capacity(data.len());
+        for byte in data {
+            let mut input = 0u8;
+            for index in 0..8 {
+                let bit = if self.lsb_first { index } else { 7 - index };
+                // With CPHA 0 the data is set up half a period before the leading edge, with CPHA 1 on the leading edge
+                if !self.mode.cpha() {
+                    if let Some(mosi) = mosi.as_mut() {
+                        mosi.write(level(byte & (1 << bit) != 0));
+                    }
+                    edge += self.half_period;
+                    wait_until(edge);
+                    sclk.write(level(!idle));
+                } else {
+                    sclk.write(level(!idle));
+                    if let Some(mosi) = mosi.as_mut() {
+                        mosi.write(level(byte & (1 << bit) != 0));
+                    }
+                    edge += self.half_period;
+                    wait_until(edge);
+                    sclk.write(level(idle));
+                }
+                if miso.as_ref().is_some_and(|miso| miso.is_high()) {
+                    input |= 1 << bit;
+                }
+                edge += self.half_period;
+                wait_until(edge);
+                if !self.mode.cpha() {
+                    sclk.write(level(idle));
+                }
+            }
+            received.push(input);
+        }
+        received
+    }
+
+    /// Runs the parts of a transfer with the device of the chip select selected, returning what was received during
+    /// each part.
+    pub(crate) fn transfer(&self, cs: Option<&ChipSelect>, parts: &[&[u8]]) -> Vec<Vec<u8>> {
+        if let Some(cs) = cs {
+            cs.select(true);
+            wait_until(Instant::now() + cs.setup.unwrap_or(self.half_period));
+        }
+        let received = parts.iter().map(|part| self.exchange(part)).collect();
+        if let Some(cs) = cs {
+            wait_until(Instant::now() + cs.hold.unwrap_or(self.half_period));
+            cs.select(false);
+        }
+        received
+    }
+}
+
+
+/// Runs an operation on the bus with the GIL released, failing when it is closed.
+pub(crate) fn with_bus<T: Send>(bus: &SharedBus, op: impl FnOnce(&mut SoftBus) -> PyResult<T> + Send) -> PyResult<T> {
+    release_gil(|| {
+        let mut bus = bus.lock().unwrap();
+        let bus = bus.as_mut().ok_or_else(|| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("The software SPI bus is closed"))?;
+        op(bus)
+    })
+}
+
+
+/// Claims the pins in order, the pins already claimed are released again when one of them can't be.
+fn claim_pins<T>(gpio_manager: &GPIOManager, pins: &[u8], mut claim: impl FnMut(u8) -> PyResult<T>) -> PyResult<Vec<T>> {
+    let mut claimed = Vec::new();
+    for pin_num in pins {
+        match claim(*pin_num) {
+            Ok(pin) => claimed.push(pin),
+            Err(e) => {
+                for pin_num in &pins[..claimed.len()] {
+                    gpio_manager.reset_pin(*pin_num)?;
+                }
+                return Err(e);
+            }
+        }
+    }
+    Ok(claimed)
+}
+
+
+fn frequency_to_half_period(frequency_hz: f64) -> PyResult<Duration> {
+    Ok(frequency_to_period_checked("Frequency", frequency_hz)? / 2)
+}
+
+
+#[pyclass]
+/// SoftSPI is an SPI master bit-banged on any GPIO pins, for when the pins of the hardware SPI buses are taken or a bus
+/// is needed on other pins. The clock is generated in Rust with the GIL released and reaches a few hundred kHz.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// spi = gpio_manager.SoftSPI(sclk_pin=21, mosi_pin=20, miso_pin=19, cs_pin=16, mode=gpio_manager.SPIMode.MODE0)
+/// response = spi.transfer(b'\x9f\x00\x00\x00')
+/// spi.close()
+/// ```
+pub struct SoftSPI {
+    bus: SharedBus,
+}
+
+
+impl SoftSPI {
+    /// Runs an operation on the open bus with the GIL released.
+    fn with_bus<T: Send>(&self, op: impl FnOnce(&mut SoftBus) -> T + Send) -> PyResult<T> {
+        with_bus(&self.bus, |bus| Ok(op(bus)))
+    }
+}
+
+
+#[pymethods]
+impl SoftSPI {
+    #[new]
+    /// Takes the pins of a software SPI bus. MOSI and MISO can be left out for devices that are only written to or only
+    /// read from, and the chip select for a single device that is always selected.
+    ///
+    /// Parameters:
+    /// - `sclk_pin` (int): The GPIO pin of the clock line.
+    /// - `mosi_pin` (int): The GPIO pin data is sent on (default is None).
+    /// - `miso_pin` (int): The GPIO pin data is received on (default is None).
+    /// - `cs_pin` (int): The GPIO pin of the chip select (default is None).
+    /// - `mode` (SPIMode): The clock polarity and phase (default is MODE0).
+    /// - `frequency_hz` (float): The clock frequency, a transfer runs slower when the pins can't be switched that fast
+    ///   (default is 500000).
+    /// - `lsb_first` (bool): Send and receive the least significant bit of each byte first (default is False).
+    /// - `cs_active_high` (bool): Select the device by driving the chip select high instead of low (default is False).
+    ///
+    /// Example usage:
+    /// ```python
+    /// spi = gpio_manager.SoftSPI(21, mosi_pin=20, miso_pin=19, cs_pin=16, frequency_hz=250_000)
+    /// ```
+    #[pyo3(signature = (sclk_pin, mosi_pin = None, miso_pin = None, cs_pin = None, mode = SPIMode::MODE0, frequency_hz = 500_000f64,
+    lsb_first = false, cs_active_high = false))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(sclk_pin: u8, mosi_pin: Option<u8>, miso_pin: Option<u8>, cs_pin: Option<u8>, mode: SPIMode, frequency_hz: f64, lsb_first: bool,
+           cs_active_high: bool) -> PyResult<Self> {
+        let half_period = frequency_to_half_period(frequency_hz)?;
+        let mut pins = vec![sclk_pin];
+        pins.extend(mosi_pin.iter().chain(cs_pin.iter()).chain(miso_pin.iter()));
+        if let Some(pin_num) = pins.iter().enumerate().find_map(|(index, pin_num)| pins[..index].contains(pin_num).then_some(pin_num)) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("The pins must be different, The value {} does not meet this condition", pin_num)));
+        }
+
+        let gpio_manager = GPIOManager::new_rust_reference();
+        let output_pins = &pins[..pins.len() - miso_pin.iter().count()];
+        let mut outputs = claim_pins(&gpio_manager, output_pins, |pin_num| gpio_manager.claim_output_pin(pin_num))?.into_iter();
+        let miso = match miso_pin.map(|pin_num| gpio_manager.claim_input_pin(pin_num, InternPullResistorState::EXTERNAL)).transpose() {
+            Ok(miso) => miso,
+            Err(e) => {
+                for pin_num in output_pins {
+                    gpio_manager.reset_pin(*pin_num)?;
+                }
+                return Err(e);
+            }
+        };
+        let bus = SoftBus {
+            sclk_pin,
+            mosi_pin,
+            miso_pin,
+            sclk: outputs.next().unwrap(),
+            mosi: mosi_pin.and(outputs.next()),
+            cs: cs_pin.zip(outputs.next()).map(|(pin_num, pin)| ChipSelect::new(pin_num, pin, cs_active_high, None, None)),
+            miso,
+            device_pins: Vec::new(),
+            mode,
+            half_period,
+            lsb_first,
+        };
+        // The pins are claimed low, the clock is moved to its idle level right away
+        bus.sclk.lock().unwrap().write(if mode.cpol() { Level::High } else { Level::Low });
+        Ok(Self { bus: Arc::new(Mutex::new(Some(bus))) })
+    }
+
+    /// Releases the pins of the bus, including the chip selects of its devices.
+    ///
+    /// Example usage:
+    /// ```python
+    /// spi.close()
+    /// ```
+    fn close(&self) -> PyResult<()> {
+        if let Some(bus) = self.bus.lock().unwrap().take() {
+            let gpio_manager = GPIOManager::new_rust_reference();
+            let pins = [Some(bus.sclk_pin), bus.mosi_pin, bus.miso_pin, bus.cs.as_ref().map(|cs| cs.pin_num)];
+            for pin_num in pins.into_iter().flatten().chain(bus.device_pins) {
+                gpio_manager.reset_pin(pin_num)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates a handle for a device on the bus with its own chip select, which is selected around every transfer made
+    /// through the handle. Any number of devices can share the bus, each on a free pin.
+    ///
+    /// Parameters:
+    /// - `cs_pin` (int): The GPIO pin of the chip select of the device.
+    /// - `cs_active_high` (bool): Select the device by driving the chip select high instead of low (default is False).
+    /// - `setup_us` (float): The delay from selecting the device to the first clock edge in microseconds, half a clock
+    ///   period when None (default is None).
+    /// - `hold_us` (float): The delay from the last clock edge to deselecting the device in microseconds, half a clock
+    ///   period when None (default is None).
+    ///
+    /// Returns:
+    /// - `SPIDevice`: The device handle.
+    ///
+    /// Example usage:
+    /// ```python
+    /// adc = spi.device(cs_pin=16)
+    /// flash = spi.device(cs_pin=12, setup_us=5, hold_us=5)
+    /// ```
+    #[pyo3(signature = (cs_pin, cs_active_high = false, setup_us = None, hold_us = None))]
+    fn device(&self, cs_pin: u8, cs_active_high: bool, setup_us: Option<f64>, hold_us: Option<f64>) -> PyResult<SPIDevice> {
+        let delay = |name: &str, us: Option<f64>| us.map(|us| us_to_duration_checked(name, us)).transpose();
+        let (setup, hold) = (delay("Setup time", setup_us)?, delay("Hold time", hold_us)?);
+        let pin = GPIOManager::new_rust_reference().claim_output_pin(cs_pin)?;
+        let cs = ChipSelect::new(cs_pin, pin, cs_active_high, setup, hold);
+        let mut bus = self.bus.lock().unwrap();
+        match bus.as_mut() {
+            Some(bus) => bus.device_pins.push(cs_pin),
+            None => {
+                GPIOManager::new_rust_reference().reset_pin(cs_pin)?;
+                return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("The software SPI bus is closed"));
+            }
+        }
+        Ok(SPIDevice::new(Arc::clone(&self.bus), cs))
+    }
+
+    /// Returns the SCLK, MOSI, MISO and CS pins, None for the lines that aren't used.
+    fn get_pins(&self) -> PyResult<SPIPins> {
+        self.with_bus(|bus| (bus.sclk_pin, bus.mosi_pin, bus.miso_pin, bus.cs.as_ref().map(|cs| cs.pin_num)))
+    }
+
+    /// Sets the clock polarity and phase, the clock is moved to its new idle level.
+    ///
+    /// Parameters:
+    /// - `mode` (SPIMode): The clock polarity and phase.
+    ///
+    /// Example usage:
+    /// ```python
+    /// spi.set_mode(gpio_manager.SPIMode.MODE3)
+    /// ```
+    fn set_mode(&self, mode: SPIMode) -> PyResult<()> {
+        self.with_bus(|bus| {
+            bus.mode = mode;
+            bus.sclk.lock().unwrap().write(if mode.cpol() { Level::High } else { Level::Low });
+        })
+    }
+
+    /// Returns the clock polarity and phase.
+    fn get_mode(&self) -> PyResult<SPIMode> {
+        self.with_bus(|bus| bus.mode)
+    }
+
+    /// Sets the clock frequency.
+    ///
+    /// Parameters:
+    /// - `frequency_hz` (float): The clock frequency.
+    ///
+    /// Example usage:
+    /// ```python
+    /// spi.set_frequency(100_000)
+    /// ```
+    fn set_frequency(&self, frequency_hz: f64) -> PyResult<()> {
+        let half_period = frequency_to_half_period(frequency_hz)?;
+        self.with_bus(|bus| bus.half_period = half_period)
+    }
+
+    /// Returns the clock frequency.
+    fn get_frequency(&self) -> PyResult<f64> {
+        self.with_bus(|bus| 0.5 / bus.half_period.as_secs_f64())
+    }
+
+    /// Sends the data while receiving as many bytes, with the device selected for the whole transfer.
+    ///
+    /// Parameters:
+    /// - `data` (bytes | bytearray | memoryview | list[int]): The data to send.
+    ///
+    /// Returns:
+    /// - `bytes`: The data received, as long as the data sent. Without a MISO pin every byte is 0.
+    ///
+    /// Example usage:
+    /// ```python
+    /// response = spi.transfer(b'\x9f\x00\x00\x00')
+    /// ```
+    fn transfer<'py>(&self, py: Python<'py>, data: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyBytes>> {
+        let data = extract_bytes(data)?;
+        let received = self.with_bus(|bus| bus.transfer(bus.cs.as_ref(), &[&data]))?;
+        Ok(PyBytes::new(py, &received[0]))
+    }
+
+    /// Sends data to the device, discarding the bytes received.
+    ///
+    /// Parameters:
+    /// - `data` (bytes | bytearray | memoryview | list[int]): The data to send.
+    ///
+    /// Example usage:
+    /// ```python
+    /// spi.write(b'\x06')
+    /// ```
+    fn write(&self, data: &Bound<'_, PyAny>) -> PyResult<()> {
+        let data = extract_bytes(data)?;
+        self.with_bus(|bus| {
+            bus.transfer(bus.cs.as_ref(), &[&data]);
+        })
+    }
+
+    /// Reads data from the device, sending the fill byte for every byte read.
+    ///
+    /// Parameters:
+    /// - `length` (int): The number of bytes to read.
+    /// - `fill` (int): The byte sent while reading (default is 0).
+    ///
+    /// Returns:
+    /// - `bytes`: The data read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// data = spi.read(4, fill=0xFF)
+    /// ```
+    #[pyo3(signature = (length, fill = 0))]
+    fn read<'py>(&self, py: Python<'py>, length: usize, fill: u8) -> PyResult<Bound<'py, PyBytes>> {
+        let received = self.with_bus(|bus| bus.transfer(bus.cs.as_ref(), &[&vec![fill; length]]))?;
+        Ok(PyBytes::new(py, &received[0]))
+    }
+
+    /// Sends data and then reads the response, with the device selected for both, as most commands of flash chips and
+    /// sensors need.
+    ///
+    /// Parameters:
+    /// - `write_data` (bytes | bytearray | memoryview | list[int]): The data to send.
+    /// - `read_length` (int): The number of bytes to read after it.
+    /// - `fill` (int): The byte sent while reading (default is 0).
+    ///
+    /// Returns:
+    /// - `bytes`: The data read after the data was sent.
+    ///
+    /// Example usage:
+    /// ```python
+    /// jedec_id = spi.write_read(b'\x9f', 3)
+    /// ```
+    #[pyo3(signature = (write_data, read_length, fill = 0))]
+    fn write_read<'py>(&self, py: Python<'py>, write_data: &Bound<'py, PyAny>, read_length: usize, fill: u8) -> PyResult<Bound<'py, PyBytes>> {
+        let write_data = extract_bytes(write_data)?;
+        let received = self.with_bus(|bus| bus.transfer(bus.cs.as_ref(), &[&write_data, &vec![fill; read_length]]))?;
+        Ok(PyBytes::new(py, &received[1]))
+    }
+}
@@ -0,0 +1,686 @@
+use crate::backend::{InputPin, OutputPin};
+use crate::gpio_module::GPIOManager;
+use crate::i2c_module::extract_bytes;
+use crate::timing::{self, wait_until};
+use crate::{ms_to_duration_checked, release_gil, us_to_duration_checked, InternPullResistorState};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use rppal::gpio::{Level, Trigger};
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+
+// How late an edge may be delivered after it happened, a frame is only completed by the time once this has passed
+const EDGE_LATENCY: Duration = Duration::from_millis(2);
+// The received bytes kept until they are read, the oldest bytes are dropped beyond it
+const RX_BUFFER_SIZE: usize = 4096;
+// With flow control RTS is released when the buffer fills up to the high mark, and asserted again once it is read down
+// to the low mark
+const RTS_HIGH_WATER: usize = RX_BUFFER_SIZE * 3 / 4;
+const RTS_LOW_WATER: usize = RX_BUFFER_SIZE / 4;
+// How often CTS is checked while the other device holds it released
+const CTS_POLL: Duration = Duration::from_micros(50);
+
+
+/// The bytes received on the RX pin, filled by the decoder thread.
+#[derive(Default)]
+struct RxState {
+    data: VecDeque<u8>,
+    framing_errors: u64,
+    closed: bool,
+    // The RTS pin of flow control, asserted (low) while rts_ready is set
+    rts: Option<Arc<Mutex<OutputPin>>>,
+    rts_ready: bool,
+}
+
+
+impl RxState {
+    /// Asserts or releases RTS after the buffer filled up or was read.
+    fn update_rts(&mut self) {
+        if let Some(rts) = &self.rts {
+            let ready = if self.rts_ready { self.data.len() < RTS_HIGH_WATER } else { self.data.len() <= RTS_LOW_WATER };
+            if ready != self.rts_ready {
+                self.rts_ready = ready;
+                rts.lock().unwrap().write(if ready { Level::Low } else { Level::High });
+            }
+        }
+    }
+
+    /// Takes the bytes up to the index from the front of the buffer.
+    fn take(&mut self, length: usize) -> Vec<u8> {
+        let data = self.data.drain(..length.min(self.data.len())).collect();
+        self.update_rts();
+        data
+    }
+}
+
+
+/// Waits until `complete` finds the length of the data to return in the received bytes, then takes it. When the
+/// deadline passes or the port is closed first, the bytes received so far are taken up to the limit.
+fn wait_for_data(buffer: &RxBuffer, limit: usize, deadline: Option<Instant>, complete: impl Fn(&VecDeque<u8>) -> Option<usize>) -> Vec<u8> {
+    let mut state = buffer.state.lock().unwrap();
+    loop {
+        if let Some(length) = complete(&state.data) {
+            return state.take(length);
+        }
+        let now = Instant::now();
+        if state.closed || deadline.is_some_and(|deadline| now >= deadline) {
+            return state.take(limit);
+        }
+        state = match deadline {
+            Some(deadline) => buffer.received.wait_timeout(state, deadline - now).unwrap().0,
+            None => buffer.received.wait(state).unwrap(),
+        };
+    }
+}
+
+
+/// Returns the index of the end of the first occurrence of the terminator in the data.
+fn find_terminator(data: &VecDeque<u8>, terminator: &[u8]) -> Option<usize> {
+    if terminator.is_empty() || data.len() < terminator.len() {
+        return None;
+    }
+    (0..=data.len() - terminator.len())
+        .find(|start| terminator.iter().enumerate().all(|(offset, byte)| data[start + offset] == *byte))
+        .map(|start| start + terminator.len())
+}
+
+
+#[derive(Default)]
+struct RxBuffer {
+    state: Mutex<RxState>,
+    received: Condvar,
+}
+
+
+/// A frame being received, from the falling edge of its start bit.
+struct Frame {
+    start_ns: i128,
+    edges: Vec<(i128, bool)>,
+}
+
+
+impl Frame {
+    /// The level of the line at the time, the start bit is low until the first edge after it.
+    fn level_at(&self, time_ns: i128) -> bool {
+        self.edges.iter().take_while(|(edge_ns, _)| *edge_ns <= time_ns).last().is_some_and(|(_, level)| *level)
+    }
+
+    /// Samples the data bits and the stop bit in their middles, returning None when the stop bit is low.
+    fn decode(&self, bit_ns: i128) -> Option<u8> {
+        let sample = |bit: i128| self.level_at(self.start_ns + (2 * bit + 1) * bit_ns / 2);
+        let byte = (0..8).filter(|bit| sample(*bit as i128 + 1)).fold(0u8, |byte, bit| byte | 1 << bit);
+        sample(9).then_some(byte)
+    }
+}
+
+
+/// Decodes 8N1 frames from the edges of the RX pin until the pin stops sending them. A frame is completed by the first
+/// edge after its stop bit, or by the time when the line stays high after it.
+fn decode_frames(edges: Receiver<(i128, bool)>, buffer: Arc<RxBuffer>, bit_ns: i128) {
+    let mut frame: Option<Frame> = None;
+    loop {
+        let edge = match edges.recv_timeout(EDGE_LATENCY) {
+            Ok(edge) => Some(edge),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        let now_ns = edge.map_or_else(|| timing::monotonic_ns() - EDGE_LATENCY.as_nanos() as i128, |(edge_ns, _)| edge_ns);
+        if frame.as_ref().is_some_and(|frame| now_ns > frame.start_ns + 19 * bit_ns / 2) {
+            let mut state = buffer.state.lock().unwrap();
+            match frame.take().unwrap().decode(bit_ns) {
+                Some(byte) => {
+                    if state.data.len() == RX_BUFFER_SIZE {
+                        state.data.pop_front();
+                    }
+                    state.data.push_back(byte);
+                    state.update_rts();
+                    buffer.received.notify_all();
+                }
+                None => state.framing_errors += 1,
+            }
+        }
+        match (edge, frame.as_mut()) {
+            (Some(edge), Some(frame)) => frame.edges.push(edge),
+            (Some((edge_ns, false)), None) => frame = Some(Frame { start_ns: edge_ns, edges: Vec::new() }),
+            _ => {}
+        }
+    }
+}
+
+
+fn check_baud_rate(baud_rate: u32) -> PyResult<()> {
+    if !(300..=38_400).contains(&baud_rate) {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Baud rate must be between 300 and 38400, The value {} does not meet this condition", baud_rate)));
+    }
+    Ok(())
+}
+
+
+#[pyclass]
+/// SoftUART is a serial port bit-banged on any GPIO pins, for a second serial device when the hardware UART is used by
+/// the console. Frames are 8N1. The TX pin is driven with the timing done in Rust, and the RX pin is decoded from the
+/// timestamps of its edges, so bytes are received in the background, up to 4096 bytes are kept until they are read.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// uart = gpio_manager.SoftUART(tx_pin=23, rx_pin=24, baud_rate=9600)
+/// uart.write(b'AT\r\n')
+/// response = uart.read(4)
+/// uart.close()
+/// ```
+pub struct SoftUART {
+    tx_pin: Option<u8>,
+    rx_pin: Option<u8>,
+    baud_rate: u32,
+    timeout: Mutex<Option<Duration>>,
+    port: Mutex<Port>,
+    buffer: Arc<RxBuffer>,
+}
+
+
+/// The driver enable pin of an RS-485 transceiver, asserted while the port sends.
+struct DirectionControl {
+    pin_num: u8,
+    pin: Arc<Mutex<OutputPin>>,
+    active_high: bool,
+    // The delays from asserting the pin to the first start bit and from the last stop bit to releasing it
+    pre_delay: Duration,
+    post_delay: Duration,
+}
+
+
+impl DirectionControl {
+    fn assert(&self, pin: &mut OutputPin, asserted: bool) {
+        pin.write(if asserted == self.active_high { Level::High } else { Level::Low });
+    }
+}
+
+
+/// The pins of an open port and the thread decoding the RX pin, taken when the port is closed.
+#[derive(Default)]
+struct Port {
+    tx: Option<Arc<Mutex<OutputPin>>>,
+    rx: Option<Arc<Mutex<InputPin>>>,
+    de: Option<Arc<DirectionControl>>,
+    // The pins of flow control, the RTS pin itself is driven from the receive buffer
+    rts_pin: Option<u8>,
+    cts: Option<(u8, Arc<Mutex<InputPin>>)>,
+    decoder: Option<JoinHandle<()>>,
+}
+
+
+impl Port {
+    /// Stops decoding the RX pin, dropping the interrupt callback disconnects the decoder.
+    fn stop_decoder(&mut self) {
+        if let Some(rx) = &self.rx {
+            let _ = rx.lock().unwrap().clear_async_interrupt();
+        }
+        if let Some(decoder) = self.decoder.take() {
+            let _ = decoder.join();
+        }
+    }
+}
+
+
+fn timeout_to_duration(timeout_ms: Option<f64>) -> PyResult<Option<Duration>> {
+    Ok(timeout_ms.map(|timeout_ms| ms_to_duration_checked("Timeout", timeout_ms)).transpose()?)
+}
+
+
+impl SoftUART {
+    pub(crate) fn bit_period(&self) -> Duration {
+        Duration::from_secs_f64(1f64 / self.baud_rate as f64)
+    }
+
+    /// Sends the bytes, with the driver enable of RS-485 mode and the CTS of flow control handled. Called with the GIL
+    /// released.
+    pub(crate) fn send(&self, data: &[u8]) -> PyResult<()> {
+        let (tx, de, cts) = {
+            let port = self.port.lock().unwrap();
+            (port.tx.clone(), port.de.clone(), port.cts.as_ref().map(|(_, cts)| Arc::clone(cts)))
+        };
+        let tx = tx.ok_or_else(|| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("The software UART has no TX pin or is closed"))?;
+        let bit_period = self.bit_period();
+        let timeout = *self.timeout.lock().unwrap();
+        let mut tx = tx.lock().unwrap();
+        let mut de_pin = de.as_ref().map(|de| de.pin.lock().unwrap());
+        if let (Some(de), Some(de_pin)) = (&de, de_pin.as_mut()) {
+            de.assert(de_pin, true);
+            wait_until(Instant::now() + de.pre_delay);
+        }
+        // The bits are timed from the start of the write, so a late bit doesn't shift the ones after it
+        let mut edge = Instant::now();
+        let mut result = Ok(());
+        for (index, byte) in data.iter().enumerate() {
+            if let Some(cts) = &cts {
+                let cts = cts.lock().unwrap();
+                if cts.is_high() {
+                    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+                    while cts.is_high() {
+                        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                            result = Err(PyErr::new::<pyo3::exceptions::PyTimeoutError, _>(format!("CTS wasn't asserted within the timeout, {} of {} bytes were sent", index, data.len())));
+                            break;
+                        }
+                        thread::sleep(CTS_POLL);
+                    }
+                    if result.is_err() {
+                        break;
+                    }
+                    edge = Instant::now();
+                }
+            }
+            let bits = (0..8).map(|bit| byte & (1 << bit) != 0);
+            for bit in std::iter::once(false).chain(bits).chain(std::iter::once(true)) {
+                tx.write(if bit { Level::High } else { Level::Low });
+                edge += bit_period;
+                wait_until(edge);
+            }
+        }
+        // The driver is released as soon as the last stop bit ends
+        if let (Some(de), Some(de_pin)) = (&de, de_pin.as_mut()) {
+            wait_until(edge + de.post_delay);
+            de.assert(de_pin, false);
+        }
+        result
+    }
+
+    /// Reads exactly the given number of bytes, returning fewer only when the deadline passes or the port is closed
+    /// first. Called with the GIL released.
+    pub(crate) fn read_exact(&self, length: usize, deadline: Instant) -> PyResult<Vec<u8>> {
+        if self.rx_pin.is_none() {
+            return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("The software UART has no RX pin"));
+        }
+        Ok(wait_for_data(&self.buffer, length, Some(deadline), |data| (data.len() >= length).then_some(length)))
+    }
+
+    /// Discards the bytes received but not read yet.
+    pub(crate) fn clear_input(&self) {
+        self.buffer.state.lock().unwrap().take(RX_BUFFER_SIZE);
+    }
+
+    /// The time the port timeout ends at, for a read starting now.
+    fn deadline(&self) -> Option<Instant> {
+        self.timeout.lock().unwrap().map(|timeout| Instant::now() + timeout)
+    }
+
+    /// Releases the pins of flow control, RTS is detached from the receive buffer first.
+    fn release_flow_control(&self, rts_pin: Option<u8>, cts: Option<(u8, Arc<Mutex<InputPin>>)>) -> PyResult<()> {
+        let gpio_manager = GPIOManager::new_rust_reference();
+        if let Some(pin_num) = rts_pin {
+            self.buffer.state.lock().unwrap().rts = None;
+            gpio_manager.reset_pin(pin_num)?;
+        }
+        if let Some((pin_num, _)) = cts {
+            gpio_manager.reset_pin(pin_num)?;
+        }
+        Ok(())
+    }
+
+    /// Reads with wait_for_data, with the GIL released while waiting.
+    fn read_with(&self, limit: usize, deadline: Option<Instant>, complete: impl Fn(&VecDeque<u8>) -> Option<usize> + Send) -> PyResult<Vec<u8>> {
+        if self.rx_pin.is_none() {
+            return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("The software UART has no RX pin"));
+        }
+        let buffer = Arc::clone(&self.buffer);
+        Ok(release_gil(move || wait_for_data(&buffer, limit, deadline, complete)))
+    }
+}
+
+
+impl Drop for SoftUART {
+    fn drop(&mut self) {
+        self.port.lock().unwrap().stop_decoder();
+    }
+}
+
+
+#[pymethods]
+impl SoftUART {
+    #[new]
+    /// Takes the pins of a software serial port, either one can be left out for a port that only sends or only receives.
+    /// The TX pin idles high and the RX pin is pulled up.
+    ///
+    /// Parameters:
+    /// - `tx_pin` (int): The GPIO pin data is sent on (default is None).
+    /// - `rx_pin` (int): The GPIO pin data is received on (default is None).
+    /// - `baud_rate` (int): The baud rate, from 300 to 38400 (default is 9600).
+    /// - `timeout_ms` (float): How long reads wait for data, and writes wait for CTS with flow control, in
+    ///   milliseconds. None waits forever (default is None).
+    ///
+    /// Example usage:
+    /// ```python
+    /// uart = gpio_manager.SoftUART(tx_pin=23, rx_pin=24, baud_rate=19200, timeout_ms=1000)
+    /// ```
+    #[pyo3(signature = (tx_pin = None, rx_pin = None, baud_rate = 9600, timeout_ms = None))]
+    fn new(tx_pin: Option<u8>, rx_pin: Option<u8>, baud_rate: u32, timeout_ms: Option<f64>) -> PyResult<Self> {
+        check_baud_rate(baud_rate)?;
+        let timeout = timeout_to_duration(timeout_ms)?;
+        if tx_pin.is_none() && rx_pin.is_none() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("At least one of tx_pin and rx_pin must be given"));
+        }
+        if let Some(pin_num) = tx_pin.filter(|pin_num| rx_pin == Some(*pin_num)) {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("TX and RX must be different pins, The value {} does not meet this condition", pin_num)));
+        }
+        let gpio_manager = GPIOManager::new_rust_reference();
+        let tx = tx_pin.map(|pin_num| gpio_manager.claim_output_pin(pin_num)).transpose()?;
+        if let Some(tx) = &tx {
+            tx.lock().unwrap().write(Level::High);
+        }
+        let uart = Self { tx_pin, rx_pin, baud_rate, timeout: Mutex::new(timeout), port: Mutex::new(Port { tx, ..Port::default() }), buffer: Arc::default() };
+        if let Some(pin_num) = rx_pin {
+            let rx = match gpio_manager.claim_input_pin(pin_num, InternPullResistorState::PULLUP) {
+                Ok(rx) => rx,
+                Err(e) => {
+                    uart.close()?;
+                    return Err(e);
+                }
+            };
+            let (sender, edges) = mpsc::channel();
+            let interrupt = rx.lock().unwrap().set_async_interrupt(Trigger::Both, None, move |event| {
+                let _ = sender.send((event.timestamp.as_nanos() as i128, event.trigger == Trigger::RisingEdge));
+            });
+            uart.port.lock().unwrap().rx = Some(rx);
+            if let Err(e) = interrupt {
+                uart.close()?;
+                return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Error setting up async interrupt: {:?}", e)));
+            }
+            let (buffer, bit_ns) = (Arc::clone(&uart.buffer), uart.bit_period().as_nanos() as i128);
+            uart.port.lock().unwrap().decoder = Some(thread::spawn(move || decode_frames(edges, buffer, bit_ns)));
+        }
+        Ok(uart)
+    }
+
+    /// Stops receiving and releases the pins. Reads waiting for data return what was received, and the data not read
+    /// yet can still be read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// uart.close()
+    /// ```
+    fn close(&self) -> PyResult<()> {
+        let mut port = std::mem::take(&mut *self.port.lock().unwrap());
+        release_gil(|| port.stop_decoder());
+        {
+            let mut state = self.buffer.state.lock().unwrap();
+            state.closed = true;
+            self.buffer.received.notify_all();
+        }
+        let gpio_manager = GPIOManager::new_rust_reference();
+        if port.rx.is_some() {
+            gpio_manager.reset_pin(self.rx_pin.unwrap())?;
+        }
+        if port.tx.is_some() {
+            gpio_manager.reset_pin(self.tx_pin.unwrap())?;
+        }
+        if let Some(de) = port.de {
+            gpio_manager.reset_pin(de.pin_num)?;
+        }
+        self.release_flow_control(port.rts_pin, port.cts)
+    }
+
+    /// Sets how long reads wait for data, and writes wait for CTS with flow control.
+    ///
+    /// Parameters:
+    /// - `timeout_ms` (float): The timeout in milliseconds, 0 returns right away with the data already received and
+    ///   None waits forever.
+    ///
+    /// Example usage:
+    /// ```python
+    /// uart.set_timeout(500)
+    /// ```
+    #[pyo3(signature = (timeout_ms))]
+    fn set_timeout(&self, timeout_ms: Option<f64>) -> PyResult<()> {
+        *self.timeout.lock().unwrap() = timeout_to_duration(timeout_ms)?;
+        Ok(())
+    }
+
+    /// Returns the timeout in milliseconds, None when reads wait forever.
+    fn get_timeout(&self) -> Option<f64> {
+        self.timeout.lock().unwrap().map(|timeout| timeout.as_secs_f64() * 1000f64)
+    }
+
+    /// Enables RTS/CTS hardware flow control on the given pins, either one can be left out. RTS is asserted (low) while
+    /// the receive buffer has room, and released once it is three quarters full until it is read down to a quarter.
+    /// Writes wait for CTS to be asserted (low) by the other device before every byte. CTS is pulled up, so nothing is
+    /// sent while it isn't connected.
+    ///
+    /// Parameters:
+    /// - `rts_pin` (int): The GPIO pin of the RTS output, wired to the CTS input of the other device (default is None).
+    /// - `cts_pin` (int): The GPIO pin of the CTS input, wired to the RTS output of the other device (default is None).
+    ///
+    /// Example usage:
+    /// ```python
+    /// uart.enable_flow_control(rts_pin=17, cts_pin=27)
+    /// ```
+    #[pyo3(signature = (rts_pin = None, cts_pin = None))]
+    fn enable_flow_control(&self, rts_pin: Option<u8>, cts_pin: Option<u8>) -> PyResult<()> {
+        if rts_pin.is_none() && cts_pin.is_none() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("At least one of rts_pin and cts_pin must be given"));
+        }
+        {
+            let port = self.port.lock().unwrap();
+            if rts_pin.is_some() && port.rx.is_none() {
+                return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("RTS needs an RX pin, the software UART has none or is closed"));
+            }
+            if cts_pin.is_some() && port.tx.is_none() {
+                return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("CTS needs a TX pin, the software UART has none or is closed"));
+            }
+        }
+        self.disable_flow_control()?;
+        let gpio_manager = GPIOManager::new_rust_reference();
+        let rts = rts_pin.map(|pin_num| gpio_manager.claim_output_pin(pin_num)).transpose()?;
+        let cts = match cts_pin.map(|pin_num| gpio_manager.claim_input_pin(pin_num, InternPullResistorState::PULLUP)).transpose() {
+            Ok(cts) => cts,
+            Err(e) => {
+                if let Some(pin_num) = rts_pin {
+                    gpio_manager.reset_pin(pin_num)?;
+                }
+                return Err(e);
+            }
+        };
+        let mut port = self.port.lock().unwrap();
+        (port.rts_pin, port.cts) = (rts_pin, cts_pin.zip(cts));
+        let mut state = self.buffer.state.lock().unwrap();
+        // The pin is claimed low, which asserts it, so it is only released when the buffer is already full
+        (state.rts, state.rts_ready) = (rts, true);
+        state.update_rts();
+        Ok(())
+    }
+
+    /// Disables flow control and releases its pins.
+    ///
+    /// Example usage:
+    /// ```python
+    /// uart.disable_flow_control()
+    /// ```
+    fn disable_flow_control(&self) -> PyResult<()> {
+        let (rts_pin, cts) = {
+            let mut port = self.port.lock().unwrap();
+            (port.rts_pin.take(), port.cts.take())
+        };
+        self.release_flow_control(rts_pin, cts)
+    }
+
+    /// Returns the RTS and CTS pins of flow control, None for the pin that isn't used.
+    fn get_flow_control_pins(&self) -> (Option<u8>, Option<u8>) {
+        let port = self.port.lock().unwrap();
+        (port.rts_pin, port.cts.as_ref().map(|(pin_num, _)| *pin_num))
+    }
+
+    /// Returns the number of bytes received and not read yet.
+    ///
+    /// Example usage:
+    /// ```python
+    /// if uart.bytes_available() > 0:
+    ///     data = uart.read(uart.bytes_available())
+    /// ```
+    fn bytes_available(&self) -> usize {
+        self.buffer.state.lock().unwrap().data.len()
+    }
+
+    /// Switches the port to RS-485 mode, where the driver enable pin of the transceiver is asserted before the first
+    /// start bit of every write and released right after its last stop bit. The timing is done in Rust, so the bus is
+    /// free for the reply of the other device within microseconds, as Modbus and DMX need. The DE and RE pins of most
+    /// transceivers are tied together, which also disables the receiver while sending so the port doesn't receive its
+    /// own data.
+    ///
+    /// Parameters:
+    /// - `de_pin` (int): The GPIO pin of the driver enable of the transceiver.
+    /// - `de_active_high` (bool): Whether the pin is driven high to enable the driver (default is True).
+    /// - `pre_delay_us` (float): The delay from asserting the pin to the first start bit in microseconds (default is 0).
+    /// - `post_delay_us` (float): The delay from the end of the last stop bit to releasing the pin in microseconds
+    ///   (default is 0).
+    ///
+    /// Example usage:
+    /// ```python
+    /// uart.enable_rs485(de_pin=18)
+    /// ```
+    #[pyo3(signature = (de_pin, de_active_high = true, pre_delay_us = 0f64, post_delay_us = 0f64))]
+    fn enable_rs485(&self, de_pin: u8, de_active_high: bool, pre_delay_us: f64, post_delay_us: f64) -> PyResult<()> {
+        let pre_delay = us_to_duration_checked("Pre delay", pre_delay_us)?;
+        let post_delay = us_to_duration_checked("Post delay", post_delay_us)?;
+        if self.port.lock().unwrap().tx.is_none() {
+            return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("The software UART has no TX pin or is closed"));
+        }
+        self.disable_rs485()?;
+        let gpio_manager = GPIOManager::new_rust_reference();
+        let de = DirectionControl {
+            pin_num: de_pin,
+            pin: gpio_manager.claim_output_pin(de_pin)?,
+            active_high: de_active_high,
+            pre_delay,
+            post_delay,
+        };
+        de.assert(&mut de.pin.lock().unwrap(), false);
+        self.port.lock().unwrap().de = Some(Arc::new(de));
+        Ok(())
+    }
+
+    /// Leaves RS-485 mode and releases the driver enable pin.
+    ///
+    /// Example usage:
+    /// ```python
+    /// uart.disable_rs485()
+    /// ```
+    fn disable_rs485(&self) -> PyResult<()> {
+        let de = self.port.lock().unwrap().de.take();
+        if let Some(de) = de {
+            // Waits for a write still using the pin
+            drop(de.pin.lock().unwrap());
+            GPIOManager::new_rust_reference().reset_pin(de.pin_num)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the driver enable pin of RS-485 mode, None when the port isn't in RS-485 mode.
+    fn get_rs485_pin(&self) -> Option<u8> {
+        self.port.lock().unwrap().de.as_ref().map(|de| de.pin_num)
+    }
+
+    /// Returns the TX and RX pins, None for the pin that isn't used.
+    fn get_pins(&self) -> (Option<u8>, Option<u8>) {
+        (self.tx_pin, self.rx_pin)
+    }
+
+    /// Returns the baud rate.
+    fn get_baud_rate(&self) -> u32 {
+        self.baud_rate
+    }
+
+    /// Returns the number of frames received with a low stop bit, which were dropped. They are caused by a wrong baud
+    /// rate, noise on the line or a break.
+    fn get_framing_errors(&self) -> u64 {
+        self.buffer.state.lock().unwrap().framing_errors
+    }
+
+    /// Sends the data on the TX pin, returning once the stop bit of the last byte is sent. In RS-485 mode the driver
+    /// enable pin is asserted for the time of the write. With flow control every byte waits for CTS, and a TimeoutError
+    /// is raised when it isn't asserted within the timeout. The GIL is released while sending.
+    ///
+    /// Parameters:
+    /// - `data` (bytes | bytearray | memoryview | list[int]): The data to send.
+    ///
+    /// Example usage:
+    /// ```python
+    /// uart.write(b'AT\r\n')
+    /// ```
+    fn write(&self, data: &Bound<'_, PyAny>) -> PyResult<()> {
+        let data = extract_bytes(data)?;
+        release_gil(|| self.send(&data))
+    }
+
+    /// Reads bytes received on the RX pin, waiting until the given number of bytes has arrived or the timeout passes.
+    /// The GIL is released while waiting.
+    ///
+    /// Parameters:
+    /// - `length` (int): The number of bytes to read (default is 1).
+    ///
+    /// Returns:
+    /// - `bytes`: The bytes read, fewer than requested when the timeout passed or the port was closed while waiting.
+    ///
+    /// Example usage:
+    /// ```python
+    /// data = uart.read(4)
+    /// ```
+    #[pyo3(signature = (length = 1))]
+    fn read<'py>(&self, py: Python<'py>, length: usize) -> PyResult<Bound<'py, PyBytes>> {
+        let data = self.read_with(length, self.deadline(), |data| (data.len() >= length).then_some(length))?;
+        Ok(PyBytes::new(py, &data))
+    }
+
+    /// Reads a line, up to and including the terminator, for line oriented devices such as GPS receivers and modems.
+    ///
+    /// Parameters:
+    /// - `terminator` (bytes): The end of a line (default is b'\n').
+    /// - `max_length` (int): The longest line read, the bytes are returned without a terminator when it is reached
+    ///   (default is None).
+    ///
+    /// Returns:
+    /// - `bytes`: The line with its terminator, or the bytes received without one when the timeout passed or the port
+    ///   was closed first.
+    ///
+    /// Example usage:
+    /// ```python
+    /// sentence = uart.read_line(b'\r\n')
+    /// ```
+    #[pyo3(signature = (terminator = b"\n".to_vec(), max_length = None))]
+    fn read_line<'py>(&self, py: Python<'py>, terminator: Vec<u8>, max_length: Option<usize>) -> PyResult<Bound<'py, PyBytes>> {
+        if terminator.is_empty() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("The terminator must not be empty"));
+        }
+        let limit = max_length.unwrap_or(usize::MAX);
+        let data = self.read_with(limit, self.deadline(), |data| find_terminator(data, &terminator).map(|end| end.min(limit)).or((data.len() >= limit).then_some(limit)))?;
+        Ok(PyBytes::new(py, &data))
+    }
+
+    /// Reads up to and including the first occurrence of a byte.
+    ///
+    /// Parameters:
+    /// - `byte` (int): The byte to read up to.
+    /// - `max_length` (int): The most bytes read, the bytes are returned without the byte when it is reached (default
+    ///   is None).
+    ///
+    /// Returns:
+    /// - `bytes`: The bytes up to and including the byte, or the bytes received without it when the timeout passed or
+    ///   the port was closed first.
+    ///
+    /// Example usage:
+    /// ```python
+    /// frame = uart.read_until(0x7E, max_length=256)
+    /// ```
+    #[pyo3(signature = (byte, max_length = None))]
+    fn read_until<'py>(&self, py: Python<'py>, byte: u8, max_length: Option<usize>) -> PyResult<Bound<'py, PyBytes>> {
+        self.read_line(py, vec![byte], max_length)
+    }
+
+    /// Discards the bytes received but not read yet.
+    fn flush_input(&self) {
+        self.clear_input();
+    }
+}
+
@@ -0,0 +1,139 @@
+use crate::gpio_module::GPIOManager;
+use crate::i2c_module::extract_bytes;
+use crate::soft_spi_module::{with_bus, ChipSelect, SharedBus, SoftBus};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::sync::Arc;
+
+
+#[pyclass]
+/// SPIDevice provides methods to communicate with a single device of a SoftSPI bus, created with `SoftSPI.device`. The
+/// chip select of the device is selected around every transfer, so devices on different chip selects can share the bus.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// spi = gpio_manager.SoftSPI(sclk_pin=21, mosi_pin=20, miso_pin=19)
+/// adc = spi.device(cs_pin=16)
+/// flash = spi.device(cs_pin=12)
+/// reading = adc.transfer(b'\x01\x80\x00')
+/// jedec_id = flash.write_read(b'\x9f', 3)
+/// ```
+pub struct SPIDevice {
+    bus: SharedBus,
+    cs: Arc<ChipSelect>,
+}
+
+
+impl SPIDevice {
+    pub(crate) fn new(bus: SharedBus, cs: ChipSelect) -> Self {
+        Self { bus, cs: Arc::new(cs) }
+    }
+
+    /// Runs the parts of a transfer with the device selected, failing when the device or its bus is closed.
+    fn transfer_parts(&self, parts: &[&[u8]]) -> PyResult<Vec<Vec<u8>>> {
+        let cs = Arc::clone(&self.cs);
+        with_bus(&self.bus, |bus: &mut SoftBus| {
+            if !bus.device_pins.contains(&cs.pin_num) {
+                return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("The SPI device is closed"));
+            }
+            Ok(bus.transfer(Some(&cs), parts))
+        })
+    }
+}
+
+
+#[pymethods]
+impl SPIDevice {
+    /// Releases the chip select of the device, the bus stays open for its other devices.
+    ///
+    /// Example usage:
+    /// ```python
+    /// adc.close()
+    /// ```
+    fn close(&self) -> PyResult<()> {
+        let removed = match self.bus.lock().unwrap().as_mut() {
+            Some(bus) => bus.device_pins.iter().position(|pin_num| *pin_num == self.cs.pin_num).map(|index| bus.device_pins.remove(index)),
+            None => None,
+        };
+        if removed.is_some() {
+            GPIOManager::new_rust_reference().reset_pin(self.cs.pin_num)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the chip select pin of the device.
+    fn get_cs_pin(&self) -> u8 {
+        self.cs.pin_num
+    }
+
+    /// Sends the data while receiving as many bytes, with the device selected for the whole transfer.
+    ///
+    /// Parameters:
+    /// - `data` (bytes | bytearray | memoryview | list[int]): The data to send.
+    ///
+    /// Returns:
+    /// - `bytes`: The data received, as long as the data sent.
+    ///
+    /// Example usage:
+    /// ```python
+    /// reading = adc.transfer(b'\x01\x80\x00')
+    /// ```
+    fn transfer<'py>(&self, py: Python<'py>, data: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyBytes>> {
+        let data = extract_bytes(data)?;
+        Ok(PyBytes::new(py, &self.transfer_parts(&[&data])?[0]))
+    }
+
+    /// Sends data to the device, discarding the bytes received.
+    ///
+    /// Parameters:
+    /// - `data` (bytes | bytearray | memoryview | list[int]): The data to send.
+    ///
+    /// Example usage:
+    /// ```python
+    /// flash.write(b'\x06')
+    /// ```
+    fn write(&self, data: &Bound<'_, PyAny>) -> PyResult<()> {
+        let data = extract_bytes(data)?;
+        self.transfer_parts(&[&data])?;
+        Ok(())
+    }
+
+    /// Reads data from the device, sending the fill byte for every byte read.
+    ///
+    /// Parameters:
+    /// - `length` (int): The number of bytes to read.
+    /// - `fill` (int): The byte sent while reading (default is 0).
+    ///
+    /// Returns:
+    /// - `bytes`: The data read.
+    ///
+    /// Example usage:
+    /// ```python
+    /// data = adc.read(2)
+    /// ```
+    #[pyo3(signature = (length, fill = 0))]
+    fn read<'py>(&self, py: Python<'py>, length: usize, fill: u8) -> PyResult<Bound<'py, PyBytes>> {
+        Ok(PyBytes::new(py, &self.transfer_parts(&[&vec![fill; length]])?[0]))
+    }
+
+    /// Sends data and then reads the response, with the device selected for both.
+    ///
+    /// Parameters:
+    /// - `write_data` (bytes | bytearray | memoryview | list[int]): The data to send.
+    /// - `read_length` (int): The number of bytes to read after it.
+    /// - `fill` (int): The byte sent while reading (default is 0).
+    ///
+    /// Returns:
+    /// - `bytes`: The data read after the data was sent.
+    ///
+    /// Example usage:
+    /// ```python
+    /// jedec_id = flash.write_read(b'\x9f', 3)
+    /// ```
+    #[pyo3(signature = (write_data, read_length, fill = 0))]
+    fn write_read<'py>(&self, py: Python<'py>, write_data: &Bound<'py, PyAny>, read_length: usize, fill: u8) -> PyResult<Bound<'py, PyBytes>> {
+        let write_data = extract_bytes(write_data)?;
+        Ok(PyBytes::new(py, &self.transfer_parts(&[&write_data, &vec![fill; read_length]])?[1]))
+    }
+}
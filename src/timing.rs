@@ -0,0 +1,272 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::hint::spin_loop;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle, ThreadId};
+use std::time::{Duration, Instant};
+
+
+// The end of a wait is spun instead of slept, since waking up from a sleep takes tens of microseconds
+const SPIN_MARGIN: Duration = Duration::from_micros(100);
+
+
+/// Waits until the deadline, sleeping for most of the time and busy-waiting for the final microseconds.
+pub fn wait_until(deadline: Instant) {
+    wait_until_with_margin(deadline, SPIN_MARGIN);
+}
+
+
+/// Waits until the deadline, busy-waiting for the given margin before it. A margin of zero only sleeps.
+pub fn wait_until_with_margin(deadline: Instant, spin_margin: Duration) {
+    let now = Instant::now();
+    if deadline > now + spin_margin {
+        thread::sleep(deadline - now - spin_margin);
+    }
+    while Instant::now() < deadline {
+        spin_loop();
+    }
+}
+
+
+// How often a thread waiting on its own condition variable checks the virtual clock, in real time
+const VIRTUAL_POLL: Duration = Duration::from_millis(1);
+
+
+/// What a thread is doing for the virtual clock.
+#[derive(Clone, Copy, PartialEq)]
+enum Waiter {
+    /// Waiting until the time, or until it is notified for None.
+    Until(Option<Instant>),
+    /// Running the work it was woken up for, the clock doesn't move on until it waits again or exits.
+    Busy,
+}
+
+
+/// A clock that only moves when it is advanced, for running the timed features of the mock backend in tests. An
+/// advance steps through the deadlines the threads wait for in order, and lets every thread woken up finish its work
+/// before the next step.
+struct VirtualClock {
+    now: Instant,
+    /// The time of CLOCK_MONOTONIC when the clock was started, for the timestamps of the edges.
+    start: Instant,
+    start_ns: i128,
+    waiters: HashMap<ThreadId, Waiter>,
+}
+
+
+static VIRTUAL_CLOCK: Lazy<(Mutex<Option<VirtualClock>>, Condvar)> = Lazy::new(|| (Mutex::new(None), Condvar::new()));
+// Whether the virtual clock runs, so the real clock is read without locking it
+static VIRTUAL: AtomicBool = AtomicBool::new(false);
+
+
+/// Forgets the thread when it exits, so the clock doesn't wait for it.
+struct Registration(ThreadId);
+
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        if !VIRTUAL.load(Ordering::Acquire) {
+            return;
+        }
+        let (lock, changed) = &*VIRTUAL_CLOCK;
+        if let Some(clock) = lock.lock().unwrap().as_mut() {
+            clock.waiters.remove(&self.0);
+        }
+        changed.notify_all();
+    }
+}
+
+
+thread_local! {
+    static REGISTRATION: Registration = Registration(thread::current().id());
+}
+
+
+/// Sets what the current thread is doing, returning whether the clock is virtual.
+fn set_waiter(waiter: Waiter) -> bool {
+    if !VIRTUAL.load(Ordering::Acquire) {
+        return false;
+    }
+    let id = REGISTRATION.with(|registration| registration.0);
+    let (lock, changed) = &*VIRTUAL_CLOCK;
+    let mut clock = lock.lock().unwrap();
+    let Some(clock) = clock.as_mut() else {
+        return false;
+    };
+    clock.waiters.insert(id, waiter);
+    changed.notify_all();
+    true
+}
+
+
+/// Returns whether the current thread was woken up by the virtual clock, or the clock stopped being virtual.
+fn is_woken() -> bool {
+    let id = thread::current().id();
+    match VIRTUAL_CLOCK.0.lock().unwrap().as_ref() {
+        None => true,
+        Some(clock) => match clock.waiters.get(&id) {
+            Some(Waiter::Until(Some(deadline))) => *deadline <= clock.now,
+            Some(Waiter::Until(None)) => false,
+            _ => true,
+        },
+    }
+}
+
+
+/// Returns the current time of the timed features, the virtual time while the virtual clock runs.
+pub fn now() -> Instant {
+    if !VIRTUAL.load(Ordering::Acquire) {
+        return Instant::now();
+    }
+    VIRTUAL_CLOCK.0.lock().unwrap().as_ref().map_or_else(Instant::now, |clock| clock.now)
+}
+
+
+/// Returns the time of CLOCK_MONOTONIC in nanoseconds, following the virtual clock while it runs.
+pub fn monotonic_ns() -> i128 {
+    match VIRTUAL_CLOCK.0.lock().unwrap().as_ref() {
+        Some(clock) => clock.start_ns + (clock.now - clock.start).as_nanos() as i128,
+        None => crate::TimestampClock::MONOTONIC.now_ns(),
+    }
+}
+
+
+/// Starts the virtual clock at the current time, or stops it and returns to the real time. The threads waiting on
+/// the virtual clock are woken up when it stops.
+pub fn set_virtual(enabled: bool) {
+    let (lock, changed) = &*VIRTUAL_CLOCK;
+    let mut clock = lock.lock().unwrap();
+    match (enabled, clock.is_some()) {
+        (true, false) => {
+            let start = Instant::now();
+            *clock = Some(VirtualClock { now: start, start, start_ns: crate::TimestampClock::MONOTONIC.now_ns(), waiters: HashMap::new() });
+        }
+        (false, true) => *clock = None,
+        _ => {}
+    }
+    VIRTUAL.store(enabled, Ordering::Release);
+    changed.notify_all();
+}
+
+
+/// Moves the virtual clock forward, waking up the threads at their deadlines in order. Returns once every thread woken
+/// up is waiting again, or false when the clock isn't virtual.
+pub fn advance(by: Duration) -> bool {
+    let (lock, changed) = &*VIRTUAL_CLOCK;
+    let mut guard = lock.lock().unwrap();
+    let Some(target) = guard.as_ref().map(|clock| clock.now + by) else {
+        return false;
+    };
+    loop {
+        // The busy threads are waited for with a timeout, in case one of them exits without the clock noticing it
+        while guard.as_ref().is_some_and(|clock| clock.waiters.values().any(|waiter| *waiter == Waiter::Busy)) {
+            guard = changed.wait_timeout(guard, VIRTUAL_POLL).unwrap().0;
+        }
+        let Some(clock) = guard.as_mut() else {
+            return false;
+        };
+        let next = clock.waiters.values()
+                        .filter_map(|waiter| match waiter {
+                            Waiter::Until(Some(deadline)) if *deadline <= target => Some(*deadline),
+                            _ => None,
+                        })
+                        .min();
+        let Some(next) = next else {
+            clock.now = target;
+            return true;
+        };
+        clock.now = clock.now.max(next);
+        let now = clock.now;
+        for waiter in clock.waiters.values_mut() {
+            if matches!(waiter, Waiter::Until(Some(deadline)) if *deadline <= now) {
+                *waiter = Waiter::Busy;
+            }
+        }
+        changed.notify_all();
+    }
+}
+
+
+/// Spawns a thread running timed work. While the clock is virtual the thread counts as busy until it first waits, so
+/// an advance right after the spawn runs its first step.
+pub fn spawn<F: FnOnce() + Send + 'static>(work: F) -> JoinHandle<()> {
+    // The clock stays locked until the thread is registered, so it can't exit before and stay busy forever
+    let mut clock = VIRTUAL_CLOCK.0.lock().unwrap();
+    let handle = thread::spawn(move || {
+        REGISTRATION.with(|_| {});
+        work();
+    });
+    if let Some(clock) = clock.as_mut() {
+        clock.waiters.insert(handle.thread().id(), Waiter::Busy);
+    }
+    handle
+}
+
+
+/// Sleeps until the deadline of the timed features like wait_until_with_margin, or until the virtual clock reaches it.
+/// Under the virtual clock it returns early when the thread is notified through its TimedCondvar.
+pub fn sleep_until(deadline: Instant, spin_margin: Duration) {
+    if !set_waiter(Waiter::Until(Some(deadline))) {
+        return wait_until_with_margin(deadline, spin_margin);
+    }
+    let id = thread::current().id();
+    let (lock, changed) = &*VIRTUAL_CLOCK;
+    let mut clock = lock.lock().unwrap();
+    while clock.as_ref().is_some_and(|clock| clock.now < deadline && clock.waiters.get(&id) != Some(&Waiter::Busy)) {
+        clock = changed.wait(clock).unwrap();
+    }
+    drop(clock);
+    set_waiter(Waiter::Busy);
+}
+
+
+/// A condition variable waited on by a single thread, whose waits follow the virtual clock while it runs. Notifying it
+/// under the virtual clock counts the thread as busy right away, so an advance doesn't run past the work handed to it.
+#[derive(Default)]
+pub struct TimedCondvar {
+    condvar: Condvar,
+    waiter: Mutex<Option<ThreadId>>,
+}
+
+
+impl TimedCondvar {
+    /// Waits until the condition variable is notified or the time of the timed features reaches the deadline, without
+    /// a deadline until it is notified. Like the waits of a condition variable it can return early, the caller checks
+    /// its state again.
+    pub fn wait<'a, T>(&self, guard: MutexGuard<'a, T>, deadline: Option<Instant>) -> MutexGuard<'a, T> {
+        *self.waiter.lock().unwrap() = Some(thread::current().id());
+        wait_on(&self.condvar, guard, deadline)
+    }
+
+    pub fn notify(&self) {
+        if VIRTUAL.load(Ordering::Acquire) {
+            let (lock, changed) = &*VIRTUAL_CLOCK;
+            if let (Some(id), Some(clock)) = (*self.waiter.lock().unwrap(), lock.lock().unwrap().as_mut()) {
+                // A thread still in a wait of the real clock isn't known to the clock yet, it is added as busy as well
+                clock.waiters.insert(id, Waiter::Busy);
+                changed.notify_all();
+            }
+        }
+        self.condvar.notify_one();
+    }
+}
+
+
+fn wait_on<'a, T>(condvar: &Condvar, guard: MutexGuard<'a, T>, deadline: Option<Instant>) -> MutexGuard<'a, T> {
+    if !set_waiter(Waiter::Until(deadline)) {
+        return match deadline {
+            None => condvar.wait(guard).unwrap(),
+            Some(deadline) => condvar.wait_timeout(guard, deadline.saturating_duration_since(Instant::now())).unwrap().0,
+        };
+    }
+    let mut guard = guard;
+    loop {
+        let (next, result) = condvar.wait_timeout(guard, VIRTUAL_POLL).unwrap();
+        guard = next;
+        if !result.timed_out() || is_woken() {
+            set_waiter(Waiter::Busy);
+            return guard;
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use crate::backend::OutputPin;
+use crate::board_module::MAX_HEADER_GPIO;
+use crate::gpio_module::GPIOManager;
+use crate::pinctrl::{level_reader, set_bias};
+use crate::timing::wait_until;
+use crate::{log_module, ms_to_duration_checked, release_gil, us_to_duration_checked, EnumArg, InternPullResistorState, LogicLevel};
+use pyo3::prelude::*;
+use rppal::gpio::{Bias, Level};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+
+// Time the electrode is driven low before every sample, so it is fully discharged
+pub const DISCHARGE_TIME: Duration = Duration::from_micros(20);
+// Factor of the untouched charge time above which a button calibrated at rest reads as touched
+const TOUCH_RATIO: f64 = 1.3;
+
+
+/// Measures the time a capacitance on a pin takes to charge through the pull resistor, averaged over the samples, in
+/// microseconds. For every sample the pin is driven low for the discharge time, then its output is disabled and the
+/// level is polled until it reads high. Samples that don't reach high within the timeout count as the timeout. The GIL
+/// is released while measuring.
+pub fn measure_charge_time(pin: &Mutex<OutputPin>, pin_num: u8, samples: usize, bias: Bias, discharge: Duration, timeout: Duration) -> PyResult<f64> {
+    if pin_num > MAX_HEADER_GPIO {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Charge timing is limited to GPIO 0 to {}, The value {} does not meet this condition",
+                                                                           MAX_HEADER_GPIO, pin_num)));
+    }
+    if samples == 0 {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Samples must be greater than 0, The value {} does not meet this condition", samples)));
+    }
+    if bias == Bias::PullDown {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("The pin charges through a pull-up, use PULLUP or EXTERNAL with a resistor to 3.3V"));
+    }
+    let io_error = |e: std::io::Error| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Unable to measure the charge time of GPIO {}: {}", pin_num, e));
+    release_gil(|| {
+        let reader = level_reader().map_err(io_error)?;
+        let mut pin = pin.lock().unwrap();
+        set_bias(pin_num, bias).map_err(io_error)?;
+        let mut total = Duration::ZERO;
+        for _ in 0..samples {
+            pin.write(Level::Low);
+            pin.set_output_enabled(true)?;
+            wait_until(Instant::now() + discharge);
+            let start = Instant::now();
+            pin.set_output_enabled(false)?;
+            let charge_time = loop {
+                let elapsed = start.elapsed();
+                if reader.read() & (1 << pin_num) != 0 || elapsed >= timeout {
+                    break elapsed.min(timeout);
+                }
+            };
+            total += charge_time;
+        }
+        // The pin is left discharged, driven low without the pull resistor drawing current
+        pin.set_output_enabled(true)?;
+        set_bias(pin_num, Bias::Off).map_err(io_error)?;
+        Ok(total.as_secs_f64() * 1_000_000f64 / samples as f64)
+    })
+}
+
+
+/// The settings of a touch button, shared with its polling thread.
+struct TouchSettings {
+    threshold_us: f64,
+    on_touch: Option<Arc<PyObject>>,
+    on_release: Option<Arc<PyObject>>,
+}
+
+
+/// The readings of a touch button.
+#[derive(Clone, Copy, Default)]
+struct TouchState {
+    charge_time_us: Option<f64>,
+    touched: bool,
+}
+
+
+/// The pin of a touch button and how it is read.
+struct TouchSensor {
+    pin_num: u8,
+    pin: Arc<Mutex<OutputPin>>,
+    samples: usize,
+    bias: Bias,
+    timeout: Duration,
+}
+
+
+impl TouchSensor {
+    fn read(&self) -> PyResult<f64> {
+        measure_charge_time(&self.pin, self.pin_num, self.samples, self.bias, DISCHARGE_TIME, self.timeout)
+    }
+}
+
+
+/// Reads the button every poll interval until the stop channel is signaled or dropped, calling the callbacks when it
+/// is touched and released.
+fn poll(sensor: Arc<TouchSensor>, settings: Arc<Mutex<TouchSettings>>, state: Arc<Mutex<TouchState>>, interval: Duration, stop: Receiver<()>) {
+    loop {
+        match sensor.read() {
+            Ok(charge_time_us) => {
+                let (touched, callback) = {
+                    let settings = settings.lock().unwrap();
+                    let touched = charge_time_us > settings.threshold_us;
+                    let callback = if touched { &settings.on_touch } else { &settings.on_release };
+                    (touched, callback.clone())
+                };
+                let changed = {
+                    let mut state = state.lock().unwrap();
+                    let changed = state.touched != touched;
+                    *state = TouchState { charge_time_us: Some(charge_time_us), touched };
+                    changed
+                };
+                if let (true, Some(callback)) = (changed, callback) {
+                    Python::with_gil(|py| {
+                        if let Err(e) = callback.call0(py) {
+                            log_module::exception(&format!("Exception in the callback of the touch button on GPIO {}", sensor.pin_num), &e);
+                        }
+                    });
+                }
+            }
+            Err(e) => log_module::warning(&format!("Failed to read the touch button on GPIO {}: {}", sensor.pin_num, e)),
+        }
+        if !matches!(stop.recv_timeout(interval), Err(RecvTimeoutError::Timeout)) {
+            return;
+        }
+    }
+}
+
+
+#[pyclass]
+/// TouchButton turns a pin wired to a bare electrode, such as a piece of foil, into a touch input. A Rust thread
+/// measures the time the electrode takes to charge through the pull-up resistor every poll interval, a finger adds
+/// capacitance and makes the charge time longer than the threshold.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// button = gpio_manager.TouchButton(4, on_touch=lambda: print("touched"), on_release=lambda: print("released"))
+/// print(button.read(), button.get_threshold())
+/// ```
+pub struct TouchButton {
+    sensor: Arc<TouchSensor>,
+    settings: Arc<Mutex<TouchSettings>>,
+    state: Arc<Mutex<TouchState>>,
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+
+impl TouchButton {
+    fn stop_thread(&mut self) {
+        // Dropping the sender wakes the thread up, the GIL is released as it may be waiting for it to call a callback
+        self.stop = None;
+        if let Some(thread) = self.thread.take() {
+            release_gil(|| {
+                let _ = thread.join();
+            });
+        }
+    }
+}
+
+
+impl Drop for TouchButton {
+    fn drop(&mut self) {
+        self.stop_thread();
+    }
+}
+
+
+#[pymethods]
+impl TouchButton {
+    #[new]
+    /// Sets up a free pin as a touch button and starts polling it. Without a threshold the button is calibrated at
+    /// rest, so it must not be touched while it is created.
+    ///
+    /// Parameters:
+    /// - `pin` (int): The GPIO pin wired to the electrode (0 to 27).
+    /// - `threshold_us` (float): The charge time in microseconds above which the button reads as touched (default is
+    ///   None, which calibrates it at 1.3 times the charge time at rest).
+    /// - `samples` (int): The number of charge times averaged for every reading (default is 10).
+    /// - `poll_ms` (float): The time between two readings in milliseconds (default is 20).
+    /// - `on_touch` (Callable[[], None]): A function called when the button is touched (default is None).
+    /// - `on_release` (Callable[[], None]): A function called when the button is released (default is None).
+    /// - `pull_resistor_state` (InternPullResistorState): PULLUP charges the electrode through the internal pull-up,
+    ///   EXTERNAL through a resistor to 3.3V, a resistor of around 1 MΩ gives longer and steadier charge times
+    ///   (default is PULLUP).
+    /// - `timeout_us` (float): The longest charge time measured in microseconds (default is 10000).
+    ///
+    /// Example usage:
+    /// ```python
+    /// button = gpio_manager.TouchButton(4, threshold_us=12, on_touch=toggle_light)
+    /// ```
+    #[pyo3(signature = (pin, threshold_us = None, samples = 10, poll_ms = 20f64, on_touch = None, on_release = None,
+                        pull_resistor_state = EnumArg(InternPullResistorState::PULLUP), timeout_us = 10_000f64))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(pin: u8, threshold_us: Option<f64>, samples: usize, poll_ms: f64, on_touch: Option<PyObject>, on_release: Option<PyObject>,
+           pull_resistor_state: EnumArg<InternPullResistorState>, timeout_us: f64) -> PyResult<Self> {
+        if poll_ms <= 0f64 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Poll interval must be greater than 0, The value {} does not meet this condition", poll_ms)));
+        }
+        let interval = ms_to_duration_checked("Poll interval", poll_ms)?;
+        if timeout_us <= 0f64 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Timeout must be greater than 0, The value {} does not meet this condition", timeout_us)));
+        }
+        let timeout = us_to_duration_checked("Timeout", timeout_us)?;
+        let gpio_manager = GPIOManager::new_rust_reference();
+        let sensor = TouchSensor {
+            pin_num: pin,
+            pin: gpio_manager.claim_output_pin(pin)?,
+            samples,
+            bias: pull_resistor_state.0.bias(LogicLevel::HIGH),
+            timeout,
+        };
+        let threshold_us = match threshold_us {
+            Some(threshold_us) => threshold_us,
+            None => match sensor.read() {
+                Ok(charge_time_us) => charge_time_us * TOUCH_RATIO,
+                Err(e) => {
+                    gpio_manager.reset_pin(pin)?;
+                    return Err(e);
+                }
+            },
+        };
+        let sensor = Arc::new(sensor);
+        let settings = Arc::new(Mutex::new(TouchSettings { threshold_us, on_touch: on_touch.map(Arc::new), on_release: on_release.map(Arc::new) }));
+        let state = Arc::new(Mutex::new(TouchState::default()));
+        let (sender, receiver) = mpsc::channel();
+        let thread = {
+            let (sensor, settings, state) = (Arc::clone(&sensor), Arc::clone(&settings), Arc::clone(&state));
+            thread::spawn(move || poll(sensor, settings, state, interval, receiver))
+        };
+        Ok(Self { sensor, settings, state, stop: Some(sender), thread: Some(thread) })
+    }
+
+    /// Measures the charge time at rest again and sets the threshold from it, the button must not be touched.
+    ///
+    /// Returns:
+    /// - `float`: The new threshold in microseconds.
+    ///
+    /// Example usage:
+    /// ```python
+    /// button.calibrate()
+    /// ```
+    fn calibrate(&self) -> PyResult<f64> {
+        let threshold_us = self.sensor.read()? * TOUCH_RATIO;
+        self.settings.lock().unwrap().threshold_us = threshold_us;
+        Ok(threshold_us)
+    }
+
+    /// Sets the charge time above which the button reads as touched.
+    ///
+    /// Parameters:
+    /// - `threshold_us` (float): The threshold in microseconds.
+    ///
+    /// Example usage:
+    /// ```python
+    /// button.set_threshold(15)
+    /// ```
+    #[pyo3(signature = (threshold_us))]
+    fn set_threshold(&self, threshold_us: f64) {
+        self.settings.lock().unwrap().threshold_us = threshold_us;
+    }
+
+    /// Returns the threshold in microseconds.
+    fn get_threshold(&self) -> f64 {
+        self.settings.lock().unwrap().threshold_us
+    }
+
+    /// Returns the last charge time measured in microseconds, or None before the first reading.
+    fn read(&self) -> Option<f64> {
+        self.state.lock().unwrap().charge_time_us
+    }
+
+    /// Returns whether the button is touched.
+    fn is_touched(&self) -> bool {
+        self.state.lock().unwrap().touched
+    }
+
+    /// Stops polling the button and releases the pin.
+    ///
+    /// Example usage:
+    /// ```python
+    /// button.close()
+    /// ```
+    fn close(&mut self) -> PyResult<()> {
+        self.stop_thread();
+        GPIOManager::new_rust_reference().reset_pin(self.sensor.pin_num)
+    }
+
+    /// Returns the GPIO pin.
+    fn get_pin(&self) -> u8 {
+        self.sensor.pin_num
+    }
+}
@@ -0,0 +1,123 @@
+use crate::log_module;
+use crate::timing::{self, TimedCondvar};
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use pyo3::types::PyTuple;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+
+/// An input pin watched for silence.
+struct Watch {
+    max_silence: Duration,
+    last_edge: Instant,
+    /// Whether the callback was called for the current silence, it is called again after the next edge.
+    fired: bool,
+    callback: Arc<PyObject>,
+    args: Arc<Py<PyTuple>>,
+}
+
+
+/// Calls a function when an input pin has no edge for too long, for all watched pins from a single thread.
+pub struct ActivityWatchdog {
+    watches: Mutex<HashMap<u8, Watch>>,
+    changed: TimedCondvar,
+}
+
+
+pub static ACTIVITY_WATCHDOG: Lazy<Arc<ActivityWatchdog>> = Lazy::new(|| {
+    let watchdog = Arc::new(ActivityWatchdog {
+        watches: Mutex::new(HashMap::new()),
+        changed: TimedCondvar::default(),
+    });
+    let runner = Arc::clone(&watchdog);
+    timing::spawn(move || runner.run());
+    watchdog
+});
+
+
+/// Restarts the window of a pin on an edge, without starting the watchdog when no pin was ever watched.
+pub fn note_edge(pin_num: u8) {
+    if let Some(watchdog) = Lazy::get(&ACTIVITY_WATCHDOG) {
+        watchdog.edge(pin_num);
+    }
+}
+
+
+/// Stops watching a pin, returning whether it was watched.
+pub fn unwatch(pin_num: u8) -> bool {
+    Lazy::get(&ACTIVITY_WATCHDOG).is_some_and(|watchdog| watchdog.unwatch(pin_num))
+}
+
+
+/// Returns whether a pin is watched, without starting the watchdog when no pin was ever watched.
+pub fn is_watched(pin_num: u8) -> bool {
+    Lazy::get(&ACTIVITY_WATCHDOG).is_some_and(|watchdog| watchdog.is_watched(pin_num))
+}
+
+
+impl ActivityWatchdog {
+    /// Watches a pin, replacing its previous watch. The window starts now.
+    pub fn watch(&self, pin_num: u8, max_silence: Duration, callback: PyObject, args: Py<PyTuple>) {
+        let watch = Watch { max_silence, last_edge: timing::now(), fired: false, callback: Arc::new(callback), args: Arc::new(args) };
+        self.watches.lock().unwrap().insert(pin_num, watch);
+        self.changed.notify();
+    }
+
+    pub fn unwatch(&self, pin_num: u8) -> bool {
+        self.watches.lock().unwrap().remove(&pin_num).is_some()
+    }
+
+    pub fn is_watched(&self, pin_num: u8) -> bool {
+        self.watches.lock().unwrap().contains_key(&pin_num)
+    }
+
+    /// Restarts the window of a pin on an edge.
+    pub fn edge(&self, pin_num: u8) {
+        let mut watches = self.watches.lock().unwrap();
+        if let Some(watch) = watches.get_mut(&pin_num) {
+            watch.last_edge = timing::now();
+            // The runner doesn't wait for a pin that already fired, so it is woken up to wait for it again
+            if watch.fired {
+                watch.fired = false;
+                self.changed.notify();
+            }
+        }
+    }
+
+    fn run(&self) {
+        loop {
+            let mut watches = self.watches.lock().unwrap();
+            let due = loop {
+                let now = timing::now();
+                let due: Vec<u8> = watches.iter()
+                                          .filter(|(_, watch)| !watch.fired && watch.last_edge + watch.max_silence <= now)
+                                          .map(|(pin_num, _)| *pin_num)
+                                          .collect();
+                if !due.is_empty() {
+                    break due;
+                }
+                let deadline = watches.values().filter(|watch| !watch.fired).map(|watch| watch.last_edge + watch.max_silence).min();
+                watches = self.changed.wait(watches, deadline);
+            };
+            let mut callbacks = Vec::new();
+            for pin_num in due {
+                if let Some(watch) = watches.get_mut(&pin_num) {
+                    watch.fired = true;
+                    callbacks.push((pin_num, Arc::clone(&watch.callback), Arc::clone(&watch.args)));
+                }
+            }
+            // The callbacks run without the watches locked, so they can watch and unwatch pins
+            drop(watches);
+
+            Python::with_gil(|py| {
+                for (pin_num, callback, args) in callbacks {
+                    if let Err(e) = callback.call1(py, args.bind(py)) {
+                        log_module::exception(&format!("Exception in the activity watchdog of GPIO {}", pin_num), &e);
+                    }
+                }
+            });
+        }
+    }
+}
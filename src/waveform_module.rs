@@ -0,0 +1,632 @@
+use crate::backend::{self, OutputPin};
+use crate::dma::{self, Program};
+use crate::gpio_module::GPIOManager;
+use crate::timing::wait_until_with_margin;
+use crate::us_to_duration_checked;
+use once_cell::sync::Lazy;
+use pyo3::{pyclass, pymethods, FromPyObject, Py, PyErr, PyResult, Python};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+
+// Pulse trains and simulated waveforms are timed by busy-waiting, only long delays are partly slept
+const WAVE_SPIN_MARGIN: Duration = Duration::from_millis(2);
+
+// Real time priority of the thread playing pulses, used when the process is allowed to set it
+const WAVE_THREAD_PRIORITY: i32 = 50;
+
+// Highest GPIO number a pin mask can address
+const MAX_MASK_PIN: u8 = 31;
+
+
+/// A single step of a waveform: the pins to set high and low, then the delay before the next step.
+#[derive(Clone, Copy, Debug)]
+pub struct Pulse {
+    pub on_mask: u32,
+    pub off_mask: u32,
+    pub delay: Duration,
+}
+
+
+/// A pulse with the pin handles resolved, ready to be played.
+pub struct Step {
+    set_high: Vec<Arc<Mutex<OutputPin>>>,
+    set_low: Vec<Arc<Mutex<OutputPin>>>,
+    delay: Duration,
+}
+
+
+/// Returns the output pin handles of the pins of a mask, setting up pins that aren't in use as outputs.
+fn claim_pins(mask: u32) -> PyResult<HashMap<u8, Arc<Mutex<OutputPin>>>> {
+    let gpio_manager = GPIOManager::new_rust_reference();
+    let mut handles = HashMap::new();
+    for pin_num in (0..=MAX_MASK_PIN).filter(|pin_num| mask & (1 << pin_num) != 0) {
+        handles.insert(pin_num, gpio_manager.get_or_claim_output_pin(pin_num)?);
+    }
+    Ok(handles)
+}
+
+
+/// Resolves the pins of the pulses into output pin handles, setting up pins that aren't in use as outputs.
+pub fn resolve_pulses(pulses: &[Pulse]) -> PyResult<Vec<Step>> {
+    let mask = pulses.iter().fold(0u32, |mask, pulse| mask | pulse.on_mask | pulse.off_mask);
+    let handles = claim_pins(mask)?;
+    let pins = |mask: u32| -> Vec<Arc<Mutex<OutputPin>>> {
+        (0..=MAX_MASK_PIN).filter(|pin_num| mask & (1 << pin_num) != 0).map(|pin_num| Arc::clone(&handles[&pin_num])).collect()
+    };
+    Ok(pulses.iter()
+             .map(|pulse| Step {
+                 set_high: pins(pulse.on_mask),
+                 set_low: pins(pulse.off_mask & !pulse.on_mask),
+                 delay: pulse.delay,
+             })
+             .collect())
+}
+
+
+/// A chain of waveforms with the steps resolved, loops hold the number of times they are played or None for forever.
+pub enum ChainNode {
+    Wave(u32, Vec<Step>),
+    Loop(Vec<ChainNode>, Option<u32>),
+}
+
+
+/// Plays steps back to back on a single time line, so chained waveforms follow each other without gaps.
+struct Player {
+    deadline: Instant,
+    stop: Arc<AtomicBool>,
+    current_wave: Arc<Mutex<Option<u32>>>,
+}
+
+
+impl Player {
+    fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    fn play_steps(&mut self, steps: &[Step], repeat: Option<u32>) {
+        let mut played = 0;
+        while repeat.is_none_or(|repeat| played < repeat) && !self.stopped() {
+            for step in steps {
+                if self.stopped() {
+                    break;
+                }
+                for pin in &step.set_high {
+                    pin.lock().unwrap().set_high();
+                }
+                for pin in &step.set_low {
+                    pin.lock().unwrap().set_low();
+                }
+                self.deadline += step.delay;
+                wait_until_with_margin(self.deadline, WAVE_SPIN_MARGIN);
+            }
+            played += 1;
+        }
+    }
+
+    fn play_chain(&mut self, chain: &[ChainNode], repeat: Option<u32>) {
+        let mut played = 0;
+        while repeat.is_none_or(|repeat| played < repeat) && !self.stopped() {
+            for node in chain {
+                match node {
+                    ChainNode::Wave(wave_id, steps) => {
+                        *self.current_wave.lock().unwrap() = Some(*wave_id);
+                        self.play_steps(steps, Some(1));
+                    }
+                    ChainNode::Loop(chain, repeat) => self.play_chain(chain, *repeat),
+                }
+            }
+            played += 1;
+        }
+    }
+}
+
+
+/// Runs the player in a dedicated thread. The thread runs with a real time priority when the process is allowed to set
+/// one.
+fn spawn_player(stop: Arc<AtomicBool>, current_wave: Arc<Mutex<Option<u32>>>, play: impl FnOnce(&mut Player) + Send + 'static,
+                on_done: impl FnOnce() + Send + 'static) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let param = libc::sched_param { sched_priority: WAVE_THREAD_PRIORITY };
+        // Without the permission to run in real time the waveform is still played, with more jitter
+        let _ = unsafe { libc::pthread_setschedparam(libc::pthread_self(), libc::SCHED_FIFO, &param) };
+
+        let mut player = Player {
+            deadline: Instant::now(),
+            stop,
+            current_wave,
+        };
+        play(&mut player);
+        *player.current_wave.lock().unwrap() = None;
+        on_done();
+    })
+}
+
+
+/// Plays the steps in a dedicated thread with absolute deadlines, `repeat` times or until stopped for None.
+pub fn play_steps(steps: Vec<Step>, repeat: Option<u32>, stop: Arc<AtomicBool>, on_done: impl FnOnce() + Send + 'static) -> JoinHandle<()> {
+    spawn_player(stop, Arc::new(Mutex::new(None)), move |player| player.play_steps(&steps, repeat), on_done)
+}
+
+
+/// Plays a chain of waveforms in a dedicated thread, `repeat` times or until stopped for None. `current_wave` holds the
+/// id of the waveform being played.
+pub fn play_chain(chain: Vec<ChainNode>, repeat: Option<u32>, stop: Arc<AtomicBool>, current_wave: Arc<Mutex<Option<u32>>>,
+                  on_done: impl FnOnce() + Send + 'static) -> JoinHandle<()> {
+    spawn_player(stop, current_wave, move |player| player.play_chain(&chain, repeat), on_done)
+}
+
+
+// Stop flags of the pulse trains sent with GPIOManager.send_pulses, by pin
+static PULSE_TRAINS: Lazy<Mutex<HashMap<u8, Arc<AtomicBool>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+
+/// Sends a pulse train on a single pin, stopping the train already running on the pin. `levels` are the physical
+/// levels with their durations.
+pub fn send_pulse_train(pin_num: u8, levels: &[(bool, Duration)], repeat: Option<u32>, on_done: impl FnOnce() + Send + 'static) -> PyResult<()> {
+    stop_pulse_train(pin_num);
+    let pulses: Vec<Pulse> = levels.iter()
+                                   .map(|(high, duration)| Pulse {
+                                       on_mask: if *high { 1 << pin_num } else { 0 },
+                                       off_mask: if *high { 0 } else { 1 << pin_num },
+                                       delay: *duration,
+                                   })
+                                   .collect();
+    let steps = resolve_pulses(&pulses)?;
+    let stop = Arc::new(AtomicBool::new(false));
+    PULSE_TRAINS.lock().unwrap().insert(pin_num, Arc::clone(&stop));
+    let train = Arc::clone(&stop);
+    play_steps(steps, repeat, stop, move || {
+        let mut trains = PULSE_TRAINS.lock().unwrap();
+        if trains.get(&pin_num).is_some_and(|current| Arc::ptr_eq(current, &train)) {
+            trains.remove(&pin_num);
+        }
+        drop(trains);
+        on_done();
+    });
+    Ok(())
+}
+
+
+/// Stops the pulse train running on a pin, returning whether there was one.
+pub fn stop_pulse_train(pin_num: u8) -> bool {
+    match PULSE_TRAINS.lock().unwrap().remove(&pin_num) {
+        Some(stop) => {
+            stop.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
+
+
+/// A waveform referenced by its id or by its name.
+#[derive(FromPyObject)]
+pub enum WaveRef {
+    Id(u32),
+    Name(String),
+}
+
+
+/// An element of a chain passed from Python, either a waveform or a `(chain, loops)` tuple.
+#[derive(FromPyObject)]
+pub enum ChainItem {
+    Wave(WaveRef),
+    Loop(Vec<ChainItem>, Option<u32>),
+}
+
+
+/// How a waveform or chain is played.
+enum Playback {
+    /// Played by the DMA controller, `waves` holds the first control block of every waveform in the program. The pins
+    /// are held for as long as the program may drive them.
+    Dma {
+        waves: Vec<(usize, u32)>,
+        _pins: Vec<Arc<Mutex<OutputPin>>>,
+    },
+    /// Simulated by a thread on the mock backend, which has no DMA controller.
+    Simulated {
+        current_wave: Arc<Mutex<Option<u32>>>,
+        stop: Arc<AtomicBool>,
+        thread: JoinHandle<()>,
+    },
+}
+
+
+/// A waveform or chain being transmitted.
+struct Transmission {
+    wave_ids: Vec<u32>,
+    playback: Playback,
+}
+
+
+struct Waveforms {
+    pending: Vec<Pulse>,
+    waves: HashMap<u32, Arc<Vec<Pulse>>>,
+    names: HashMap<String, u32>,
+    next_id: u32,
+    transmission: Option<Transmission>,
+}
+
+
+impl Waveforms {
+    fn resolve_id(&self, wave: &WaveRef) -> PyResult<u32> {
+        match wave {
+            WaveRef::Id(wave_id) if self.waves.contains_key(wave_id) => Ok(*wave_id),
+            WaveRef::Id(wave_id) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Wave {} does not exist", wave_id))),
+            WaveRef::Name(name) => self.names
+                                       .get(name)
+                                       .copied()
+                                       .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Wave {} does not exist", name))),
+        }
+    }
+
+    /// Collects the ids of the waveforms of a chain, checking that they exist.
+    fn collect_ids(&self, chain: &[ChainItem], wave_ids: &mut Vec<u32>) -> PyResult<()> {
+        for item in chain {
+            match item {
+                ChainItem::Wave(wave) => wave_ids.push(self.resolve_id(wave)?),
+                ChainItem::Loop(chain, _) => self.collect_ids(chain, wave_ids)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolves the waveforms of a chain for the simulated playback.
+    fn resolve_chain(&self, chain: &[ChainItem]) -> PyResult<Vec<ChainNode>> {
+        chain.iter()
+             .map(|item| match item {
+                 ChainItem::Wave(wave) => {
+                     let wave_id = self.resolve_id(wave)?;
+                     Ok(ChainNode::Wave(wave_id, resolve_pulses(&self.waves[&wave_id])?))
+                 }
+                 ChainItem::Loop(chain, loops) => Ok(ChainNode::Loop(self.resolve_chain(chain)?, *loops)),
+             })
+             .collect()
+    }
+
+    /// Appends a chain played `repeat` times, or forever for None, to a DMA program. Finite loops are unrolled and a
+    /// loop played forever jumps back to its start, nothing after it is played. Returns false once the chain ended in
+    /// a loop played forever.
+    fn append_chain(&self, program: &mut Program, waves: &mut Vec<(usize, u32)>, chain: &[ChainItem], repeat: Option<u32>) -> PyResult<bool> {
+        let start = program.len();
+        let mut played = 0;
+        while repeat.is_none_or(|repeat| played < repeat) {
+            for item in chain {
+                let more = match item {
+                    ChainItem::Wave(wave) => {
+                        let wave_id = self.resolve_id(wave)?;
+                        waves.push((program.len(), wave_id));
+                        for pulse in self.waves[&wave_id].iter() {
+                            program.push_pulse(pulse.on_mask, pulse.off_mask, pulse.delay)?;
+                        }
+                        true
+                    }
+                    ChainItem::Loop(chain, loops) => self.append_chain(program, waves, chain, *loops)?,
+                };
+                if !more {
+                    return Ok(false);
+                }
+            }
+            if repeat.is_none() {
+                if !program.has_delay_from(start) {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("A waveform played forever must have a delay"));
+                }
+                program.loop_from(start);
+                return Ok(false);
+            }
+            played += 1;
+        }
+        Ok(true)
+    }
+
+    fn is_running(&self) -> bool {
+        match self.transmission.as_ref().map(|transmission| &transmission.playback) {
+            Some(Playback::Dma { .. }) => dma::position().is_some(),
+            Some(Playback::Simulated { thread, .. }) => !thread.is_finished(),
+            None => false,
+        }
+    }
+
+    fn current_wave(&self) -> Option<u32> {
+        match &self.transmission.as_ref()?.playback {
+            Playback::Dma { waves, .. } => {
+                let block = dma::position()?;
+                let index = waves.partition_point(|(start, _)| *start <= block).checked_sub(1)?;
+                Some(waves[index].1)
+            }
+            Playback::Simulated { current_wave, thread, .. } if !thread.is_finished() => *current_wave.lock().unwrap(),
+            Playback::Simulated { .. } => None,
+        }
+    }
+
+    fn stop_transmission(&mut self) {
+        match self.transmission.take().map(|transmission| transmission.playback) {
+            Some(Playback::Dma { .. }) => dma::stop(),
+            Some(Playback::Simulated { stop, thread, .. }) => {
+                stop.store(true, Ordering::Relaxed);
+                let _ = thread.join();
+            }
+            None => {}
+        }
+    }
+}
+
+
+// Singleton instance of the waveforms
+static WAVEFORMS: Lazy<Arc<Mutex<Waveforms>>> = Lazy::new(|| {
+    Arc::new(Mutex::new(Waveforms {
+        pending: Vec::new(),
+        waves: HashMap::new(),
+        names: HashMap::new(),
+        next_id: 0,
+        transmission: None,
+    }))
+});
+
+
+#[pyclass]
+/// WaveformManager composes precise multi-pin waveforms in the style of pigpio and plays them by DMA, so the timing
+/// doesn't depend on the CPU load. A waveform is a list of pulses `(on_mask, off_mask, delay_us)`: the pins set in
+/// `on_mask` are driven high, the pins set in `off_mask` low, then the next pulse follows after the delay. Masks address
+/// GPIO 0 to 31 and drive physical levels, delays have a resolution of 0.2 µs.
+///
+/// DMA playback needs the DMA controller of the BCM2835 to BCM2711 and root access, the RP1 of the Raspberry Pi 5 can't
+/// drive its pins by DMA. The delays are paced by the PWM peripheral, which can't be used for hardware PWM or analog
+/// audio at the same time. On the mock backend the waveforms are simulated by a thread.
+///
+/// Waveforms can be given names and chained with loops, for example to play stepper motor acceleration profiles.
+///
+/// Example usage in Python:
+///
+/// ```python
+/// waves = gpio_manager.WaveformManager()
+/// waves.wave_add_generic([(1 << 17, 0, 500), (0, 1 << 17, 1500)])
+/// wave_id = waves.wave_create()
+/// waves.wave_send_repeat(wave_id)
+/// ```
+pub struct WaveformManager {
+    waveforms: Arc<Mutex<Waveforms>>,
+}
+
+
+impl WaveformManager {
+    fn shared(py: Python) -> PyResult<Py<WaveformManager>> {
+        Py::new(py, WaveformManager {
+            waveforms: Arc::clone(&WAVEFORMS),
+        })
+    }
+
+    fn send(&self, chain: &[ChainItem], repeat: Option<u32>) -> PyResult<()> {
+        let mut waveforms = self.waveforms.lock().unwrap();
+        let mut wave_ids = Vec::new();
+        waveforms.collect_ids(chain, &mut wave_ids)?;
+        if backend::is_mock() {
+            let chain = waveforms.resolve_chain(chain)?;
+            waveforms.stop_transmission();
+            let stop = Arc::new(AtomicBool::new(false));
+            let current_wave = Arc::new(Mutex::new(None));
+            let thread = play_chain(chain, repeat, Arc::clone(&stop), Arc::clone(&current_wave), || {});
+            waveforms.transmission = Some(Transmission {
+                wave_ids,
+                playback: Playback::Simulated { current_wave, stop, thread },
+            });
+            return Ok(());
+        }
+
+        dma::available()?;
+        let mut program = Program::default();
+        let mut waves = Vec::new();
+        waveforms.append_chain(&mut program, &mut waves, chain, repeat)?;
+        let mask = wave_ids.iter()
+                           .flat_map(|wave_id| waveforms.waves[wave_id].iter())
+                           .fold(0u32, |mask, pulse| mask | pulse.on_mask | pulse.off_mask);
+        let pins = claim_pins(mask)?;
+        waveforms.stop_transmission();
+        dma::start(&program)?;
+        waveforms.transmission = Some(Transmission {
+            wave_ids,
+            playback: Playback::Dma { waves, _pins: pins.into_values().collect() },
+        });
+        Ok(())
+    }
+}
+
+
+#[pymethods]
+impl WaveformManager {
+    #[new]
+    /// Initializes a new WaveformManager instance, all instances share the same waveforms.
+    ///
+    /// Example usage:
+    /// ```python
+    /// waves = gpio_manager.WaveformManager()
+    /// ```
+    fn new(py: Python) -> PyResult<Py<WaveformManager>> {
+        WaveformManager::shared(py)
+    }
+
+    /// Appends pulses to the waveform being composed.
+    ///
+    /// Parameters:
+    /// - `pulses` (list[tuple[int, int, float]]): The pulses as `(on_mask, off_mask, delay_us)`, delays are rounded to
+    ///   0.2 µs.
+    ///
+    /// Returns:
+    /// - `int`: The number of pulses in the waveform being composed.
+    ///
+    /// Example usage:
+    /// ```python
+    /// waves.wave_add_generic([(1 << 17, 0, 10), (0, 1 << 17, 10)])
+    /// ```
+    #[pyo3(signature = (pulses))]
+    fn wave_add_generic(&self, pulses: Vec<(u32, u32, f64)>) -> PyResult<usize> {
+        let pulses = pulses.into_iter()
+                           .map(|(on_mask, off_mask, delay_us)| Ok(Pulse { on_mask, off_mask, delay: us_to_duration_checked("Delays", delay_us)? }))
+                           .collect::<PyResult<Vec<_>>>()?;
+        let mut waveforms = self.waveforms.lock().unwrap();
+        waveforms.pending.extend(pulses);
+        Ok(waveforms.pending.len())
+    }
+
+    /// Creates a waveform from the pulses added since the last call and clears them.
+    ///
+    /// Parameters:
+    /// - `name` (str): A name the waveform can be referenced by in place of its id (default is None).
+    ///
+    /// Returns:
+    /// - `int`: The id of the waveform.
+    ///
+    /// Example usage:
+    /// ```python
+    /// wave_id = waves.wave_create()
+    /// waves.wave_create(name="accelerate")
+    /// ```
+    #[pyo3(signature = (name = None))]
+    fn wave_create(&self, name: Option<String>) -> PyResult<u32> {
+        let mut waveforms = self.waveforms.lock().unwrap();
+        if waveforms.pending.is_empty() {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>("No pulses were added to the waveform"));
+        }
+        if let Some(name) = &name {
+            if waveforms.names.contains_key(name) {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Wave {} already exists", name)));
+            }
+        }
+        let pulses = std::mem::take(&mut waveforms.pending);
+        let wave_id = waveforms.next_id;
+        waveforms.next_id += 1;
+        waveforms.waves.insert(wave_id, Arc::new(pulses));
+        if let Some(name) = name {
+            waveforms.names.insert(name, wave_id);
+        }
+        Ok(wave_id)
+    }
+
+    /// Deletes a waveform, stopping the transmission if it uses the waveform.
+    ///
+    /// Parameters:
+    /// - `wave` (int | str): The id or the name of the waveform.
+    ///
+    /// Example usage:
+    /// ```python
+    /// waves.wave_delete(wave_id)
+    /// ```
+    #[pyo3(signature = (wave))]
+    fn wave_delete(&self, wave: WaveRef) -> PyResult<()> {
+        let mut waveforms = self.waveforms.lock().unwrap();
+        let wave_id = waveforms.resolve_id(&wave)?;
+        if waveforms.transmission.as_ref().is_some_and(|transmission| transmission.wave_ids.contains(&wave_id)) {
+            waveforms.stop_transmission();
+        }
+        waveforms.names.retain(|_, id| *id != wave_id);
+        waveforms.waves.remove(&wave_id);
+        Ok(())
+    }
+
+    /// Stops any transmission and deletes all waveforms and pending pulses.
+    ///
+    /// Example usage:
+    /// ```python
+    /// waves.wave_clear()
+    /// ```
+    fn wave_clear(&self) {
+        let mut waveforms = self.waveforms.lock().unwrap();
+        waveforms.stop_transmission();
+        waveforms.pending.clear();
+        waveforms.waves.clear();
+        waveforms.names.clear();
+    }
+
+    /// Transmits a waveform once, replacing the current transmission. Pins in the masks that aren't in use are set up
+    /// as outputs.
+    ///
+    /// Parameters:
+    /// - `wave` (int | str): The id or the name of the waveform.
+    ///
+    /// Example usage:
+    /// ```python
+    /// waves.wave_send_once(wave_id)
+    /// ```
+    #[pyo3(signature = (wave))]
+    fn wave_send_once(&self, wave: WaveRef) -> PyResult<()> {
+        self.send(&[ChainItem::Wave(wave)], Some(1))
+    }
+
+    /// Transmits a waveform repeatedly until it is stopped, replacing the current transmission.
+    ///
+    /// Parameters:
+    /// - `wave` (int | str): The id or the name of the waveform.
+    ///
+    /// Example usage:
+    /// ```python
+    /// waves.wave_send_repeat(wave_id)
+    /// ```
+    #[pyo3(signature = (wave))]
+    fn wave_send_repeat(&self, wave: WaveRef) -> PyResult<()> {
+        self.send(&[ChainItem::Wave(wave)], None)
+    }
+
+    /// Transmits waveforms back to back without gaps, replacing the current transmission. Elements of the chain are
+    /// waveforms or `(chain, loops)` tuples, which play the nested chain `loops` times or forever for None.
+    ///
+    /// Parameters:
+    /// - `chain` (list): The waveforms and loops to play.
+    /// - `loops` (int): The number of times the whole chain is played, None plays it until it is stopped (default is 1).
+    ///
+    /// Example usage:
+    /// ```python
+    /// # Accelerate, run 200 steps at full speed, then decelerate
+    /// waves.wave_chain(["accelerate", (["run"], 200), "decelerate"])
+    /// ```
+    #[pyo3(signature = (chain, loops = Some(1)))]
+    fn wave_chain(&self, chain: Vec<ChainItem>, loops: Option<u32>) -> PyResult<()> {
+        self.send(&chain, loops)
+    }
+
+    /// Returns whether a waveform or chain is being transmitted.
+    fn wave_tx_busy(&self) -> bool {
+        self.waveforms.lock().unwrap().is_running()
+    }
+
+    /// Returns the id of the waveform being transmitted, within a chain the waveform currently playing, or None.
+    fn wave_tx_at(&self) -> Option<u32> {
+        self.waveforms.lock().unwrap().current_wave()
+    }
+
+    /// Returns the name of the waveform being transmitted, or None if it has no name or nothing is transmitted.
+    fn wave_tx_name(&self) -> Option<String> {
+        let wave_id = self.wave_tx_at()?;
+        let waveforms = self.waveforms.lock().unwrap();
+        waveforms.names.iter().find(|(_, id)| **id == wave_id).map(|(name, _)| name.clone())
+    }
+
+    /// Stops the transmission, the pins keep their last level.
+    ///
+    /// Example usage:
+    /// ```python
+    /// waves.wave_tx_stop()
+    /// ```
+    fn wave_tx_stop(&self) {
+        self.waveforms.lock().unwrap().stop_transmission();
+    }
+
+    /// Returns the length of a waveform in microseconds.
+    ///
+    /// Parameters:
+    /// - `wave` (int | str): The id or the name of the waveform.
+    ///
+    /// Example usage:
+    /// ```python
+    /// length_us = waves.wave_get_micros(wave_id)
+    /// ```
+    #[pyo3(signature = (wave))]
+    fn wave_get_micros(&self, wave: WaveRef) -> PyResult<f64> {
+        let waveforms = self.waveforms.lock().unwrap();
+        let wave_id = waveforms.resolve_id(&wave)?;
+        Ok(waveforms.waves[&wave_id].iter().map(|pulse| pulse.delay).sum::<Duration>().as_secs_f64() * 1_000_000f64)
+    }
+}
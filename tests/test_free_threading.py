@@ -0,0 +1,84 @@
+"""Stress test of the callbacks and the managers from many threads, run on the mock backend so it needs no hardware.
+
+On a free-threaded build (python3.13t) it also checks that importing the module leaves the GIL disabled, run it with
+``python3.13t -m unittest tests/test_free_threading.py`` after installing the wheel.
+"""
+import sys
+import sysconfig
+import threading
+import unittest
+
+import gpio_manager
+
+INPUT_PINS = [5, 6, 13, 19]
+OUTPUT_PINS = [20, 21, 26]
+TOGGLES = 2000
+
+
+class FreeThreadingTest(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        gpio_manager.set_backend("mock")
+        cls.manager = gpio_manager.GPIOManager()
+
+    def tearDown(self):
+        self.manager.cleanup()
+
+    @unittest.skipUnless(sysconfig.get_config_var("Py_GIL_DISABLED"), "needs a free-threaded build")
+    def test_gil_stays_disabled(self):
+        self.assertFalse(sys._is_gil_enabled())
+
+    def test_concurrent_interrupts_and_calls(self):
+        counts = {pin: 0 for pin in INPUT_PINS}
+        counts_lock = threading.Lock()
+
+        def on_edge(pin):
+            with counts_lock:
+                counts[pin] += 1
+            # Callbacks can use the manager while the edges of other pins are dispatched
+            self.manager.set_output_pin(OUTPUT_PINS[pin % len(OUTPUT_PINS)], gpio_manager.PinState.HIGH)
+
+        for pin in INPUT_PINS:
+            self.manager.add_input_pin(pin, gpio_manager.InternPullResistorState.EXTERNAL)
+            self.manager.assign_callback(pin, on_edge, debounce_time_ms=0, args=(pin,))
+        for pin in OUTPUT_PINS:
+            self.manager.add_output_pin(pin)
+
+        errors = []
+        stop = threading.Event()
+
+        def fire(pin):
+            try:
+                for i in range(TOGGLES):
+                    state = gpio_manager.PinState.HIGH if i % 2 == 0 else gpio_manager.PinState.LOW
+                    gpio_manager.mock.set_input(pin, state)
+            except Exception as e:
+                errors.append(e)
+
+        def read_write():
+            try:
+                while not stop.is_set():
+                    for pin in INPUT_PINS:
+                        self.manager.get_pin(pin)
+                    for pin in OUTPUT_PINS:
+                        self.manager.set_output_pin(pin, gpio_manager.PinState.LOW)
+            except Exception as e:
+                errors.append(e)
+
+        workers = [threading.Thread(target=read_write) for _ in range(4)]
+        firers = [threading.Thread(target=fire, args=(pin,)) for pin in INPUT_PINS]
+        for thread in workers + firers:
+            thread.start()
+        for thread in firers:
+            thread.join(timeout=60)
+        stop.set()
+        for thread in workers:
+            thread.join(timeout=60)
+
+        self.assertFalse(any(thread.is_alive() for thread in workers + firers), "a thread deadlocked")
+        self.assertEqual(errors, [])
+        self.assertEqual(counts, {pin: TOGGLES for pin in INPUT_PINS})
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,112 @@
+"""Tests of the timed features on the virtual clock of the mock backend, the clock only moves with advance_time so
+every deadline is checked at its exact time without waiting on the wall clock.
+
+Run it with ``python3 -m unittest tests/test_mock_clock.py`` after installing the wheel.
+"""
+import time
+import unittest
+
+import gpio_manager
+
+DEBOUNCE_PIN = 5
+WATCHED_PIN = 6
+OUTPUT_PIN = 20
+PWM_PIN = 21
+
+
+class MockClockTest(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        gpio_manager.set_backend("mock")
+        cls.manager = gpio_manager.GPIOManager()
+
+    def setUp(self):
+        gpio_manager.mock.set_virtual_clock()
+
+    def tearDown(self):
+        self.manager.cleanup()
+        gpio_manager.mock.set_virtual_clock(False)
+        # The simulated levels outlive the manager, they are left low for the tests that follow
+        for pin in [DEBOUNCE_PIN, WATCHED_PIN]:
+            gpio_manager.mock.set_input(pin, gpio_manager.PinState.LOW)
+
+    def test_stable_debounce(self):
+        edges = []
+        self.manager.add_input_pin(DEBOUNCE_PIN, gpio_manager.InternPullResistorState.EXTERNAL)
+        self.manager.assign_callback(DEBOUNCE_PIN, lambda: edges.append(True), gpio_manager.TriggerEdge.RISING,
+                                     debounce_time_ms=10, filter="stable")
+
+        # A glitch shorter than the debounce time is never reported
+        gpio_manager.mock.set_input(DEBOUNCE_PIN, gpio_manager.PinState.HIGH)
+        gpio_manager.mock.advance_time(5)
+        gpio_manager.mock.set_input(DEBOUNCE_PIN, gpio_manager.PinState.LOW)
+        gpio_manager.mock.advance_time(20)
+        self.assertEqual(edges, [])
+
+        # A level held for the debounce time is reported once it has been held that long
+        gpio_manager.mock.set_input(DEBOUNCE_PIN, gpio_manager.PinState.HIGH)
+        gpio_manager.mock.advance_time(9)
+        self.assertEqual(edges, [])
+        gpio_manager.mock.advance_time(2)
+        self.assertEqual(edges, [True])
+
+    def test_schedule_in(self):
+        self.manager.add_output_pin(OUTPUT_PIN)
+        self.manager.schedule_in(OUTPUT_PIN, gpio_manager.PinState.HIGH, 100)
+
+        gpio_manager.mock.advance_time(99)
+        self.assertEqual(gpio_manager.mock.get_level(OUTPUT_PIN), gpio_manager.PinState.LOW)
+        gpio_manager.mock.advance_time(1)
+        self.assertEqual(gpio_manager.mock.get_level(OUTPUT_PIN), gpio_manager.PinState.HIGH)
+
+    def test_schedule_follows_the_virtual_clock(self):
+        self.manager.add_output_pin(OUTPUT_PIN)
+        # The virtual clock started at the monotonic time of set_virtual_clock, which is at most a moment before now
+        self.manager.schedule(OUTPUT_PIN, gpio_manager.PinState.HIGH, time.monotonic() + 0.1)
+
+        gpio_manager.mock.advance_time(95)
+        self.assertEqual(gpio_manager.mock.get_level(OUTPUT_PIN), gpio_manager.PinState.LOW)
+        gpio_manager.mock.advance_time(10)
+        self.assertEqual(gpio_manager.mock.get_level(OUTPUT_PIN), gpio_manager.PinState.HIGH)
+        with self.assertRaises(ValueError):
+            self.manager.schedule(OUTPUT_PIN, gpio_manager.PinState.LOW, 1e300)
+
+    def test_watch_activity(self):
+        silences = []
+        self.manager.add_input_pin(WATCHED_PIN, gpio_manager.InternPullResistorState.EXTERNAL)
+        self.manager.watch_activity(WATCHED_PIN, 50, silences.append, args=(WATCHED_PIN,))
+
+        # Edges within the window keep the watchdog quiet
+        for state in [gpio_manager.PinState.HIGH, gpio_manager.PinState.LOW, gpio_manager.PinState.HIGH]:
+            gpio_manager.mock.advance_time(40)
+            gpio_manager.mock.set_input(WATCHED_PIN, state)
+        self.assertEqual(silences, [])
+
+        # The silence is reported once, the next edge starts a new window. The edges pass the 2ms debounce of the
+        # interrupt, so the checks leave some time after the window
+        gpio_manager.mock.advance_time(60)
+        self.assertEqual(silences, [WATCHED_PIN])
+        gpio_manager.mock.advance_time(200)
+        self.assertEqual(silences, [WATCHED_PIN])
+        gpio_manager.mock.set_input(WATCHED_PIN, gpio_manager.PinState.LOW)
+        gpio_manager.mock.advance_time(60)
+        self.assertEqual(silences, [WATCHED_PIN, WATCHED_PIN])
+
+    def test_advance_time_rejects_times_that_are_not_finite(self):
+        for ms in [float("nan"), float("inf"), -1, 1e300]:
+            with self.assertRaises(ValueError):
+                gpio_manager.mock.advance_time(ms)
+
+    def test_pwm_ramp(self):
+        self.manager.setup_pwm(PWM_PIN, frequency_hz=100, duty_cycle=0)
+        self.manager.start_pwm(PWM_PIN)
+        self.manager.set_pwm_duty_cycle(PWM_PIN, 1.0, ramp_ms=1000, easing=gpio_manager.Easing.LINEAR)
+
+        gpio_manager.mock.advance_time(500)
+        self.assertAlmostEqual(self.manager.get_pwm_duty_cycle(PWM_PIN), 0.5, delta=0.05)
+        gpio_manager.mock.advance_time(500)
+        self.assertAlmostEqual(self.manager.get_pwm_duty_cycle(PWM_PIN), 1.0)
+
+
+if __name__ == "__main__":
+    unittest.main()